@@ -0,0 +1,307 @@
+/*!
+api.rs - api subcommand.
+
+Local HTTP API so dashboards and other tools can drive mcp-hack without
+starting a process per command on their side. Uses the HTTP plumbing of
+`serve --http` (see crate::cmd::serve); every answer is application/json.
+
+Endpoints:
+  POST /list      {"target": T}                   `list tools` output
+  POST /scan      {"target": T, "passive": false} `scan` output, saved into
+                                                  the workspace as scan-*.json
+  GET  /findings  findings of the saved outputs in the workspace, each with
+                  its target and file (?target=T, ?min_severity=S narrow them)
+
+Each list / scan runs in its own mcp-hack process, like fleet targets (see
+crate::cmd::fleet), and is answered with that process's exit status and
+JSON document: {"exit_code": N, "result": {...}}. The workspace is read as
+`stats` reads it (*.json outputs under --workspace, recursively).
+
+Targets are commands this machine runs: the API listens on 127.0.0.1 by
+default, and POSTs must be application/json without an Origin header, so a
+web page open in a browser cannot trigger them.
+
+Example:
+  mcp-hack api --workspace engagement/
+  curl -s localhost:8845/scan -H 'Content-Type: application/json' \
+       -d '{"target": "python3 server.py"}'
+  curl -s 'localhost:8845/findings?min_severity=high'
+*/
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::cmd::fleet::run_one;
+use crate::cmd::output::OutputFormat;
+use crate::cmd::serve::{HttpRequest, listen, read_request, write_response};
+use crate::cmd::stats::collect_files;
+use crate::scan::Severity;
+use crate::utils::logging::{LogLevel, current_log_level};
+use crate::utils::utc_timestamp;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports (answers are always JSON).
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text];
+
+#[derive(Args, Debug)]
+pub struct ApiArgs {
+    /// Address to listen on
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8845")]
+    pub listen: String,
+
+    /// Directory scans are saved to and findings are read from
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    pub workspace: String,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// Findings of the saved outputs under `root`, highest severity first; each
+/// carries the output's `target` and the `file` it came from.
+pub fn workspace_findings(root: &Path) -> Vec<Value> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    let mut out = Vec::new();
+    for file in files
+        .iter()
+        .filter(|f| f.extension().and_then(|e| e.to_str()) == Some("json"))
+    {
+        let Some(doc) = std::fs::read_to_string(file)
+            .ok()
+            .and_then(|t| serde_json::from_str::<Value>(&t).ok())
+            .filter(|d| d.get("status").and_then(|v| v.as_str()) == Some("ok"))
+        else {
+            continue;
+        };
+        let name = file
+            .strip_prefix(root)
+            .unwrap_or(file)
+            .display()
+            .to_string();
+        for f in doc
+            .get("findings")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let mut f = f.clone();
+            f["target"] = doc.get("target").cloned().unwrap_or(Value::Null);
+            f["file"] = json!(name);
+            out.push(f);
+        }
+    }
+    let severity = |f: &Value| {
+        f.get("severity")
+            .and_then(|v| v.as_str())
+            .and_then(Severity::parse)
+    };
+    out.sort_by_key(|f| std::cmp::Reverse(severity(f)));
+    out
+}
+
+fn error(status: &'static str, msg: &str) -> (&'static str, Value) {
+    (status, json!({"status": "error", "error": msg}))
+}
+
+fn findings(workspace: &Path, params: &[(String, String)]) -> (&'static str, Value) {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let min = match param("min_severity") {
+        Some(s) => match Severity::parse(s) {
+            Some(min) => Some(min),
+            None => return error("400 Bad Request", &format!("unknown severity '{s}'")),
+        },
+        None => None,
+    };
+    let findings: Vec<Value> = workspace_findings(workspace)
+        .into_iter()
+        .filter(|f| param("target").is_none_or(|t| f["target"].as_str() == Some(t)))
+        .filter(|f| {
+            min.is_none_or(|min| {
+                f["severity"]
+                    .as_str()
+                    .and_then(Severity::parse)
+                    .is_some_and(|s| s >= min)
+            })
+        })
+        .collect();
+    (
+        "200 OK",
+        json!({"status": "ok", "count": findings.len(), "findings": findings}),
+    )
+}
+
+/// Run `base` against the body's target in a child mcp-hack.
+fn run(workspace: &Path, base: &[&str], body: &Value) -> (&'static str, Value) {
+    let Some(target) = body.get("target").and_then(|v| v.as_str()) else {
+        return error("400 Bad Request", "missing \"target\"");
+    };
+    let mut base: Vec<String> = base.iter().map(|s| s.to_string()).collect();
+    if base[0] == "scan" && body.get("passive").and_then(|v| v.as_bool()) == Some(true) {
+        base.push("--passive".into());
+    }
+    let outcome = run_one(&base, target, "json");
+    let mut out = json!({"exit_code": outcome.exit_code, "result": outcome.result});
+    if base[0] == "scan" && outcome.result.get("status").and_then(|v| v.as_str()) == Some("ok") {
+        let stamp: String = utc_timestamp()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let path: PathBuf = workspace.join(format!("scan-{stamp}.json"));
+        let text = serde_json::to_string_pretty(&outcome.result).unwrap_or_default();
+        if let Err(e) = std::fs::write(&path, text) {
+            return error(
+                "500 Internal Server Error",
+                &format!("failed to save {}: {e}", path.display()),
+            );
+        }
+        out["saved"] = json!(path.display().to_string());
+    }
+    ("200 OK", out)
+}
+
+/// Status line and JSON answer for one request.
+fn route(workspace: &Path, request: &HttpRequest) -> (&'static str, Value) {
+    let (path, params) = request.path_and_query();
+    let base: &[&str] = match (request.method.as_str(), path) {
+        ("GET", "/findings") => return findings(workspace, &params),
+        ("POST", "/list") => &["list", "tools"],
+        ("POST", "/scan") => &["scan"],
+        (_, "/findings" | "/list" | "/scan") => {
+            return error("405 Method Not Allowed", "method not allowed");
+        }
+        _ => return error("404 Not Found", &format!("no endpoint {path}")),
+    };
+    if request.header("origin").is_some() {
+        return error("403 Forbidden", "cross-origin requests are refused");
+    }
+    let json_body = request
+        .header("content-type")
+        .is_some_and(|t| t.starts_with("application/json"));
+    if !json_body {
+        return error("415 Unsupported Media Type", "expected application/json");
+    }
+    match serde_json::from_slice::<Value>(&request.body) {
+        Ok(body) => run(workspace, base, &body),
+        Err(e) => error("400 Bad Request", &format!("invalid JSON body: {e}")),
+    }
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_api(args: ApiArgs) -> Result<()> {
+    if current_log_level() >= LogLevel::Info {
+        eprintln!(
+            "[api] listening on http://{} (workspace {})",
+            args.listen, args.workspace
+        );
+    }
+    let workspace = Arc::new(PathBuf::from(&args.workspace));
+    listen(&args.listen, "api", move |stream| {
+        let request = read_request(&stream)?;
+        let (status, body) = route(&workspace, &request);
+        write_response(stream, status, "application/json", &body.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, target: &str, headers: &[(&str, &str)], body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.into(),
+            target: target.into(),
+            headers: headers
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn routes_findings_and_refuses_unsafe_posts() {
+        let dir = std::env::temp_dir().join(format!("mcp-hack-api-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a")).unwrap();
+        let scan = json!({"status": "ok", "target": "python3 a.py", "findings": [
+            {"check": "tool-naming", "severity": "low", "title": "odd"},
+            {"check": "dangerous-tool", "severity": "high", "title": "shell", "tool": "exec"},
+        ]});
+        std::fs::write(dir.join("a/scan.json"), scan.to_string()).unwrap();
+        std::fs::write(
+            dir.join("failed.json"),
+            r#"{"status":"error","findings":[{}]}"#,
+        )
+        .unwrap();
+
+        let (status, out) = route(&dir, &request("GET", "/findings", &[], ""));
+        assert_eq!(status, "200 OK");
+        assert_eq!(out["count"], 2);
+        assert_eq!(out["findings"][0]["title"], "shell");
+        assert_eq!(out["findings"][0]["target"], "python3 a.py");
+        assert_eq!(
+            out["findings"][0]["file"],
+            Path::new("a").join("scan.json").display().to_string()
+        );
+        let (_, out) = route(
+            &dir,
+            &request(
+                "GET",
+                "/findings?min_severity=high&target=python3+a.py",
+                &[],
+                "",
+            ),
+        );
+        assert_eq!(out["count"], 1);
+        let (_, out) = route(&dir, &request("GET", "/findings?target=other", &[], ""));
+        assert_eq!(out["count"], 0);
+        assert_eq!(
+            route(&dir, &request("GET", "/findings?min_severity=bad", &[], "")).0,
+            "400 Bad Request"
+        );
+
+        let json_type = ("Content-Type", "application/json");
+        let body = r#"{"target": "python3 a.py"}"#;
+        assert_eq!(
+            route(&dir, &request("POST", "/scan", &[], body)).0,
+            "415 Unsupported Media Type"
+        );
+        assert_eq!(
+            route(
+                &dir,
+                &request(
+                    "POST",
+                    "/scan",
+                    &[json_type, ("Origin", "http://evil")],
+                    body
+                )
+            )
+            .0,
+            "403 Forbidden"
+        );
+        assert_eq!(
+            route(&dir, &request("POST", "/list", &[json_type], "{}")).0,
+            "400 Bad Request"
+        );
+        assert_eq!(
+            route(&dir, &request("GET", "/scan", &[], "")).0,
+            "405 Method Not Allowed"
+        );
+        assert_eq!(
+            route(&dir, &request("GET", "/", &[], "")).0,
+            "404 Not Found"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,486 @@
+/*!
+bench.rs - bench subcommand.
+
+Drives sustained load against one session for --duration: --concurrency
+workers each send the next request as soon as the previous one answered.
+  bench tool NAME   tools/call NAME (--param values; other required params
+                    get placeholders, like soak)
+  bench tools       tools/list
+Reports throughput (requests per second), latency percentiles (p50 / p90 /
+p95 / p99, min / mean / max) and the error rate. Errors are protocol errors,
+timeouts (--timeout) and, counted separately, isError tool results; the
+first message of each error kind is kept as a sample. A worker whose
+connection is lost stops early.
+
+Example:
+  mcp-hack bench tool search -t "python3 server.py" --param q=test --duration 30s --concurrency 8
+  mcp-hack bench tools -t "npx -y some-mcp-server" --duration 10s --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::cmd::shared::placeholder_arguments;
+use crate::mcp::{self, TargetSpec};
+use crate::utils::parse_duration;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+/// What each worker sends.
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum BenchSubject {
+    /// tools/call of one tool
+    Tool,
+    /// tools/list
+    Tools,
+}
+
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Workload: 'tool' (tools/call NAME) or 'tools' (tools/list)
+    pub subject: BenchSubject,
+
+    /// Tool to call
+    #[arg(value_name = "TOOL", required_if_eq("subject", "tool"))]
+    pub tool: Option<String>,
+
+    /// Tool parameter (KEY=VALUE), repeatable; other required params get placeholders
+    #[arg(long = "param", value_name = "KEY=VALUE")]
+    pub params: Vec<String>,
+
+    /// How long to drive load (e.g. 500ms, 30s, 5m; bare numbers are seconds)
+    #[arg(long, value_name = "DURATION", default_value = "30s")]
+    pub duration: String,
+
+    /// Requests in flight at once
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub concurrency: u64,
+
+    /// Seconds to wait for each response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_bench(mut args: BenchArgs) -> Result<()> {
+    let json = args.output.is_json();
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => return usage_error(json, "no target specified (use --target or MCP_TARGET)"),
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(json, "remote benchmarking not implemented yet");
+    }
+    let duration = match parse_duration(&args.duration) {
+        Ok(d) if !d.is_zero() => d,
+        Ok(_) => return usage_error(json, "--duration must be greater than zero"),
+        Err(e) => return report_error(json, e),
+    };
+    let mut provided = HashMap::new();
+    for kv in &args.params {
+        match kv.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => {
+                provided.insert(k.trim().to_string(), v.to_string());
+            }
+            _ => {
+                return usage_error(json, &format!("invalid --param (expected KEY=VALUE): {kv}"));
+            }
+        }
+    }
+
+    let run = match bench(&spec, &args, &provided, duration).await {
+        Ok(r) => r,
+        Err(e) => return report_error(json, e),
+    };
+    crate::utils::progress_events::phase("done", None);
+
+    let workload = match &args.tool {
+        Some(t) if args.subject == BenchSubject::Tool => format!("tools/call {t}"),
+        _ => "tools/list".to_string(),
+    };
+    let stats = run.stats();
+    let out = json!({
+        "status": "ok",
+        "kind": "bench",
+        "target": target_raw,
+        "workload": workload,
+        "arguments": run.arguments,
+        "concurrency": args.concurrency,
+        "duration_ms": duration.as_millis(),
+        "elapsed_ms": run.elapsed.as_millis(),
+        "timings": crate::mcp::timing::snapshot().to_json(),
+        "requests": stats.requests,
+        "ok": stats.ok,
+        "errors": stats.errors,
+        "tool_errors": stats.tool_errors,
+        "error_rate": stats.error_rate(),
+        "rps": round2(stats.requests as f64 / run.elapsed.as_secs_f64().max(1e-9)),
+        "latency_ms": stats.latency_json(),
+        "error_samples": run.error_samples.iter().map(|(kind, (count, message))| json!({
+            "kind": kind,
+            "count": count,
+            "message": message,
+        })).collect::<Vec<_>>(),
+        "workers_stopped": run.workers_stopped,
+    });
+
+    if json {
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!("{} Bench ({workload})", emoji("rocket", &style)),
+        Some(format!(
+            "target={target_raw} • {} worker(s) • {} ms",
+            args.concurrency,
+            run.elapsed.as_millis()
+        )),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} {} request(s) • {} req/s • {} error(s) ({:.2}%) • {} isError result(s)",
+        emoji("info", &style),
+        stats.requests,
+        out["rps"],
+        stats.errors,
+        stats.error_rate() * 100.0,
+        stats.tool_errors
+    );
+    let latency = stats.latency_json();
+    if latency.is_object() {
+        let rows: Vec<Vec<String>> = ["min", "mean", "p50", "p90", "p95", "p99", "max"]
+            .iter()
+            .map(|k| {
+                vec![
+                    k.to_string(),
+                    format!("{:.2}", latency[*k].as_f64().unwrap_or(0.0)),
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["LATENCY", "MS"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+    }
+    for (kind, (count, message)) in &run.error_samples {
+        println!(
+            "{} {} {}",
+            emoji("warn", &style),
+            color(Role::Warning, format!("{kind} ×{count}"), &style),
+            color(Role::Dim, message, &style)
+        );
+    }
+    if run.workers_stopped > 0 {
+        println!(
+            "{} {}",
+            emoji("warn", &style),
+            color(
+                Role::Warning,
+                format!(
+                    "{} worker(s) stopped early: connection to the server lost",
+                    run.workers_stopped
+                ),
+                &style
+            )
+        );
+    }
+    Ok(())
+}
+
+/* ---- Helpers ---- */
+
+/// Outcome of one request.
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Ok,
+    /// An isError tool result
+    ToolError,
+    /// Protocol error, timeout or transport failure: (kind, message)
+    Error(String, String),
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    requests: u64,
+    ok: u64,
+    errors: u64,
+    tool_errors: u64,
+    /// Latencies in microseconds, sorted
+    latencies_us: Vec<u64>,
+}
+
+impl Stats {
+    fn from_samples(samples: &[(u64, Outcome)]) -> Self {
+        let mut stats = Stats {
+            requests: samples.len() as u64,
+            ..Default::default()
+        };
+        for (us, outcome) in samples {
+            match outcome {
+                Outcome::Ok => stats.ok += 1,
+                Outcome::ToolError => stats.tool_errors += 1,
+                Outcome::Error(..) => stats.errors += 1,
+            }
+            stats.latencies_us.push(*us);
+        }
+        stats.latencies_us.sort_unstable();
+        stats
+    }
+
+    /// Share of requests that failed (isError results are not failures).
+    fn error_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.requests as f64
+    }
+
+    /// Nearest-rank percentile `p` (0-100) in milliseconds.
+    fn percentile(&self, p: f64) -> Option<f64> {
+        let n = self.latencies_us.len();
+        if n == 0 {
+            return None;
+        }
+        let rank = ((p / 100.0) * n as f64).ceil().max(1.0) as usize;
+        Some(self.latencies_us[rank.min(n) - 1] as f64 / 1000.0)
+    }
+
+    fn latency_json(&self) -> Value {
+        let (Some(min), Some(max)) = (self.latencies_us.first(), self.latencies_us.last()) else {
+            return Value::Null;
+        };
+        let mean =
+            self.latencies_us.iter().sum::<u64>() as f64 / self.latencies_us.len() as f64 / 1000.0;
+        let p = |q| round2(self.percentile(q).unwrap_or_default());
+        json!({
+            "min": round2(*min as f64 / 1000.0),
+            "mean": round2(mean),
+            "p50": p(50.0),
+            "p90": p(90.0),
+            "p95": p(95.0),
+            "p99": p(99.0),
+            "max": round2(*max as f64 / 1000.0),
+        })
+    }
+}
+
+fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+struct BenchRun {
+    samples: Vec<(u64, Outcome)>,
+    /// kind -> (count, first message)
+    error_samples: std::collections::BTreeMap<String, (u64, String)>,
+    arguments: Value,
+    workers_stopped: u64,
+    elapsed: Duration,
+}
+
+impl BenchRun {
+    fn stats(&self) -> Stats {
+        Stats::from_samples(&self.samples)
+    }
+}
+
+/// Error kind and message of a failed request.
+fn classify(err: &rmcp::ServiceError) -> (String, String) {
+    use rmcp::ServiceError;
+    let kind = match err {
+        ServiceError::McpError(data) => format!("rpc {}", data.code.0),
+        ServiceError::Timeout { .. } => "timeout".to_string(),
+        ServiceError::TransportClosed | ServiceError::TransportSend(_) => "transport".to_string(),
+        _ => "other".to_string(),
+    };
+    (kind, err.to_string())
+}
+
+async fn bench(
+    spec: &TargetSpec,
+    args: &BenchArgs,
+    provided: &HashMap<String, String>,
+    duration: Duration,
+) -> Result<BenchRun> {
+    use crate::mcp::timing::{self, Phase};
+    use rmcp::model::CallToolRequestParam;
+
+    if !spec.is_local() {
+        return Err(exit::usage("bench only supports local process targets"));
+    }
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    let service = crate::mcp::spawn::connect(spec, ()).await?;
+
+    let call = match (&args.subject, &args.tool) {
+        (BenchSubject::Tool, Some(name)) => {
+            let tools =
+                crate::mcp::limits::request("tools/list", service.list_tools(Default::default()))
+                    .await?
+                    .context("Failed to list tools")?;
+            let tools = serde_json::to_value(&tools).unwrap_or_default();
+            let Some(tool) = tools["tools"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|t| t["name"].as_str() == Some(name))
+            else {
+                return Err(exit::tool_not_found(name));
+            };
+            let arguments = placeholder_arguments(tool, provided)?;
+            Some(CallToolRequestParam {
+                name: name.clone().into(),
+                arguments: arguments.as_object().cloned(),
+            })
+        }
+        _ => None,
+    };
+    let arguments = call
+        .as_ref()
+        .and_then(|c| c.arguments.clone())
+        .map(Value::Object)
+        .unwrap_or(Value::Null);
+
+    crate::utils::progress_events::phase("bench", None);
+    let timeout = Duration::from_secs(args.timeout);
+    let started = Instant::now();
+    let deadline = started + duration;
+    let mut workers = Vec::new();
+    for _ in 0..args.concurrency {
+        let peer = service.peer().clone();
+        let call = call.clone();
+        workers.push(tokio::spawn(async move {
+            let mut samples = Vec::new();
+            let mut lost = false;
+            while Instant::now() < deadline {
+                let sent = Instant::now();
+                let response = match &call {
+                    Some(c) => tokio::time::timeout(timeout, peer.call_tool(c.clone()))
+                        .await
+                        .map(|r| r.map(|r| r.is_error == Some(true))),
+                    None => tokio::time::timeout(timeout, peer.list_tools(Default::default()))
+                        .await
+                        .map(|r| r.map(|_| false)),
+                };
+                let us = sent.elapsed().as_micros() as u64;
+                let outcome = match response {
+                    Ok(Ok(false)) => Outcome::Ok,
+                    Ok(Ok(true)) => Outcome::ToolError,
+                    Ok(Err(e)) => {
+                        lost = matches!(e, rmcp::ServiceError::TransportClosed);
+                        let (kind, message) = classify(&e);
+                        Outcome::Error(kind, message)
+                    }
+                    Err(_) => Outcome::Error(
+                        "timeout".to_string(),
+                        format!("no response within {}s", timeout.as_secs()),
+                    ),
+                };
+                samples.push((us, outcome));
+                if lost {
+                    break;
+                }
+            }
+            (samples, lost)
+        }));
+    }
+
+    let mut run = BenchRun {
+        samples: Vec::new(),
+        error_samples: Default::default(),
+        arguments,
+        workers_stopped: 0,
+        elapsed: Duration::ZERO,
+    };
+    for worker in workers {
+        let (samples, lost) = worker.await.context("bench worker panicked")?;
+        run.workers_stopped += u64::from(lost);
+        for (_, outcome) in &samples {
+            if let Outcome::Error(kind, message) = outcome {
+                run.error_samples
+                    .entry(kind.clone())
+                    .or_insert_with(|| (0, message.clone()))
+                    .0 += 1;
+            }
+        }
+        run.samples.extend(samples);
+    }
+    run.elapsed = started.elapsed();
+    let _ = timing::timed(Phase::Shutdown, service.cancel()).await;
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_latencies_and_errors() {
+        let samples: Vec<(u64, Outcome)> = (1..=100)
+            .map(|i| {
+                let outcome = match i {
+                    7 => Outcome::ToolError,
+                    13 | 42 => Outcome::Error("timeout".into(), "no response".into()),
+                    _ => Outcome::Ok,
+                };
+                (i * 1000, outcome)
+            })
+            .collect();
+        let stats = Stats::from_samples(&samples);
+        assert_eq!((stats.requests, stats.ok), (100, 97));
+        assert_eq!((stats.errors, stats.tool_errors), (2, 1));
+        assert!((stats.error_rate() - 0.02).abs() < 1e-9);
+        assert_eq!(stats.percentile(50.0), Some(50.0));
+        assert_eq!(stats.percentile(99.0), Some(99.0));
+        let latency = stats.latency_json();
+        assert_eq!(latency["min"], 1.0);
+        assert_eq!(latency["mean"], 50.5);
+        assert_eq!(latency["p90"], 90.0);
+        assert_eq!(latency["max"], 100.0);
+
+        let empty = Stats::from_samples(&[]);
+        assert_eq!(empty.error_rate(), 0.0);
+        assert!(empty.latency_json().is_null());
+    }
+}
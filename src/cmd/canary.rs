@@ -0,0 +1,195 @@
+/*!
+canary.rs - canary subcommand.
+
+Sends unique canary values to every tool parameter that looks like a secret
+(password, token, api key, format=password / writeOnly) and reports where
+those values resurface in the same session: tool results, log
+notifications, stdout, resource contents or prompts (see crate::scan::canary).
+
+Tools are really invoked. Destructive tools (annotations.destructiveHint)
+are skipped unless --include-destructive is given.
+
+Example:
+  mcp-hack canary -t "python3 server.py"
+  mcp-hack canary -t "npx -y some-mcp-server" --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::scan::{Finding, canary};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct CanaryArgs {
+    /// Also call tools annotated as destructive
+    #[arg(long = "include-destructive")]
+    pub include_destructive: bool,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_canary(mut args: CanaryArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote canary testing not implemented yet",
+        );
+    }
+
+    let started = Instant::now();
+    let run = match canary::run(
+        &spec,
+        Duration::from_secs(args.timeout),
+        args.include_destructive,
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let findings = run.findings();
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+
+    if args.output.is_json() {
+        let out = serde_json::json!({
+            "status": "ok",
+            "target": target_raw,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "planted": run.plants.iter().map(|p| serde_json::json!({
+                "tool": p.tool,
+                "param": p.param,
+                "canary": p.canary,
+            })).collect::<Vec<_>>(),
+            "skipped": run.skipped.iter().map(|(tool, reason)| serde_json::json!({
+                "tool": tool,
+                "reason": reason,
+            })).collect::<Vec<_>>(),
+            "resources_checked": run.resources_checked,
+            "prompts_checked": run.prompts_checked,
+            "count": findings.len(),
+            "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Secret canary • {} finding(s)",
+            emoji("spark", &style),
+            findings.len()
+        ),
+        Some(format!("target={target_raw} • {elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} planted {} canary value(s) • checked {} resource content(s), {} prompt(s)",
+        emoji("info", &style),
+        run.plants.len(),
+        run.resources_checked,
+        run.prompts_checked
+    );
+    for p in &run.plants {
+        println!(
+            "  {} {}.{}",
+            emoji("tool", &style),
+            p.tool,
+            color(Role::Dim, &p.param, &style)
+        );
+    }
+    for (tool, reason) in &run.skipped {
+        println!(
+            "  {} {tool} {}",
+            emoji("info", &style),
+            color(Role::Dim, format!("skipped: {reason}"), &style)
+        );
+    }
+    println!();
+    if run.plants.is_empty() {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(Role::Dim, "No secret-looking parameters found", &style)
+        );
+        return Ok(());
+    }
+    if findings.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, "No canary resurfaced", &style)
+        );
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = findings
+        .iter()
+        .map(|f| {
+            vec![
+                f.severity.as_str().to_uppercase(),
+                f.tool.clone().unwrap_or_default(),
+                f.title.clone(),
+            ]
+        })
+        .collect();
+    let tbl = table(
+        &["SEVERITY", "TOOL", "FINDING"],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            ..Default::default()
+        },
+        &style,
+    );
+    println!("{tbl}");
+    Ok(())
+}
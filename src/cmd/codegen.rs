@@ -0,0 +1,640 @@
+/*!
+codegen.rs - typed client stubs from tool schemas (`export client`).
+
+For each tool: an argument type built from its inputSchema (one field per
+property, optional unless listed in `required`) and a wrapper that sends
+the call through a `CallTool` function / trait the caller implements over
+whatever MCP client they use. Generated code needs nothing else (Rust:
+serde + serde_json; Python: 3.11+).
+
+Schema types map to string / integer / number / boolean, arrays of those,
+string enums (TypeScript unions, Python Literal) and generic objects;
+anything else ($ref, anyOf, ...) becomes unknown / Value / Any. Names are
+rewritten per language (readFile / read_file / ReadFileArgs) and made
+distinct; tool and property names only ever appear inside escaped string
+literals, and descriptions only inside comments with their terminators
+defused, so a hostile server cannot inject code.
+*/
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+/// Language of the generated stubs (`--lang`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    /// TypeScript
+    #[value(alias = "typescript")]
+    Ts,
+    #[value(alias = "rs")]
+    Rust,
+    #[value(alias = "py")]
+    Python,
+}
+
+impl Lang {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lang::Ts => "ts",
+            Lang::Rust => "rust",
+            Lang::Python => "python",
+        }
+    }
+}
+
+/* ---- Schema Model ---- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ty {
+    String,
+    Enum(Vec<String>),
+    Integer,
+    Number,
+    Boolean,
+    Array(Box<Ty>),
+    Object,
+    Any,
+}
+
+impl Ty {
+    fn from_schema(schema: &Value) -> Ty {
+        let strings = |v: &Value| -> Option<Vec<String>> {
+            v.as_array()?
+                .iter()
+                .map(|s| s.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .filter(|e| !e.is_empty())
+        };
+        if let Some(values) = schema.get("enum").and_then(strings) {
+            return Ty::Enum(values);
+        }
+        if let Some(c) = schema.get("const").and_then(|c| c.as_str()) {
+            return Ty::Enum(vec![c.to_string()]);
+        }
+        let ty = match schema.get("type") {
+            Some(Value::String(t)) => Some(t.as_str()),
+            // ["string", "null"] and the like: the one non-null type.
+            Some(Value::Array(types)) => {
+                let mut rest = types
+                    .iter()
+                    .filter_map(|t| t.as_str())
+                    .filter(|t| *t != "null");
+                match (rest.next(), rest.next()) {
+                    (Some(t), None) => Some(t),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        match ty {
+            Some("string") => Ty::String,
+            Some("integer") => Ty::Integer,
+            Some("number") => Ty::Number,
+            Some("boolean") => Ty::Boolean,
+            Some("array") => Ty::Array(Box::new(
+                schema.get("items").map_or(Ty::Any, Ty::from_schema),
+            )),
+            Some("object") => Ty::Object,
+            _ => Ty::Any,
+        }
+    }
+
+    fn ts(&self) -> String {
+        match self {
+            Ty::String => "string".into(),
+            Ty::Enum(values) => values
+                .iter()
+                .map(|v| json_literal(v))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Ty::Integer | Ty::Number => "number".into(),
+            Ty::Boolean => "boolean".into(),
+            Ty::Array(item) => format!("Array<{}>", item.ts()),
+            Ty::Object => "Record<string, unknown>".into(),
+            Ty::Any => "unknown".into(),
+        }
+    }
+
+    fn rust(&self) -> String {
+        match self {
+            Ty::String | Ty::Enum(_) => "String".into(),
+            Ty::Integer => "i64".into(),
+            Ty::Number => "f64".into(),
+            Ty::Boolean => "bool".into(),
+            Ty::Array(item) => format!("Vec<{}>", item.rust()),
+            Ty::Object => "serde_json::Map<String, Value>".into(),
+            Ty::Any => "Value".into(),
+        }
+    }
+
+    fn python(&self) -> String {
+        match self {
+            Ty::String => "str".into(),
+            Ty::Enum(values) => format!(
+                "Literal[{}]",
+                values
+                    .iter()
+                    .map(|v| json_literal(v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Ty::Integer => "int".into(),
+            Ty::Number => "float".into(),
+            Ty::Boolean => "bool".into(),
+            Ty::Array(item) => format!("list[{}]", item.python()),
+            Ty::Object => "dict[str, Any]".into(),
+            Ty::Any => "Any".into(),
+        }
+    }
+}
+
+struct Param {
+    name: String,
+    ty: Ty,
+    required: bool,
+    description: Option<String>,
+}
+
+struct Stub {
+    tool: String,
+    /// Identifier words, distinct across stubs.
+    words: Vec<String>,
+    description: Option<String>,
+    params: Vec<Param>,
+}
+
+fn description(v: &Value) -> Option<String> {
+    v.get("description")
+        .and_then(|d| d.as_str())
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(str::to_string)
+}
+
+fn stubs(tools: &[Value]) -> Vec<Stub> {
+    let mut seen = HashSet::new();
+    tools
+        .iter()
+        .map(|tool| {
+            let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let schema = tool.get("inputSchema").or_else(|| tool.get("input_schema"));
+            let required: Vec<&str> = schema
+                .and_then(|s| s.get("required"))
+                .and_then(|r| r.as_array())
+                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            let params = schema
+                .and_then(|s| s.get("properties"))
+                .and_then(|p| p.as_object())
+                .map(|props| {
+                    props
+                        .iter()
+                        .map(|(key, prop)| Param {
+                            name: key.clone(),
+                            ty: Ty::from_schema(prop),
+                            required: required.contains(&key.as_str()),
+                            description: description(prop),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            Stub {
+                tool: name.to_string(),
+                words: distinct(words(name), &mut seen),
+                description: description(tool),
+                params,
+            }
+        })
+        .collect()
+}
+
+/* ---- Names ---- */
+
+/// Split a name into lowercase ASCII words: `getURLInfo` / `get-url_info`
+/// -> get, url, info. Never empty, never starts with a digit.
+fn words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_ascii_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_ascii_lowercase()
+                    || p.is_ascii_digit()
+                    || (p.is_ascii_uppercase() && next.is_some_and(|n| n.is_ascii_lowercase()))
+            });
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    if words
+        .first()
+        .is_none_or(|w| w.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        words.insert(0, "tool".into());
+    }
+    words
+}
+
+/// `words`, with a number appended when already taken.
+fn distinct(words: Vec<String>, seen: &mut HashSet<String>) -> Vec<String> {
+    let mut n = 1;
+    let mut candidate = words.clone();
+    while !seen.insert(candidate.join("_")) {
+        n += 1;
+        candidate = words.clone();
+        candidate.push(n.to_string());
+    }
+    candidate
+}
+
+fn snake(words: &[String]) -> String {
+    words.join("_")
+}
+
+fn pascal(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|w| {
+            let mut c = w.chars();
+            c.next()
+                .map(|f| f.to_ascii_uppercase().to_string() + c.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn camel(words: &[String]) -> String {
+    let p = pascal(words);
+    let mut c = p.chars();
+    c.next()
+        .map(|f| f.to_ascii_lowercase().to_string() + c.as_str())
+        .unwrap_or_default()
+}
+
+const TS_RESERVED: &[&str] = &[
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const RUST_RESERVED: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const PYTHON_RESERVED: &[&str] = &[
+    "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif",
+    "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda",
+    "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+fn unreserved(ident: String, reserved: &[&str]) -> String {
+    if reserved.contains(&ident.as_str()) {
+        ident + "_"
+    } else {
+        ident
+    }
+}
+
+/* ---- Literals / Comments ---- */
+
+/// A double-quoted string literal valid in TypeScript and Python.
+fn json_literal(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".into())
+}
+
+/// `text` as comment lines: every kind of line break splits, other control
+/// characters are dropped.
+fn comment_lines(text: &str) -> Vec<String> {
+    text.replace("\r\n", "\n")
+        .split(['\n', '\r', '\u{2028}', '\u{2029}', '\u{85}'])
+        .map(|l| {
+            l.chars()
+                .filter(|c| !c.is_control())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// `/** ... */` (TypeScript), `*/` defused.
+fn jsdoc(out: &mut String, indent: &str, text: Option<&str>) {
+    let Some(text) = text else { return };
+    let lines = comment_lines(text);
+    if let [line] = lines.as_slice() {
+        let _ = writeln!(out, "{indent}/** {} */", line.replace("*/", "*\\/"));
+        return;
+    }
+    let _ = writeln!(out, "{indent}/**");
+    for line in lines {
+        let _ = writeln!(out, "{indent} * {}", line.replace("*/", "*\\/").trim_end());
+    }
+    let _ = writeln!(out, "{indent} */");
+}
+
+/// Line comments (`//`, `///`, `#`).
+fn line_comment(out: &mut String, indent: &str, marker: &str, text: Option<&str>) {
+    for line in text.map(comment_lines).unwrap_or_default() {
+        let _ = writeln!(out, "{}", format!("{indent}{marker} {line}").trim_end());
+    }
+}
+
+/* ---- Generators ---- */
+
+/// Stubs for `tools` (tools/list entries) listed by `source`.
+pub fn generate(lang: Lang, source: &str, tools: &[Value]) -> String {
+    let stubs = stubs(tools);
+    match lang {
+        Lang::Ts => typescript(source, &stubs),
+        Lang::Rust => rust(source, &stubs),
+        Lang::Python => python(source, &stubs),
+    }
+}
+
+fn typescript(source: &str, stubs: &[Stub]) -> String {
+    let mut out = String::new();
+    line_comment(
+        &mut out,
+        "",
+        "//",
+        Some(&format!("Typed wrappers for the tools of {source}")),
+    );
+    out.push_str("// Generated by `mcp-hack export client --lang ts`; do not edit.\n\n");
+    out.push_str("/** Sends a tools/call request and resolves to its result. */\n");
+    out.push_str(
+        "export type CallTool = (name: string, args: Record<string, unknown>) => Promise<unknown>;\n",
+    );
+    for stub in stubs {
+        let args = pascal(&stub.words) + "Args";
+        out.push('\n');
+        jsdoc(&mut out, "", stub.description.as_deref());
+        if stub.params.is_empty() {
+            let _ = writeln!(out, "export type {args} = Record<string, never>;");
+        } else {
+            let _ = writeln!(out, "export type {args} = {{");
+            for p in &stub.params {
+                jsdoc(&mut out, "  ", p.description.as_deref());
+                let key = if ts_ident(&p.name) {
+                    p.name.clone()
+                } else {
+                    json_literal(&p.name)
+                };
+                let opt = if p.required { "" } else { "?" };
+                let _ = writeln!(out, "  {key}{opt}: {};", p.ty.ts());
+            }
+            out.push_str("};\n");
+        }
+        out.push('\n');
+        jsdoc(&mut out, "", stub.description.as_deref());
+        let _ = writeln!(
+            out,
+            "export function {}(call: CallTool, args: {args}): Promise<unknown> {{",
+            unreserved(camel(&stub.words), TS_RESERVED)
+        );
+        let _ = writeln!(out, "  return call({}, args);", json_literal(&stub.tool));
+        out.push_str("}\n");
+    }
+    out
+}
+
+/// A plain identifier (`[A-Za-z_$][A-Za-z0-9_$]*`), usable as a TS property key.
+fn ts_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+fn rust(source: &str, stubs: &[Stub]) -> String {
+    let mut out = String::new();
+    line_comment(
+        &mut out,
+        "",
+        "//!",
+        Some(&format!("Typed wrappers for the tools of {source}")),
+    );
+    out.push_str("//!\n");
+    out.push_str("//! Generated by `mcp-hack export client --lang rust`; do not edit.\n");
+    out.push_str("//! Needs serde (derive) and serde_json.\n\n");
+    out.push_str("use serde::Serialize;\nuse serde_json::Value;\n\n");
+    out.push_str("/// Sends a tools/call request and returns its result.\n");
+    out.push_str("#[allow(async_fn_in_trait)]\n");
+    out.push_str("pub trait CallTool {\n    type Error;\n\n");
+    out.push_str(
+        "    async fn call_tool(&mut self, name: &str, arguments: Value) -> Result<Value, Self::Error>;\n}\n",
+    );
+    for stub in stubs {
+        let args = pascal(&stub.words) + "Args";
+        out.push('\n');
+        line_comment(&mut out, "", "///", stub.description.as_deref());
+        out.push_str("#[derive(Debug, Clone, Default, Serialize)]\n");
+        if stub.params.is_empty() {
+            let _ = writeln!(out, "pub struct {args} {{}}");
+        } else {
+            let _ = writeln!(out, "pub struct {args} {{");
+            let mut seen = HashSet::new();
+            for p in &stub.params {
+                let field = unreserved(snake(&distinct(words(&p.name), &mut seen)), RUST_RESERVED);
+                line_comment(&mut out, "    ", "///", p.description.as_deref());
+                if field != p.name {
+                    let _ = writeln!(out, "    #[serde(rename = {:?})]", p.name);
+                }
+                let ty = if p.required {
+                    p.ty.rust()
+                } else {
+                    out.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+                    format!("Option<{}>", p.ty.rust())
+                };
+                let _ = writeln!(out, "    pub {field}: {ty},");
+            }
+            out.push_str("}\n");
+        }
+        out.push('\n');
+        line_comment(&mut out, "", "///", stub.description.as_deref());
+        let _ = writeln!(
+            out,
+            "pub async fn {}<C: CallTool>(client: &mut C, args: &{args}) -> Result<Value, C::Error> {{",
+            unreserved(snake(&stub.words), RUST_RESERVED)
+        );
+        let _ = writeln!(
+            out,
+            "    let arguments = serde_json::to_value(args).unwrap_or_default();"
+        );
+        let _ = writeln!(
+            out,
+            "    client.call_tool({:?}, arguments).await",
+            stub.tool
+        );
+        out.push_str("}\n");
+    }
+    out
+}
+
+fn python(source: &str, stubs: &[Stub]) -> String {
+    let mut out = String::new();
+    line_comment(
+        &mut out,
+        "",
+        "#",
+        Some(&format!("Typed wrappers for the tools of {source}")),
+    );
+    out.push_str("# Generated by `mcp-hack export client --lang python`; do not edit.\n");
+    out.push_str("# Python 3.11+.\n\n");
+    out.push_str(
+        "from typing import Any, Awaitable, Callable, Literal, NotRequired, TypedDict\n\n",
+    );
+    out.push_str("# Sends a tools/call request and returns its result.\n");
+    out.push_str("CallTool = Callable[[str, dict[str, Any]], Awaitable[Any]]\n");
+    for stub in stubs {
+        let args = pascal(&stub.words) + "Args";
+        out.push_str("\n\n");
+        line_comment(&mut out, "", "#", stub.description.as_deref());
+        // The functional form takes any property name.
+        if stub.params.is_empty() {
+            let _ = writeln!(out, "{args} = TypedDict({}, {{}})", json_literal(&args));
+        } else {
+            let _ = writeln!(out, "{args} = TypedDict({}, {{", json_literal(&args));
+            for p in &stub.params {
+                line_comment(&mut out, "    ", "#", p.description.as_deref());
+                let ty = if p.required {
+                    p.ty.python()
+                } else {
+                    format!("NotRequired[{}]", p.ty.python())
+                };
+                let _ = writeln!(out, "    {}: {ty},", json_literal(&p.name));
+            }
+            out.push_str("})\n");
+        }
+        out.push_str("\n\n");
+        let _ = writeln!(
+            out,
+            "async def {}(call: CallTool, args: {args}) -> Any:",
+            unreserved(snake(&stub.words), PYTHON_RESERVED)
+        );
+        line_comment(&mut out, "    ", "#", stub.description.as_deref());
+        let _ = writeln!(
+            out,
+            "    return await call({}, dict(args))",
+            json_literal(&stub.tool)
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn generates_typed_stubs() {
+        assert_eq!(words("getURLInfo"), ["get", "url", "info"]);
+        assert_eq!(words("read-file_v2"), ["read", "file", "v2"]);
+        assert_eq!(words("2fa"), ["tool", "2fa"]);
+        assert_eq!(words("検索"), ["tool"]);
+
+        let tools = vec![
+            json!({
+                "name": "search_issues",
+                "description": "Search */ issues\nline two",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "query": {"type": "string"},
+                        "maxResults": {"type": ["integer", "null"]},
+                        "state": {"enum": ["open", "closed"]},
+                        "labels": {"type": "array", "items": {"type": "string"}},
+                        "type": {},
+                    },
+                    "required": ["query"],
+                },
+            }),
+            json!({"name": "delete", "inputSchema": {"type": "object"}}),
+            json!({"name": "searchIssues"}),
+        ];
+
+        let ts = generate(Lang::Ts, "server.py", &tools);
+        assert!(ts.contains("export type SearchIssuesArgs = {\n"));
+        assert!(ts.contains("  query: string;\n  state?: \"open\" | \"closed\";\n"));
+        assert!(ts.contains("  maxResults?: number;\n"));
+        assert!(ts.contains(" * Search *\\/ issues\n * line two\n"));
+        assert!(ts.contains("export function delete_(call: CallTool, args: DeleteArgs)"));
+        assert!(ts.contains("export function searchIssues2("));
+        assert!(ts.contains("  return call(\"searchIssues\", args);"));
+
+        let rs = generate(Lang::Rust, "server.py", &tools);
+        assert!(rs.contains("    #[serde(rename = \"maxResults\")]\n"));
+        assert!(rs.contains("    pub max_results: Option<i64>,\n"));
+        assert!(rs.contains("    pub query: String,\n"));
+        assert!(rs.contains("    pub labels: Option<Vec<String>>,\n"));
+        assert!(rs.contains("    #[serde(rename = \"type\")]\n"));
+        assert!(rs.contains("    pub type_: Option<Value>,\n"));
+        assert!(rs.contains("pub async fn search_issues<C: CallTool>("));
+
+        let py = generate(Lang::Python, "server.py", &tools);
+        assert!(py.contains("    \"state\": NotRequired[Literal[\"open\", \"closed\"]],\n"));
+        assert!(py.contains("async def delete(call: CallTool, args: DeleteArgs) -> Any:"));
+        assert!(py.contains("# Search */ issues\n# line two\n"));
+    }
+}
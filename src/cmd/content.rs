@@ -0,0 +1,490 @@
+/*!
+content.rs - binary items of a tool result (`exec --save-dir`).
+
+A CallToolResult can carry base64 payloads:
+  - image     {"type":"image","data":..,"mimeType":..}
+  - audio     {"type":"audio","data":..,"mimeType":..}
+  - blob      {"type":"resource","resource":{"uri":..,"blob":..,"mimeType":..}}
+
+`--save-dir DIR` decodes each one to DIR/<tool>-<index>.<ext> (the extension
+from the MIME type; a blob with a URI gets DIR/<tool>-<index>-<URI file name>),
+reports the written paths, and the result summary shows
+`<saved to PATH (N bytes)>` instead of the base64 text.
+
+Images are summarized as `<image/png 640x480, 12034 bytes>` (dimensions read
+from the PNG / GIF / JPEG / BMP / WebP header; a payload whose bytes do not
+match the declared MIME type is flagged). `exec --preview` also draws them
+inline on terminals with an image protocol (detected from TERM_PROGRAM /
+LC_TERMINAL / TERM / KITTY_WINDOW_ID):
+  - iterm   iTerm2 / WezTerm (OSC 1337 File=, any format the terminal decodes)
+  - kitty   kitty graphics protocol (PNG only)
+*/
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+
+use crate::utils::base64_encode;
+
+/// One base64 payload of a result's `content` array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryItem {
+    /// Position in `content`.
+    pub index: usize,
+    /// image | audio | blob
+    pub kind: &'static str,
+    pub mime_type: Option<String>,
+    pub uri: Option<String>,
+    pub data: String,
+}
+
+/// A decoded item written to disk.
+#[derive(Debug, Clone)]
+pub struct Saved {
+    pub index: usize,
+    pub kind: &'static str,
+    pub mime_type: Option<String>,
+    pub path: PathBuf,
+    pub bytes: usize,
+}
+
+impl Saved {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "index": self.index,
+            "type": self.kind,
+            "mime_type": self.mime_type,
+            "path": self.path.display().to_string(),
+            "bytes": self.bytes,
+        })
+    }
+}
+
+/// Base64 payloads in a (serialized) CallToolResult.
+pub fn binary_items(result: &Value) -> Vec<BinaryItem> {
+    let str_of = |v: &Value, key: &str| v.get(key).and_then(|s| s.as_str()).map(str::to_string);
+    let mut out = Vec::new();
+    for (index, item) in result
+        .get("content")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let (kind, holder, field) = match item.get("type").and_then(|v| v.as_str()) {
+            Some("image") => ("image", item, "data"),
+            Some("audio") => ("audio", item, "data"),
+            Some("resource") => match item.get("resource") {
+                Some(r) if r.get("blob").is_some() => ("blob", r, "blob"),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        let Some(data) = str_of(holder, field) else {
+            continue;
+        };
+        out.push(BinaryItem {
+            index,
+            kind,
+            mime_type: str_of(holder, "mimeType"),
+            uri: str_of(holder, "uri"),
+            data,
+        });
+    }
+    out
+}
+
+/// File extension for a MIME type (`bin` when unknown).
+pub fn extension(mime_type: Option<&str>) -> &'static str {
+    let mime = mime_type
+        .unwrap_or("")
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    match mime.as_str() {
+        "image/png" => "png",
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+        "audio/mpeg" | "audio/mp3" => "mp3",
+        "audio/ogg" => "ogg",
+        "audio/flac" => "flac",
+        "audio/webm" | "video/webm" => "webm",
+        "video/mp4" | "audio/mp4" => "mp4",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" => "gz",
+        "application/json" => "json",
+        "text/plain" => "txt",
+        "text/html" => "html",
+        "text/csv" => "csv",
+        _ => "bin",
+    }
+}
+
+/// File name for an item: `<tool>-<index>-<last URI segment>` for a blob
+/// with a URI, else `<tool>-<index>.<ext>`. Only `[A-Za-z0-9._-]` is kept, so a server
+/// cannot point the file outside the directory.
+pub fn file_name(item: &BinaryItem, tool: &str) -> String {
+    let clean = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>()
+            .trim_start_matches('.')
+            .to_string()
+    };
+    let from_uri = item
+        .uri
+        .as_deref()
+        .and_then(|u| u.split(['?', '#']).next())
+        .and_then(|u| u.trim_end_matches('/').rsplit('/').next())
+        .map(clean)
+        .filter(|n| !n.is_empty());
+    match from_uri {
+        Some(name) => format!("{}-{}-{name}", clean(tool), item.index),
+        None => format!(
+            "{}-{}.{}",
+            clean(tool),
+            item.index,
+            extension(item.mime_type.as_deref())
+        ),
+    }
+}
+
+/// Decode every binary item of `result` into `dir` (created if needed).
+pub fn save_all(dir: &Path, tool: &str, result: &Value) -> Result<Vec<Saved>> {
+    let items = binary_items(result);
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut saved = Vec::new();
+    for item in items {
+        let bytes = crate::utils::base64_decode(&item.data)
+            .with_context(|| format!("content[{}]: bad {} data", item.index, item.kind))?;
+        let path = dir.join(file_name(&item, tool));
+        std::fs::write(&path, &bytes)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        saved.push(Saved {
+            index: item.index,
+            kind: item.kind,
+            mime_type: item.mime_type,
+            path,
+            bytes: bytes.len(),
+        });
+    }
+    Ok(saved)
+}
+
+/// Replace the base64 text of saved items in a result summary with a note.
+pub fn mark_saved(summary: &mut Value, saved: &[Saved]) {
+    for s in saved {
+        let Some(item) = summary.get_mut("content").and_then(|c| c.get_mut(s.index)) else {
+            continue;
+        };
+        let (holder, field) = match s.kind {
+            "blob" => (item.get_mut("resource"), "blob"),
+            _ => (Some(item), "data"),
+        };
+        if let Some(h) = holder {
+            h[field] = json!(format!(
+                "<saved to {} ({} bytes)>",
+                s.path.display(),
+                s.bytes
+            ));
+        }
+    }
+}
+
+/* ---- Images ---- */
+
+/// Format and size of an image payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageInfo {
+    pub index: usize,
+    pub mime_type: Option<String>,
+    /// MIME type the bytes look like, if recognized.
+    pub detected: Option<&'static str>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub bytes: usize,
+}
+
+impl ImageInfo {
+    /// `image/png 640x480, 12034 bytes` (plus a mismatch note).
+    pub fn label(&self) -> String {
+        let mime = self.mime_type.as_deref().unwrap_or("image");
+        let mut out = mime.to_string();
+        if let (Some(w), Some(h)) = (self.width, self.height) {
+            out.push_str(&format!(" {w}x{h}"));
+        }
+        out.push_str(&format!(", {} bytes", self.bytes));
+        if let Some(d) = self.detected
+            && !mime.eq_ignore_ascii_case(d)
+        {
+            out.push_str(&format!(", content looks like {d}"));
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "index": self.index,
+            "mime_type": self.mime_type,
+            "detected": self.detected,
+            "width": self.width,
+            "height": self.height,
+            "bytes": self.bytes,
+        })
+    }
+}
+
+fn be16(b: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn le16(b: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// MIME type of an image by its magic bytes.
+pub fn sniff(b: &[u8]) -> Option<&'static str> {
+    if b.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if b.starts_with(b"GIF87a") || b.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if b.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if b.starts_with(b"BM") && b.len() > 26 {
+        Some("image/bmp")
+    } else if b.len() > 12 && &b[..4] == b"RIFF" && &b[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Width and height from the header of a PNG, GIF, JPEG, BMP or WebP image.
+pub fn image_dimensions(b: &[u8]) -> Option<(u32, u32)> {
+    match sniff(b)? {
+        "image/png" => Some((be32(b, 16)?, be32(b, 20)?)),
+        "image/gif" => Some((le16(b, 6)?, le16(b, 8)?)),
+        "image/bmp" => Some((le32(b, 18)?, (le32(b, 22)? as i32).unsigned_abs())),
+        "image/webp" => match b.get(12..16)? {
+            b"VP8 " => Some((le16(b, 26)? & 0x3fff, le16(b, 28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = le32(b, 21)?;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => {
+                let w = le32(b, 24)? & 0xff_ffff;
+                let h = le32(b, 26)? >> 8;
+                Some((w + 1, h + 1))
+            }
+            _ => None,
+        },
+        "image/jpeg" => {
+            // Walk the segments up to a start-of-frame marker.
+            let mut i = 2;
+            while i + 9 < b.len() {
+                if b[i] != 0xff {
+                    return None;
+                }
+                let marker = b[i + 1];
+                if (0xc0..=0xcf).contains(&marker) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+                    return Some((be16(b, i + 7)?, be16(b, i + 5)?));
+                }
+                i += 2 + be16(b, i + 2)? as usize;
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Format and size of every image item of a (serialized) CallToolResult.
+pub fn images(result: &Value) -> Vec<(ImageInfo, Vec<u8>)> {
+    binary_items(result)
+        .into_iter()
+        .filter(|item| item.kind == "image")
+        .map(|item| {
+            let bytes = crate::utils::base64_decode(&item.data).unwrap_or_default();
+            let dims = image_dimensions(&bytes);
+            let info = ImageInfo {
+                index: item.index,
+                mime_type: item.mime_type,
+                detected: sniff(&bytes),
+                width: dims.map(|d| d.0),
+                height: dims.map(|d| d.1),
+                bytes: bytes.len(),
+            };
+            (info, bytes)
+        })
+        .collect()
+}
+
+/// Replace the base64 text of images in a result summary with their label
+/// (items already marked by [`mark_saved`] are left alone).
+pub fn mark_images(summary: &mut Value, images: &[(ImageInfo, Vec<u8>)]) {
+    for (info, _) in images {
+        if let Some(item) = summary
+            .get_mut("content")
+            .and_then(|c| c.get_mut(info.index))
+            && item
+                .get("data")
+                .and_then(|v| v.as_str())
+                .is_some_and(|d| !d.starts_with("<saved to "))
+        {
+            item["data"] = json!(format!("<{}>", info.label()));
+        }
+    }
+}
+
+/// Terminal image protocols for `--preview`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageProtocol {
+    Iterm,
+    Kitty,
+}
+
+impl ImageProtocol {
+    /// The protocol the current terminal speaks, if any.
+    pub fn detect() -> Option<Self> {
+        let var = |k: &str| std::env::var(k).unwrap_or_default();
+        if matches!(var("TERM_PROGRAM").as_str(), "iTerm.app" | "WezTerm")
+            || var("LC_TERMINAL") == "iTerm2"
+        {
+            Some(Self::Iterm)
+        } else if var("TERM") == "xterm-kitty" || std::env::var_os("KITTY_WINDOW_ID").is_some() {
+            Some(Self::Kitty)
+        } else {
+            None
+        }
+    }
+
+    /// Escape sequence drawing `bytes` (None when the protocol cannot show
+    /// this format).
+    pub fn render(self, bytes: &[u8], mime_type: &str) -> Option<String> {
+        let data = base64_encode(bytes);
+        match self {
+            Self::Iterm => Some(format!(
+                "\x1b]1337;File=inline=1;size={};preserveAspectRatio=1:{data}\x07",
+                bytes.len()
+            )),
+            Self::Kitty if mime_type == "image/png" => {
+                // Payload in chunks of at most 4096 bytes; m=1 while more follow.
+                let chunks: Vec<&str> = data
+                    .as_bytes()
+                    .chunks(4096)
+                    .map(|c| std::str::from_utf8(c).unwrap_or_default())
+                    .collect();
+                let mut out = String::new();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    let head = if i == 0 { "a=T,f=100," } else { "" };
+                    out.push_str(&format!("\x1b_G{head}m={more};{chunk}\x1b\\"));
+                }
+                Some(out)
+            }
+            Self::Kitty => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_binary_content() {
+        let result = json!({"content": [
+            {"type": "text", "text": "done"},
+            {"type": "image", "data": "iVBORw0KGgo=", "mimeType": "image/png"},
+            {"type": "resource", "resource": {"uri": "file:///tmp/../report.pdf", "blob": "JVBERg==", "mimeType": "application/pdf"}},
+            {"type": "resource", "resource": {"uri": "mem://x", "text": "plain"}}
+        ]});
+        let items = binary_items(&result);
+        assert_eq!(items.len(), 2);
+        assert_eq!(file_name(&items[0], "shot/tool"), "shot_tool-1.png");
+        assert_eq!(file_name(&items[1], "export"), "export-2-report.pdf");
+
+        let dir = std::env::temp_dir().join(format!("mcp-hack-content-{}", std::process::id()));
+        let saved = save_all(&dir, "export", &result).unwrap();
+        assert_eq!(std::fs::read(&saved[1].path).unwrap(), b"%PDF");
+        assert_eq!(saved[0].bytes, 8);
+        let mut summary = result.clone();
+        mark_saved(&mut summary, &saved);
+        assert!(
+            summary["content"][1]["data"]
+                .as_str()
+                .unwrap()
+                .starts_with("<saved to ")
+        );
+        assert!(
+            summary["content"][2]["resource"]["blob"]
+                .as_str()
+                .unwrap()
+                .ends_with("(4 bytes)>")
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn describes_images() {
+        let png = crate::utils::base64_decode(
+            "iVBORw0KGgoAAAANSUhEUgAAAAMAAAACCAIAAAASFvFNAAAAEElEQVR4nGP4z8AAQQxwFgBB0gX7h/C5SAAAAABJRU5ErkJggg==",
+        )
+        .unwrap();
+        assert_eq!(image_dimensions(&png), Some((3, 2)));
+        assert_eq!(base64_encode(&png[..4]), "iVBORw==");
+        let gif = b"GIF89a\x10\x00\x20\x00";
+        assert_eq!(image_dimensions(gif), Some((16, 32)));
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x01,
+            0xe0, 0x02, 0x80, 0x03,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some((640, 480)));
+
+        let result = json!({"content": [
+            {"type": "image", "data": base64_encode(&png), "mimeType": "image/jpeg"}
+        ]});
+        let found = images(&result);
+        assert_eq!(
+            found[0].0.label(),
+            "image/jpeg 3x2, 73 bytes, content looks like image/png"
+        );
+        let mut summary = result.clone();
+        mark_images(&mut summary, &found);
+        assert_eq!(
+            summary["content"][0]["data"],
+            "<image/jpeg 3x2, 73 bytes, content looks like image/png>"
+        );
+        assert!(
+            ImageProtocol::Kitty
+                .render(&png, "image/png")
+                .unwrap()
+                .starts_with("\x1b_Ga=T,f=100,m=0;iVBOR")
+        );
+        assert_eq!(ImageProtocol::Kitty.render(&png, "image/gif"), None);
+    }
+}
@@ -0,0 +1,247 @@
+/*!
+diff.rs - diff subcommand.
+
+Compares the tool inventories of two sources and prints added / removed /
+changed tools, with a unified diff of each changed definition (see
+crate::scan::diff). A source is either a local target command or a saved
+snapshot: an existing JSON file holding a tool array, `{"tools": [...]}`,
+a tools/list result or `raw --method tools/list --json` output.
+
+Sources:
+  Two positional SOURCEs (A, then B), or one SOURCE compared against
+  -t / MCP_TARGET (A = SOURCE, B = target).
+
+Snapshots:
+  --save PATH writes B's tools as a snapshot for a later comparison.
+
+Example:
+  mcp-hack diff "python3 server_v1.py" "python3 server_v2.py"
+  mcp-hack diff tools-2025-01.json -t "npx -y some-mcp-server"
+  mcp-hack diff tools.json -t "python3 server.py" --save tools.json --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, box_header, color, diff_line_role, emoji};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::scan::diff::{ToolDiff, compare, snapshot_tools};
+use crate::scan::list_all;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Sources to compare: target commands or snapshot JSON files
+    #[arg(value_name = "SOURCE", num_args = 1..=2)]
+    pub sources: Vec<String>,
+
+    /// Write the second source's tools to a snapshot file
+    #[arg(long, value_name = "PATH")]
+    pub save: Option<String>,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command), compared as the second source. Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// One side of the comparison (also the source of `export`).
+pub struct Side {
+    pub source: String,
+    /// "snapshot" or "target"
+    pub kind: &'static str,
+    pub tools: Vec<Value>,
+}
+
+impl Side {
+    pub fn to_json(&self) -> Value {
+        json!({"source": self.source, "kind": self.kind, "tools": self.tools.len()})
+    }
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_diff(mut args: DiffArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let mut sources: Vec<String> = args
+        .sources
+        .iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if sources.len() < 2
+        && let Some(t) = args.target.as_deref().map(str::trim)
+        && !t.is_empty()
+    {
+        sources.push(t.to_string());
+    }
+    if sources.len() != 2 {
+        return usage_error(
+            args.output.is_json(),
+            "need two sources (SOURCE SOURCE, or SOURCE with --target / MCP_TARGET)",
+        );
+    }
+
+    let started = Instant::now();
+    let timeout = Duration::from_secs(args.timeout);
+    let mut sides = Vec::new();
+    for source in &sources {
+        match load_side(source, timeout).await {
+            Ok(side) => sides.push(side),
+            Err(e) => return report_error(args.output.is_json(), e.context(source.to_string())),
+        }
+    }
+    let (a, b) = (&sides[0], &sides[1]);
+    let changes = compare(&a.tools, &b.tools);
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+
+    if let Some(path) = &args.save {
+        let snapshot = json!({
+            "source": b.source,
+            "saved_at": crate::utils::utc_timestamp(),
+            "tools": b.tools,
+        });
+        let body = serde_json::to_string_pretty(&snapshot).unwrap_or_else(|_| snapshot.to_string());
+        if let Err(e) = std::fs::write(path, body) {
+            return output_error(
+                args.output.is_json(),
+                &format!("failed to write snapshot {path}: {e}"),
+            );
+        }
+    }
+
+    if args.output.is_json() {
+        let out = json!({
+            "status": "ok",
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "a": a.to_json(),
+            "b": b.to_json(),
+            "count": changes.len(),
+            "changes": changes.iter().map(ToolDiff::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Diff • {} change(s)",
+            emoji("list", &style),
+            changes.len()
+        ),
+        Some(format!("{elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    for (label, side) in [("A", a), ("B", b)] {
+        println!(
+            "  {label} {} ({}, {} tool(s))",
+            side.source,
+            side.kind,
+            side.tools.len()
+        );
+    }
+    println!();
+    if changes.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, "Tool inventories are identical", &style)
+        );
+    }
+    for c in &changes {
+        let (mark, role) = match c.change {
+            "added" => ("+", Role::Success),
+            "removed" => ("-", Role::Error),
+            _ => ("~", Role::Warning),
+        };
+        let parts = if c.parts.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", c.parts.join(", "))
+        };
+        println!(
+            "{} {} {}{}",
+            color(role, mark, &style),
+            color(Role::Bold, &c.name, &style),
+            c.change,
+            color(Role::Dim, parts, &style)
+        );
+        for line in c.unified.lines() {
+            println!("    {}", color(diff_line_role(line), line, &style));
+        }
+    }
+    if let Some(path) = &args.save {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(Role::Dim, format!("Saved snapshot of B to {path}"), &style)
+        );
+    }
+    Ok(())
+}
+
+/* ---- Helpers ---- */
+
+/// Read a snapshot file, or enumerate a target's tools.
+pub async fn load_side(source: &str, timeout: Duration) -> Result<Side> {
+    if Path::new(source).is_file() {
+        let raw = std::fs::read_to_string(source).context("failed to read snapshot")?;
+        let doc: Value = serde_json::from_str(&raw).context("snapshot is not valid JSON")?;
+        return Ok(Side {
+            source: source.to_string(),
+            kind: "snapshot",
+            tools: snapshot_tools(&doc)?,
+        });
+    }
+    let spec = mcp::parse_target(source).context("failed to parse target")?;
+    if !spec.is_local() {
+        anyhow::bail!("remote targets not implemented yet");
+    }
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    // Raw frames: definitions are compared as sent (see mcp::raw).
+    let mut session = mcp::raw::RawSession::spawn(&spec, timeout)?;
+    let init = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    let init = init.get("result").cloned().unwrap_or(init);
+    let tools = if init.pointer("/capabilities/tools").is_some() {
+        list_all(&mut session, "tools/list", "tools", usize::MAX).await?
+    } else {
+        Vec::new()
+    };
+    session.shutdown().await;
+    Ok(Side {
+        source: source.to_string(),
+        kind: "target",
+        tools,
+    })
+}
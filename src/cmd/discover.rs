@@ -0,0 +1,227 @@
+/*!
+discover.rs - discover subcommand.
+
+Inventories the MCP servers configured on this machine before any audit:
+looks through the well-known config files of Claude Desktop / Code,
+Cursor, VS Code, Cline, Windsurf, Zed and Gemini CLI plus project-local
+files in the current directory (see crate::mcp::discover), and reports per
+server its command or URL, the env variables it sets and the env / header
+entries that hold a literal credential.
+
+Extra PATHs (files) are read as well; --home points the user-wide lookup
+at another home directory (e.g. a mounted disk image). Only files that
+exist are reported. Server entries can be used directly as targets.
+
+Example:
+  mcp-hack discover
+  mcp-hack discover ./team/mcp.json --json
+  mcp-hack discover --home /mnt/image/home/alice
+*/
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+use crate::cmd::exec::output_error;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp::client_config::{self, ConfiguredServer};
+use crate::mcp::discover::{self, Location};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct DiscoverArgs {
+    /// Additional config files to read
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<String>,
+
+    /// Home directory to search (default: $HOME)
+    #[arg(long, value_name = "DIR")]
+    pub home: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// One config file found.
+struct FoundFile {
+    client: &'static str,
+    path: String,
+    servers: Vec<ConfiguredServer>,
+    error: Option<String>,
+}
+
+fn read(loc: &Location) -> Option<FoundFile> {
+    let raw = std::fs::read_to_string(&loc.path).ok()?;
+    let mut found = FoundFile {
+        client: loc.client,
+        path: loc.path.display().to_string(),
+        servers: Vec::new(),
+        error: None,
+    };
+    match client_config::parse_document(&raw).and_then(|doc| discover::servers(&doc)) {
+        Ok(servers) => found.servers = servers,
+        Err(e) => found.error = Some(format!("{e:#}")),
+    }
+    Some(found)
+}
+
+fn transport(s: &ConfiguredServer) -> &'static str {
+    match crate::mcp::parse_target(&s.target) {
+        Ok(spec) if spec.is_local() => "local",
+        Ok(_) => "remote",
+        Err(_) => "invalid",
+    }
+}
+
+fn server_json(s: &ConfiguredServer) -> Value {
+    json!({
+        "name": s.name,
+        "target": s.target,
+        "transport": transport(s),
+        "env": s.env_keys,
+        "inline_secrets": s.inline_secrets,
+    })
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_discover(args: DiscoverArgs) -> Result<()> {
+    let home = args
+        .home
+        .clone()
+        .or_else(|| std::env::var("HOME").ok())
+        .or_else(|| std::env::var("USERPROFILE").ok())
+        .map_or_else(|| PathBuf::from("."), PathBuf::from);
+    let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    if let Some(p) = args
+        .paths
+        .iter()
+        .find(|p| !std::path::Path::new(p).is_file())
+    {
+        return output_error(args.output.is_json(), &format!("{p}: no such file"));
+    }
+    let mut locations = discover::locations(&home, &cwd);
+    locations.extend(args.paths.iter().map(|p| Location {
+        client: "given",
+        path: PathBuf::from(p),
+    }));
+    let files: Vec<FoundFile> = locations.iter().filter_map(read).collect();
+    let servers = files.iter().map(|f| f.servers.len()).sum::<usize>();
+    let secrets = files
+        .iter()
+        .flat_map(|f| &f.servers)
+        .filter(|s| !s.inline_secrets.is_empty())
+        .count();
+
+    if args.output.is_json() {
+        let out = json!({
+            "status": "ok",
+            "home": home.display().to_string(),
+            "summary": {"files": files.len(), "servers": servers, "with_inline_secrets": secrets},
+            "files": files.iter().map(|f| json!({
+                "client": f.client,
+                "path": f.path,
+                "error": f.error,
+                "servers": f.servers.iter().map(server_json).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Discovered MCP servers ({servers})",
+            emoji("list", &style)
+        ),
+        Some(format!(
+            "{} config file(s) • {secrets} with inline secrets",
+            files.len()
+        )),
+        &style,
+    );
+    println!("{header}");
+    if files.is_empty() {
+        println!(
+            "{}",
+            color(Role::Dim, "No MCP client configs found.", &style)
+        );
+        return Ok(());
+    }
+    for f in &files {
+        println!();
+        println!(
+            "{} {}",
+            color(Role::Accent, f.client, &style),
+            color(Role::Dim, &f.path, &style)
+        );
+        if let Some(e) = &f.error {
+            println!("  {}", color(Role::Error, e, &style));
+            continue;
+        }
+        if f.servers.is_empty() {
+            println!("  {}", color(Role::Dim, "no servers configured", &style));
+            continue;
+        }
+        let rows: Vec<Vec<String>> = f
+            .servers
+            .iter()
+            .map(|s| {
+                let env = s
+                    .env_keys
+                    .iter()
+                    .map(|k| {
+                        if s.inline_secrets.contains(k) {
+                            format!("{k}(!)")
+                        } else {
+                            k.clone()
+                        }
+                    })
+                    .chain(
+                        s.inline_secrets
+                            .iter()
+                            .filter(|k| !s.env_keys.contains(k))
+                            .map(|k| format!("header {k}(!)")),
+                    )
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                vec![
+                    s.name.clone(),
+                    transport(s).to_string(),
+                    s.target.clone(),
+                    env,
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["SERVER", "TRANSPORT", "TARGET", "ENV"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+    }
+    if secrets > 0 {
+        println!();
+        println!(
+            "{}",
+            color(
+                Role::Warning,
+                "(!) literal credential in the config file (not a ${VAR} reference)",
+                &style
+            )
+        );
+    }
+    Ok(())
+}
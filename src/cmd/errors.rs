@@ -0,0 +1,309 @@
+/*!
+errors.rs - error presentation.
+
+Every command error is shown by `present`: `output_error` calls it for the
+errors commands report themselves, and main.rs calls it for errors that
+propagate with `?`. Common failure signatures (program not on PATH,
+missing runtime, initialize timeout, server exiting early, unsupported
+method) get actionable hints and, where one exists, a docs link.
+
+When the command spawned a local server, the tail of what it wrote to
+stderr (mcp::spawn, --stderr capture) follows the hints: for a server that
+failed to start that is usually the real error (missing module, bad
+config, usage text).
+
+Human output: red error box, one line per hint, then the server's stderr.
+JSON output: {"status":"error","error":MSG,"hints":[{"hint","docs"}],
+"stderr":[LINE]} ("stderr" only when the server wrote any).
+
+The target of the current command (set by the dispatcher) lets hints name
+the program that failed to start.
+*/
+
+use serde_json::json;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::mcp::{self, TargetSpec};
+use crate::scan::shadow::levenshtein;
+
+const LIFECYCLE_DOCS: &str =
+    "https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle";
+const CAPABILITY_DOCS: &str = "https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#capability-negotiation";
+
+static TARGET: OnceLock<String> = OnceLock::new();
+
+/// Remember the command's target for hints (first call wins).
+pub fn set_target(target: &str) {
+    let _ = TARGET.set(target.to_string());
+}
+
+/// Marker for errors that were already presented (`output_error`), so the
+/// dispatcher does not print them a second time; `status` is the exit
+/// status they end the command with (crate::cmd::exit).
+#[derive(Debug)]
+pub struct Reported {
+    pub message: String,
+    pub status: i32,
+}
+
+impl std::fmt::Display for Reported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Reported {}
+
+/// One actionable suggestion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub text: String,
+    pub docs: Option<&'static str>,
+}
+
+impl Hint {
+    fn new(text: impl Into<String>) -> Self {
+        Hint {
+            text: text.into(),
+            docs: None,
+        }
+    }
+
+    fn docs(mut self, url: &'static str) -> Self {
+        self.docs = Some(url);
+        self
+    }
+}
+
+/// How to install the runtime behind a well-known launcher.
+fn install_hint(program: &str) -> Option<Hint> {
+    let name = Path::new(program)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(program);
+    let (what, url) = match name {
+        "npx" | "npm" | "node" => ("Node.js (provides node / npm / npx)", "https://nodejs.org/"),
+        "uvx" | "uv" => ("uv (provides uvx)", "https://docs.astral.sh/uv/"),
+        "python" | "python3" | "pip" | "pip3" => ("Python 3", "https://www.python.org/downloads/"),
+        "docker" => ("Docker", "https://docs.docker.com/get-docker/"),
+        "deno" => ("Deno", "https://deno.land/"),
+        "bun" | "bunx" => ("Bun", "https://bun.sh/"),
+        _ => return None,
+    };
+    Some(Hint::new(format!("install {what}")).docs(url))
+}
+
+/// Executables on PATH within edit distance 2 of `program`, closest first.
+fn similar_programs(program: &str, path_var: &str) -> Vec<String> {
+    let mut found: Vec<(usize, String)> = Vec::new();
+    for dir in std::env::split_paths(path_var) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let d = levenshtein(program, &name);
+            if d > 0 && d <= 2 && !found.iter().any(|(_, n)| *n == name) {
+                found.push((d, name));
+            }
+        }
+    }
+    found.sort();
+    found.into_iter().take(3).map(|(_, n)| n).collect()
+}
+
+fn local_program(target: Option<&str>) -> Option<String> {
+    match mcp::parse_target(target?).ok()? {
+        TargetSpec::LocalCommand { program, .. } => Some(program),
+        TargetSpec::Socket { .. } | TargetSpec::RemoteUrl { .. } => None,
+    }
+}
+
+/// Hints for an error message (full anyhow chain) given the command target.
+pub fn hints(message: &str, target: Option<&str>) -> Vec<Hint> {
+    let msg = message.to_ascii_lowercase();
+    let program = local_program(target);
+    let mut out = Vec::new();
+
+    if msg.contains("no target specified") {
+        out.push(Hint::new(
+            "pass -t \"<server command>\", set MCP_TARGET, or reuse one with -t last / -t @profile",
+        ));
+    }
+    if msg.contains("not implemented yet") && msg.contains("remote") {
+        out.push(Hint::new(
+            "remote transports are not supported yet; run the server locally (-t \"<command>\")",
+        ));
+    }
+    if let Some(program) = &program {
+        if msg.contains("os error 2") || msg.contains("no such file or directory") {
+            if program.contains('/') {
+                out.push(Hint::new(format!("'{program}' does not exist")));
+            } else {
+                out.push(Hint::new(format!("'{program}' was not found on PATH")));
+                let similar = std::env::var("PATH")
+                    .map(|p| similar_programs(program, &p))
+                    .unwrap_or_default();
+                if !similar.is_empty() {
+                    out.push(Hint::new(format!(
+                        "did you mean '{}'?",
+                        similar.join("' / '")
+                    )));
+                }
+            }
+            out.extend(install_hint(program));
+        }
+        if msg.contains("os error 13") || msg.contains("permission denied") {
+            out.push(Hint::new(format!(
+                "'{program}' is not executable; chmod +x it or run it through its interpreter (e.g. python3 server.py)"
+            )));
+        }
+    }
+    if msg.contains("timed out") {
+        if msg.contains("initialize") {
+            out.push(
+                Hint::new(
+                    "the server did not answer initialize; check that it speaks MCP over stdio (not HTTP) and starts without prompts",
+                )
+                .docs(LIFECYCLE_DOCS),
+            );
+            if matches!(program.as_deref(), Some("npx" | "uvx" | "bunx" | "docker")) {
+                out.push(Hint::new(
+                    "first runs download the package; retry, or raise --timeout / --connect-timeout",
+                ));
+            }
+        } else {
+            out.push(Hint::new("the server is slow to answer; raise --timeout"));
+        }
+    }
+    if msg.contains("closed stdout") || msg.contains("connection closed") {
+        out.push(Hint::new(
+            "the server exited early; run the target command directly to see its error output",
+        ));
+        if msg.contains("initialize") {
+            out.push(Hint::new(
+                "if the server prints a banner or logs on stdout, move them to stderr: stdout carries only protocol messages",
+            ));
+        }
+    }
+    if msg.contains("-32601") || msg.contains("method not found") {
+        let feature = ["resources", "prompts", "completion", "logging"]
+            .into_iter()
+            .find(|f| msg.contains(f));
+        let text = match feature {
+            Some(f) => format!(
+                "the server may not support {f}; check the capabilities it advertises (mcp-hack fingerprint)"
+            ),
+            None => "the server does not implement this method".to_string(),
+        };
+        out.push(Hint::new(text).docs(CAPABILITY_DOCS));
+    }
+    if msg.contains("tool '") && msg.contains("not found") {
+        out.push(Hint::new(
+            "list the available tools with `mcp-hack list tools`",
+        ));
+    }
+    out
+}
+
+/// Print an error in the requested format, with hints.
+pub fn present(json: bool, message: &str) {
+    let hints = hints(message, TARGET.get().map(String::as_str));
+    let stderr = mcp::spawn::stderr_tail();
+    if json {
+        let mut err = json!({
+            "status": "error",
+            "error": message,
+            "hints": hints.iter().map(|h| json!({"hint": h.text, "docs": h.docs})).collect::<Vec<_>>(),
+        });
+        if !stderr.is_empty() {
+            err["stderr"] = json!(stderr);
+        }
+        println!("{}", crate::cmd::output::pretty(&err));
+        return;
+    }
+    // Fancy red error box for human output
+    let style = StyleOptions::detect();
+    let title = format!("{} Error", emoji("error", &style));
+    let subtitle = color(Role::Error, message, &style);
+    println!("{}", box_header(title, Some(subtitle), &style));
+    if hints.is_empty() {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                "Re-run with --json for machine-readable output or -v for more detail.",
+                &style
+            )
+        );
+    }
+    for h in &hints {
+        println!("{} {}", emoji("info", &style), h.text);
+        if let Some(url) = h.docs {
+            println!("  {}", color(Role::Dim, url, &style));
+        }
+    }
+    if !stderr.is_empty() {
+        println!("Server stderr:");
+        for line in &stderr {
+            println!("  {}", color(Role::Dim, line, &style));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(message: &str, target: Option<&str>) -> Vec<String> {
+        hints(message, target).into_iter().map(|h| h.text).collect()
+    }
+
+    #[test]
+    fn maps_failure_signatures() {
+        let h = texts("No such file or directory (os error 2)", Some("npx -y pkg"));
+        assert_eq!(h[0], "'npx' was not found on PATH");
+        assert!(
+            hints("os error 2", Some("npx -y pkg"))
+                .iter()
+                .any(|h| h.docs == Some("https://nodejs.org/"))
+        );
+
+        let h = texts(
+            "initialize handshake failed: timed out waiting for response to id 1",
+            Some("uvx mcp-server-fetch"),
+        );
+        assert_eq!(h.len(), 2);
+        assert!(h[1].contains("raise --timeout"));
+
+        let h = texts(
+            "Failed to spawn MCP process: python3: connection closed: initialize response",
+            Some("python3 server.py"),
+        );
+        assert_eq!(h.len(), 2);
+        assert!(h[1].contains("banner"));
+
+        let h = texts(
+            "resources/list returned error: -32601 Method not found",
+            None,
+        );
+        assert!(h[0].contains("may not support resources"));
+
+        assert!(texts("unrelated failure", Some("python3 s.py")).is_empty());
+    }
+
+    #[test]
+    fn suggests_similar_programs() {
+        let dir = std::env::temp_dir().join(format!("mcp-hack-errors-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("python3"), "").unwrap();
+        let similar = similar_programs("pythn3", &dir.to_string_lossy());
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(similar, vec!["python3"]);
+    }
+}
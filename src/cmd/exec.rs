@@ -1,16 +1,93 @@
 /*!
 exec.rs - exec subcommand.
 
-Invokes a single MCP tool from a local process target.
+Invokes a single MCP tool from a local process target, or a batch of
+tool calls over one session.
 
 Supports:
   - Local process target (spawn/spawn+invoke)
-  - Subject: 'tool' (preferred) / 'tools' (deprecated alias)
-  - --param KEY=VALUE (repeat)
+  - Subject: 'tool' (preferred) / 'tools' (deprecated alias) / 'batch'
+  - --param KEY=VALUE (repeat); dotted / indexed keys build nested
+    arguments: --param filter.status=open --param filter.tags[0]=bug
   - --param-file file.(json|yaml) (merged; CLI overrides)
-  - --interactive (prompt missing required params)
+  - --args-json '{...}' / --args-json - (stdin): the arguments object as
+    JSON, sent as written; --param / --param-file values override its keys
+  - --expand-env: `${VAR}` / `${VAR:-default}` in --param and param file
+    values is replaced from the environment, so secrets stay out of argv and
+    shell history (quote the argument: --param 'token=${API_TOKEN}')
+  - --interactive: prompt for the parameters not given, required ones
+    first, then optional ones (Enter skips); defaults and descriptions are
+    shown, enum values are picked from a numbered list and answers are
+    type-checked on entry
   - Primitive coercion (integer/number/boolean/array)
   - Human or --json output; --raw includes full result object
+  - --retries / --retry-backoff for transient failures (see crate::cmd::retry)
+  - notifications/progress from the server are shown on stderr while the
+    call runs (a live bar on a terminal; see crate::mcp::progress)
+  - Other notifications the server sends during the session (log messages,
+    resource / tool / prompt list changes) are listed after the result and
+    returned as `notifications` in JSON (see crate::mcp::notifications)
+  - Ctrl-C during the call sends notifications/cancelled for it, shuts the
+    server down (killed if it does not exit) and exits with status 130
+    (see crate::mcp::interrupt)
+  - --dry-run: resolve the schema and coerce the arguments, then print the
+    tools/call request that would be sent plus validation problems (missing
+    required, type / enum mismatch) without sending it; exit status 2 when
+    the arguments do not validate
+  - --generate: print a ready-to-edit arguments object synthesized from the
+    input schema (defaults, first enum values, typed placeholders), with any
+    --args-json / --param values filled in, without calling the tool
+  - Arguments are checked against the input schema before the call (types,
+    enum, minimum / maximum, minLength / maxLength, format, ...); a mismatch
+    fails the call unless --lenient, which only warns on stderr; --strict
+    also fails on parameters the schema does not declare (typos such as
+    `--param urll=`), even with --lenient
+  - --count N [--interval MS]: call the tool N times over one session and
+    report each call's latency, min / median / mean / max, and whether the
+    results were identical (distinct results are numbered as variants); a
+    consistency / flakiness check; when a call failed the exit status is
+    that of the first failure (5 for an isError result, see cmd::exit)
+  - --save-dir DIR: decode image / audio / blob content of the result into
+    files in DIR and list their paths instead of the base64 text (see
+    crate::cmd::content)
+  - Image content is summarized by MIME type, dimensions and size instead
+    of base64; --preview draws it inline on iTerm2 / WezTerm / kitty
+  - A tool declaring an outputSchema has the returned structuredContent
+    checked against it (crate::mcp::schema); mismatches are reported in
+    `output_validation` / after the result, without changing the exit status
+  - --expect-contains STR / --expect-regex RE / --expect-jsonpath EXPR
+    assert on the result (exit status 6 when one fails; see
+    crate::cmd::expect)
+  - --extract PATH prints only the value(s) at a JSONPath into the result
+    (`$.content[0].text`, `$.structuredContent.items[*].id`; see
+    crate::utils::jsonpath), one per line with strings unquoted, or as a
+    JSON array with --json, for piping into other commands; a path that
+    matches nothing is an error
+  - A result with isError: true is shown as a tool error (JSON `status`
+    "tool_error", `is_error` true) and exits with status 5 after printing;
+    --no-fail-on-error keeps the exit status 0 (also for --count / batch)
+  - Destructive tools (annotations.destructiveHint, or a name matching a
+    config.toml `danger_patterns` glob) are only called after a y/N prompt
+    on the terminal or with --yes; without a terminal they are refused
+
+Batch:
+  `exec batch --file calls.json` (JSON or YAML) runs a list of calls in
+  order over a single session and reports every result:
+    [{"tool": "read_file", "arguments": {"path": "/etc/hosts"}},
+     {"tool": "list_dir", "arguments": {"path": "/tmp"}}]
+  Arguments are sent as written (no schema coercion). A failed call does
+  not stop the batch; when a call failed the exit status is that of the
+  first failure (4 unknown tool, 5 isError result unless --no-fail-on-error,
+  see cmd::exit), after printing the output.
+  --retries only repeats a batch whose session could not be set up.
+
+Example:
+  mcp-hack exec tool read_file -t "python3 server.py" --param path=/etc/hosts
+  mcp-hack exec batch --file calls.json -t "python3 server.py" --json
+  mcp-hack exec tool delete_file -t "python3 server.py" --param path=/tmp/x --dry-run
+  mcp-hack exec tool fetch -t "python3 server.py" --generate > args.json
+  mcp-hack exec tool read_file -t "python3 server.py" --param path=/etc/hosts --extract '$.content[0].text'
+  mcp-hack exec tool search -t "python3 server.py" --param q=x --count 10 --interval 200
 
 Remote execution is not implemented yet.
 */
@@ -20,23 +97,53 @@ use clap::Args;
 use std::io::{self, Write};
 use std::time::Instant;
 
-use super::subject::Subject;
+use crate::cmd::content;
+use crate::cmd::errors;
+use crate::cmd::exit;
+use crate::cmd::expect::{self, ExpectArgs};
 use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, pretty, print_json, print_line};
+use crate::cmd::retry::RetryArgs;
 use crate::cmd::shared::{
-    build_arguments_from_schema, find_tool_case_insensitive, summarize_call_result,
+    coerce_arguments, coerce_value, deep_merge, example_arguments, fetch_tools_for,
+    find_tool_case_insensitive, has_tool, merge_arguments, param_root, summarize_call_result,
+    undeclared_arguments, validate_arguments,
 };
 use crate::mcp;
+use crate::mcp::interrupt::Watch;
+use crate::mcp::progress::ProgressClient;
+use crate::mcp::session::McpSession;
+use crate::utils::jsonpath::Path;
+use crate::utils::logging::{LogLevel, current_log_level};
 
 /* ---- Argument Struct ---- */
 
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+/// Subjects exec accepts.
+#[derive(clap::ValueEnum, Clone, Debug, Eq, PartialEq)]
+pub enum ExecSubject {
+    /// A single tool
+    Tool,
+    /// Deprecated alias of `tool`
+    Tools,
+    /// Several calls from --file over one session
+    Batch,
+}
+
 #[derive(Args, Debug)]
 pub struct ExecArgs {
-    /// Subject to execute ('tool' preferred; 'tools' is a deprecated alias)
-    pub subject: Subject,
+    /// Subject to execute ('tool' preferred; 'tools' is a deprecated alias; 'batch' with --file)
+    pub subject: ExecSubject,
 
     /// Tool name to invoke
-    #[arg(value_name = "TOOL")]
-    pub tool: String,
+    #[arg(value_name = "TOOL", required_if_eq_any = [("subject", "tool"), ("subject", "tools")])]
+    pub tool: Option<String>,
+
+    /// Calls to run with `exec batch` (JSON or YAML list of {tool, arguments})
+    #[arg(long, value_name = "PATH", required_if_eq("subject", "batch"))]
+    pub file: Option<String>,
 
     /// Provide parameter (KEY=VALUE), repeatable
     #[arg(long = "param", value_name = "KEY=VALUE")]
@@ -46,7 +153,15 @@ pub struct ExecArgs {
     #[arg(long = "param-file", value_name = "PATH")]
     pub param_file: Option<String>,
 
-    /// Prompt interactively for missing required parameters
+    /// Expand ${VAR} in --param / --param-file values from the environment
+    #[arg(long = "expand-env")]
+    pub expand_env: bool,
+
+    /// Arguments as a JSON object ('-' reads it from stdin); --param overrides keys
+    #[arg(long = "args-json", value_name = "JSON")]
+    pub args_json: Option<String>,
+
+    /// Prompt for parameters not given (required, then optional ones)
     #[arg(long)]
     pub interactive: bool,
 
@@ -54,22 +169,78 @@ pub struct ExecArgs {
     #[arg(short = 't', long)]
     pub target: Option<String>,
 
-    /// Output JSON
-    #[arg(long)]
-    pub json: bool,
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
 
     /// Include raw MCP call result (instead of summary) in JSON / human output
     #[arg(long)]
     pub raw: bool,
+
+    /// Decode image / audio / blob result content into files in this directory
+    #[arg(long = "save-dir", value_name = "PATH")]
+    pub save_dir: Option<String>,
+
+    /// Draw image results inline (iTerm2 / WezTerm / kitty terminals)
+    #[arg(long)]
+    pub preview: bool,
+
+    /// Print the tools/call request (and validation results) without sending it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print only the value(s) at this JSONPath into the result, e.g. '$.content[0].text'
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["raw", "generate", "dry_run"])]
+    pub extract: Option<String>,
+
+    /// Print an example arguments object from the input schema (with --param /
+    /// --args-json values filled in) without calling the tool
+    #[arg(long, conflicts_with = "dry_run")]
+    pub generate: bool,
+
+    /// Call the tool N times over one session and compare the results
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub count: Option<u32>,
+
+    /// Delay between calls in milliseconds (--count, exec batch)
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    pub interval: u64,
+
+    /// Warn about arguments that do not match the input schema instead of failing
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// Fail on parameters the tool's input schema does not declare
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Call destructive tools without asking for confirmation
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    /// Exit 0 even when the tool answers with an error result (isError)
+    #[arg(long = "no-fail-on-error")]
+    pub no_fail_on_error: bool,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub expect: ExpectArgs,
 }
 
 /* ---- Public Entry Point ---- */
 
-pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
+pub async fn execute_exec(mut args: ExecArgs) -> Result<()> {
+    mcp::progress::enable();
+    mcp::notifications::enable();
+    if args.subject == ExecSubject::Batch {
+        return execute_batch(args).await;
+    }
     // Subject check & deprecation handling
-    if matches!(args.subject, Subject::Tools) {
+    if args.subject == ExecSubject::Tools {
         // Backward compatibility: allow plural with a warning
-        if args.json {
+        if args.output.is_json() {
             eprintln!(r#"{{"warning":"subject 'tools' is deprecated; use 'tool'"}}"#);
         } else {
             let style = StyleOptions::detect();
@@ -83,14 +254,12 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
                 )
             );
         }
-    } else if !matches!(args.subject, Subject::Tool) {
-        return output_error(args.json, "exec currently supports only subject 'tool'");
     }
 
     // Tool name validation
-    let tool_name_owned = args.tool.trim().to_string();
+    let tool_name_owned = args.tool.as_deref().unwrap_or_default().trim().to_string();
     if tool_name_owned.is_empty() {
-        return output_error(args.json, "tool name cannot be empty");
+        return usage_error(args.output.is_json(), "tool name cannot be empty");
     }
 
     // Determine target (CLI > env)
@@ -103,19 +272,23 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
     let target_raw = match &args.target {
         Some(t) if !t.trim().is_empty() => t.trim().to_string(),
         _ => {
-            return output_error(
-                args.json,
+            return usage_error(
+                args.output.is_json(),
                 "no target specified (use --target or MCP_TARGET)",
             );
         }
     };
 
     // Parse target spec
-    let spec = mcp::parse_target(&target_raw)
-        .with_context(|| format!("Failed to parse target: '{target_raw}'"))?;
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
 
     if !spec.is_local() {
-        return output_error(args.json, "remote exec not implemented yet");
+        return usage_error(args.output.is_json(), "remote exec not implemented yet");
     }
 
     // Collect parameters from CLI
@@ -124,12 +297,15 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
         if let Some((k, v)) = kv.split_once('=') {
             let key = k.trim();
             if key.is_empty() {
-                return output_error(args.json, &format!("invalid --param (empty key): {kv}"));
+                return usage_error(
+                    args.output.is_json(),
+                    &format!("invalid --param (empty key): {kv}"),
+                );
             }
             provided.insert(key.to_string(), v.trim().to_string());
         } else {
-            return output_error(
-                args.json,
+            return usage_error(
+                args.output.is_json(),
                 &format!("invalid --param (expected KEY=VALUE): {kv}"),
             );
         }
@@ -139,33 +315,148 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
     if let Some(ref pf) = args.param_file
         && let Err(e) = load_param_file_into_map(pf, &mut provided)
     {
-        return output_error(args.json, &e.to_string());
+        return report_error(args.output.is_json(), e);
     }
 
-    // Build runtime + spawn + list tools + interactive prompts + call tool
+    if args.expand_env {
+        for (key, value) in provided.iter_mut() {
+            match crate::utils::expand_env(value) {
+                Ok(v) => *value = v,
+                Err(e) => {
+                    return usage_error(args.output.is_json(), &format!("--param {key}: {e}"));
+                }
+            }
+        }
+    }
+
+    let base = match args
+        .args_json
+        .as_deref()
+        .map(|raw| read_args_json(raw, args.interactive))
+    {
+        Some(Ok(map)) => Some(map),
+        Some(Err(e)) => return report_error(args.output.is_json(), e),
+        None => None,
+    };
+
+    let expectations = match args.expect.compile() {
+        Ok(e) => e,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let extract = match args.extract.as_deref().map(Path::parse).transpose() {
+        Ok(p) => p,
+        Err(e) => return usage_error(args.output.is_json(), &format!("invalid --extract: {e:#}")),
+    };
+
+    if args.generate {
+        return execute_generate(&args, &spec, &tool_name_owned, base, provided).await;
+    }
+    if args.dry_run {
+        return execute_dry_run(&args, &spec, &tool_name_owned, base.as_ref(), provided).await;
+    }
+    if let Some(count) = args.count {
+        return execute_repeat(&args, &spec, &tool_name_owned, base, provided, count).await;
+    }
+
+    // Spawn + list tools + interactive prompts + call tool
     let started = Instant::now();
-    let result = invoke_tool(
-        &spec,
-        &tool_name_owned,
-        provided,
-        args.interactive,
-        args.json,
-    );
+    // Ask once, even when the call is retried.
+    let confirmed = std::cell::Cell::new(args.yes);
+    let output_schema = std::cell::RefCell::new(None);
+    let pre_call = |tool: &serde_json::Value,
+                    arguments: &serde_json::Map<String, serde_json::Value>|
+     -> Result<()> {
+        output_schema.replace(mcp::schema::output_schema(tool).cloned());
+        check_arguments(tool, arguments, args.strict, args.lenient)?;
+        if !confirmed.get()
+            && let Some(reason) = danger_reason(tool)
+        {
+            confirm_call(&tool_name_owned, &reason)?;
+        }
+        confirmed.set(true);
+        Ok(())
+    };
+    let (result, attempts) = args
+        .retry
+        .run(|| {
+            invoke_tool(
+                &spec,
+                &tool_name_owned,
+                provided.clone(),
+                base.as_ref(),
+                args.interactive,
+                args.output.is_json(),
+                Some(&pre_call),
+            )
+        })
+        .await;
 
     let elapsed_ms = started.elapsed().as_millis();
+    let hook_findings = mcp::hook::take_findings();
+    let notifications = mcp::notifications::take();
 
     match result {
         Ok((final_args_map, call_result)) => {
-            if args.json {
+            let output_problems = output_schema
+                .take()
+                .map(|schema| output_mismatches(&schema, &call_result));
+            let result_value = serde_json::to_value(&call_result).unwrap_or_default();
+            let saved = match args.save_dir.as_deref().map(|dir| {
+                content::save_all(std::path::Path::new(dir), &tool_name_owned, &result_value)
+            }) {
+                Some(Ok(saved)) => saved,
+                Some(Err(e)) => {
+                    return output_error(
+                        args.output.is_json(),
+                        &format!("failed to save result content: {e:#}"),
+                    );
+                }
+                None => Vec::new(),
+            };
+            let images = content::images(&result_value);
+            let tool_error = call_result.is_error == Some(true);
+            let outcomes = expect::evaluate(&expectations, &result_value);
+            let mut summary = summarize_call_result(&call_result);
+            content::mark_saved(&mut summary, &saved);
+            content::mark_images(&mut summary, &images);
+            if let Some(path) = &extract {
+                let values = path.select(&result_value);
+                if values.is_empty() {
+                    return output_error(
+                        args.output.is_json(),
+                        &format!(
+                            "--extract '{}' matched nothing in the result",
+                            args.extract.as_deref().unwrap_or_default().trim()
+                        ),
+                    );
+                }
+                print_extracted(&values, args.output.is_json());
+            } else if args.output.is_json() {
                 // JSON output
                 let mut base = serde_json::json!({
-                    "status":"ok",
+                    "status": if tool_error { "tool_error" } else { "ok" },
+                    "is_error": tool_error,
                     "subject": "tool",
                     "tool": tool_name_owned,
                     "target": target_raw,
                     "elapsed_ms": elapsed_ms,
+                    "timings": crate::mcp::timing::snapshot().to_json(),
                     "arguments": final_args_map,
                 });
+                attempts.annotate(&mut base);
+                if args.save_dir.is_some() {
+                    base["saved"] = saved.iter().map(content::Saved::to_json).collect();
+                }
+                if !images.is_empty() {
+                    base["images"] = images.iter().map(|(info, _)| info.to_json()).collect();
+                }
+                if let Some(problems) = &output_problems {
+                    base["output_validation"] =
+                        serde_json::json!({"valid": problems.is_empty(), "errors": problems});
+                }
+                if !outcomes.is_empty() {
+                    base["expectations"] = outcomes.iter().map(expect::Outcome::to_json).collect();
+                }
                 if args.raw {
                     if let serde_json::Value::Object(ref mut map) = base {
                         map.insert(
@@ -175,26 +466,36 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
                         );
                     }
                 } else if let serde_json::Value::Object(ref mut map) = base {
-                    map.insert(
-                        "result_summary".to_string(),
-                        summarize_call_result(&call_result),
-                    );
+                    map.insert("result_summary".to_string(), summary);
                 }
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&base).unwrap_or_else(|_| base.to_string())
-                );
+                base["notifications"] = notifications
+                    .iter()
+                    .map(mcp::notifications::Notification::to_json)
+                    .collect();
+                if mcp::hook::installed() {
+                    base["findings"] = hook_findings.iter().map(|f| f.to_json()).collect();
+                }
+                print_json(&base);
             } else {
                 // Fancy human-readable output
                 let style = StyleOptions::detect();
 
                 // Header box
-                let header = box_header(
+                let title = if tool_error {
+                    format!(
+                        "{} Tool Error ({})",
+                        emoji("error", &style),
+                        tool_name_owned
+                    )
+                } else {
                     format!(
                         "{} Exec Success ({})",
                         emoji("success", &style),
                         tool_name_owned
-                    ),
+                    )
+                };
+                let header = box_header(
+                    title,
                     Some(format!("target={target_raw} • {elapsed_ms} ms")),
                     &style,
                 );
@@ -247,11 +548,10 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
                     );
                     println!(
                         "{}",
-                        serde_json::to_string_pretty(
+                        pretty(
                             &serde_json::to_value(&call_result)
                                 .unwrap_or_else(|_| serde_json::json!({"error":"serialize"}))
                         )
-                        .unwrap_or_else(|_| "<serialize error>".into())
                     );
                 } else {
                     println!(
@@ -259,12 +559,7 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
                         emoji("info", &style),
                         color(Role::Accent, "Result Summary:", &style)
                     );
-                    let summary = summarize_call_result(&call_result);
-                    println!(
-                        "{}",
-                        serde_json::to_string_pretty(&summary)
-                            .unwrap_or_else(|_| summary.to_string())
-                    );
+                    println!("{}", pretty(&summary));
                     println!(
                         "\n{} {}",
                         emoji("info", &style),
@@ -275,112 +570,1259 @@ pub fn execute_exec(mut args: ExecArgs) -> Result<()> {
                         )
                     );
                 }
+
+                if args.preview && !images.is_empty() {
+                    print_image_previews(&images, &style);
+                }
+
+                if !saved.is_empty() {
+                    println!("{}", color(Role::Accent, "Saved:", &style));
+                    for s in &saved {
+                        println!(
+                            "  {} {}",
+                            s.path.display(),
+                            color(
+                                Role::Dim,
+                                format!(
+                                    "({}, {}, {} bytes)",
+                                    s.kind,
+                                    s.mime_type.as_deref().unwrap_or("unknown type"),
+                                    s.bytes
+                                ),
+                                &style
+                            )
+                        );
+                    }
+                }
+
+                match &output_problems {
+                    Some(problems) if problems.is_empty() => println!(
+                        "{} {}",
+                        emoji("success", &style),
+                        color(
+                            Role::Success,
+                            "structuredContent matches the outputSchema",
+                            &style
+                        )
+                    ),
+                    Some(problems) => {
+                        for p in problems {
+                            println!(
+                                "{} {}",
+                                emoji("warn", &style),
+                                color(Role::Warning, format!("outputSchema: {p}"), &style)
+                            );
+                        }
+                    }
+                    None => {}
+                }
+
+                if !outcomes.is_empty() {
+                    println!("{}", color(Role::Accent, "Expectations:", &style));
+                    for o in &outcomes {
+                        match &o.failure {
+                            None => println!(
+                                "  {} {}",
+                                emoji("success", &style),
+                                color(Role::Success, &o.label, &style)
+                            ),
+                            Some(why) => println!(
+                                "  {} {} {}",
+                                emoji("error", &style),
+                                color(Role::Error, &o.label, &style),
+                                color(Role::Dim, why, &style)
+                            ),
+                        }
+                    }
+                }
+
+                for f in &hook_findings {
+                    println!(
+                        "{} {} {}",
+                        emoji("warn", &style),
+                        color(
+                            Role::Warning,
+                            format!("hook [{}] {}", f.severity.as_str(), f.title),
+                            &style
+                        ),
+                        color(Role::Dim, f.evidence.as_deref().unwrap_or(""), &style)
+                    );
+                }
+
+                if !notifications.is_empty() {
+                    println!("{}", color(Role::Accent, "Notifications:", &style));
+                    for n in &notifications {
+                        println!(
+                            "  {} {}",
+                            color(Role::Dim, format!("+{}ms", n.elapsed_ms), &style),
+                            n.describe()
+                        );
+                    }
+                }
+            }
+            if !outcomes.iter().all(expect::Outcome::passed) {
+                return Err(exit::Status(exit::FINDINGS).into());
+            }
+            if tool_error && !args.no_fail_on_error {
+                return Err(exit::Status(exit::TOOL_ERROR).into());
             }
         }
         Err(e) => {
-            return output_error(args.json, &e.to_string());
+            return report_error(args.output.is_json(), e);
+        }
+    }
+
+    Ok(())
+}
+
+/// `--extract` output: a JSON array with --json, else one value per line
+/// (strings as-is, anything else as compact JSON).
+fn print_extracted(values: &[&serde_json::Value], json: bool) {
+    if json {
+        let all = serde_json::Value::Array(values.iter().map(|v| (*v).clone()).collect());
+        print_json(&all);
+        return;
+    }
+    for v in values {
+        match v {
+            serde_json::Value::String(s) => println!("{s}"),
+            other => println!("{other}"),
+        }
+    }
+}
+
+/// structuredContent problems against a tool's outputSchema. Error results
+/// are not checked; a missing structuredContent is a mismatch.
+fn output_mismatches(
+    schema: &serde_json::Value,
+    result: &rmcp::model::CallToolResult,
+) -> Vec<String> {
+    if result.is_error == Some(true) {
+        return Vec::new();
+    }
+    match &result.structured_content {
+        Some(content) => mcp::schema::check(schema, content),
+        None => vec!["structuredContent missing (the tool declares an outputSchema)".to_string()],
+    }
+}
+
+/// Fail (or with `lenient`, warn on stderr) when `arguments` do not match
+/// the tool's input schema; with `strict`, undeclared parameters always fail.
+fn check_arguments(
+    tool: &serde_json::Value,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+    strict: bool,
+    lenient: bool,
+) -> Result<()> {
+    let Some(tool_obj) = tool.as_object() else {
+        return Ok(());
+    };
+    if strict {
+        let unknown = undeclared_arguments(tool_obj, arguments);
+        if !unknown.is_empty() {
+            return Err(exit::usage(format!(
+                "parameters not in the input schema (--strict): {}",
+                unknown.join(", ")
+            )));
+        }
+    }
+    let problems = validate_arguments(tool_obj, arguments);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if !lenient {
+        return Err(exit::usage(format!(
+            "arguments do not match the input schema: {} (use --lenient to send anyway)",
+            problems.join("; ")
+        )));
+    }
+    if current_log_level() >= LogLevel::Info {
+        for p in &problems {
+            eprintln!("[lenient] {p}");
+        }
+    }
+    Ok(())
+}
+
+/* ---- Destructive Tool Confirmation ---- */
+
+/// Why calling `tool` needs confirmation: `annotations.destructiveHint` or a
+/// name matching a config.toml `danger_patterns` glob (case-insensitive).
+pub fn danger_reason(tool: &serde_json::Value) -> Option<String> {
+    danger_reason_with(tool, &crate::utils::config::get().danger_patterns)
+}
+
+fn danger_reason_with(tool: &serde_json::Value, patterns: &[String]) -> Option<String> {
+    if tool.pointer("/annotations/destructiveHint") == Some(&serde_json::Value::Bool(true)) {
+        return Some("annotations.destructiveHint".to_string());
+    }
+    let name = tool.get("name")?.as_str()?.to_ascii_lowercase();
+    patterns
+        .iter()
+        .find(|p| crate::utils::pattern::glob_match(&p.to_ascii_lowercase(), &name))
+        .map(|p| format!("danger pattern '{p}'"))
+}
+
+/// Ask on the terminal before calling a destructive tool. Refused when
+/// stdin is not a terminal (scripts must pass --yes); a refusal is a usage
+/// error (exit 2). Callers ask before the server is started.
+fn confirm_call(tool_name: &str, reason: &str) -> Result<()> {
+    use std::io::IsTerminal;
+    if !io::stdin().is_terminal() {
+        return Err(exit::usage(format!(
+            "tool '{tool_name}' is destructive ({reason}); pass --yes to call it"
+        )));
+    }
+    eprint!("Tool '{tool_name}' is destructive ({reason}). Call it? [y/N] ");
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    if matches!(line.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        Ok(())
+    } else {
+        Err(exit::usage(format!(
+            "call of destructive tool '{tool_name}' not confirmed"
+        )))
+    }
+}
+
+/// Draw image results inline when the terminal has an image protocol.
+fn print_image_previews(images: &[(content::ImageInfo, Vec<u8>)], style: &StyleOptions) {
+    use std::io::IsTerminal;
+    let protocol = content::ImageProtocol::detect().filter(|_| io::stdout().is_terminal());
+    println!("{}", color(Role::Accent, "Images:", style));
+    for (info, bytes) in images {
+        println!("  content[{}] {}", info.index, info.label());
+        let mime = info
+            .detected
+            .or(info.mime_type.as_deref())
+            .unwrap_or_default();
+        match protocol.and_then(|p| p.render(bytes, mime)) {
+            Some(seq) => println!("{seq}"),
+            None if protocol.is_some() => println!(
+                "  {}",
+                color(Role::Dim, "(format not supported by this terminal)", style)
+            ),
+            None => {}
+        }
+    }
+    if protocol.is_none() {
+        println!(
+            "  {}",
+            color(
+                Role::Dim,
+                "(no inline image support detected; iTerm2, WezTerm and kitty are supported)",
+                style
+            )
+        );
+    }
+}
+
+/* ---- Dry Run ---- */
+
+/// The `tools/call` that `invoke_tool` would send, and its schema problems.
+async fn preview_tool(
+    spec: &crate::mcp::TargetSpec,
+    tool_name: &str,
+    base: Option<&serde_json::Map<String, serde_json::Value>>,
+    mut provided: std::collections::HashMap<String, String>,
+    interactive: bool,
+    strict: bool,
+    retry: &RetryArgs,
+) -> Result<(serde_json::Value, Vec<String>, Option<String>)> {
+    let (tool_list, _) = retry
+        .run(|| fetch_tools_for(spec, std::slice::from_ref(&tool_name)))
+        .await;
+    let tools = serde_json::json!({ "tools": tool_list?.tools });
+    let tool_val = find_tool_case_insensitive(&tools, tool_name)
+        .ok_or_else(|| exit::tool_not_found(tool_name))?;
+    let tool_obj = tool_val
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("tool JSON is not an object"))?;
+    let mut arguments = base.cloned().unwrap_or_default();
+    if interactive {
+        prompt_for_parameters(tool_obj, &arguments, &mut provided)?;
+    }
+    let danger = danger_reason(&tool_val);
+    arguments.extend(coerce_arguments(tool_obj, &provided)?);
+    crate::mcp::hook::pre_request(spec.original(), tool_name, &mut arguments);
+    let mut problems = validate_arguments(tool_obj, &arguments);
+    if strict {
+        problems.extend(
+            undeclared_arguments(tool_obj, &arguments)
+                .into_iter()
+                .map(|p| format!("{p}: not in the input schema (--strict)")),
+        );
+    }
+    let mut params = serde_json::json!({ "name": tool_name });
+    if !arguments.is_empty() {
+        params["arguments"] = serde_json::Value::Object(arguments);
+    }
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "tools/call",
+        "params": params,
+    });
+    Ok((request, problems, danger))
+}
+
+async fn execute_dry_run(
+    args: &ExecArgs,
+    spec: &crate::mcp::TargetSpec,
+    tool_name: &str,
+    base: Option<&serde_json::Map<String, serde_json::Value>>,
+    provided: std::collections::HashMap<String, String>,
+) -> Result<()> {
+    let json = args.output.is_json();
+    let (request, problems, danger) = match preview_tool(
+        spec,
+        tool_name,
+        base,
+        provided,
+        args.interactive,
+        args.strict,
+        &args.retry,
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => return report_error(json, e),
+    };
+    if json {
+        let out = serde_json::json!({
+            "status": "ok",
+            "subject": "tool",
+            "dry_run": true,
+            "tool": tool_name,
+            "target": spec.original(),
+            "request": request,
+            "validation": {"valid": problems.is_empty(), "errors": problems},
+            "confirmation_required": danger,
+        });
+        print_json(&out);
+    } else {
+        let style = StyleOptions::detect();
+        let header = box_header(
+            format!("{} Dry Run ({tool_name})", emoji("info", &style)),
+            Some(format!("target={} • not sent", spec.original())),
+            &style,
+        );
+        println!("{header}");
+        println!("{}", color(Role::Accent, "Request:", &style));
+        println!("{}", pretty(&request));
+        println!();
+        if let Some(reason) = &danger {
+            println!(
+                "{} {}",
+                emoji("warn", &style),
+                color(
+                    Role::Warning,
+                    format!("Destructive ({reason}): exec asks for confirmation or --yes"),
+                    &style
+                )
+            );
+        }
+        if problems.is_empty() {
+            println!(
+                "{} {}",
+                emoji("success", &style),
+                color(Role::Success, "Arguments match the input schema", &style)
+            );
+        } else {
+            println!("{}", color(Role::Accent, "Validation:", &style));
+            for p in &problems {
+                println!(
+                    "  {} {}",
+                    emoji("warn", &style),
+                    color(Role::Warning, p, &style)
+                );
+            }
+        }
+    }
+    if !problems.is_empty() {
+        return Err(exit::Status(exit::USAGE).into());
+    }
+    Ok(())
+}
+
+/* ---- Generate ---- */
+
+/// An example arguments object for the tool, overlaid with `base` and the
+/// coerced `provided` values.
+async fn generate_arguments(
+    spec: &crate::mcp::TargetSpec,
+    tool_name: &str,
+    base: Option<serde_json::Map<String, serde_json::Value>>,
+    provided: &std::collections::HashMap<String, String>,
+    retry: &RetryArgs,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let (tool_list, _) = retry
+        .run(|| fetch_tools_for(spec, std::slice::from_ref(&tool_name)))
+        .await;
+    let tools = serde_json::json!({ "tools": tool_list?.tools });
+    let tool_val = find_tool_case_insensitive(&tools, tool_name)
+        .ok_or_else(|| exit::tool_not_found(tool_name))?;
+    let tool_obj = tool_val
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("tool JSON is not an object"))?;
+    let mut arguments = example_arguments(tool_obj);
+    for (k, v) in base
+        .into_iter()
+        .flatten()
+        .chain(coerce_arguments(tool_obj, provided)?)
+    {
+        match arguments.get_mut(&k) {
+            Some(existing) => deep_merge(existing, v),
+            None => {
+                arguments.insert(k, v);
+            }
+        }
+    }
+    Ok(arguments)
+}
+
+async fn execute_generate(
+    args: &ExecArgs,
+    spec: &crate::mcp::TargetSpec,
+    tool_name: &str,
+    base: Option<serde_json::Map<String, serde_json::Value>>,
+    provided: std::collections::HashMap<String, String>,
+) -> Result<()> {
+    let json = args.output.is_json();
+    let arguments = match generate_arguments(spec, tool_name, base, &provided, &args.retry).await {
+        Ok(a) => a,
+        Err(e) => return report_error(json, e),
+    };
+    if json {
+        print_line(&serde_json::json!({
+            "status": "ok",
+            "subject": "tool",
+            "tool": tool_name,
+            "target": spec.original(),
+            "arguments": arguments,
+        }));
+    } else {
+        println!("{}", pretty(&serde_json::Value::Object(arguments)));
+    }
+    Ok(())
+}
+
+/* ---- Batch ---- */
+
+/// One call of `exec batch --file`.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchCall {
+    pub tool: String,
+    #[serde(default)]
+    pub arguments: serde_json::Map<String, serde_json::Value>,
+    /// --param values, coerced with the tool's input schema (exec --count)
+    #[serde(skip)]
+    pub params: std::collections::HashMap<String, String>,
+}
+
+/// Parse a batch file (JSON, or YAML for .yaml / .yml).
+pub fn parse_batch(path: &str, raw: &str) -> Result<Vec<BatchCall>> {
+    let lower = path.to_ascii_lowercase();
+    let calls: Vec<BatchCall> = if lower.ends_with(".yaml") || lower.ends_with(".yml") {
+        serde_yaml::from_str(raw).context("failed to parse YAML batch file")?
+    } else {
+        serde_json::from_str(raw).context("failed to parse JSON batch file")?
+    };
+    if calls.is_empty() {
+        anyhow::bail!("batch file lists no calls");
+    }
+    if let Some(i) = calls.iter().position(|c| c.tool.trim().is_empty()) {
+        anyhow::bail!("call #{} has an empty tool name", i + 1);
+    }
+    Ok(calls)
+}
+
+/// Outcome of one batch call.
+struct BatchResult {
+    tool: String,
+    arguments: serde_json::Map<String, serde_json::Value>,
+    result: Option<rmcp::model::CallToolResult>,
+    error: Option<String>,
+    /// Exit status of `error` (see cmd::exit)
+    error_status: i32,
+    elapsed_ms: u128,
+    /// outputSchema mismatches, when the tool declares one
+    output_problems: Option<Vec<String>>,
+    /// Server notifications received during the call
+    notifications: Vec<mcp::notifications::Notification>,
+}
+
+impl BatchResult {
+    fn failed(&self) -> bool {
+        self.error.is_some()
+            || self
+                .result
+                .as_ref()
+                .is_some_and(|r| r.is_error == Some(true))
+    }
+
+    /// Exit status of a failed call (see cmd::exit): the invocation error's
+    /// kind, or [`exit::TOOL_ERROR`] for an isError result unless
+    /// `fail_on_error` is off.
+    fn exit_status(&self, fail_on_error: bool) -> Option<i32> {
+        match &self.error {
+            Some(_) => Some(self.error_status),
+            None => (fail_on_error && self.failed()).then_some(exit::TOOL_ERROR),
+        }
+    }
+
+    /// Record the call's failure.
+    fn fail(&mut self, err: anyhow::Error) {
+        self.error_status = exit::for_error(&err);
+        self.error = Some(format!("{err:#}"));
+    }
+
+    fn to_json(&self, index: usize, raw: bool) -> serde_json::Value {
+        let mut v = serde_json::json!({
+            "index": index,
+            "tool": self.tool,
+            "arguments": self.arguments,
+            "status": if self.error.is_some() { "error" } else { "ok" },
+            "is_error": self.failed(),
+            "elapsed_ms": self.elapsed_ms,
+            "error": self.error,
+            "notifications": self
+                .notifications
+                .iter()
+                .map(mcp::notifications::Notification::to_json)
+                .collect::<Vec<_>>(),
+        });
+        if let Some(problems) = &self.output_problems {
+            v["output_validation"] =
+                serde_json::json!({"valid": problems.is_empty(), "errors": problems});
+        }
+        if let Some(r) = &self.result {
+            if raw {
+                v["result"] = serde_json::to_value(r).unwrap_or_default();
+            } else {
+                v["result_summary"] = summarize_call_result(r);
+            }
+        }
+        v
+    }
+}
+
+/// Run `calls` in order over one session, --interval apart; per-call
+/// failures are recorded. Destructive calls are confirmed one by one (a
+/// repeated identical call only once) unless --yes; arguments are checked
+/// like `exec tool` (--strict / --lenient).
+async fn invoke_batch(
+    spec: &crate::mcp::TargetSpec,
+    calls: &[BatchCall],
+    opts: &ExecArgs,
+) -> Result<Vec<BatchResult>> {
+    if !spec.is_local() {
+        return Err(exit::usage(
+            "exec batch only supports local process targets",
+        ));
+    }
+    // A cached listing with every tool of the batch will do.
+    let names: Vec<&str> = calls.iter().map(|c| c.tool.as_str()).collect();
+    let tools = fetch_tools_for(spec, &names).await?.tools;
+    let tools_val = serde_json::json!({ "tools": tools });
+    // Started with the first call that passes its checks, so a refused
+    // destructive call never spawns the server.
+    let mut session: Option<McpSession<ProgressClient>> = None;
+
+    let mut results = Vec::with_capacity(calls.len());
+    let mut confirmed: Vec<(String, serde_json::Map<String, serde_json::Value>)> = Vec::new();
+    for (i, call) in calls.iter().enumerate() {
+        if i > 0 && opts.interval > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(opts.interval)).await;
+        }
+        let mut arguments = call.arguments.clone();
+        let mut out = BatchResult {
+            tool: call.tool.clone(),
+            arguments: arguments.clone(),
+            result: None,
+            error: None,
+            error_status: exit::SUCCESS,
+            elapsed_ms: 0,
+            output_problems: None,
+            notifications: Vec::new(),
+        };
+        // Resolve the advertised name (case-insensitive, like exec tool).
+        let tool = find_tool_case_insensitive(&tools_val, &call.tool);
+        let Some(name) = tool
+            .as_ref()
+            .and_then(|t| t.get("name").and_then(|n| n.as_str()).map(str::to_string))
+        else {
+            out.fail(exit::tool_not_found(&call.tool));
+            results.push(out);
+            continue;
+        };
+        if !call.params.is_empty()
+            && let Some(tool_obj) = tool.as_ref().and_then(|t| t.as_object())
+        {
+            match merge_arguments(tool_obj, arguments, &call.params) {
+                Ok(merged) => arguments = merged,
+                Err(e) => {
+                    out.fail(e);
+                    results.push(out);
+                    continue;
+                }
+            }
+        }
+        let key = (name.clone(), arguments.clone());
+        if !opts.yes && !confirmed.contains(&key) {
+            if let Some(reason) = tool.as_ref().and_then(danger_reason)
+                && let Err(e) = confirm_call(&name, &reason)
+            {
+                out.fail(e);
+                results.push(out);
+                continue;
+            }
+            confirmed.push(key);
+        }
+        crate::mcp::hook::pre_request(spec.original(), &name, &mut arguments);
+        out.arguments = arguments.clone();
+        if let Some(tool) = &tool
+            && let Err(e) = check_arguments(tool, &arguments, opts.strict, opts.lenient)
+        {
+            out.fail(e);
+            results.push(out);
+            continue;
+        }
+        let live = match session.take() {
+            Some(live) => live,
+            None => McpSession::connect(spec, ProgressClient::default()).await?,
+        };
+        let live = session.insert(live);
+        crate::utils::progress_events::phase("call", Some(&name));
+        let started = Instant::now();
+        let response = live
+            .call_tool(&name, arguments.clone(), &Watch::start())
+            .await;
+        live.client().finish();
+        out.elapsed_ms = started.elapsed().as_millis();
+        out.notifications = mcp::notifications::take();
+        if crate::mcp::interrupt::interrupted() {
+            if let Some(live) = session.take() {
+                crate::mcp::interrupt::shutdown(live).await;
+            }
+            return Err(crate::mcp::interrupt::Interrupted.into());
+        }
+        match response {
+            Err(e) => out.fail(e),
+            Ok(Ok(r)) => {
+                crate::mcp::hook::post_response(
+                    spec.original(),
+                    &name,
+                    &arguments,
+                    &serde_json::to_value(&r).unwrap_or_default(),
+                    out.elapsed_ms,
+                );
+                out.output_problems = tool
+                    .as_ref()
+                    .and_then(mcp::schema::output_schema)
+                    .map(|schema| output_mismatches(schema, &r));
+                out.result = Some(r);
+            }
+            Ok(Err(e)) => out.fail(anyhow::Error::from(e).context("tool invocation failed")),
         }
+        results.push(out);
+    }
+    if let Some(session) = session {
+        session.shutdown().await;
+    }
+    Ok(results)
+}
+
+async fn execute_batch(mut args: ExecArgs) -> Result<()> {
+    let json = args.output.is_json();
+    if args.dry_run
+        || args.generate
+        || args.args_json.is_some()
+        || args.save_dir.is_some()
+        || args.preview
+        || args.count.is_some()
+        || !args.expect.is_empty()
+        || args.extract.is_some()
+    {
+        return usage_error(
+            json,
+            "--dry-run, --generate, --args-json, --save-dir, --preview, --count, --expect-* and --extract are not supported with exec batch",
+        );
+    }
+    let path = args.file.clone().unwrap_or_default();
+    let calls = match std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read batch file: {path}"))
+        .and_then(|raw| parse_batch(&path, &raw))
+    {
+        Ok(c) => c,
+        Err(e) => return report_error(json, e),
+    };
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => return usage_error(json, "no target specified (use --target or MCP_TARGET)"),
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(json, "remote exec not implemented yet");
+    }
+
+    let started = Instant::now();
+    let (results, attempts) = args.retry.run(|| invoke_batch(&spec, &calls, &args)).await;
+    let results = match results {
+        Ok(r) => r,
+        Err(e) => return report_error(json, e),
+    };
+    let elapsed_ms = started.elapsed().as_millis();
+    let hook_findings = mcp::hook::take_findings();
+    let failed = results.iter().filter(|r| r.failed()).count();
+
+    if json {
+        let mut out = serde_json::json!({
+            "status": "ok",
+            "subject": "batch",
+            "target": target_raw,
+            "file": path,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "summary": {"calls": results.len(), "ok": results.len() - failed, "failed": failed},
+            "results": results.iter().enumerate().map(|(i, r)| r.to_json(i, args.raw)).collect::<Vec<_>>(),
+        });
+        attempts.annotate(&mut out);
+        if mcp::hook::installed() {
+            out["findings"] = hook_findings.iter().map(|f| f.to_json()).collect();
+        }
+        print_json(&out);
+    } else {
+        let style = StyleOptions::detect();
+        let header = box_header(
+            format!(
+                "{} Exec Batch • {} ok / {failed} failed",
+                emoji("rocket", &style),
+                results.len() - failed
+            ),
+            Some(format!("target={target_raw} • {elapsed_ms} ms")),
+            &style,
+        );
+        println!("{header}");
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let summary = match (&r.error, &r.result) {
+                    (Some(e), _) => e.clone(),
+                    (None, Some(res)) => summarize_call_result(res).to_string(),
+                    (None, None) => String::new(),
+                };
+                let status = if r.failed() {
+                    color(Role::Error, "failed", &style)
+                } else {
+                    color(Role::Success, "ok", &style)
+                };
+                vec![
+                    (i + 1).to_string(),
+                    r.tool.clone(),
+                    status,
+                    r.elapsed_ms.to_string(),
+                    summary,
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["#", "TOOL", "STATUS", "MS", "RESULT"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+        for f in &hook_findings {
+            println!(
+                "{} {} {}",
+                emoji("warn", &style),
+                color(
+                    Role::Warning,
+                    format!("hook [{}] {}", f.severity.as_str(), f.title),
+                    &style
+                ),
+                color(Role::Dim, f.evidence.as_deref().unwrap_or(""), &style)
+            );
+        }
+    }
+    if let Some(status) = results
+        .iter()
+        .find_map(|r| r.exit_status(!args.no_fail_on_error))
+    {
+        return Err(exit::Status(status).into());
+    }
+    Ok(())
+}
+
+/* ---- Repeat (--count) ---- */
+
+/// Group results that are identical: the variant number (0 = the first
+/// result seen) of each call, and how many distinct results there were.
+fn result_variants(results: &[BatchResult]) -> (Vec<usize>, usize) {
+    let mut seen: Vec<serde_json::Value> = Vec::new();
+    let variants = results
+        .iter()
+        .map(|r| {
+            let key = match (&r.error, &r.result) {
+                (Some(e), _) => serde_json::json!({ "error": e }),
+                (None, r) => serde_json::to_value(r).unwrap_or_default(),
+            };
+            seen.iter().position(|v| *v == key).unwrap_or_else(|| {
+                seen.push(key);
+                seen.len() - 1
+            })
+        })
+        .collect();
+    (variants, seen.len())
+}
+
+/// min / median / mean / max of the call latencies.
+fn latency_stats(results: &[BatchResult]) -> serde_json::Value {
+    let mut ms: Vec<u128> = results.iter().map(|r| r.elapsed_ms).collect();
+    ms.sort_unstable();
+    if ms.is_empty() {
+        return serde_json::Value::Null;
+    }
+    let mid = ms.len() / 2;
+    let median = if ms.len().is_multiple_of(2) {
+        (ms[mid - 1] + ms[mid]) / 2
+    } else {
+        ms[mid]
+    };
+    serde_json::json!({
+        "min": ms[0],
+        "median": median,
+        "mean": ms.iter().sum::<u128>() / ms.len() as u128,
+        "max": ms[ms.len() - 1],
+    })
+}
+
+/// `exec tool --count N`: the same call N times over one session (through
+/// `invoke_batch`), reporting each latency and whether the results matched.
+async fn execute_repeat(
+    args: &ExecArgs,
+    spec: &crate::mcp::TargetSpec,
+    tool_name: &str,
+    base: Option<serde_json::Map<String, serde_json::Value>>,
+    provided: std::collections::HashMap<String, String>,
+    count: u32,
+) -> Result<()> {
+    let json = args.output.is_json();
+    if args.interactive
+        || args.save_dir.is_some()
+        || args.preview
+        || !args.expect.is_empty()
+        || args.extract.is_some()
+    {
+        return usage_error(
+            json,
+            "--interactive, --save-dir, --preview, --expect-* and --extract are not supported with --count",
+        );
     }
+    let call = BatchCall {
+        tool: tool_name.to_string(),
+        arguments: base.unwrap_or_default(),
+        params: provided,
+    };
+    let calls = vec![call; count as usize];
+
+    let started = Instant::now();
+    let (results, attempts) = args.retry.run(|| invoke_batch(spec, &calls, args)).await;
+    let results = match results {
+        Ok(r) => r,
+        Err(e) => return report_error(json, e),
+    };
+    let elapsed_ms = started.elapsed().as_millis();
+    let hook_findings = mcp::hook::take_findings();
+    let failed = results.iter().filter(|r| r.failed()).count();
+    let (variants, distinct) = result_variants(&results);
+    let latency = latency_stats(&results);
 
+    if json {
+        let mut out = serde_json::json!({
+            "status": "ok",
+            "subject": "tool",
+            "tool": tool_name,
+            "target": spec.original(),
+            "count": count,
+            "interval_ms": args.interval,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "arguments": results.first().map(|r| r.arguments.clone()).unwrap_or_default(),
+            "identical": distinct == 1,
+            "distinct_results": distinct,
+            "latency_ms": latency,
+            "summary": {"calls": results.len(), "ok": results.len() - failed, "failed": failed},
+            "iterations": results.iter().zip(&variants).enumerate().map(|(i, (r, v))| {
+                let mut it = r.to_json(i, args.raw);
+                it["variant"] = serde_json::json!(v);
+                it
+            }).collect::<Vec<_>>(),
+        });
+        attempts.annotate(&mut out);
+        if mcp::hook::installed() {
+            out["findings"] = hook_findings.iter().map(|f| f.to_json()).collect();
+        }
+        print_json(&out);
+    } else {
+        let style = StyleOptions::detect();
+        let verdict = if distinct == 1 {
+            "identical results".to_string()
+        } else {
+            format!("{distinct} distinct results")
+        };
+        let header = box_header(
+            format!(
+                "{} Exec Repeat ({tool_name}) • {count} calls • {verdict}",
+                emoji("rocket", &style)
+            ),
+            Some(format!("target={} • {elapsed_ms} ms", spec.original())),
+            &style,
+        );
+        println!("{header}");
+        let rows: Vec<Vec<String>> = results
+            .iter()
+            .zip(&variants)
+            .enumerate()
+            .map(|(i, (r, v))| {
+                let status = if r.failed() {
+                    color(Role::Error, "failed", &style)
+                } else {
+                    color(Role::Success, "ok", &style)
+                };
+                let same = if *v == 0 {
+                    "same as #1".to_string()
+                } else {
+                    color(
+                        Role::Warning,
+                        format!("differs (variant {})", v + 1),
+                        &style,
+                    )
+                };
+                vec![(i + 1).to_string(), status, r.elapsed_ms.to_string(), same]
+            })
+            .collect();
+        let tbl = table(
+            &["#", "STATUS", "MS", "RESULT"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!(
+                    "latency ms: min {} • median {} • mean {} • max {}",
+                    latency["min"], latency["median"], latency["mean"], latency["max"]
+                ),
+                &style
+            )
+        );
+        for variant in 0..distinct {
+            let Some(first) = variants.iter().position(|v| *v == variant) else {
+                continue;
+            };
+            let r = &results[first];
+            let summary = match (&r.error, &r.result) {
+                (Some(e), _) => e.clone(),
+                (None, Some(res)) => summarize_call_result(res).to_string(),
+                (None, None) => String::new(),
+            };
+            let label = if distinct == 1 {
+                "Result:".to_string()
+            } else {
+                format!("Variant {} (first at #{}):", variant + 1, first + 1)
+            };
+            println!("{} {summary}", color(Role::Accent, label, &style));
+        }
+        for f in &hook_findings {
+            println!(
+                "{} {} {}",
+                emoji("warn", &style),
+                color(
+                    Role::Warning,
+                    format!("hook [{}] {}", f.severity.as_str(), f.title),
+                    &style
+                ),
+                color(Role::Dim, f.evidence.as_deref().unwrap_or(""), &style)
+            );
+        }
+    }
+    if let Some(status) = results
+        .iter()
+        .find_map(|r| r.exit_status(!args.no_fail_on_error))
+    {
+        return Err(exit::Status(status).into());
+    }
     Ok(())
 }
 
 /* ---- Core Invocation Logic ---- */
 
-pub fn invoke_tool(
+/// Check run with the resolved tool object and the final arguments right
+/// before the call is sent (Err aborts it).
+pub type PreCallFn<'a> =
+    dyn Fn(&serde_json::Value, &serde_json::Map<String, serde_json::Value>) -> Result<()> + 'a;
+
+pub async fn invoke_tool(
     spec: &crate::mcp::TargetSpec,
     tool_name: &str,
     mut provided: std::collections::HashMap<String, String>,
+    base: Option<&serde_json::Map<String, serde_json::Value>>,
     interactive: bool,
     json_mode: bool,
+    pre_call: Option<&PreCallFn<'_>>,
 ) -> Result<(
     serde_json::Map<String, serde_json::Value>,
     rmcp::model::CallToolResult,
 )> {
-    use rmcp::ServiceExt;
-    use rmcp::model::CallToolRequestParam;
-    use rmcp::transport::{ConfigureCommandExt, TokioChildProcess};
-    use tokio::process::Command;
-
-    // Spawn runtime (main is currently sync)
-    let rt = tokio::runtime::Runtime::new().context("Failed to create Tokio runtime")?;
-
-    rt.block_on(async {
-        // Extract local program/args
-        let (program, args_vec) = match spec {
-            crate::mcp::TargetSpec::LocalCommand { program, args, .. } => {
-                (program.clone(), args.clone())
+    if !spec.is_local() {
+        return Err(exit::usage(
+            "invoke_tool only supports local process targets",
+        ));
+    }
+
+    // Ctrl-C cancels the call and stops the server (crate::mcp::interrupt);
+    // with --interactive only once the prompts are answered.
+    let mut interrupt = if interactive {
+        Watch::default()
+    } else {
+        Watch::start()
+    };
+
+    // Enumerate tools (a cached listing that has the tool will do)
+    let tools = interrupt
+        .guard(fetch_tools_for(spec, &[tool_name]))
+        .await??
+        .tools;
+    let tools_val = serde_json::json!({ "tools": tools });
+    let tool_obj_val = find_tool_case_insensitive(&tools_val, tool_name)
+        .ok_or_else(|| exit::tool_not_found(tool_name))?;
+
+    let tool_obj = tool_obj_val
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("tool JSON is not an object"))?;
+
+    // Interactive prompt for missing required parameters (if requested)
+    let base = base.cloned().unwrap_or_default();
+    if interactive {
+        prompt_for_parameters(tool_obj, &base, &mut provided)?;
+        interrupt = Watch::start();
+    }
+
+    // Build argument object (schema-driven, on top of --args-json)
+    let mut arg_obj =
+        merge_arguments(tool_obj, base, &provided).context("Failed to build arguments")?;
+    crate::mcp::hook::pre_request(spec.original(), tool_name, &mut arg_obj);
+    if let Some(pre_call) = pre_call {
+        pre_call(&tool_obj_val, &arg_obj)?;
+    }
+
+    // Spawn child MCP process (after the checks: a refused call starts nothing)
+    let session = interrupt
+        .guard(McpSession::connect(spec, ProgressClient::default()))
+        .await??;
+
+    // Invoke tool
+    crate::utils::progress_events::phase("call", Some(tool_name));
+    let call_started = Instant::now();
+    let call = session
+        .call_tool(tool_name, arg_obj.clone(), &interrupt)
+        .await;
+    session.client().finish();
+    let call_result = match call {
+        Ok(result) => result,
+        Err(e) => {
+            crate::mcp::interrupt::shutdown(session).await;
+            let what = if e.is::<crate::mcp::interrupt::Interrupted>() {
+                "interrupted"
+            } else {
+                "failed"
+            };
+            return Err(e.context(format!("tool invocation {what}: {tool_name}")));
+        }
+    };
+    let call_result =
+        call_result.with_context(|| format!("tool invocation failed: {}", tool_name))?;
+    crate::mcp::hook::post_response(
+        spec.original(),
+        tool_name,
+        &arg_obj,
+        &serde_json::to_value(&call_result).unwrap_or_default(),
+        call_started.elapsed().as_millis(),
+    );
+
+    // Attempt graceful shutdown
+    session.shutdown().await;
+
+    if json_mode {
+        // For JSON output we want to pass through the argument map unchanged
+        Ok((arg_obj, call_result))
+    } else {
+        // In human mode we also keep the same map
+        Ok((arg_obj, call_result))
+    }
+}
+
+/// One server kept across many tool calls: fuzz sends every word through the
+/// same session rather than spawning the target per call. The server is
+/// started on the first call and again after a call that broke the session
+/// (transport closed, timed out), so one crashing input does not fail the
+/// words after it.
+pub struct ToolSession<'a> {
+    spec: &'a crate::mcp::TargetSpec,
+    session: Option<McpSession<ProgressClient>>,
+    tools: Option<Vec<serde_json::Value>>,
+    interrupt: Watch,
+}
+
+impl<'a> ToolSession<'a> {
+    /// Ctrl-C cancels the pending call and stops the server until this is
+    /// closed or dropped.
+    pub fn new(spec: &'a crate::mcp::TargetSpec) -> Self {
+        Self {
+            spec,
+            session: None,
+            tools: None,
+            interrupt: Watch::start(),
+        }
+    }
+
+    /// Call `tool_name` with `provided` parameters, like [`invoke_tool`]
+    /// without the interactive prompts.
+    pub async fn call(
+        &mut self,
+        tool_name: &str,
+        provided: &std::collections::HashMap<String, String>,
+    ) -> Result<(
+        serde_json::Map<String, serde_json::Value>,
+        rmcp::model::CallToolResult,
+    )> {
+        if !self.spec.is_local() {
+            return Err(exit::usage(
+                "ToolSession only supports local process targets",
+            ));
+        }
+        let session = match self.session.take() {
+            Some(session) => session,
+            None => {
+                self.interrupt
+                    .guard(McpSession::connect(self.spec, ProgressClient::default()))
+                    .await??
             }
-            _ => anyhow::bail!("invoke_tool only supports local process targets"),
         };
+        let session = self.session.insert(session);
 
-        // Spawn child MCP process
-        let service = ()
-            .serve(TokioChildProcess::new(Command::new(&program).configure(
-                |c| {
-                    for a in &args_vec {
-                        c.arg(a);
-                    }
-                    // Silence child stderr (banners/log noise) while preserving stdout for protocol
-                    c.stderr(std::process::Stdio::null());
-                },
-            ))?)
-            .await
-            .with_context(|| format!("Failed to spawn MCP process: {}", program))?;
-
-        // Enumerate tools
-        let tools_resp = service
-            .list_tools(Default::default())
-            .await
-            .context("Failed to list tools")?;
-
-        let tools_val = serde_json::to_value(&tools_resp).unwrap_or(serde_json::Value::Null);
+        // Enumerate once per run (a cached listing that has the tool will do)
+        if !self
+            .tools
+            .as_deref()
+            .is_some_and(|tools| has_tool(tools, tool_name))
+        {
+            let list = self
+                .interrupt
+                .guard(fetch_tools_for(self.spec, &[tool_name]))
+                .await??;
+            self.tools = Some(list.tools);
+        }
+        let tools_val = serde_json::json!({ "tools": self.tools });
         let tool_obj_val = find_tool_case_insensitive(&tools_val, tool_name)
-            .ok_or_else(|| anyhow::anyhow!(format!("tool '{}' not found", tool_name)))?;
-
+            .ok_or_else(|| exit::tool_not_found(tool_name))?;
         let tool_obj = tool_obj_val
             .as_object()
             .ok_or_else(|| anyhow::anyhow!("tool JSON is not an object"))?;
 
-        // Interactive prompt for missing required parameters (if requested)
-        if interactive {
-            prompt_for_missing_required(tool_obj, &mut provided)?;
-        }
-
-        // Build argument object (schema-driven)
-        let arg_obj = build_arguments_from_schema(tool_obj, &provided)
+        let mut arg_obj = merge_arguments(tool_obj, Default::default(), provided)
             .context("Failed to build arguments")?;
+        crate::mcp::hook::pre_request(self.spec.original(), tool_name, &mut arg_obj);
 
-        // Invoke tool
-        let call_result = service
-            .call_tool(CallToolRequestParam {
-                name: tool_name.to_string().into(),
-                arguments: if arg_obj.is_empty() {
-                    None
-                } else {
-                    Some(arg_obj.clone())
-                },
-            })
-            .await
-            .with_context(|| format!("tool invocation failed: {}", tool_name))?;
+        crate::utils::progress_events::phase("call", Some(tool_name));
+        let call_started = Instant::now();
+        let call = session
+            .call_tool(tool_name, arg_obj.clone(), &self.interrupt)
+            .await;
+        session.client().finish();
+        let call_result =
+            match call {
+                Ok(Ok(result)) => result,
+                Ok(Err(e @ rmcp::ServiceError::McpError(_))) => {
+                    // A JSON-RPC error answer: the session is still usable
+                    return Err(anyhow::Error::from(e)
+                        .context(format!("tool invocation failed: {tool_name}")));
+                }
+                Ok(Err(e)) => {
+                    self.restart().await;
+                    return Err(anyhow::Error::from(e)
+                        .context(format!("tool invocation failed: {tool_name}")));
+                }
+                Err(e) => {
+                    self.restart().await;
+                    let what = if e.is::<crate::mcp::interrupt::Interrupted>() {
+                        "interrupted"
+                    } else {
+                        "failed"
+                    };
+                    return Err(e.context(format!("tool invocation {what}: {tool_name}")));
+                }
+            };
+        crate::mcp::hook::post_response(
+            self.spec.original(),
+            tool_name,
+            &arg_obj,
+            &serde_json::to_value(&call_result).unwrap_or_default(),
+            call_started.elapsed().as_millis(),
+        );
+        Ok((arg_obj, call_result))
+    }
 
-        // Attempt graceful shutdown
-        let _ = service.cancel().await;
+    /// Stop the current server; the next call starts a fresh one.
+    async fn restart(&mut self) {
+        if let Some(session) = self.session.take() {
+            crate::mcp::interrupt::shutdown(session).await;
+        }
+    }
 
-        if json_mode {
-            // For JSON output we want to pass through the argument map unchanged
-            Ok((arg_obj, call_result))
-        } else {
-            // In human mode we also keep the same map
-            Ok((arg_obj, call_result))
+    /// Close the session gracefully.
+    pub async fn close(mut self) {
+        if let Some(session) = self.session.take() {
+            session.shutdown().await;
         }
-    })
+    }
 }
 
 /* ---- Interactive Prompting ---- */
 
-fn prompt_for_missing_required(
+/// Prompt on the terminal for parameters `base` / `provided` do not set:
+/// required ones first (asked until answered), then optional ones (Enter
+/// skips). Each shows its type, description and default; enum values are
+/// offered as a numbered list. Answers are checked against the parameter's
+/// schema and asked again when they do not match.
+fn prompt_for_parameters(
     tool_obj: &serde_json::Map<String, serde_json::Value>,
+    base: &serde_json::Map<String, serde_json::Value>,
     provided: &mut std::collections::HashMap<String, String>,
 ) -> Result<()> {
     // Extract schema (support both snake_case `input_schema` and camelCase `inputSchema`)
@@ -392,59 +1834,166 @@ fn prompt_for_missing_required(
         return Ok(()); // No schema -> nothing to prompt
     };
 
-    // Collect required
     let required: std::collections::HashSet<&str> = schema_obj
         .get("required")
         .and_then(|v| v.as_array())
-        .map(|arr| {
-            arr.iter()
-                .filter_map(|x| x.as_str())
-                .collect::<std::collections::HashSet<_>>()
-        })
-        .unwrap_or_default();
-
+        .into_iter()
+        .flatten()
+        .filter_map(|x| x.as_str())
+        .collect();
     let props = schema_obj
         .get("properties")
         .and_then(|v| v.as_object())
         .cloned()
         .unwrap_or_default();
+    let pending: Vec<(String, serde_json::Value, bool)> = props
+        .into_iter()
+        .filter(|(pname, _)| {
+            !base.contains_key(pname) && !provided.keys().any(|k| param_root(k) == pname)
+        })
+        .map(|(pname, pobj)| {
+            let req = required.contains(pname.as_str());
+            (pname, pobj, req)
+        })
+        .collect();
+    let (mandatory, optional): (Vec<_>, Vec<_>) = pending.into_iter().partition(|p| p.2);
 
-    for (pname, pobj) in props {
-        if !required.contains(pname.as_str()) {
-            continue;
-        }
-        if provided.contains_key(&pname) {
-            continue;
+    for (i, (pname, pobj, req)) in mandatory.iter().chain(&optional).enumerate() {
+        if i == mandatory.len() {
+            println!("Optional parameters (Enter to skip):");
         }
-        // Determine type (for display)
-        let ptype = pobj
-            .as_object()
-            .and_then(|m| m.get("type"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("string");
+        describe_parameter(pname, pobj, *req);
         loop {
-            print!(
-                "Enter value for required param '{}'(type: {}): ",
-                pname, ptype
-            );
+            print!("  {pname}> ");
             let _ = io::stdout().flush();
             let mut line = String::new();
-            io::stdin().read_line(&mut line)?;
-            let val = line.trim();
-            if val.is_empty() {
-                println!("  (value required)");
-                continue;
+            if io::stdin().read_line(&mut line)? == 0 {
+                anyhow::bail!("stdin closed while prompting for '{pname}'");
+            }
+            match parse_answer(pobj, line.trim(), *req) {
+                Ok(Some(raw)) => {
+                    provided.insert(pname.clone(), raw);
+                    break;
+                }
+                Ok(None) => break,
+                Err(e) => println!("  ({e})"),
             }
-            // (We do not coerce here; final coercion is handled by build_arguments_from_schema / coerce_value)
-            provided.insert(pname.clone(), val.to_string());
-            break;
         }
     }
     Ok(())
 }
 
+/// Raw text of a schema value (strings unquoted).
+fn raw_text(v: &serde_json::Value) -> String {
+    match v {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Declared type of a parameter for prompting / coercion (first non-null
+/// entry of a type list), defaulting to string.
+fn prompt_type(prop: &serde_json::Value) -> &str {
+    match prop.get("type") {
+        Some(serde_json::Value::String(t)) => t,
+        Some(serde_json::Value::Array(ts)) => ts
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("string"),
+        _ => "string",
+    }
+}
+
+fn describe_parameter(pname: &str, prop: &serde_json::Value, required: bool) {
+    let mut line = format!(
+        "{pname} ({}, {})",
+        prompt_type(prop),
+        if required { "required" } else { "optional" }
+    );
+    if let Some(desc) = prop
+        .get("description")
+        .and_then(|v| v.as_str())
+        .and_then(|d| d.lines().next())
+        .filter(|d| !d.trim().is_empty())
+    {
+        line.push_str(&format!(" - {}", desc.trim()));
+    }
+    if let Some(d) = prop.get("default") {
+        line.push_str(&format!(" [default: {}]", raw_text(d)));
+    }
+    if matches!(prompt_type(prop), "object" | "array") {
+        line.push_str(" (JSON)");
+    }
+    println!("{line}");
+    for (i, choice) in prop
+        .get("enum")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        println!("    {}) {}", i + 1, raw_text(choice));
+    }
+}
+
+/// The raw value for one prompt answer: an enum choice by number or value,
+/// the default on Enter for a required parameter, None when an optional one
+/// is skipped. Errors describe why the answer does not fit the schema.
+fn parse_answer(prop: &serde_json::Value, answer: &str, required: bool) -> Result<Option<String>> {
+    let ty = prompt_type(prop);
+    let raw = if answer.is_empty() {
+        match prop.get("default") {
+            Some(d) if required => raw_text(d),
+            _ if required => anyhow::bail!("value required"),
+            _ => return Ok(None),
+        }
+    } else if let Some(choices) = prop.get("enum").and_then(|v| v.as_array())
+        && !choices.contains(&coerce_value(answer, ty))
+        && let Ok(n) = answer.parse::<usize>()
+        && (1..=choices.len()).contains(&n)
+    {
+        raw_text(&choices[n - 1])
+    } else {
+        answer.to_string()
+    };
+    let problems = crate::mcp::schema::check(prop, &coerce_value(&raw, ty));
+    if let Some(p) = problems.first() {
+        anyhow::bail!("{}", p.strip_prefix("$: ").unwrap_or(p));
+    }
+    Ok(Some(raw))
+}
+
 /* ---- Parameter File Loading ---- */
 
+/// The --args-json object, read from stdin for `-`.
+fn read_args_json(
+    raw: &str,
+    interactive: bool,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let text = if raw.trim() == "-" {
+        if interactive {
+            return Err(exit::usage(
+                "--args-json - reads stdin and cannot be combined with --interactive",
+            ));
+        }
+        let mut buf = String::new();
+        io::Read::read_to_string(&mut io::stdin(), &mut buf)
+            .context("failed to read --args-json from stdin")?;
+        buf
+    } else {
+        raw.to_string()
+    };
+    match serde_json::from_str(&text)
+        .map_err(|e| exit::usage(format!("invalid --args-json: {e}")))?
+    {
+        serde_json::Value::Object(map) => Ok(map),
+        other => Err(exit::usage(format!(
+            "--args-json must be a JSON object, got {other}"
+        ))),
+    }
+}
+
 pub fn load_param_file_into_map(
     path: &str,
     provided: &mut std::collections::HashMap<String, String>,
@@ -480,40 +2029,138 @@ pub fn load_param_file_into_map(
 
 /* ---- Output Helpers ---- */
 
+/// Present a command error (see cmd::errors) and return it as already reported.
 pub fn output_error(json: bool, msg: &str) -> Result<()> {
-    if json {
-        let err = serde_json::json!({"status":"error","error":msg});
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&err).unwrap_or_else(|_| err.to_string())
-        );
-    } else {
-        // Fancy red error box for human output
-        let style = StyleOptions::detect();
-        let title = format!("{} Exec Error", emoji("error", &style));
-        // Color the message in red (Role::Error)
-        let subtitle = color(Role::Error, msg, &style);
-        let boxed = box_header(title, Some(subtitle), &style);
-        println!("{boxed}");
-        println!(
-            "{} {}",
-            emoji("info", &style),
-            color(
-                Role::Dim,
-                "Re-run with --json for machine-readable output or add --raw for full result payload (if available).",
-                &style
-            )
-        );
+    reported(json, msg, exit::INTERNAL)
+}
+
+/// [`output_error`] for a usage or target error (exit 2).
+pub fn usage_error(json: bool, msg: &str) -> Result<()> {
+    reported(json, msg, exit::USAGE)
+}
+
+/// [`output_error`] for `err`, keeping the exit status its type carries.
+pub fn report_error(json: bool, err: anyhow::Error) -> Result<()> {
+    reported(json, &format!("{err:#}"), exit::for_error(&err))
+}
+
+fn reported(json: bool, msg: &str, status: i32) -> Result<()> {
+    errors::present(json, msg);
+    Err(errors::Reported {
+        message: msg.to_string(),
+        status,
     }
-    anyhow::bail!(msg.to_string())
+    .into())
 }
 
 /* ---- Tests (basic components) ---- */
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn reads_args_json_objects() {
+        let map = read_args_json(r#"{"filter": {"and": [{"a": 1}]}}"#, false).unwrap();
+        assert_eq!(map["filter"]["and"][0]["a"], 1);
+        assert!(read_args_json("[1, 2]", false).is_err());
+        assert!(read_args_json("{nope", false).is_err());
+        assert!(read_args_json("-", true).is_err());
+    }
+
+    #[test]
+    fn flags_destructive_tools() {
+        let patterns = vec!["DELETE*".to_string()];
+        let hinted = serde_json::json!({"name": "wipe", "annotations": {"destructiveHint": true}});
+        assert_eq!(
+            danger_reason_with(&hinted, &patterns).as_deref(),
+            Some("annotations.destructiveHint")
+        );
+        let named = serde_json::json!({"name": "delete_file"});
+        assert_eq!(
+            danger_reason_with(&named, &patterns).as_deref(),
+            Some("danger pattern 'DELETE*'")
+        );
+        let safe =
+            serde_json::json!({"name": "read_file", "annotations": {"destructiveHint": false}});
+        assert!(danger_reason_with(&safe, &patterns).is_none());
+    }
     // Import only for tests (runtime code does not need coerce_value directly)
-    use crate::cmd::shared::coerce_value;
+
+    #[test]
+    fn parses_batch_files() {
+        let calls = parse_batch(
+            "calls.yaml",
+            "- tool: read_file\n  arguments: {path: /etc/hosts}\n- tool: ping\n",
+        )
+        .unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].arguments["path"], "/etc/hosts");
+        assert!(calls[1].arguments.is_empty());
+        assert!(parse_batch("calls.json", "[]").is_err());
+        assert!(parse_batch("calls.json", r#"[{"tool": "a", "args": {}}]"#).is_err());
+    }
+
+    #[test]
+    fn compares_repeated_results() {
+        let call = |ms: u128, text: Option<&str>| BatchResult {
+            tool: "t".into(),
+            arguments: Default::default(),
+            result: text
+                .map(|t| rmcp::model::CallToolResult::success(vec![rmcp::model::Content::text(t)])),
+            error: text.is_none().then(|| "timed out".to_string()),
+            error_status: exit::INTERNAL,
+            elapsed_ms: ms,
+            output_problems: None,
+            notifications: Vec::new(),
+        };
+        let results = [
+            call(10, Some("a")),
+            call(30, Some("b")),
+            call(20, Some("a")),
+            call(50, None),
+        ];
+        assert_eq!(result_variants(&results), (vec![0, 1, 0, 2], 3));
+        assert_eq!(
+            latency_stats(&results),
+            serde_json::json!({"min": 10, "median": 25, "mean": 27, "max": 50})
+        );
+        assert_eq!(result_variants(&results[..1]).1, 1);
+    }
+
+    #[test]
+    fn exit_status_of_failed_calls() {
+        let call = |result: Option<rmcp::model::CallToolResult>| BatchResult {
+            tool: "t".into(),
+            arguments: Default::default(),
+            result,
+            error: None,
+            error_status: exit::SUCCESS,
+            elapsed_ms: 0,
+            output_problems: None,
+            notifications: Vec::new(),
+        };
+        let text = || vec![rmcp::model::Content::text("x")];
+        let ok = call(Some(rmcp::model::CallToolResult::success(text())));
+        let failed = call(Some(rmcp::model::CallToolResult::error(text())));
+        let mut missing = call(None);
+        missing.fail(exit::tool_not_found("nope"));
+        let mut strict = call(None);
+        strict.fail(
+            check_arguments(
+                &serde_json::json!({"name": "t", "inputSchema": {"type": "object", "properties": {}}}),
+                &serde_json::json!({"x": 1}).as_object().unwrap().clone(),
+                true,
+                false,
+            )
+            .unwrap_err(),
+        );
+        assert_eq!(ok.exit_status(true), None);
+        assert_eq!(failed.exit_status(true), Some(exit::TOOL_ERROR));
+        assert_eq!(failed.exit_status(false), None);
+        assert_eq!(missing.exit_status(false), Some(exit::TOOL_NOT_FOUND));
+        assert_eq!(missing.error.as_deref(), Some("tool 'nope' not found"));
+        assert_eq!(strict.exit_status(false), Some(exit::USAGE));
+    }
 
     #[test]
     fn param_file_json_merge() {
@@ -537,4 +2184,43 @@ mod tests {
         assert_eq!(coerce_value("yes", "boolean"), serde_json::json!(true));
         assert_eq!(coerce_value("No", "boolean"), serde_json::json!(false));
     }
+
+    #[test]
+    fn parses_prompt_answers() {
+        let mode =
+            serde_json::json!({"type": "string", "enum": ["fast", "slow"], "default": "slow"});
+        assert_eq!(
+            parse_answer(&mode, "2", true).unwrap().as_deref(),
+            Some("slow")
+        );
+        assert_eq!(
+            parse_answer(&mode, "fast", true).unwrap().as_deref(),
+            Some("fast")
+        );
+        assert_eq!(
+            parse_answer(&mode, "", true).unwrap().as_deref(),
+            Some("slow")
+        );
+        assert_eq!(parse_answer(&mode, "", false).unwrap(), None);
+        assert!(parse_answer(&mode, "3", true).is_err());
+
+        let port = serde_json::json!({"type": "integer", "minimum": 1});
+        assert_eq!(
+            parse_answer(&port, "x", true).unwrap_err().to_string(),
+            "expected integer, got string"
+        );
+        assert_eq!(
+            parse_answer(&port, "0", true).unwrap_err().to_string(),
+            "0 violates minimum 1"
+        );
+        assert_eq!(
+            parse_answer(&port, "", true).unwrap_err().to_string(),
+            "value required"
+        );
+        let levels = serde_json::json!({"type": "integer", "enum": [2, 1]});
+        assert_eq!(
+            parse_answer(&levels, "1", true).unwrap().as_deref(),
+            Some("1")
+        );
+    }
 }
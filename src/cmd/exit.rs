@@ -0,0 +1,169 @@
+/*!
+exit.rs - process exit status.
+
+Every command ends with one of these, so scripts can tell "the tool failed"
+from "the server never came up":
+
+  0    success
+  1    internal error (anything not classified below)
+  2    usage or target error (bad flags, unparsable or unsupported target,
+       arguments that do not validate)
+  3    connection failure (server did not spawn, initialize failed or timed
+       out, server exited early)
+  4    tool not found
+  5    the tool answered with an error result (isError; exec
+       --no-fail-on-error keeps 0)
+  6    findings at or above the gate (scan --fail-on, replay --fail-on-diff,
+       exec --expect-*, failed playbook steps / fleet targets gated the
+       same way)
+  130  interrupted (Ctrl-C; 143 on SIGTERM, see mcp::interrupt)
+
+Errors carry their status as a type: a failure is wrapped in [`Usage`],
+[`Connection`] or [`ToolNotFound`] where it happens, and [`for_error`] picks
+the status by downcasting the error chain; anything else is internal. A
+command that has already reported its outcome returns [`Status`] instead of
+exiting, so only main.rs ends the process.
+*/
+
+use std::fmt;
+
+pub const SUCCESS: i32 = 0;
+pub const INTERNAL: i32 = 1;
+pub const USAGE: i32 = 2;
+pub const CONNECTION: i32 = 3;
+pub const TOOL_NOT_FOUND: i32 = 4;
+pub const TOOL_ERROR: i32 = 5;
+pub const FINDINGS: i32 = 6;
+
+/// Usage or target error (exit 2).
+#[derive(Debug)]
+pub struct Usage(pub String);
+
+/// The server could not be started or reached (exit 3); shows as its cause.
+#[derive(Debug)]
+pub struct Connection(pub anyhow::Error);
+
+/// The named tool is not on the server (exit 4).
+#[derive(Debug)]
+pub struct ToolNotFound(pub String);
+
+/// The command has reported its outcome and ends with this status.
+#[derive(Debug)]
+pub struct Status(pub i32);
+
+impl fmt::Display for Usage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for Connection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The cause's own message; its chain follows through `source`
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for ToolNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "tool '{}' not found", self.0)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "exit status {}", self.0)
+    }
+}
+
+impl std::error::Error for Usage {}
+
+impl std::error::Error for Connection {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl std::error::Error for ToolNotFound {}
+
+impl std::error::Error for Status {}
+
+/// A [`Usage`] error.
+pub fn usage(message: impl Into<String>) -> anyhow::Error {
+    Usage(message.into()).into()
+}
+
+/// `err` as a [`Connection`] error.
+pub fn connection(err: impl Into<anyhow::Error>) -> anyhow::Error {
+    Connection(err.into()).into()
+}
+
+/// A [`ToolNotFound`] error.
+pub fn tool_not_found(tool: impl Into<String>) -> anyhow::Error {
+    ToolNotFound(tool.into()).into()
+}
+
+/// Status of one error in a chain, if it is one of the typed errors.
+fn status_of(err: &(dyn std::error::Error + 'static)) -> Option<i32> {
+    if let Some(Status(status)) = err.downcast_ref::<Status>() {
+        Some(*status)
+    } else if let Some(reported) = err.downcast_ref::<crate::cmd::errors::Reported>() {
+        Some(reported.status)
+    } else if err.is::<Usage>() {
+        Some(USAGE)
+    } else if err.is::<Connection>() {
+        Some(CONNECTION)
+    } else if err.is::<ToolNotFound>() {
+        Some(TOOL_NOT_FOUND)
+    } else {
+        None
+    }
+}
+
+/// Exit status for an error that reached the dispatcher: the outermost
+/// typed error in its chain decides.
+pub fn for_error(err: &anyhow::Error) -> i32 {
+    if crate::mcp::interrupt::interrupted() {
+        return crate::mcp::interrupt::EXIT_CODE;
+    }
+    err.chain().find_map(status_of).unwrap_or(INTERNAL)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn classifies_typed_errors() {
+        assert_eq!(for_error(&usage("remote exec not implemented yet")), USAGE);
+        let spawn = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let err = Err::<(), _>(connection(
+            anyhow::Error::from(spawn).context("Failed to spawn MCP process: nosuch"),
+        ))
+        .context("Failed to list tools")
+        .unwrap_err();
+        assert_eq!(for_error(&err), CONNECTION);
+        assert_eq!(
+            format!("{err:#}"),
+            "Failed to list tools: Failed to spawn MCP process: nosuch: entity not found"
+        );
+        assert_eq!(for_error(&tool_not_found("nope")), TOOL_NOT_FOUND);
+        assert_eq!(tool_not_found("nope").to_string(), "tool 'nope' not found");
+        assert_eq!(for_error(&Status(FINDINGS).into()), FINDINGS);
+    }
+
+    #[test]
+    fn server_text_does_not_pick_the_status() {
+        // Messages from the server (tool results, JSON-RPC errors) mention
+        // anything; only the error types count.
+        for msg in [
+            "Failed to initialize database",
+            "tool 'x' not found",
+            "connection refused by upstream",
+            "tools/call (--request-timeout) timed out after 2s",
+        ] {
+            assert_eq!(for_error(&anyhow::anyhow!("{msg}")), INTERNAL, "{msg}");
+        }
+    }
+}
@@ -0,0 +1,185 @@
+/*!
+expect.rs - result assertions for `exec tool` (`--expect-*`).
+
+Turns exec into a smoke test for CI: every option is repeatable and the
+call passes when all of them hold.
+  --expect-contains STR   the result text (joined text content) contains STR
+  --expect-regex RE       the result text matches RE anywhere (anchor with
+                          ^ $; regex crate syntax)
+  --expect-jsonpath EXPR  a JSONPath into the CallToolResult, optionally
+                          compared (see crate::utils::jsonpath):
+                          '$.structuredContent.count >= 1',
+                          '$.content[0].text == "pong"', '$.isError == false'
+
+Assertions are compiled before the server is started, so a typo fails at
+once (exit status 2). Outcomes are listed after the result and returned as
+`expectations` in JSON; when one fails the exit status is 6 (see
+crate::cmd::exit), after printing. An isError result whose assertions hold
+still exits 5 unless --no-fail-on-error.
+
+Example:
+  mcp-hack exec tool ping -t "python3 server.py" --expect-contains pong
+  mcp-hack exec tool stats -t "python3 server.py" --expect-jsonpath '$.structuredContent.users > 0'
+*/
+
+use anyhow::Result;
+use clap::Args;
+use regex::Regex;
+use serde_json::{Value, json};
+
+use crate::cmd::exit;
+use crate::scan::playbook::result_text;
+use crate::utils::jsonpath::Expr;
+use crate::utils::pattern::regex;
+
+/* ---- Argument Struct ---- */
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct ExpectArgs {
+    /// Fail unless the result text contains STR (repeatable)
+    #[arg(long = "expect-contains", value_name = "STR")]
+    pub contains: Vec<String>,
+
+    /// Fail unless the result text matches the regular expression (repeatable)
+    #[arg(long = "expect-regex", value_name = "RE")]
+    pub regex: Vec<String>,
+
+    /// Fail unless the JSONPath expression holds, e.g. '$.structuredContent.count >= 1' (repeatable)
+    #[arg(long = "expect-jsonpath", value_name = "EXPR")]
+    pub jsonpath: Vec<String>,
+}
+
+/// One compiled assertion.
+#[derive(Debug, Clone)]
+enum Check {
+    Contains(String),
+    Regex(Regex),
+    JsonPath(Expr),
+}
+
+/// A compiled assertion and how it was written.
+#[derive(Debug, Clone)]
+pub struct Expectation {
+    label: String,
+    check: Check,
+}
+
+/// Result of one assertion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub label: String,
+    /// Why it failed; None when it held.
+    pub failure: Option<String>,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({"expect": self.label, "passed": self.passed(), "failure": self.failure})
+    }
+}
+
+impl ExpectArgs {
+    pub fn is_empty(&self) -> bool {
+        self.contains.is_empty() && self.regex.is_empty() && self.jsonpath.is_empty()
+    }
+
+    /// Compile the assertions (bad expressions are reported by option).
+    pub fn compile(&self) -> Result<Vec<Expectation>> {
+        let mut out = Vec::new();
+        for s in &self.contains {
+            out.push(Expectation {
+                label: format!("contains {s:?}"),
+                check: Check::Contains(s.clone()),
+            });
+        }
+        for s in &self.regex {
+            let re = regex(s, false)
+                .map_err(|e| exit::usage(format!("invalid --expect-regex '{s}': {e:#}")))?;
+            out.push(Expectation {
+                label: format!("matches /{s}/"),
+                check: Check::Regex(re),
+            });
+        }
+        for s in &self.jsonpath {
+            let expr = Expr::parse(s)
+                .map_err(|e| exit::usage(format!("invalid --expect-jsonpath '{s}': {e:#}")))?;
+            out.push(Expectation {
+                label: s.trim().to_string(),
+                check: Check::JsonPath(expr),
+            });
+        }
+        Ok(out)
+    }
+}
+
+/// Evaluate `expectations` against a CallToolResult (as JSON).
+pub fn evaluate(expectations: &[Expectation], result: &Value) -> Vec<Outcome> {
+    let text = result_text(&json!({ "result": result }));
+    let short = || {
+        let s: String = text.chars().take(80).collect();
+        format!("result text {s:?}")
+    };
+    expectations
+        .iter()
+        .map(|e| {
+            let failure = match &e.check {
+                Check::Contains(s) => (!text.contains(s.as_str())).then(short),
+                Check::Regex(re) => (!re.is_match(&text)).then(short),
+                Check::JsonPath(expr) => expr.evaluate(result).err(),
+            };
+            Outcome {
+                label: e.label.clone(),
+                failure,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_against_result() {
+        let args = ExpectArgs {
+            contains: vec!["pong".into(), "nope".into()],
+            regex: vec!["^po+ng$".into()],
+            jsonpath: vec!["$.structuredContent.n >= 2".into(), "$.isError".into()],
+        };
+        let result = json!({
+            "content": [{"type": "text", "text": "pong"}],
+            "structuredContent": {"n": 2},
+        });
+        let outcomes = evaluate(&args.compile().unwrap(), &result);
+        let passed: Vec<bool> = outcomes.iter().map(Outcome::passed).collect();
+        assert_eq!(passed, [true, false, true, true, false]);
+        assert_eq!(outcomes[1].failure.as_deref(), Some("result text \"pong\""));
+        assert_eq!(outcomes[4].label, "$.isError");
+
+        let bad = ExpectArgs {
+            jsonpath: vec!["count > 1".into()],
+            ..Default::default()
+        };
+        let err = format!("{:#}", bad.compile().unwrap_err());
+        assert!(err.starts_with("invalid --expect-jsonpath"), "{err}");
+    }
+
+    #[test]
+    fn regex_on_large_results() {
+        let args = ExpectArgs {
+            regex: vec!["a.*b".into(), "^a+c$".into()],
+            ..Default::default()
+        };
+        let text = format!("{}c", "a".repeat(30_000));
+        let result = json!({"content": [{"type": "text", "text": text}]});
+        let passed: Vec<bool> = evaluate(&args.compile().unwrap(), &result)
+            .iter()
+            .map(Outcome::passed)
+            .collect();
+        assert_eq!(passed, [false, true]);
+    }
+}
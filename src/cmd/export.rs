@@ -0,0 +1,382 @@
+/*!
+export.rs - export subcommand.
+
+Writes what a server declares in a form other tools consume. Subjects:
+
+  schemas   JSON Schema documents of the tools, for contract tests and
+            schema diffing pipelines
+  client    typed wrapper functions for the tools (--lang ts|rust|python,
+            see crate::cmd::codegen), to stdout or --out FILE
+
+Each tool becomes one document (draft 2020-12) with its inputSchema and
+outputSchema copied verbatim under `$defs`:
+
+  {"$schema": "https://json-schema.org/draft/2020-12/schema",
+   "title": "read_file", "description": "...",
+   "$defs": {"input": {...}, "output": {...}}}
+
+By default one file per tool is written to --out-dir (`NAME.schema.json`,
+only `[A-Za-z0-9._-]` kept in NAME); --bundle FILE writes a single document
+instead, with each tool's document under `$defs.NAME` (`-`: stdout). The
+output carries no timestamp, so exporting an unchanged server gives
+identical files. A `$ref` such as `#/$defs/Item` inside a copied schema
+still points at that schema's own root, not the exported document.
+
+The source is a local target command or a snapshot file (see
+crate::cmd::diff); --filter / --grep pick tools (see crate::cmd::selection).
+
+Example:
+  mcp-hack export schemas -t "python3 server.py" --out-dir schemas/
+  mcp-hack export schemas tools.json --bundle tools.schema.json
+  mcp-hack export schemas -t "npx -y some-mcp-server" --bundle - --filter 'get_*'
+  mcp-hack export client --lang ts -t "python3 server.py" --out src/tools.ts
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Map, Value, json};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cmd::codegen::{self, Lang};
+use crate::cmd::diff::{Side, load_side};
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::cmd::selection::FilterArgs;
+
+pub const JSON_SCHEMA_DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+/// What `export` writes.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportSubject {
+    /// Tool input / output schemas as JSON Schema documents
+    Schemas,
+    /// Typed client stubs (--lang)
+    Client,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// What to export
+    pub subject: ExportSubject,
+
+    /// Target command or snapshot JSON file (default: --target / MCP_TARGET)
+    #[arg(value_name = "SOURCE")]
+    pub source: Option<String>,
+
+    /// Directory for the per-tool schema files (created if needed)
+    #[arg(long, value_name = "DIR", default_value = "schemas")]
+    pub out_dir: String,
+
+    /// Write one schema bundle with a $defs entry per tool instead (`-`: stdout)
+    #[arg(long, value_name = "FILE", conflicts_with = "out_dir")]
+    pub bundle: Option<String>,
+
+    /// Language of the client stubs
+    #[arg(long, value_enum, required_if_eq("subject", "client"))]
+    pub lang: Option<Lang>,
+
+    /// Write the client stubs to FILE instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub out: Option<String>,
+
+    #[command(flatten)]
+    pub select: FilterArgs,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_export(mut args: ExportArgs) -> Result<()> {
+    // Determine source (positional > CLI target > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let source = match args.source.as_deref().or(args.target.as_deref()) {
+        Some(s) if !s.trim().is_empty() => s.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no source specified (SOURCE, --target or MCP_TARGET)",
+            );
+        }
+    };
+
+    let started = Instant::now();
+    let mut side = match load_side(&source, Duration::from_secs(args.timeout)).await {
+        Ok(side) => side,
+        Err(e) => return report_error(args.output.is_json(), e.context(source.to_string())),
+    };
+    args.select.apply(&mut side.tools);
+    crate::utils::progress_events::phase("done", None);
+
+    match (args.subject, args.lang) {
+        (ExportSubject::Client, Some(lang)) => export_client(&args, lang, &side, started),
+        _ => export_schemas(&args, &side, started),
+    }
+}
+
+fn export_schemas(args: &ExportArgs, side: &Side, started: Instant) -> Result<()> {
+    let names = unique_names(&side.tools, false);
+    let written = match &args.bundle {
+        Some(path) => {
+            let doc = bundle(&side.source, &side.tools, &names);
+            let body = serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string());
+            if path == "-" {
+                println!("{body}");
+                return Ok(());
+            }
+            if let Err(e) = std::fs::write(path, body + "\n") {
+                return output_error(
+                    args.output.is_json(),
+                    &format!("failed to write {path}: {e}"),
+                );
+            }
+            vec![path.clone()]
+        }
+        None => match write_files(Path::new(&args.out_dir), &side.tools) {
+            Ok(files) => files,
+            Err(e) => return report_error(args.output.is_json(), e),
+        },
+    };
+    let extra = json!({"bundle": args.bundle, "files": written});
+    report(args, side, started, "schema(s)", &names, extra, &written)
+}
+
+fn export_client(args: &ExportArgs, lang: Lang, side: &Side, started: Instant) -> Result<()> {
+    let code = codegen::generate(lang, &side.source, &side.tools);
+    let Some(path) = args.out.as_deref().filter(|p| *p != "-") else {
+        print!("{code}");
+        return Ok(());
+    };
+    if let Err(e) = std::fs::write(path, code) {
+        return output_error(
+            args.output.is_json(),
+            &format!("failed to write {path}: {e}"),
+        );
+    }
+    let names = unique_names(&side.tools, false);
+    let extra = json!({"lang": lang.as_str(), "files": [path]});
+    report(
+        args,
+        side,
+        started,
+        "stub(s)",
+        &names,
+        extra,
+        &[path.to_string()],
+    )
+}
+
+/// Summary of what was written (`extra` is merged into the JSON output).
+fn report(
+    args: &ExportArgs,
+    side: &Side,
+    started: Instant,
+    what: &str,
+    names: &[String],
+    extra: Value,
+    written: &[String],
+) -> Result<()> {
+    let elapsed_ms = started.elapsed().as_millis();
+
+    if args.output.is_json() {
+        let mut out = json!({
+            "status": "ok",
+            "source": side.to_json(),
+            "elapsed_ms": elapsed_ms,
+            "count": side.tools.len(),
+            "tools": names,
+        });
+        if let (Some(out), Value::Object(extra)) = (out.as_object_mut(), extra) {
+            out.extend(extra);
+        }
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Export • {} {what}",
+            emoji("list", &style),
+            side.tools.len()
+        ),
+        Some(format!("{elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    println!("  Source {} ({})", side.source, side.kind);
+    println!();
+    for path in written {
+        println!("{} {path}", emoji("success", &style));
+    }
+    if side.tools.is_empty() {
+        println!(
+            "{} {}",
+            emoji("warn", &style),
+            color(Role::Warning, "No tools to export", &style)
+        );
+    }
+    Ok(())
+}
+
+/* ---- Documents ---- */
+
+/// `{title, description, $defs: {input, output}}` of one tool, schemas verbatim.
+fn tool_schema(tool: &Value) -> Map<String, Value> {
+    let mut doc = Map::new();
+    if let Some(name) = tool.get("name") {
+        doc.insert("title".into(), name.clone());
+    }
+    if let Some(desc) = tool.get("description").filter(|d| !d.is_null()) {
+        doc.insert("description".into(), desc.clone());
+    }
+    let mut defs = Map::new();
+    for (key, fields) in [
+        ("input", ["inputSchema", "input_schema"]),
+        ("output", ["outputSchema", "output_schema"]),
+    ] {
+        if let Some(schema) = fields.iter().find_map(|f| tool.get(*f)) {
+            defs.insert(key.into(), schema.clone());
+        }
+    }
+    doc.insert("$defs".into(), Value::Object(defs));
+    doc
+}
+
+/// The standalone document of one tool.
+pub fn tool_document(tool: &Value) -> Value {
+    let mut doc = Map::new();
+    doc.insert("$schema".into(), JSON_SCHEMA_DRAFT.into());
+    doc.extend(tool_schema(tool));
+    Value::Object(doc)
+}
+
+/// One document with every tool under `$defs.NAME` (`names` from unique_names).
+pub fn bundle(source: &str, tools: &[Value], names: &[String]) -> Value {
+    let defs: Map<String, Value> = names
+        .iter()
+        .zip(tools)
+        .map(|(name, tool)| (name.clone(), Value::Object(tool_schema(tool))))
+        .collect();
+    json!({
+        "$schema": JSON_SCHEMA_DRAFT,
+        "$comment": format!("Tool schemas exported by mcp-hack from {source}"),
+        "$defs": defs,
+    })
+}
+
+/// Tool names, made distinct (`name-2`, ...) and, with `file_safe`, reduced
+/// to `[A-Za-z0-9._-]` so a server cannot point a file outside the directory.
+pub fn unique_names(tools: &[Value], file_safe: bool) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tools
+        .iter()
+        .map(|tool| {
+            let raw = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let mut base = if file_safe {
+                raw.chars()
+                    .map(|c| {
+                        if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                            c
+                        } else {
+                            '_'
+                        }
+                    })
+                    .collect::<String>()
+                    .trim_start_matches('.')
+                    .to_string()
+            } else {
+                raw.to_string()
+            };
+            if base.is_empty() {
+                base = "tool".into();
+            }
+            let mut name = base.clone();
+            let mut n = 1;
+            while !seen.insert(name.to_lowercase()) {
+                n += 1;
+                name = format!("{base}-{n}");
+            }
+            name
+        })
+        .collect()
+}
+
+/// Write `DIR/NAME.schema.json` per tool; returns the paths written.
+fn write_files(dir: &Path, tools: &[Value]) -> Result<Vec<String>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+    let mut written = Vec::new();
+    for (tool, name) in tools.iter().zip(unique_names(tools, true)) {
+        let path = dir.join(format!("{name}.schema.json"));
+        let doc = tool_document(tool);
+        let body = serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string());
+        std::fs::write(&path, body + "\n")
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        written.push(path.display().to_string());
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_schema_documents() {
+        let tools = vec![
+            json!({
+                "name": "read_file",
+                "description": "Read a file",
+                "inputSchema": {"type": "object", "properties": {"path": {"$ref": "#/$defs/P"}}, "$defs": {"P": {"type": "string"}}},
+                "outputSchema": {"type": "object"},
+            }),
+            json!({"name": "../etc/passwd", "inputSchema": {"type": "object"}}),
+            json!({"name": "READ_FILE", "inputSchema": {}}),
+        ];
+        let doc = tool_document(&tools[0]);
+        assert_eq!(doc["$schema"], JSON_SCHEMA_DRAFT);
+        assert_eq!(doc["title"], "read_file");
+        assert_eq!(doc["$defs"]["input"], tools[0]["inputSchema"]);
+        assert_eq!(doc["$defs"]["output"], tools[0]["outputSchema"]);
+        assert!(tool_document(&tools[1])["$defs"].get("output").is_none());
+
+        assert_eq!(
+            unique_names(&tools, true),
+            ["read_file", "_etc_passwd", "READ_FILE-2"]
+        );
+        let names = unique_names(&tools, false);
+        assert_eq!(names[1], "../etc/passwd");
+        let b = bundle("server.py", &tools, &names);
+        assert_eq!(
+            b["$defs"]["read_file"]["$defs"]["input"],
+            tools[0]["inputSchema"]
+        );
+        assert!(b["$defs"]["read_file"].get("$schema").is_none());
+        assert_eq!(b["$defs"].as_object().unwrap().len(), 3);
+    }
+}
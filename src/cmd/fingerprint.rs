@@ -0,0 +1,221 @@
+/*!
+fingerprint.rs - fingerprint subcommand.
+
+Guesses what the target is built with (official TypeScript / Python SDK,
+FastMCP, rmcp, Go, known vendor servers) from serverInfo, capability
+serialization quirks, error message formats, handshake timing and the
+server process tree, then lists known issues for that stack (see
+crate::scan::fingerprint). Results are ranked heuristics with the
+evidence behind each score.
+
+Probes sent: an unknown method, tools/call for a tool that does not exist
+and tools/call without a tool name. No real tool is invoked.
+
+Example:
+  mcp-hack fingerprint -t "npx -y @modelcontextprotocol/server-filesystem /tmp"
+  mcp-hack fingerprint -t "uvx mcp-server-fetch" --json
+  mcp-hack fingerprint --targets fleet.txt --parallel 4
+
+Several targets (-t repeated / --targets FILE) give one combined report
+(see crate::cmd::fleet).
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::fleet::FleetArgs;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::scan::fingerprint::{self, KnownIssue, stack_label};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct FingerprintArgs {
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    /// Repeat (or use --targets) to run against several targets.
+    #[arg(short = 't', long = "target", id = "target", value_name = "TARGET")]
+    pub targets: Vec<String>,
+
+    #[command(flatten)]
+    pub fleet: FleetArgs,
+
+    /// Effective single target (set by the dispatcher)
+    #[arg(skip)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_fingerprint(mut args: FingerprintArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote fingerprinting not implemented yet",
+        );
+    }
+
+    let started = Instant::now();
+    let obs = match fingerprint::observe(&spec, Duration::from_secs(args.timeout)).await {
+        Ok(o) => o,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let fp = fingerprint::analyze(&obs);
+    let issues = fp.known_issues();
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+    let server = obs.initialize.get("serverInfo").cloned();
+
+    if args.output.is_json() {
+        let out = json!({
+            "status": "ok",
+            "target": target_raw,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "server": server,
+            "protocol_version": obs.initialize.get("protocolVersion"),
+            "stack": fp.candidates.first().map(|c| c.stack),
+            "product": fp.product,
+            "candidates": fp.candidates.iter().map(|c| json!({
+                "stack": c.stack,
+                "label": stack_label(c.stack),
+                "score": c.score,
+                "evidence": c.evidence,
+            })).collect::<Vec<_>>(),
+            "known_issues": issues.iter().map(|i| i.to_json()).collect::<Vec<_>>(),
+            "observations": obs.to_json(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let verdict = fp
+        .candidates
+        .first()
+        .map_or("unknown", |c| stack_label(c.stack));
+    let header = box_header(
+        format!("{} Fingerprint • {verdict}", emoji("spark", &style)),
+        Some(format!("target={target_raw} • {elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    let info = |k: &str| {
+        server
+            .as_ref()
+            .and_then(|s| s.get(k))
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
+            .to_string()
+    };
+    println!(
+        "{} server {} {} • initialize {} ms",
+        emoji("info", &style),
+        info("name"),
+        info("version"),
+        obs.initialize_ms
+    );
+    if !obs.executables.is_empty() {
+        println!(
+            "{} processes: {}{}",
+            emoji("info", &style),
+            obs.executables.join(", "),
+            obs.native
+                .map(|n| format!(" (native: {n})"))
+                .unwrap_or_default()
+        );
+    }
+    if let Some(product) = fp.product {
+        println!("{} product: {product}", emoji("tool", &style));
+    }
+    println!();
+    if fp.candidates.is_empty() {
+        println!(
+            "{} {}",
+            emoji("warn", &style),
+            color(Role::Warning, "No signature matched", &style)
+        );
+    } else {
+        let rows: Vec<Vec<String>> = fp
+            .candidates
+            .iter()
+            .map(|c| {
+                vec![
+                    stack_label(c.stack).to_string(),
+                    c.score.to_string(),
+                    c.evidence.join("; "),
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["STACK", "SCORE", "EVIDENCE"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+    }
+    if !issues.is_empty() {
+        println!();
+        println!(
+            "{} Known issues (verify the installed version):",
+            emoji("warn", &style)
+        );
+        for KnownIssue {
+            id,
+            title,
+            fixed_in,
+            ..
+        } in issues
+        {
+            println!(
+                "  {} {title} {}",
+                color(Role::Warning, id, &style),
+                color(Role::Dim, format!("(fixed in {fixed_in})"), &style)
+            );
+        }
+    }
+    Ok(())
+}
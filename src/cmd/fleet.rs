@@ -0,0 +1,523 @@
+/*!
+fleet.rs - several targets for list / scan / fingerprint.
+
+With more than one target (repeated -t and/or --targets FILE, one target
+per line, blank lines and `#` comments skipped) the command runs once per
+target and the results are combined into one report keyed by target:
+  - text     a table of targets with status, summary and duration
+  - json     {"results": {TARGET: <the command's --json output>, ...}}
+  - sarif    (scan) one SARIF log with a run per target
+  - markdown / csv: the text table; yaml: the json report
+
+Each target runs in its own mcp-hack process (the parsed command line
+rebuilt with -t TARGET --format json in place of the target, --docker and
+format options), --parallel N at a time, so a hanging or crashing
+server only affects its own entry. --record, --report and --export are
+per-target outputs and are rejected with several targets; --query applies
+to the combined report.
+
+Exit status: that of the first failed target (see crate::cmd::exit), else
+6 when one tripped a gate (scan --fail-on), after printing the combined
+report.
+
+Example:
+  mcp-hack scan --targets fleet.txt --parallel 4 --json
+  mcp-hack list tools -t "python3 a.py" -t "python3 b.py"
+*/
+
+use anyhow::{Context, Result, bail};
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, Args};
+use serde_json::{Map, Value, json};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::cmd::exec::report_error;
+use crate::cmd::exit;
+use crate::cmd::format::{
+    Role, StyleOptions, TableOpts, box_header, color, csv_table, emoji, markdown_table, table,
+};
+use crate::cmd::output::{OutputFormat, print_document, print_json};
+use crate::scan::Severity;
+
+/* ---- Argument Struct ---- */
+
+#[derive(Args, Debug, Default)]
+pub struct FleetArgs {
+    /// File listing targets, one per line (combines with repeated -t)
+    #[arg(long = "targets", value_name = "PATH")]
+    pub targets_file: Option<String>,
+
+    /// Targets processed at the same time when there are several
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    pub parallel: u64,
+}
+
+/// Targets listed in a file.
+pub fn load_targets(path: &str) -> Result<Vec<String>> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read targets {path}"))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Arguments replaced in child command lines (by id): the target options,
+/// --docker (the targets carry the image) and the output format.
+const REPLACED: &[&str] = &[
+    "target",
+    "docker",
+    "targets_file",
+    "parallel",
+    "format",
+    "json",
+    "ndjson",
+    "query",
+];
+const PER_TARGET: &[&str] = &[
+    "record",
+    "report",
+    "export",
+    "stderr_file",
+    "update_baseline",
+];
+/// Refused only with a file value (`--wire-log` alone logs to stderr).
+const PER_TARGET_FILE: &[&str] = &["wire_log"];
+
+/// Arguments of this invocation for one child, rebuilt from what clap
+/// parsed (`matches` against `command`): options given on the command line
+/// as `--name=value`, so values are passed on as given, without the target
+/// and format options.
+pub fn child_args(command: &clap::Command, matches: &ArgMatches) -> Result<Vec<String>> {
+    let mut out = Vec::new();
+    let mut command = command.clone();
+    command.build();
+    push_args(&command, matches, true, &mut out)?;
+    Ok(out)
+}
+
+fn push_args(
+    command: &clap::Command,
+    matches: &ArgMatches,
+    top: bool,
+    out: &mut Vec<String>,
+) -> Result<()> {
+    let mut positionals = Vec::new();
+    for arg in command.get_arguments() {
+        let id = arg.get_id().as_str();
+        // Global options are taken where they are defined.
+        if (!top && arg.is_global_set())
+            || REPLACED.contains(&id)
+            || matches.try_contains_id(id).ok() != Some(true)
+            || matches.value_source(id) != Some(ValueSource::CommandLine)
+        {
+            continue;
+        }
+        let values: Vec<String> = matches
+            .get_raw(id)
+            .into_iter()
+            .flatten()
+            .map(|v| v.to_string_lossy().into_owned())
+            .collect();
+        let name = match (arg.get_long(), arg.get_short()) {
+            (Some(long), _) => format!("--{long}"),
+            (None, Some(short)) => format!("-{short}"),
+            (None, None) => {
+                positionals.extend(values);
+                continue;
+            }
+        };
+        if PER_TARGET.contains(&id) || (PER_TARGET_FILE.contains(&id) && values != ["-"]) {
+            bail!("{name} is not supported with several targets");
+        }
+        match arg.get_action() {
+            ArgAction::SetTrue | ArgAction::SetFalse => out.push(name),
+            ArgAction::Count => {
+                let n = matches.get_count(id);
+                out.extend((0..n).map(|_| name.clone()));
+            }
+            ArgAction::Set | ArgAction::Append => {
+                let sep = if name.starts_with("--") { "=" } else { "" };
+                out.extend(values.iter().map(|v| format!("{name}{sep}{v}")));
+            }
+            _ => {}
+        }
+    }
+    match matches.subcommand() {
+        Some((name, sub)) => {
+            out.extend(positionals);
+            out.push(name.to_string());
+            let sub_command = command
+                .find_subcommand(name)
+                .with_context(|| format!("unknown subcommand {name}"))?;
+            push_args(sub_command, sub, false, out)?;
+        }
+        None => {
+            if positionals.iter().any(|v| v.starts_with('-')) {
+                out.push("--".to_string());
+            }
+            out.extend(positionals);
+        }
+    }
+    Ok(())
+}
+
+/// Outcome of one target's run.
+pub(crate) struct Outcome {
+    pub target: String,
+    pub exit_code: i32,
+    pub result: Value,
+    pub ms: u128,
+}
+
+impl Outcome {
+    fn status(&self) -> &'static str {
+        match self.exit_code {
+            exit::SUCCESS => "ok",
+            exit::FINDINGS => "gated",
+            _ => "error",
+        }
+    }
+}
+
+/// The JSON document a child printed (log lines before it are skipped).
+fn parse_output(stdout: &str) -> Option<Value> {
+    let text = stdout.trim();
+    serde_json::from_str(text).ok().or_else(|| {
+        let start = text.find("\n{").map(|i| i + 1)?;
+        serde_json::from_str(&text[start..]).ok()
+    })
+}
+
+/// Run `mcp-hack <base> -q -t TARGET --format FORMAT` in a child process.
+pub(crate) fn run_one(base: &[String], target: &str, format: &str) -> Outcome {
+    let started = Instant::now();
+    let output = std::env::current_exe().and_then(|exe| {
+        Command::new(exe)
+            .args(base)
+            .args(["-q", "-t", target, "--format", format])
+            .stdin(Stdio::null())
+            .stderr(Stdio::inherit())
+            .output()
+    });
+    let (exit_code, result) = match output {
+        Ok(o) => {
+            let stdout = String::from_utf8_lossy(&o.stdout);
+            let code = o.status.code().unwrap_or(1);
+            match parse_output(&stdout) {
+                Some(v) => (code, v),
+                None => (
+                    code.max(1),
+                    json!({"status": "error", "error": format!("no JSON output (exit status {code})")}),
+                ),
+            }
+        }
+        Err(e) => (
+            1,
+            json!({"status": "error", "error": format!("failed to run: {e}")}),
+        ),
+    };
+    Outcome {
+        target: crate::mcp::redact_target(target),
+        exit_code,
+        result,
+        ms: started.elapsed().as_millis(),
+    }
+}
+
+/// One-line summary of a command's JSON output.
+fn summarize(result: &Value) -> String {
+    if let Some(err) = result.get("error").and_then(|v| v.as_str()) {
+        return err.to_string();
+    }
+    if let Some(summary) = result.get("summary").and_then(|v| v.as_object())
+        && result.get("findings").is_some()
+    {
+        let counts: Vec<String> = Severity::ALL
+            .iter()
+            .filter_map(|s| {
+                let n = summary.get(s.as_str()).and_then(|v| v.as_u64())?;
+                (n > 0).then(|| format!("{n} {}", s.as_str()))
+            })
+            .collect();
+        let total = result["findings"].as_array().map_or(0, Vec::len);
+        return if counts.is_empty() {
+            "no findings".to_string()
+        } else {
+            format!("{total} finding(s): {}", counts.join(", "))
+        };
+    }
+    if let Some(stack) = result.get("stack") {
+        let product = result.get("product").and_then(|v| v.as_str());
+        return match (stack.as_str(), product) {
+            (_, Some(p)) => p.to_string(),
+            (Some(s), None) => crate::scan::fingerprint::stack_label(s).to_string(),
+            (None, None) => "unknown stack".to_string(),
+        };
+    }
+    if let Some(n) = result.get("count").and_then(|v| v.as_u64()) {
+        return format!("{n} item(s)");
+    }
+    String::new()
+}
+
+/* ---- Public Entry Point ---- */
+
+/// Run `command` against every target; `cli` / `matches` are the command
+/// line definition and what was parsed from it (see child_args).
+pub fn execute_fleet(
+    command: &str,
+    targets: &[String],
+    fleet: &FleetArgs,
+    output: OutputFormat,
+    cli: &clap::Command,
+    matches: &ArgMatches,
+) -> Result<()> {
+    let base = match child_args(cli, matches) {
+        Ok(b) => b,
+        Err(e) => return report_error(output.is_json(), e),
+    };
+    let format = if output == OutputFormat::Sarif {
+        "sarif"
+    } else {
+        "json"
+    };
+
+    let started = Instant::now();
+    let next = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<Outcome>>> =
+        Mutex::new((0..targets.len()).map(|_| None).collect());
+    let workers = (fleet.parallel as usize).min(targets.len()).max(1);
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(target) = targets.get(i) else { break };
+                    crate::utils::progress_events::phase(
+                        "target",
+                        Some(&crate::mcp::redact_target(target)),
+                    );
+                    let outcome = run_one(&base, target, format);
+                    if let Ok(mut all) = outcomes.lock() {
+                        all[i] = Some(outcome);
+                    }
+                }
+            });
+        }
+    });
+    let outcomes: Vec<Outcome> = outcomes
+        .into_inner()
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect();
+    let elapsed_ms = started.elapsed().as_millis();
+    let count = |status: &str| outcomes.iter().filter(|o| o.status() == status).count();
+    let (ok, gated, failed) = (count("ok"), count("gated"), count("error"));
+
+    match output {
+        OutputFormat::Sarif => {
+            let runs: Vec<Value> = outcomes
+                .iter()
+                .filter_map(|o| o.result.get("runs").and_then(|v| v.as_array()))
+                .flatten()
+                .cloned()
+                .collect();
+            let mut log = outcomes
+                .iter()
+                .find(|o| o.result.get("runs").is_some())
+                .map_or_else(|| json!({"version": "2.1.0"}), |o| o.result.clone());
+            log["runs"] = Value::Array(runs);
+            print_json(&log);
+        }
+        OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Yaml => {
+            let results: Map<String, Value> = outcomes
+                .iter()
+                .map(|o| (o.target.clone(), o.result.clone()))
+                .collect();
+            let out = json!({
+                "status": "ok",
+                "command": command,
+                "elapsed_ms": elapsed_ms,
+                "summary": {"targets": outcomes.len(), "ok": ok, "gated": gated, "failed": failed},
+                "results": results,
+            });
+            if output == OutputFormat::Yaml {
+                print_document(output, &out);
+            } else {
+                print_json(&out);
+            }
+        }
+        OutputFormat::Markdown | OutputFormat::Csv => {
+            let rows: Vec<Vec<String>> = outcomes
+                .iter()
+                .map(|o| {
+                    vec![
+                        o.target.clone(),
+                        o.status().to_string(),
+                        summarize(&o.result),
+                        o.ms.to_string(),
+                    ]
+                })
+                .collect();
+            let headers = ["TARGET", "STATUS", "SUMMARY", "MS"];
+            if output == OutputFormat::Csv {
+                print!("{}", csv_table(&headers, &rows));
+            } else {
+                println!(
+                    "## {command} • {} target(s)\n\n{ok} ok / {gated} gated / {failed} failed • {elapsed_ms}ms\n",
+                    outcomes.len()
+                );
+                println!("{}", markdown_table(&headers, &rows));
+            }
+        }
+        OutputFormat::Text => {
+            let style = StyleOptions::detect();
+            let header = box_header(
+                format!(
+                    "{} {command} • {} target(s)",
+                    emoji("list", &style),
+                    outcomes.len()
+                ),
+                Some(format!(
+                    "{ok} ok / {gated} gated / {failed} failed • {elapsed_ms}ms"
+                )),
+                &style,
+            );
+            println!("{header}");
+            let rows: Vec<Vec<String>> = outcomes
+                .iter()
+                .map(|o| {
+                    let role = match o.status() {
+                        "ok" => Role::Success,
+                        "gated" => Role::Warning,
+                        _ => Role::Error,
+                    };
+                    vec![
+                        o.target.clone(),
+                        color(role, o.status(), &style),
+                        summarize(&o.result),
+                        o.ms.to_string(),
+                    ]
+                })
+                .collect();
+            let tbl = table(
+                &["TARGET", "STATUS", "SUMMARY", "MS"],
+                &rows,
+                TableOpts {
+                    max_width: style.term_width,
+                    ..Default::default()
+                },
+                &style,
+            );
+            println!("{tbl}");
+        }
+    }
+    if let Some(o) = outcomes.iter().find(|o| o.status() == "error") {
+        return Err(exit::Status(o.exit_code).into());
+    }
+    if gated > 0 {
+        return Err(exit::Status(exit::FINDINGS).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[arg(short, long, action = ArgAction::Count, global = true)]
+        verbose: u8,
+        #[arg(short = 't', long = "target", global = true)]
+        target: Option<String>,
+        #[arg(long, global = true, conflicts_with = "target")]
+        docker: Option<String>,
+        #[arg(short = 'e', long = "env", global = true)]
+        env: Vec<String>,
+        #[arg(long, global = true)]
+        json: bool,
+        #[arg(long, global = true)]
+        query: Option<String>,
+        #[arg(long = "wire-log", global = true, num_args = 0..=1, require_equals = true, default_missing_value = "-")]
+        wire_log: Option<String>,
+        #[command(subcommand)]
+        command: Sub,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum Sub {
+        Scan {
+            #[arg(short = 't', long = "target", id = "target")]
+            targets: Vec<String>,
+            #[arg(long)]
+            passive: bool,
+            #[arg(long)]
+            report: Option<String>,
+            #[arg(long)]
+            token: Option<String>,
+            #[command(flatten)]
+            fleet: FleetArgs,
+        },
+    }
+
+    fn rebuild(argv: &[&str]) -> Result<Vec<String>> {
+        use clap::CommandFactory;
+        let command = Cli::command();
+        let matches = command
+            .clone()
+            .try_get_matches_from(std::iter::once("mcp-hack").chain(argv.iter().copied()))?;
+        child_args(&command, &matches)
+    }
+
+    #[test]
+    fn child_args_drop_target_and_format_options() {
+        let args = rebuild(&[
+            "-vv",
+            "--docker",
+            "mcp/fetch",
+            "scan",
+            "--targets",
+            "f.txt",
+            "--parallel=4",
+            "--json",
+            "-tb",
+            "--token=-tsecret",
+            "--passive",
+            "-e",
+            "K=-t",
+            "--target=c",
+            "--query",
+            ".summary",
+        ])
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "--verbose",
+                "--verbose",
+                "--env=K=-t",
+                "scan",
+                "--passive",
+                "--token=-tsecret"
+            ]
+        );
+        assert!(rebuild(&["scan", "--report=r.html"]).is_err());
+        assert_eq!(
+            rebuild(&["--wire-log", "scan"]).unwrap(),
+            vec!["--wire-log=-", "scan"]
+        );
+        assert!(rebuild(&["--wire-log=w.log", "scan"]).is_err());
+
+        let scan = json!({"summary": {"high": 1, "low": 2}, "findings": [{}, {}, {}]});
+        assert_eq!(summarize(&scan), "3 finding(s): 1 high, 2 low");
+        assert_eq!(summarize(&json!({"count": 4})), "4 item(s)");
+    }
+}
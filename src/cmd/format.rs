@@ -1,20 +1,34 @@
 /*!
 Formatting utilities: color, emoji, boxed headers, tables, wrapping, truncation.
 
-Used only for human output; JSON paths must remain free of formatting codes.
+Used only for human output; JSON paths must remain free of formatting codes,
+except `json_highlight`, which cmd::output uses only when stdout is a
+terminal.
 
 Env toggles:
   NO_COLOR  disable ANSI
   NO_EMOJI  disable emoji
 
+Global flags (main.rs, `set_flags`): --no-emoji, --no-border (headers
+without the box), --plain (no color, emoji or borders) for scripts and
+terminals where they break alignment.
+
 Key API:
   StyleOptions::detect
   color / emoji
   box_header / table
   wrap_text / truncate_ellipsis
+  markdown_table / csv_table / yaml   (--format markdown|csv|yaml backends)
+  json_highlight   (pretty JSON with colored keys / strings / numbers)
+
+The document backends never truncate and never add color or emoji. Cells
+hold server-controlled text, so Markdown cells are escaped (no HTML, links
+or table breaks) and CSV cells that a spreadsheet would run as a formula
+(`=`, `+`, `-`, `@`) are prefixed with `'`.
 */
 
 use std::borrow::Cow;
+use std::sync::OnceLock;
 
 /* ---- Style Options ---- */
 
@@ -22,12 +36,14 @@ use std::borrow::Cow;
 pub struct StyleOptions {
     pub use_color: bool,
     pub use_emoji: bool,
+    pub use_border: bool,
     pub term_width: usize,
     pub box_style: BoxStyle,
     pub padding: usize,
 }
 
 #[derive(Debug, Clone, Copy)]
+#[allow(dead_code)] // Rounded is selectable by callers; default stays Light
 pub enum BoxStyle {
     Light,   // ─ │ ┌ ┐ └ ┘
     Rounded, // ╭ ╮ ╰ ╯
@@ -39,10 +55,29 @@ impl Default for StyleOptions {
     }
 }
 
+/// Style flags given on the command line.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StyleFlags {
+    pub plain: bool,
+    pub no_emoji: bool,
+    pub no_border: bool,
+}
+
+static FLAGS: OnceLock<StyleFlags> = OnceLock::new();
+
+/// Install the command line's style flags (first call wins).
+pub fn set_flags(flags: StyleFlags) {
+    let _ = FLAGS.set(flags);
+}
+
 impl StyleOptions {
     pub fn detect() -> Self {
-        let no_color = std::env::var_os("NO_COLOR").is_some();
-        let no_emoji = std::env::var_os("NO_EMOJI").is_some();
+        Self::with_flags(FLAGS.get().copied().unwrap_or_default())
+    }
+
+    fn with_flags(flags: StyleFlags) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some() || flags.plain;
+        let no_emoji = std::env::var_os("NO_EMOJI").is_some() || flags.no_emoji || flags.plain;
         let use_color = !no_color;
         let use_emoji = !no_emoji;
 
@@ -55,6 +90,7 @@ impl StyleOptions {
         StyleOptions {
             use_color,
             use_emoji,
+            use_border: !(flags.no_border || flags.plain),
             term_width: width,
             box_style: BoxStyle::Light,
             padding: 1,
@@ -65,6 +101,7 @@ impl StyleOptions {
 /* ---- Color / Emoji ---- */
 
 #[derive(Debug, Clone, Copy)]
+#[allow(dead_code)] // full palette; not every role has a caller yet
 pub enum Role {
     Primary,
     Secondary,
@@ -77,6 +114,21 @@ pub enum Role {
     Bold,
 }
 
+/// Role of a unified-diff line (headers, additions, removals, hunks, context).
+pub fn diff_line_role(line: &str) -> Role {
+    if line.starts_with("+++") || line.starts_with("---") {
+        Role::Bold
+    } else if line.starts_with('+') {
+        Role::Success
+    } else if line.starts_with('-') {
+        Role::Error
+    } else if line.starts_with("@@") {
+        Role::Accent
+    } else {
+        Role::Dim
+    }
+}
+
 pub fn color(role: Role, text: impl AsRef<str>, style: &StyleOptions) -> String {
     if !style.use_color {
         return text.as_ref().to_string();
@@ -120,7 +172,8 @@ pub fn box_header(
     subtitle: Option<impl AsRef<str>>,
     style: &StyleOptions,
 ) -> String {
-    let title = title.as_ref();
+    // Without emoji a title may start with the separator space.
+    let title = title.as_ref().trim_start();
     let sub = subtitle.as_ref().map(|s| s.as_ref());
 
     let (h, v, tl, tr, bl, br) = match style.box_style {
@@ -128,7 +181,7 @@ pub fn box_header(
         BoxStyle::Rounded => ('─', '│', '╭', '╮', '╰', '╯'),
     };
 
-    let content_width = style.term_width.min(200).max(20);
+    let content_width = style.term_width.clamp(20, 200);
     let padding = style.padding;
     let mut lines: Vec<String> = Vec::new();
 
@@ -148,6 +201,10 @@ pub fn box_header(
         None => title_styled,
     };
 
+    if !style.use_border {
+        return inner_title;
+    }
+
     let inner_len = strip_ansi(&inner_title).chars().count();
     // Box width = min(requested, inner_len + borders + padding)
     let total_inner = (inner_len + padding * 2).min(content_width - 2);
@@ -284,7 +341,7 @@ pub fn table(
         let mut overflow = total_raw - width_limit;
         // shrink from the widest columns
         let mut ordered: Vec<(usize, usize)> = widths.iter().copied().enumerate().collect();
-        ordered.sort_by(|a, b| b.1.cmp(&a.1)); // desc by width
+        ordered.sort_by_key(|o| std::cmp::Reverse(o.1)); // desc by width
         for (idx, _) in ordered {
             if overflow == 0 {
                 break;
@@ -324,12 +381,12 @@ pub fn table(
     }
 
     for (r_idx, row) in rows.iter().enumerate() {
-        for c in 0..col_count {
+        for (c, width) in widths.iter().enumerate() {
             if c > 0 {
                 out.push_str("  ");
             }
             let raw = row.get(c).map(|s| s.as_str()).unwrap_or("");
-            let cell = pad_or_truncate(raw, widths[c], opts.truncate);
+            let cell = pad_or_truncate(raw, *width, opts.truncate);
             if opts.zebra && (r_idx % 2 == 1) && style.use_color {
                 out.push_str(&color(Role::Dim, cell, style));
             } else {
@@ -375,6 +432,129 @@ fn pad_or_truncate(s: &str, width: usize, truncate: bool) -> String {
     out
 }
 
+/* ---- Document Backends ---- */
+
+/// Escape text for a Markdown table cell (or inline text).
+pub fn markdown_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.trim().chars() {
+        match c {
+            '\\' | '|' | '`' | '*' | '[' | ']' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\r' => {}
+            '\n' => out.push_str("<br>"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// GitHub-flavored Markdown table.
+pub fn markdown_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |", cells.join(" | "));
+    let mut out = vec![
+        line(headers.iter().map(|h| markdown_escape(h)).collect()),
+        line(headers.iter().map(|_| "---".to_string()).collect()),
+    ];
+    for row in rows {
+        out.push(line(
+            (0..headers.len())
+                .map(|i| markdown_escape(row.get(i).map_or("", |c| c.as_str())))
+                .collect(),
+        ));
+    }
+    out.join("\n")
+}
+
+fn csv_cell(s: &str) -> String {
+    let s = if s.starts_with(['=', '+', '-', '@', '\t', '\r']) && s != "-" {
+        Cow::Owned(format!("'{s}"))
+    } else {
+        Cow::Borrowed(s)
+    };
+    if s.contains([',', '"', '\n', '\r']) || s.starts_with(' ') || s.ends_with(' ') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.into_owned()
+    }
+}
+
+/// CSV (RFC 4180 quoting) with a header row; ends with a newline.
+pub fn csv_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut out = String::new();
+    let mut push = |cells: Vec<&str>| {
+        out.push_str(
+            &cells
+                .into_iter()
+                .map(csv_cell)
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+        out.push('\n');
+    };
+    push(headers.to_vec());
+    for row in rows {
+        push(
+            (0..headers.len())
+                .map(|i| row.get(i).map_or("", |c| c.as_str()))
+                .collect(),
+        );
+    }
+    out
+}
+
+/// YAML rendering of a JSON document; ends with a newline.
+pub fn yaml(doc: &serde_json::Value) -> String {
+    serde_yaml::to_string(doc).unwrap_or_else(|_| format!("{doc}\n"))
+}
+
+/// Pretty JSON in serde_json's layout, colored by role: keys Primary,
+/// strings Success, numbers Accent, booleans and null Warning.
+pub fn json_highlight(doc: &serde_json::Value, style: &StyleOptions) -> String {
+    let mut out = String::new();
+    write_json(doc, 0, style, &mut out);
+    out
+}
+
+fn write_json(v: &serde_json::Value, depth: usize, style: &StyleOptions, out: &mut String) {
+    use serde_json::Value;
+    let quoted = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let indent = |out: &mut String, depth: usize| out.push_str(&"  ".repeat(depth));
+    match v {
+        Value::Object(m) if m.is_empty() => out.push_str("{}"),
+        Value::Array(a) if a.is_empty() => out.push_str("[]"),
+        Value::Object(m) => {
+            out.push_str("{\n");
+            for (i, (k, v)) in m.iter().enumerate() {
+                indent(out, depth + 1);
+                out.push_str(&color(Role::Primary, quoted(k), style));
+                out.push_str(": ");
+                write_json(v, depth + 1, style, out);
+                out.push_str(if i + 1 < m.len() { ",\n" } else { "\n" });
+            }
+            indent(out, depth);
+            out.push('}');
+        }
+        Value::Array(a) => {
+            out.push_str("[\n");
+            for (i, v) in a.iter().enumerate() {
+                indent(out, depth + 1);
+                write_json(v, depth + 1, style, out);
+                out.push_str(if i + 1 < a.len() { ",\n" } else { "\n" });
+            }
+            indent(out, depth);
+            out.push(']');
+        }
+        Value::String(s) => out.push_str(&color(Role::Success, quoted(s), style)),
+        Value::Number(n) => out.push_str(&color(Role::Accent, n.to_string(), style)),
+        Value::Bool(_) | Value::Null => out.push_str(&color(Role::Warning, v.to_string(), style)),
+    }
+}
+
 /* ---- Text Helpers ---- */
 
 pub fn wrap_text(s: &str, max_width: usize) -> Vec<String> {
@@ -402,6 +582,7 @@ pub fn wrap_text(s: &str, max_width: usize) -> Vec<String> {
     lines
 }
 
+#[allow(dead_code)]
 pub fn truncate_ellipsis(s: &str, max_chars: usize) -> String {
     if max_chars == 0 {
         return String::new();
@@ -471,6 +652,28 @@ mod tests {
         assert!(b.contains("Title"));
     }
 
+    #[test]
+    fn test_plain_style() {
+        let style = StyleOptions::with_flags(StyleFlags {
+            plain: true,
+            ..Default::default()
+        });
+        assert!(!style.use_color && !style.use_emoji && !style.use_border);
+        assert_eq!(
+            box_header(
+                format!("{} Tools (2)", emoji("list", &style)),
+                Some("target=x"),
+                &style
+            ),
+            "Tools (2)  target=x"
+        );
+        let style = StyleOptions::with_flags(StyleFlags {
+            no_border: true,
+            ..Default::default()
+        });
+        assert!(!style.use_border && style.use_emoji == std::env::var_os("NO_EMOJI").is_none());
+    }
+
     #[test]
     fn test_table_basic() {
         let style = StyleOptions::detect();
@@ -499,6 +702,48 @@ mod tests {
         assert_eq!(s, "abc…");
     }
 
+    #[test]
+    fn test_document_backends() {
+        let rows = vec![vec!["a|b".to_string(), "<img src=x>\nline".to_string()]];
+        assert_eq!(
+            markdown_table(&["NAME", "DESCRIPTION"], &rows),
+            "| NAME | DESCRIPTION |\n| --- | --- |\n| a\\|b | &lt;img src=x&gt;<br>line |"
+        );
+        let rows = vec![
+            vec![
+                "=cmd|' /C calc'!A0".to_string(),
+                "say \"hi\", bye".to_string(),
+            ],
+            vec!["-".to_string(), "plain".to_string()],
+        ];
+        assert_eq!(
+            csv_table(&["NAME", "DESCRIPTION"], &rows),
+            "NAME,DESCRIPTION\n'=cmd|' /C calc'!A0,\"say \"\"hi\"\", bye\"\n-,plain\n"
+        );
+        assert_eq!(yaml(&serde_json::json!({"count": 1})), "count: 1\n");
+    }
+
+    #[test]
+    fn test_json_highlight() {
+        let doc = serde_json::json!({
+            "name": "a\"b", "n": 1.5, "ok": true, "none": null,
+            "items": [1, {"k": []}], "empty": {},
+        });
+        let plain = StyleOptions::with_flags(StyleFlags {
+            plain: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            json_highlight(&doc, &plain),
+            serde_json::to_string_pretty(&doc).unwrap()
+        );
+        let mut colored = plain.clone();
+        colored.use_color = true;
+        let out = json_highlight(&serde_json::json!({"k": "v", "n": 2}), &colored);
+        assert!(out.contains("\x1b[38;5;45m\"k\"\x1b[0m: \x1b[38;5;82m\"v\"\x1b[0m"));
+        assert_eq!(strip_ansi(&out), "{\n  \"k\": \"v\",\n  \"n\": 2\n}");
+    }
+
     #[test]
     fn test_strip_ansi() {
         let colored = "\x1b[31mRED\x1b[0m";
@@ -5,33 +5,91 @@ Iterates through a wordlist, substituting a placeholder in parameters,
 and invokes an MCP tool for each variation. This is useful for basic
 fuzzing and enumeration tasks.
 
+Auto-calibration (`--auto-calibrate` / `--ac`, like ffuf's `-ac`):
+  Before the wordlist run, a few random canary values are sent and the
+  response shape (error flag, content types, size with the probe removed)
+  and latency are recorded. Responses matching that baseline are hidden so
+  only deviations surface; a response slower than the slowest canary times
+  `--calibration-latency` (default 3x, plus 100 ms of jitter) deviates.
+
+Time anomalies (`--time-anomaly 3x`):
+  Each request latency is compared with the rolling median of previous
+  requests; responses slower than the given multiple are flagged (and never
+  hidden by calibration). Useful for blind injection / SSRF sinks where only
+  timing changes.
+
+Auto mode (`fuzz tools --auto`):
+  Sweeps every tool on the server, fuzzing each string parameter in turn
+  (other required parameters get schema defaults / placeholders). Each
+  parameter is calibrated first; responses deviating from the baseline (or
+  slower than `--time-anomaly`) are hits, summarized in a per-tool report.
+
+Content rules (`--rules FILE`, repeatable):
+  YARA-style rules (see crate::scan::rules) are matched against the text of
+  every tool result. Matches are reported with the response and, like time
+  anomalies, are never hidden by calibration; in auto mode they count as hits.
+
+Content anomalies (`--content-anomalies`):
+  Every result text is scored against the run so far (crate::scan::entropy):
+  high-entropy blobs, binary data, length outliers and unseen character
+  classes are reported with the response and never hidden by calibration.
+  In auto mode each parameter sweep has its own baseline.
+
+Ctrl-C:
+  Cancels the request in flight (notifications/cancelled, server shut
+  down), prints what was found so far and exits with status 130.
+
+SARIF (`fuzz tools --auto --format sarif`):
+  The per-tool hit report is printed as a SARIF 2.1.0 log (one result per
+  hit, rule "fuzz-hit") instead of NDJSON.
+
 Example:
   mcp fuzz tool "file.read" -p "path=FUZZ" -w /usr/share/wordlists/common.txt
+  mcp fuzz tool "file.read" --param "path=FUZZ" -w common.txt --ac
+  mcp fuzz tool "ping" --param "host=FUZZ" -w payloads.txt --time-anomaly 3x
+  mcp fuzz tool "file.read" --param "path=FUZZ" -w lfi.txt --content-anomalies
+  mcp fuzz tools --auto -w list.txt
+  mcp fuzz tools --auto -w list.txt --rules secrets.yar
 
 */
 
 use anyhow::{Context, Result};
 use clap::Args;
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::time::Instant;
 
 use super::subject::Subject;
-use crate::cmd::exec::{invoke_tool, load_param_file_into_map, output_error};
-use crate::cmd::format::{Role, StyleOptions, color, emoji};
-use crate::cmd::shared::summarize_call_result;
+use crate::cmd::exec::{
+    ToolSession, load_param_file_into_map, output_error, report_error, usage_error,
+};
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json, print_line};
+use crate::cmd::shared::{fetch_tools_local, placeholder_for, summarize_call_result};
 use crate::mcp;
+use crate::scan::entropy::{Anomaly, ContentAnalyzer};
+use crate::scan::rules::{self, Rule, RuleMatch};
+use crate::scan::{Finding, Severity, sarif};
+use crate::utils::{Progress, progress_events, random_token};
 
 /* ---- Argument Struct ---- */
 
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json, OutputFormat::Sarif];
+
 #[derive(Args, Debug)]
 pub struct FuzzArgs {
-    /// Subject to execute ('tool' only)
+    /// Subject to execute ('tool', or 'tools' together with --auto)
     pub subject: Subject,
 
-    /// Tool name to invoke
-    #[arg(value_name = "TOOL")]
-    pub tool: String,
+    /// Tool name to invoke (omitted in --auto mode)
+    #[arg(value_name = "TOOL", required_unless_present = "auto")]
+    pub tool: Option<String>,
+
+    /// Fuzz every string parameter of every tool and print a per-tool hit report
+    #[arg(long)]
+    pub auto: bool,
 
     /// Path to the wordlist file
     #[arg(short = 'w', long, value_name = "PATH")]
@@ -53,51 +111,289 @@ pub struct FuzzArgs {
     #[arg(short = 't', long)]
     pub target: Option<String>,
 
-    /// Output JSON
-    #[arg(long)]
-    pub json: bool,
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
 
     /// Include raw MCP call result (instead of summary) in JSON / human output
     #[arg(long)]
     pub raw: bool,
+
+    /// Auto-calibrate: probe with random canaries first and hide responses matching their baseline
+    #[arg(long = "auto-calibrate", visible_alias = "ac")]
+    pub auto_calibrate: bool,
+
+    /// Number of canary requests sent for auto-calibration
+    #[arg(long = "calibration-samples", value_name = "N", default_value_t = 3)]
+    pub calibration_samples: usize,
+
+    /// Calibrated responses slower than this multiple of the slowest canary are not hidden
+    #[arg(
+        long = "calibration-latency",
+        value_name = "MULTIPLE",
+        default_value = "3x",
+        value_parser = parse_multiplier
+    )]
+    pub calibration_latency: f64,
+
+    /// Flag responses slower than this multiple of the rolling median latency (e.g. 3x)
+    #[arg(long = "time-anomaly", value_name = "MULTIPLE", value_parser = parse_multiplier)]
+    pub time_anomaly: Option<f64>,
+
+    /// Score result text for encoded blobs, binary data, length and charset outliers
+    #[arg(long = "content-anomalies")]
+    pub content_anomalies: bool,
+
+    /// Match YARA-style content rules against every tool result (repeatable)
+    #[arg(long = "rules", value_name = "PATH")]
+    pub rules: Vec<String>,
+}
+
+/* ---- Time Anomaly Detection ---- */
+
+/// Number of previous latencies kept for the rolling median.
+const LATENCY_WINDOW: usize = 25;
+/// Minimum number of observations before anomalies are reported.
+const LATENCY_MIN_SAMPLES: usize = 3;
+
+/// Parse a latency multiplier such as `3x`, `2.5x` or `4`.
+pub fn parse_multiplier(raw: &str) -> std::result::Result<f64, String> {
+    let trimmed = raw.trim();
+    let number = trimmed.strip_suffix(['x', 'X']).unwrap_or(trimmed).trim();
+    match number.parse::<f64>() {
+        Ok(m) if m > 1.0 && m.is_finite() => Ok(m),
+        Ok(_) => Err(format!("multiplier must be greater than 1: {raw}")),
+        Err(_) => Err(format!("invalid multiplier (expected e.g. 3x): {raw}")),
+    }
+}
+
+/// Rolling latency window used to compute the median of recent requests.
+#[derive(Debug, Default)]
+struct LatencyTracker {
+    window: VecDeque<u128>,
+}
+
+impl LatencyTracker {
+    fn observe(&mut self, ms: u128) {
+        if self.window.len() == LATENCY_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back(ms);
+    }
+
+    fn median(&self) -> Option<u128> {
+        if self.window.len() < LATENCY_MIN_SAMPLES {
+            return None;
+        }
+        let mut sorted: Vec<u128> = self.window.iter().copied().collect();
+        sorted.sort_unstable();
+        Some(sorted[sorted.len() / 2])
+    }
+
+    /// Returns the current median when `ms` exceeds `multiplier` times it.
+    fn anomaly(&self, ms: u128, multiplier: f64) -> Option<u128> {
+        let median = self.median()?;
+        ((ms as f64) > (median.max(1) as f64) * multiplier).then_some(median)
+    }
+}
+
+/* ---- Auto-Calibration ---- */
+
+/// Coarse fingerprint of a single fuzz response, used for baseline comparison.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ResponseShape {
+    /// The invocation itself failed (spawn / protocol / tool-not-found error)
+    failed: bool,
+    /// `isError` flag of the call result
+    is_error: bool,
+    /// Content item types in order (text, image, ...)
+    content_types: Vec<String>,
+    /// Serialized size with every occurrence of the probe word removed
+    size: usize,
+    /// Round-trip latency of the call
+    elapsed_ms: u128,
+}
+
+impl ResponseShape {
+    fn from_result(
+        result: &Result<(
+            serde_json::Map<String, serde_json::Value>,
+            rmcp::model::CallToolResult,
+        )>,
+        word: &str,
+        elapsed_ms: u128,
+    ) -> Self {
+        match result {
+            Ok((_, call_result)) => {
+                let summary = summarize_call_result(call_result);
+                let content_types = summary
+                    .get("content")
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .map(|c| {
+                                c.get("type")
+                                    .and_then(|v| v.as_str())
+                                    .unwrap_or("?")
+                                    .to_string()
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                ResponseShape {
+                    failed: false,
+                    is_error: call_result.is_error.unwrap_or(false),
+                    content_types,
+                    size: size_without_word(&summary.to_string(), word),
+                    elapsed_ms,
+                }
+            }
+            Err(e) => ResponseShape {
+                failed: true,
+                is_error: true,
+                content_types: Vec::new(),
+                size: size_without_word(&e.to_string(), word),
+                elapsed_ms,
+            },
+        }
+    }
+
+    /// Short human description used for auto-mode hits.
+    fn describe(&self) -> String {
+        let kind = if self.failed {
+            "call failed, "
+        } else if self.is_error {
+            "isError, "
+        } else {
+            ""
+        };
+        format!("{kind}size {}, {} ms", self.size, self.elapsed_ms)
+    }
+
+    fn same_kind(&self, other: &ResponseShape) -> bool {
+        self.failed == other.failed
+            && self.is_error == other.is_error
+            && self.content_types == other.content_types
+    }
+}
+
+/// Length of `text` after removing the probe word (reflected input must not skew sizes).
+fn size_without_word(text: &str, word: &str) -> usize {
+    if word.is_empty() {
+        return text.len();
+    }
+    text.replace(word, "").len()
+}
+
+/// Latency jitter allowed on top of `--calibration-latency`, so canaries
+/// answered within a millisecond or two do not make every response "slow".
+const LATENCY_SLACK_MS: u128 = 100;
+
+/// Baseline built from canary responses.
+///
+/// A response matches when a canary of the same kind exists, its size falls
+/// inside the observed size range widened by the spread (min 2 bytes of slack)
+/// and it is no slower than the slowest such canary times `latency_tolerance`
+/// (plus [`LATENCY_SLACK_MS`]).
+#[derive(Debug)]
+struct Baseline {
+    samples: Vec<ResponseShape>,
+    latency_tolerance: f64,
+}
+
+impl Baseline {
+    fn new(latency_tolerance: f64) -> Self {
+        Self {
+            samples: Vec::new(),
+            latency_tolerance,
+        }
+    }
+
+    fn matches(&self, shape: &ResponseShape) -> bool {
+        let same: Vec<&ResponseShape> =
+            self.samples.iter().filter(|s| s.same_kind(shape)).collect();
+        let (Some(min), Some(max), Some(slowest)) = (
+            same.iter().map(|s| s.size).min(),
+            same.iter().map(|s| s.size).max(),
+            same.iter().map(|s| s.elapsed_ms).max(),
+        ) else {
+            return false;
+        };
+        let slack = (max - min).max(2);
+        let latency_limit = (slowest as f64 * self.latency_tolerance) as u128 + LATENCY_SLACK_MS;
+        shape.size + slack >= min && shape.size <= max + slack && shape.elapsed_ms <= latency_limit
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(
+            self.samples
+                .iter()
+                .map(|s| {
+                    serde_json::json!({
+                        "failed": s.failed,
+                        "is_error": s.is_error,
+                        "content_types": s.content_types,
+                        "size": s.size,
+                        "elapsed_ms": s.elapsed_ms,
+                    })
+                })
+                .collect(),
+        )
+    }
 }
 
 /* ---- Public Entry Point ---- */
 
-pub fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
+pub async fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
     // Subject check
-    if !matches!(args.subject, Subject::Tool) {
-        return output_error(args.json, "fuzz currently supports only subject 'tool'");
+    if args.auto {
+        if !matches!(args.subject, Subject::Tools) {
+            return usage_error(args.output.is_json(), "--auto requires subject 'tools'");
+        }
+    } else if !matches!(args.subject, Subject::Tool) {
+        return usage_error(
+            args.output.is_json(),
+            "fuzz supports subject 'tool' (or 'tools' with --auto)",
+        );
+    }
+
+    if args.output == OutputFormat::Sarif && !args.auto {
+        return usage_error(true, "--format sarif requires --auto (hit reports)");
     }
 
     // Tool name validation
-    let tool_name_owned = args.tool.trim().to_string();
-    if tool_name_owned.is_empty() {
-        return output_error(args.json, "tool name cannot be empty");
+    let tool_name_owned = args.tool.as_deref().unwrap_or("").trim().to_string();
+    if !args.auto && tool_name_owned.is_empty() {
+        return usage_error(args.output.is_json(), "tool name cannot be empty");
     }
 
     // Determine target (CLI > env)
     if args.target.is_none()
         && let Ok(env_t) = std::env::var("MCP_TARGET")
-            && !env_t.trim().is_empty() {
-                args.target = Some(env_t);
-            }
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
     let target_raw = match &args.target {
         Some(t) if !t.trim().is_empty() => t.trim().to_string(),
         _ => {
-            return output_error(
-                args.json,
+            return usage_error(
+                args.output.is_json(),
                 "no target specified (use --target or MCP_TARGET)",
             );
         }
     };
 
     // Parse target spec
-    let spec = mcp::parse_target(&target_raw)
-        .with_context(|| format!("Failed to parse target: '{}'", target_raw))?;
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
 
     if !spec.is_local() {
-        return output_error(args.json, "remote fuzz not implemented yet");
+        return usage_error(args.output.is_json(), "remote fuzz not implemented yet");
     }
 
     // --- Fuzzing-specific logic starts here ---
@@ -110,7 +406,19 @@ pub fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
     let words: Vec<String> = reader.lines().collect::<Result<_, _>>()?;
     let total_requests = words.len();
 
-    if !args.json {
+    let mut content_rules = Vec::new();
+    for path in &args.rules {
+        match rules::load(path) {
+            Ok(r) => content_rules.extend(r),
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+    }
+
+    if args.auto {
+        return fuzz_all_tools(&args, &spec, &target_raw, &words, &content_rules).await;
+    }
+
+    if !args.output.is_json() {
         let style = StyleOptions::detect();
         println!(
             "{} {}",
@@ -126,51 +434,131 @@ pub fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
         );
     }
 
-    // Loop through wordlist and execute
-    for (i, word) in words.iter().enumerate() {
-        let mut provided: std::collections::HashMap<String, String> =
-            std::collections::HashMap::new();
-
-        // Collect parameters from CLI, substituting the placeholder
-        for kv in &args.params {
-            let substituted_kv = kv.replace(&args.placeholder, word);
-            if let Some((k, v)) = substituted_kv.split_once('=') {
-                let key = k.trim();
-                if key.is_empty() {
-                    return output_error(
-                        args.json,
-                        &format!("invalid --param (empty key): {}", kv),
-                    );
-                }
-                provided.insert(key.to_string(), v.trim().to_string());
-            } else {
-                return output_error(
-                    args.json,
-                    &format!("invalid --param (expected KEY=VALUE): {}", kv),
-                );
-            }
+    // One server for the whole run (restarted only if a word breaks it)
+    let mut session = ToolSession::new(&spec);
+
+    // Optional auto-calibration with random canary values
+    let baseline = if args.auto_calibrate {
+        let baseline = match calibrate(&args, &mut session, &tool_name_owned, |w| {
+            provided_for_word(&args, w)
+        })
+        .await
+        {
+            Ok(b) => b,
+            Err(msg) => return usage_error(args.output.is_json(), &msg),
+        };
+        if args.output.is_json() {
+            let cal = serde_json::json!({
+                "status": "calibrated",
+                "samples": baseline.samples.len(),
+                "baseline": baseline.to_json(),
+            });
+            print_line(&cal);
+        } else {
+            let style = StyleOptions::detect();
+            println!(
+                "{} {}",
+                emoji("info", &style),
+                color(
+                    Role::Dim,
+                    format!(
+                        "Auto-calibrated with {} canary request(s); matching responses are hidden",
+                        baseline.samples.len()
+                    ),
+                    &style
+                )
+            );
         }
+        Some(baseline)
+    } else {
+        None
+    };
+    let mut filtered = 0usize;
+    let mut latencies = LatencyTracker::default();
+    let mut analyzer = ContentAnalyzer::default();
+    let mut progress = Progress::new(Some(total_requests as u64));
+    let mut interrupted_at = None;
 
-        // Load param file if specified (merge non-conflicting keys)
-        if let Some(ref pf) = args.param_file
-            && let Err(e) = load_param_file_into_map(pf, &mut provided) {
-                return output_error(args.json, &e.to_string());
-            }
+    // Loop through wordlist and execute
+    for (i, word) in words.iter().enumerate() {
+        let provided = match provided_for_word(&args, word) {
+            Ok(p) => p,
+            Err(msg) => return usage_error(args.output.is_json(), &msg),
+        };
 
-        // Build runtime + spawn + list tools + call tool
         let started = Instant::now();
-        let result = invoke_tool(
-            &spec,
-            &tool_name_owned,
-            provided,
-            false, // Interactive mode is disabled for fuzzing
-            args.json,
-        );
+        let result = session.call(&tool_name_owned, &provided).await;
+        if mcp::interrupt::interrupted() {
+            interrupted_at = Some(i);
+            break;
+        }
         let elapsed_ms = started.elapsed().as_millis();
+        progress.inc(1);
+        progress_events::emit("fuzz", &progress.snapshot(), Some(word));
+
+        // Compare against the median of previous requests, then record this one
+        let slow_median = args
+            .time_anomaly
+            .and_then(|m| latencies.anomaly(elapsed_ms, m));
+        latencies.observe(elapsed_ms);
+        let rule_hits = match_rules(&content_rules, &result);
+        let anomalies = score_content(args.content_anomalies, &mut analyzer, &result);
+        let hook_findings = mcp::hook::take_findings();
+
+        if slow_median.is_none()
+            && rule_hits.is_empty()
+            && hook_findings.is_empty()
+            && anomalies.is_empty()
+            && let Some(ref baseline) = baseline
+            && baseline.matches(&ResponseShape::from_result(&result, word, elapsed_ms))
+        {
+            filtered += 1;
+            continue;
+        }
+        let time_note = slow_median.map(|median| {
+            let style = StyleOptions::detect();
+            color(
+                Role::Warning,
+                format!(
+                    " {} slow: {elapsed_ms} ms ({:.1}x median {median} ms)",
+                    emoji("clock", &style),
+                    elapsed_ms as f64 / median.max(1) as f64
+                ),
+                &style,
+            )
+        });
+        let anomaly_note = (!anomalies.is_empty()).then(|| {
+            let style = StyleOptions::detect();
+            let kinds: Vec<String> = anomalies.iter().map(Anomaly::describe).collect();
+            color(
+                Role::Warning,
+                format!(" {} {}", emoji("warn", &style), kinds.join("; ")),
+                &style,
+            )
+        });
+        let rule_note = (!rule_hits.is_empty()).then(|| {
+            let style = StyleOptions::detect();
+            let names: Vec<&str> = rule_hits.iter().map(|m| m.rule.as_str()).collect();
+            color(
+                Role::Warning,
+                format!(" {} rules: {}", emoji("warn", &style), names.join(", ")),
+                &style,
+            )
+        });
+
+        let hook_note = (!hook_findings.is_empty()).then(|| {
+            let style = StyleOptions::detect();
+            let titles: Vec<&str> = hook_findings.iter().map(|f| f.title.as_str()).collect();
+            color(
+                Role::Warning,
+                format!(" {} hook: {}", emoji("warn", &style), titles.join(", ")),
+                &style,
+            )
+        });
 
         match result {
             Ok((final_args_map, call_result)) => {
-                if args.json {
+                if args.output.is_json() {
                     let mut base = serde_json::json!({
                         "status": "ok",
                         "request_index": i,
@@ -195,10 +583,20 @@ pub fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
                             summarize_call_result(&call_result),
                         );
                     }
-                    println!(
-                        "{}",
-                        serde_json::to_string(&base).unwrap_or_else(|_| base.to_string())
-                    );
+                    if args.time_anomaly.is_some() {
+                        insert_time_anomaly(&mut base, slow_median);
+                    }
+                    if !content_rules.is_empty() {
+                        base["rule_matches"] = rule_hits.iter().map(RuleMatch::to_json).collect();
+                    }
+                    if args.content_anomalies {
+                        base["content_anomalies"] =
+                            anomalies.iter().map(Anomaly::to_json).collect();
+                    }
+                    if mcp::hook::installed() {
+                        base["findings"] = hook_findings.iter().map(Finding::to_json).collect();
+                    }
+                    print_line(&base);
                 } else {
                     let style = StyleOptions::detect();
                     let summary = summarize_call_result(&call_result);
@@ -206,42 +604,675 @@ pub fn execute_fuzz(mut args: FuzzArgs) -> Result<()> {
                         serde_json::to_string(&summary).unwrap_or_else(|_| summary.to_string());
 
                     println!(
-                        "{} Request {}/{}: word='{}' -> {}",
+                        "{} Request {}/{}: word='{}' -> {}{}{}{}{}",
                         emoji("success", &style),
                         i + 1,
                         total_requests,
                         word,
-                        summary_str
+                        summary_str,
+                        time_note.unwrap_or_default(),
+                        rule_note.unwrap_or_default(),
+                        anomaly_note.unwrap_or_default(),
+                        hook_note.unwrap_or_default()
                     );
                 }
             }
             Err(e) => {
-                if args.json {
-                    let err = serde_json::json!({
+                if args.output.is_json() {
+                    let mut err = serde_json::json!({
                         "status": "error",
                         "request_index": i,
                         "total_requests": total_requests,
                         "word": word,
+                        "elapsed_ms": elapsed_ms,
                         "error": e.to_string()
                     });
-                    println!(
-                        "{}",
-                        serde_json::to_string(&err).unwrap_or_else(|_| err.to_string())
-                    );
+                    if args.time_anomaly.is_some() {
+                        insert_time_anomaly(&mut err, slow_median);
+                    }
+                    print_line(&err);
                 } else {
                     let style = StyleOptions::detect();
                     println!(
-                        "{} Request {}/{}: word='{}' -> {}",
+                        "{} Request {}/{}: word='{}' -> {}{}",
                         emoji("error", &style),
                         i + 1,
                         total_requests,
                         word,
-                        color(Role::Error, e.to_string(), &style)
+                        color(Role::Error, e.to_string(), &style),
+                        time_note.unwrap_or_default()
                     );
                 }
             }
         }
     }
 
+    session.close().await;
+    progress_events::phase("done", None);
+
+    if baseline.is_some() && filtered > 0 && !args.output.is_json() {
+        let style = StyleOptions::detect();
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!("{filtered} response(s) matched the calibration baseline and were hidden"),
+                &style
+            )
+        );
+    }
+    if let Some(i) = interrupted_at {
+        return output_error(
+            args.output.is_json(),
+            &format!("interrupted at request {}/{total_requests}", i + 1),
+        );
+    }
+
     Ok(())
 }
+
+/// Send `--calibration-samples` random canaries and record their response shapes.
+async fn calibrate(
+    args: &FuzzArgs,
+    session: &mut ToolSession<'_>,
+    tool: &str,
+    make_provided: impl Fn(
+        &str,
+    )
+        -> std::result::Result<std::collections::HashMap<String, String>, String>,
+) -> std::result::Result<Baseline, String> {
+    let mut baseline = Baseline::new(args.calibration_latency);
+    progress_events::phase("calibrate", Some(tool));
+    for _ in 0..args.calibration_samples.max(1) {
+        let canary = random_token(12);
+        let provided = make_provided(&canary)?;
+        let started = Instant::now();
+        let result = session.call(tool, &provided).await;
+        baseline.samples.push(ResponseShape::from_result(
+            &result,
+            &canary,
+            started.elapsed().as_millis(),
+        ));
+    }
+    Ok(baseline)
+}
+
+/* ---- Auto Mode (all tools) ---- */
+
+/// A deviating response found while sweeping one parameter.
+#[derive(Debug)]
+struct Hit {
+    word: String,
+    reason: String,
+    elapsed_ms: u128,
+}
+
+/// Outcome of fuzzing one string parameter of one tool.
+#[derive(Debug)]
+struct ParamReport {
+    tool: String,
+    param: String,
+    requests: usize,
+    hits: Vec<Hit>,
+}
+
+/// Fuzz every string parameter of every tool, then print a consolidated report.
+async fn fuzz_all_tools(
+    args: &FuzzArgs,
+    spec: &mcp::TargetSpec,
+    target_raw: &str,
+    words: &[String],
+    content_rules: &[Rule],
+) -> Result<()> {
+    let tool_list = match fetch_tools_local(spec).await {
+        Ok(t) => t,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let style = StyleOptions::detect();
+
+    if !args.output.is_json() {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Accent,
+                format!(
+                    "Starting auto fuzz: {} tools, {} words per string parameter",
+                    tool_list.count(),
+                    words.len()
+                ),
+                &style
+            )
+        );
+    }
+
+    let mut reports: Vec<ParamReport> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let param_count: usize = tool_list.iter().map(|t| string_params(t).len()).sum();
+    let mut progress = Progress::new(Some((param_count * words.len()) as u64));
+    let mut session = ToolSession::new(spec);
+    'sweep: for tool in &tool_list.tools {
+        let Some(tool_name) = tool.get("name").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let params = string_params(tool);
+        if params.is_empty() {
+            skipped.push(tool_name.to_string());
+            continue;
+        }
+
+        for param in params {
+            let make_provided = |word: &str| provided_for_param(args, tool, &param, word);
+            let baseline = match calibrate(args, &mut session, tool_name, make_provided).await {
+                Ok(b) => b,
+                Err(msg) => return usage_error(args.output.is_json(), &msg),
+            };
+            if !args.output.is_json() {
+                println!(
+                    "{} {}",
+                    emoji("tool", &style),
+                    color(
+                        Role::Dim,
+                        format!(
+                            "{tool_name}.{param}: calibrated, fuzzing {} words",
+                            words.len()
+                        ),
+                        &style
+                    )
+                );
+            }
+
+            let mut latencies = LatencyTracker::default();
+            let mut analyzer = ContentAnalyzer::default();
+            let mut report = ParamReport {
+                tool: tool_name.to_string(),
+                param: param.clone(),
+                requests: 0,
+                hits: Vec::new(),
+            };
+            for word in words {
+                let provided = match make_provided(word) {
+                    Ok(p) => p,
+                    Err(msg) => return usage_error(args.output.is_json(), &msg),
+                };
+                let started = Instant::now();
+                let result = session.call(tool_name, &provided).await;
+                if mcp::interrupt::interrupted() {
+                    reports.push(report);
+                    break 'sweep;
+                }
+                let elapsed_ms = started.elapsed().as_millis();
+                report.requests += 1;
+                progress.inc(1);
+                progress_events::emit(
+                    "fuzz",
+                    &progress.snapshot(),
+                    Some(&format!("{tool_name}.{param}={word}")),
+                );
+
+                let slow_median = args
+                    .time_anomaly
+                    .and_then(|m| latencies.anomaly(elapsed_ms, m));
+                latencies.observe(elapsed_ms);
+
+                let shape = ResponseShape::from_result(&result, word, elapsed_ms);
+                let rule_hits = match_rules(content_rules, &result);
+                let anomalies = score_content(args.content_anomalies, &mut analyzer, &result);
+                let hook_findings = mcp::hook::take_findings();
+                let reason = match slow_median {
+                    _ if !hook_findings.is_empty() => Some(format!(
+                        "hook: {}",
+                        hook_findings
+                            .iter()
+                            .map(|f| f.title.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                    _ if !rule_hits.is_empty() => Some(format!(
+                        "rules: {}",
+                        rule_hits
+                            .iter()
+                            .map(|m| m.rule.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )),
+                    _ if !anomalies.is_empty() => Some(
+                        anomalies
+                            .iter()
+                            .map(Anomaly::describe)
+                            .collect::<Vec<_>>()
+                            .join("; "),
+                    ),
+                    Some(median) => Some(format!("slow: {elapsed_ms} ms (median {median} ms)")),
+                    None if !baseline.matches(&shape) => Some(shape.describe()),
+                    None => None,
+                };
+                let Some(reason) = reason else {
+                    continue;
+                };
+
+                if args.output == OutputFormat::Json {
+                    let hit = serde_json::json!({
+                        "status": "hit",
+                        "tool": tool_name,
+                        "param": param,
+                        "word": word,
+                        "reason": reason,
+                        "elapsed_ms": elapsed_ms,
+                    });
+                    print_line(&hit);
+                } else if !args.output.is_json() {
+                    println!(
+                        "  {} {tool_name}.{param} word='{word}' -> {}",
+                        emoji("warn", &style),
+                        color(Role::Warning, &reason, &style)
+                    );
+                }
+                report.hits.push(Hit {
+                    word: word.clone(),
+                    reason,
+                    elapsed_ms,
+                });
+            }
+            reports.push(report);
+        }
+    }
+
+    session.close().await;
+    let total_hits: usize = reports.iter().map(|r| r.hits.len()).sum();
+    progress_events::phase("done", None);
+
+    if args.output == OutputFormat::Sarif {
+        let findings: Vec<Finding> = reports
+            .iter()
+            .flat_map(|r| {
+                r.hits.iter().map(|h| {
+                    Finding::new(
+                        "fuzz-hit",
+                        Severity::Low,
+                        format!("{}.{}: {}", r.tool, r.param, h.reason),
+                    )
+                    .tool(r.tool.clone())
+                    .detail(format!(
+                        "Fuzzing parameter '{}' with this value produced a response that \
+                         deviated from the calibrated baseline.",
+                        r.param
+                    ))
+                    .evidence(format!("{}={}", r.param, h.word))
+                })
+            })
+            .collect();
+        let log = sarif::render(target_raw, &findings);
+        print_json(&log);
+        return sweep_done(args.output.is_json());
+    }
+
+    if args.output.is_json() {
+        let report = serde_json::json!({
+            "status": "report",
+            "target": target_raw,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "total_hits": total_hits,
+            "skipped_tools": skipped,
+            "results": reports.iter().map(|r| serde_json::json!({
+                "tool": r.tool,
+                "param": r.param,
+                "requests": r.requests,
+                "hits": r.hits.iter().map(|h| serde_json::json!({
+                    "word": h.word,
+                    "reason": h.reason,
+                    "elapsed_ms": h.elapsed_ms,
+                })).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        });
+        print_line(&report);
+        return sweep_done(args.output.is_json());
+    }
+
+    println!();
+    let header = box_header(
+        format!(
+            "{} Auto Fuzz Report ({total_hits} hits)",
+            emoji("list", &style)
+        ),
+        Some(format!("target={target_raw}")),
+        &style,
+    );
+    println!("{header}");
+    if reports.is_empty() {
+        println!(
+            "{}",
+            color(
+                Role::Dim,
+                format!("{} no string parameters to fuzz", emoji("info", &style)),
+                &style
+            )
+        );
+    } else {
+        let rows: Vec<Vec<String>> = reports
+            .iter()
+            .map(|r| {
+                let sample = r
+                    .hits
+                    .iter()
+                    .take(3)
+                    .map(|h| h.word.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                vec![
+                    r.tool.clone(),
+                    r.param.clone(),
+                    r.requests.to_string(),
+                    r.hits.len().to_string(),
+                    if sample.is_empty() {
+                        "-".into()
+                    } else {
+                        sample
+                    },
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["TOOL", "PARAM", "REQUESTS", "HITS", "SAMPLE HITS"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                truncate: true,
+                header_sep: true,
+                zebra: false,
+                min_col_width: 2,
+            },
+            &style,
+        );
+        println!("{tbl}");
+    }
+    if !skipped.is_empty() {
+        println!(
+            "\n{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!("Skipped (no string parameters): {}", skipped.join(", ")),
+                &style
+            )
+        );
+    }
+
+    sweep_done(false)
+}
+
+/// Exit status of a finished auto sweep: an error after Ctrl-C (the partial
+/// report has been printed).
+fn sweep_done(json: bool) -> Result<()> {
+    if mcp::interrupt::interrupted() {
+        return output_error(
+            json,
+            "interrupted; the report covers the requests sent so far",
+        );
+    }
+    Ok(())
+}
+
+/// Names of `type: string` properties in a tool's input schema (schema order).
+fn string_params(tool: &serde_json::Value) -> Vec<String> {
+    tool.get("input_schema")
+        .or_else(|| tool.get("inputSchema"))
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object())
+        .map(|props| {
+            props
+                .iter()
+                .filter(|(_, p)| p.get("type").and_then(|t| t.as_str()) == Some("string"))
+                .map(|(name, _)| name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parameter map for auto mode: user params, then placeholders for other required
+/// parameters, then the fuzzed parameter itself.
+fn provided_for_param(
+    args: &FuzzArgs,
+    tool: &serde_json::Value,
+    param: &str,
+    word: &str,
+) -> std::result::Result<std::collections::HashMap<String, String>, String> {
+    let mut provided = provided_for_word(args, word)?;
+    let schema = tool.get("input_schema").or_else(|| tool.get("inputSchema"));
+    if let Some(required) = schema
+        .and_then(|s| s.get("required"))
+        .and_then(|v| v.as_array())
+    {
+        for name in required.iter().filter_map(|v| v.as_str()) {
+            if name == param || provided.contains_key(name) {
+                continue;
+            }
+            let prop = schema
+                .and_then(|s| s.get("properties"))
+                .and_then(|p| p.get(name))
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            provided.insert(name.to_string(), placeholder_for(&prop));
+        }
+    }
+    provided.insert(param.to_string(), word.to_string());
+    Ok(provided)
+}
+
+/// Content rules matching the text of a successful tool result.
+fn match_rules(
+    content_rules: &[Rule],
+    result: &Result<(
+        serde_json::Map<String, serde_json::Value>,
+        rmcp::model::CallToolResult,
+    )>,
+) -> Vec<RuleMatch> {
+    match result {
+        Ok((_, call_result)) if !content_rules.is_empty() => {
+            let text = rules::json_text(&summarize_call_result(call_result));
+            rules::scan_all(content_rules, text.as_bytes())
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Text carried by a successful tool result: text items, embedded resource
+/// text and structured content (binary image / audio payloads are skipped).
+fn response_text(call_result: &rmcp::model::CallToolResult) -> String {
+    let summary = summarize_call_result(call_result);
+    let mut parts: Vec<String> = Vec::new();
+    for item in summary
+        .get("content")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        if let Some(t) = item.get("text").and_then(|v| v.as_str()) {
+            parts.push(t.to_string());
+        } else if let Some(t) = item.pointer("/resource/text").and_then(|v| v.as_str()) {
+            parts.push(t.to_string());
+        }
+    }
+    if let Some(structured) = summary.get("structuredContent") {
+        parts.push(rules::json_text(structured));
+    }
+    parts.join("\n")
+}
+
+/// Content anomalies for a successful result (empty when scoring is disabled).
+fn score_content(
+    enabled: bool,
+    analyzer: &mut ContentAnalyzer,
+    result: &Result<(
+        serde_json::Map<String, serde_json::Value>,
+        rmcp::model::CallToolResult,
+    )>,
+) -> Vec<Anomaly> {
+    match result {
+        Ok((_, call_result)) if enabled => analyzer.observe(&response_text(call_result)),
+        _ => Vec::new(),
+    }
+}
+
+/// Add `time_anomaly` / `median_ms` fields to a per-request JSON object.
+fn insert_time_anomaly(obj: &mut serde_json::Value, slow_median: Option<u128>) {
+    if let serde_json::Value::Object(map) = obj {
+        map.insert(
+            "time_anomaly".to_string(),
+            serde_json::Value::Bool(slow_median.is_some()),
+        );
+        if let Some(median) = slow_median {
+            map.insert("median_ms".to_string(), serde_json::json!(median));
+        }
+    }
+}
+
+/// Build the parameter map for one wordlist entry (placeholder substituted).
+///
+/// Returns a user-facing error message for malformed `--param` values or param files.
+fn provided_for_word(
+    args: &FuzzArgs,
+    word: &str,
+) -> std::result::Result<std::collections::HashMap<String, String>, String> {
+    let mut provided: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+
+    // Collect parameters from CLI, substituting the placeholder
+    for kv in &args.params {
+        let substituted_kv = kv.replace(&args.placeholder, word);
+        if let Some((k, v)) = substituted_kv.split_once('=') {
+            let key = k.trim();
+            if key.is_empty() {
+                return Err(format!("invalid --param (empty key): {}", kv));
+            }
+            provided.insert(key.to_string(), v.trim().to_string());
+        } else {
+            return Err(format!("invalid --param (expected KEY=VALUE): {}", kv));
+        }
+    }
+
+    // Load param file if specified (merge non-conflicting keys)
+    if let Some(ref pf) = args.param_file
+        && let Err(e) = load_param_file_into_map(pf, &mut provided)
+    {
+        return Err(e.to_string());
+    }
+
+    Ok(provided)
+}
+
+/* ---- Tests ---- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(is_error: bool, size: usize) -> ResponseShape {
+        ResponseShape {
+            failed: false,
+            is_error,
+            content_types: vec!["text".into()],
+            size,
+            elapsed_ms: 5,
+        }
+    }
+
+    #[test]
+    fn size_ignores_reflected_word() {
+        assert_eq!(
+            size_without_word("echo: abc123 abc123", "abc123"),
+            size_without_word("echo: zz zz", "zz")
+        );
+    }
+
+    #[test]
+    fn baseline_matches_same_shape_within_slack() {
+        let baseline = Baseline {
+            samples: vec![shape(false, 40), shape(false, 42)],
+            latency_tolerance: 3.0,
+        };
+        assert!(baseline.matches(&shape(false, 41)));
+        assert!(baseline.matches(&shape(false, 44)));
+        assert!(
+            !baseline.matches(&shape(false, 80)),
+            "size deviation surfaces"
+        );
+        assert!(
+            !baseline.matches(&shape(true, 41)),
+            "error flag deviation surfaces"
+        );
+    }
+
+    #[test]
+    fn baseline_latency_tolerance() {
+        let mut slow_canary = shape(false, 40);
+        slow_canary.elapsed_ms = 400;
+        let baseline = Baseline {
+            samples: vec![shape(false, 40), slow_canary],
+            latency_tolerance: 3.0,
+        };
+        let mut response = shape(false, 40);
+        response.elapsed_ms = 1300;
+        assert!(baseline.matches(&response), "within 3x + jitter");
+        response.elapsed_ms = 1301;
+        assert!(!baseline.matches(&response), "latency deviation surfaces");
+
+        // Fast canaries still leave room for jitter
+        let baseline = Baseline {
+            samples: vec![shape(false, 40)],
+            latency_tolerance: 3.0,
+        };
+        response.elapsed_ms = 90;
+        assert!(baseline.matches(&response));
+    }
+
+    #[test]
+    fn empty_baseline_matches_nothing() {
+        assert!(!Baseline::new(3.0).matches(&shape(false, 10)));
+    }
+
+    #[test]
+    fn string_params_and_placeholders() {
+        let tool = serde_json::json!({
+            "name": "demo",
+            "inputSchema": {
+                "type": "object",
+                "required": ["q", "limit", "mode"],
+                "properties": {
+                    "q": {"type": "string"},
+                    "limit": {"type": "integer"},
+                    "mode": {"type": "string", "enum": ["fast", "slow"]},
+                    "flag": {"type": "boolean", "default": false}
+                }
+            }
+        });
+        let mut params = string_params(&tool);
+        params.sort();
+        assert_eq!(params, vec!["mode", "q"]);
+
+        let props = &tool["inputSchema"]["properties"];
+        assert_eq!(placeholder_for(&props["limit"]), "1");
+        assert_eq!(placeholder_for(&props["mode"]), "fast");
+        assert_eq!(placeholder_for(&props["flag"]), "false");
+    }
+
+    #[test]
+    fn parse_multiplier_forms() {
+        assert_eq!(parse_multiplier("3x"), Ok(3.0));
+        assert_eq!(parse_multiplier("2.5X"), Ok(2.5));
+        assert_eq!(parse_multiplier("4"), Ok(4.0));
+        assert!(parse_multiplier("1x").is_err());
+        assert!(parse_multiplier("fast").is_err());
+    }
+
+    #[test]
+    fn latency_tracker_flags_outliers() {
+        let mut t = LatencyTracker::default();
+        assert_eq!(t.anomaly(500, 3.0), None, "no median before min samples");
+        for ms in [100, 110, 90, 105] {
+            t.observe(ms);
+        }
+        assert_eq!(t.median(), Some(105));
+        assert_eq!(t.anomaly(200, 3.0), None);
+        assert_eq!(t.anomaly(400, 3.0), Some(105));
+    }
+}
@@ -9,8 +9,19 @@ Subjects:
   resources / prompts : placeholders
 
 Outputs:
-  Human: boxed header + parameter table
-  JSON : stable fields (status, subject, target, elapsed_ms, parameters)
+  Human: boxed header + annotations + parameter table (+ output schema fields)
+  JSON : stable fields (status, subject, target, elapsed_ms, timings, attempts, parameters, output_schema)
+  YAML : the JSON document
+  Markdown: a section per tool with the same tables, for reports
+  CSV  : one row per parameter (TOOL, PARAMETER, TYPE, REQUIRED, DESCRIPTION)
+
+`get tool NAME --example` prints a ready-to-edit arguments object instead:
+every parameter with its default, first enum value or a placeholder of its
+type / format (JSON: {"status", "subject", "target", "name", "example"}).
+Edit it and pass it back with `exec tool NAME --args-json "$(cat args.json)"`.
+
+`--filter` / `--grep` / `--sort` / `--reverse` apply to `get tools` and the
+choices of `get tool` without a name (see crate::cmd::selection).
 
 Remote targets: parsed only; retrieval not implemented yet.
 */
@@ -19,12 +30,30 @@ use anyhow::{Context, Result};
 use clap::Args;
 use std::io::{self, Write};
 
-use crate::cmd::format::{StyleOptions, box_header, emoji};
-use crate::cmd::shared::fetch_tools_local;
+use crate::cmd::exit;
+use crate::cmd::format::{
+    Role, StyleOptions, box_header, color, csv_table, emoji, markdown_escape, markdown_table,
+};
+use crate::cmd::output::{OutputFormat, print_document};
+use crate::cmd::retry::RetryArgs;
+use crate::cmd::selection::{FilterArgs, SortArgs};
+use crate::cmd::shared::{
+    annotation_flags, annotation_summary, example_arguments, fetch_tools_for, fetch_tools_local,
+};
 use crate::cmd::subject::Subject;
 use crate::mcp;
+use crate::scan::unicode;
 
 /// CLI arguments for `mcp-hack get <subject> [NAME]`
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[
+    OutputFormat::Text,
+    OutputFormat::Json,
+    OutputFormat::Markdown,
+    OutputFormat::Csv,
+    OutputFormat::Yaml,
+];
+
 #[derive(Args, Debug)]
 pub struct GetArgs {
     /// Subject (tools|tool|resources|prompts)
@@ -34,18 +63,31 @@ pub struct GetArgs {
     #[arg(value_name = "NAME")]
     pub name: Option<String>,
 
-    /// Output JSON instead of human-readable text
-    #[arg(long)]
-    pub json: bool,
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
 
     /// Target MCP endpoint (local command or remote URL)
     /// (Falls back to MCP_TARGET env var if omitted)
     #[arg(short = 't', long)]
     pub target: Option<String>,
+
+    /// Print an example arguments object built from the input schema (subject=tool)
+    #[arg(long)]
+    pub example: bool,
+
+    #[command(flatten)]
+    pub select: FilterArgs,
+
+    #[command(flatten)]
+    pub order: SortArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
 }
 
 /// Entrypoint for `get` subcommand.
-pub fn execute_get(mut args: GetArgs) -> Result<()> {
+pub async fn execute_get(mut args: GetArgs) -> Result<()> {
     // Fallback to environment target if not supplied.
     if args.target.is_none()
         && let Ok(env_t) = std::env::var("MCP_TARGET")
@@ -55,28 +97,28 @@ pub fn execute_get(mut args: GetArgs) -> Result<()> {
     }
 
     match args.subject {
-        Subject::Tools => get_all_tools(args),
-        Subject::Tool => get_single_tool(args),
-        Subject::Resources => get_placeholder("resources", args.json),
-        Subject::Prompts => get_placeholder("prompts", args.json),
+        Subject::Tools => get_all_tools(args).await,
+        Subject::Tool => get_single_tool(args).await,
+        Subject::Resources => get_placeholder("resources", args.output),
+        Subject::Prompts => get_placeholder("prompts", args.output),
     }
 }
 
 /* ---- Tools (plural) ---- */
 
-fn get_all_tools(args: GetArgs) -> Result<()> {
+async fn get_all_tools(args: GetArgs) -> Result<()> {
     let Some(target) = args.target.as_deref() else {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tools",
                     "target": null,
                     "count":0,
                     "tools":[],
                     "note":"no target specified; use --target or MCP_TARGET"
-                })
+                }),
             );
         } else {
             println!("No target specified (use --target or set MCP_TARGET).");
@@ -85,22 +127,23 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
         return Ok(());
     };
 
-    let spec =
-        mcp::parse_target(target).with_context(|| format!("Failed to parse target: '{target}'"))?;
+    let spec = mcp::parse_target(target)
+        .with_context(|| format!("Failed to parse target: '{}'", mcp::redact_target(target)))?;
+    let target = spec.original();
 
     if !spec.is_local() {
         // Remote placeholder
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tools",
                     "target": target,
                     "count":0,
                     "tools":[],
                     "note":"remote tool retrieval not implemented yet"
-                })
+                }),
             );
         } else {
             println!("(remote) Detailed tool retrieval not implemented for {target}");
@@ -108,8 +151,12 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
         return Ok(());
     }
 
-    let tool_list = fetch_tools_local(&spec)?;
-    if args.json {
+    let (tool_list, attempts) = args.retry.run(|| fetch_tools_local(&spec)).await;
+    let mut tool_list = tool_list?;
+    let total = tool_list.count();
+    args.select.apply(&mut tool_list.tools);
+    args.order.apply(&mut tool_list.tools);
+    if args.output.is_json() {
         // Build enriched JSON objects with parameters
         let mut enriched = Vec::with_capacity(tool_list.count());
         for t in &tool_list.tools {
@@ -127,23 +174,55 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
             enriched.push(serde_json::json!({
                 "name": name,
                 "description": desc,
+                "unicode_issues": unicode::issues_json(&unicode::tool_issues(t)),
+                "annotations": t.get("annotations"),
+                "hints": annotation_flags(t),
                 "parameters": params.into_iter().map(|(n,t,r,d)| serde_json::json!({
                     "name":n,"type":t,"required":r,"description":d
                 })).collect::<Vec<_>>()
             }));
         }
 
+        let mut out = serde_json::json!({
+            "status":"ok",
+            "subject":"tools",
+            "target": target,
+            "elapsed_ms": tool_list.elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "count": tool_list.count(),
+            "tools": enriched
+        });
+        if args.select.active() {
+            out["total"] = total.into();
+        }
+        attempts.annotate(&mut out);
+        tool_list.annotate(&mut out);
+        print_document(args.output, &out);
+        return Ok(());
+    }
+
+    if args.output == OutputFormat::Markdown {
         println!(
-            "{}",
-            serde_json::json!({
-                "status":"ok",
-                "subject":"tools",
-                "target": target,
-                "elapsed_ms": tool_list.elapsed_ms,
-                "count": tool_list.count(),
-                "tools": enriched
-            })
+            "## Tools ({})\n\nTarget: {} • {}",
+            args.select.shown(tool_list.count(), total),
+            markdown_escape(target),
+            tool_list.took()
         );
+        for (idx, t) in tool_list.tools.iter().enumerate() {
+            let name = t
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("<unnamed>");
+            println!(
+                "\n{}",
+                markdown_tool(&format!("### {}. ", idx + 1), name, t)
+            );
+        }
+        return Ok(());
+    }
+    if args.output == OutputFormat::Csv {
+        let rows: Vec<Vec<String>> = tool_list.tools.iter().flat_map(csv_rows).collect();
+        print!("{}", csv_table(&CSV_COLUMNS, &rows));
         return Ok(());
     }
 
@@ -153,9 +232,9 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
         format!(
             "{} Tools Detail ({})",
             emoji("list", &style),
-            tool_list.count()
+            args.select.shown(tool_list.count(), total)
         ),
-        Some(format!("target={target} • {} ms", tool_list.elapsed_ms)),
+        Some(format!("target={target} • {}", tool_list.took())),
         &style,
     );
     println!("{header}");
@@ -173,11 +252,19 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
             .and_then(|v| v.as_str())
             .unwrap_or("<no description>");
         println!();
-        println!("#{}: {}", idx + 1, name);
+        println!("#{}: {}", idx + 1, unicode::reveal(name));
         println!(
             "  Description: {}",
-            if desc.is_empty() { "<none>" } else { desc }
+            if desc.is_empty() {
+                "<none>".to_string()
+            } else {
+                unicode::reveal(desc)
+            }
         );
+        if let Some(ann) = annotation_summary(t) {
+            println!("  Annotations: {ann}");
+        }
+        print_unicode_warnings(t, "  ", &style);
         let params = extract_params(t);
         if params.is_empty() {
             println!("  Parameters: (none)");
@@ -215,18 +302,18 @@ fn get_all_tools(args: GetArgs) -> Result<()> {
 
 /* ---- Singular tool ---- */
 
-fn get_single_tool(args: GetArgs) -> Result<()> {
+async fn get_single_tool(args: GetArgs) -> Result<()> {
     let Some(target) = args.target.as_deref() else {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tool",
                     "target": null,
                     "tool": null,
                     "note":"no target specified; use --target or MCP_TARGET"
-                })
+                }),
             );
         } else {
             println!("No target specified (use --target or MCP_TARGET).");
@@ -234,20 +321,21 @@ fn get_single_tool(args: GetArgs) -> Result<()> {
         return Ok(());
     };
 
-    let spec =
-        mcp::parse_target(target).with_context(|| format!("Failed to parse target: '{target}'"))?;
+    let spec = mcp::parse_target(target)
+        .with_context(|| format!("Failed to parse target: '{}'", mcp::redact_target(target)))?;
+    let target = spec.original();
 
     if !spec.is_local() {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tool",
                     "target": target,
                     "tool": null,
                     "note":"remote single-tool retrieval not implemented yet"
-                })
+                }),
             );
         } else {
             println!("(remote) Single tool retrieval not implemented for {target}");
@@ -255,18 +343,32 @@ fn get_single_tool(args: GetArgs) -> Result<()> {
         return Ok(());
     }
 
-    let tool_list = fetch_tools_local(&spec)?;
+    let (tool_list, attempts) = args
+        .retry
+        .run(|| async {
+            match &args.name {
+                Some(name) => fetch_tools_for(&spec, &[name]).await,
+                None => fetch_tools_local(&spec).await,
+            }
+        })
+        .await;
+    let mut tool_list = tool_list?;
+    if args.name.is_none() {
+        // Narrow the interactive choices.
+        args.select.apply(&mut tool_list.tools);
+        args.order.apply(&mut tool_list.tools);
+    }
     if tool_list.tools.is_empty() {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tool",
                     "target": target,
                     "tool": null,
                     "note":"no tools"
-                })
+                }),
             );
         } else {
             println!("No tools available.");
@@ -293,40 +395,84 @@ fn get_single_tool(args: GetArgs) -> Result<()> {
     }
 
     let Some(tool_obj) = found else {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"error",
                     "error":"tool not found",
                     "requested": final_name,
                     "subject":"tool",
                     "target": target
-                })
+                }),
             );
         } else {
             println!("Tool '{}' not found.", final_name);
         }
-        return Ok(());
+        return Err(exit::Status(exit::TOOL_NOT_FOUND).into());
     };
 
+    if args.example {
+        let example = tool_obj
+            .as_object()
+            .map(example_arguments)
+            .unwrap_or_default();
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
+                    "status":"ok",
+                    "subject":"tool",
+                    "target": target,
+                    "name": final_name,
+                    "example": example
+                }),
+            );
+        } else if args.output == OutputFormat::Markdown {
+            println!(
+                "```json\n{}\n```",
+                serde_json::to_string_pretty(&example).unwrap_or_else(|_| "{}".into())
+            );
+        } else {
+            println!(
+                "{}",
+                crate::cmd::output::pretty(&serde_json::Value::Object(example))
+            );
+        }
+        return Ok(());
+    }
+
     let params = extract_params(&tool_obj);
 
-    if args.json {
-        println!(
-            "{}",
-            serde_json::json!({
-                "status":"ok",
-                "subject":"tool",
-                "target": target,
-                "elapsed_ms": tool_list.elapsed_ms,
-                "name": final_name,
-                "tool": tool_obj,
-                "parameters": params.iter().map(|(n,t,r,d)| serde_json::json!({
-                    "name":n,"type":t,"required":r,"description":d
-                })).collect::<Vec<_>>()
-            })
-        );
+    if args.output.is_json() {
+        let mut out = serde_json::json!({
+            "status":"ok",
+            "subject":"tool",
+            "target": target,
+            "elapsed_ms": tool_list.elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "name": final_name,
+            "tool": tool_obj,
+            "unicode_issues": unicode::issues_json(&unicode::tool_issues(&tool_obj)),
+            "annotations": tool_obj.get("annotations"),
+            "hints": annotation_flags(&tool_obj),
+            "output_schema": mcp::schema::output_schema(&tool_obj),
+            "parameters": params.iter().map(|(n,t,r,d)| serde_json::json!({
+                "name":n,"type":t,"required":r,"description":d
+            })).collect::<Vec<_>>()
+        });
+        attempts.annotate(&mut out);
+        tool_list.annotate(&mut out);
+        print_document(args.output, &out);
+        return Ok(());
+    }
+
+    if args.output == OutputFormat::Markdown {
+        println!("{}", markdown_tool("## Tool: ", &final_name, &tool_obj));
+        return Ok(());
+    }
+    if args.output == OutputFormat::Csv {
+        print!("{}", csv_table(&CSV_COLUMNS, &csv_rows(&tool_obj)));
         return Ok(());
     }
 
@@ -334,63 +480,170 @@ fn get_single_tool(args: GetArgs) -> Result<()> {
     let style = StyleOptions::detect();
     let header = box_header(
         format!("{} Tool: {}", emoji("tool", &style), final_name),
-        Some(format!("target={target} • {} ms", tool_list.elapsed_ms)),
+        Some(format!("target={target} • {}", tool_list.took())),
         &style,
     );
     println!("{header}");
     if let Some(desc) = tool_obj.get("description").and_then(|v| v.as_str()) {
         println!(
             "Description: {}",
-            if desc.is_empty() { "<none>" } else { desc }
+            if desc.is_empty() {
+                "<none>".to_string()
+            } else {
+                unicode::reveal(desc)
+            }
         );
     } else {
         println!("Description: <none>");
     }
+    if let Some(ann) = annotation_summary(&tool_obj) {
+        println!("Annotations: {ann}");
+    }
+    print_unicode_warnings(&tool_obj, "", &style);
     if params.is_empty() {
         println!("Parameters: (none)");
     } else {
-        use crate::cmd::format::{StyleOptions, TableOpts, table};
-        let style = StyleOptions::detect();
-        let mut rows: Vec<Vec<String>> = Vec::new();
-        for (n, t, r, d) in params {
-            rows.push(vec![
+        println!("{}", fields_table(params));
+    }
+    if let Some(schema) = mcp::schema::output_schema(&tool_obj) {
+        let fields = schema_fields(Some(schema));
+        if fields.is_empty() {
+            println!("Output schema: {schema}");
+        } else {
+            println!("Output schema:");
+            println!("{}", fields_table(fields));
+        }
+    }
+
+    Ok(())
+}
+
+/// NAME / TYPE / REQ / DESCRIPTION table of schema fields.
+fn fields_table(fields: Vec<(String, String, bool, String)>) -> String {
+    use crate::cmd::format::{TableOpts, table};
+    let style = StyleOptions::detect();
+    let rows: Vec<Vec<String>> = fields
+        .into_iter()
+        .map(|(n, t, r, d)| {
+            vec![
                 n,
                 t,
                 if r { "yes".into() } else { "no".into() },
                 if d.is_empty() { "-".into() } else { d },
-            ]);
-        }
-        let tbl = table(
-            &["NAME", "TYPE", "REQ", "DESCRIPTION"],
-            &rows,
-            TableOpts {
-                max_width: style.term_width,
-                truncate: true,
-                header_sep: true,
-                zebra: false,
-                min_col_width: 2,
-            },
-            &style,
-        );
-        println!("{tbl}");
+            ]
+        })
+        .collect();
+    table(
+        &["NAME", "TYPE", "REQ", "DESCRIPTION"],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            truncate: true,
+            header_sep: true,
+            zebra: false,
+            min_col_width: 2,
+        },
+        &style,
+    )
+}
+
+/* ---- Markdown / CSV ---- */
+
+const CSV_COLUMNS: [&str; 5] = ["TOOL", "PARAMETER", "TYPE", "REQUIRED", "DESCRIPTION"];
+
+/// One parameter per row; a tool without parameters still gets a row.
+fn csv_rows(tool_obj: &serde_json::Value) -> Vec<Vec<String>> {
+    let name = tool_obj
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(unicode::reveal)
+        .unwrap_or_else(|| "<unnamed>".into());
+    let params = extract_params(tool_obj);
+    if params.is_empty() {
+        return vec![vec![
+            name,
+            String::new(),
+            String::new(),
+            String::new(),
+            String::new(),
+        ]];
     }
+    params
+        .into_iter()
+        .map(|(n, t, r, d)| {
+            vec![
+                name.clone(),
+                n,
+                t,
+                if r { "yes".into() } else { "no".into() },
+                d,
+            ]
+        })
+        .collect()
+}
 
-    Ok(())
+/// Heading, description, annotations, Unicode warnings, parameter and
+/// output schema tables of one tool.
+fn markdown_tool(heading: &str, name: &str, tool_obj: &serde_json::Value) -> String {
+    let mut out = vec![format!(
+        "{heading}{}",
+        markdown_escape(&unicode::reveal(name))
+    )];
+    let desc = tool_obj
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    out.push(if desc.is_empty() {
+        "_No description._".to_string()
+    } else {
+        markdown_escape(&unicode::reveal(desc))
+    });
+    if let Some(ann) = annotation_summary(tool_obj) {
+        out.push(format!("Annotations: {}", markdown_escape(&ann)));
+    }
+    for (loc, issue) in unicode::tool_issues(tool_obj) {
+        out.push(format!(
+            "**Warning:** suspicious Unicode in {loc}: {}",
+            markdown_escape(&issue.describe())
+        ));
+    }
+    let fields_table = |fields: Vec<(String, String, bool, String)>| {
+        let rows: Vec<Vec<String>> = fields
+            .into_iter()
+            .map(|(n, t, r, d)| vec![n, t, if r { "yes" } else { "no" }.into(), d])
+            .collect();
+        markdown_table(&["NAME", "TYPE", "REQ", "DESCRIPTION"], &rows)
+    };
+    let params = extract_params(tool_obj);
+    if params.is_empty() {
+        out.push("Parameters: none".into());
+    } else {
+        out.push(format!("Parameters:\n\n{}", fields_table(params)));
+    }
+    if let Some(schema) = mcp::schema::output_schema(tool_obj) {
+        let fields = schema_fields(Some(schema));
+        if fields.is_empty() {
+            out.push(format!("Output schema: `{schema}`"));
+        } else {
+            out.push(format!("Output schema:\n\n{}", fields_table(fields)));
+        }
+    }
+    out.join("\n\n")
 }
 
 /* ---- Placeholder subjects ---- */
 
-fn get_placeholder(subject: &str, json: bool) -> Result<()> {
-    if json {
-        println!(
-            "{}",
-            serde_json::json!({
+fn get_placeholder(subject: &str, output: OutputFormat) -> Result<()> {
+    if output.is_json() {
+        print_document(
+            output,
+            &serde_json::json!({
                 "status":"ok",
                 "subject": subject,
                 "count":0,
                 "items":[],
                 "note":"get for this subject not implemented yet"
-            })
+            }),
         );
     } else {
         println!("{subject}: detailed retrieval not implemented (0 items)");
@@ -403,13 +656,33 @@ fn get_placeholder(subject: &str, json: bool) -> Result<()> {
 /// Extract parameter list from a raw tool JSON object.
 ///
 /// Return vector of (name, type, required, description)
+/// Print one warning line per invisible-Unicode / homoglyph issue in the tool.
+fn print_unicode_warnings(tool_obj: &serde_json::Value, indent: &str, style: &StyleOptions) {
+    for (loc, issue) in unicode::tool_issues(tool_obj) {
+        println!(
+            "{indent}{} {}",
+            emoji("warn", style),
+            color(
+                Role::Warning,
+                format!("Suspicious Unicode in {loc}: {}", issue.describe()),
+                style
+            )
+        );
+    }
+}
+
 fn extract_params(tool_obj: &serde_json::Value) -> Vec<(String, String, bool, String)> {
+    schema_fields(
+        tool_obj
+            .get("input_schema")
+            .or_else(|| tool_obj.get("inputSchema")),
+    )
+}
+
+/// Top-level properties of an object schema as (name, type, required, description).
+fn schema_fields(schema: Option<&serde_json::Value>) -> Vec<(String, String, bool, String)> {
     let mut params = Vec::new();
-    let Some(schema) = tool_obj
-        .get("input_schema")
-        .or_else(|| tool_obj.get("inputSchema"))
-        .and_then(|v| v.as_object())
-    else {
+    let Some(schema) = schema.and_then(|v| v.as_object()) else {
         return params;
     };
 
@@ -520,6 +793,23 @@ mod tests {
         assert!(!p[1].2);
     }
 
+    #[test]
+    fn csv_and_markdown_sections() {
+        let val = serde_json::json!({
+            "name":"demo",
+            "description":"Reads <b>files</b>",
+            "inputSchema":{"type":"object","required":["a"],"properties":{"a":{"type":"string"}}}
+        });
+        assert_eq!(csv_rows(&val), vec![vec!["demo", "a", "string", "yes", ""]]);
+        assert_eq!(
+            csv_rows(&serde_json::json!({"name":"bare"})),
+            vec![vec!["bare", "", "", "", ""]]
+        );
+        let md = markdown_tool("## Tool: ", "demo", &val);
+        assert!(md.starts_with("## Tool: demo\n\nReads &lt;b&gt;files&lt;/b&gt;\n\nParameters:"));
+        assert!(md.ends_with("| a | string | yes |  |"));
+    }
+
     #[test]
     fn interactive_select_tool_fallback_name() {
         // We cannot simulate stdin easily here; just test helper functions above.
@@ -2,36 +2,131 @@
 list.rs - list subcommand.
 
 Lists tools (and placeholder subjects). Uses a local MCP process target to
-enumerate tool names + brief metadata, emitting either a human table or JSON.
-Remote enumeration is not implemented yet.
+enumerate tool names + brief metadata, emitting either a human table or JSON
+(or YAML); markdown and CSV give the same table, untruncated, for reports
+and spreadsheets.
+The RISK column shows the highest capability severity and the dangerous
+capabilities detected (exec, fs-write, credentials, egress, messaging);
+HINTS the annotations the server sets to true (ro, destructive,
+idempotent, open-world). `--columns name,params,risk` picks (and orders)
+the columns; REQUIRED and TITLE are available in addition to the default
+set. Resource listing is not implemented yet, so it has no columns.
+`--filter` / `--grep` narrow and `--sort` / `--reverse` order the tools first
+(see crate::cmd::selection).
+The listing always comes from the server, never from the metadata cache
+(crate::mcp::cache), so added or changed tools show up at once; it does
+refresh the cache for get / exec.
+Several targets (-t repeated / --targets FILE) give one combined report
+(see crate::cmd::fleet). Remote enumeration is not implemented yet.
 */
 
 use anyhow::{Context, Result};
 use clap::Args;
 
-use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
-use crate::cmd::shared::fetch_tools_local;
+use crate::cmd::fleet::FleetArgs;
+use crate::cmd::format::{
+    Role, StyleOptions, TableOpts, box_header, color, csv_table, emoji, markdown_table, table,
+};
+use crate::cmd::output::{OutputFormat, print_document};
+use crate::cmd::retry::RetryArgs;
+use crate::cmd::selection::{FilterArgs, SortArgs};
+use crate::cmd::shared::{annotation_flags, fetch_tools_live};
 use crate::cmd::subject::Subject;
 use crate::mcp;
+use crate::scan::{risk, unicode};
 
 /// CLI arguments for `mcp-hack list <subject>`
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[
+    OutputFormat::Text,
+    OutputFormat::Json,
+    OutputFormat::Markdown,
+    OutputFormat::Csv,
+    OutputFormat::Yaml,
+];
+
+/// Columns of the tools table (`--columns`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Position in the server's list (#)
+    #[value(alias = "#")]
+    Index,
+    Name,
+    /// Highest capability severity and dangerous capabilities
+    Risk,
+    /// Annotations set to true (ro, destructive, idempotent, open-world)
+    Hints,
+    /// Parameters as name:type
+    Params,
+    /// Required parameters
+    Required,
+    /// Display title (annotations.title or title)
+    Title,
+    Description,
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Column::Index => "#",
+            Column::Name => "NAME",
+            Column::Risk => "RISK",
+            Column::Hints => "HINTS",
+            Column::Params => "PARAMS",
+            Column::Required => "REQUIRED",
+            Column::Title => "TITLE",
+            Column::Description => "DESCRIPTION",
+        }
+    }
+}
+
+const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Index,
+    Column::Name,
+    Column::Risk,
+    Column::Hints,
+    Column::Params,
+    Column::Description,
+];
+
 #[derive(Args, Debug)]
 pub struct ListArgs {
     /// Subject to list (tools|tool|resources|prompts)
     pub subject: Subject,
 
-    /// Output JSON instead of human-readable text
-    #[arg(long)]
-    pub json: bool,
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+
+    /// Target MCP endpoint (local command or remote URL). Falls back to MCP_TARGET env.
+    /// Repeat (or use --targets) to run against several targets.
+    #[arg(short = 't', long = "target", id = "target", value_name = "TARGET")]
+    pub targets: Vec<String>,
 
-    /// Target MCP endpoint (local command or remote URL)
-    /// (Falls back to MCP_TARGET env var if omitted)
-    #[arg(short = 't', long)]
+    /// Table columns, comma separated (text / markdown / csv), e.g. name,params,risk
+    /// [default: index,name,risk,hints,params,description]
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<Column>,
+
+    #[command(flatten)]
+    pub select: FilterArgs,
+
+    #[command(flatten)]
+    pub order: SortArgs,
+
+    #[command(flatten)]
+    pub fleet: FleetArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    /// Effective single target (set by the dispatcher)
+    #[arg(skip)]
     pub target: Option<String>,
 }
 
 /// Entry point for the list subcommand.
-pub fn execute_list(mut args: ListArgs) -> Result<()> {
+pub async fn execute_list(mut args: ListArgs) -> Result<()> {
     // If user didn't supply --target, fall back to MCP_TARGET env.
     if args.target.is_none()
         && let Ok(env_t) = std::env::var("MCP_TARGET")
@@ -41,29 +136,29 @@ pub fn execute_list(mut args: ListArgs) -> Result<()> {
     }
 
     match args.subject {
-        Subject::Tools | Subject::Tool => list_tools(args),
-        Subject::Resources => list_placeholder("resources", args.json),
-        Subject::Prompts => list_placeholder("prompts", args.json),
+        Subject::Tools | Subject::Tool => list_tools(args).await,
+        Subject::Resources => list_placeholder("resources", args.output),
+        Subject::Prompts => list_placeholder("prompts", args.output),
     }
 }
 
 /// List tools (plural). Subject `tool` (singular) aliases to this command to
 /// avoid special-casing the output format for a single item selection here.
-fn list_tools(args: ListArgs) -> Result<()> {
+async fn list_tools(args: ListArgs) -> Result<()> {
     let target_opt = args.target.as_deref();
 
     let Some(target) = target_opt else {
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tools",
                     "target": null,
                     "count":0,
                     "tools":[],
                     "note":"no target specified; use --target or MCP_TARGET"
-                })
+                }),
             );
         } else {
             println!("No target specified (use --target or set MCP_TARGET).");
@@ -72,22 +167,23 @@ fn list_tools(args: ListArgs) -> Result<()> {
         return Ok(());
     };
 
-    let spec =
-        mcp::parse_target(target).with_context(|| format!("Failed to parse target: '{target}'"))?;
+    let spec = mcp::parse_target(target)
+        .with_context(|| format!("Failed to parse target: '{}'", mcp::redact_target(target)))?;
+    let target = spec.original();
 
     if !spec.is_local() {
         // Remote placeholder
-        if args.json {
-            println!(
-                "{}",
-                serde_json::json!({
+        if args.output.is_json() {
+            print_document(
+                args.output,
+                &serde_json::json!({
                     "status":"ok",
                     "subject":"tools",
                     "target": target,
                     "count":0,
                     "tools":[],
                     "note":"remote tool enumeration not implemented yet"
-                })
+                }),
             );
         } else {
             println!("Tools (0) - target: {target} (remote enumeration not implemented)");
@@ -95,10 +191,14 @@ fn list_tools(args: ListArgs) -> Result<()> {
         return Ok(());
     }
 
-    let tool_list = fetch_tools_local(&spec)?;
+    let (tool_list, attempts) = args.retry.run(|| fetch_tools_live(&spec)).await;
+    let mut tool_list = tool_list?;
+    let total = tool_list.count();
+    args.select.apply(&mut tool_list.tools);
+    args.order.apply(&mut tool_list.tools);
     let count = tool_list.count();
 
-    if args.json {
+    if args.output.is_json() {
         let mut items = Vec::with_capacity(count);
         for t in &tool_list.tools {
             let name = t
@@ -112,102 +212,177 @@ fn list_tools(args: ListArgs) -> Result<()> {
                 .to_string();
             items.push(serde_json::json!({
                 "name": name,
-                "description": desc
+                "description": desc,
+                "unicode_issues": unicode::issues_json(&unicode::tool_issues(t)),
+                "risk": risk::risk_json(&risk::classify(t)),
+                "annotations": t.get("annotations"),
+                "hints": annotation_flags(t),
             }));
         }
 
-        println!(
-            "{}",
-            serde_json::json!({
-                "status":"ok",
-                "subject":"tools",
-                "target": target,
-                "elapsed_ms": tool_list.elapsed_ms,
-                "count": count,
-                "tools": items
-            })
-        );
+        let mut out = serde_json::json!({
+            "status":"ok",
+            "subject":"tools",
+            "target": target,
+            "elapsed_ms": tool_list.elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "count": count,
+            "tools": items
+        });
+        if args.select.active() {
+            out["total"] = total.into();
+        }
+        attempts.annotate(&mut out);
+        tool_list.annotate(&mut out);
+        print_document(args.output, &out);
         return Ok(());
     }
 
+    // Markdown / CSV: the same columns, untruncated and without decoration
+    let document = matches!(args.output, OutputFormat::Markdown | OutputFormat::Csv);
+
     // Human-readable output
     // Fancy header + table formatting
     let style = StyleOptions::detect();
 
-    let header = box_header(
-        format!("{} Tools ({count})", emoji("list", &style)),
-        Some(format!("target={target} • {} ms", tool_list.elapsed_ms)),
-        &style,
-    );
-    println!("{header}");
-
-    if count == 0 {
-        println!(
-            "{}",
-            color(
-                Role::Dim,
-                format!("{} (none)", emoji("info", &style)),
-                &style
-            )
+    if !document {
+        let header = box_header(
+            format!(
+                "{} Tools ({})",
+                emoji("list", &style),
+                args.select.shown(count, total)
+            ),
+            Some(format!("target={target} • {}", tool_list.took())),
+            &style,
         );
-        return Ok(());
+        println!("{header}");
+
+        if count == 0 {
+            println!(
+                "{}",
+                color(
+                    Role::Dim,
+                    format!("{} (none)", emoji("info", &style)),
+                    &style
+                )
+            );
+            return Ok(());
+        }
     }
 
-    // Build rows with columns: ["#", "NAME", "PARAMS", "DESCRIPTION"]
+    // Build rows with the selected columns (default: # NAME RISK HINTS PARAMS DESCRIPTION)
     // PARAMS: summarized as "p1:type, p2:type" (truncated)
+    let columns: &[Column] = if args.columns.is_empty() {
+        DEFAULT_COLUMNS
+    } else {
+        &args.columns
+    };
     let mut table_rows: Vec<Vec<String>> = Vec::with_capacity(count);
+    let mut suspicious = 0usize;
     for (idx, t) in tool_list.tools.iter().enumerate() {
-        let name = t
+        let raw_name = t
             .get("name")
             .and_then(|v| v.as_str())
-            .unwrap_or("<unnamed>")
-            .to_string();
-        let desc_raw = t
-            .get("description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("")
-            .replace('\n', " ");
-
-        // Parameter summary
-        let mut param_pairs: Vec<String> = Vec::new();
-        if let Some(schema) = t
-            .get("input_schema")
-            .or_else(|| t.get("inputSchema"))
-            .and_then(|v| v.as_object())
-            && let Some(props) = schema.get("properties").and_then(|v| v.as_object())
-        {
-            for (pname, pobj) in props.iter().take(8) {
-                let ptype = pobj
-                    .as_object()
-                    .and_then(|m| m.get("type"))
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("any");
-                param_pairs.push(format!("{pname}:{ptype}"));
-            }
-            if props.len() > 8 {
-                param_pairs.push("…".into());
-            }
-        }
-        let param_summary = if param_pairs.is_empty() {
-            "-".to_string()
+            .unwrap_or("<unnamed>");
+        // Invisible characters are revealed; flagged tools get a warning marker.
+        let name = if unicode::tool_issues(t).is_empty() {
+            raw_name.to_string()
+        } else if document {
+            unicode::reveal(raw_name)
         } else {
-            param_pairs.join(", ")
+            suspicious += 1;
+            format!("{} {}", emoji("warn", &style), unicode::reveal(raw_name))
         };
+        let schema = t
+            .get("input_schema")
+            .or_else(|| t.get("inputSchema"))
+            .and_then(|v| v.as_object());
+        let props = schema
+            .and_then(|s| s.get("properties"))
+            .and_then(|v| v.as_object());
 
-        // Truncate description for table view
-        let desc = if desc_raw.len() > 90 {
-            let mut s = desc_raw[..87].to_string();
-            s.push_str("...");
-            s
-        } else {
-            desc_raw
-        };
+        let row = columns
+            .iter()
+            .map(|column| match column {
+                Column::Index => (idx + 1).to_string(),
+                Column::Name => name.clone(),
+                Column::Risk => risk::risk_cell(&risk::classify(t)),
+                Column::Hints => or_dash(annotation_flags(t).join(",")),
+                Column::Params => {
+                    // Parameter summary
+                    let mut param_pairs: Vec<String> = Vec::new();
+                    if let Some(props) = props {
+                        let shown = if document { props.len() } else { 8 };
+                        for (pname, pobj) in props.iter().take(shown) {
+                            let ptype = pobj
+                                .as_object()
+                                .and_then(|m| m.get("type"))
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("any");
+                            param_pairs.push(format!("{pname}:{ptype}"));
+                        }
+                        if props.len() > shown {
+                            param_pairs.push("…".into());
+                        }
+                    }
+                    or_dash(param_pairs.join(", "))
+                }
+                Column::Required => or_dash(
+                    schema
+                        .and_then(|s| s.get("required"))
+                        .and_then(|v| v.as_array())
+                        .map(|r| {
+                            r.iter()
+                                .filter_map(|v| v.as_str())
+                                .collect::<Vec<_>>()
+                                .join(",")
+                        })
+                        .unwrap_or_default(),
+                ),
+                Column::Title => or_dash(unicode::reveal(
+                    t.pointer("/annotations/title")
+                        .or_else(|| t.get("title"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                )),
+                Column::Description => {
+                    let desc_raw = unicode::reveal(
+                        t.get("description").and_then(|v| v.as_str()).unwrap_or(""),
+                    )
+                    .replace('\n', " ");
+                    // Truncate description for the default table view; the
+                    // table itself still fits the terminal width.
+                    if !document && args.columns.is_empty() && desc_raw.len() > 90 {
+                        let mut s = desc_raw[..87].to_string();
+                        s.push_str("...");
+                        s
+                    } else {
+                        desc_raw
+                    }
+                }
+            })
+            .collect();
+        table_rows.push(row);
+    }
 
-        table_rows.push(vec![(idx + 1).to_string(), name, param_summary, desc]);
+    let columns: Vec<&str> = columns.iter().map(|c| c.header()).collect();
+    if args.output == OutputFormat::Csv {
+        print!("{}", csv_table(&columns, &table_rows));
+        return Ok(());
+    }
+    if args.output == OutputFormat::Markdown {
+        println!(
+            "## Tools ({})\n\nTarget: {} • {}\n",
+            args.select.shown(count, total),
+            crate::cmd::format::markdown_escape(target),
+            tool_list.took()
+        );
+        println!("{}", markdown_table(&columns, &table_rows));
+        return Ok(());
     }
 
     let tbl = table(
-        &["#", "NAME", "PARAMS", "DESCRIPTION"],
+        &columns,
         &table_rows,
         TableOpts {
             max_width: style.term_width,
@@ -220,6 +395,20 @@ fn list_tools(args: ListArgs) -> Result<()> {
     );
     println!("{tbl}");
 
+    if suspicious > 0 {
+        println!(
+            "\n{} {}",
+            emoji("warn", &style),
+            color(
+                Role::Warning,
+                format!(
+                    "{suspicious} tool(s) contain invisible Unicode or homoglyphs (see `mcp-hack scan`)"
+                ),
+                &style
+            )
+        );
+    }
+
     println!(
         "\n{} {}",
         emoji("info", &style),
@@ -233,18 +422,22 @@ fn list_tools(args: ListArgs) -> Result<()> {
     Ok(())
 }
 
+fn or_dash(cell: String) -> String {
+    if cell.is_empty() { "-".into() } else { cell }
+}
+
 /// Placeholder listing for unimplemented subjects.
-fn list_placeholder(subject: &str, json: bool) -> Result<()> {
-    if json {
-        println!(
-            "{}",
-            serde_json::json!({
+fn list_placeholder(subject: &str, output: OutputFormat) -> Result<()> {
+    if output.is_json() {
+        print_document(
+            output,
+            &serde_json::json!({
                 "status":"ok",
                 "subject": subject,
                 "count":0,
                 "items":[],
                 "note":"listing for this subject not implemented yet"
-            })
+            }),
         );
     } else {
         println!("{subject}: listing not implemented (0 items)");
@@ -276,7 +469,20 @@ mod tests {
         match cli.cmd {
             TestSub::List(a) => {
                 assert!(matches!(a.subject, Subject::Tools));
+                assert!(a.columns.is_empty());
             }
         }
     }
+
+    #[test]
+    fn clap_parses_columns() {
+        let cli = TestCli::try_parse_from(["t", "list", "tools", "--columns", "name,#,required"])
+            .unwrap();
+        let TestSub::List(a) = cli.cmd;
+        assert_eq!(
+            a.columns,
+            vec![Column::Name, Column::Index, Column::Required]
+        );
+        assert!(TestCli::try_parse_from(["t", "list", "tools", "--columns", "nope"]).is_err());
+    }
 }
@@ -6,20 +6,72 @@ Keeps only:
   - Public re-exports used by `main.rs`
 
 All logic lives in the per-command modules:
-  exec.rs, get.rs, list.rs, subject.rs, shared.rs, format.rs
+  api.rs, bench.rs, canary.rs, codegen.rs, content.rs, diff.rs, discover.rs, exec.rs, exit.rs, expect.rs, export.rs, fingerprint.rs, fleet.rs, fuzz.rs, get.rs, list.rs, pollution.rs, proxy.rs, raw.rs, replay.rs, retry.rs, run.rs, scan.rs, selection.rs, serve.rs, shadow.rs, soak.rs, stats.rs, stress.rs, subject.rs, watch.rs, web.rs (feature `web`), shared.rs, format.rs, output.rs, errors.rs
 
 Add new commands by creating a file and re-exporting its args + execute function here.
 */
 
+pub mod api;
+pub mod bench;
+pub mod canary;
+pub mod codegen;
+pub mod content;
+pub mod diff;
+pub mod discover;
+pub mod errors;
 pub mod exec;
+pub mod exit;
+pub mod expect;
+pub mod export;
+pub mod fingerprint;
+pub mod fleet;
 pub mod format;
 pub mod fuzz;
 pub mod get;
 pub mod list;
+pub mod output;
+pub mod pollution;
+pub mod proxy;
+pub mod raw;
+pub mod replay;
+pub mod retry;
+pub mod run;
+pub mod scan;
+pub mod selection;
+pub mod serve;
+pub mod shadow;
 pub mod shared;
+pub mod soak;
+pub mod stats;
+pub mod stress;
 pub mod subject;
+pub mod watch;
+#[cfg(feature = "web")]
+pub mod web;
 
+pub use api::{ApiArgs, execute_api};
+pub use bench::{BenchArgs, execute_bench};
+pub use canary::{CanaryArgs, execute_canary};
+pub use diff::{DiffArgs, execute_diff};
+pub use discover::{DiscoverArgs, execute_discover};
 pub use exec::{ExecArgs, execute_exec};
+pub use export::{ExportArgs, execute_export};
+pub use fingerprint::{FingerprintArgs, execute_fingerprint};
 pub use fuzz::{FuzzArgs, execute_fuzz};
 pub use get::{GetArgs, execute_get};
 pub use list::{ListArgs, execute_list};
+pub use output::OutputFormat;
+pub use pollution::{PollutionArgs, execute_pollution};
+pub use proxy::{ProxyArgs, execute_proxy};
+pub use raw::{RawArgs, execute_raw};
+pub use replay::{ReplayArgs, execute_replay};
+pub use run::{RunArgs, execute_run};
+pub use scan::{ScanArgs, execute_scan};
+pub use serve::{ServeArgs, execute_serve};
+pub use shadow::{ShadowArgs, execute_shadow};
+pub use soak::{SoakArgs, execute_soak};
+pub use stats::{StatsArgs, execute_stats};
+pub use stress::{StressArgs, execute_stress};
+pub use watch::{WatchArgs, execute_watch};
+#[cfg(feature = "web")]
+pub use web::{WebArgs, execute_web};
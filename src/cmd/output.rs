@@ -0,0 +1,200 @@
+/*!
+output.rs - output format selection.
+
+One global option picks the output format for every command:
+  --format text|json|ndjson|sarif|markdown|csv|yaml
+  (--json / --ndjson are short forms, `table` is another name for text)
+
+Each command module declares the formats it can produce in `FORMATS`; the
+dispatcher (main.rs) checks the request against that list with `select`
+and stores the result in the command's `output` field. Commands branch on
+`output.is_json()` (any machine-readable format) and on specific variants
+where the shape differs (SARIF). YAML is the JSON document re-serialized
+(`print_document`); markdown and CSV are tables for reports and
+spreadsheets, rendered by crate::cmd::format.
+
+--query FILTER (crate::utils::query) reshapes every JSON / YAML / NDJSON
+document a command prints: each output of the filter is printed in the
+document's place. Error documents are printed unfiltered. A filter that
+fails on a document is reported once on stderr, later documents are
+dropped, and the command ends with status 2 (`query_failed`, checked by
+the dispatcher).
+
+Pretty JSON (`print_json`) is syntax-highlighted with the format.rs role
+palette when stdout is a terminal and color is enabled (no --plain /
+NO_COLOR); piped or redirected output is always plain JSON.
+*/
+
+use anyhow::{Result, bail};
+use clap::ValueEnum;
+use serde_json::Value;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::utils::query::Query;
+
+static QUERY: OnceLock<Query> = OnceLock::new();
+static QUERY_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Install the --query filter for this process (first call wins).
+pub fn set_query(query: Query) {
+    let _ = QUERY.set(query);
+}
+
+/// Output format of a command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables / boxes
+    #[default]
+    #[value(alias = "table")]
+    Text,
+    /// One JSON document
+    Json,
+    /// Newline-delimited JSON events
+    Ndjson,
+    /// SARIF 2.1.0 log
+    Sarif,
+    /// Markdown tables, for pasting into reports
+    Markdown,
+    /// Comma-separated values with a header row
+    Csv,
+    /// The JSON document as YAML
+    Yaml,
+}
+
+impl OutputFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Yaml => "yaml",
+        }
+    }
+
+    /// Output built from the JSON document (errors are reported as JSON
+    /// objects too, which YAML readers accept as well).
+    pub fn is_json(self) -> bool {
+        !matches!(
+            self,
+            OutputFormat::Text | OutputFormat::Markdown | OutputFormat::Csv
+        )
+    }
+}
+
+/// `doc` through --query: the filter's outputs, or the document itself.
+fn queried(doc: &Value) -> Vec<Value> {
+    let Some(query) = QUERY.get() else {
+        return vec![doc.clone()];
+    };
+    if QUERY_FAILED.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    match query.apply(doc) {
+        Ok(values) => values,
+        Err(e) => {
+            if !QUERY_FAILED.swap(true, Ordering::Relaxed) {
+                eprintln!("--query '{}': {e:#}", query.source());
+            }
+            Vec::new()
+        }
+    }
+}
+
+/// Whether --query failed on a document this run.
+pub fn query_failed() -> bool {
+    QUERY_FAILED.load(Ordering::Relaxed)
+}
+
+/// Print a command's JSON document: one JSON line, or YAML.
+pub fn print_document(format: OutputFormat, doc: &Value) {
+    for doc in queried(doc) {
+        if format == OutputFormat::Yaml {
+            print!("{}", crate::cmd::format::yaml(&doc));
+        } else {
+            println!("{doc}");
+        }
+    }
+}
+
+/// `doc` pretty-printed, highlighted when stdout is a color terminal.
+pub fn pretty(doc: &Value) -> String {
+    let style = crate::cmd::format::StyleOptions::detect();
+    if style.use_color && std::io::stdout().is_terminal() {
+        return crate::cmd::format::json_highlight(doc, &style);
+    }
+    serde_json::to_string_pretty(doc).unwrap_or_else(|_| doc.to_string())
+}
+
+/// Print a command's JSON document pretty-printed.
+pub fn print_json(doc: &Value) {
+    for doc in queried(doc) {
+        println!("{}", pretty(&doc));
+    }
+}
+
+/// Print one NDJSON event.
+pub fn print_line(event: &Value) {
+    for event in queried(event) {
+        println!("{event}");
+    }
+}
+
+/// Resolve the requested format against a command's supported formats.
+///
+/// No request means text. JSON and NDJSON stand in for each other when a
+/// command only streams events (or only prints one document).
+pub fn select(
+    command: &str,
+    supported: &[OutputFormat],
+    requested: Option<OutputFormat>,
+) -> Result<OutputFormat> {
+    let Some(want) = requested else {
+        return Ok(OutputFormat::Text);
+    };
+    if supported.contains(&want) {
+        return Ok(want);
+    }
+    let substitute = match want {
+        OutputFormat::Json => Some(OutputFormat::Ndjson),
+        OutputFormat::Ndjson => Some(OutputFormat::Json),
+        _ => None,
+    };
+    if let Some(s) = substitute
+        && supported.contains(&s)
+    {
+        return Ok(s);
+    }
+    let names: Vec<&str> = supported.iter().map(|f| f.as_str()).collect();
+    bail!(
+        "'{command}' does not support --format {} (supported: {})",
+        want.as_str(),
+        names.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OutputFormat::*;
+
+    #[test]
+    fn selects_supported_or_substitute() {
+        assert_eq!(select("list", &[Text, Json], None).unwrap(), Text);
+        assert_eq!(
+            select("scan", &[Text, Json, Sarif], Some(Sarif)).unwrap(),
+            Sarif
+        );
+        assert_eq!(
+            select("watch", &[Text, Ndjson], Some(Json)).unwrap(),
+            Ndjson
+        );
+        assert_eq!(select("list", &[Text, Json], Some(Ndjson)).unwrap(), Json);
+        let err = select("list", &[Text, Json], Some(Sarif)).unwrap_err();
+        assert!(err.to_string().contains("supported: text, json"));
+    }
+}
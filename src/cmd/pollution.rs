@@ -0,0 +1,205 @@
+/*!
+pollution.rs - pollution subcommand.
+
+Maps shared-state channels inside one server: a unique marker is sent
+through each tool's free-form string parameters, then every tool is called
+again with neutral placeholders and resources / prompts are read. Markers
+that show up outside their own plant result are reported as source -> sink
+edges (see crate::scan::pollution).
+
+Tools are really invoked. Destructive tools (annotations.destructiveHint)
+and exec / messaging tools are skipped unless --include-destructive is given.
+
+Example:
+  mcp-hack pollution -t "python3 server.py"
+  mcp-hack pollution -t "npx -y some-mcp-server" --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::scan::{Finding, pollution};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct PollutionArgs {
+    /// Also call destructive, exec and messaging tools
+    #[arg(long = "include-destructive")]
+    pub include_destructive: bool,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_pollution(mut args: PollutionArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote pollution testing not implemented yet",
+        );
+    }
+
+    let started = Instant::now();
+    let run = match pollution::run(
+        &spec,
+        Duration::from_secs(args.timeout),
+        args.include_destructive,
+    )
+    .await
+    {
+        Ok(r) => r,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let findings = run.findings();
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+
+    if args.output.is_json() {
+        let out = serde_json::json!({
+            "status": "ok",
+            "target": target_raw,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "markers": run.markers.iter().map(|m| serde_json::json!({
+                "tool": m.tool,
+                "params": m.params,
+                "marker": m.value,
+            })).collect::<Vec<_>>(),
+            "channels": run.edges.iter().map(|(i, sink)| serde_json::json!({
+                "source": run.markers[*i].tool,
+                "sink": sink.describe(),
+            })).collect::<Vec<_>>(),
+            "skipped": run.skipped.iter().map(|(tool, reason)| serde_json::json!({
+                "tool": tool,
+                "reason": reason,
+            })).collect::<Vec<_>>(),
+            "probes": run.probes,
+            "count": findings.len(),
+            "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} State pollution • {} channel(s)",
+            emoji("spark", &style),
+            run.edges.len()
+        ),
+        Some(format!("target={target_raw} • {elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} planted {} marker(s) • {} probe(s)",
+        emoji("info", &style),
+        run.markers.len(),
+        run.probes
+    );
+    for m in &run.markers {
+        println!(
+            "  {} {} {}",
+            emoji("tool", &style),
+            m.tool,
+            color(Role::Dim, m.params.join(", "), &style)
+        );
+    }
+    for (tool, reason) in &run.skipped {
+        println!(
+            "  {} {tool} {}",
+            emoji("info", &style),
+            color(Role::Dim, format!("skipped: {reason}"), &style)
+        );
+    }
+    println!();
+    if run.markers.is_empty() {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                "No callable tool takes free-form string input",
+                &style
+            )
+        );
+        return Ok(());
+    }
+    if run.edges.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, "No shared-state channel observed", &style)
+        );
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = run
+        .edges
+        .iter()
+        .map(|(i, sink)| vec![run.markers[*i].tool.clone(), sink.describe()])
+        .collect();
+    let tbl = table(
+        &["SOURCE", "SINK"],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            ..Default::default()
+        },
+        &style,
+    );
+    println!("{tbl}");
+    println!();
+    for f in &findings {
+        println!(
+            "  {} {}",
+            color(Role::Dim, f.severity.as_str().to_uppercase(), &style),
+            f.title
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+/*!
+proxy.rs - proxy subcommand.
+
+Stdio man-in-the-middle between an MCP client and a server: configure the
+client to launch `mcp-hack proxy -t "<server command>"` instead of the
+server. Newline-delimited JSON-RPC from the client (stdin) goes to the
+spawned server and the server's stdout comes back, both passing through
+the tamper rules (see crate::mcp::tamper), which can rewrite, drop, delay
+or inject messages in either direction. Without --rules messages are
+forwarded unchanged, which is useful with --log alone.
+
+stdout belongs to the protocol: notes about fired rules go to stderr
+(silenced by -q) and --log PATH appends one NDJSON record per message
+(time, direction, fired rules, original and forwarded message).
+
+Example:
+  mcp-hack proxy -t "python3 server.py" --rules tamper.yaml
+  mcp-hack proxy -t "npx -y some-mcp-server" --log traffic.ndjson
+
+Socket targets (unix:// / named pipe / tcp://) are connected to instead
+of spawned. Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::output::OutputFormat;
+use crate::mcp::record::{self, Dir};
+use crate::mcp::spawn::ServerProcess;
+use crate::mcp::tamper::{self, Direction, Tamper};
+use crate::mcp::{self, TargetSpec};
+use crate::utils::logging::{LogLevel, current_log_level};
+use crate::utils::utc_timestamp;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports (stdout carries the protocol).
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text];
+
+#[derive(Args, Debug)]
+pub struct ProxyArgs {
+    /// Tamper rules file (YAML)
+    #[arg(long, value_name = "PATH")]
+    pub rules: Option<String>,
+
+    /// Append one NDJSON record per proxied message to this file
+    #[arg(long, value_name = "PATH")]
+    pub log: Option<String>,
+
+    /// Target MCP server (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// State shared by both pump threads.
+struct Shared {
+    tamper: Mutex<Tamper>,
+    log: Option<Mutex<File>>,
+    notes: bool,
+    /// Transcript session (see mcp::record)
+    session: Option<u64>,
+}
+
+impl Shared {
+    /// Run one line through the rules; returns the lines to send.
+    fn handle(&self, dir: Direction, line: &str) -> Vec<String> {
+        let Ok(msg) = serde_json::from_str::<Value>(line) else {
+            return vec![line.to_string()];
+        };
+        if dir == Direction::ToClient {
+            record::message(self.session, Dir::Recv, &msg);
+        }
+        let outcome = self
+            .tamper
+            .lock()
+            .map(|mut t| t.apply(dir, msg.clone()))
+            .unwrap_or_default();
+        if self.notes && !outcome.fired.is_empty() {
+            eprintln!(
+                "[proxy] {} {}: {}{}",
+                dir.as_str(),
+                msg.get("method")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("response"),
+                outcome.fired.join(", "),
+                if outcome.forward.is_none() {
+                    " (dropped)"
+                } else {
+                    ""
+                }
+            );
+        }
+        if let Some(log) = &self.log
+            && let Ok(mut f) = log.lock()
+        {
+            let record = json!({
+                "time": utc_timestamp(),
+                "direction": dir.as_str(),
+                "rules": outcome.fired,
+                "message": msg,
+                "forwarded": outcome.forward,
+                "injected": outcome.inject,
+            });
+            let _ = writeln!(f, "{record}");
+        }
+        if !outcome.delay.is_zero() {
+            std::thread::sleep(outcome.delay);
+        }
+        // The transcript shows the server's side: what it was sent and what it said.
+        if dir == Direction::ToServer {
+            for v in outcome.forward.iter().chain(&outcome.inject) {
+                record::message(self.session, Dir::Send, v);
+            }
+        }
+        outcome
+            .forward
+            .iter()
+            .chain(&outcome.inject)
+            .map(|v| v.to_string())
+            .collect()
+    }
+}
+
+/// Copy lines from `input` to `output` through the rules until EOF.
+fn pump(shared: &Shared, dir: Direction, input: impl BufRead, mut output: impl Write) {
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+        for out in shared.handle(dir, &line) {
+            if writeln!(output, "{out}")
+                .and_then(|_| output.flush())
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_proxy(mut args: ProxyArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if spec.is_remote() {
+        return usage_error(args.output.is_json(), "remote proxying not implemented yet");
+    }
+
+    let rules = match &args.rules {
+        Some(path) => match tamper::load(path) {
+            Ok(r) => r,
+            Err(e) => return report_error(args.output.is_json(), e),
+        },
+        None => Vec::new(),
+    };
+    let log = match &args.log {
+        Some(path) => Some(Mutex::new(
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("failed to open log {path}"))?,
+        )),
+        None => None,
+    };
+    let shared = Arc::new(Shared {
+        tamper: Mutex::new(Tamper::new(rules)),
+        log,
+        notes: current_log_level() >= LogLevel::Info,
+        session: record::session(&target_raw),
+    });
+
+    let (server_out, server_in, child) = match &spec {
+        TargetSpec::Socket { socket, .. } => {
+            let (read, write) =
+                mcp::socket::connect_blocking(socket).map_err(crate::cmd::exit::connection)?;
+            (read, write, None)
+        }
+        TargetSpec::LocalCommand {
+            program,
+            args: argv,
+            ..
+        } => {
+            // Server stderr is passed through so the client still sees its logs.
+            let mut command = Command::new(program);
+            crate::mcp::spawn::prepare(command.args(argv));
+            let mut child = command
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .spawn()
+                .with_context(|| format!("failed to spawn '{program}'"))?;
+            let server_in = child.stdin.take().context("server stdin unavailable")?;
+            let server_out = child.stdout.take().context("server stdout unavailable")?;
+            let out: Box<dyn std::io::Read + Send> = Box::new(server_out);
+            let write: Box<dyn Write + Send> = Box::new(server_in);
+            // Killed by mcp::spawn::kill_children on Ctrl-C / SIGTERM.
+            (out, write, Some(ServerProcess::track_blocking(child)))
+        }
+        TargetSpec::RemoteUrl { .. } => unreachable!("rejected above"),
+    };
+
+    let back = {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            pump(
+                &shared,
+                Direction::ToClient,
+                BufReader::new(server_out),
+                std::io::stdout(),
+            )
+        })
+    };
+    // Client EOF closes the server's stdin (or the socket), which ends the
+    // session.
+    pump(
+        &shared,
+        Direction::ToServer,
+        std::io::stdin().lock(),
+        server_in,
+    );
+    let _ = back.join();
+    if let Some(status) = child.and_then(|c| c.wait_blocking())
+        && !status.success()
+        && shared.notes
+    {
+        eprintln!("[proxy] server exited with {status}");
+    }
+    Ok(())
+}
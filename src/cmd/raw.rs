@@ -0,0 +1,278 @@
+/*!
+raw.rs - raw subcommand.
+
+Sends an arbitrary JSON-RPC request (or notification) to a local MCP
+process and prints the raw response. Intended for experimental and
+vendor-specific methods that the typed client does not know about.
+
+Supports:
+  - --method METHOD [--params JSON]      (request with generated id)
+  - --method METHOD --notify             (notification, no response awaited)
+  - --file request.json | --file -       (complete JSON-RPC message; no "id" -> notification)
+  - --no-init                            (skip the initialize handshake)
+
+Example:
+  mcp-hack raw -t "npx -y @modelcontextprotocol/server-everything" --method tools/call --params '{"name":"echo","arguments":{"message":"hi"}}'
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::cmd::output::{OutputFormat, pretty, print_json};
+use crate::mcp;
+use crate::mcp::raw::RawSession;
+
+/// How long to keep listening for server reactions after a notification.
+const NOTIFY_DRAIN_MS: u64 = 500;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct RawArgs {
+    /// JSON-RPC method to call (e.g. tools/call)
+    #[arg(long, value_name = "METHOD", required_unless_present = "file")]
+    pub method: Option<String>,
+
+    /// JSON params (object or array) for --method
+    #[arg(long, value_name = "JSON", requires = "method")]
+    pub params: Option<String>,
+
+    /// Send a complete JSON-RPC message from file ('-' reads stdin). Without "id" it is a notification
+    #[arg(long, value_name = "PATH", conflicts_with = "method")]
+    pub file: Option<String>,
+
+    /// Send --method as a notification (no id, no response awaited)
+    #[arg(long, requires = "method")]
+    pub notify: bool,
+
+    /// Skip the initialize handshake (probe pre-initialization behavior)
+    #[arg(long = "no-init")]
+    pub no_init: bool,
+
+    /// Seconds to wait for the response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command or remote URL). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_raw(mut args: RawArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote raw requests not implemented yet",
+        );
+    }
+
+    let message = match build_message(&args) {
+        Ok(m) => m,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let method = message
+        .get("method")
+        .and_then(|v| v.as_str())
+        .unwrap_or("<none>")
+        .to_string();
+
+    let started = Instant::now();
+    let outcome = async {
+        let mut session = RawSession::spawn(&spec, Duration::from_secs(args.timeout))?;
+        if !args.no_init {
+            session
+                .initialize()
+                .await
+                .context("initialize handshake failed")?;
+            // Handshake chatter is not part of the user's exchange.
+            session.interleaved.clear();
+        }
+        let response = session.send_message(&message).await?;
+        let mut others = std::mem::take(&mut session.interleaved);
+        if response.is_null() {
+            others.extend(session.drain(Duration::from_millis(NOTIFY_DRAIN_MS)).await);
+        }
+        session.shutdown().await;
+        anyhow::Ok((response, others))
+    }
+    .await;
+    let elapsed_ms = started.elapsed().as_millis();
+
+    let (response, others) = match outcome {
+        Ok(v) => v,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+
+    if args.output.is_json() {
+        let out = serde_json::json!({
+            "status": "ok",
+            "target": target_raw,
+            "method": method,
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "request": message,
+            "response": response,
+            "messages": others,
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!("{} Raw {}", emoji("rocket", &style), method),
+        Some(format!("target={target_raw} • {elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    println!("{}", color(Role::Accent, "Request:", &style));
+    println!("{}", pretty(&message));
+    println!();
+    if response.is_null() {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                "Notification sent (no response expected)",
+                &style
+            )
+        );
+    } else {
+        println!("{}", color(Role::Accent, "Response:", &style));
+        println!("{}", pretty(&response));
+    }
+    if !others.is_empty() {
+        println!();
+        println!(
+            "{}",
+            color(
+                Role::Accent,
+                format!("Other messages ({}):", others.len()),
+                &style
+            )
+        );
+        for m in &others {
+            println!("  {m}");
+        }
+    }
+    Ok(())
+}
+
+/* ---- Helpers ---- */
+
+/// Build the outgoing JSON-RPC message from --file or --method/--params/--notify.
+fn build_message(args: &RawArgs) -> Result<serde_json::Value> {
+    if let Some(path) = &args.file {
+        let raw = if path == "-" {
+            std::io::read_to_string(std::io::stdin()).context("failed to read stdin")?
+        } else {
+            std::fs::read_to_string(path)
+                .with_context(|| format!("failed to read request file: {path}"))?
+        };
+        let mut msg: serde_json::Value =
+            serde_json::from_str(&raw).context("request file is not valid JSON")?;
+        let obj = msg
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("request file root must be a JSON object"))?;
+        obj.entry("jsonrpc")
+            .or_insert_with(|| serde_json::Value::String("2.0".into()));
+        return Ok(msg);
+    }
+
+    let method = args
+        .method
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("--method cannot be empty"))?;
+    let mut msg = serde_json::json!({"jsonrpc": "2.0", "method": method});
+    if !args.notify {
+        // The handshake uses small sequential ids; keep the user request distinct.
+        msg["id"] = serde_json::json!(1000);
+    }
+    if let Some(p) = &args.params {
+        let params: serde_json::Value =
+            serde_json::from_str(p).context("--params is not valid JSON")?;
+        if !params.is_object() && !params.is_array() {
+            anyhow::bail!("--params must be a JSON object or array");
+        }
+        msg["params"] = params;
+    }
+    Ok(msg)
+}
+
+/* ---- Tests ---- */
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(method: &str, params: Option<&str>, notify: bool) -> RawArgs {
+        RawArgs {
+            method: Some(method.into()),
+            params: params.map(Into::into),
+            file: None,
+            notify,
+            no_init: false,
+            timeout: 5,
+            target: None,
+            output: OutputFormat::Json,
+        }
+    }
+
+    #[test]
+    fn build_request_with_params() {
+        let msg = build_message(&args("tools/call", Some(r#"{"name":"x"}"#), false)).unwrap();
+        assert_eq!(msg["method"], "tools/call");
+        assert_eq!(msg["params"]["name"], "x");
+        assert!(msg.get("id").is_some());
+    }
+
+    #[test]
+    fn build_notification_has_no_id() {
+        let msg = build_message(&args("notifications/foo", None, true)).unwrap();
+        assert!(msg.get("id").is_none());
+    }
+
+    #[test]
+    fn reject_scalar_params() {
+        assert!(build_message(&args("x", Some("42"), false)).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+/*!
+replay.rs - replay subcommand.
+
+Replays the client side of a transcript recorded with --record against a
+target and reports how each response differs from the recording (see
+crate::scan::replay) - e.g. to check that a server fix changes exactly the
+responses it should.
+
+Each recorded session is replayed in a fresh server process, message by
+message in the recorded order (initialize, notifications, requests with
+their original ids). The target is the recorded one unless -t / MCP_TARGET
+overrides it. Messages the server sent on its own are not replayed.
+
+Comparison:
+  The jsonrpc / id envelope is ignored; --ignore POINTER (repeatable)
+  drops volatile fields from both sides (e.g. /result/serverInfo/version).
+  Verdicts: same, changed (with a unified diff) and missing (no response
+  now).
+
+CI gating:
+  --fail-on-diff exits with status 6 when any response changed or went
+  missing, after printing the normal output.
+
+Example:
+  mcp-hack --record before.mcpt scan -t "python3 server.py"
+  mcp-hack replay before.mcpt -t "python3 server_fixed.py"
+  mcp-hack replay before.mcpt --ignore /result/serverInfo/version --fail-on-diff --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::format::{Role, StyleOptions, box_header, color, diff_line_role, emoji};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp::raw::RawSession;
+use crate::mcp::record::{self, Exchange};
+use crate::mcp::{self, TargetSpec};
+use crate::scan::replay::{Verdict, compare};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Transcript file written with --record
+    #[arg(value_name = "TRANSCRIPT")]
+    pub transcript: String,
+
+    /// Replay only this session number
+    #[arg(long, value_name = "N")]
+    pub session: Option<u64>,
+
+    /// JSON pointer left out of the comparison (repeatable)
+    #[arg(long, value_name = "POINTER")]
+    pub ignore: Vec<String>,
+
+    /// Exit with status 6 when a response changed or went missing
+    #[arg(long = "fail-on-diff")]
+    pub fail_on_diff: bool,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command) replacing the recorded one. Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// One replayed request.
+struct Replayed {
+    session: u64,
+    method: String,
+    tool: Option<String>,
+    verdict: Verdict,
+    diff: String,
+    recorded_ms: Option<u64>,
+    replayed_ms: Option<u64>,
+    error: Option<String>,
+}
+
+impl Replayed {
+    fn to_json(&self) -> Value {
+        json!({
+            "session": self.session,
+            "method": self.method,
+            "tool": self.tool,
+            "verdict": self.verdict.as_str(),
+            "diff": self.diff,
+            "recorded_ms": self.recorded_ms,
+            "replayed_ms": self.replayed_ms,
+            "error": self.error,
+        })
+    }
+}
+
+/// Replay one session's messages in a fresh server process.
+async fn replay_session(
+    spec: &TargetSpec,
+    session: u64,
+    exchanges: &[&Exchange],
+    args: &ReplayArgs,
+) -> Result<Vec<Replayed>> {
+    // Raw frames: recorded messages are sent as they were captured.
+    let mut server = RawSession::spawn(spec, Duration::from_secs(args.timeout))?;
+    let mut out = Vec::new();
+    let mut gone: Option<String> = None;
+    for ex in exchanges {
+        let is_request = ex.request.get("id").is_some_and(|id| !id.is_null());
+        let started = Instant::now();
+        let (replayed, error) = match &gone {
+            Some(e) => (None, Some(e.clone())),
+            None => match server.send_message(&ex.request).await {
+                Ok(v) if is_request => (Some(v), None),
+                Ok(_) => (None, None),
+                Err(e) => {
+                    let msg = format!("{e:#}");
+                    // A closed pipe ends the session; a timeout only this request.
+                    if !msg.contains("timed out") {
+                        gone = Some(msg.clone());
+                    }
+                    (None, Some(msg))
+                }
+            },
+        };
+        if !is_request {
+            continue;
+        }
+        let (verdict, diff) = compare(ex.response.as_ref(), replayed.as_ref(), &args.ignore);
+        out.push(Replayed {
+            session,
+            method: ex.method().unwrap_or_default().to_string(),
+            tool: ex.tool().map(str::to_string),
+            verdict,
+            diff,
+            recorded_ms: ex.latency_ms,
+            replayed_ms: replayed
+                .is_some()
+                .then(|| started.elapsed().as_millis() as u64),
+            error,
+        });
+    }
+    server.shutdown().await;
+    Ok(out)
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_replay(mut args: ReplayArgs) -> Result<()> {
+    // Determine target override (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let transcript = match record::load(&args.transcript) {
+        Ok(t) => t,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let sessions: Vec<(u64, String)> = transcript
+        .sessions
+        .iter()
+        .filter(|(id, _)| args.session.is_none_or(|s| s == **id))
+        .map(|(id, target)| {
+            let target = args.target.clone().unwrap_or_else(|| target.clone());
+            (*id, target.trim().to_string())
+        })
+        .collect();
+    if sessions.is_empty() {
+        return output_error(args.output.is_json(), "no matching session in transcript");
+    }
+
+    let started = Instant::now();
+    let mut results = Vec::new();
+    for (id, target) in &sessions {
+        let spec = mcp::parse_target(target)
+            .with_context(|| format!("Failed to parse target: '{}'", mcp::redact_target(target)))?;
+        if !spec.is_local() {
+            return usage_error(args.output.is_json(), "remote replay not implemented yet");
+        }
+        let exchanges: Vec<&Exchange> = transcript
+            .exchanges
+            .iter()
+            .filter(|e| e.session == *id)
+            .collect();
+        crate::utils::progress_events::phase("replay", Some(target));
+        match replay_session(&spec, *id, &exchanges, &args).await {
+            Ok(r) => results.extend(r),
+            Err(e) => {
+                return report_error(args.output.is_json(), e.context(format!("session {id}")));
+            }
+        }
+    }
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+    let count = |v: Verdict| results.iter().filter(|r| r.verdict == v).count();
+    let differing = count(Verdict::Changed) + count(Verdict::Missing);
+
+    if args.output.is_json() {
+        let out = json!({
+            "status": "ok",
+            "transcript": args.transcript,
+            "elapsed_ms": elapsed_ms,
+            "sessions": sessions.iter().map(|(id, t)| json!({"session": id, "target": t})).collect::<Vec<_>>(),
+            "summary": {
+                "requests": results.len(),
+                "same": count(Verdict::Same),
+                "changed": count(Verdict::Changed),
+                "missing": count(Verdict::Missing),
+            },
+            "results": results.iter().map(Replayed::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+    } else {
+        let style = StyleOptions::detect();
+        let header = box_header(
+            format!(
+                "{} Replay • {} same / {} changed / {} missing",
+                emoji("clock", &style),
+                count(Verdict::Same),
+                count(Verdict::Changed),
+                count(Verdict::Missing)
+            ),
+            Some(format!(
+                "{} • {} session(s) • {elapsed_ms} ms",
+                args.transcript,
+                sessions.len()
+            )),
+            &style,
+        );
+        println!("{header}");
+        for r in &results {
+            let (mark, role) = match r.verdict {
+                Verdict::Same => ("=", Role::Success),
+                Verdict::Changed => ("~", Role::Warning),
+                Verdict::Missing => ("!", Role::Error),
+            };
+            let ms = |v: Option<u64>| v.map_or("-".to_string(), |ms| format!("{ms}"));
+            println!(
+                "{} [{}] {}{} {}",
+                color(role, mark, &style),
+                r.session,
+                r.method,
+                r.tool
+                    .as_deref()
+                    .map(|t| format!(" {t}"))
+                    .unwrap_or_default(),
+                color(
+                    Role::Dim,
+                    format!("{} -> {} ms", ms(r.recorded_ms), ms(r.replayed_ms)),
+                    &style
+                )
+            );
+            if let Some(e) = &r.error {
+                println!("    {}", color(Role::Error, e, &style));
+            }
+            for line in r.diff.lines() {
+                println!("    {}", color(diff_line_role(line), line, &style));
+            }
+        }
+    }
+    if args.fail_on_diff && differing > 0 {
+        return Err(exit::Status(exit::FINDINGS).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+/*!
+retry.rs - retrying transient failures (`--retries`, `--retry-backoff`).
+
+A failed attempt is retried when the error is transient:
+  - transport errors (send failure, connection closed, the server exiting
+    during initialize)
+  - timeouts (--connect-timeout / --request-timeout / rmcp's own)
+  - a JSON-RPC error whose code is listed by --retry-on (default -32000,
+    -32001 and 429, the codes servers commonly use for overload / rate limits)
+
+Anything else (spawn failure, unknown tool, bad arguments, isError results)
+fails at once. The delay starts at --retry-backoff and doubles per retry,
+capped at 30s. Each attempt is a fresh session, so a retried tools/call is
+sent again: only use --retries with calls that are safe to repeat.
+
+JSON output reports `"attempts"` (1 when the first attempt succeeded) and,
+after a retry, `"retried"` with the error of each failed attempt.
+
+Example:
+  mcp-hack exec tool fetch -t "python3 server.py" --retries 3 --retry-backoff 250
+*/
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::{Value, json};
+use std::future::Future;
+use std::time::Duration;
+
+use crate::utils::logging::{LogLevel, current_log_level};
+
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/* ---- Argument Struct ---- */
+
+#[derive(Args, Debug, Clone)]
+pub struct RetryArgs {
+    /// Retry transient failures up to N times
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub retries: u32,
+
+    /// Delay before the first retry in milliseconds (doubles per retry)
+    #[arg(long = "retry-backoff", value_name = "MS", default_value_t = 500)]
+    pub retry_backoff: u64,
+
+    /// JSON-RPC error codes treated as transient (repeatable)
+    #[arg(
+        long = "retry-on",
+        value_name = "CODE",
+        allow_negative_numbers = true,
+        default_values_t = [-32000, -32001, 429]
+    )]
+    pub retry_on: Vec<i32>,
+}
+
+impl Default for RetryArgs {
+    fn default() -> Self {
+        Self {
+            retries: 0,
+            retry_backoff: 500,
+            retry_on: vec![-32000, -32001, 429],
+        }
+    }
+}
+
+/// Attempts made by one [`RetryArgs::run`].
+#[derive(Debug, Default)]
+pub struct Attempts {
+    pub count: u32,
+    /// Error of each failed attempt that was retried.
+    pub errors: Vec<String>,
+}
+
+impl Attempts {
+    /// Add `"attempts"` (and `"retried"` after a retry) to a JSON object.
+    pub fn annotate(&self, out: &mut Value) {
+        out["attempts"] = json!(self.count);
+        if !self.errors.is_empty() {
+            out["retried"] = json!(self.errors);
+        }
+    }
+}
+
+impl RetryArgs {
+    /// Whether `err` is worth another attempt.
+    pub fn is_transient(&self, err: &anyhow::Error) -> bool {
+        use rmcp::ServiceError;
+        use rmcp::service::ClientInitializeError;
+        err.chain().any(|e| {
+            if let Some(se) = e.downcast_ref::<ServiceError>() {
+                return match se {
+                    ServiceError::McpError(data) => self.retry_on.contains(&data.code.0),
+                    ServiceError::TransportSend(_)
+                    | ServiceError::TransportClosed
+                    | ServiceError::Timeout { .. } => true,
+                    _ => false,
+                };
+            }
+            e.downcast_ref::<ClientInitializeError>().is_some()
+                || e.to_string().contains(" timed out after ")
+        })
+    }
+
+    /// Delay before retry number `retry` (1-based).
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = 1u64 << retry.saturating_sub(1).min(20);
+        Duration::from_millis(self.retry_backoff.saturating_mul(factor)).min(MAX_DELAY)
+    }
+
+    /// Run `op` until it succeeds, fails permanently or runs out of retries.
+    pub async fn run<T, F>(&self, mut op: impl FnMut() -> F) -> (Result<T>, Attempts)
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let mut attempts = Attempts::default();
+        loop {
+            attempts.count += 1;
+            match op().await {
+                Err(e) if attempts.count <= self.retries && self.is_transient(&e) => {
+                    let wait = self.delay(attempts.count);
+                    if current_log_level() >= LogLevel::Info {
+                        eprintln!(
+                            "[retry] attempt {} failed ({e:#}); retrying in {}ms",
+                            attempts.count,
+                            wait.as_millis()
+                        );
+                    }
+                    attempts.errors.push(format!("{e:#}"));
+                    tokio::time::sleep(wait).await;
+                }
+                result => return (result, attempts),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{ErrorCode, ErrorData};
+
+    #[tokio::test]
+    async fn retries_transient_errors_with_backoff() {
+        let retry = RetryArgs {
+            retries: 3,
+            retry_backoff: 1,
+            ..Default::default()
+        };
+        assert_eq!(retry.delay(1), Duration::from_millis(1));
+        assert_eq!(retry.delay(4), Duration::from_millis(8));
+        let slow = RetryArgs {
+            retry_backoff: 20_000,
+            ..Default::default()
+        };
+        assert_eq!(slow.delay(3), MAX_DELAY);
+
+        let rate_limited = || {
+            anyhow::Error::new(rmcp::ServiceError::McpError(ErrorData::new(
+                ErrorCode(429),
+                "slow down",
+                None,
+            )))
+            .context("tool invocation failed: fetch")
+        };
+        assert!(retry.is_transient(&rate_limited()));
+        assert!(!retry.is_transient(&anyhow::anyhow!("tool 'x' not found")));
+
+        let mut calls = 0;
+        let (result, attempts) = retry
+            .run(|| {
+                calls += 1;
+                std::future::ready(if calls < 3 {
+                    Err(rate_limited())
+                } else {
+                    Ok(calls)
+                })
+            })
+            .await;
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(attempts.count, 3);
+        assert_eq!(attempts.errors.len(), 2);
+
+        let (result, attempts) = retry
+            .run(|| async {
+                let failed: Result<()> = Err(anyhow::anyhow!("bad argument"));
+                failed
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.count, 1);
+    }
+}
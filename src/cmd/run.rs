@@ -0,0 +1,316 @@
+/*!
+run.rs - run subcommand.
+
+Executes a playbook: a YAML sequence of tool calls (or raw JSON-RPC
+requests) in one server session, with parameter templating, values saved
+from earlier results, conditional steps and assertions (see
+crate::scan::playbook for the format). Multi-step exploit chains no longer
+need shell scripting around exec.
+
+The target is -t / MCP_TARGET, else the playbook's `target`. --var
+KEY=VALUE (repeatable) sets or overrides playbook vars. Execution stops at
+the first step whose request or assertions fail unless --keep-going is
+given.
+
+Exit status: 6 when a step failed (see cmd::exit), after printing the
+normal output.
+
+Example:
+  mcp-hack run chain.yaml -t "python3 server.py"
+  mcp-hack run chain.yaml --var user=bob --keep-going --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp::raw::RawSession;
+use crate::mcp::{self, TargetSpec};
+use crate::scan::playbook::{self, Playbook, Step, Vars, render};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Playbook file (YAML)
+    #[arg(value_name = "PLAYBOOK")]
+    pub playbook: String,
+
+    /// Playbook variable KEY=VALUE (repeatable; overrides the file's vars)
+    #[arg(long = "var", value_name = "KEY=VALUE")]
+    pub vars: Vec<String>,
+
+    /// Run the remaining steps after a failed one
+    #[arg(long = "keep-going")]
+    pub keep_going: bool,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env, then the playbook's target.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// Outcome of one playbook step.
+struct StepResult {
+    label: String,
+    call: String,
+    skipped: Option<String>,
+    request: Option<Value>,
+    response: Option<Value>,
+    saved: Vars,
+    /// Failed assertions / request error
+    failures: Vec<String>,
+    ms: u64,
+}
+
+impl StepResult {
+    fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "step": self.label,
+            "call": self.call,
+            "status": if self.skipped.is_some() { "skipped" } else if self.passed() { "passed" } else { "failed" },
+            "skipped": self.skipped,
+            "request": self.request,
+            "response": self.response,
+            "saved": self.saved,
+            "failures": self.failures,
+            "ms": self.ms,
+        })
+    }
+}
+
+/// Method and params a step sends.
+fn step_request(step: &Step, vars: &Vars) -> Result<(String, Value)> {
+    match (&step.tool, &step.method) {
+        (Some(tool), _) => {
+            let args = render(&Value::Object(step.args.clone()), vars)?;
+            Ok((
+                "tools/call".to_string(),
+                json!({"name": playbook::render_str(tool, vars)?, "arguments": args}),
+            ))
+        }
+        (None, Some(method)) => {
+            let params = match &step.params {
+                Some(p) => render(p, vars)?,
+                None => Value::Null,
+            };
+            Ok((method.clone(), params))
+        }
+        (None, None) => anyhow::bail!("step has neither tool nor method"),
+    }
+}
+
+async fn run_playbook(
+    spec: &TargetSpec,
+    book: &Playbook,
+    mut vars: Vars,
+    args: &RunArgs,
+) -> Result<Vec<StepResult>> {
+    // Raw frames: `method` steps and assertions on the response envelope.
+    let mut server = RawSession::spawn(spec, Duration::from_secs(args.timeout))?;
+    server.initialize().await.context("initialize failed")?;
+    let mut results = Vec::new();
+    let mut last = Value::Null;
+    let mut failed = false;
+    for (i, step) in book.steps.iter().enumerate() {
+        let label = step.label(i);
+        let call = step
+            .tool
+            .clone()
+            .or_else(|| step.method.clone())
+            .unwrap_or_default();
+        let mut result = StepResult {
+            label: label.clone(),
+            call,
+            skipped: None,
+            request: None,
+            response: None,
+            saved: Vars::new(),
+            failures: Vec::new(),
+            ms: 0,
+        };
+        if failed && !args.keep_going {
+            result.skipped = Some("an earlier step failed".into());
+            results.push(result);
+            continue;
+        }
+        if let Some(when) = &step.when
+            && let Err(why) = when.evaluate(&last, &vars)
+        {
+            result.skipped = Some(format!("when: {why}"));
+            results.push(result);
+            continue;
+        }
+        crate::utils::progress_events::phase("step", Some(&label));
+        let started = Instant::now();
+        let response = match step_request(step, &vars) {
+            Ok((method, params)) => {
+                result.request = Some(json!({"method": method, "params": params}));
+                let params = (!params.is_null()).then_some(params);
+                server.request(&method, params).await
+            }
+            Err(e) => Err(e),
+        };
+        result.ms = started.elapsed().as_millis() as u64;
+        match response {
+            Ok(resp) => {
+                for check in &step.assert {
+                    if let Err(why) = check.evaluate(&resp, &vars) {
+                        result.failures.push(why);
+                    }
+                }
+                result.saved = playbook::saved(step, &resp);
+                vars.extend(result.saved.clone());
+                result.response = Some(resp.clone());
+                last = resp;
+            }
+            Err(e) => {
+                result.failures.push(format!("{e:#}"));
+                last = Value::Null;
+            }
+        }
+        failed |= !result.passed();
+        results.push(result);
+    }
+    server.shutdown().await;
+    Ok(results)
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_run(mut args: RunArgs) -> Result<()> {
+    let book = match playbook::load(&args.playbook) {
+        Ok(b) => b,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    // Determine target (CLI > env > playbook)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match args.target.as_ref().or(book.target.as_ref()) {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target, MCP_TARGET or the playbook's target)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote playbooks not implemented yet",
+        );
+    }
+    let mut vars = book.vars.clone();
+    for kv in &args.vars {
+        let Some((k, v)) = kv.split_once('=') else {
+            return usage_error(
+                args.output.is_json(),
+                &format!("invalid --var '{kv}' (expected KEY=VALUE)"),
+            );
+        };
+        vars.insert(k.trim().to_string(), Value::String(v.to_string()));
+    }
+
+    let started = Instant::now();
+    let results = match run_playbook(&spec, &book, vars, &args).await {
+        Ok(r) => r,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+    let count = |f: &dyn Fn(&StepResult) -> bool| results.iter().filter(|r| f(r)).count();
+    let skipped = count(&|r| r.skipped.is_some());
+    let failed = count(&|r| r.skipped.is_none() && !r.passed());
+    let passed = results.len() - skipped - failed;
+
+    if args.output.is_json() {
+        let out = json!({
+            "status": "ok",
+            "playbook": args.playbook,
+            "name": book.name,
+            "target": target_raw,
+            "elapsed_ms": elapsed_ms,
+            "summary": {"steps": results.len(), "passed": passed, "failed": failed, "skipped": skipped},
+            "steps": results.iter().map(StepResult::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+    } else {
+        let style = StyleOptions::detect();
+        let header = box_header(
+            format!(
+                "{} {} • {passed} passed / {failed} failed / {skipped} skipped",
+                emoji("rocket", &style),
+                book.name.as_deref().unwrap_or("Playbook")
+            ),
+            Some(format!("target={target_raw} • {elapsed_ms}ms")),
+            &style,
+        );
+        println!("{header}");
+        for r in &results {
+            let (mark, role) = match (&r.skipped, r.passed()) {
+                (Some(_), _) => ("-", Role::Dim),
+                (None, true) => ("✓", Role::Success),
+                (None, false) => ("✗", Role::Error),
+            };
+            println!(
+                "{} {} {} {}",
+                color(role, mark, &style),
+                r.label,
+                color(Role::Accent, &r.call, &style),
+                color(Role::Dim, format!("{}ms", r.ms), &style)
+            );
+            if let Some(why) = &r.skipped {
+                println!(
+                    "    {}",
+                    color(Role::Dim, format!("skipped: {why}"), &style)
+                );
+            }
+            for (name, value) in &r.saved {
+                let text = value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_string);
+                let text: String = text.chars().take(80).collect();
+                println!("    {} {name} = {text}", color(Role::Dim, "saved", &style));
+            }
+            for f in &r.failures {
+                println!("    {}", color(Role::Error, f, &style));
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(exit::Status(exit::FINDINGS).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,985 @@
+/*!
+scan.rs - scan subcommand.
+
+Connects to a target, collects its initialize result and tool inventory,
+optionally probes protocol handling, then runs every registered check
+(see crate::scan::checks) and reports findings with severities.
+
+Checks:
+  - dangerous-tool      exec / fs-write / credential / egress / messaging capabilities (scan::risk)
+  - prompt-injection    instruction-smuggling phrases in tool + parameter descriptions
+  - hidden-unicode      zero-width / bidi / tag characters and homoglyphs in names + descriptions
+  - missing-auth        remote endpoint reachable without credentials
+  - broad-schema        missing schemas, open additionalProperties, unconstrained sensitive params
+  - tool-naming         duplicate / malformed tool names
+  - rug-pull            tool definitions changed / added / removed since first contact (pins)
+  - content-rules       YARA-style rules (--rules) matched against resource contents
+  - templates           YAML detection templates (--templates) over tools / resources / server
+  - policy              deviations from an expected-state policy (--policy)
+  - protocol            pre-initialize requests, malformed frames, version negotiation, stdout noise
+
+Selection:
+  --checks ID,... runs only those checks, --skip-checks ID,... leaves some
+  out (reported as skipped); --list-checks prints every check id and title.
+  Protocol probes are only sent when the protocol check runs, resources
+  only read for content-rules / templates.
+
+Capabilities the server does not declare (tools, resources, prompts,
+logging, completions) are listed as absent, and checks depending on them
+report status "not_applicable" instead of running against nothing.
+
+The report ends with a per-capability risk summary (which tools can execute
+commands, write files, reach the network, ...).
+
+Pins:
+  Tool definition hashes are stored on first contact (state dir, see
+  MCP_HACK_STATE_DIR) and compared on later scans. --repin accepts the
+  current definitions; --no-pin neither reads nor writes pins.
+
+Content rules:
+  --rules FILE (repeatable) loads YARA-style rules (see crate::scan::rules);
+  when given, resources are listed and read, and every text content is
+  matched. The same rules can be applied to tool results with `fuzz --rules`.
+
+Templates:
+  --templates PATH (repeatable; file or directory searched recursively)
+  loads YAML detection templates (see crate::scan::templates) matching tool
+  names, descriptions, parameters and schemas, resource contents or the
+  initialize result, each with its own severity and message.
+
+Exports (repeatable --export FORMAT=PATH):
+  - defectdojo   DefectDojo "Generic Findings Import" JSON
+  - generic      flat JSON with per-finding dedup keys
+  - sarif        SARIF 2.1.0 (GitHub code scanning); also `--format sarif` on stdout
+  - stix         STIX 2.1 indicator bundle (domains, URLs, hashes, packages) for poisoned servers
+  - ioc-csv      same indicators as CSV
+
+Policy:
+  --policy FILE loads a YAML policy (see crate::scan::policy): allowed /
+  denied tools, forbidden capability classes, description hashes and which
+  checks run. Every deviation is reported as a `policy` finding.
+
+Reports:
+  --report PATH writes a shareable Markdown (.md) or HTML (.html) report:
+  summary, affected tools, findings with evidence and remediation notes,
+  risk summary and checks. It is rendered from the --json document.
+
+Fleets:
+  -t repeated and/or --targets FILE (one target per line) scan every target,
+  --parallel N at a time, into one report keyed by target (see
+  crate::cmd::fleet); with --format sarif, one SARIF run per target.
+
+Suppressions:
+  Findings matching an entry of .mcp-hack-ignore (current directory) or
+  --ignore-file PATH (check / tool / target globs, expiry date, reason; see
+  crate::scan::ignore) are left out of the output, reports, exports and
+  --fail-on, and listed as suppressed with their reason. Expired entries
+  stop suppressing and are reported.
+
+Baseline:
+  --baseline FILE compares findings with accepted ones (see
+  crate::scan::baseline): only new findings are shown, reported, exported
+  and counted by --fail-on; known and resolved ones are summarized.
+  --update-baseline writes the current findings as accepted (creating the
+  file), so the run reports nothing new.
+
+DefectDojo:
+  --dojo-engagement ID imports the findings into that engagement as a new
+  test through the import-scan API: the defectdojo export, dedup keys as
+  unique_id_from_tool (see crate::scan::export). The instance comes from
+  --dojo-url or DEFECTDOJO_URL, the API v2 key from --dojo-token or
+  DEFECTDOJO_TOKEN (prefer the environment: flags show in process lists).
+
+Display:
+  --min-severity SEVERITY shows only findings at or above SEVERITY on
+  stdout (text, JSON, SARIF) with a count of the hidden ones; --report,
+  --export files and --fail-on still see every finding.
+
+CI gating:
+  --fail-on SEVERITY exits with status 6 when at least one finding is at or
+  above SEVERITY (critical|high|medium|low|info), after printing the normal
+  output. Errors still exit with their own status (1-4, see crate::cmd::exit),
+  so a failed scan never looks like a pass.
+
+Example:
+  mcp-hack scan -t "npx -y @modelcontextprotocol/server-everything"
+  mcp-hack scan -t "python3 server.py" --passive --json
+  mcp-hack scan -t "python3 server.py" --checks dangerous-tool,prompt-injection
+  mcp-hack scan --list-checks
+  mcp-hack scan -t "python3 server.py" --format sarif > mcp.sarif
+  mcp-hack scan -t "python3 server.py" --export defectdojo=dojo.json
+  mcp-hack scan -t "python3 server.py" --dojo-engagement 7
+  mcp-hack scan -t "python3 server.py" --rules secrets.yar
+  mcp-hack scan -t "python3 server.py" --templates ./templates/
+  mcp-hack scan -t "python3 server.py" --report report.html
+  mcp-hack scan -t "python3 server.py" --policy policy.yaml --fail-on high
+  mcp-hack scan -t "python3 server.py" --fail-on medium --format sarif > mcp.sarif
+  mcp-hack scan -t "python3 server.py" --baseline mcp-baseline.json --update-baseline
+  mcp-hack scan -t "python3 server.py" --baseline mcp-baseline.json --fail-on low
+  mcp-hack scan --targets fleet.txt --parallel 4 --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::fleet::FleetArgs;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::scan::export::{Dojo, parse_export_spec, render};
+use crate::scan::pins::{self, PinSet};
+use crate::scan::report::ReportFormat;
+use crate::scan::risk;
+use crate::scan::{self, Finding, Severity};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json, OutputFormat::Sarif];
+
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// Skip active protocol probes (malformed frames, pre-initialize requests)
+    #[arg(long)]
+    pub passive: bool,
+
+    /// Run only these checks (comma separated ids, see --list-checks)
+    #[arg(long, value_delimiter = ',', value_name = "IDS")]
+    pub checks: Vec<String>,
+
+    /// Leave these checks out (comma separated ids)
+    #[arg(long = "skip-checks", value_delimiter = ',', value_name = "IDS")]
+    pub skip_checks: Vec<String>,
+
+    /// List the available checks and exit
+    #[arg(long = "list-checks")]
+    pub list_checks: bool,
+
+    /// Write findings for another platform (FORMAT=PATH; defectdojo|generic|sarif|stix|ioc-csv), repeatable
+    #[arg(long = "export", value_name = "FORMAT=PATH")]
+    pub exports: Vec<String>,
+
+    /// Import findings into this DefectDojo engagement (import-scan API)
+    #[arg(long = "dojo-engagement", value_name = "ID")]
+    pub dojo_engagement: Option<u64>,
+
+    /// DefectDojo base URL. Falls back to DEFECTDOJO_URL env.
+    #[arg(long = "dojo-url", value_name = "URL", requires = "dojo_engagement")]
+    pub dojo_url: Option<String>,
+
+    /// DefectDojo API v2 key. Falls back to DEFECTDOJO_TOKEN env.
+    #[arg(
+        long = "dojo-token",
+        value_name = "TOKEN",
+        requires = "dojo_engagement"
+    )]
+    pub dojo_token: Option<String>,
+
+    /// Write a Markdown / HTML report (format from the .md / .html extension)
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<String>,
+
+    /// Load YARA-style content rules and match them against resource contents (repeatable)
+    #[arg(long = "rules", value_name = "PATH")]
+    pub rules: Vec<String>,
+
+    /// Load YAML detection templates from a file or directory (repeatable)
+    #[arg(long = "templates", value_name = "PATH")]
+    pub templates: Vec<String>,
+
+    /// Check the server against a YAML policy (allowed tools, denied capabilities, ...)
+    #[arg(long, value_name = "PATH")]
+    pub policy: Option<String>,
+
+    /// Do not read or write pinned tool definitions
+    #[arg(long = "no-pin", conflicts_with = "repin")]
+    pub no_pin: bool,
+
+    /// Accept current tool definitions as the new pins
+    #[arg(long)]
+    pub repin: bool,
+
+    /// Exit with status 6 when findings at or above this severity exist
+    #[arg(long = "fail-on", value_name = "SEVERITY", value_parser = ["critical", "high", "medium", "low", "info"])]
+    pub fail_on: Option<String>,
+
+    /// Suppression file (default: .mcp-hack-ignore when present)
+    #[arg(long = "ignore-file", value_name = "PATH")]
+    pub ignore_file: Option<String>,
+
+    /// Accepted findings: report and fail only on findings not in this file
+    #[arg(long, value_name = "PATH")]
+    pub baseline: Option<String>,
+
+    /// Accept the current findings into --baseline (creates the file)
+    #[arg(long = "update-baseline", requires = "baseline")]
+    pub update_baseline: bool,
+
+    /// Show only findings at or above this severity (reports and exports keep all)
+    #[arg(long = "min-severity", value_name = "SEVERITY", value_parser = ["critical", "high", "medium", "low", "info"])]
+    pub min_severity: Option<String>,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command or remote URL). Falls back to MCP_TARGET env.
+    /// Repeat (or use --targets) to run against several targets.
+    #[arg(short = 't', long = "target", id = "target", value_name = "TARGET")]
+    pub targets: Vec<String>,
+
+    #[command(flatten)]
+    pub fleet: FleetArgs,
+
+    /// Effective single target (set by the dispatcher)
+    #[arg(skip)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_scan(mut args: ScanArgs) -> Result<()> {
+    if args.list_checks {
+        list_checks(args.output.is_json());
+        return Ok(());
+    }
+    // SARIF is JSON too: errors are reported as JSON objects.
+    let sarif = args.output == OutputFormat::Sarif;
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(args.output.is_json(), "remote scanning not implemented yet");
+    }
+
+    let selection = match scan::checks::Selection::new(&args.checks, &args.skip_checks) {
+        Ok(s) => s,
+        Err(e) => return usage_error(args.output.is_json(), &format!("{e}")),
+    };
+    let active = !args.passive && selection.includes("protocol");
+
+    let mut exports = Vec::with_capacity(args.exports.len());
+    for spec in &args.exports {
+        match parse_export_spec(spec) {
+            Ok(e) => exports.push(e),
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+    }
+
+    let dojo = match args.dojo_engagement {
+        Some(engagement) => {
+            let setting = |flag: &Option<String>, env: &str| {
+                flag.clone()
+                    .or_else(|| std::env::var(env).ok())
+                    .filter(|v| !v.is_empty())
+            };
+            match (
+                setting(&args.dojo_url, "DEFECTDOJO_URL"),
+                setting(&args.dojo_token, "DEFECTDOJO_TOKEN"),
+            ) {
+                (Some(url), Some(token)) => Some(Dojo {
+                    url,
+                    token,
+                    engagement,
+                }),
+                _ => {
+                    return usage_error(
+                        args.output.is_json(),
+                        "--dojo-engagement needs --dojo-url (or DEFECTDOJO_URL) and --dojo-token (or DEFECTDOJO_TOKEN)",
+                    );
+                }
+            }
+        }
+        None => None,
+    };
+
+    let report_format = match args.report.as_deref().map(ReportFormat::from_path) {
+        Some(Ok(f)) => Some(f),
+        Some(Err(e)) => return report_error(args.output.is_json(), e),
+        None => None,
+    };
+
+    let mut rules = Vec::new();
+    for path in &args.rules {
+        match scan::rules::load(path) {
+            Ok(r) => rules.extend(r),
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+    }
+
+    let templates = match scan::templates::load(&args.templates) {
+        Ok(t) => t,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+
+    let suppressions = match scan::ignore::load(args.ignore_file.as_deref()) {
+        Ok(s) => s,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+
+    let baseline = match args.baseline.as_deref().map(scan::baseline::load) {
+        Some(Ok(None)) if !args.update_baseline => {
+            return usage_error(
+                args.output.is_json(),
+                &format!(
+                    "baseline {} not found (create it with --update-baseline)",
+                    args.baseline.as_deref().unwrap_or_default()
+                ),
+            );
+        }
+        Some(Ok(b)) => b,
+        Some(Err(e)) => return report_error(args.output.is_json(), e),
+        None => None,
+    };
+
+    let policy = match args.policy.as_deref().map(scan::policy::load) {
+        Some(Ok(p)) => Some(p),
+        Some(Err(e)) => return report_error(args.output.is_json(), e),
+        None => None,
+    };
+
+    let started = Instant::now();
+    let mut input = match scan::collect(&spec, Duration::from_secs(args.timeout), active).await {
+        Ok(i) => i,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    if (!rules.is_empty() && selection.includes("content-rules"))
+        || (scan::templates::needs_resources(&templates) && selection.includes("templates"))
+    {
+        match scan::collect_resources(&spec, Duration::from_secs(args.timeout)).await {
+            Ok(r) => input.resources = r,
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+        input.rules = rules;
+    }
+    input.policy = policy;
+    input.templates = templates;
+    input.selection = selection;
+    let mut pin_status = "disabled";
+    let mut pin_file = None;
+    if !args.no_pin {
+        match pins::load(&target_raw) {
+            Ok(p) => input.pinned = p,
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+    }
+    crate::utils::progress_events::phase("check", None);
+    let mut report = scan::run_checks(&input);
+    report.findings.extend(crate::mcp::hook::scan(&input));
+    let applied = scan::ignore::apply(
+        &suppressions,
+        &target_raw,
+        std::mem::take(&mut report.findings),
+        &scan::ignore::today(),
+    );
+    report.findings = applied.kept;
+    let mut compared = None;
+    if let Some(path) = &args.baseline {
+        let mut c = scan::baseline::compare(
+            baseline.as_ref(),
+            &target_raw,
+            std::mem::take(&mut report.findings),
+        );
+        if args.update_baseline {
+            // Everything found now is accepted: nothing is new after the update.
+            let accepted: Vec<Finding> = c.known.iter().chain(&c.new).cloned().collect();
+            let updated = scan::baseline::update(baseline, &target_raw, &accepted);
+            if let Err(e) = scan::baseline::save(path, &updated) {
+                return output_error(args.output.is_json(), &format!("{e:#}"));
+            }
+            c.known = accepted;
+            c.new.clear();
+        }
+        report.findings = std::mem::take(&mut c.new);
+        compared = Some(c);
+    }
+    report.findings.sort_by(Finding::report_order);
+    if !args.no_pin && !input.has_capability("tools") {
+        pin_status = "not_applicable";
+    } else if !args.no_pin {
+        pin_status = "compared";
+        if input.pinned.is_none() || args.repin {
+            pin_status = if input.pinned.is_none() {
+                "created"
+            } else {
+                "updated"
+            };
+            match pins::save(&PinSet::from_tools(&target_raw, &input.tools)) {
+                Ok(path) => pin_file = Some(path.display().to_string()),
+                Err(e) => return report_error(args.output.is_json(), e),
+            }
+        }
+    }
+    let fail_on = args.fail_on.as_deref().and_then(Severity::parse);
+    let failing = fail_on.map_or(0, |min| at_or_above(&report.findings, Some(min)).len());
+    let min_severity = args.min_severity.as_deref().and_then(Severity::parse);
+    let shown = at_or_above(&report.findings, min_severity);
+    let hidden = report.findings.len() - shown.len();
+    let (declared, absent) = input.capability_coverage();
+    let risk_summary = risk::summarize(&input.tools);
+    let elapsed_ms = started.elapsed().as_millis();
+    crate::utils::progress_events::phase("done", None);
+
+    for (format, path) in &exports {
+        if let Err(e) = std::fs::write(path, render(*format, &input, &report)) {
+            return output_error(
+                args.output.is_json(),
+                &format!("failed to write export {path}: {e}"),
+            );
+        }
+    }
+
+    let mut dojo_import = None;
+    if let Some(dojo) = &dojo {
+        let answer =
+            tokio::task::block_in_place(|| scan::export::import_scan(dojo, &input, &report));
+        match answer {
+            Ok(answer) => {
+                dojo_import = Some(serde_json::json!({
+                    "url": dojo.url,
+                    "engagement": dojo.engagement,
+                    "test": answer.get("test_id").or_else(|| answer.get("test")),
+                    "findings": report.findings.len(),
+                }));
+            }
+            Err(e) => {
+                return output_error(
+                    args.output.is_json(),
+                    &format!("DefectDojo import failed: {e:#}"),
+                );
+            }
+        }
+    }
+
+    let mut out = serde_json::json!({
+        "status": "ok",
+        "target": target_raw,
+        "elapsed_ms": elapsed_ms,
+        "timings": crate::mcp::timing::snapshot().to_json(),
+        "mode": if active { "active" } else { "passive" },
+        "server": {
+            "name": input.server_name(),
+            "version": input.server_version(),
+            "protocolVersion": input.protocol_version(),
+        },
+        "tools_scanned": input.tools.len(),
+        "capabilities": {"declared": declared, "absent": absent},
+        "checks": report.checks.iter().map(|c| serde_json::json!({
+            "id": c.id,
+            "title": c.title,
+            "status": c.status(),
+            "reason": c.skipped,
+            "findings": c.findings,
+        })).collect::<Vec<_>>(),
+        "summary": report.summary_json(),
+        "risk": risk_summary.iter().map(|(cap, tools)| {
+            (cap.as_str().to_string(), serde_json::json!(tools))
+        }).collect::<serde_json::Map<_, _>>(),
+        "findings": report.findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        "exports": exports.iter().map(|(_, p)| p).collect::<Vec<_>>(),
+        "defectdojo": dojo_import,
+        "pins": {"status": pin_status, "path": pin_file},
+        "suppressed": applied.suppressed.iter().map(|(f, e)| {
+            let mut f = f.to_json();
+            f["suppression"] = e.to_json();
+            f
+        }).collect::<Vec<_>>(),
+        "expired_suppressions": applied.expired.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+        "baseline": compared.as_ref().map(|c| serde_json::json!({
+            "path": args.baseline,
+            "status": if args.update_baseline { "updated" } else { "compared" },
+            "new": report.findings.len(),
+            "known": c.known.len(),
+            "resolved": c.resolved.iter().map(|e| e.to_json()).collect::<Vec<_>>(),
+        })),
+        "fail_on": fail_on.map(|min| serde_json::json!({
+            "severity": min.as_str(),
+            "failing": failing,
+        })),
+    });
+    if let (Some(format), Some(path)) = (report_format, &args.report)
+        && let Err(e) = std::fs::write(path, scan::report::render(format, &out))
+    {
+        return output_error(
+            args.output.is_json(),
+            &format!("failed to write report {path}: {e}"),
+        );
+    }
+
+    if sarif {
+        let shown: Vec<Finding> = shown.into_iter().cloned().collect();
+        let log = scan::sarif::render(&target_raw, &shown);
+        print_json(&log);
+        return fail_gate(failing);
+    }
+    if args.output.is_json() {
+        if let Some(min) = min_severity {
+            apply_min_severity(&mut out, &shown, min, hidden);
+        }
+        print_json(&out);
+        return fail_gate(failing);
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Scan • {} finding(s)",
+            emoji("spark", &style),
+            shown.len()
+        ),
+        Some(format!("target={target_raw} • {elapsed_ms} ms")),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} server={} {} • protocol={} • tools={} • mode={}",
+        emoji("info", &style),
+        or_dash(input.server_name()),
+        input.server_version(),
+        or_dash(input.protocol_version()),
+        input.tools.len(),
+        if active { "active" } else { "passive" }
+    );
+    println!(
+        "{}",
+        color(
+            Role::Dim,
+            format!(
+                "Capabilities: {} • absent: {}",
+                or_dash(&declared.join(", ")),
+                or_dash(&absent.join(", "))
+            ),
+            &style
+        )
+    );
+
+    if shown.is_empty() {
+        let none = match min_severity {
+            Some(min) if hidden > 0 => format!("No findings at or above {}", min.as_str()),
+            _ if compared.is_some() => "No new findings".to_string(),
+            _ => "No findings".to_string(),
+        };
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, none, &style)
+        );
+    } else {
+        let rows = finding_rows(&shown, &style);
+        let tbl = table(
+            &["SEVERITY", "CHECK", "TOOL", "FINDING"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+        println!(
+            "{}",
+            color(
+                Role::Dim,
+                "Evidence and remediation details: re-run with --json or --report FILE",
+                &style
+            )
+        );
+    }
+
+    if !applied.suppressed.is_empty() {
+        println!(
+            "{}",
+            color(
+                Role::Dim,
+                format!(
+                    "{} finding(s) suppressed (--json lists them with their reason)",
+                    applied.suppressed.len()
+                ),
+                &style
+            )
+        );
+    }
+    for e in &applied.expired {
+        println!(
+            "{} {}",
+            emoji("warn", &style),
+            color(
+                Role::Warning,
+                format!(
+                    "Suppression of {} expired on {} ({}); review or renew it",
+                    e.check,
+                    e.expires.as_deref().unwrap_or("-"),
+                    e.reason
+                ),
+                &style
+            )
+        );
+    }
+    if let (Some(c), Some(path)) = (&compared, &args.baseline) {
+        let text = if args.update_baseline {
+            format!(
+                "Baseline {path} updated: {} finding(s) accepted, {} resolved",
+                c.known.len(),
+                c.resolved.len()
+            )
+        } else {
+            format!(
+                "Baseline {path}: {} known finding(s) not shown, {} resolved",
+                c.known.len(),
+                c.resolved.len()
+            )
+        };
+        println!("{}", color(Role::Dim, text, &style));
+    }
+    if let Some(note) = hidden_note(min_severity, hidden) {
+        println!("{}", color(Role::Dim, note, &style));
+    }
+    let summary: Vec<String> = Severity::ALL
+        .iter()
+        .map(|s| format!("{}={}", s.as_str(), report.count(*s)))
+        .collect();
+    println!(
+        "{}",
+        color(Role::Dim, format!("Summary: {}", summary.join(" ")), &style)
+    );
+    if !risk_summary.is_empty() {
+        let parts: Vec<String> = risk_summary
+            .iter()
+            .map(|(cap, tools)| format!("{} ({}): {}", cap.as_str(), tools.len(), tools.join(", ")))
+            .collect();
+        println!(
+            "{}",
+            color(Role::Dim, format!("Risk: {}", parts.join(" • ")), &style)
+        );
+    }
+    if let Some(path) = &pin_file {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!(
+                    "Pinned {} tool definition(s) ({pin_status}) to {path}",
+                    input.tools.len()
+                ),
+                &style
+            )
+        );
+    }
+    if let Some(path) = &args.report {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(Role::Dim, format!("Wrote report to {path}"), &style)
+        );
+    }
+    for (format, path) in &exports {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!("Exported {format:?} findings to {path}"),
+                &style
+            )
+        );
+    }
+    if let Some(import) = &dojo_import {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(
+                Role::Dim,
+                format!(
+                    "Imported {} finding(s) into DefectDojo engagement {} (test {})",
+                    import["findings"], import["engagement"], import["test"]
+                ),
+                &style
+            )
+        );
+    }
+    // Checks left out with --checks / --skip-checks are not repeated here.
+    let skipped: Vec<String> = report
+        .checks
+        .iter()
+        .filter(|c| c.applicable && input.selection.includes(c.id))
+        .filter_map(|c| c.skipped.as_ref().map(|r| format!("{} ({r})", c.id)))
+        .collect();
+    let not_applicable: Vec<&str> = report
+        .checks
+        .iter()
+        .filter(|c| !c.applicable)
+        .map(|c| c.id)
+        .collect();
+    if !not_applicable.is_empty() {
+        println!(
+            "{}",
+            color(
+                Role::Dim,
+                format!(
+                    "Not applicable (capability not declared): {}",
+                    not_applicable.join(", ")
+                ),
+                &style
+            )
+        );
+    }
+    if !skipped.is_empty() {
+        println!(
+            "{}",
+            color(
+                Role::Dim,
+                format!("Skipped checks: {}", skipped.join(", ")),
+                &style
+            )
+        );
+    }
+    if let Some(min) = fail_on
+        && failing > 0
+    {
+        println!(
+            "{} {}",
+            emoji("error", &style),
+            color(
+                Role::Error,
+                format!(
+                    "{failing} finding(s) at or above {} (--fail-on)",
+                    min.as_str()
+                ),
+                &style
+            )
+        );
+    }
+    fail_gate(failing)
+}
+
+/* ---- Helpers ---- */
+
+/// `--list-checks`: every registered check.
+fn list_checks(json: bool) {
+    let registry = scan::checks::REGISTRY;
+    if json {
+        print_json(&serde_json::json!({
+            "status": "ok",
+            "count": registry.len(),
+            "checks": registry.iter().map(|c| serde_json::json!({
+                "id": c.id,
+                "title": c.title,
+                "requires": c.requires,
+            })).collect::<Vec<_>>(),
+        }));
+        return;
+    }
+    let style = StyleOptions::detect();
+    println!(
+        "{}",
+        box_header(
+            format!("{} Scan checks ({})", emoji("list", &style), registry.len()),
+            Some("scan --checks ID,... / --skip-checks ID,..."),
+            &style
+        )
+    );
+    let rows: Vec<Vec<String>> = registry
+        .iter()
+        .map(|c| {
+            vec![
+                c.id.to_string(),
+                c.title.to_string(),
+                c.requires.unwrap_or("-").to_string(),
+            ]
+        })
+        .collect();
+    let tbl = table(
+        &["ID", "TITLE", "REQUIRES"],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            ..Default::default()
+        },
+        &style,
+    );
+    println!("{tbl}");
+}
+
+/// Findings at or above `min` (all of them when None), in report order.
+fn at_or_above(findings: &[Finding], min: Option<Severity>) -> Vec<&Finding> {
+    findings
+        .iter()
+        .filter(|f| min.is_none_or(|min| f.severity >= min))
+        .collect()
+}
+
+/// Restrict the JSON document's findings to the displayed ones (`--min-severity`).
+fn apply_min_severity(
+    out: &mut serde_json::Value,
+    shown: &[&Finding],
+    min: Severity,
+    hidden: usize,
+) {
+    out["findings"] = shown.iter().map(|f| f.to_json()).collect();
+    out["min_severity"] = serde_json::json!({"severity": min.as_str(), "hidden": hidden});
+}
+
+fn hidden_note(min: Option<Severity>, hidden: usize) -> Option<String> {
+    let min = min.filter(|_| hidden > 0)?;
+    Some(format!(
+        "{hidden} finding(s) below {} hidden (--min-severity)",
+        min.as_str()
+    ))
+}
+
+fn finding_rows(shown: &[&Finding], style: &StyleOptions) -> Vec<Vec<String>> {
+    shown
+        .iter()
+        .map(|f| {
+            vec![
+                severity_label(f.severity, style),
+                f.check.to_string(),
+                f.tool.clone().unwrap_or_else(|| "-".into()),
+                f.title.clone(),
+            ]
+        })
+        .collect()
+}
+
+fn fail_gate(failing: usize) -> Result<()> {
+    if failing > 0 {
+        return Err(exit::Status(exit::FINDINGS).into());
+    }
+    Ok(())
+}
+
+fn severity_label(s: Severity, style: &StyleOptions) -> String {
+    let role = match s {
+        Severity::Critical | Severity::High => Role::Error,
+        Severity::Medium => Role::Warning,
+        Severity::Low => Role::Accent,
+        Severity::Info => Role::Dim,
+    };
+    color(role, s.as_str().to_uppercase(), style)
+}
+
+fn or_dash(s: &str) -> &str {
+    if s.is_empty() { "-" } else { s }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::format::BoxStyle;
+    use crate::scan::export::ExportFormat;
+    use crate::scan::{ScanInput, ScanReport, checks::Selection};
+    use serde_json::json;
+
+    fn report() -> (ScanInput, ScanReport) {
+        let input = ScanInput {
+            target: "python3 server.py".into(),
+            remote: false,
+            auth_supplied: false,
+            initialize: json!({"serverInfo": {"name": "s", "version": "1"}}),
+            tools: vec![],
+            stdout_noise: vec![],
+            probes: None,
+            pinned: None,
+            resources: vec![],
+            rules: vec![],
+            policy: None,
+            templates: vec![],
+            selection: Selection::default(),
+        };
+        let mut findings = vec![
+            Finding::new("dangerous-tool", Severity::Critical, "shell").tool("exec"),
+            Finding::new("prompt-injection", Severity::High, "ignore previous").tool("read"),
+            Finding::new("tool-naming", Severity::Low, "odd name").tool("x"),
+            Finding::new("protocol", Severity::Info, "no logging"),
+        ];
+        findings.sort_by(Finding::report_order);
+        let report = ScanReport {
+            checks: vec![],
+            findings,
+        };
+        (input, report)
+    }
+
+    #[test]
+    fn min_severity_filters_display_only() {
+        let (input, report) = report();
+        let min = Severity::High;
+        let shown = at_or_above(&report.findings, Some(min));
+        let hidden = report.findings.len() - shown.len();
+        assert_eq!(hidden, 2);
+        assert_eq!(at_or_above(&report.findings, None).len(), 4);
+
+        // Human table and hidden-count line.
+        let style = StyleOptions {
+            use_color: false,
+            use_emoji: false,
+            use_border: false,
+            term_width: 100,
+            box_style: BoxStyle::Light,
+            padding: 1,
+        };
+        let rows = finding_rows(&shown, &style);
+        let severities: Vec<&str> = rows.iter().map(|r| r[0].as_str()).collect();
+        assert_eq!(severities, vec!["CRITICAL", "HIGH"]);
+        assert_eq!(
+            hidden_note(Some(min), hidden).as_deref(),
+            Some("2 finding(s) below high hidden (--min-severity)")
+        );
+        assert!(hidden_note(Some(min), 0).is_none());
+        assert!(hidden_note(None, hidden).is_none());
+
+        // JSON: findings filtered, summary untouched.
+        let mut out = json!({
+            "summary": report.summary_json(),
+            "findings": report.findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        });
+        apply_min_severity(&mut out, &shown, min, hidden);
+        assert_eq!(out["findings"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            out["min_severity"],
+            json!({"severity": "high", "hidden": 2})
+        );
+        assert_eq!(out["summary"]["low"], 1);
+
+        // SARIF.
+        let shown_owned: Vec<Finding> = shown.iter().map(|f| (*f).clone()).collect();
+        let log = scan::sarif::render(&input.target, &shown_owned);
+        assert_eq!(log["runs"][0]["results"].as_array().unwrap().len(), 2);
+
+        // --fail-on and exports still see every finding.
+        assert_eq!(at_or_above(&report.findings, Some(Severity::Low)).len(), 3);
+        let export: serde_json::Value =
+            serde_json::from_str(&render(ExportFormat::Generic, &input, &report)).unwrap();
+        assert_eq!(export["findings"].as_array().unwrap().len(), 4);
+    }
+}
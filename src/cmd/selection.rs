@@ -0,0 +1,186 @@
+/*!
+selection.rs - narrowing and ordering tool listings.
+
+  --filter PATTERN   tool name matches a glob (`get_*`, case-insensitive)
+                     or a /regex/ (`/^(read|write)_/i`, see utils::pattern)
+  --grep TEXT        name or description contains TEXT (case-insensitive)
+  --sort KEY         name (case-insensitive), params (fewest first), risk
+                     (highest first, see scan::risk) or none (server order,
+                     the default); ties keep the server order
+  --reverse          reverse the order
+
+They apply before output, human and JSON alike; --filter and --grep given
+together must both match. Used by `list tools` and `get tools` (and the
+choices offered by `get tool` without a name). When filtering, JSON output
+carries `"total"`, the number of tools the server listed, next to `"count"`.
+
+Example:
+  mcp-hack list tools -t "npx -y @modelcontextprotocol/server-github" --filter '*issue*'
+*/
+
+use clap::Args;
+use serde_json::Value;
+
+use crate::scan::risk;
+use crate::utils::pattern::Pattern;
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Only tools whose name matches a glob (get_*) or /regex/ (/^read_/i)
+    #[arg(long, value_name = "PATTERN", value_parser = parse_pattern)]
+    pub filter: Option<Pattern>,
+
+    /// Only tools whose name or description contains TEXT (case-insensitive)
+    #[arg(long, value_name = "TEXT")]
+    pub grep: Option<String>,
+}
+
+fn parse_pattern(s: &str) -> Result<Pattern, String> {
+    Pattern::parse(s).map_err(|e| format!("{e:#}"))
+}
+
+impl FilterArgs {
+    pub fn active(&self) -> bool {
+        self.filter.is_some() || self.grep.is_some()
+    }
+
+    /// Whether `tool` (a tools/list entry) passes both filters.
+    pub fn keeps(&self, tool: &Value) -> bool {
+        let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if let Some(pattern) = &self.filter
+            && !pattern.matches(name)
+        {
+            return false;
+        }
+        let Some(text) = &self.grep else {
+            return true;
+        };
+        let text = text.to_lowercase();
+        let description = tool
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        name.to_lowercase().contains(&text) || description.to_lowercase().contains(&text)
+    }
+
+    /// "N" or, when filtering, "N of TOTAL" (for headings).
+    pub fn shown(&self, count: usize, total: usize) -> String {
+        if self.active() {
+            format!("{count} of {total}")
+        } else {
+            count.to_string()
+        }
+    }
+
+    /// Drop the tools that do not pass.
+    pub fn apply(&self, tools: &mut Vec<Value>) {
+        if self.active() {
+            tools.retain(|t| self.keeps(t));
+        }
+    }
+}
+
+/// Order of a tool listing (`--sort`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Server order
+    #[default]
+    None,
+    Name,
+    /// Number of parameters, fewest first
+    Params,
+    /// Highest capability risk first
+    Risk,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct SortArgs {
+    /// Order tools by name, params, risk or none (server order)
+    #[arg(long, value_enum, value_name = "KEY", default_value_t = SortKey::None)]
+    pub sort: SortKey,
+
+    /// Reverse the order
+    #[arg(long)]
+    pub reverse: bool,
+}
+
+impl SortArgs {
+    /// Sort `tools` (tools/list entries) in place; stable.
+    pub fn apply(&self, tools: &mut [Value]) {
+        let name = |t: &Value| {
+            t.get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_lowercase()
+        };
+        match self.sort {
+            SortKey::None => {}
+            SortKey::Name => tools.sort_by_cached_key(name),
+            SortKey::Params => tools.sort_by_cached_key(|t| {
+                t.get("inputSchema")
+                    .or_else(|| t.get("input_schema"))
+                    .and_then(|s| s.get("properties"))
+                    .and_then(|p| p.as_object())
+                    .map_or(0, |p| p.len())
+            }),
+            SortKey::Risk => tools
+                .sort_by_cached_key(|t| std::cmp::Reverse(risk::risk_level(&risk::classify(t)))),
+        }
+        if self.reverse {
+            tools.reverse();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn filters_by_name_and_text() {
+        let mut tools = vec![
+            json!({"name": "read_file", "description": "Read a file"}),
+            json!({"name": "write_file", "description": "Write a FILE to disk"}),
+            json!({"name": "search", "description": "Search issues"}),
+        ];
+        let args = FilterArgs {
+            filter: Some(Pattern::parse("*_file").unwrap()),
+            grep: Some("disk".into()),
+        };
+        args.apply(&mut tools);
+        assert_eq!(
+            tools,
+            vec![json!({"name": "write_file", "description": "Write a FILE to disk"})]
+        );
+
+        let grep = FilterArgs {
+            filter: None,
+            grep: Some("SEARCH".into()),
+        };
+        assert!(grep.keeps(&json!({"name": "search"})));
+        assert!(!grep.keeps(&json!({"name": "x", "description": "nothing"})));
+        assert!(!FilterArgs::default().active());
+    }
+
+    #[test]
+    fn sorts_by_key() {
+        let tools = vec![
+            json!({"name": "b", "inputSchema": {"properties": {"x": {}, "y": {}}}}),
+            json!({"name": "exec_command", "description": "Run a shell command"}),
+            json!({"name": "A", "inputSchema": {"properties": {"x": {}}}}),
+        ];
+        let order = |sort, reverse| {
+            let mut t = tools.clone();
+            SortArgs { sort, reverse }.apply(&mut t);
+            t.iter()
+                .map(|t| t["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(SortKey::None, false), ["b", "exec_command", "A"]);
+        assert_eq!(order(SortKey::Name, false), ["A", "b", "exec_command"]);
+        assert_eq!(order(SortKey::Params, false), ["exec_command", "A", "b"]);
+        assert_eq!(order(SortKey::Risk, false), ["exec_command", "b", "A"]);
+        assert_eq!(order(SortKey::Name, true), ["exec_command", "b", "A"]);
+    }
+}
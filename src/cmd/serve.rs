@@ -0,0 +1,234 @@
+/*!
+serve.rs - serve subcommand.
+
+Runs a mock MCP server answering from a snapshot file (see crate::mcp::mock
+for the format): its tools, resources and prompts are advertised as-is and
+tool calls get the canned responses. Useful to exercise a client, a proxy
+rule set or mcp-hack itself without the real server.
+
+Transports:
+  stdio (default)   newline-delimited JSON-RPC on stdin / stdout
+  --http ADDR       JSON-RPC over HTTP POST on ADDR (any path); each request
+                    body gets the response as application/json, a body with
+                    only notifications gets 202
+
+stdout belongs to the protocol in stdio mode: the startup note goes to
+stderr (silenced by -q).
+
+Example:
+  mcp-hack serve --from snapshot.json
+  mcp-hack list tools -t "mcp-hack serve --from snapshot.json"
+  mcp-hack serve --from snapshot.json --http 127.0.0.1:8931
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+
+use crate::cmd::exec::report_error;
+use crate::cmd::output::OutputFormat;
+use crate::mcp::mock::MockServer;
+use crate::utils::logging::{LogLevel, current_log_level};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports (stdout carries the protocol).
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text];
+
+#[derive(Args, Debug)]
+pub struct ServeArgs {
+    /// Snapshot file with the tools / resources / prompts to serve
+    #[arg(long, value_name = "PATH")]
+    pub from: String,
+
+    /// Serve over HTTP on this address instead of stdio (e.g. 127.0.0.1:8931)
+    #[arg(long, value_name = "ADDR")]
+    pub http: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// Answer one JSON-RPC payload (single message or batch); None when
+/// nothing needs a response.
+fn answer(server: &MockServer, text: &str) -> Option<Value> {
+    let msg: Value = match serde_json::from_str(text) {
+        Ok(v) => v,
+        Err(e) => {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": {"code": -32700, "message": format!("Parse error: {e}")},
+            }));
+        }
+    };
+    match msg {
+        Value::Array(batch) => {
+            let out: Vec<Value> = batch.iter().filter_map(|m| server.handle(m)).collect();
+            (!out.is_empty()).then_some(Value::Array(out))
+        }
+        single => server.handle(&single),
+    }
+}
+
+fn serve_stdio(server: &MockServer) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let line = line.context("failed to read stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(resp) = answer(server, &line) {
+            writeln!(stdout, "{resp}").and_then(|_| stdout.flush())?;
+        }
+    }
+    Ok(())
+}
+
+/* ---- HTTP ---- */
+
+/// One HTTP/1.1 request (no keep-alive; the body is read per Content-Length).
+pub(crate) struct HttpRequest {
+    pub method: String,
+    /// Request target as sent: path and query
+    pub target: String,
+    /// (name, value) in the order received
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Path and decoded query parameters of the request target.
+    pub fn path_and_query(&self) -> (&str, Vec<(String, String)>) {
+        let (path, query) = self.target.split_once('?').unwrap_or((&self.target, ""));
+        let params = url::form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        (path, params)
+    }
+}
+
+pub(crate) fn read_request(stream: &TcpStream) -> Result<HttpRequest> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/").to_string();
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let mut request = HttpRequest {
+        method,
+        target,
+        headers,
+        body: Vec::new(),
+    };
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    request.body = vec![0u8; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+pub(crate) fn write_response(
+    mut stream: TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Accept connections on `addr` until the process ends, each handled on its
+/// own thread; `tag` prefixes connection errors (shown at debug level).
+pub(crate) fn listen<F>(addr: &str, tag: &'static str, handle: F) -> Result<()>
+where
+    F: Fn(TcpStream) -> Result<()> + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind(addr).with_context(|| format!("failed to bind {addr}"))?;
+    let handle = Arc::new(handle);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else { continue };
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = handle(stream)
+                && current_log_level() >= LogLevel::Debug
+            {
+                eprintln!("[{tag}] connection error: {e:#}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answer one JSON-RPC POST.
+fn serve_connection(server: &MockServer, stream: TcpStream) -> Result<()> {
+    let request = read_request(&stream)?;
+    let (status, body) = if request.method != "POST" {
+        ("405 Method Not Allowed", String::new())
+    } else {
+        match answer(server, &String::from_utf8_lossy(&request.body)) {
+            Some(resp) => ("200 OK", resp.to_string()),
+            None => ("202 Accepted", String::new()),
+        }
+    };
+    write_response(stream, status, "application/json", &body)
+}
+
+fn serve_http(server: Arc<MockServer>, addr: &str) -> Result<()> {
+    listen(addr, "serve", move |stream| {
+        serve_connection(&server, stream)
+    })
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_serve(args: ServeArgs) -> Result<()> {
+    let server = match MockServer::load(&args.from) {
+        Ok(s) => s,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let (tools, resources, prompts) = server.counts();
+    if current_log_level() >= LogLevel::Info {
+        eprintln!(
+            "[serve] {} tool(s), {} resource(s), {} prompt(s) from {} on {}",
+            tools,
+            resources,
+            prompts,
+            args.from,
+            args.http.as_deref().unwrap_or("stdio")
+        );
+    }
+    match &args.http {
+        Some(addr) => serve_http(Arc::new(server), addr),
+        None => serve_stdio(&server),
+    }
+}
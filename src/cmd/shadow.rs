@@ -0,0 +1,209 @@
+/*!
+shadow.rs - shadow subcommand.
+
+Enumerates tools on several local targets (given directly or imported from
+an MCP client config) and reports cross-server tool shadowing:
+  - identical tool names on different servers
+  - look-alike names (separators / case / homoglyphs / one-letter edits)
+  - descriptions that reference another server's tools
+
+Example:
+  mcp-hack shadow "npx -y @modelcontextprotocol/server-everything" "python3 evil.py"
+  mcp-hack shadow --config ~/Library/Application\ Support/Claude/claude_desktop_config.json
+
+Remote targets in a config are listed as skipped (remote transports not implemented yet).
+*/
+
+use anyhow::Result;
+use clap::Args;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp;
+use crate::mcp::client_config;
+use crate::scan::shadow::{ServerTools, analyze};
+use crate::scan::{self, Finding};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct ShadowArgs {
+    /// Targets to compare (local commands)
+    #[arg(value_name = "TARGET")]
+    pub targets: Vec<String>,
+
+    /// Import servers from an MCP client config (mcpServers JSON)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// Per-server enumeration status.
+struct ServerStatus {
+    label: String,
+    target: String,
+    tools: usize,
+    error: Option<String>,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_shadow(args: ShadowArgs) -> Result<()> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    if let Some(path) = &args.config {
+        match client_config::load(path) {
+            Ok(servers) => entries.extend(servers.into_iter().map(|s| (s.name, s.target))),
+            Err(e) => return report_error(args.output.is_json(), e),
+        }
+    }
+    for t in &args.targets {
+        if !t.trim().is_empty() {
+            entries.push((t.trim().to_string(), t.trim().to_string()));
+        }
+    }
+    if entries.len() < 2 {
+        return usage_error(
+            args.output.is_json(),
+            "need at least two targets (positional TARGETs and/or --config)",
+        );
+    }
+
+    let started = Instant::now();
+    let mut statuses = Vec::new();
+    let mut inventories = Vec::new();
+    let mut label_counts: std::collections::HashMap<String, usize> = Default::default();
+    for (label, target) in entries {
+        let result = async {
+            let spec = mcp::parse_target(&target)?;
+            if !spec.is_local() {
+                anyhow::bail!("remote targets not implemented yet");
+            }
+            scan::collect(&spec, Duration::from_secs(args.timeout), false).await
+        }
+        .await;
+        match result {
+            Ok(input) => {
+                // Prefer the config key; fall back to what the server calls itself.
+                let mut label = if label == target && !input.server_name().is_empty() {
+                    input.server_name().to_string()
+                } else {
+                    label
+                };
+                // Keep labels unique ("name", "name#2", ...) so findings stay unambiguous.
+                let n = label_counts.entry(label.clone()).or_default();
+                *n += 1;
+                if *n > 1 {
+                    label = format!("{label}#{n}");
+                }
+                statuses.push(ServerStatus {
+                    label: label.clone(),
+                    target,
+                    tools: input.tools.len(),
+                    error: None,
+                });
+                inventories.push(ServerTools {
+                    label,
+                    tools: input.tools,
+                });
+            }
+            Err(e) => statuses.push(ServerStatus {
+                label,
+                target,
+                tools: 0,
+                error: Some(format!("{e:#}")),
+            }),
+        }
+    }
+    let findings = analyze(&inventories);
+    let elapsed_ms = started.elapsed().as_millis();
+
+    if args.output.is_json() {
+        let out = serde_json::json!({
+            "status": "ok",
+            "elapsed_ms": elapsed_ms,
+            "timings": crate::mcp::timing::snapshot().to_json(),
+            "servers": statuses.iter().map(|s| serde_json::json!({
+                "label": s.label,
+                "target": s.target,
+                "tools": s.tools,
+                "error": s.error,
+            })).collect::<Vec<_>>(),
+            "count": findings.len(),
+            "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Tool shadowing • {} finding(s)",
+            emoji("spark", &style),
+            findings.len()
+        ),
+        Some(format!("{} server(s) • {elapsed_ms} ms", statuses.len())),
+        &style,
+    );
+    println!("{header}");
+    for s in &statuses {
+        match &s.error {
+            None => println!(
+                "{} {} ({} tools) {}",
+                emoji("success", &style),
+                s.label,
+                s.tools,
+                color(Role::Dim, &s.target, &style)
+            ),
+            Some(e) => println!(
+                "{} {} {}",
+                emoji("error", &style),
+                s.label,
+                color(Role::Error, format!("skipped: {e}"), &style)
+            ),
+        }
+    }
+    println!();
+    if findings.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, "No shadowing detected", &style)
+        );
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = findings
+        .iter()
+        .map(|f| {
+            vec![
+                f.severity.as_str().to_uppercase(),
+                f.tool.clone().unwrap_or_default(),
+                f.title.clone(),
+                f.evidence.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    let tbl = table(
+        &["SEVERITY", "TOOL", "FINDING", "EVIDENCE"],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            ..Default::default()
+        },
+        &style,
+    );
+    println!("{tbl}");
+    Ok(())
+}
@@ -2,18 +2,25 @@
 shared.rs - shared helpers for subcommands.
 
 Focus:
-  - fetch_tools_local(_async): spawn local MCP process + list tools
+  - fetch_tools_local / fetch_tools_for / fetch_tools_live: spawn local MCP
+    process + list tools
   - extract_tool_array / find_tool_case_insensitive
-  - build_arguments_from_schema + primitive coercion
+  - build_arguments_from_schema / merge_arguments + primitive coercion / placeholder values
+  - validate_arguments (input schema checks, see crate::mcp::schema) / undeclared_arguments
+  - annotation hints (readOnlyHint, destructiveHint, ...) for display
   - summarize_call_result
 
-Goal: keep reusable, minimal logic for list/get/exec. Remote transports,
-caching, richer validation left for future iterations.
+Goal: keep reusable, minimal logic for list/get/exec. Tool listings go
+through the metadata cache (crate::mcp::cache). Remote transports and
+nested-schema validation left for future iterations.
 */
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::time::Instant;
 
+use crate::cmd::exit;
+use crate::mcp::session::McpSession;
+
 /* ---- Data Structures ---- */
 
 /// Result of fetching tools from a local MCP target process.
@@ -23,6 +30,8 @@ pub struct ToolList {
     pub tools: Vec<serde_json::Value>,
     /// Elapsed time (milliseconds) for the entire spawn + enumerate + shutdown flow
     pub elapsed_ms: u128,
+    /// Read from the metadata cache (no server was started)
+    pub cached: bool,
 }
 
 impl ToolList {
@@ -35,70 +44,92 @@ impl ToolList {
     pub fn iter(&self) -> impl Iterator<Item = &serde_json::Value> {
         self.tools.iter()
     }
+
+    /// "N ms", or "cached" (for headers).
+    pub fn took(&self) -> String {
+        if self.cached {
+            "cached".to_string()
+        } else {
+            format!("{} ms", self.elapsed_ms)
+        }
+    }
+
+    /// Add `"cached": true` to a JSON object when read from the cache.
+    pub fn annotate(&self, out: &mut serde_json::Value) {
+        if self.cached {
+            out["cached"] = true.into();
+        }
+    }
 }
 
 /* ---- Fetch / Spawn Helpers ---- */
 
-/// Synchronous convenience wrapper:
-///   - Creates a temporary Tokio runtime
+/// Tool enumeration for local targets (or the metadata cache):
 ///   - Spawns the local MCP server process
 ///   - Queries available tools
 ///   - Cancels (graceful shutdown attempt)
 ///
 /// Returns a `ToolList` with raw tool JSON objects.
-/// Only supports *local* targets (`TargetSpec::LocalCommand`).
-pub fn fetch_tools_local(spec: &crate::mcp::TargetSpec) -> Result<ToolList> {
-    let rt = tokio::runtime::Runtime::new().context("Failed to create Tokio runtime")?;
-    rt.block_on(fetch_tools_local_async(spec))
-}
+/// Only supports *local* targets (process or socket, see TargetSpec::is_local).
+pub async fn fetch_tools_local(spec: &crate::mcp::TargetSpec) -> Result<ToolList> {
+    if !spec.is_local() {
+        return Err(exit::usage(
+            "fetch_tools_local only supports local process targets",
+        ));
+    }
 
-/// Async variant of tool enumeration for local targets.
-pub async fn fetch_tools_local_async(spec: &crate::mcp::TargetSpec) -> Result<ToolList> {
-    use rmcp::ServiceExt;
-    use rmcp::transport::{ConfigureCommandExt, TokioChildProcess};
-    use tokio::process::Command;
+    if let Some(tools) = crate::mcp::cache::load(spec.original(), "tools") {
+        return Ok(ToolList {
+            tools,
+            elapsed_ms: 0,
+            cached: true,
+        });
+    }
+    fetch_tools_live(spec).await
+}
 
-    let (program, args) = match spec {
-        crate::mcp::TargetSpec::LocalCommand { program, args, .. } => {
-            (program.clone(), args.clone())
-        }
-        _ => anyhow::bail!("fetch_tools_local_async only supports local process targets"),
-    };
+/// Like [`fetch_tools_local`], but always asks the server; the listing still
+/// refreshes the cache. Used by `list`, whose job includes noticing changed tools.
+pub async fn fetch_tools_live(spec: &crate::mcp::TargetSpec) -> Result<ToolList> {
+    if !spec.is_local() {
+        return Err(exit::usage(
+            "fetch_tools_live only supports local process targets",
+        ));
+    }
 
     let started = Instant::now();
+    let session = McpSession::connect(spec, ()).await?;
+    let tools = session.list_tools().await;
+    session.shutdown().await;
+    let tools = tools?;
 
-    let service = ()
-        .serve(TokioChildProcess::new(Command::new(&program).configure(
-            |c| {
-                for a in &args {
-                    c.arg(a);
-                }
-                // Suppress child stderr (banner / noisy logs) — keep stdout for protocol.
-                c.stderr(std::process::Stdio::null());
-            },
-        ))?)
-        .await
-        .with_context(|| format!("Failed to spawn MCP process: {}", program))?;
-
-    let tools_resp = service
-        .list_tools(Default::default())
-        .await
-        .context("Failed to list tools from MCP service")?;
-
-    // Attempt graceful shutdown (ignore failure).
-    let _ = service.cancel().await;
-
-    let val = serde_json::to_value(&tools_resp).unwrap_or(serde_json::Value::Null);
-    let mut tools = Vec::new();
-    if let Some(arr) = val.get("tools").and_then(|v| v.as_array()) {
-        for t in arr {
-            tools.push(t.clone());
-        }
-    }
+    crate::mcp::cache::store(spec.original(), "tools", &tools);
 
     Ok(ToolList {
         tools,
         elapsed_ms: started.elapsed().as_millis(),
+        cached: false,
+    })
+}
+
+/// Like [`fetch_tools_local`], for a command about the tools `names`: when
+/// a cached listing lacks one (the tool may be new), the server is asked
+/// again.
+pub async fn fetch_tools_for(spec: &crate::mcp::TargetSpec, names: &[&str]) -> Result<ToolList> {
+    let list = fetch_tools_local(spec).await?;
+    if list.cached && !names.iter().all(|name| has_tool(&list.tools, name)) {
+        crate::mcp::cache::bypass();
+        return fetch_tools_local(spec).await;
+    }
+    Ok(list)
+}
+
+/// Whether `tools` has one named `name` (case-insensitive).
+pub fn has_tool(tools: &[serde_json::Value], name: &str) -> bool {
+    tools.iter().any(|t| {
+        t.get("name")
+            .and_then(|v| v.as_str())
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
     })
 }
 
@@ -106,6 +137,7 @@ pub async fn fetch_tools_local_async(spec: &crate::mcp::TargetSpec) -> Result<To
 
 /// Return a cloned vector of tool objects from a JSON value containing a `tools` array.
 /// Silent on missing / malformed content (returns empty vec).
+#[allow(dead_code)]
 pub fn extract_tool_array(value: &serde_json::Value) -> Vec<serde_json::Value> {
     value
         .get("tools")
@@ -132,69 +164,320 @@ pub fn find_tool_case_insensitive(
 
 /* ---- Argument Building / Schema Handling ---- */
 
+/// A tool's input schema (`input_schema` or `inputSchema`), if any.
+fn input_schema(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+) -> Option<&serde_json::Map<String, serde_json::Value>> {
+    tool_obj
+        .get("input_schema")
+        .or_else(|| tool_obj.get("inputSchema"))
+        .and_then(|v| v.as_object())
+}
+
 /// Build a JSON arguments object based on a tool's `input_schema` / `inputSchema`.
 ///
 /// - `provided` map contains raw string values (from CLI, files, interactive input).
 /// - Required detection uses `input_schema.required` (or `inputSchema.required`) array.
 /// - Each parameter is coerced according to its declared `"type"` property:
-///       integer | number | boolean | array | (default -> string)
+///   integer | number | boolean | array | (default -> string)
+/// - Dotted / indexed keys build nested values: `filter.status=open`,
+///   `filter.tags[0]=bug` -> {"filter": {"status": "open", "tags": ["bug"]}},
+///   each leaf coerced by its sub-schema (`properties` / `items`).
 /// - Extra keys in `provided` (not in schema) are passed through as strings.
 /// - Returns an error if a required parameter is missing.
 ///
-/// NOTE: Strict schema validation (enum constraints, nested objects, etc.) is
-/// intentionally deferred for future enhancement.
+/// Use [`validate_arguments`] for enum / type checks of the result.
 pub fn build_arguments_from_schema(
     tool_obj: &serde_json::Map<String, serde_json::Value>,
     provided: &std::collections::HashMap<String, String>,
 ) -> Result<serde_json::Map<String, serde_json::Value>> {
-    // Support both snake_case `input_schema` and camelCase `inputSchema`
-    let schema = tool_obj
-        .get("input_schema")
-        .or_else(|| tool_obj.get("inputSchema"))
-        .and_then(|v| v.as_object());
-    let mut result = serde_json::Map::new();
+    merge_arguments(tool_obj, serde_json::Map::new(), provided)
+}
 
-    // Collect required names
-    let mut required: std::collections::HashSet<&str> = std::collections::HashSet::new();
-    if let Some(req_arr) = schema
-        .and_then(|s| s.get("required"))
-        .and_then(|v| v.as_array())
-    {
-        for r in req_arr {
-            if let Some(s) = r.as_str() {
-                required.insert(s);
+/// `base` (e.g. from --args-json) with the coerced `provided` values merged
+/// in (nested objects key by key), then the required check of
+/// [`build_arguments_from_schema`].
+pub fn merge_arguments(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+    mut base: serde_json::Map<String, serde_json::Value>,
+    provided: &std::collections::HashMap<String, String>,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    for (k, v) in coerce_arguments(tool_obj, provided)? {
+        match base.get_mut(&k) {
+            Some(existing) => deep_merge(existing, v),
+            None => {
+                base.insert(k, v);
+            }
+        }
+    }
+    if let Some(missing) = missing_required(tool_obj, &base).first() {
+        return Err(exit::usage(format!(
+            "missing required parameter: {missing}"
+        )));
+    }
+    Ok(base)
+}
+
+/// Merge `src` into `dst`: objects key by key, anything else replaced.
+pub fn deep_merge(dst: &mut serde_json::Value, src: serde_json::Value) {
+    match (dst, src) {
+        (serde_json::Value::Object(d), serde_json::Value::Object(s)) => {
+            for (k, v) in s {
+                match d.get_mut(&k) {
+                    Some(existing) => deep_merge(existing, v),
+                    None => {
+                        d.insert(k, v);
+                    }
+                }
             }
         }
+        (dst, src) => *dst = src,
     }
+}
 
-    let mut remaining = provided.clone();
+/// One step of a dotted / indexed parameter key.
+#[derive(Debug, Clone, PartialEq)]
+enum PathSeg {
+    Key(String),
+    Index(usize),
+}
 
-    if let Some(props) = schema
-        .and_then(|s| s.get("properties"))
-        .and_then(|v| v.as_object())
-    {
-        for (pname, pobj) in props {
-            let ptype = pobj
-                .as_object()
-                .and_then(|m| m.get("type"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("string");
-            if let Some(raw_v) = remaining.remove(pname) {
-                result.insert(pname.clone(), coerce_value(&raw_v, ptype));
-            } else if required.contains(pname.as_str()) {
-                anyhow::bail!("missing required parameter: {}", pname);
+/// Split `filter.tags[0]` into segments; None for a plain key.
+fn parse_param_path(key: &str) -> Result<Option<Vec<PathSeg>>> {
+    if !key.contains(['.', '[']) {
+        return Ok(None);
+    }
+    let invalid = || exit::usage(format!("invalid parameter path '{key}'"));
+    let mut segs = Vec::new();
+    let mut rest = key;
+    let mut first = true;
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('[') {
+            let end = r.find(']').ok_or_else(invalid)?;
+            segs.push(PathSeg::Index(r[..end].parse().map_err(|_| invalid())?));
+            rest = &r[end + 1..];
+        } else {
+            let r = if first {
+                rest
+            } else {
+                rest.strip_prefix('.').ok_or_else(invalid)?
+            };
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            if end == 0 {
+                return Err(invalid());
             }
+            segs.push(PathSeg::Key(r[..end].to_string()));
+            rest = &r[end..];
         }
+        first = false;
     }
+    Ok(Some(segs))
+}
+
+/// Top-level parameter a (possibly dotted) key belongs to.
+pub fn param_root(key: &str) -> &str {
+    key.split(['.', '[']).next().unwrap_or(key)
+}
+
+/// Store `leaf` at `path` below `slot`, creating objects / arrays on the way.
+fn insert_path(
+    slot: &mut serde_json::Value,
+    path: &[PathSeg],
+    leaf: serde_json::Value,
+    key: &str,
+) -> Result<()> {
+    let Some((seg, rest)) = path.split_first() else {
+        *slot = leaf;
+        return Ok(());
+    };
+    let child = match seg {
+        PathSeg::Key(k) => {
+            if slot.is_null() {
+                *slot = serde_json::Value::Object(serde_json::Map::new());
+            }
+            let serde_json::Value::Object(map) = slot else {
+                return Err(exit::usage(format!(
+                    "parameter '{key}' conflicts with a non-object value"
+                )));
+            };
+            map.entry(k.clone()).or_insert(serde_json::Value::Null)
+        }
+        PathSeg::Index(i) => {
+            if slot.is_null() {
+                *slot = serde_json::Value::Array(Vec::new());
+            }
+            let serde_json::Value::Array(items) = slot else {
+                return Err(exit::usage(format!(
+                    "parameter '{key}' conflicts with a non-array value"
+                )));
+            };
+            if items.len() <= *i {
+                items.resize(i + 1, serde_json::Value::Null);
+            }
+            &mut items[*i]
+        }
+    };
+    insert_path(child, rest, leaf, key)
+}
 
-    // Any leftovers not in schema -> add as simple strings
-    for (k, v) in remaining {
-        result.insert(k, serde_json::Value::String(v));
+/// Declared `"type"` of a (sub-)schema, defaulting to string.
+fn schema_type(schema: Option<&serde_json::Value>) -> &str {
+    schema
+        .and_then(|s| s.get("type"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("string")
+}
+
+/// Coerce `provided` by the schema's property types, without the required check.
+pub fn coerce_arguments(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+    provided: &std::collections::HashMap<String, String>,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let props = input_schema(tool_obj)
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object());
+    let mut result = serde_json::Map::new();
+
+    // Plain keys first so dotted keys can add to (or conflict with) them.
+    let mut keys: Vec<&String> = provided.keys().collect();
+    keys.sort_by_key(|k| (k.contains(['.', '[']), k.as_str()));
+    for key in keys {
+        let raw_v = &provided[key];
+        let path = match parse_param_path(key)? {
+            // A declared property whose name contains '.' stays flat.
+            Some(path) if props.is_none_or(|p| !p.contains_key(key)) => path,
+            _ => {
+                let ptype = schema_type(props.and_then(|p| p.get(key)));
+                result.insert(key.clone(), coerce_value(raw_v, ptype));
+                continue;
+            }
+        };
+        let PathSeg::Key(root) = &path[0] else {
+            return Err(exit::usage(format!("invalid parameter path '{key}'")));
+        };
+        // Follow the schema along the path for the leaf type.
+        let mut schema = props.and_then(|p| p.get(root));
+        for seg in &path[1..] {
+            schema = match seg {
+                PathSeg::Key(k) => schema
+                    .and_then(|s| s.get("properties"))
+                    .and_then(|p| p.get(k)),
+                PathSeg::Index(_) => schema.and_then(|s| s.get("items")),
+            };
+        }
+        let slot = result
+            .entry(root.clone())
+            .or_insert(serde_json::Value::Null);
+        insert_path(
+            slot,
+            &path[1..],
+            coerce_value(raw_v, schema_type(schema)),
+            key,
+        )?;
     }
 
     Ok(result)
 }
 
+/// Declared properties listed in `required` but absent from `arguments`.
+fn missing_required<'a>(
+    tool_obj: &'a serde_json::Map<String, serde_json::Value>,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<&'a str> {
+    let Some(schema) = input_schema(tool_obj) else {
+        return Vec::new();
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|x| x.as_str()).collect())
+        .unwrap_or_default();
+    schema
+        .get("properties")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flat_map(|props| props.keys())
+        .map(String::as_str)
+        .filter(|p| required.contains(p) && !arguments.contains_key(*p))
+        .collect()
+}
+
+/// Schema problems of an argument object (see crate::mcp::schema): missing
+/// required parameters, type / enum mismatches, ranges, lengths, formats and
+/// parameters the schema does not declare when it sets
+/// `additionalProperties: false`. Empty when valid or without a schema.
+pub fn validate_arguments(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<String> {
+    match input_schema(tool_obj) {
+        Some(schema) => crate::mcp::schema::check(
+            &serde_json::Value::Object(schema.clone()),
+            &serde_json::Value::Object(arguments.clone()),
+        ),
+        None => Vec::new(),
+    }
+}
+
+/// Arguments the schema does not declare, as `$.path` (with a "did you mean"
+/// hint for near-miss names). Only objects whose schema lists `properties`
+/// are checked, so free-form objects pass.
+pub fn undeclared_arguments(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+    arguments: &serde_json::Map<String, serde_json::Value>,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    if let Some(schema) = input_schema(tool_obj) {
+        undeclared_in(schema, arguments, "$", &mut out);
+    }
+    out
+}
+
+fn undeclared_in(
+    schema: &serde_json::Map<String, serde_json::Value>,
+    value: &serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    out: &mut Vec<String>,
+) {
+    let Some(props) = schema.get("properties").and_then(|v| v.as_object()) else {
+        return;
+    };
+    for (name, v) in value {
+        let child = format!("{path}.{name}");
+        let Some(sub) = props.get(name) else {
+            let hint = props
+                .keys()
+                .map(|k| (crate::scan::shadow::levenshtein(name, k), k))
+                .filter(|(d, _)| *d <= 2)
+                .min();
+            out.push(match hint {
+                Some((_, k)) => format!("{child} (did you mean '{k}'?)"),
+                None => child,
+            });
+            continue;
+        };
+        let items = match v {
+            serde_json::Value::Object(m) => vec![(child, m)],
+            serde_json::Value::Array(a) => a
+                .iter()
+                .enumerate()
+                .filter_map(|(i, x)| x.as_object().map(|m| (format!("{child}[{i}]"), m)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let sub = if v.is_array() {
+            sub.get("items")
+        } else {
+            Some(sub)
+        };
+        if let Some(sub) = sub.and_then(|s| s.as_object()) {
+            for (p, m) in items {
+                undeclared_in(sub, m, &p, out);
+            }
+        }
+    }
+}
+
 /// Attempt to coerce a raw string into a JSON value using a primitive type hint.
 pub fn coerce_value(raw: &str, type_hint: &str) -> serde_json::Value {
     match type_hint {
@@ -216,6 +499,14 @@ pub fn coerce_value(raw: &str, type_hint: &str) -> serde_json::Value {
                 _ => serde_json::Value::String(raw.to_string()),
             }
         }
+        // A JSON array / object as written (e.g. typed at the interactive prompt)
+        "array" | "object"
+            if raw.trim_start().starts_with(['[', '{'])
+                && let Ok(v) = serde_json::from_str::<serde_json::Value>(raw)
+                && (v.is_array() == (type_hint == "array")) =>
+        {
+            v
+        }
         "array" => {
             let arr = raw
                 .split(',')
@@ -228,6 +519,114 @@ pub fn coerce_value(raw: &str, type_hint: &str) -> serde_json::Value {
     }
 }
 
+/// Arguments for a tool called as a workload (soak, bench): the given
+/// params, placeholders ([`placeholder_for`]) for the other required ones.
+pub fn placeholder_arguments(
+    tool: &serde_json::Value,
+    provided: &std::collections::HashMap<String, String>,
+) -> Result<serde_json::Value> {
+    let Some(obj) = tool.as_object() else {
+        anyhow::bail!("malformed tool definition");
+    };
+    let mut provided = provided.clone();
+    let schema = tool.get("inputSchema").or_else(|| tool.get("input_schema"));
+    for name in schema
+        .and_then(|s| s.get("required"))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+    {
+        if !provided.contains_key(name) {
+            let prop = schema
+                .and_then(|s| s.pointer(&format!("/properties/{name}")))
+                .cloned()
+                .unwrap_or(serde_json::Value::Null);
+            provided.insert(name.to_string(), placeholder_for(&prop));
+        }
+    }
+    Ok(serde_json::Value::Object(build_arguments_from_schema(
+        obj, &provided,
+    )?))
+}
+
+/// Filler value for a required parameter the caller does not control: default, first enum value, or a
+/// type-appropriate placeholder.
+pub fn placeholder_for(prop: &serde_json::Value) -> String {
+    let as_string = |v: &serde_json::Value| match v {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if let Some(d) = prop.get("default") {
+        return as_string(d);
+    }
+    if let Some(first) = prop
+        .get("enum")
+        .and_then(|v| v.as_array())
+        .and_then(|a| a.first())
+    {
+        return as_string(first);
+    }
+    match prop.get("type").and_then(|v| v.as_str()) {
+        Some("integer") | Some("number") => "1".into(),
+        Some("boolean") => "true".into(),
+        _ => "test".into(),
+    }
+}
+
+/// A ready-to-edit arguments object for the tool: every declared parameter
+/// with its default, first enum value or a placeholder (see
+/// [`crate::mcp::schema::example`]).
+pub fn example_arguments(
+    tool_obj: &serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let schema = input_schema(tool_obj)
+        .map(|s| serde_json::Value::Object(s.clone()))
+        .unwrap_or_default();
+    match crate::mcp::schema::example(&schema) {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/* ---- Annotations ---- */
+
+/// Behaviour hints of a tool's `annotations` and their compact flag.
+pub const ANNOTATION_HINTS: &[(&str, &str)] = &[
+    ("readOnlyHint", "ro"),
+    ("destructiveHint", "destructive"),
+    ("idempotentHint", "idempotent"),
+    ("openWorldHint", "open-world"),
+];
+
+/// Flags of the hints the tool sets to true, e.g. `["ro", "idempotent"]`.
+/// Unset hints are left out (the server did not claim anything).
+pub fn annotation_flags(tool: &serde_json::Value) -> Vec<&'static str> {
+    let Some(ann) = tool.get("annotations") else {
+        return Vec::new();
+    };
+    ANNOTATION_HINTS
+        .iter()
+        .filter(|(key, _)| ann.get(key).and_then(|v| v.as_bool()) == Some(true))
+        .map(|(_, flag)| *flag)
+        .collect()
+}
+
+/// `annotations` as one line: title first, then every hint with its value.
+pub fn annotation_summary(tool: &serde_json::Value) -> Option<String> {
+    let ann = tool.get("annotations")?.as_object()?;
+    let mut parts = Vec::new();
+    if let Some(title) = ann.get("title").and_then(|v| v.as_str()) {
+        parts.push(format!("title={title:?}"));
+    }
+    for (key, _) in ANNOTATION_HINTS {
+        if let Some(v) = ann.get(*key) {
+            parts.push(format!("{key}={v}"));
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
 /* ---- Result Summarization ---- */
 
 /// Convert a `CallToolResult` into JSON for summarization.
@@ -267,6 +666,9 @@ mod tests {
             json!(["a", "b", "c"]),
             "comma splitting with trimming"
         );
+        assert_eq!(coerce_value("[1, \"x\"]", "array"), json!([1, "x"]));
+        assert_eq!(coerce_value("{\"a\": 1}", "object"), json!({"a": 1}));
+        assert_eq!(coerce_value("{\"a\": 1}", "array"), json!(["{\"a\": 1}"]));
     }
 
     #[test]
@@ -329,10 +731,151 @@ mod tests {
         assert!(list.is_empty());
     }
 
+    #[test]
+    fn annotation_flags_and_summary() {
+        let t = json!({"name": "rm", "annotations": {
+            "title": "Remove", "readOnlyHint": false, "destructiveHint": true, "openWorldHint": true
+        }});
+        assert_eq!(annotation_flags(&t), vec!["destructive", "open-world"]);
+        assert_eq!(
+            annotation_summary(&t).unwrap(),
+            "title=\"Remove\", readOnlyHint=false, destructiveHint=true, openWorldHint=true"
+        );
+        assert!(annotation_flags(&json!({"name": "x"})).is_empty());
+        assert!(annotation_summary(&json!({"name": "x", "annotations": {}})).is_none());
+    }
+
     #[test]
     fn find_tool_case_insensitive_works() {
         let val = json!({"tools":[{"name":"Alpha"},{"name":"beta"}]});
         let t = find_tool_case_insensitive(&val, "ALPHA").unwrap();
         assert_eq!(t.get("name").and_then(|v| v.as_str()), Some("Alpha"));
     }
+
+    #[test]
+    fn validate_reports_schema_problems() {
+        let tool_obj = json!({
+            "name":"demo",
+            "inputSchema":{
+                "type":"object",
+                "required":["id","mode"],
+                "additionalProperties": false,
+                "properties":{
+                    "id":{"type":"integer"},
+                    "mode":{"type":"string","enum":["ro","rw"]},
+                    "note":{"type":["string","null"]}
+                }
+            }
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+        let mut provided = std::collections::HashMap::new();
+        provided.insert("id".into(), "x1".into());
+        provided.insert("extra".into(), "1".into());
+        let args = coerce_arguments(&tool_obj, &provided).unwrap();
+        assert_eq!(
+            validate_arguments(&tool_obj, &args),
+            vec![
+                "$.mode: required property missing",
+                "$.extra: property not declared by the schema",
+                "$.id: expected integer, got string",
+            ]
+        );
+        assert!(build_arguments_from_schema(&tool_obj, &provided).is_err());
+
+        let ok = json!({"id": 3, "mode": "ro", "note": null});
+        assert!(validate_arguments(&tool_obj, ok.as_object().unwrap()).is_empty());
+        let bad = json!({"id": 3, "mode": "all"});
+        assert_eq!(
+            validate_arguments(&tool_obj, bad.as_object().unwrap()),
+            vec!["$.mode: \"all\" is not an allowed value"]
+        );
+    }
+
+    #[test]
+    fn build_nested_arguments_from_dotted_keys() {
+        let tool_obj = json!({
+            "name": "search",
+            "inputSchema": {
+                "type": "object",
+                "required": ["filter"],
+                "properties": {
+                    "filter": {"type": "object", "properties": {
+                        "status": {"type": "string"},
+                        "limit": {"type": "integer"},
+                        "tags": {"type": "array", "items": {"type": "string"}},
+                        "range": {"type": "array", "items": {"type": "object", "properties": {
+                            "min": {"type": "number"}
+                        }}}
+                    }},
+                    "a.b": {"type": "boolean"}
+                }
+            }
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+        let provided: std::collections::HashMap<String, String> = [
+            ("filter.status", "open"),
+            ("filter.limit", "5"),
+            ("filter.tags[1]", "ui"),
+            ("filter.tags[0]", "bug"),
+            ("filter.range[0].min", "1.5"),
+            ("a.b", "yes"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let args = build_arguments_from_schema(&tool_obj, &provided).unwrap();
+        assert_eq!(
+            serde_json::Value::Object(args),
+            json!({
+                "filter": {"status": "open", "limit": 5, "tags": ["bug", "ui"], "range": [{"min": 1.5}]},
+                "a.b": true
+            })
+        );
+
+        let base = json!({"filter": {"status": "closed", "owner": "me"}});
+        let provided = [("filter.status".to_string(), "open".to_string())].into();
+        let merged =
+            merge_arguments(&tool_obj, base.as_object().cloned().unwrap(), &provided).unwrap();
+        assert_eq!(merged["filter"], json!({"status": "open", "owner": "me"}));
+
+        let conflict = [
+            ("filter".to_string(), "x".to_string()),
+            ("filter.status".to_string(), "open".to_string()),
+        ]
+        .into();
+        assert!(build_arguments_from_schema(&tool_obj, &conflict).is_err());
+        assert!(parse_param_path("a..b").is_err());
+        assert!(parse_param_path("a[x]").is_err());
+        assert_eq!(param_root("filter.tags[0]"), "filter");
+    }
+
+    #[test]
+    fn reports_undeclared_arguments() {
+        let tool_obj = json!({"name": "fetch", "inputSchema": {"type": "object", "properties": {
+            "url": {"type": "string"},
+            "headers": {"type": "object"},
+            "opts": {"type": "object", "properties": {"retries": {"type": "integer"}}},
+            "rules": {"type": "array", "items": {"type": "object", "properties": {"id": {}}}}
+        }}})
+        .as_object()
+        .cloned()
+        .unwrap();
+        let args = json!({
+            "urll": "x", "zzz": 1, "headers": {"X-Any": "1"},
+            "opts": {"retires": 2}, "rules": [{"id": 1}, {"idd": 2}]
+        });
+        assert_eq!(
+            undeclared_arguments(&tool_obj, args.as_object().unwrap()),
+            vec![
+                "$.opts.retires (did you mean 'retries'?)",
+                "$.rules[1].idd (did you mean 'id'?)",
+                "$.urll (did you mean 'url'?)",
+                "$.zzz",
+            ]
+        );
+    }
 }
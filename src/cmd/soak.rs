@@ -0,0 +1,341 @@
+/*!
+soak.rs - soak subcommand.
+
+Keeps one session open for --duration seconds, sending a steady workload
+(tools/list by default, or calls to --tool with --param values) while
+sampling the server process tree's resident memory and open descriptors
+every --interval milliseconds (see crate::scan::growth). Steady growth is
+reported as a leak finding with the growth curve as evidence.
+
+Sampling reads /proc and is Linux-only.
+
+Reports:
+  --report PATH writes a Markdown (.md) or HTML (.html) report with the
+  findings and the RSS / descriptor curves (same renderer as `scan`).
+
+Example:
+  mcp-hack soak -t "python3 server.py" --duration 300
+  mcp-hack soak -t "python3 server.py" --tool search --param query=test --report soak.html
+  mcp-hack soak -t "npx -y some-mcp-server" --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use rmcp::ServiceError;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::cmd::shared::placeholder_arguments;
+use crate::mcp::interrupt::Watch;
+use crate::mcp::session::McpSession;
+use crate::mcp::{self, TargetSpec, limits};
+use crate::scan::growth::{GrowthTracker, sparkline};
+use crate::scan::report::{self, ReportFormat};
+use crate::scan::{Finding, Severity};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct SoakArgs {
+    /// Seconds to keep the session busy
+    #[arg(long, value_name = "SECS", default_value_t = 60)]
+    pub duration: u64,
+
+    /// Milliseconds between resource samples
+    #[arg(long, value_name = "MS", default_value_t = 1000)]
+    pub interval: u64,
+
+    /// Tool to call repeatedly (default workload: tools/list)
+    #[arg(long, value_name = "NAME")]
+    pub tool: Option<String>,
+
+    /// Tool parameter (KEY=VALUE), repeatable; other required params get placeholders
+    #[arg(long = "param", value_name = "KEY=VALUE", requires = "tool")]
+    pub params: Vec<String>,
+
+    /// Write a Markdown / HTML report (format from the .md / .html extension)
+    #[arg(long, value_name = "PATH")]
+    pub report: Option<String>,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_soak(mut args: SoakArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(
+            args.output.is_json(),
+            "remote soak testing not implemented yet",
+        );
+    }
+    let report_format = match args.report.as_deref().map(ReportFormat::from_path) {
+        Some(Ok(f)) => Some(f),
+        Some(Err(e)) => return report_error(args.output.is_json(), e),
+        None => None,
+    };
+    let mut provided = HashMap::new();
+    for kv in &args.params {
+        match kv.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => {
+                provided.insert(k.trim().to_string(), v.to_string());
+            }
+            _ => {
+                return usage_error(
+                    args.output.is_json(),
+                    &format!("invalid --param (expected KEY=VALUE): {kv}"),
+                );
+            }
+        }
+    }
+
+    let run = match soak(&spec, &args, &provided).await {
+        Ok(r) => r,
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    crate::utils::progress_events::phase("done", None);
+
+    let findings = run.growth.findings();
+    let mut summary = serde_json::Map::new();
+    for s in Severity::ALL {
+        summary.insert(
+            s.as_str().into(),
+            json!(findings.iter().filter(|f| f.severity == s).count()),
+        );
+    }
+    let out = json!({
+        "status": "ok",
+        "kind": "soak",
+        "target": target_raw,
+        "elapsed_ms": run.elapsed_ms,
+        "timings": crate::mcp::timing::snapshot().to_json(),
+        "workload": args.tool.as_deref().map_or("tools/list".to_string(), |t| format!("tools/call {t}")),
+        "requests": run.requests,
+        "errors": run.errors,
+        "sampling": run.sampling,
+        "growth": run.growth.to_json(),
+        "summary": summary,
+        "count": findings.len(),
+        "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+    });
+    if let (Some(format), Some(path)) = (report_format, &args.report)
+        && let Err(e) = std::fs::write(path, report::render(format, &out))
+    {
+        return output_error(
+            args.output.is_json(),
+            &format!("failed to write report {path}: {e}"),
+        );
+    }
+
+    if args.output.is_json() {
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Soak • {} finding(s)",
+            emoji("spark", &style),
+            findings.len()
+        ),
+        Some(format!("target={target_raw} • {} ms", run.elapsed_ms)),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} {} request(s) • {} error(s) • {} sample(s)",
+        emoji("info", &style),
+        run.requests,
+        run.errors,
+        run.growth.samples.len()
+    );
+    if !run.sampling {
+        println!(
+            "{} {}",
+            emoji("warn", &style),
+            color(
+                Role::Warning,
+                "Resource sampling unavailable (needs Linux /proc)",
+                &style
+            )
+        );
+        return Ok(());
+    }
+    let samples = &run.growth.samples;
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        let rss: Vec<u64> = samples.iter().map(|s| s.rss_kb).collect();
+        let fds: Vec<u64> = samples.iter().map(|s| s.fds).collect();
+        println!(
+            "  RSS {} {} KiB -> {} KiB",
+            sparkline(&rss),
+            first.rss_kb,
+            last.rss_kb
+        );
+        println!("  FDs {} {} -> {}", sparkline(&fds), first.fds, last.fds);
+    }
+    println!();
+    if findings.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(Role::Success, "No steady resource growth", &style)
+        );
+    }
+    for f in &findings {
+        println!(
+            "{} {} {}",
+            emoji("warn", &style),
+            color(Role::Warning, f.severity.as_str().to_uppercase(), &style),
+            f.title
+        );
+    }
+    if let Some(path) = &args.report {
+        println!(
+            "{} {}",
+            emoji("info", &style),
+            color(Role::Dim, format!("Wrote report to {path}"), &style)
+        );
+    }
+    Ok(())
+}
+
+/* ---- Helpers ---- */
+
+struct SoakRun {
+    growth: GrowthTracker,
+    requests: u64,
+    errors: u64,
+    sampling: bool,
+    elapsed_ms: u128,
+}
+
+async fn soak(
+    spec: &TargetSpec,
+    args: &SoakArgs,
+    provided: &HashMap<String, String>,
+) -> Result<SoakRun> {
+    let session = McpSession::connect(spec, ()).await?;
+    // None: tools/list.
+    let call = match &args.tool {
+        Some(name) => {
+            let tools = session.list_tools().await?;
+            let Some(tool) = tools.iter().find(|t| t["name"].as_str() == Some(name)) else {
+                return Err(exit::tool_not_found(name));
+            };
+            let Value::Object(arguments) = placeholder_arguments(tool, provided)? else {
+                anyhow::bail!("malformed tool definition");
+            };
+            Some((name.as_str(), arguments))
+        }
+        None => None,
+    };
+    let wait = Duration::from_secs(args.timeout);
+
+    crate::utils::progress_events::phase("soak", None);
+    let started = Instant::now();
+    let duration = Duration::from_secs(args.duration);
+    let interval = Duration::from_millis(args.interval.max(50));
+    let mut run = SoakRun {
+        growth: GrowthTracker::default(),
+        requests: 0,
+        errors: 0,
+        sampling: true,
+        elapsed_ms: 0,
+    };
+    let mut next_sample = Instant::now();
+    while started.elapsed() < duration {
+        if Instant::now() >= next_sample {
+            if let Some(pid) = session.pid()
+                && run.sampling
+            {
+                run.sampling = run.growth.record(pid);
+            }
+            next_sample += interval;
+        }
+        let outcome = match &call {
+            Some((name, arguments)) => {
+                let interrupt = Watch::default();
+                let fut = session.call_tool(name, arguments.clone(), &interrupt);
+                within(wait, fut).await.and_then(|r| r).map(|r| r.map(drop))
+            }
+            None => {
+                let fut = limits::request("tools/list", session.peer().list_tools(None));
+                within(wait, fut).await.and_then(|r| r).map(|r| r.map(drop))
+            }
+        };
+        run.requests += 1;
+        match outcome {
+            Ok(Ok(())) => {}
+            Ok(Err(ServiceError::McpError(_))) => run.errors += 1,
+            Ok(Err(e)) => {
+                crate::utils::logging::debug(format!("soak request failed: {e}"));
+                run.errors += 1;
+                break;
+            }
+            Err(e) => {
+                // Timeouts / a dead server end the run; what was sampled stands.
+                crate::utils::logging::debug(format!("soak request failed: {e:#}"));
+                run.errors += 1;
+                break;
+            }
+        }
+    }
+    if run.sampling
+        && let Some(pid) = session.pid()
+    {
+        run.growth.record(pid);
+    }
+    run.elapsed_ms = started.elapsed().as_millis();
+    session.shutdown().await;
+    Ok(run)
+}
+
+/// `fut` bounded by --timeout.
+async fn within<F: Future>(wait: Duration, fut: F) -> Result<F::Output> {
+    tokio::time::timeout(wait, fut)
+        .await
+        .map_err(|_| anyhow::anyhow!("no response within {}s (--timeout)", wait.as_secs()))
+}
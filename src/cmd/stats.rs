@@ -0,0 +1,184 @@
+/*!
+stats.rs - stats subcommand.
+
+Summary numbers for an engagement: targets assessed, tools inventoried,
+calls made, findings by severity, busiest tools and average latencies (see
+crate::scan::stats). The workspace is whatever the PATHs hold (default:
+the current directory, searched recursively):
+  - *.mcpt   transcripts written with --record
+  - *.json   saved --json outputs (scan, canary, pollution, list, ...)
+
+Other files and JSON documents that are not command outputs are skipped.
+
+Example:
+  mcp-hack --record engagement/scan-1.mcpt scan -t "python3 server.py"
+  mcp-hack stats engagement/
+  mcp-hack stats engagement/ --top 10 --json
+*/
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::cmd::exec::output_error;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::mcp::record;
+use crate::scan::Severity;
+use crate::scan::stats::Stats;
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Files or directories holding transcripts and saved JSON outputs
+    #[arg(value_name = "PATH", default_value = ".")]
+    pub paths: Vec<String>,
+
+    /// Number of busiest tools to list
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    pub top: usize,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/// Transcript and JSON files under `path` (hidden directories skipped).
+pub(crate) fn collect_files(path: &Path, out: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        out.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    for p in entries {
+        let hidden = p
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        if p.is_dir() && !hidden {
+            collect_files(&p, out);
+        } else if matches!(
+            p.extension().and_then(|e| e.to_str()),
+            Some("mcpt" | "json")
+        ) {
+            out.push(p);
+        }
+    }
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_stats(args: StatsArgs) -> Result<()> {
+    let mut files = Vec::new();
+    for p in &args.paths {
+        let path = Path::new(p);
+        if !path.exists() {
+            return output_error(
+                args.output.is_json(),
+                &format!("{p}: no such file or directory"),
+            );
+        }
+        collect_files(path, &mut files);
+    }
+
+    let mut stats = Stats::default();
+    let mut skipped = 0u64;
+    for file in &files {
+        let Ok(text) = std::fs::read_to_string(file) else {
+            skipped += 1;
+            continue;
+        };
+        let counted = if file.extension().and_then(|e| e.to_str()) == Some("mcpt") {
+            record::parse(&text)
+                .map(|t| stats.add_transcript(&t))
+                .is_ok()
+        } else {
+            serde_json::from_str::<Value>(&text).is_ok_and(|doc| stats.add_report(&doc))
+        };
+        if !counted {
+            skipped += 1;
+        }
+    }
+
+    if args.output.is_json() {
+        let mut out = stats.to_json(args.top);
+        out["status"] = "ok".into();
+        out["files"]["skipped"] = skipped.into();
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!("{} Stats", emoji("list", &style)),
+        Some(format!(
+            "{} transcript(s) • {} report(s) • {skipped} skipped",
+            stats.transcripts, stats.reports
+        )),
+        &style,
+    );
+    println!("{header}");
+    let avg = stats
+        .avg_call_ms()
+        .map_or("-".to_string(), |ms| format!("{ms} ms"));
+    for (label, value) in [
+        ("targets assessed", stats.targets.len().to_string()),
+        ("tools inventoried", stats.tools.len().to_string()),
+        ("requests", stats.requests.to_string()),
+        ("tool calls", stats.total_calls().to_string()),
+        ("avg call latency", avg),
+    ] {
+        println!("  {:<18} {}", color(Role::Dim, label, &style), value);
+    }
+    let findings: Vec<String> = Severity::ALL
+        .iter()
+        .map(|s| {
+            format!(
+                "{} {}",
+                s.as_str(),
+                stats.findings.get(s).copied().unwrap_or(0)
+            )
+        })
+        .collect();
+    println!(
+        "  {:<18} {}",
+        color(Role::Dim, "findings", &style),
+        findings.join(" • ")
+    );
+
+    let busiest = stats.busiest(args.top);
+    if !busiest.is_empty() {
+        println!();
+        let rows: Vec<Vec<String>> = busiest
+            .iter()
+            .map(|(name, c)| {
+                vec![
+                    name.to_string(),
+                    c.calls.to_string(),
+                    c.errors.to_string(),
+                    c.avg_ms().map_or("-".into(), |ms| ms.to_string()),
+                ]
+            })
+            .collect();
+        let tbl = table(
+            &["TOOL", "CALLS", "ERRORS", "AVG MS"],
+            &rows,
+            TableOpts {
+                max_width: style.term_width,
+                ..Default::default()
+            },
+            &style,
+        );
+        println!("{tbl}");
+    }
+    Ok(())
+}
@@ -0,0 +1,792 @@
+/*!
+stress.rs - stress subcommand.
+
+Resilience checks against a local server. After a baseline (one initialize,
+then five sequential probes) each check runs on fresh sessions:
+  notifications  one session flooded with --notifications client
+                 notifications (progress / cancelled for unknown requests),
+                 then one probe
+  sessions       --sessions servers spawned and initialized at once, one
+                 probe each
+  reconnect      --cycles rapid spawn / initialize / disconnect cycles
+  calls          --concurrency workers sending --calls probes each over
+                 one session
+The probe is tools/list, or tools/call --tool (with --param values; other
+required params get placeholders, like soak).
+
+A check is reported when the server
+  crashed       exited or closed the connection                 high
+  erred         error responses, timeouts (--timeout)           medium
+  slowed down   median latency above --slowdown x the baseline  low
+                (and by at least 50 ms, so sub-millisecond
+                baselines do not flag noise)
+sessions and reconnect are compared with the baseline initialize time, the
+other checks with the baseline probe latency.
+
+Only point this at servers you run yourself: every check spawns the target
+locally, the sessions check many times at once.
+
+Example:
+  mcp-hack stress -t "python3 server.py"
+  mcp-hack stress -t "python3 server.py" --checks calls --tool search --param q=x --concurrency 32
+  mcp-hack stress -t "node server.js" --checks notifications,reconnect --slowdown 3x --json
+
+Sessions use raw JSON-RPC (crate::mcp::raw): the flood sends notifications
+the typed client cannot, and a crash is told from an error reply by
+whether the server process exited.
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result, bail};
+use clap::Args;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::cmd::exec::{report_error, usage_error};
+use crate::cmd::exit;
+use crate::cmd::format::{Role, StyleOptions, TableOpts, box_header, color, emoji, table};
+use crate::cmd::fuzz::parse_multiplier;
+use crate::cmd::output::{OutputFormat, print_json};
+use crate::cmd::shared::placeholder_arguments;
+use crate::mcp::raw::RawSession;
+use crate::mcp::{self, TargetSpec};
+use crate::scan::{Finding, Severity, list_all};
+
+const BASELINE_SAMPLES: usize = 5;
+/// A slowdown must also exceed the baseline by this much.
+const MIN_SLOWDOWN_MS: f64 = 50.0;
+/// How long a failed session's server gets to exit before it counts as alive.
+const EXIT_WAIT: Duration = Duration::from_millis(200);
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Json];
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StressCheck {
+    /// Flood one session with notifications
+    Notifications,
+    /// Many sessions at once
+    Sessions,
+    /// Rapid connect / disconnect cycles
+    Reconnect,
+    /// Parallel tool calls over one session
+    Calls,
+}
+
+impl StressCheck {
+    pub const ALL: [StressCheck; 4] = [
+        StressCheck::Notifications,
+        StressCheck::Sessions,
+        StressCheck::Reconnect,
+        StressCheck::Calls,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            StressCheck::Notifications => "notifications",
+            StressCheck::Sessions => "sessions",
+            StressCheck::Reconnect => "reconnect",
+            StressCheck::Calls => "calls",
+        }
+    }
+
+    /// Finding check id.
+    fn id(self) -> &'static str {
+        match self {
+            StressCheck::Notifications => "stress-notifications",
+            StressCheck::Sessions => "stress-sessions",
+            StressCheck::Reconnect => "stress-reconnect",
+            StressCheck::Calls => "stress-calls",
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            StressCheck::Notifications => "a notification flood",
+            StressCheck::Sessions => "concurrent sessions",
+            StressCheck::Reconnect => "rapid reconnects",
+            StressCheck::Calls => "parallel calls",
+        }
+    }
+
+    /// Measured by initialize time rather than probe latency.
+    fn connect_bound(self) -> bool {
+        matches!(self, StressCheck::Sessions | StressCheck::Reconnect)
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct StressArgs {
+    /// Checks to run, comma-separated (default: all)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "CHECK")]
+    pub checks: Vec<StressCheck>,
+
+    /// Probe with tools/call of this tool instead of tools/list
+    #[arg(long, value_name = "NAME")]
+    pub tool: Option<String>,
+
+    /// Tool parameter (KEY=VALUE), repeatable; other required params get placeholders
+    #[arg(long = "param", value_name = "KEY=VALUE", requires = "tool")]
+    pub params: Vec<String>,
+
+    /// Notifications sent by the notifications check
+    #[arg(long, value_name = "N", default_value_t = 5000)]
+    pub notifications: u64,
+
+    /// Sessions opened at once by the sessions check
+    #[arg(long, value_name = "N", default_value_t = 16)]
+    pub sessions: u64,
+
+    /// Connect / disconnect cycles of the reconnect check
+    #[arg(long, value_name = "N", default_value_t = 30)]
+    pub cycles: u64,
+
+    /// Parallel workers of the calls check
+    #[arg(long, value_name = "N", default_value_t = 16, value_parser = clap::value_parser!(u64).range(1..))]
+    pub concurrency: u64,
+
+    /// Probes per worker of the calls check
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    pub calls: u64,
+
+    /// Report a check whose median latency exceeds the baseline this many times (e.g. 5x)
+    #[arg(long, value_name = "MULTIPLE", default_value = "5x", value_parser = parse_multiplier)]
+    pub slowdown: f64,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_stress(mut args: StressArgs) -> Result<()> {
+    let json = args.output.is_json();
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => return usage_error(json, "no target specified (use --target or MCP_TARGET)"),
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(json, "remote stress testing not implemented yet");
+    }
+    let mut provided = HashMap::new();
+    for kv in &args.params {
+        match kv.split_once('=') {
+            Some((k, v)) if !k.trim().is_empty() => {
+                provided.insert(k.trim().to_string(), v.to_string());
+            }
+            _ => {
+                return usage_error(json, &format!("invalid --param (expected KEY=VALUE): {kv}"));
+            }
+        }
+    }
+    let checks: Vec<StressCheck> = if args.checks.is_empty() {
+        StressCheck::ALL.to_vec()
+    } else {
+        StressCheck::ALL
+            .into_iter()
+            .filter(|c| args.checks.contains(c))
+            .collect()
+    };
+
+    let timeout = Duration::from_secs(args.timeout);
+    let (probe, baseline) = match baseline(&spec, timeout, &args, &provided).await {
+        Ok(b) => b,
+        Err(e) => return report_error(json, e.context("baseline failed")),
+    };
+    let mut results = Vec::new();
+    for check in checks {
+        crate::utils::progress_events::phase("stress", Some(check.name()));
+        let started = Instant::now();
+        let mut result = run_check(check, &spec, timeout, &probe, &args).await;
+        result.elapsed_ms = started.elapsed().as_millis();
+        results.push((check, result));
+    }
+    crate::utils::progress_events::phase("done", None);
+
+    let mut findings: Vec<Finding> = results
+        .iter()
+        .flat_map(|(check, r)| evaluate(*check, r, &baseline, args.slowdown))
+        .collect();
+    findings.sort_by(Finding::report_order);
+    let mut summary = serde_json::Map::new();
+    for s in Severity::ALL {
+        summary.insert(
+            s.as_str().into(),
+            json!(findings.iter().filter(|f| f.severity == s).count()),
+        );
+    }
+
+    if json {
+        let out = json!({
+            "status": "ok",
+            "kind": "stress",
+            "target": target_raw,
+            "probe": probe.label(),
+            "baseline": {"connect_ms": round1(baseline.connect_ms), "probe_ms": round1(baseline.probe_ms)},
+            "slowdown": args.slowdown,
+            "checks": results.iter().map(|(check, r)| r.to_json(*check)).collect::<Vec<_>>(),
+            "summary": summary,
+            "count": findings.len(),
+            "findings": findings.iter().map(Finding::to_json).collect::<Vec<_>>(),
+        });
+        print_json(&out);
+        return Ok(());
+    }
+
+    let style = StyleOptions::detect();
+    let header = box_header(
+        format!(
+            "{} Stress • {} finding(s)",
+            emoji("spark", &style),
+            findings.len()
+        ),
+        Some(format!("target={target_raw} • probe={}", probe.label())),
+        &style,
+    );
+    println!("{header}");
+    println!(
+        "{} baseline: initialize {:.1} ms • probe {:.1} ms • slowdown threshold {}x",
+        emoji("info", &style),
+        baseline.connect_ms,
+        baseline.probe_ms,
+        args.slowdown
+    );
+    let ms = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{v:.1}"));
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|(check, r)| {
+            vec![
+                check.name().to_string(),
+                r.requests.to_string(),
+                r.errors.to_string(),
+                if r.crashed {
+                    color(Role::Error, "yes", &style)
+                } else {
+                    "no".to_string()
+                },
+                ms(r.median_ms()),
+                ms(r.max_ms()),
+            ]
+        })
+        .collect();
+    let tbl = table(
+        &[
+            "CHECK",
+            "REQUESTS",
+            "ERRORS",
+            "CRASHED",
+            "MEDIAN MS",
+            "MAX MS",
+        ],
+        &rows,
+        TableOpts {
+            max_width: style.term_width,
+            ..Default::default()
+        },
+        &style,
+    );
+    println!("{tbl}");
+    println!();
+    if findings.is_empty() {
+        println!(
+            "{} {}",
+            emoji("success", &style),
+            color(
+                Role::Success,
+                "No crashes, errors or slowdowns under stress",
+                &style
+            )
+        );
+    }
+    for f in &findings {
+        println!(
+            "{} {} {} {}",
+            emoji("warn", &style),
+            color(Role::Warning, f.severity.as_str().to_uppercase(), &style),
+            f.title,
+            color(Role::Dim, f.evidence.as_deref().unwrap_or(""), &style)
+        );
+    }
+    Ok(())
+}
+
+/* ---- Helpers ---- */
+
+/// The request timed by every check.
+#[derive(Debug, Clone)]
+struct Probe {
+    method: &'static str,
+    params: Option<Value>,
+}
+
+impl Probe {
+    fn label(&self) -> String {
+        match self.params.as_ref().and_then(|p| p["name"].as_str()) {
+            Some(tool) => format!("{} {tool}", self.method),
+            None => self.method.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Baseline {
+    connect_ms: f64,
+    probe_ms: f64,
+}
+
+#[derive(Debug, Default)]
+struct CheckResult {
+    requests: u64,
+    errors: u64,
+    crashed: bool,
+    latencies_ms: Vec<f64>,
+    first_error: Option<String>,
+    elapsed_ms: u128,
+}
+
+impl CheckResult {
+    fn error(&mut self, message: String) {
+        self.errors += 1;
+        self.first_error.get_or_insert(message);
+    }
+
+    fn merge(&mut self, other: CheckResult) {
+        self.requests += other.requests;
+        self.errors += other.errors;
+        self.crashed |= other.crashed;
+        self.latencies_ms.extend(other.latencies_ms);
+        if self.first_error.is_none() {
+            self.first_error = other.first_error;
+        }
+    }
+
+    fn median_ms(&self) -> Option<f64> {
+        let mut sorted = self.latencies_ms.clone();
+        sorted.sort_by(f64::total_cmp);
+        sorted.get(sorted.len() / 2).copied()
+    }
+
+    fn max_ms(&self) -> Option<f64> {
+        self.latencies_ms.iter().copied().reduce(f64::max)
+    }
+
+    fn to_json(&self, check: StressCheck) -> Value {
+        json!({
+            "check": check.name(),
+            "requests": self.requests,
+            "errors": self.errors,
+            "crashed": self.crashed,
+            "median_ms": self.median_ms().map(round1),
+            "max_ms": self.max_ms().map(round1),
+            "elapsed_ms": self.elapsed_ms,
+            "first_error": self.first_error,
+        })
+    }
+}
+
+fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+fn ms_since(t: Instant) -> f64 {
+    t.elapsed().as_secs_f64() * 1000.0
+}
+
+/// Findings for one check (see module docs).
+fn evaluate(
+    check: StressCheck,
+    r: &CheckResult,
+    baseline: &Baseline,
+    slowdown: f64,
+) -> Vec<Finding> {
+    let mut out = Vec::new();
+    if r.crashed {
+        out.push(
+            Finding::new(
+                check.id(),
+                Severity::High,
+                format!("Server crashed under {}", check.describe()),
+            )
+            .detail(
+                "The server exited or closed the connection while being stressed; a client \
+                 (or a prompt-injected agent) can take it down the same way.",
+            )
+            .evidence(r.first_error.clone().unwrap_or_default()),
+        );
+    } else if r.errors > 0 {
+        out.push(
+            Finding::new(
+                check.id(),
+                Severity::Medium,
+                format!(
+                    "Server failed {} of {} request(s) under {}",
+                    r.errors,
+                    r.requests,
+                    check.describe()
+                ),
+            )
+            .detail("The server answered with errors or not at all while being stressed.")
+            .evidence(r.first_error.clone().unwrap_or_default()),
+        );
+    }
+    let reference = if check.connect_bound() {
+        baseline.connect_ms
+    } else {
+        baseline.probe_ms
+    };
+    if let Some(median) = r.median_ms()
+        && median > reference * slowdown
+        && median - reference >= MIN_SLOWDOWN_MS
+    {
+        out.push(
+            Finding::new(
+                check.id(),
+                Severity::Low,
+                format!("Server slowed down under {}", check.describe()),
+            )
+            .detail("Median latency rose well above the baseline while being stressed.")
+            .evidence(format!(
+                "median {median:.1} ms vs baseline {reference:.1} ms ({:.1}x)",
+                median / reference.max(0.001)
+            )),
+        );
+    }
+    out
+}
+
+/// Resolve the probe and time the baseline on a quiet session.
+async fn baseline(
+    spec: &TargetSpec,
+    timeout: Duration,
+    args: &StressArgs,
+    provided: &HashMap<String, String>,
+) -> Result<(Probe, Baseline)> {
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    let started = Instant::now();
+    let mut session = RawSession::spawn(spec, timeout)?;
+    session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    let connect_ms = ms_since(started);
+    let probe = match &args.tool {
+        Some(name) => {
+            let tools = list_all(&mut session, "tools/list", "tools", usize::MAX).await?;
+            let Some(tool) = tools.iter().find(|t| t["name"].as_str() == Some(name)) else {
+                return Err(exit::tool_not_found(name));
+            };
+            Probe {
+                method: "tools/call",
+                params: Some(json!({
+                    "name": name,
+                    "arguments": placeholder_arguments(tool, provided)?,
+                })),
+            }
+        }
+        None => Probe {
+            method: "tools/list",
+            params: None,
+        },
+    };
+    let mut samples = CheckResult::default();
+    for _ in 0..BASELINE_SAMPLES {
+        if let Some(ms) = probe_once(&mut session, &probe, &mut samples).await {
+            samples.latencies_ms.push(ms);
+        }
+    }
+    session.shutdown().await;
+    if let Some(e) = samples.first_error {
+        bail!("{} failed: {e}", probe.label());
+    }
+    let probe_ms = samples.median_ms().unwrap_or_default();
+    Ok((
+        probe,
+        Baseline {
+            connect_ms,
+            probe_ms,
+        },
+    ))
+}
+
+/// Send the probe; its latency when it succeeded (failures go to `r`).
+async fn probe_once(session: &mut RawSession, probe: &Probe, r: &mut CheckResult) -> Option<f64> {
+    r.requests += 1;
+    let sent = Instant::now();
+    let outcome = session.request(probe.method, probe.params.clone()).await;
+    session.interleaved.clear();
+    match outcome {
+        Ok(resp) => match resp.get("error") {
+            Some(err) => {
+                r.error(format!("error response: {err}"));
+                None
+            }
+            None => Some(ms_since(sent)),
+        },
+        Err(e) => {
+            r.error(format!("{e:#}"));
+            r.crashed |= session.exited_within(EXIT_WAIT).await;
+            None
+        }
+    }
+}
+
+async fn run_check(
+    check: StressCheck,
+    spec: &TargetSpec,
+    timeout: Duration,
+    probe: &Probe,
+    args: &StressArgs,
+) -> CheckResult {
+    match check {
+        StressCheck::Notifications => flood(spec, timeout, probe, args.notifications).await,
+        StressCheck::Sessions => {
+            let mut set = tokio::task::JoinSet::new();
+            for _ in 0..args.sessions {
+                let (spec, probe) = (spec.clone(), probe.clone());
+                set.spawn(async move { connect_once(&spec, timeout, Some(&probe)).await });
+            }
+            let mut result = CheckResult::default();
+            while let Some(done) = set.join_next().await {
+                match done {
+                    Ok(r) => result.merge(r),
+                    Err(e) => result.error(format!("session task failed: {e}")),
+                }
+            }
+            result
+        }
+        StressCheck::Reconnect => {
+            let mut result = CheckResult::default();
+            for _ in 0..args.cycles {
+                result.merge(connect_once(spec, timeout, None).await);
+            }
+            result
+        }
+        StressCheck::Calls => parallel_calls(spec, timeout, probe, args).await,
+    }
+}
+
+/// Flood one session with notifications, then probe it.
+async fn flood(spec: &TargetSpec, timeout: Duration, probe: &Probe, count: u64) -> CheckResult {
+    let mut r = CheckResult::default();
+    let mut session = match RawSession::spawn(spec, timeout) {
+        Ok(s) => s,
+        Err(e) => {
+            r.error(format!("{e:#}"));
+            return r;
+        }
+    };
+    if let Err(e) = session.initialize().await {
+        r.error(format!("initialize: {e:#}"));
+        return r;
+    }
+    for i in 0..count {
+        let (method, params) = if i % 2 == 0 {
+            (
+                "notifications/progress",
+                json!({"progressToken": format!("mcp-hack-stress-{i}"), "progress": i}),
+            )
+        } else {
+            (
+                "notifications/cancelled",
+                json!({"requestId": 1_000_000 + i, "reason": "mcp-hack stress"}),
+            )
+        };
+        r.requests += 1;
+        if let Err(e) = session.notify(method, Some(params)).await {
+            r.error(format!("after {i} notification(s): {e:#}"));
+            r.crashed = true;
+            break;
+        }
+    }
+    if !r.crashed
+        && let Some(ms) = probe_once(&mut session, probe, &mut r).await
+    {
+        r.latencies_ms.push(ms);
+    }
+    session.shutdown().await;
+    r
+}
+
+/// Spawn and initialize one session (timed), probe it when asked, disconnect.
+async fn connect_once(spec: &TargetSpec, timeout: Duration, probe: Option<&Probe>) -> CheckResult {
+    let mut r = CheckResult {
+        requests: 1,
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let mut session = match RawSession::spawn(spec, timeout) {
+        Ok(s) => s,
+        Err(e) => {
+            r.error(format!("{e:#}"));
+            return r;
+        }
+    };
+    match session.initialize().await {
+        Ok(resp) if resp.get("error").is_none() => r.latencies_ms.push(ms_since(started)),
+        Ok(resp) => r.error(format!("initialize error: {}", resp["error"])),
+        Err(e) => {
+            r.error(format!("initialize: {e:#}"));
+            r.crashed |= session.exited_within(EXIT_WAIT).await;
+            return r;
+        }
+    }
+    if let Some(probe) = probe {
+        probe_once(&mut session, probe, &mut r).await;
+    }
+    session.shutdown().await;
+    r
+}
+
+/// --concurrency workers sending --calls probes each over one session.
+async fn parallel_calls(
+    spec: &TargetSpec,
+    timeout: Duration,
+    probe: &Probe,
+    args: &StressArgs,
+) -> CheckResult {
+    use rmcp::model::CallToolRequestParam;
+
+    let mut result = CheckResult::default();
+    let service = match crate::mcp::spawn::connect(spec, ()).await {
+        Ok(s) => s,
+        Err(e) => {
+            result.error(format!("{e:#}"));
+            return result;
+        }
+    };
+    let call: Option<CallToolRequestParam> = probe
+        .params
+        .clone()
+        .and_then(|p| serde_json::from_value(p).ok());
+
+    let mut set = tokio::task::JoinSet::new();
+    for _ in 0..args.concurrency {
+        let peer = service.peer().clone();
+        let call = call.clone();
+        let calls = args.calls;
+        set.spawn(async move {
+            let mut r = CheckResult::default();
+            for _ in 0..calls {
+                r.requests += 1;
+                let sent = Instant::now();
+                let outcome = match &call {
+                    Some(c) => tokio::time::timeout(timeout, peer.call_tool(c.clone()))
+                        .await
+                        .map(|res| res.map(|_| ())),
+                    None => tokio::time::timeout(timeout, peer.list_tools(Default::default()))
+                        .await
+                        .map(|res| res.map(|_| ())),
+                };
+                match outcome {
+                    Ok(Ok(())) => r.latencies_ms.push(ms_since(sent)),
+                    Ok(Err(rmcp::ServiceError::TransportClosed)) => {
+                        r.error("connection closed by the server".into());
+                        r.crashed = true;
+                        break;
+                    }
+                    Ok(Err(e)) => r.error(e.to_string()),
+                    Err(_) => r.error(format!("no response within {}s", timeout.as_secs())),
+                }
+            }
+            r
+        });
+    }
+    while let Some(done) = set.join_next().await {
+        match done {
+            Ok(r) => result.merge(r),
+            Err(e) => result.error(format!("worker failed: {e}")),
+        }
+    }
+    let _ = service.cancel().await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_stress_results() {
+        let baseline = Baseline {
+            connect_ms: 100.0,
+            probe_ms: 2.0,
+        };
+        let quiet = CheckResult {
+            requests: 10,
+            latencies_ms: vec![2.0, 3.0, 4.0],
+            ..Default::default()
+        };
+        assert!(evaluate(StressCheck::Calls, &quiet, &baseline, 5.0).is_empty());
+
+        // 5x a 2 ms baseline, but under the 50 ms floor: noise.
+        let jitter = CheckResult {
+            latencies_ms: vec![20.0, 30.0, 40.0],
+            ..Default::default()
+        };
+        assert!(evaluate(StressCheck::Calls, &jitter, &baseline, 5.0).is_empty());
+
+        let slow = CheckResult {
+            latencies_ms: vec![300.0, 400.0, 500.0],
+            ..Default::default()
+        };
+        let findings = evaluate(StressCheck::Calls, &slow, &baseline, 5.0);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Low);
+        assert_eq!(
+            findings[0].evidence.as_deref(),
+            Some("median 400.0 ms vs baseline 2.0 ms (200.0x)")
+        );
+        // Connect-bound checks compare with the initialize time.
+        assert!(evaluate(StressCheck::Sessions, &slow, &baseline, 5.0).is_empty());
+
+        let mut crashed = CheckResult {
+            requests: 2,
+            ..Default::default()
+        };
+        crashed.error("server closed stdout".into());
+        crashed.error("later".into());
+        crashed.crashed = true;
+        let findings = evaluate(StressCheck::Notifications, &crashed, &baseline, 5.0);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].check, "stress-notifications");
+        assert_eq!(
+            findings[0].evidence.as_deref(),
+            Some("server closed stdout")
+        );
+
+        crashed.crashed = false;
+        let findings = evaluate(StressCheck::Reconnect, &crashed, &baseline, 5.0);
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(
+            findings[0].title,
+            "Server failed 2 of 2 request(s) under rapid reconnects"
+        );
+    }
+}
@@ -0,0 +1,254 @@
+/*!
+watch.rs - watch subcommand.
+
+Continuous rug-pull monitoring: every --interval the target is spawned,
+its tools / resources / prompts are enumerated and hashed (see
+crate::scan::watch), and definitions that appeared, disappeared or changed
+since the previous poll are reported. The first poll is the baseline.
+Runs until interrupted, or for --count polls.
+
+A failed poll is reported and the previous baseline is kept, so a server
+that is briefly unavailable does not show up as every definition being
+removed and re-added.
+
+Output:
+  Human: one timestamped line per change.
+  --json: NDJSON, one event per line ("baseline", "change", "error").
+  --log PATH: the same NDJSON events appended to a file (alongside either
+  output mode).
+
+Example:
+  mcp-hack watch -t "python3 server.py" --interval 60s
+  mcp-hack watch -t "npx -y some-mcp-server" --interval 5m --log watch.ndjson
+  mcp-hack watch -t "python3 server.py" --interval 10s --count 6 --json
+
+Remote targets are not implemented yet.
+*/
+
+use anyhow::{Context, Result};
+use clap::Args;
+use serde_json::{Value, json};
+use std::io::Write;
+use std::time::Duration;
+
+use crate::cmd::exec::{output_error, report_error, usage_error};
+use crate::cmd::format::{Role, StyleOptions, box_header, color, emoji};
+use crate::cmd::output::{OutputFormat, print_line};
+use crate::mcp::{self, TargetSpec};
+use crate::scan::watch::{self, Change, Inventory};
+use crate::utils::{parse_duration, utc_timestamp};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports.
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text, OutputFormat::Ndjson];
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Time between polls (e.g. 30s, 5m, 1h; bare numbers are seconds)
+    #[arg(long, value_name = "DURATION", default_value = "60s")]
+    pub interval: String,
+
+    /// Stop after this many polls (0 = until interrupted)
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub count: u64,
+
+    /// Append NDJSON events to this file
+    #[arg(long, value_name = "PATH")]
+    pub log: Option<String>,
+
+    /// Seconds to wait for each server response
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub timeout: u64,
+
+    /// Target MCP endpoint (local command). Falls back to MCP_TARGET env.
+    #[arg(short = 't', long)]
+    pub target: Option<String>,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Public Entry Point ---- */
+
+pub async fn execute_watch(mut args: WatchArgs) -> Result<()> {
+    // Determine target (CLI > env)
+    if args.target.is_none()
+        && let Ok(env_t) = std::env::var("MCP_TARGET")
+        && !env_t.trim().is_empty()
+    {
+        args.target = Some(env_t);
+    }
+    let target_raw = match &args.target {
+        Some(t) if !t.trim().is_empty() => t.trim().to_string(),
+        _ => {
+            return usage_error(
+                args.output.is_json(),
+                "no target specified (use --target or MCP_TARGET)",
+            );
+        }
+    };
+    let spec = mcp::parse_target(&target_raw).with_context(|| {
+        format!(
+            "Failed to parse target: '{}'",
+            mcp::redact_target(&target_raw)
+        )
+    })?;
+    if !spec.is_local() {
+        return usage_error(args.output.is_json(), "remote watch not implemented yet");
+    }
+    let interval = match parse_duration(&args.interval) {
+        Ok(d) if !d.is_zero() => d,
+        Ok(_) => {
+            return usage_error(
+                args.output.is_json(),
+                "--interval must be greater than zero",
+            );
+        }
+        Err(e) => return report_error(args.output.is_json(), e),
+    };
+    let log = match &args.log {
+        Some(path) => match std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+        {
+            Ok(f) => Some(f),
+            Err(e) => {
+                return output_error(
+                    args.output.is_json(),
+                    &format!("failed to open log {path}: {e}"),
+                );
+            }
+        },
+        None => None,
+    };
+
+    let style = StyleOptions::detect();
+    if !args.output.is_json() {
+        let header = box_header(
+            format!("{} Watch", emoji("clock", &style)),
+            Some(format!("target={target_raw} • every {}", args.interval)),
+            &style,
+        );
+        println!("{header}");
+    }
+    let mut watcher = Watcher {
+        args: &args,
+        style,
+        log,
+    };
+    watcher.run(&spec, interval).await
+}
+
+/* ---- Helpers ---- */
+
+struct Watcher<'a> {
+    args: &'a WatchArgs,
+    style: StyleOptions,
+    log: Option<std::fs::File>,
+}
+
+impl Watcher<'_> {
+    async fn run(&mut self, spec: &TargetSpec, interval: Duration) -> Result<()> {
+        let timeout = Duration::from_secs(self.args.timeout);
+        let mut baseline: Option<Inventory> = None;
+        let mut poll = 0u64;
+        loop {
+            poll += 1;
+            crate::utils::progress_events::phase("poll", Some(&poll.to_string()));
+            match watch::snapshot(spec, timeout).await {
+                Ok(current) => {
+                    match &baseline {
+                        None => self.baseline(&current),
+                        Some(prev) => {
+                            for change in watch::diff(prev, &current) {
+                                self.change(&change);
+                            }
+                        }
+                    }
+                    baseline = Some(current);
+                }
+                Err(e) => self.error(&format!("{e:#}")),
+            }
+            if self.args.count != 0 && poll >= self.args.count {
+                break;
+            }
+            tokio::time::sleep(interval).await;
+        }
+        crate::utils::progress_events::phase("done", None);
+        Ok(())
+    }
+
+    /// Print (NDJSON mode) and log one event.
+    fn emit(&mut self, mut event: Value, time: &str) {
+        event["time"] = json!(time);
+        if self.args.output.is_json() {
+            print_line(&event);
+        }
+        if let Some(f) = &mut self.log
+            && let Err(e) = writeln!(f, "{event}")
+        {
+            crate::utils::logging::error(format!("failed to write watch log: {e}"));
+        }
+    }
+
+    fn baseline(&mut self, inv: &Inventory) {
+        let time = utc_timestamp();
+        self.emit(
+            json!({"event": "baseline", "counts": inv.counts_json()}),
+            &time,
+        );
+        if !self.args.output.is_json() {
+            println!(
+                "{} {} baseline: {} tool(s), {} resource(s), {} prompt(s)",
+                color(Role::Dim, &time, &self.style),
+                emoji("info", &self.style),
+                inv.tools.tools.len(),
+                inv.resources.len(),
+                inv.prompts.len()
+            );
+        }
+    }
+
+    fn change(&mut self, change: &Change) {
+        let time = utc_timestamp();
+        let mut event = change.to_json();
+        event["event"] = json!("change");
+        self.emit(event, &time);
+        if !self.args.output.is_json() {
+            let parts = if change.parts.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", change.parts.join(", "))
+            };
+            let role = match change.change {
+                "added" => Role::Success,
+                "removed" => Role::Error,
+                _ => Role::Warning,
+            };
+            println!(
+                "{} {} {} {} {}{parts}",
+                color(Role::Dim, &time, &self.style),
+                emoji("warn", &self.style),
+                color(role, change.change.to_uppercase(), &self.style),
+                change.kind,
+                change.name
+            );
+        }
+    }
+
+    fn error(&mut self, msg: &str) {
+        let time = utc_timestamp();
+        self.emit(json!({"event": "error", "error": msg}), &time);
+        if !self.args.output.is_json() {
+            println!(
+                "{} {} {}",
+                color(Role::Dim, &time, &self.style),
+                emoji("error", &self.style),
+                color(Role::Error, format!("poll failed: {msg}"), &self.style)
+            );
+        }
+    }
+}
@@ -0,0 +1,472 @@
+/*!
+web.rs - web subcommand (cargo feature `web`).
+
+Read-only local UI over an engagement workspace, for browsing results while
+writing the report. The workspace is what `stats` reads (see
+crate::cmd::stats):
+  - *.mcpt   transcripts written with --record
+  - *.json   saved --json outputs (scan, canary, pollution, list, ...)
+
+Files are read again on every request, so new outputs show up on reload.
+
+Pages:
+  /                   targets with their tool inventories, findings by
+                      severity, and the workspace files
+  /findings?q=TEXT    findings of every saved output; TEXT matches the
+                      title, check, tool, target, detail or evidence
+  /file?path=PATH     one workspace file: outputs with findings as the HTML
+                      report (see crate::scan::report), transcripts as
+                      request / response exchanges (?q= filters them),
+                      other JSON pretty-printed
+
+Only GET is answered, and only files found in the workspace are served.
+Served over the HTTP plumbing of `serve --http` (see crate::cmd::serve),
+on 127.0.0.1 unless --bind says otherwise.
+
+Example:
+  cargo install --path . --features web
+  mcp-hack web engagement/
+  mcp-hack web engagement/ --port 9000
+*/
+
+use anyhow::Result;
+use clap::Args;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::cmd::api::workspace_findings;
+use crate::cmd::exec::output_error;
+use crate::cmd::output::OutputFormat;
+use crate::cmd::serve::{HttpRequest, listen, read_request, write_response};
+use crate::cmd::stats::collect_files;
+use crate::mcp::record::{self, Transcript};
+use crate::scan::Severity;
+use crate::scan::report::{CSS, ReportFormat, esc, sev_badge};
+use crate::scan::stats::Stats;
+use crate::utils::logging::{LogLevel, current_log_level};
+
+/* ---- Argument Struct ---- */
+
+/// Output formats this command supports (it serves HTML).
+pub const FORMATS: &[OutputFormat] = &[OutputFormat::Text];
+
+#[derive(Args, Debug)]
+pub struct WebArgs {
+    /// Workspace directory holding transcripts and saved JSON outputs
+    #[arg(value_name = "DIR", default_value = ".")]
+    pub path: String,
+
+    /// Port to serve on
+    #[arg(long, value_name = "PORT", default_value_t = 8844)]
+    pub port: u16,
+
+    /// Address to bind
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1")]
+    pub bind: String,
+
+    /// Output format (set by the dispatcher from the global --format / --json)
+    #[arg(skip)]
+    pub output: OutputFormat,
+}
+
+/* ---- Workspace ---- */
+
+/// A workspace file as listed in pages: path relative to the workspace.
+struct Entry {
+    name: String,
+    path: PathBuf,
+}
+
+fn entries(root: &Path) -> Vec<Entry> {
+    let mut files = Vec::new();
+    collect_files(root, &mut files);
+    files
+        .into_iter()
+        .map(|path| Entry {
+            name: path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .display()
+                .to_string(),
+            path,
+        })
+        .collect()
+}
+
+fn is_transcript(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("mcpt")
+}
+
+fn load_transcript(path: &Path) -> Option<Transcript> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|t| record::parse(&t).ok())
+}
+
+fn load_json(path: &Path) -> Option<Value> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|t| serde_json::from_str(&t).ok())
+}
+
+/* ---- Pages ---- */
+
+fn link(name: &str) -> String {
+    let query: String = url::form_urlencoded::byte_serialize(name.as_bytes()).collect();
+    format!("<a href=\"/file?path={query}\">{}</a>", esc(name))
+}
+
+/// GET form searching with `q`; `hidden` fields are submitted along.
+fn search_form(action: &str, hidden: &[(&str, &str)], q: &str, placeholder: &str) -> String {
+    let hidden: String = hidden
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "<input type=\"hidden\" name=\"{name}\" value=\"{}\">",
+                esc(value)
+            )
+        })
+        .collect();
+    format!(
+        "<form action=\"{action}\">{hidden}<input name=\"q\" value=\"{}\" \
+         placeholder=\"{placeholder}\" size=\"40\"> <button>Search</button></form>\n",
+        esc(q)
+    )
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{} - mcp-hack</title>\
+         <style>{CSS}</style></head><body>\n<p><a href=\"/\">Workspace</a> · \
+         <a href=\"/findings\">Findings</a></p>\n<h1>{}</h1>\n{body}</body></html>\n",
+        esc(title),
+        esc(title)
+    )
+}
+
+fn index(root: &Path) -> String {
+    let files = entries(root);
+    let mut stats = Stats::default();
+    let mut rows = String::new();
+    for e in &files {
+        let (kind, about, findings) = if is_transcript(&e.path) {
+            match load_transcript(&e.path) {
+                Some(t) => {
+                    stats.add_transcript(&t);
+                    let calls = t.exchanges.len().to_string();
+                    ("transcript", t.argv.join(" "), calls)
+                }
+                None => continue,
+            }
+        } else {
+            match load_json(&e.path) {
+                Some(doc) if stats.add_report(&doc) => {
+                    let n = doc
+                        .get("findings")
+                        .and_then(|v| v.as_array())
+                        .map_or("-".to_string(), |f| f.len().to_string());
+                    let target = doc.get("target").and_then(|v| v.as_str());
+                    ("output", target.unwrap_or_default().to_string(), n)
+                }
+                _ => continue,
+            }
+        };
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{kind}</td><td><code>{}</code></td><td>{findings}</td></tr>\n",
+            link(&e.name),
+            esc(&about)
+        ));
+    }
+
+    let mut body = search_form("/findings", &[], "", "search findings");
+    body.push_str(&format!(
+        "<p>{} transcript(s) · {} output(s) · {} target(s) · {} tool(s) inventoried · \
+         {} tool call(s)</p>\n",
+        stats.transcripts,
+        stats.reports,
+        stats.targets.len(),
+        stats.tools.len(),
+        stats.total_calls()
+    ));
+    body.push_str("<h2>Findings</h2>\n<table><tr><th>Severity</th><th>Count</th></tr>\n");
+    for sev in Severity::ALL {
+        body.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            sev_badge(sev.as_str()),
+            stats.findings.get(&sev).copied().unwrap_or(0)
+        ));
+    }
+    body.push_str("</table>\n<h2>Targets</h2>\n");
+    if stats.targets.is_empty() {
+        body.push_str("<p>No targets yet.</p>\n");
+    }
+    for target in &stats.targets {
+        let tools: Vec<String> = stats
+            .tools
+            .iter()
+            .filter(|(t, _)| t == target)
+            .map(|(_, name)| format!("<code>{}</code>", esc(name)))
+            .collect();
+        body.push_str(&format!(
+            "<h3><code>{}</code></h3>\n<p>{}</p>\n",
+            esc(target),
+            if tools.is_empty() {
+                "No tool inventory.".to_string()
+            } else {
+                tools.join(", ")
+            }
+        ));
+    }
+    body.push_str("<h2>Files</h2>\n");
+    if rows.is_empty() {
+        body.push_str("<p>No transcripts or saved outputs.</p>\n");
+    } else {
+        body.push_str(
+            "<table><tr><th>File</th><th>Kind</th><th>Command / target</th>\
+             <th>Findings / exchanges</th></tr>\n",
+        );
+        body.push_str(&rows);
+        body.push_str("</table>\n");
+    }
+    page(&format!("Workspace {}", root.display()), &body)
+}
+
+fn contains(haystack: &str, q: &str) -> bool {
+    q.is_empty() || haystack.to_lowercase().contains(&q.to_lowercase())
+}
+
+fn findings(root: &Path, q: &str) -> String {
+    let field = |f: &Value, key: &str| {
+        f.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let found: Vec<Value> = workspace_findings(root)
+        .into_iter()
+        .filter(|f| {
+            ["title", "check", "tool", "target", "detail", "evidence"]
+                .iter()
+                .any(|key| contains(&field(f, key), q))
+        })
+        .collect();
+    let mut body = search_form("/findings", &[], q, "title, check, tool, target, evidence");
+    body.push_str(&format!("<p>{} finding(s)</p>\n", found.len()));
+    if !found.is_empty() {
+        body.push_str(
+            "<table><tr><th>Severity</th><th>Check</th><th>Tool</th><th>Finding</th>\
+             <th>Target</th><th>File</th></tr>\n",
+        );
+        for f in &found {
+            body.push_str(&format!(
+                "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td>{}</td>\
+                 <td><code>{}</code></td><td>{}</td></tr>\n",
+                sev_badge(&field(f, "severity")),
+                esc(&field(f, "check")),
+                esc(&field(f, "tool")),
+                esc(&field(f, "title")),
+                esc(&field(f, "target")),
+                link(&field(f, "file"))
+            ));
+        }
+        body.push_str("</table>\n");
+    }
+    page("Findings", &body)
+}
+
+fn pretty(v: &Value) -> String {
+    esc(&serde_json::to_string_pretty(v).unwrap_or_default())
+}
+
+fn transcript(name: &str, t: &Transcript, q: &str) -> String {
+    let mut body = format!(
+        "<p><b>Command:</b> <code>{}</code><br><b>Started:</b> {}</p>\n",
+        esc(&t.argv.join(" ")),
+        esc(&t.started)
+    );
+    body.push_str(&search_form(
+        "/file",
+        &[("path", name)],
+        q,
+        "method, tool, arguments, results",
+    ));
+    body.push_str(
+        "<table><tr><th>#</th><th>Target</th><th>Method</th><th>Latency</th><th>Error</th>\
+         <th>Messages</th></tr>\n",
+    );
+    for (i, ex) in t.exchanges.iter().enumerate() {
+        let response = ex.response.as_ref().map(pretty).unwrap_or_default();
+        let request = pretty(&ex.request);
+        if !contains(&request, q) && !contains(&response, q) {
+            continue;
+        }
+        let method = match ex.tool() {
+            Some(tool) => format!("tools/call {tool}"),
+            None => ex.method().unwrap_or("").to_string(),
+        };
+        body.push_str(&format!(
+            "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td>{}</td>\
+             <td>{}</td><td><details><summary>request / response</summary>\
+             <pre>{request}</pre><pre>{response}</pre></details></td></tr>\n",
+            i + 1,
+            esc(t.sessions.get(&ex.session).map_or("", String::as_str)),
+            esc(&method),
+            ex.latency_ms
+                .map_or("-".to_string(), |ms| format!("{ms} ms")),
+            if ex.is_error() { "yes" } else { "" }
+        ));
+    }
+    body.push_str("</table>\n");
+    page(name, &body)
+}
+
+/* ---- Routing ---- */
+
+type Response = (&'static str, &'static str, String);
+
+fn not_found(what: &str) -> Response {
+    (
+        "404 Not Found",
+        "text/html; charset=utf-8",
+        page("Not found", &format!("<p>{}</p>\n", esc(what))),
+    )
+}
+
+fn file(root: &Path, name: &str, q: &str) -> Response {
+    // Only names listed from the workspace: no paths outside it.
+    let Some(entry) = entries(root).into_iter().find(|e| e.name == name) else {
+        return not_found(&format!("{name} is not a workspace file"));
+    };
+    let html = if is_transcript(&entry.path) {
+        match load_transcript(&entry.path) {
+            Some(t) => transcript(name, &t, q),
+            None => return not_found(&format!("{name} is not a readable transcript")),
+        }
+    } else {
+        match load_json(&entry.path) {
+            Some(doc) if doc.get("findings").is_some_and(Value::is_array) => {
+                crate::scan::report::render(ReportFormat::Html, &doc)
+            }
+            Some(doc) => page(name, &format!("<pre>{}</pre>\n", pretty(&doc))),
+            None => return not_found(&format!("{name} is not readable JSON")),
+        }
+    };
+    ("200 OK", "text/html; charset=utf-8", html)
+}
+
+fn route(root: &Path, request: &HttpRequest) -> Response {
+    if request.method != "GET" {
+        return (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "read-only\n".to_string(),
+        );
+    }
+    let (path, params) = request.path_and_query();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k == name)
+            .map_or("", |(_, v)| v.as_str())
+    };
+    match path {
+        "/" => ("200 OK", "text/html; charset=utf-8", index(root)),
+        "/findings" => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            findings(root, param("q")),
+        ),
+        "/file" => file(root, param("path"), param("q")),
+        _ => not_found(&format!("no page {path}")),
+    }
+}
+
+/* ---- Public Entry Point ---- */
+
+pub fn execute_web(args: WebArgs) -> Result<()> {
+    let root = PathBuf::from(&args.path);
+    if !root.is_dir() {
+        return output_error(
+            args.output.is_json(),
+            &format!("{}: not a directory", args.path),
+        );
+    }
+    let addr = format!("{}:{}", args.bind, args.port);
+    if current_log_level() >= LogLevel::Info {
+        eprintln!("[web] serving {} on http://{addr}/", args.path);
+    }
+    let root = Arc::new(root);
+    listen(&addr, "web", move |stream| {
+        let request = read_request(&stream)?;
+        let (status, content_type, body) = route(&root, &request);
+        write_response(stream, status, content_type, &body)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn get(root: &Path, target: &str) -> Response {
+        route(
+            root,
+            &HttpRequest {
+                method: "GET".into(),
+                target: target.into(),
+                headers: vec![],
+                body: vec![],
+            },
+        )
+    }
+
+    #[test]
+    fn serves_workspace_pages() {
+        let dir = std::env::temp_dir().join(format!("mcp-hack-web-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let scan = json!({"status": "ok", "target": "python3 a.py", "findings": [
+            {"check": "dangerous-tool", "severity": "high", "title": "shell <exec>", "tool": "exec"},
+            {"check": "tool-naming", "severity": "low", "title": "odd name"},
+        ]});
+        std::fs::write(dir.join("scan.json"), scan.to_string()).unwrap();
+        let transcript = [
+            r#"{"mcpt":1,"started":"2025-01-01T00:00:00.000Z","argv":["mcp-hack","exec"]}"#,
+            r#"{"event":"session","session":1,"target":"python3 a.py","t_ms":1}"#,
+            r#"{"session":1,"dir":"send","t_ms":2,"message":{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"read_file","arguments":{"path":"/etc/passwd"}}}}"#,
+            r#"{"session":1,"dir":"recv","t_ms":9,"message":{"jsonrpc":"2.0","id":1,"result":{"content":[]}}}"#,
+            r#"{"session":1,"dir":"send","t_ms":10,"message":{"jsonrpc":"2.0","id":2,"method":"tools/list"}}"#,
+        ]
+        .join("\n");
+        std::fs::write(dir.join("run.mcpt"), transcript).unwrap();
+
+        let (status, _, html) = get(&dir, "/");
+        assert_eq!(status, "200 OK");
+        assert!(html.contains("href=\"/file?path=run.mcpt\""));
+        assert!(html.contains("<code>python3 a.py</code>"));
+
+        let (_, _, html) = get(&dir, "/findings?q=SHELL");
+        assert!(html.contains("1 finding(s)"));
+        assert!(html.contains("shell &lt;exec&gt;"));
+        assert!(get(&dir, "/findings").2.contains("2 finding(s)"));
+
+        let (_, _, html) = get(&dir, "/file?path=run.mcpt&q=passwd");
+        assert!(html.contains("tools/call read_file"));
+        assert!(html.contains("7 ms"));
+        assert!(!html.contains("<code>tools/list</code>"));
+        let (_, _, html) = get(&dir, "/file?path=scan.json");
+        assert!(html.contains("shell &lt;exec&gt;"));
+
+        // Nothing outside the workspace listing, and nothing but GET.
+        assert_eq!(get(&dir, "/file?path=../etc/passwd").0, "404 Not Found");
+        assert_eq!(get(&dir, "/nope").0, "404 Not Found");
+        let post = HttpRequest {
+            method: "POST".into(),
+            target: "/".into(),
+            headers: vec![],
+            body: vec![],
+        };
+        assert_eq!(route(&dir, &post).0, "405 Method Not Allowed");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
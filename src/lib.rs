@@ -0,0 +1,34 @@
+//! MCP Hack as a library: the engine behind the `mcp-hack` CLI, for Rust
+//! security tooling that wants to embed it instead of shelling out.
+//!
+//!   mcp     targets (parse_target), spawning and sessions (spawn, raw),
+//!           transcripts, timeouts, the listing cache
+//!   scan    security checks, risk classification, fuzz payloads, diffing
+//!   cmd     the subcommands (`*Args` + `execute_*`) and their shared
+//!           helpers: tool enumeration, argument building, invocation
+//!   utils   logging / tracing, hashing, durations, config, patterns
+//!
+//! Commands print their report to stdout, as the CLI does; the pieces they
+//! are built from return values. Process-wide settings (timeouts, --env,
+//! the cache, output style) are installed by the `set*` functions of their
+//! modules and fall back to defaults when unset.
+//!
+//! Sessions and commands are async and expect a multi-threaded Tokio
+//! runtime (the CLI runs everything on one, from `#[tokio::main]`).
+//!
+//! ```no_run
+//! # async fn demo() -> anyhow::Result<()> {
+//! let spec = mcp_hack::mcp::parse_target("python3 server.py")?;
+//! let tools = mcp_hack::cmd::shared::fetch_tools_local(&spec).await?;
+//! for tool in tools.iter() {
+//!     let risk = mcp_hack::scan::risk::classify(tool);
+//!     println!("{} {:?}", tool["name"], mcp_hack::scan::risk::risk_level(&risk));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod cmd;
+pub mod mcp;
+pub mod scan;
+pub mod utils;
@@ -1,13 +1,20 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 
-mod cmd;
-mod mcp;
-mod utils;
+use mcp_hack::{cmd, mcp, utils};
 
+use cmd::OutputFormat;
 use cmd::{
-    ExecArgs, FuzzArgs, GetArgs, ListArgs, execute_exec, execute_fuzz, execute_get, execute_list,
+    ApiArgs, BenchArgs, CanaryArgs, DiffArgs, DiscoverArgs, ExecArgs, ExportArgs, FingerprintArgs,
+    FuzzArgs, GetArgs, ListArgs, PollutionArgs, ProxyArgs, RawArgs, ReplayArgs, RunArgs, ScanArgs,
+    ServeArgs, ShadowArgs, SoakArgs, StatsArgs, StressArgs, WatchArgs, execute_api, execute_bench,
+    execute_canary, execute_diff, execute_discover, execute_exec, execute_export,
+    execute_fingerprint, execute_fuzz, execute_get, execute_list, execute_pollution, execute_proxy,
+    execute_raw, execute_replay, execute_run, execute_scan, execute_serve, execute_shadow,
+    execute_soak, execute_stats, execute_stress, execute_watch,
 };
+#[cfg(feature = "web")]
+use cmd::{WebArgs, execute_web};
 
 /// MCP Hack CLI
 ///
@@ -18,18 +25,84 @@ use cmd::{
 ///   mcp-hack get tool scan_with_dalfox -t "dalfox server --type=mcp" --json
 ///   mcp-hack get tool -t "dalfox server --type=mcp"            (interactive choose)
 ///   mcp-hack exec tool scan_with_dalfox -t "dalfox server --type=mcp" --param url=https://target --json
+///   mcp-hack raw -t "dalfox server --type=mcp" --method tools/list
+///   mcp-hack scan -t "npx -y @modelcontextprotocol/server-everything"
+///   mcp-hack canary -t "python3 server.py"
+///   mcp-hack pollution -t "python3 server.py"
+///   mcp-hack soak -t "python3 server.py" --duration 300 --report soak.html
+///   mcp-hack stress -t "python3 server.py" --checks calls,notifications
+///   mcp-hack bench tool search -t "python3 server.py" --duration 30s --concurrency 8
+///   mcp-hack watch -t "python3 server.py" --interval 60s
+///   mcp-hack diff tools.json -t "python3 server.py"
+///   mcp-hack fingerprint -t "uvx mcp-server-fetch"
+///   mcp-hack proxy -t "python3 server.py" --rules tamper.yaml
+///   mcp-hack stats engagement/
+///   mcp-hack replay session.mcpt -t "python3 server_fixed.py"
+///   mcp-hack serve --from snapshot.json
+///   mcp-hack run chain.yaml -t "python3 server.py"
+///   mcp-hack discover
 ///
 /// Targets:
 ///   - Local command (spawned child process)  [supported]
 ///   - Remote URL (http/https/ws/wss)         [parsing only; remote ops not yet implemented]
 ///
 /// Global flags / env:
-///   -v / -vv increase verbosity; -q quiet
+///   -v / -vv increase verbosity (-vv: per-request spans); -q quiet;
+///     RUST_LOG=mcp_hack=debug,rmcp=trace style filters (see utils::diagnostics)
 ///   -t / --target or MCP_TARGET env for default target
+///   -t last (previous target) / -t @name (profile, see mcp::alias)
+///   -t "ssh://[USER@]HOST[:PORT] -- COMMAND" runs the server over SSH (see mcp::ssh)
+///   -t unix:///path/to/socket, a Windows named pipe or tcp://HOST:PORT for
+///     servers listening on a socket (see mcp::socket)
+///   -t "docker:IMAGE [-- ARG...]" or --docker IMAGE [--volume HOST:CONTAINER]
+///     runs the server in a container (see mcp::docker)
+///   list / scan / fingerprint: -t repeated or --targets FILE [--parallel N]
+///     for one combined report (see cmd::fleet)
 ///   -H / --header KEY=VALUE (reserved for future remote support)
+///   --format FORMAT / --json / --ndjson   output format (see Output)
+///   --query FILTER    jq-like filter over the JSON output, e.g.
+///                     '.tools[] | select(.name | test("^read")) | .name'
+///                     (see utils::query; implies --json)
+///   --plain (no color, emoji or borders), --no-emoji, --no-border;
+///     NO_COLOR / NO_EMOJI are honored too (pretty JSON on a terminal is
+///     highlighted unless color is off)
+///   --record PATH     transcript of all JSON-RPC traffic (see mcp::record)
+///   --wire-log[=FILE] [--wire-format compact|pretty]   print every JSON-RPC
+///                     frame to stderr or FILE (see mcp::wire)
+///   --log-file PATH [--log-format text|json]   append log lines and events
+///                     (level, ts, event, target, elapsed_ms) for long audits
+///   --hook FILE       Rhai script hook on tool calls / scans (see mcp::hook)
+///   --progress-fd N   NDJSON progress events on fd N (2 = stderr)
+///   --connect-timeout / --request-timeout DURATION   fail fast on slow or
+///                     dead servers (see mcp::limits)
+///   --stderr inherit|capture|null, --stderr-file PATH   what happens to a
+///                     spawned server's stderr (see mcp::spawn)
+///   -e / --env KEY=VALUE, --env-file PATH   extra environment for spawned
+///                     servers, e.g. API keys (see mcp::spawn)
+///   --cwd PATH        working directory of spawned servers
+///   --cache-ttl DURATION (default 5m), --refresh, --no-cache   on-disk cache
+///                     of tool listings for get / exec; list always asks the
+///                     server and refreshes it (see mcp::cache)
+///
+/// Config:
+///   ~/.config/mcp-hack/config.toml (or MCP_HACK_CONFIG): default target,
+///   headers, timeout, output format and wordlist (see utils::config).
+///   Precedence: CLI flag > env > config.
 ///
 /// Output:
-///   Human-readable tables / boxes or --json`.
+///   Human-readable tables / boxes by default; --format json|ndjson|sarif
+///   (or --json / --ndjson) where the command supports it; list / get also
+///   take markdown|csv|yaml for reports and spreadsheets.
+///   JSON carries `elapsed_ms` (total) and `timings` (connect_ms, initialize_ms,
+///   operation_ms, shutdown_ms).
+///   Ordering is stable between runs: tools / resources / prompts keep the
+///   order the server advertised them in, parameters and JSON object keys are
+///   sorted by name, findings by severity (highest first), check, tool, title.
+///
+/// Exit status (see cmd::exit):
+///   0 success, 1 internal error, 2 usage / target error, 3 connection
+///   failure, 4 tool not found, 5 tool returned isError, 6 findings at or
+///   above the gate, 130 interrupted.
 #[derive(Parser, Debug)]
 #[command(
     name = "mcp-hack",
@@ -48,14 +121,123 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     quiet: bool,
 
+    /// No color, emoji or box borders (for scripts and limited terminals)
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// No emoji in human output (same as NO_EMOJI=1)
+    #[arg(long = "no-emoji", global = true)]
+    no_emoji: bool,
+
+    /// Headers without box borders
+    #[arg(long = "no-border", global = true)]
+    no_border: bool,
+
     /// Default target (local command or remote URL)
     #[arg(short = 't', long = "target", global = true, value_name = "TARGET")]
     target: Option<String>,
 
+    /// Run the target from a container image (short for -t docker:IMAGE)
+    #[arg(
+        long = "docker",
+        global = true,
+        value_name = "IMAGE",
+        conflicts_with = "target"
+    )]
+    docker: Option<String>,
+
+    /// Volume for docker targets (repeatable HOST:CONTAINER[:ro])
+    #[arg(long = "volume", global = true, value_name = "MOUNT")]
+    volumes: Vec<String>,
+
     /// Extra header(s) for remote transports (repeatable KEY=VALUE)
     #[arg(short = 'H', long = "header", global = true, value_name = "KEY=VALUE")]
     headers: Vec<String>,
 
+    /// Output format (each command supports a subset; see cmd::output)
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Short for --format json
+    #[arg(long, global = true, conflicts_with_all = ["format", "ndjson"])]
+    json: bool,
+
+    /// Short for --format ndjson
+    #[arg(long, global = true, conflicts_with = "format")]
+    ndjson: bool,
+
+    /// Filter JSON output through a jq-like expression, e.g. '.tools[].name' (implies --json)
+    #[arg(long, global = true, value_name = "FILTER")]
+    query: Option<String>,
+
+    /// Record every JSON-RPC message exchanged with servers to this transcript file
+    #[arg(long = "record", global = true, value_name = "PATH")]
+    record: Option<String>,
+
+    /// Print every JSON-RPC frame to stderr (or --wire-log=FILE) as it passes
+    #[arg(long = "wire-log", global = true, value_name = "FILE", num_args = 0..=1, require_equals = true, default_missing_value = "-")]
+    wire_log: Option<String>,
+
+    /// Append log lines and events (phases, errors, finish) to this file
+    #[arg(long = "log-file", global = true, value_name = "PATH")]
+    log_file: Option<String>,
+
+    /// Log file layout: text or one JSON object per line (for ELK / Splunk)
+    #[arg(long = "log-format", global = true, value_enum, value_name = "FORMAT", default_value_t = utils::logging::LogFormat::Text)]
+    log_format: utils::logging::LogFormat,
+
+    /// Wire log layout: one line per frame (compact) or indented JSON (pretty)
+    #[arg(long = "wire-format", global = true, value_enum, value_name = "FORMAT", default_value_t = mcp::wire::WireFormat::Compact)]
+    wire_format: mcp::wire::WireFormat,
+
+    /// Rhai hook script run on tool calls and scans (see mcp::hook)
+    #[arg(long = "hook", global = true, value_name = "FILE")]
+    hook: Option<String>,
+
+    /// Give up when starting a server (spawn + initialize) takes longer (e.g. 10s, 500ms; default 30s)
+    #[arg(long = "connect-timeout", global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    connect_timeout: Option<std::time::Duration>,
+
+    /// Give up on any single request taking longer (e.g. 30s)
+    #[arg(long = "request-timeout", global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    request_timeout: Option<std::time::Duration>,
+
+    /// Spawned servers' stderr: capture (tail shown with errors), inherit or null
+    #[arg(long = "stderr", global = true, value_enum, value_name = "MODE", default_value_t = mcp::spawn::StderrMode::Capture)]
+    stderr: mcp::spawn::StderrMode,
+
+    /// Environment variable for spawned servers (repeatable KEY=VALUE)
+    #[arg(short = 'e', long = "env", global = true, value_name = "KEY=VALUE")]
+    env: Vec<String>,
+
+    /// Environment variables for spawned servers from a .env file (--env wins)
+    #[arg(long = "env-file", global = true, value_name = "PATH")]
+    env_file: Option<String>,
+
+    /// Working directory for spawned servers (relative paths in the target resolve against it)
+    #[arg(long = "cwd", global = true, value_name = "PATH")]
+    cwd: Option<String>,
+
+    /// Also write captured server stderr to this file
+    #[arg(long = "stderr-file", global = true, value_name = "PATH")]
+    stderr_file: Option<String>,
+
+    /// Reuse cached tool listings younger than this (e.g. 30s, 1h; 0 = off; default 5m)
+    #[arg(long = "cache-ttl", global = true, value_name = "DURATION", value_parser = parse_duration_arg)]
+    cache_ttl: Option<std::time::Duration>,
+
+    /// Enumerate again instead of reading cached tool listings (still updates the cache)
+    #[arg(long, global = true)]
+    refresh: bool,
+
+    /// Neither read nor write the tool listing cache
+    #[arg(long = "no-cache", global = true, conflicts_with = "refresh")]
+    no_cache: bool,
+
+    /// Emit NDJSON progress events on this file descriptor (2 = stderr)
+    #[arg(long = "progress-fd", global = true, value_name = "FD")]
+    progress_fd: Option<i32>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -73,53 +255,582 @@ pub enum Commands {
 
     /// Fuzz a tool with a wordlist
     Fuzz(FuzzArgs),
+
+    /// Send a raw JSON-RPC request / notification
+    Raw(RawArgs),
+
+    /// Run security checks against a target and report findings
+    Scan(ScanArgs),
+
+    /// Detect cross-server tool shadowing between several targets
+    Shadow(ShadowArgs),
+
+    /// Plant canary values in secret parameters and detect where they resurface
+    Canary(CanaryArgs),
+
+    /// Plant markers through each tool and map cross-tool shared-state channels
+    Pollution(PollutionArgs),
+
+    /// Keep a session busy and track server memory / descriptor growth
+    Soak(SoakArgs),
+
+    /// Drive sustained load and report throughput, latency and errors
+    Bench(BenchArgs),
+
+    /// Check whether the server survives floods, many sessions and parallel calls
+    Stress(StressArgs),
+
+    /// Periodically re-enumerate a target and report definition changes
+    Watch(WatchArgs),
+
+    /// Compare the tool inventories of two targets or snapshots
+    Diff(DiffArgs),
+
+    /// Export tool schemas as JSON Schema documents, or typed client stubs
+    Export(ExportArgs),
+
+    /// Identify the server's SDK / product and its known issues
+    Fingerprint(FingerprintArgs),
+
+    /// Relay a stdio session, tampering with messages in flight
+    Proxy(ProxyArgs),
+
+    /// Summarize transcripts and saved outputs of an engagement
+    Stats(StatsArgs),
+
+    /// Replay a recorded transcript and diff the responses
+    Replay(ReplayArgs),
+
+    /// Serve a snapshot as a mock MCP server
+    Serve(ServeArgs),
+
+    /// Local HTTP API to list, scan and fetch findings
+    Api(ApiArgs),
+
+    /// Browse a workspace's transcripts and findings in a local web UI
+    #[cfg(feature = "web")]
+    Web(WebArgs),
+
+    /// Run a playbook of chained tool calls with assertions
+    Run(RunArgs),
+
+    /// Find MCP servers configured in local client configs
+    Discover(DiscoverArgs),
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Expand `last` / `@profile` target shorthands (exit status 2 on failure).
+///
+/// A config.toml profile's env vars are handed to mcp::spawn for the
+/// servers it starts.
+fn resolve_target(raw: &str) -> String {
+    let target = match mcp::alias::resolve(raw) {
+        Ok(t) => t,
+        Err(e) => {
+            eprintln!("Invalid target '{}': {:#}", mcp::redact_target(raw), e);
+            std::process::exit(2);
+        }
+    };
+    if let Some(profile) = mcp::alias::profile(raw) {
+        mcp::spawn::add_profile_env(&profile.env);
+    }
+    target
+}
+
+/// Targets of a command run against several (-t repeated / --targets FILE),
+/// shorthands expanded (config profiles stay `@name` for the children) and
+/// duplicates dropped; None for a single target.
+fn fleet_targets(own: &[String], fleet: &cmd::fleet::FleetArgs) -> Option<Vec<String>> {
+    let mut raw = own.to_vec();
+    if let Some(path) = &fleet.targets_file {
+        match cmd::fleet::load_targets(path) {
+            Ok(t) if t.is_empty() => {
+                eprintln!("Invalid --targets {}: no targets listed", path);
+                std::process::exit(2);
+            }
+            Ok(t) => raw.extend(t),
+            Err(e) => {
+                eprintln!("Invalid --targets {}: {:#}", path, e);
+                std::process::exit(2);
+            }
+        }
+    } else if raw.len() < 2 {
+        return None;
+    }
+    let mut targets: Vec<String> = Vec::new();
+    for t in &raw {
+        let resolved = match mcp::alias::resolve(t) {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("Invalid target '{}': {:#}", mcp::redact_target(t), e);
+                std::process::exit(2);
+            }
+        };
+        if let Err(e) = mcp::parse_target(&resolved) {
+            eprintln!("Invalid target '{}': {}", mcp::redact_target(&resolved), e);
+            std::process::exit(2);
+        }
+        // Each child resolves a config profile itself, env and headers included.
+        let t = match mcp::alias::profile(t) {
+            Some(_) => t.trim().to_string(),
+            None => resolved,
+        };
+        if !targets.contains(&t) {
+            targets.push(t);
+        }
+    }
+    Some(targets)
+}
+
+/// Effective target of a command (its own -t > global), recorded for `-t last`.
+fn command_target(own: Option<String>, global: &Option<String>) -> Option<String> {
+    let target = own.map(|t| resolve_target(&t)).or_else(|| global.clone())?;
+    let shown = mcp::redact_target(&target);
+    cmd::errors::set_target(&shown);
+    utils::logging::set_target(&shown);
+    if mcp::parse_target(&target).is_ok()
+        && let Err(e) = mcp::alias::remember(&shown)
+    {
+        utils::logging::debug(format!("failed to record last target: {e:#}"));
+    }
+    Some(target)
+}
+
+/// Clap value parser for human durations (see utils::parse_duration).
+fn parse_duration_arg(s: &str) -> Result<std::time::Duration, String> {
+    utils::parse_duration(s).map_err(|e| e.to_string())
+}
+
+/// Command line with config-file defaults for --timeout and --wordlist.
+fn command_with_config(config: &utils::config::Config) -> clap::Command {
+    let mut command = Cli::command();
+    let names: Vec<String> = command
+        .get_subcommands()
+        .map(|c| c.get_name().to_string())
+        .collect();
+    for name in names {
+        command = command.mut_subcommand(name, |mut sub| {
+            let has = |sub: &clap::Command, id: &str| sub.get_arguments().any(|a| a.get_id() == id);
+            if let Some(t) = config.timeout
+                && has(&sub, "timeout")
+            {
+                sub = sub.mut_arg("timeout", |a| a.default_value(&*t.to_string().leak()));
+            }
+            if let Some(w) = &config.wordlist
+                && has(&sub, "wordlist")
+            {
+                sub = sub.mut_arg("wordlist", |a| {
+                    a.required(false).default_value(&*w.clone().leak())
+                });
+            }
+            sub
+        });
+    }
+    command
+}
+
+/// Output format preferred by the config file, if any.
+fn preferred_output() -> Option<OutputFormat> {
+    let f = utils::config::get().format.as_deref()?;
+    OutputFormat::from_str(f, true).ok()
+}
+
+/// Output format of a command, checked against what it supports (exit status 2).
+///
+/// Without --format / --json / --ndjson the config file's preference is
+/// used where the command supports it.
+fn command_output(
+    name: &str,
+    supported: &[OutputFormat],
+    requested: Option<OutputFormat>,
+) -> OutputFormat {
+    if requested.is_none()
+        && let Some(f) = preferred_output()
+    {
+        return cmd::output::select(name, supported, Some(f)).unwrap_or_default();
+    }
+    match cmd::output::select(name, supported, requested) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Config file defaults (CLI flag > env > config)
+    let config = match utils::config::load() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Invalid config: {:#}", e);
+            std::process::exit(2);
+        }
+    };
+    let command = command_with_config(config);
+    let matches = command.clone().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if cli.headers.is_empty() {
+        cli.headers = config
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect();
+    }
+    cmd::format::set_flags(cmd::format::StyleFlags {
+        plain: cli.plain,
+        no_emoji: cli.no_emoji,
+        no_border: cli.no_border,
+    });
+    mcp::timing::begin();
+    mcp::limits::set(cli.connect_timeout, cli.request_timeout);
+    if let Err(e) = mcp::spawn::set_stderr(cli.stderr, cli.stderr_file.as_deref()) {
+        eprintln!("Invalid --stderr-file: {:#}", e);
+        std::process::exit(2);
+    }
+    if let Err(e) = mcp::spawn::set_env(&cli.env, cli.env_file.as_deref()) {
+        eprintln!("Invalid --env / --env-file: {:#}", e);
+        std::process::exit(2);
+    }
+    if let Err(e) = mcp::docker::set_volumes(&cli.volumes) {
+        eprintln!("Invalid --volume: {:#}", e);
+        std::process::exit(2);
+    }
+    if let Some(dir) = &cli.cwd
+        && let Err(e) = mcp::spawn::set_cwd(dir)
+    {
+        eprintln!("Invalid --cwd: {:#}", e);
+        std::process::exit(2);
+    }
+    mcp::cache::set(mcp::cache::CacheOptions {
+        ttl: cli.cache_ttl.unwrap_or(mcp::cache::DEFAULT_TTL),
+        disabled: cli.no_cache,
+        refresh: cli.refresh,
+    });
 
     // Initialize logging
     let level = utils::derive_level(cli.verbose, cli.quiet);
     utils::init_logging(level);
+    if let Some(path) = &cli.log_file {
+        if let Err(e) = utils::logging::init_file(path, cli.log_format) {
+            eprintln!("Invalid --log-file {}: {:#}", path, e);
+            std::process::exit(2);
+        }
+        utils::logging::event(
+            utils::logging::LogLevel::Info,
+            "start",
+            serde_json::json!({
+                "command": std::env::args().nth(1),
+                "version": env!("CARGO_PKG_VERSION"),
+            }),
+        );
+    }
+    if let Err(e) = utils::diagnostics::init(level) {
+        eprintln!("Invalid RUST_LOG: {:#}", e);
+        std::process::exit(2);
+    }
 
-    // Effective global target (CLI flag > MCP_TARGET env)
-    let global_target = cli.target.clone().or_else(|| {
-        std::env::var("MCP_TARGET")
-            .ok()
-            .filter(|s| !s.trim().is_empty())
-    });
+    // Structured progress events for embedding UIs (opt-in)
+    if let Some(fd) = cli.progress_fd
+        && let Err(e) = utils::progress_events::init_fd(fd)
+    {
+        eprintln!("Invalid --progress-fd {}: {}", fd, e);
+        std::process::exit(2);
+    }
+
+    // Traffic transcript (opt-in)
+    if let Some(path) = &cli.record
+        && let Err(e) = mcp::record::start(path, &std::env::args().collect::<Vec<_>>())
+    {
+        eprintln!("Invalid --record {}: {:#}", path, e);
+        std::process::exit(2);
+    }
+
+    // Wire log (opt-in)
+    if let Some(path) = &cli.wire_log
+        && let Err(e) = mcp::wire::start(Some(path), cli.wire_format)
+    {
+        eprintln!("Invalid --wire-log {}: {:#}", path, e);
+        std::process::exit(2);
+    }
+
+    // Scripting hook (opt-in)
+    if let Some(path) = &cli.hook
+        && let Err(e) = mcp::hook::install(path)
+    {
+        eprintln!("Invalid --hook {}: {:#}", path, e);
+        std::process::exit(2);
+    }
+
+    if let Some(filter) = &cli.query {
+        match utils::query::Query::parse(filter) {
+            Ok(query) => cmd::output::set_query(query),
+            Err(e) => {
+                eprintln!("Invalid --query: {:#}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // Ctrl-C / SIGTERM: no spawned server outlives the run
+    mcp::interrupt::install();
+
+    // Effective global target (CLI flag > MCP_TARGET env > config), shorthands expanded
+    let global_target = cli
+        .target
+        .clone()
+        .or_else(|| {
+            cli.docker
+                .as_ref()
+                .map(|image| format!("{}{image}", mcp::docker::SCHEME))
+        })
+        .or_else(|| {
+            std::env::var("MCP_TARGET")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+        })
+        .or_else(|| config.target.clone());
+    // Profile headers follow -H / config headers
+    if let Some(profile) = global_target.as_deref().and_then(mcp::alias::profile) {
+        match profile.header_args() {
+            Ok(h) => cli.headers.extend(h),
+            Err(e) => {
+                eprintln!(
+                    "Invalid target '{}': {:#}",
+                    mcp::redact_target(&global_target.unwrap_or_default()),
+                    e
+                );
+                std::process::exit(2);
+            }
+        }
+    }
+    let global_target = global_target.map(|t| resolve_target(&t));
 
     // Validate target syntax early if provided
     if let Some(t) = &global_target
-        && let Err(e) = mcp::parse_target(t) {
-            eprintln!("Invalid target '{}': {}", t, e);
-            std::process::exit(2);
-        }
+        && let Err(e) = mcp::parse_target(t)
+    {
+        eprintln!("Invalid target '{}': {}", mcp::redact_target(t), e);
+        std::process::exit(2);
+    }
+
+    let requested = match (cli.format, cli.json, cli.ndjson) {
+        (Some(f), _, _) => Some(f),
+        (None, true, _) => Some(OutputFormat::Json),
+        (None, _, true) => Some(OutputFormat::Ndjson),
+        _ => cli.query.is_some().then_some(OutputFormat::Json),
+    };
 
-    match cli.command {
+    // Errors are shown once, with hints, in the requested format (exit status
+    // by kind, see cmd::exit).
+    let json = requested
+        .or_else(preferred_output)
+        .is_some_and(|f| f.is_json());
+    let result = match cli.command {
         Commands::List(mut args) => {
-            if args.target.is_none() {
-                args.target = global_target.clone();
+            args.output = command_output("list", cmd::list::FORMATS, requested);
+            match fleet_targets(&args.targets, &args.fleet) {
+                Some(targets) => cmd::fleet::execute_fleet(
+                    "list",
+                    &targets,
+                    &args.fleet,
+                    args.output,
+                    &command,
+                    &matches,
+                ),
+                None => {
+                    args.target = command_target(args.targets.pop(), &global_target);
+                    execute_list(args).await
+                }
             }
-            execute_list(args)
         }
         Commands::Get(mut args) => {
-            if args.target.is_none() {
-                args.target = global_target.clone();
-            }
-            execute_get(args)
+            args.output = command_output("get", cmd::get::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_get(args).await
         }
         Commands::Exec(mut args) => {
-            if args.target.is_none() {
-                args.target = global_target.clone();
-            }
-            execute_exec(args)
+            args.output = command_output("exec", cmd::exec::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_exec(args).await
         }
         Commands::Fuzz(mut args) => {
-            if args.target.is_none() {
-                args.target = global_target.clone();
+            args.output = command_output("fuzz", cmd::fuzz::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_fuzz(args).await
+        }
+        Commands::Raw(mut args) => {
+            args.output = command_output("raw", cmd::raw::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_raw(args).await
+        }
+        Commands::Scan(mut args) => {
+            args.output = command_output("scan", cmd::scan::FORMATS, requested);
+            let fleet = (!args.list_checks)
+                .then(|| fleet_targets(&args.targets, &args.fleet))
+                .flatten();
+            match fleet {
+                Some(targets) => cmd::fleet::execute_fleet(
+                    "scan",
+                    &targets,
+                    &args.fleet,
+                    args.output,
+                    &command,
+                    &matches,
+                ),
+                None => {
+                    args.target = command_target(args.targets.pop(), &global_target);
+                    execute_scan(args).await
+                }
+            }
+        }
+        Commands::Shadow(mut args) => {
+            args.output = command_output("shadow", cmd::shadow::FORMATS, requested);
+            args.targets = args.targets.iter().map(|t| resolve_target(t)).collect();
+            // The global target joins the comparison set.
+            if let Some(t) = &global_target
+                && !args.targets.contains(t)
+            {
+                args.targets.insert(0, t.clone());
+            }
+            execute_shadow(args).await
+        }
+        Commands::Canary(mut args) => {
+            args.output = command_output("canary", cmd::canary::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_canary(args).await
+        }
+        Commands::Pollution(mut args) => {
+            args.output = command_output("pollution", cmd::pollution::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_pollution(args).await
+        }
+        Commands::Soak(mut args) => {
+            args.output = command_output("soak", cmd::soak::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_soak(args).await
+        }
+        Commands::Bench(mut args) => {
+            args.output = command_output("bench", cmd::bench::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_bench(args).await
+        }
+        Commands::Stress(mut args) => {
+            args.output = command_output("stress", cmd::stress::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_stress(args).await
+        }
+        Commands::Watch(mut args) => {
+            args.output = command_output("watch", cmd::watch::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_watch(args).await
+        }
+        Commands::Diff(mut args) => {
+            args.output = command_output("diff", cmd::diff::FORMATS, requested);
+            // Snapshot files are taken as-is; other sources may be shorthands.
+            for source in &mut args.sources {
+                if !std::path::Path::new(source.as_str()).is_file() {
+                    *source = resolve_target(source);
+                }
+            }
+            args.target = command_target(args.target.take(), &global_target);
+            execute_diff(args).await
+        }
+        Commands::Export(mut args) => {
+            args.output = command_output("export", cmd::export::FORMATS, requested);
+            // A snapshot file is taken as-is; another source may be a shorthand.
+            if let Some(source) = &mut args.source
+                && !std::path::Path::new(source.as_str()).is_file()
+            {
+                *source = resolve_target(source);
             }
-            execute_fuzz(args)
+            args.target = command_target(args.target.take(), &global_target);
+            execute_export(args).await
+        }
+        Commands::Fingerprint(mut args) => {
+            args.output = command_output("fingerprint", cmd::fingerprint::FORMATS, requested);
+            match fleet_targets(&args.targets, &args.fleet) {
+                Some(targets) => cmd::fleet::execute_fleet(
+                    "fingerprint",
+                    &targets,
+                    &args.fleet,
+                    args.output,
+                    &command,
+                    &matches,
+                ),
+                None => {
+                    args.target = command_target(args.targets.pop(), &global_target);
+                    execute_fingerprint(args).await
+                }
+            }
+        }
+        Commands::Proxy(mut args) => {
+            args.output = command_output("proxy", cmd::proxy::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_proxy(args)
+        }
+        Commands::Stats(mut args) => {
+            args.output = command_output("stats", cmd::stats::FORMATS, requested);
+            execute_stats(args)
+        }
+        Commands::Replay(mut args) => {
+            args.output = command_output("replay", cmd::replay::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_replay(args).await
+        }
+        Commands::Serve(mut args) => {
+            args.output = command_output("serve", cmd::serve::FORMATS, requested);
+            execute_serve(args)
+        }
+        Commands::Api(mut args) => {
+            args.output = command_output("api", cmd::api::FORMATS, requested);
+            execute_api(args)
+        }
+        #[cfg(feature = "web")]
+        Commands::Web(mut args) => {
+            args.output = command_output("web", cmd::web::FORMATS, requested);
+            execute_web(args)
+        }
+        Commands::Run(mut args) => {
+            args.output = command_output("run", cmd::run::FORMATS, requested);
+            args.target = command_target(args.target.take(), &global_target);
+            execute_run(args).await
+        }
+        Commands::Discover(mut args) => {
+            args.output = command_output("discover", cmd::discover::FORMATS, requested);
+            execute_discover(args)
+        }
+    };
+    let result = result.and_then(|()| {
+        if cmd::output::query_failed() {
+            return Err(cmd::exit::Status(cmd::exit::USAGE).into());
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        // Reported errors were shown by the command, a Status is its outcome
+        let shown = e.is::<cmd::errors::Reported>() || e.is::<cmd::exit::Status>();
+        if !shown {
+            cmd::errors::present(json, &format!("{e:#}"));
         }
+        let status = cmd::exit::for_error(&e);
+        let error = (!e.is::<cmd::exit::Status>()).then(|| format!("{e:#}"));
+        utils::logging::event(
+            if error.is_some() {
+                utils::logging::LogLevel::Error
+            } else {
+                utils::logging::LogLevel::Info
+            },
+            "finish",
+            serde_json::json!({ "status": status, "error": error }),
+        );
+        mcp::spawn::kill_children();
+        std::process::exit(status);
     }
+    utils::logging::event(
+        utils::logging::LogLevel::Info,
+        "finish",
+        serde_json::json!({ "status": 0 }),
+    );
+    Ok(())
 }
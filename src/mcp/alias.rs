@@ -0,0 +1,122 @@
+//! Target shorthands resolved before dispatch.
+//!
+//! `-t last`   the most recent target used by any command
+//!             (stored in `<state_dir>/last_target`, URL userinfo removed)
+//! `-t @name`  a named profile: a `[targets.name]` table of config.toml
+//!             (see utils::config), else a server entry of the profiles
+//!             file, which uses the MCP client config format (see client_config)
+//!
+//! Config profiles may carry headers (and a bearer token read from an
+//! environment variable) and environment variables for the spawned server.
+//!
+//! Profiles file: `MCP_HACK_PROFILES`, else
+//! `$XDG_CONFIG_HOME/mcp-hack/profiles.json`, else
+//! `~/.config/mcp-hack/profiles.json`. Pointing MCP_HACK_PROFILES at a
+//! Claude Desktop / Cursor config makes its servers usable as `@name`.
+//! Environment values of profiles-file entries are not applied.
+
+use anyhow::{Context, Result, bail};
+use std::path::PathBuf;
+
+use super::client_config::{self, ConfiguredServer};
+use crate::utils::config::{self, TargetProfile};
+use crate::utils::{config_dir, state_dir};
+
+/// Keyword for the most recently used target.
+pub const LAST: &str = "last";
+
+/// Location of the profiles file.
+pub fn profiles_path() -> PathBuf {
+    if let Ok(path) = std::env::var("MCP_HACK_PROFILES")
+        && !path.trim().is_empty()
+    {
+        return PathBuf::from(path);
+    }
+    config_dir().join("profiles.json")
+}
+
+fn last_path() -> PathBuf {
+    state_dir().join("last_target")
+}
+
+/// Target of profile `name` among configured servers.
+pub fn lookup(name: &str, servers: &[ConfiguredServer]) -> Result<String> {
+    match servers.iter().find(|s| s.name == name) {
+        Some(s) => Ok(s.target.clone()),
+        None => {
+            let known: Vec<&str> = servers.iter().map(|s| s.name.as_str()).collect();
+            bail!(
+                "unknown profile '@{name}' (available: {})",
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        }
+    }
+}
+
+/// Expand `last` / `@name`; any other value is returned unchanged.
+pub fn resolve(raw: &str) -> Result<String> {
+    let t = raw.trim();
+    if t == LAST {
+        let path = last_path();
+        let last = std::fs::read_to_string(&path)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty());
+        return last.context("no previous target recorded yet ('-t last' needs one earlier run)");
+    }
+    if let Some(profile) = profile(t) {
+        return Ok(profile.target().to_string());
+    }
+    if let Some(name) = t.strip_prefix('@') {
+        let path = profiles_path();
+        let servers = client_config::load(&path.to_string_lossy()).with_context(|| {
+            format!("profile '@{name}' is not in config.toml [targets] and needs a profiles file")
+        })?;
+        return lookup(name, &servers);
+    }
+    Ok(raw.to_string())
+}
+
+/// The config.toml profile `raw` names (`@name`), if any.
+pub fn profile(raw: &str) -> Option<&'static TargetProfile> {
+    let name = raw.trim().strip_prefix('@')?;
+    config::get().targets.get(name)
+}
+
+/// Record `target` as the most recently used one.
+pub fn remember(target: &str) -> Result<()> {
+    let path = last_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    std::fs::write(&path, target.trim())
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn profiles_resolve_by_name() {
+        let servers = client_config::parse(&json!({"mcpServers": {
+            "fs": {"command": "npx", "args": ["-y", "server-fs", "/tmp"]},
+            "prod": {"url": "https://mcp.example.com/mcp"}
+        }}))
+        .unwrap();
+        assert_eq!(lookup("fs", &servers).unwrap(), "npx -y server-fs /tmp");
+        assert_eq!(
+            lookup("prod", &servers).unwrap(),
+            "https://mcp.example.com/mcp"
+        );
+        let err = lookup("dev", &servers).unwrap_err().to_string();
+        assert!(err.contains("available: fs, prod"));
+        assert_eq!(resolve("python3 server.py").unwrap(), "python3 server.py");
+    }
+}
@@ -0,0 +1,167 @@
+//! On-disk cache of a target's tool listing, so repeated `get tool` /
+//! `exec tool` / `exec batch` / `fuzz` runs skip the enumerate (through
+//! cmd::shared::fetch_tools_for). `list tools` never reads it (it is how
+//! changed tools are noticed) but refreshes it. Entries are stored per
+//! listing kind; only `tools` is cached today.
+//!
+//! One file per target under `<cache_dir>/lists/` (see utils::cache_dir),
+//! keyed by the target command, the server's working directory (--cwd, else
+//! mcp-hack's own) and --env. An entry is used while it
+//! is younger than --cache-ttl (default 5m, 0 turns the cache off);
+//! --refresh enumerates again and rewrites it, --no-cache neither reads nor
+//! writes. Only listings are cached: tool calls always reach the server, and
+//! `exec` enumerates live when the tool is missing from the cached list.
+//! Under --record / --wire-log nothing is read, so the traffic is complete.
+
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::utils::{cache_dir, sha256_hex};
+
+/// Entry lifetime when --cache-ttl is not given.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Cache behaviour of this process (global flags).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheOptions {
+    pub ttl: Duration,
+    /// Neither read nor write (--no-cache)
+    pub disabled: bool,
+    /// Write but do not read (--refresh)
+    pub refresh: bool,
+}
+
+static OPTIONS: OnceLock<CacheOptions> = OnceLock::new();
+static BYPASS: AtomicBool = AtomicBool::new(false);
+
+/// Install the options for this process (first call wins). Without a call
+/// the cache is off.
+pub fn set(options: CacheOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+/// Stop reading the cache for the rest of the process: a cached listing
+/// turned out stale (a tool asked for by name is not in it).
+pub fn bypass() {
+    BYPASS.store(true, Ordering::Relaxed);
+}
+
+fn writable() -> Option<CacheOptions> {
+    OPTIONS
+        .get()
+        .copied()
+        .filter(|o| !o.disabled && !o.ttl.is_zero())
+}
+
+fn readable() -> Option<CacheOptions> {
+    writable().filter(|o| {
+        !o.refresh
+            && !BYPASS.load(Ordering::Relaxed)
+            && !super::record::enabled()
+            && !super::wire::enabled()
+    })
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Cache file of `target` as started with the current --cwd / --env.
+fn path(target: &str) -> PathBuf {
+    let mut key = target.trim().to_string();
+    key.push('\0');
+    // Relative commands and paths resolve against the server's directory.
+    let dir = super::spawn::cwd()
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok());
+    if let Some(dir) = dir {
+        key.push_str(&dir.to_string_lossy());
+    }
+    for (k, v) in super::spawn::env() {
+        key.push_str(&format!("\0{k}={v}"));
+    }
+    cache_dir()
+        .join("lists")
+        .join(format!("{}.json", &sha256_hex(key.as_bytes())[..32]))
+}
+
+/// `kind` items (e.g. `tools`) of a cache document, if
+/// fetched within `ttl` of `now`.
+fn fresh(doc: &Value, kind: &str, ttl: Duration, now: u64) -> Option<Vec<Value>> {
+    let entry = doc.get(kind)?;
+    let fetched = entry.get("fetched_at")?.as_u64()?;
+    if now.saturating_sub(fetched) >= ttl.as_secs().max(1) || fetched > now {
+        return None;
+    }
+    entry.get("items")?.as_array().cloned()
+}
+
+/// Cached `kind` items of `target`, when fresh and the cache may be read.
+pub fn load(target: &str, kind: &str) -> Option<Vec<Value>> {
+    let options = readable()?;
+    let raw = std::fs::read_to_string(path(target)).ok()?;
+    let doc: Value = serde_json::from_str(&raw).ok()?;
+    let items = fresh(&doc, kind, options.ttl, now_secs())?;
+    tracing::debug!("{kind} of {target} read from the cache");
+    Some(items)
+}
+
+/// Remember `items` as the `kind` listing of `target` (failures are only logged).
+pub fn store(target: &str, kind: &str, items: &[Value]) {
+    if writable().is_none() {
+        return;
+    }
+    let path = path(target);
+    let mut doc = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .filter(|d| d.get("target").and_then(|t| t.as_str()) == Some(target.trim()))
+        .and_then(|d| d.as_object().cloned())
+        .unwrap_or_default();
+    doc.insert("target".into(), json!(target.trim()));
+    doc.insert(
+        kind.into(),
+        json!({"fetched_at": now_secs(), "items": items}),
+    );
+    let write = || -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write then rename, so a concurrent reader never sees half a file.
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        std::fs::write(&tmp, Value::Object(doc).to_string())?;
+        std::fs::rename(&tmp, &path)
+    };
+    if let Err(e) = write() {
+        tracing::debug!("failed to write cache {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_expire_after_ttl() {
+        let doc = json!({
+            "target": "python3 server.py",
+            "tools": {"fetched_at": 1000, "items": [{"name": "a"}]},
+        });
+        let ttl = Duration::from_secs(60);
+        assert_eq!(
+            fresh(&doc, "tools", ttl, 1059),
+            Some(vec![json!({"name": "a"})])
+        );
+        assert_eq!(fresh(&doc, "tools", ttl, 1060), None);
+        assert_eq!(fresh(&doc, "tools", ttl, 999), None);
+        assert_eq!(fresh(&doc, "prompts", ttl, 1000), None);
+        // Not set up by main: off.
+        assert!(load("python3 server.py", "tools").is_none());
+    }
+}
@@ -0,0 +1,214 @@
+//! MCP client configuration import.
+//!
+//! Reads the `mcpServers` map used by Claude Desktop, Cursor, Windsurf and
+//! friends (VS Code's `servers` / `mcp.servers` and Zed's `context_servers`
+//! keys are accepted too) and turns each entry into a target string
+//! understood by `parse_target`.
+//!
+//! { "mcpServers": { "fs": { "command": "npx", "args": ["-y", "..."], "env": {...} },
+//!                   "remote": { "url": "https://host/mcp" } } }
+//!
+//! Editor settings files are JSON with comments: `//` and `/* */` comments
+//! and trailing commas are tolerated.
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+
+/// One server entry from a client config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfiguredServer {
+    /// Key in the config map
+    pub name: String,
+    /// Target string (shell-quoted command line or URL)
+    pub target: String,
+    /// Environment variable names the entry sets (values are not kept)
+    pub env_keys: Vec<String>,
+    /// Env / header names holding a literal credential rather than a
+    /// `${VAR}` reference (values are not kept)
+    pub inline_secrets: Vec<String>,
+}
+
+/// Remove `//` / `/* */` comments and trailing commas outside strings.
+pub fn strip_jsonc(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+    let mut in_str = false;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_str {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 1;
+            } else if c == '"' {
+                in_str = false;
+            }
+        } else if c == '"' {
+            in_str = true;
+            out.push(c);
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+            continue;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if !matches!(next, Some('}') | Some(']')) {
+                out.push(c);
+            }
+        } else {
+            out.push(c);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Parse a config document, tolerating comments and trailing commas.
+pub fn parse_document(raw: &str) -> Result<Value> {
+    serde_json::from_str(raw)
+        .or_else(|e| serde_json::from_str(&strip_jsonc(raw)).map_err(|_| e))
+        .context("not valid JSON")
+}
+
+/// True when an env / header name suggests a credential.
+fn secret_name(name: &str) -> bool {
+    let n = name.to_ascii_uppercase();
+    [
+        "TOKEN",
+        "KEY",
+        "SECRET",
+        "PASSWORD",
+        "PASSWD",
+        "AUTH",
+        "CREDENTIAL",
+        "COOKIE",
+    ]
+    .iter()
+    .any(|w| n.contains(w))
+}
+
+/// True when a value is a reference (`${VAR}`, `$VAR`, `${input:...}`) or empty.
+fn is_reference(value: &str) -> bool {
+    let v = value.trim();
+    v.is_empty() || v.starts_with('$') || (v.starts_with("{{") && v.ends_with("}}"))
+}
+
+/// Load a client config file.
+pub fn load(path: &str) -> Result<Vec<ConfiguredServer>> {
+    let raw =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read config: {path}"))?;
+    let doc = parse_document(&raw).with_context(|| format!("config is not valid JSON: {path}"))?;
+    parse(&doc)
+}
+
+/// Extract server entries from a parsed config document.
+pub fn parse(doc: &Value) -> Result<Vec<ConfiguredServer>> {
+    let Some(map) = doc
+        .get("mcpServers")
+        .or_else(|| doc.get("servers"))
+        .or_else(|| doc.pointer("/mcp/servers"))
+        .or_else(|| doc.get("context_servers"))
+        .and_then(|v| v.as_object())
+    else {
+        bail!("no \"mcpServers\" (or \"servers\") object found");
+    };
+
+    let mut out = Vec::new();
+    for (name, entry) in map {
+        let target = if let Some(url) = entry
+            .get("url")
+            .or_else(|| entry.get("serverUrl"))
+            .and_then(|v| v.as_str())
+        {
+            url.to_string()
+        } else if let Some(cmd) = entry.get("command").and_then(|v| {
+            // Zed (older settings): "command": {"path", "args", "env"}
+            v.as_str()
+                .or_else(|| v.get("path").and_then(|p| p.as_str()))
+        }) {
+            let entry = match entry.get("command") {
+                Some(c) if c.is_object() => c,
+                _ => entry,
+            };
+            let mut parts = vec![cmd.to_string()];
+            if let Some(args) = entry.get("args").and_then(|v| v.as_array()) {
+                parts.extend(args.iter().filter_map(|a| a.as_str()).map(str::to_string));
+            }
+            shell_words::join(parts)
+        } else {
+            bail!("server '{name}' has neither \"command\" nor \"url\"");
+        };
+        let env = entry
+            .get("env")
+            .or_else(|| entry.pointer("/command/env"))
+            .and_then(|v| v.as_object());
+        let env_keys = env.map(|m| m.keys().cloned().collect()).unwrap_or_default();
+        let inline_secrets = env
+            .into_iter()
+            .chain(entry.get("headers").and_then(|v| v.as_object()))
+            .flatten()
+            .filter(|(k, v)| {
+                v.as_str()
+                    .is_some_and(|v| !is_reference(v) && (secret_name(k) || v.contains("Bearer ")))
+            })
+            .map(|(k, _)| k.clone())
+            .collect();
+        out.push(ConfiguredServer {
+            name: name.clone(),
+            target,
+            env_keys,
+            inline_secrets,
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_command_and_url_entries() {
+        let doc = json!({"mcpServers": {
+            "fs": {"command": "npx", "args": ["-y", "@scope/server fs"], "env": {"TOKEN": "x"}},
+            "web": {"url": "https://example.com/mcp"}
+        }});
+        let servers = parse(&doc).unwrap();
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].target, "npx -y '@scope/server fs'");
+        assert_eq!(servers[0].env_keys, vec!["TOKEN".to_string()]);
+        assert_eq!(servers[1].target, "https://example.com/mcp");
+        assert_eq!(servers[0].inline_secrets, vec!["TOKEN".to_string()]);
+    }
+
+    #[test]
+    fn reads_zed_settings_with_comments() {
+        let raw = r#"{
+  // editor settings
+  "theme": "One Dark", /* block */
+  "context_servers": {
+    "gh": {"command": {"path": "gh-mcp", "args": ["--stdio"],
+           "env": {"GITHUB_TOKEN": "${GITHUB_TOKEN}", "URL": "http://x//y"}}},
+  },
+}"#;
+        let servers = parse(&parse_document(raw).unwrap()).unwrap();
+        assert_eq!(servers[0].target, "gh-mcp --stdio");
+        assert_eq!(servers[0].env_keys, vec!["GITHUB_TOKEN", "URL"]);
+        assert!(servers[0].inline_secrets.is_empty());
+    }
+
+    #[test]
+    fn rejects_missing_map() {
+        assert!(parse(&json!({"other": {}})).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+//! Well-known MCP client config locations (`discover`).
+//!
+//! Per client, the files it reads its server list from, relative to the
+//! home directory (user-wide) or the current directory (project-local):
+//!   Claude Desktop   Library/Application Support/Claude/claude_desktop_config.json,
+//!                    .config/Claude/claude_desktop_config.json, AppData/Roaming/Claude/...
+//!   Claude Code      .claude.json (top level and per project), ./.mcp.json
+//!   Cursor           .cursor/mcp.json, ./.cursor/mcp.json
+//!   VS Code          Code/User/settings.json + mcp.json (per platform), ./.vscode/mcp.json
+//!   Cline            Code/User/globalStorage/saoudrizwan.claude-dev/settings/cline_mcp_settings.json
+//!   Windsurf         .codeium/windsurf/mcp_config.json
+//!   Zed              .config/zed/settings.json, ./.zed/settings.json
+//!   Gemini CLI       .gemini/settings.json, ./.gemini/settings.json
+//!   generic          ./mcp.json
+//!
+//! `$APPDATA` is used for the Windows paths when set.
+
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::client_config::{self, ConfiguredServer};
+
+/// A config file to look at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub client: &'static str,
+    pub path: PathBuf,
+}
+
+/// VS Code user directories (Linux, macOS, Windows).
+fn vscode_user_dirs(home: &Path, appdata: &Path) -> Vec<PathBuf> {
+    vec![
+        home.join(".config/Code/User"),
+        home.join("Library/Application Support/Code/User"),
+        appdata.join("Code/User"),
+    ]
+}
+
+/// Candidate config files below `home` (user-wide) and `cwd` (project).
+pub fn locations(home: &Path, cwd: &Path) -> Vec<Location> {
+    let appdata = std::env::var("APPDATA")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map_or_else(|| home.join("AppData/Roaming"), PathBuf::from);
+    let mut out = Vec::new();
+    let mut add = |client: &'static str, path: PathBuf| out.push(Location { client, path });
+    for dir in [
+        home.join("Library/Application Support/Claude"),
+        home.join(".config/Claude"),
+        appdata.join("Claude"),
+    ] {
+        add("Claude Desktop", dir.join("claude_desktop_config.json"));
+    }
+    add("Claude Code", home.join(".claude.json"));
+    add("Claude Code", cwd.join(".mcp.json"));
+    add("Cursor", home.join(".cursor/mcp.json"));
+    add("Cursor", cwd.join(".cursor/mcp.json"));
+    for dir in vscode_user_dirs(home, &appdata) {
+        add("VS Code", dir.join("settings.json"));
+        add("VS Code", dir.join("mcp.json"));
+        add(
+            "Cline",
+            dir.join("globalStorage/saoudrizwan.claude-dev/settings/cline_mcp_settings.json"),
+        );
+    }
+    add("VS Code", cwd.join(".vscode/mcp.json"));
+    add("Windsurf", home.join(".codeium/windsurf/mcp_config.json"));
+    add("Zed", home.join(".config/zed/settings.json"));
+    add("Zed", cwd.join(".zed/settings.json"));
+    add("Gemini CLI", home.join(".gemini/settings.json"));
+    add("Gemini CLI", cwd.join(".gemini/settings.json"));
+    add("generic", cwd.join("mcp.json"));
+    out.dedup_by(|a, b| a.path == b.path);
+    out
+}
+
+/// Servers configured in one document. Claude Code's `.claude.json` also
+/// keeps per-project lists (`projects.<dir>.mcpServers`), reported as
+/// `<dir>:<name>`. Ok(empty) when the document configures no servers.
+pub fn servers(doc: &Value) -> Result<Vec<ConfiguredServer>> {
+    let mut out = match client_config::parse(doc) {
+        Ok(s) => s,
+        Err(e) if e.to_string().starts_with("no \"mcpServers\"") => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    for (dir, project) in doc
+        .get("projects")
+        .and_then(|v| v.as_object())
+        .into_iter()
+        .flatten()
+    {
+        if project.get("mcpServers").is_none() {
+            continue;
+        }
+        for mut s in client_config::parse(project)? {
+            s.name = format!("{dir}:{}", s.name);
+            out.push(s);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lists_locations_and_project_servers() {
+        let locs = locations(Path::new("/h"), Path::new("/w"));
+        assert!(
+            locs.iter()
+                .any(|l| l.client == "Cursor" && l.path == Path::new("/h/.cursor/mcp.json"))
+        );
+        assert!(
+            locs.iter()
+                .any(|l| l.path == Path::new("/w/.vscode/mcp.json"))
+        );
+
+        let doc = json!({
+            "mcpServers": {"fs": {"command": "npx", "args": ["server-fs"]}},
+            "projects": {"/w": {"mcpServers": {"db": {"url": "https://db/mcp"}}}, "/x": {}}
+        });
+        let names: Vec<String> = servers(&doc).unwrap().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["fs", "/w:db"]);
+        assert!(servers(&json!({"theme": "dark"})).unwrap().is_empty());
+    }
+}
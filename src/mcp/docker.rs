@@ -0,0 +1,107 @@
+//! `docker:` targets: an MCP server shipped as a container image.
+//!
+//!   -t "docker:IMAGE[:TAG] [-- ARG...]"   (or --docker IMAGE)
+//!
+//! runs `docker run -i --rm [-v MOUNT]... [-e KEY]... IMAGE [ARG...]`:
+//! stdin / stdout stay attached for the protocol and the container is
+//! removed when the session ends. `--volume HOST:CONTAINER[:ro]` mounts are
+//! added (a relative host path is made absolute); `--env` / `--env-file`
+//! variables are forwarded by name (`-e KEY`, docker takes the value from its
+//! own environment, so secrets stay off the command line).
+
+use anyhow::{Context, Result, bail};
+use shell_words::split as shell_split;
+use std::sync::OnceLock;
+
+use super::spawn;
+
+/// Target prefix.
+pub const SCHEME: &str = "docker:";
+
+static VOLUMES: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Install the `--volume` mounts of docker targets (first call wins).
+pub fn set_volumes(volumes: &[String]) -> Result<()> {
+    let mut mounts = Vec::with_capacity(volumes.len());
+    for v in volumes {
+        let Some((host, container)) = v.split_once(':') else {
+            bail!("expected HOST:CONTAINER[:ro], got '{v}'");
+        };
+        if host.is_empty() || container.is_empty() {
+            bail!("expected HOST:CONTAINER[:ro], got '{v}'");
+        }
+        // Bare names are docker named volumes; paths are bind mounts.
+        let host = if host.contains('/') || host.starts_with('.') {
+            std::path::absolute(host)
+                .with_context(|| format!("invalid volume path '{host}'"))?
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            host.to_string()
+        };
+        mounts.push(format!("{host}:{container}"));
+    }
+    let _ = VOLUMES.set(mounts);
+    Ok(())
+}
+
+fn volumes() -> &'static [String] {
+    VOLUMES.get().map(Vec::as_slice).unwrap_or_default()
+}
+
+/// `docker run` arguments for the part of a target after `docker:`.
+pub fn run_args(rest: &str) -> Result<Vec<String>> {
+    let mut parts = shell_split(rest)
+        .context("Failed to parse docker target (shell splitting)")?
+        .into_iter();
+    let Some(image) = parts
+        .next()
+        .filter(|i| !i.is_empty() && !i.starts_with('-'))
+    else {
+        bail!("docker target needs an image: docker:IMAGE[:TAG] [-- ARG...]");
+    };
+    let mut args: Vec<String> = ["run", "-i", "--rm"].map(String::from).to_vec();
+    for mount in volumes() {
+        args.extend(["-v".to_string(), mount.clone()]);
+    }
+    let mut keys: Vec<String> = Vec::new();
+    for (key, _) in spawn::env() {
+        if !keys.contains(&key) {
+            args.extend(["-e".to_string(), key.clone()]);
+            keys.push(key);
+        }
+    }
+    args.push(image);
+    let mut rest: Vec<String> = parts.collect();
+    if rest.first().is_some_and(|a| a == "--") {
+        rest.remove(0);
+    }
+    args.extend(rest);
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_docker_run_arguments() {
+        assert_eq!(
+            run_args("mcp/fetch:latest -- --ignore-robots-txt 'a b'").unwrap(),
+            vec![
+                "run",
+                "-i",
+                "--rm",
+                "mcp/fetch:latest",
+                "--ignore-robots-txt",
+                "a b"
+            ]
+        );
+        assert_eq!(
+            run_args("ghcr.io/org/srv").unwrap(),
+            vec!["run", "-i", "--rm", "ghcr.io/org/srv"]
+        );
+        assert!(run_args("").is_err());
+        assert!(run_args("-- x").is_err());
+    }
+}
@@ -0,0 +1,276 @@
+//! Scripting hooks (`--hook FILE`, embedded Rhai).
+//!
+//! Custom logic without forking the crate: the hook is a [Rhai](https://rhai.rs)
+//! script evaluated in-process. It may define any of these functions; JSON
+//! values are passed as Rhai object maps / arrays:
+//!
+//! ```text
+//! fn pre_request(ctx)    ctx: #{target, tool, arguments}
+//!     -> map replacing the call's arguments, or () to keep them
+//! fn post_response(ctx)  ctx: #{target, tool, arguments, result, elapsed_ms}
+//!     -> array of findings, or ()
+//! fn scan(ctx)           ctx: #{target, initialize, tools}
+//!     -> array of findings, or ()
+//! ```
+//!
+//! A finding is `#{severity: "high", title: "...", detail: "...",
+//! evidence: "...", tool: "..."}`; severity and title are required.
+//!
+//! ```text
+//! fn pre_request(ctx) {
+//!     let args = ctx.arguments;
+//!     if "path" in args { args.path = "../../etc/passwd"; }
+//!     args
+//! }
+//! fn post_response(ctx) {
+//!     let text = `${ctx.result}`;
+//!     if text.contains("root:x:0:0") {
+//!         [#{severity: "high", title: "passwd contents returned", evidence: "root:x:0:0"}]
+//!     }
+//! }
+//! ```
+//!
+//! Tool-call hooks run for every call made through exec::invoke_tool (exec)
+//! and exec::ToolSession (fuzz); `scan` runs once per scan after the
+//! built-in checks. Findings carry check id `hook`; post-response findings
+//! are collected until the command takes them (`take_findings`).
+//!
+//! Top-level statements run once when the script is loaded. Scripts have no
+//! file, network or process access; `print` / `debug` go to stderr. A script
+//! that does not compile is rejected at startup; a hook function that fails
+//! at run time is reported on stderr and otherwise ignored.
+
+use anyhow::{Context, Result, anyhow};
+use rhai::{AST, CallFnOptions, Dynamic, Engine, Scope};
+use serde_json::{Map, Value, json};
+use std::sync::{Mutex, OnceLock};
+
+use crate::scan::{Finding, ScanInput, Severity};
+use crate::utils::logging::{LogLevel, current_log_level};
+
+struct Hook {
+    engine: Engine,
+    ast: AST,
+    findings: Mutex<Vec<Finding>>,
+}
+
+static HOOK: OnceLock<Hook> = OnceLock::new();
+
+/// Load and install the hook script for this process.
+pub fn install(path: &str) -> Result<()> {
+    let source = std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+    let hook = Hook::compile(&source)?;
+    let _ = HOOK.set(hook);
+    Ok(())
+}
+
+pub fn installed() -> bool {
+    HOOK.get().is_some()
+}
+
+fn warn(msg: String) {
+    if current_log_level() >= LogLevel::Info {
+        eprintln!("[hook] {msg}");
+    }
+}
+
+impl Hook {
+    fn compile(source: &str) -> Result<Self> {
+        let mut engine = Engine::new();
+        // stdout carries the command's output (JSON included).
+        engine.on_print(|s| eprintln!("[hook] {s}"));
+        engine.on_debug(|s, _, pos| eprintln!("[hook] {pos:?} {s}"));
+        let ast = engine.compile(source).map_err(|e| anyhow!("{e}"))?;
+        engine
+            .run_ast(&ast)
+            .map_err(|e| anyhow!("{e}"))
+            .context("top-level statements failed")?;
+        Ok(Hook {
+            engine,
+            ast,
+            findings: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn defines(&self, name: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == name)
+    }
+
+    /// Call `name(ctx)` when the script defines it; None when it is not
+    /// defined, fails or returns ().
+    fn call(&self, name: &str, ctx: &Value) -> Option<Value> {
+        if !self.defines(name) {
+            return None;
+        }
+        let result = rhai::serde::to_dynamic(ctx).and_then(|ctx| {
+            self.engine.call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false),
+                &mut Scope::new(),
+                &self.ast,
+                name,
+                (ctx,),
+            )
+        });
+        let out = match result {
+            Ok(out) => out,
+            Err(e) => {
+                warn(format!("{name}: {e}"));
+                return None;
+            }
+        };
+        if out.is_unit() {
+            return None;
+        }
+        match rhai::serde::from_dynamic(&out) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                warn(format!("{name}: unusable return value: {e}"));
+                None
+            }
+        }
+    }
+}
+
+/// Findings returned by a hook function (entries without a known severity
+/// or a title are dropped).
+pub fn parse_findings(output: &Value, tool: Option<&str>) -> Vec<Finding> {
+    let str_of = |f: &Value, key: &str| f.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    output
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|f| {
+            let severity = f
+                .get("severity")
+                .and_then(|v| v.as_str())
+                .and_then(Severity::parse)?;
+            let mut finding = Finding::new("hook", severity, str_of(f, "title")?)
+                .detail(str_of(f, "detail").unwrap_or_default());
+            finding.tool = str_of(f, "tool").or_else(|| tool.map(str::to_string));
+            finding.evidence = str_of(f, "evidence");
+            Some(finding)
+        })
+        .collect()
+}
+
+/// Let the hook rewrite a tool call's arguments before it is sent.
+pub fn pre_request(target: &str, tool: &str, arguments: &mut Map<String, Value>) {
+    let Some(hook) = HOOK.get() else { return };
+    let ctx = json!({"target": target, "tool": tool, "arguments": arguments});
+    match hook.call("pre_request", &ctx) {
+        Some(Value::Object(replaced)) => *arguments = replaced,
+        Some(other) => warn(format!(
+            "pre_request: expected a map of arguments, got {other}"
+        )),
+        None => {}
+    }
+}
+
+/// Show the hook a tool call's result; its findings are kept for `take_findings`.
+pub fn post_response(
+    target: &str,
+    tool: &str,
+    arguments: &Map<String, Value>,
+    result: &Value,
+    elapsed_ms: u128,
+) {
+    let Some(hook) = HOOK.get() else { return };
+    let ctx = json!({
+        "target": target,
+        "tool": tool,
+        "arguments": arguments,
+        "result": result,
+        "elapsed_ms": elapsed_ms,
+    });
+    if let Some(out) = hook.call("post_response", &ctx)
+        && let Ok(mut found) = hook.findings.lock()
+    {
+        found.extend(parse_findings(&out, Some(tool)));
+    }
+}
+
+/// Findings emitted by post-response hooks since the last call.
+pub fn take_findings() -> Vec<Finding> {
+    HOOK.get()
+        .and_then(|h| h.findings.lock().ok().map(|mut f| std::mem::take(&mut *f)))
+        .unwrap_or_default()
+}
+
+/// Findings the hook adds to a scan.
+pub fn scan(input: &ScanInput) -> Vec<Finding> {
+    let Some(hook) = HOOK.get() else {
+        return Vec::new();
+    };
+    let ctx = json!({
+        "target": input.target,
+        "initialize": input.initialize,
+        "tools": input.tools,
+    });
+    hook.call("scan", &ctx)
+        .map(|out| parse_findings(&out, None))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hook_findings() {
+        let out = json!([
+            {"severity": "high", "title": "token echoed", "evidence": "tok-1"},
+            {"severity": "medium", "title": "other tool", "tool": "b"},
+            {"severity": "bogus", "title": "dropped"},
+            {"severity": "low"},
+        ]);
+        let f = parse_findings(&out, Some("a"));
+        assert_eq!(f.len(), 2);
+        assert_eq!(f[0].check, "hook");
+        assert_eq!(f[0].severity, Severity::High);
+        assert_eq!(f[0].tool.as_deref(), Some("a"));
+        assert_eq!(f[0].evidence.as_deref(), Some("tok-1"));
+        assert_eq!(f[1].tool.as_deref(), Some("b"));
+        assert!(parse_findings(&json!({}), None).is_empty());
+    }
+
+    #[test]
+    fn runs_script_functions() {
+        let hook = Hook::compile(
+            r#"
+            fn pre_request(ctx) {
+                let args = ctx.arguments;
+                args.path = "../" + args.path;
+                args
+            }
+            fn post_response(ctx) {
+                if ctx.result.text.contains("secret") {
+                    [#{severity: "high", title: "leak in " + ctx.tool}]
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let args = hook.call(
+            "pre_request",
+            &json!({"tool": "read", "arguments": {"path": "a"}}),
+        );
+        assert_eq!(args, Some(json!({"path": "../a"})));
+        let out = hook
+            .call(
+                "post_response",
+                &json!({"tool": "read", "result": {"text": "a secret"}}),
+            )
+            .unwrap();
+        assert_eq!(parse_findings(&out, Some("read"))[0].title, "leak in read");
+        // () and undefined functions change nothing.
+        assert!(
+            hook.call(
+                "post_response",
+                &json!({"tool": "read", "result": {"text": ""}})
+            )
+            .is_none()
+        );
+        assert!(hook.call("scan", &json!({})).is_none());
+        assert!(Hook::compile("fn broken( {").is_err());
+    }
+}
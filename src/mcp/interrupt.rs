@@ -0,0 +1,201 @@
+//! Ctrl-C during a tool call (exec / fuzz).
+//!
+//! A process-wide listener handles SIGINT from [`install`] (main) or once
+//! any [`Watch`] was started. While a watch is live (spawn, initialize, tools/list, tools/call):
+//!   1. the in-flight tools/call gets `notifications/cancelled`
+//!      (reason "interrupted") so the server can stop its work
+//!   2. the session is shut down (stdin closed, the server given a moment
+//!      to exit, then killed) instead of leaving an orphaned server behind
+//!   3. the call fails with [`Interrupted`]; callers stop and exit with
+//!      status 130 ([`EXIT_CODE`])
+//!
+//! With no live watch (between fuzz requests, while prompting), or on a
+//! second Ctrl-C, the process exits with status 130 at once, killing the
+//! servers it spawned (crate::mcp::spawn::kill_children).
+//!
+//! Once a watch has fired, [`interrupted`] stays true for the process.
+//! SIGTERM (unix) also kills the spawned servers, then exits with 143.
+
+use anyhow::Result;
+use rmcp::model::{CallToolRequestParam, CallToolResult, CancelledNotificationParam};
+use rmcp::service::{Peer, RoleClient};
+use std::future::Future;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Exit status after an interrupt (128 + SIGINT).
+pub const EXIT_CODE: i32 = 130;
+
+/// How long the server gets to exit after an interrupt.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static SIGNAL: OnceLock<watch::Receiver<bool>> = OnceLock::new();
+
+/// Install the SIGINT listener (a task on the process runtime, so it
+/// outlives the session that started it) and return its channel.
+fn listener() -> watch::Receiver<bool> {
+    SIGNAL
+        .get_or_init(|| {
+            let (tx, rx) = watch::channel(false);
+            tokio::spawn(async move {
+                while tokio::signal::ctrl_c().await.is_ok() {
+                    if LIVE.load(Ordering::SeqCst) == 0 || interrupted() {
+                        super::spawn::kill_children();
+                        std::process::exit(EXIT_CODE);
+                    }
+                    INTERRUPTED.store(true, Ordering::SeqCst);
+                    let _ = tx.send(true);
+                }
+            });
+            rx
+        })
+        .clone()
+}
+
+/// Handle Ctrl-C (and SIGTERM) for the whole run, so no exit leaves spawned
+/// servers behind. Needs the Tokio runtime.
+pub fn install() {
+    listener();
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        if let Ok(mut term) = signal(SignalKind::terminate()) {
+            tokio::spawn(async move {
+                if term.recv().await.is_some() {
+                    super::spawn::kill_children();
+                    std::process::exit(128 + 15);
+                }
+            });
+        }
+    }
+}
+
+/// Whether Ctrl-C interrupted a call in this process.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Error of an operation cut short by Ctrl-C.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl std::fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("interrupted (Ctrl-C)")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// Ctrl-C handling for one session. The default one does not listen
+/// (Ctrl-C keeps its usual effect, e.g. while prompting on the terminal).
+#[derive(Default)]
+pub struct Watch {
+    rx: Option<watch::Receiver<bool>>,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        if self.rx.is_some() {
+            LIVE.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Watch {
+    /// Cancel the session on Ctrl-C until this watch is dropped. Must be
+    /// called within the Tokio runtime.
+    pub fn start() -> Self {
+        let rx = listener();
+        LIVE.fetch_add(1, Ordering::SeqCst);
+        Self { rx: Some(rx) }
+    }
+
+    async fn fired(&self) {
+        let Some(mut rx) = self.rx.clone() else {
+            return std::future::pending().await;
+        };
+        if rx.wait_for(|hit| *hit).await.is_err() {
+            // Listener gone (no signal support): never fire.
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// `fut`, or [`Interrupted`] when Ctrl-C comes first.
+    pub async fn guard<F: Future>(&self, fut: F) -> Result<F::Output> {
+        tokio::select! {
+            out = fut => Ok(out),
+            _ = self.fired() => Err(Interrupted.into()),
+        }
+    }
+
+    /// `tools/call` that is cancelled on Ctrl-C: the server is sent
+    /// `notifications/cancelled` for it and the call fails with
+    /// [`Interrupted`] (then [`shutdown`] the session).
+    /// The outer Result is the interrupt, the inner one the call itself.
+    pub async fn call_tool(
+        &self,
+        peer: &Peer<RoleClient>,
+        params: CallToolRequestParam,
+    ) -> Result<Result<CallToolResult, rmcp::ServiceError>> {
+        use rmcp::model::{CallToolRequest, ClientRequest, ServerResult};
+        use rmcp::service::PeerRequestOptions;
+
+        let request = ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params,
+            extensions: Default::default(),
+        });
+        let handle = match peer
+            .send_cancellable_request(request, PeerRequestOptions::no_options())
+            .await
+        {
+            Ok(h) => h,
+            Err(e) => return Ok(Err(e)),
+        };
+        let request_id = handle.id.clone();
+        tokio::select! {
+            response = handle.await_response() => {
+                Ok(response.and_then(|r| match r {
+                    ServerResult::CallToolResult(r) => Ok(r),
+                    _ => Err(rmcp::ServiceError::UnexpectedResponse),
+                }))
+            }
+            _ = self.fired() => {
+                let _ = peer
+                    .notify_cancelled(CancelledNotificationParam {
+                        request_id,
+                        reason: Some("interrupted".into()),
+                    })
+                    .await;
+                Err(Interrupted.into())
+            }
+        }
+    }
+}
+
+/// Close the session after an interrupt, killing a server that does not
+/// exit within the grace period.
+pub async fn shutdown<S: rmcp::Service<RoleClient>>(session: super::session::McpSession<S>) {
+    let _ = tokio::time::timeout(SHUTDOWN_GRACE, session.shutdown()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_error_is_recognizable() {
+        let err = anyhow::Error::from(Interrupted).context("tool invocation failed: scan");
+        assert!(err.downcast_ref::<Interrupted>().is_some());
+        assert_eq!(
+            format!("{err:#}"),
+            "tool invocation failed: scan: interrupted (Ctrl-C)"
+        );
+        assert!(!interrupted());
+    }
+}
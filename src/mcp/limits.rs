@@ -0,0 +1,105 @@
+//! Global operation timeouts (`--connect-timeout`, `--request-timeout`).
+//!
+//! connect  spawning the server and the initialize handshake
+//! request  every later request / response round trip
+//!
+//! Both apply on top of a command's own `--timeout` (the shorter one wins),
+//! so a dead or slow target fails fast with a "timed out" error instead of
+//! hanging enumeration. Without --connect-timeout, start-up through the rmcp
+//! client still gives up after [`DEFAULT_CONNECT`]: a server that never
+//! answers initialize would otherwise hang the command.
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::Instrument;
+
+/// Initialize timeout when --connect-timeout is not set.
+pub const DEFAULT_CONNECT: Duration = Duration::from_secs(30);
+
+static CONNECT: OnceLock<Duration> = OnceLock::new();
+static REQUEST: OnceLock<Duration> = OnceLock::new();
+
+/// Install the timeouts for this process (first call wins).
+pub fn set(connect: Option<Duration>, request: Option<Duration>) {
+    if let Some(d) = connect {
+        let _ = CONNECT.set(d);
+    }
+    if let Some(d) = request {
+        let _ = REQUEST.set(d);
+    }
+}
+
+pub fn connect_timeout() -> Option<Duration> {
+    CONNECT.get().copied()
+}
+
+pub fn request_timeout() -> Option<Duration> {
+    REQUEST.get().copied()
+}
+
+/// `own` capped by `limit`, if any.
+pub fn cap(own: Duration, limit: Option<Duration>) -> Duration {
+    limit.map_or(own, |l| own.min(l))
+}
+
+fn shown(d: Duration) -> String {
+    if d.subsec_millis() == 0 {
+        format!("{}s", d.as_secs())
+    } else {
+        format!("{}ms", d.as_millis())
+    }
+}
+
+async fn within<F: Future>(limit: Option<Duration>, what: String, fut: F) -> Result<F::Output> {
+    match limit {
+        Some(d) => tokio::time::timeout(d, fut).await.map_err(|_| {
+            tracing::debug!("{what} timed out after {}", shown(d));
+            anyhow::anyhow!("{what} timed out after {}", shown(d))
+        }),
+        None => Ok(fut.await),
+    }
+}
+
+/// Await a server start-up (spawn + initialize) within --connect-timeout
+/// ([`DEFAULT_CONNECT`] when unset).
+pub async fn connect<F: Future>(fut: F) -> Result<F::Output> {
+    match connect_timeout() {
+        Some(d) => within(Some(d), "initialize (--connect-timeout)".into(), fut).await,
+        None => within(Some(DEFAULT_CONNECT), "initialize".into(), fut).await,
+    }
+}
+
+/// Await one request within --request-timeout (traced as a `request` span).
+pub async fn request<F: Future>(method: &str, fut: F) -> Result<F::Output> {
+    within(
+        request_timeout(),
+        format!("{method} (--request-timeout)"),
+        fut,
+    )
+    .instrument(tracing::trace_span!("request", method))
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn caps_and_reports_timeouts() {
+        assert_eq!(
+            cap(Duration::from_secs(30), Some(Duration::from_secs(5))),
+            Duration::from_secs(5)
+        );
+        assert_eq!(cap(Duration::from_secs(3), None), Duration::from_secs(3));
+        let err = within(
+            Some(Duration::from_millis(10)),
+            "tools/list".into(),
+            tokio::time::sleep(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "tools/list timed out after 10ms");
+    }
+}
@@ -0,0 +1,289 @@
+//! Mock MCP server logic (`serve`).
+//!
+//! Answers JSON-RPC messages from a snapshot file instead of a real backend:
+//!
+//! ```json
+//! {
+//!   "server": {"name": "fake-fs", "version": "1.0.0"},
+//!   "instructions": "optional",
+//!   "tools": [{"name": "read_file", "description": "...", "inputSchema": {...}}],
+//!   "responses": {
+//!     "read_file": {"content": [{"type": "text", "text": "contents of {{path}}"}]},
+//!     "ping_host": {"text": "pong from {{host}}"},
+//!     "fail": {"isError": true, "text": "denied"}
+//!   },
+//!   "resources": [{"uri": "file:///etc/motd", "name": "motd", "text": "hello"}],
+//!   "prompts": [{"name": "greet", "description": "...", "messages": [...]}]
+//! }
+//! ```
+//!
+//! Tool definitions may also come from any snapshot `diff` reads (a bare
+//! array, a tools/list result, `raw --json` output). A response is either a
+//! full CallToolResult or the `{"text", "isError"}` shorthand; `{{arg}}` in
+//! its strings is replaced by the call's argument. Tools without a canned
+//! response echo their arguments. Capabilities are advertised for what the
+//! snapshot contains.
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value, json};
+
+use crate::scan::diff::snapshot_tools;
+
+/// A server answering from a snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct MockServer {
+    server: Value,
+    instructions: Option<String>,
+    tools: Vec<Value>,
+    responses: Map<String, Value>,
+    resources: Vec<Value>,
+    prompts: Vec<Value>,
+}
+
+fn array(doc: &Value, key: &str) -> Vec<Value> {
+    doc.get(key)
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Replace `{{name}}` in every string of `v` with the argument's text.
+fn fill(v: &mut Value, args: &Map<String, Value>) {
+    match v {
+        Value::String(s) if s.contains("{{") => {
+            for (k, a) in args {
+                let text = match a {
+                    Value::String(t) => t.clone(),
+                    other => other.to_string(),
+                };
+                *s = s.replace(&format!("{{{{{k}}}}}"), &text);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|i| fill(i, args)),
+        Value::Object(map) => map.values_mut().for_each(|i| fill(i, args)),
+        _ => {}
+    }
+}
+
+fn rpc_error(id: &Value, code: i64, message: String) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": message}})
+}
+
+fn by_key<'a>(items: &'a [Value], key: &str, want: &str) -> Option<&'a Value> {
+    items
+        .iter()
+        .find(|i| i.get(key).and_then(|v| v.as_str()) == Some(want))
+}
+
+impl MockServer {
+    /// Build from a snapshot document.
+    pub fn from_snapshot(doc: &Value) -> Result<Self> {
+        let tools = snapshot_tools(doc).unwrap_or_default();
+        let (resources, prompts) = (array(doc, "resources"), array(doc, "prompts"));
+        if tools.is_empty() && resources.is_empty() && prompts.is_empty() {
+            bail!("no tools, resources or prompts found");
+        }
+        Ok(MockServer {
+            server: doc.get("server").cloned().unwrap_or_else(
+                || json!({"name": "mcp-hack-mock", "version": env!("CARGO_PKG_VERSION")}),
+            ),
+            instructions: doc
+                .get("instructions")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            tools,
+            responses: doc
+                .get("responses")
+                .and_then(|v| v.as_object())
+                .cloned()
+                .unwrap_or_default(),
+            resources,
+            prompts,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+        let doc: Value =
+            serde_json::from_str(&text).with_context(|| format!("{path}: invalid JSON"))?;
+        Self::from_snapshot(&doc).with_context(|| format!("snapshot {path}"))
+    }
+
+    pub fn counts(&self) -> (usize, usize, usize) {
+        (self.tools.len(), self.resources.len(), self.prompts.len())
+    }
+
+    fn capabilities(&self) -> Value {
+        let mut caps = Map::new();
+        if !self.tools.is_empty() {
+            caps.insert("tools".into(), json!({"listChanged": false}));
+        }
+        if !self.resources.is_empty() {
+            caps.insert("resources".into(), json!({"listChanged": false}));
+        }
+        if !self.prompts.is_empty() {
+            caps.insert("prompts".into(), json!({"listChanged": false}));
+        }
+        Value::Object(caps)
+    }
+
+    fn call_tool(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if by_key(&self.tools, "name", name).is_none() {
+            return Err((-32602, format!("Unknown tool: {name}")));
+        }
+        let args = params
+            .get("arguments")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let mut result = match self.responses.get(name) {
+            Some(r) if r.get("content").is_some() => r.clone(),
+            Some(r) => json!({
+                "content": [{"type": "text", "text": r.get("text").and_then(|v| v.as_str()).unwrap_or("")}],
+                "isError": r.get("isError").and_then(|v| v.as_bool()).unwrap_or(false),
+            }),
+            None => {
+                json!({"content": [{"type": "text", "text": Value::Object(args.clone()).to_string()}]})
+            }
+        };
+        fill(&mut result, &args);
+        Ok(result)
+    }
+
+    fn read_resource(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let uri = params.get("uri").and_then(|v| v.as_str()).unwrap_or("");
+        let Some(r) = by_key(&self.resources, "uri", uri) else {
+            return Err((-32002, format!("Resource not found: {uri}")));
+        };
+        let mut content = json!({"uri": uri});
+        for key in ["mimeType", "text", "blob"] {
+            if let Some(v) = r.get(key) {
+                content[key] = v.clone();
+            }
+        }
+        if content.get("text").is_none() && content.get("blob").is_none() {
+            content["text"] = "".into();
+        }
+        Ok(json!({"contents": [content]}))
+    }
+
+    fn get_prompt(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let Some(p) = by_key(&self.prompts, "name", name) else {
+            return Err((-32602, format!("Unknown prompt: {name}")));
+        };
+        let args = params
+            .get("arguments")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+        let mut result = json!({
+            "description": p.get("description").cloned().unwrap_or(Value::Null),
+            "messages": p.get("messages").cloned().unwrap_or_else(|| json!([])),
+        });
+        fill(&mut result, &args);
+        Ok(result)
+    }
+
+    /// Answer one message; None for notifications and responses.
+    pub fn handle(&self, msg: &Value) -> Option<Value> {
+        let id = msg.get("id").filter(|v| !v.is_null())?.clone();
+        let method = msg.get("method").and_then(|v| v.as_str())?;
+        let params = msg.get("params").cloned().unwrap_or(Value::Null);
+        let listing = |key: &str, items: &[Value]| {
+            let strip = |v: &Value| {
+                let mut v = v.clone();
+                if let Some(m) = v.as_object_mut() {
+                    m.remove("text");
+                    m.remove("blob");
+                    m.remove("messages");
+                }
+                v
+            };
+            Ok(json!({ key: items.iter().map(strip).collect::<Vec<_>>() }))
+        };
+        let result = match method {
+            "initialize" => {
+                let version = params
+                    .get("protocolVersion")
+                    .cloned()
+                    .unwrap_or_else(|| json!(rmcp::model::ProtocolVersion::LATEST));
+                let mut r = json!({
+                    "protocolVersion": version,
+                    "capabilities": self.capabilities(),
+                    "serverInfo": self.server,
+                });
+                if let Some(i) = &self.instructions {
+                    r["instructions"] = i.clone().into();
+                }
+                Ok(r)
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({"tools": self.tools})),
+            "tools/call" => self.call_tool(&params),
+            "resources/list" => listing("resources", &self.resources),
+            "resources/templates/list" => Ok(json!({"resourceTemplates": []})),
+            "resources/read" => self.read_resource(&params),
+            "prompts/list" => listing("prompts", &self.prompts),
+            "prompts/get" => self.get_prompt(&params),
+            other => Err((-32601, format!("Method not found: {other}"))),
+        };
+        Some(match result {
+            Ok(r) => json!({"jsonrpc": "2.0", "id": id, "result": r}),
+            Err((code, message)) => rpc_error(&id, code, message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_from_snapshot() {
+        let s = MockServer::from_snapshot(&json!({
+            "tools": [{"name": "read_file", "inputSchema": {"type": "object"}}, {"name": "echo"}],
+            "responses": {"read_file": {"text": "contents of {{path}}"}},
+            "resources": [{"uri": "file:///motd", "name": "motd", "text": "hi"}],
+        }))
+        .unwrap();
+        let call = |m: &str, p: Value| {
+            s.handle(&json!({"jsonrpc": "2.0", "id": 1, "method": m, "params": p}))
+                .unwrap()
+        };
+
+        let init = call("initialize", json!({"protocolVersion": "2025-06-18"}));
+        assert_eq!(init["result"]["protocolVersion"], "2025-06-18");
+        assert!(init["result"]["capabilities"].get("prompts").is_none());
+
+        let r = call(
+            "tools/call",
+            json!({"name": "read_file", "arguments": {"path": "/etc/passwd"}}),
+        );
+        assert_eq!(r["result"]["content"][0]["text"], "contents of /etc/passwd");
+        let r = call("tools/call", json!({"name": "echo", "arguments": {"a": 1}}));
+        assert_eq!(r["result"]["content"][0]["text"], "{\"a\":1}");
+        assert_eq!(
+            call("tools/call", json!({"name": "nope"}))["error"]["code"],
+            -32602
+        );
+
+        assert!(
+            call("resources/list", json!({}))["result"]["resources"][0]
+                .get("text")
+                .is_none()
+        );
+        assert_eq!(
+            call("resources/read", json!({"uri": "file:///motd"}))["result"]["contents"][0]["text"],
+            "hi"
+        );
+        assert_eq!(call("bogus", json!({}))["error"]["code"], -32601);
+        assert!(
+            s.handle(&json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+                .is_none()
+        );
+        assert!(MockServer::from_snapshot(&json!({"server": {}})).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Server-initiated notifications received while a tool call runs.
+//!
+//! Besides `notifications/progress` (see crate::mcp::progress) a server may
+//! send, while handling a request:
+//!   notifications/message                  log message (level, logger, data)
+//!   notifications/resources/updated        a subscribed resource changed
+//!   notifications/resources/list_changed   / tools/ / prompts/ list changed
+//!   notifications/cancelled                the server gave up on a request
+//! The client handler ([`crate::mcp::progress::ProgressClient`]) records them
+//! here once a command calls [`enable`] (exec does); [`take`] hands them to
+//! the output as a `notifications` array (JSON) or a list after the result.
+
+use serde_json::{Value, json};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RECEIVED: Mutex<Vec<Notification>> = Mutex::new(Vec::new());
+
+/// Collect notifications for the rest of this process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// One notification, `elapsed_ms` after the session started.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub method: String,
+    pub params: Value,
+    pub elapsed_ms: u128,
+}
+
+impl Notification {
+    pub fn to_json(&self) -> Value {
+        let mut v = json!({"method": self.method, "elapsed_ms": self.elapsed_ms});
+        if !self.params.is_null() {
+            v["params"] = self.params.clone();
+        }
+        v
+    }
+
+    /// One line for the human output, e.g. `[warning] db: slow query`.
+    pub fn describe(&self) -> String {
+        let text = |key: &str| match self.params.get(key) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        match self.method.as_str() {
+            "notifications/message" => {
+                let logger = text("logger");
+                if logger.is_empty() {
+                    format!("[{}] {}", text("level"), text("data"))
+                } else {
+                    format!("[{}] {logger}: {}", text("level"), text("data"))
+                }
+            }
+            "notifications/resources/updated" => format!("resource updated: {}", text("uri")),
+            "notifications/resources/list_changed" => "resource list changed".to_string(),
+            "notifications/tools/list_changed" => "tool list changed".to_string(),
+            "notifications/prompts/list_changed" => "prompt list changed".to_string(),
+            "notifications/cancelled" => {
+                let reason = text("reason");
+                if reason.is_empty() {
+                    format!("request {} cancelled", text("requestId"))
+                } else {
+                    format!("request {} cancelled: {reason}", text("requestId"))
+                }
+            }
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Record a notification (no-op until [`enable`]).
+pub fn record(method: &str, params: Value, elapsed_ms: u128) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(mut received) = RECEIVED.lock() {
+        received.push(Notification {
+            method: method.to_string(),
+            params,
+            elapsed_ms,
+        });
+    }
+}
+
+/// Notifications recorded since the last call, in arrival order.
+pub fn take() -> Vec<Notification> {
+    RECEIVED
+        .lock()
+        .map(|mut r| std::mem::take(&mut *r))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_notifications() {
+        let log = Notification {
+            method: "notifications/message".into(),
+            params: json!({"level": "warning", "logger": "db", "data": "slow query"}),
+            elapsed_ms: 12,
+        };
+        assert_eq!(log.describe(), "[warning] db: slow query");
+        assert_eq!(log.to_json()["params"]["logger"], "db");
+
+        let structured = Notification {
+            method: "notifications/message".into(),
+            params: json!({"level": "info", "data": {"rows": 3}}),
+            elapsed_ms: 0,
+        };
+        assert_eq!(structured.describe(), r#"[info] {"rows":3}"#);
+
+        let changed = Notification {
+            method: "notifications/tools/list_changed".into(),
+            params: Value::Null,
+            elapsed_ms: 40,
+        };
+        assert_eq!(changed.describe(), "tool list changed");
+        assert_eq!(
+            changed.to_json(),
+            json!({"method": "notifications/tools/list_changed", "elapsed_ms": 40})
+        );
+
+        let updated = Notification {
+            method: "notifications/resources/updated".into(),
+            params: json!({"uri": "file:///tmp/a"}),
+            elapsed_ms: 1,
+        };
+        assert_eq!(updated.describe(), "resource updated: file:///tmp/a");
+    }
+}
@@ -0,0 +1,212 @@
+//! Live `notifications/progress` display while a tool call runs.
+//!
+//! rmcp puts a progressToken on every request, so a server may report
+//! progress for a long `tools/call`. [`ProgressClient`] is the client handler
+//! that shows those updates on stderr as they arrive:
+//!   terminal   one redrawn line: `[progress] [#####-----] 50% (5/10) message`
+//!   otherwise  one `[progress] 5/10 (50%) message` line per update
+//! and as `"phase":"call"` events on --progress-fd. Nothing is shown until a
+//! command calls [`enable`] (exec does; fuzz keeps its own progress bar) or
+//! below info log level (-q).
+//!
+//! The same handler records the server's other notifications (log messages,
+//! list changes, ...) for crate::mcp::notifications.
+
+use rmcp::ClientHandler;
+use rmcp::model::{
+    CancelledNotificationParam, LoggingMessageNotificationParam, ProgressNotificationParam,
+    ResourceUpdatedNotificationParam,
+};
+use rmcp::service::{NotificationContext, RoleClient};
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::mcp::notifications;
+use crate::utils::logging::{LogLevel, current_log_level};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Show progress notifications for the rest of this process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+const BAR_WIDTH: usize = 20;
+
+/// `5/10 (50%) message`; the total and message only when the server sent them.
+pub fn describe(p: &ProgressNotificationParam) -> String {
+    let mut out = match p.total.filter(|t| *t > 0.0) {
+        Some(total) => format!(
+            "{}/{} ({:.0}%)",
+            p.progress,
+            total,
+            (p.progress / total * 100.0).clamp(0.0, 100.0)
+        ),
+        None => p.progress.to_string(),
+    };
+    if let Some(m) = p.message.as_deref().filter(|m| !m.trim().is_empty()) {
+        out.push(' ');
+        out.push_str(m.trim());
+    }
+    out
+}
+
+/// `[#####-----] 50% (5/10) message` (an unknown total shows the count only).
+pub fn bar(p: &ProgressNotificationParam) -> String {
+    let Some(total) = p.total.filter(|t| *t > 0.0) else {
+        return describe(p);
+    };
+    let ratio = (p.progress / total).clamp(0.0, 1.0);
+    let filled = (ratio * BAR_WIDTH as f64).round() as usize;
+    let mut out = format!(
+        "[{}{}] {:.0}% ({}/{})",
+        "#".repeat(filled),
+        "-".repeat(BAR_WIDTH - filled),
+        ratio * 100.0,
+        p.progress,
+        total
+    );
+    if let Some(m) = p.message.as_deref().filter(|m| !m.trim().is_empty()) {
+        out.push(' ');
+        out.push_str(m.trim());
+    }
+    out
+}
+
+/// Client handler rendering progress notifications and recording the
+/// others (see module docs).
+#[derive(Debug)]
+pub struct ProgressClient {
+    started: Instant,
+    tty: bool,
+    /// A terminal line is open (cleared by [`ProgressClient::finish`]).
+    drawn: AtomicBool,
+}
+
+impl Default for ProgressClient {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            tty: std::io::stderr().is_terminal(),
+            drawn: AtomicBool::new(false),
+        }
+    }
+}
+
+impl ProgressClient {
+    fn show(&self, p: &ProgressNotificationParam) {
+        crate::utils::progress_events::emit(
+            "call",
+            &crate::utils::ProgressSnapshot {
+                current: p.progress.max(0.0) as u64,
+                total: p.total.map(|t| t.max(0.0) as u64),
+                elapsed_ms: self.started.elapsed().as_millis(),
+            },
+            p.message.as_deref(),
+        );
+        if !enabled() || current_log_level() < LogLevel::Info {
+            return;
+        }
+        let mut err = std::io::stderr().lock();
+        if self.tty {
+            // \x1b[2K clears what a longer previous update left behind.
+            let _ = write!(err, "\r\x1b[2K[progress] {}", bar(p));
+            let _ = err.flush();
+            self.drawn.store(true, Ordering::Relaxed);
+        } else {
+            let _ = writeln!(err, "[progress] {}", describe(p));
+        }
+    }
+
+    /// End the terminal progress line, if one was drawn.
+    pub fn finish(&self) {
+        if self.drawn.swap(false, Ordering::Relaxed) {
+            eprintln!();
+        }
+    }
+
+    fn record(&self, method: &str, params: impl serde::Serialize) {
+        notifications::record(
+            method,
+            serde_json::to_value(params).unwrap_or_default(),
+            self.started.elapsed().as_millis(),
+        );
+    }
+}
+
+impl ClientHandler for ProgressClient {
+    async fn on_progress(
+        &self,
+        params: ProgressNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.show(&params);
+    }
+
+    async fn on_logging_message(
+        &self,
+        params: LoggingMessageNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.record("notifications/message", params);
+    }
+
+    async fn on_resource_updated(
+        &self,
+        params: ResourceUpdatedNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.record("notifications/resources/updated", params);
+    }
+
+    async fn on_resource_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.record("notifications/resources/list_changed", ());
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.record("notifications/tools/list_changed", ());
+    }
+
+    async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.record("notifications/prompts/list_changed", ());
+    }
+
+    async fn on_cancelled(
+        &self,
+        params: CancelledNotificationParam,
+        _context: NotificationContext<RoleClient>,
+    ) {
+        self.record("notifications/cancelled", params);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::{NumberOrString, ProgressToken};
+
+    #[test]
+    fn formats_progress() {
+        let mut p = ProgressNotificationParam {
+            progress_token: ProgressToken(NumberOrString::Number(0)),
+            progress: 5.0,
+            total: Some(10.0),
+            message: Some("scanning 10.0.0.5".into()),
+        };
+        assert_eq!(describe(&p), "5/10 (50%) scanning 10.0.0.5");
+        assert_eq!(
+            bar(&p),
+            "[##########----------] 50% (5/10) scanning 10.0.0.5"
+        );
+        p.total = None;
+        p.message = None;
+        p.progress = 0.25;
+        assert_eq!(describe(&p), "0.25");
+        assert_eq!(bar(&p), "0.25");
+    }
+}
@@ -0,0 +1,343 @@
+//! Raw JSON-RPC session over a local process's stdio (or a socket target's
+//! stream, see crate::mcp::socket).
+//!
+//! rmcp only speaks the typed MCP method set, so arbitrary / vendor-specific
+//! methods (and deliberately malformed traffic) go through this minimal
+//! newline-delimited JSON-RPC client instead.
+//!
+//! RawSession::spawn -> initialize (optional) -> request / notify -> shutdown
+//!
+//! Commands that only list and call go through crate::mcp::session
+//! (McpSession). These stay on raw frames, which the typed client cannot
+//! give them:
+//!   raw, replay          send caller-built or recorded messages verbatim
+//!   run                  `method` steps; assertions see the whole response
+//!                        envelope (`error` included)
+//!   scan probes,         requests before initialize, bogus versions,
+//!   fingerprint          malformed frames and the exact error replies
+//!   scan inventory,      definitions hashed / diffed as sent, including
+//!   watch, diff          fields rmcp does not model, over every page
+//!   canary, pollution    every frame and non-JSON stdout line between
+//!                        calls (`interleaved`) is searched for markers
+//!   stress               notification floods and telling a crash from an
+//!                        error reply (`exited_within`)
+//!
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::Command;
+use tracing::Instrument;
+
+use super::TargetSpec;
+use super::limits;
+use super::record::{self, Dir};
+use super::socket::{self, Reader, Writer};
+use super::spawn::{self, ServerProcess, StderrTail};
+use super::timing::{self, Phase};
+use crate::cmd::exit;
+
+/// A spawned local MCP server spoken to with hand-built JSON-RPC frames.
+pub struct RawSession {
+    /// None for a socket target.
+    process: Option<Killed>,
+    stdin: Writer,
+    lines: Lines<BufReader<Reader>>,
+    next_id: i64,
+    timeout: Duration,
+    /// Messages received while waiting for a response (notifications, server requests).
+    pub interleaved: Vec<Value>,
+    /// Transcript session (see record)
+    session: Option<u64>,
+    /// In the initialize handshake (--connect-timeout applies, not --request-timeout)
+    connecting: bool,
+    /// What the server wrote to stderr (shown when initialize fails)
+    stderr: StderrTail,
+}
+
+impl RawSession {
+    /// Spawn the target process with piped stdin / stdout (stderr per
+    /// --stderr), or connect to a socket target.
+    pub fn spawn(spec: &TargetSpec, timeout: Duration) -> Result<Self> {
+        let (program, args) = match spec {
+            TargetSpec::LocalCommand { program, args, .. } => (program, args),
+            TargetSpec::Socket { socket, .. } => {
+                let (read, write) = timing::measure(Phase::Connect, || socket::connect(socket))
+                    .map_err(exit::connection)?;
+                return Ok(Self::new(
+                    spec,
+                    timeout,
+                    None,
+                    read,
+                    write,
+                    StderrTail::default(),
+                ));
+            }
+            TargetSpec::RemoteUrl { .. } => {
+                return Err(exit::usage(
+                    "raw sessions only support local process and socket targets",
+                ));
+            }
+        };
+        let mut command = Command::new(program);
+        spawn::prepare(command.args(args).as_std_mut());
+        let mut child = timing::measure(Phase::Connect, || {
+            command
+                .stdin(std::process::Stdio::piped())
+                .stdout(std::process::Stdio::piped())
+                .stderr(spawn::stdio())
+                .spawn()
+        })
+        .with_context(|| format!("Failed to spawn MCP process: {}", program))
+        .map_err(exit::connection)?;
+        let stdin = child.stdin.take().context("child stdin unavailable")?;
+        let stdout = child.stdout.take().context("child stdout unavailable")?;
+        let stderr = child
+            .stderr
+            .take()
+            .map(StderrTail::capture)
+            .unwrap_or_default();
+        let process = Killed(ServerProcess::track(child));
+        Ok(Self::new(
+            spec,
+            timeout,
+            Some(process),
+            Box::new(stdout),
+            Box::new(stdin),
+            stderr,
+        ))
+    }
+
+    fn new(
+        spec: &TargetSpec,
+        timeout: Duration,
+        process: Option<Killed>,
+        read: Reader,
+        write: Writer,
+        stderr: StderrTail,
+    ) -> Self {
+        RawSession {
+            process,
+            stdin: write,
+            lines: BufReader::new(read).lines(),
+            next_id: 1,
+            timeout,
+            interleaved: Vec::new(),
+            session: record::session(spec.original()),
+            connecting: false,
+            stderr,
+        }
+    }
+
+    /// Perform the MCP initialize handshake (request + `notifications/initialized`).
+    /// Returns the raw initialize response.
+    pub async fn initialize(&mut self) -> Result<Value> {
+        let params = json!({
+            "protocolVersion": rmcp::model::ProtocolVersion::LATEST,
+            "capabilities": {},
+            "clientInfo": {"name": "mcp-hack", "version": env!("CARGO_PKG_VERSION")}
+        });
+        self.connecting = true;
+        let resp: Result<Value> = timing::timed(Phase::Initialize, async {
+            let resp = self.request("initialize", Some(params)).await?;
+            self.notify("notifications/initialized", None).await?;
+            Ok(resp)
+        })
+        .await;
+        self.connecting = false;
+        if resp.is_err() {
+            self.stderr.settle().await;
+        }
+        resp.map_err(exit::connection)
+    }
+
+    /// Send a request with a fresh numeric id and wait for the matching response.
+    pub async fn request(&mut self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut msg = json!({"jsonrpc": "2.0", "id": id, "method": method});
+        if let Some(p) = params {
+            msg["params"] = p;
+        }
+        let span = tracing::trace_span!("request", method, id);
+        let resp = self.send_message(&msg).instrument(span.clone()).await;
+        if let Ok(error) = resp.as_ref().map(|r| &r["error"])
+            && !error.is_null()
+        {
+            tracing::trace!(parent: &span, "error response: {error}");
+        }
+        resp
+    }
+
+    /// Send a notification (no id, no response expected).
+    pub async fn notify(&mut self, method: &str, params: Option<Value>) -> Result<()> {
+        let mut msg = json!({"jsonrpc": "2.0", "method": method});
+        if let Some(p) = params {
+            msg["params"] = p;
+        }
+        self.write_frame(&msg).await
+    }
+
+    /// Send a complete, caller-built JSON-RPC message.
+    ///
+    /// If it carries an `id`, waits for and returns the response with that id;
+    /// otherwise it is a notification and `Value::Null` is returned.
+    pub async fn send_message(&mut self, msg: &Value) -> Result<Value> {
+        self.write_frame(msg).await?;
+        match msg.get("id") {
+            Some(id) if !id.is_null() => {
+                let id = id.clone();
+                self.read_response(&id).await
+            }
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Write an arbitrary (possibly invalid) line and wait for the next response
+    /// frame of any id — used to see how a server answers malformed input.
+    pub async fn send_line_expect_any(&mut self, line: &str) -> Result<Value> {
+        let sent = serde_json::from_str(line).unwrap_or_else(|_| json!({ "non_json_line": line }));
+        record::message(self.session, Dir::Send, &sent);
+        let mut raw = line.to_string();
+        raw.push('\n');
+        self.stdin
+            .write_all(raw.as_bytes())
+            .await
+            .context("failed to write to server stdin")?;
+        self.stdin.flush().await.context("failed to flush stdin")?;
+        self.read_response(&Value::Null).await
+    }
+
+    /// Collect any frames that arrive within `wait` (used after notifications).
+    pub async fn drain(&mut self, wait: Duration) -> Vec<Value> {
+        let mut out = Vec::new();
+        while let Ok(Ok(Some(line))) = tokio::time::timeout(wait, self.lines.next_line()).await {
+            if let Some(v) = parse_frame(&line) {
+                record::message(self.session, Dir::Recv, &v);
+                out.push(v);
+            }
+        }
+        out
+    }
+
+    /// OS pid of the server process (None once it has exited, and for a
+    /// socket target).
+    pub fn pid(&self) -> Option<u32> {
+        let process = &self.process.as_ref()?.0;
+        if process.exited() {
+            return None;
+        }
+        process.pid()
+    }
+
+    /// Whether the server process exits within `wait` (at once when it
+    /// already has), e.g. to tell a crash from an error response. Always
+    /// false for a socket target.
+    pub async fn exited_within(&mut self, wait: Duration) -> bool {
+        match &self.process {
+            Some(process) => process.0.exited_within(wait).await,
+            None => false,
+        }
+    }
+
+    /// Close stdin and give the process a moment to exit (killed on drop
+    /// otherwise); a socket connection is just closed.
+    pub async fn shutdown(self) {
+        let RawSession { process, stdin, .. } = self;
+        drop(stdin);
+        let Some(process) = process else {
+            return;
+        };
+        timing::timed(
+            Phase::Shutdown,
+            process.0.exited_within(Duration::from_secs(2)),
+        )
+        .await;
+    }
+
+    async fn write_frame(&mut self, msg: &Value) -> Result<()> {
+        record::message(self.session, Dir::Send, msg);
+        let mut line = serde_json::to_string(msg).context("failed to serialize message")?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .context("failed to write to server stdin")?;
+        self.stdin.flush().await.context("failed to flush stdin")?;
+        Ok(())
+    }
+
+    /// Wait for the response carrying `id`; `Value::Null` accepts any response.
+    async fn read_response(&mut self, id: &Value) -> Result<Value> {
+        let (limit, flag) = if self.connecting {
+            (limits::connect_timeout(), "--connect-timeout")
+        } else {
+            (limits::request_timeout(), "--request-timeout")
+        };
+        let wait = limits::cap(self.timeout, limit);
+        let by_flag = if wait < self.timeout {
+            format!(" ({flag})")
+        } else {
+            String::new()
+        };
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            let line = tokio::time::timeout_at(deadline, self.lines.next_line())
+                .await
+                .map_err(|_| anyhow::anyhow!("timed out waiting for response to id {id}{by_flag}"))?
+                .context("failed to read from server stdout")?;
+            let Some(line) = line else {
+                return Err(exit::connection(anyhow::anyhow!(
+                    "server closed stdout before responding to id {id}"
+                )));
+            };
+            let Some(frame) = parse_frame(&line) else {
+                continue;
+            };
+            record::message(self.session, Dir::Recv, &frame);
+            let is_response = frame.get("method").is_none()
+                && (frame.get("result").is_some() || frame.get("error").is_some());
+            if is_response && (id.is_null() || frame.get("id") == Some(id)) {
+                return Ok(frame);
+            }
+            self.interleaved.push(frame);
+        }
+    }
+}
+
+/// Parse one stdout line; non-JSON lines (banners, logs) are wrapped for visibility.
+fn parse_frame(line: &str) -> Option<Value> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    Some(serde_json::from_str(trimmed).unwrap_or_else(|_| json!({ "non_json_line": trimmed })))
+}
+
+/// The session's server, killed with the session (like spawn's rmcp
+/// sessions) and by spawn::kill_children on Ctrl-C.
+struct Killed(ServerProcess);
+
+impl Drop for Killed {
+    fn drop(&mut self) {
+        self.0.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_frame_json_and_noise() {
+        assert_eq!(
+            parse_frame(r#"{"jsonrpc":"2.0","id":1,"result":{}}"#),
+            Some(json!({"jsonrpc":"2.0","id":1,"result":{}}))
+        );
+        assert_eq!(
+            parse_frame("Server listening..."),
+            Some(json!({"non_json_line": "Server listening..."}))
+        );
+        assert_eq!(parse_frame("   "), None);
+    }
+}
@@ -0,0 +1,320 @@
+//! Traffic transcripts (`--record session.mcpt`).
+//!
+//! When started, every JSON-RPC message exchanged with a server (raw
+//! sessions, the rmcp client transport and `proxy`) is appended to the
+//! transcript as it happens, so the evidence trail of exec / fuzz / scan
+//! survives the process. The file is NDJSON, flushed per line:
+//!
+//! ```text
+//! {"mcpt":1,"started":"2025-01-01T00:00:00.000Z","argv":["mcp-hack","scan",...]}
+//! {"event":"session","session":1,"target":"python3 server.py","t_ms":3}
+//! {"session":1,"dir":"send","t_ms":4,"time":"...","message":{...}}
+//! {"session":1,"dir":"recv","t_ms":90,"time":"...","message":{...}}
+//! ```
+//!
+//! `session` numbers each spawned server (scan and fuzz may start several);
+//! `t_ms` is milliseconds since the recording started. Lines a server
+//! prints that are not JSON are kept as `{"non_json_line": ...}`.
+//!
+//! `parse` / `load` read a transcript back as request / response exchanges
+//! (used by `stats` and `replay`).
+
+use anyhow::{Context, Result, bail};
+use rmcp::RoleClient;
+use rmcp::service::{RxJsonRpcMessage, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use super::wire;
+use crate::utils::utc_timestamp;
+
+/// Direction of a recorded message, from mcp-hack's point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dir {
+    Send,
+    Recv,
+}
+
+impl Dir {
+    fn as_str(self) -> &'static str {
+        match self {
+            Dir::Send => "send",
+            Dir::Recv => "recv",
+        }
+    }
+}
+
+struct Recorder {
+    file: Mutex<File>,
+    started: Instant,
+}
+
+static RECORDER: OnceLock<Recorder> = OnceLock::new();
+static SESSIONS: AtomicU64 = AtomicU64::new(0);
+
+impl Recorder {
+    fn write(&self, line: &Value) {
+        if let Ok(mut f) = self.file.lock() {
+            let _ = writeln!(f, "{line}").and_then(|_| f.flush());
+        }
+    }
+
+    fn t_ms(&self) -> u128 {
+        self.started.elapsed().as_millis()
+    }
+}
+
+/// Start recording to `path` (truncated) with a header naming the command.
+pub fn start(path: &str, argv: &[String]) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("failed to create {path}"))?;
+    let recorder = Recorder {
+        file: Mutex::new(file),
+        started: Instant::now(),
+    };
+    recorder.write(&json!({"mcpt": 1, "started": utc_timestamp(), "argv": argv}));
+    let _ = RECORDER.set(recorder);
+    Ok(())
+}
+
+/// Whether --record is active.
+pub fn enabled() -> bool {
+    RECORDER.get().is_some()
+}
+
+/// Open a session for a newly spawned server; None when neither recording
+/// nor writing a wire log (crate::mcp::wire).
+pub fn session(target: &str) -> Option<u64> {
+    let recorder = RECORDER.get();
+    if recorder.is_none() && !wire::enabled() {
+        return None;
+    }
+    let id = SESSIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    if let Some(r) = recorder {
+        r.write(&json!({"event": "session", "session": id, "target": target, "t_ms": r.t_ms()}));
+    }
+    wire::session(id, target);
+    Some(id)
+}
+
+/// Record one message of `session` (no-op when not recording).
+pub fn message(session: Option<u64>, dir: Dir, msg: &Value) {
+    let Some(id) = session else {
+        return;
+    };
+    wire::frame(id, dir, msg);
+    let Some(r) = RECORDER.get() else {
+        return;
+    };
+    r.write(&json!({
+        "session": id,
+        "dir": dir.as_str(),
+        "t_ms": r.t_ms(),
+        "time": utc_timestamp(),
+        "message": msg,
+    }));
+}
+
+/// rmcp client transport that records what passes through it.
+pub struct Recorded<T> {
+    inner: T,
+    session: Option<u64>,
+}
+
+/// Wrap an rmcp client transport for `target`.
+pub fn wrap<T>(inner: T, target: &str) -> Recorded<T> {
+    Recorded {
+        inner,
+        session: session(target),
+    }
+}
+
+impl<T: Transport<RoleClient>> Transport<RoleClient> for Recorded<T> {
+    type Error = T::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        if self.session.is_some() {
+            let v = serde_json::to_value(&item).unwrap_or(Value::Null);
+            message(self.session, Dir::Send, &v);
+        }
+        self.inner.send(item)
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleClient>> {
+        let item = self.inner.receive().await;
+        if let Some(m) = &item
+            && self.session.is_some()
+        {
+            let v = serde_json::to_value(m).unwrap_or(Value::Null);
+            message(self.session, Dir::Recv, &v);
+        }
+        item
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.inner.close()
+    }
+}
+
+/// A message mcp-hack sent and, for requests, the server's response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    pub session: u64,
+    pub request: Value,
+    /// None for notifications and unanswered requests
+    pub response: Option<Value>,
+    pub sent_ms: u64,
+    pub latency_ms: Option<u64>,
+}
+
+impl Exchange {
+    pub fn method(&self) -> Option<&str> {
+        self.request.get("method").and_then(|v| v.as_str())
+    }
+
+    /// Tool name of a tools/call request.
+    pub fn tool(&self) -> Option<&str> {
+        (self.method() == Some("tools/call"))
+            .then(|| {
+                self.request
+                    .pointer("/params/name")
+                    .and_then(|v| v.as_str())
+            })
+            .flatten()
+    }
+
+    /// Error response, or a tool result flagged `isError`.
+    pub fn is_error(&self) -> bool {
+        self.response.as_ref().is_some_and(|r| {
+            r.get("error").is_some()
+                || r.pointer("/result/isError").and_then(|v| v.as_bool()) == Some(true)
+        })
+    }
+}
+
+/// A transcript read back from disk.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    pub argv: Vec<String>,
+    pub started: String,
+    /// Session number -> target
+    pub sessions: BTreeMap<u64, String>,
+    /// In the order the messages were sent
+    pub exchanges: Vec<Exchange>,
+}
+
+/// Parse transcript text.
+pub fn parse(text: &str) -> Result<Transcript> {
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header: Value = lines
+        .next()
+        .and_then(|l| serde_json::from_str(l).ok())
+        .filter(|h: &Value| h.get("mcpt").is_some())
+        .context("not an mcp-hack transcript (missing mcpt header)")?;
+    let mut t = Transcript {
+        argv: header
+            .get("argv")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|s| s.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        started: header
+            .get("started")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        ..Default::default()
+    };
+    // (session, request id) -> index in exchanges
+    let mut pending: HashMap<(u64, String), usize> = HashMap::new();
+    for (n, line) in lines.enumerate() {
+        let Ok(rec) = serde_json::from_str::<Value>(line) else {
+            bail!("line {}: invalid JSON", n + 2);
+        };
+        let session = rec.get("session").and_then(|v| v.as_u64()).unwrap_or(0);
+        let t_ms = rec.get("t_ms").and_then(|v| v.as_u64()).unwrap_or(0);
+        if rec.get("event").and_then(|v| v.as_str()) == Some("session") {
+            let target = rec
+                .get("target")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            t.sessions.insert(session, target.to_string());
+            continue;
+        }
+        let Some(msg) = rec.get("message") else {
+            continue;
+        };
+        let id = msg
+            .get("id")
+            .filter(|v| !v.is_null())
+            .map(|v| v.to_string());
+        match rec.get("dir").and_then(|v| v.as_str()) {
+            Some("send") if msg.get("method").is_some() => {
+                if let Some(id) = id {
+                    pending.insert((session, id), t.exchanges.len());
+                }
+                t.exchanges.push(Exchange {
+                    session,
+                    request: msg.clone(),
+                    response: None,
+                    sent_ms: t_ms,
+                    latency_ms: None,
+                });
+            }
+            Some("recv") if msg.get("method").is_none() => {
+                if let Some(i) = id.and_then(|id| pending.remove(&(session, id))) {
+                    let ex = &mut t.exchanges[i];
+                    ex.latency_ms = Some(t_ms.saturating_sub(ex.sent_ms));
+                    ex.response = Some(msg.clone());
+                }
+            }
+            // Server-initiated requests / notifications and our replies to them
+            _ => {}
+        }
+    }
+    Ok(t)
+}
+
+pub fn load(path: &str) -> Result<Transcript> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read transcript {path}"))?;
+    parse(&text).with_context(|| format!("transcript {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_exchanges() {
+        let text = r#"{"mcpt":1,"started":"2025-01-01T00:00:00.000Z","argv":["mcp-hack","exec"]}
+{"event":"session","session":1,"target":"python3 s.py","t_ms":0}
+{"session":1,"dir":"send","t_ms":5,"message":{"jsonrpc":"2.0","id":0,"method":"initialize"}}
+{"session":1,"dir":"recv","t_ms":45,"message":{"jsonrpc":"2.0","id":0,"result":{}}}
+{"session":1,"dir":"send","t_ms":46,"message":{"jsonrpc":"2.0","method":"notifications/initialized"}}
+{"session":1,"dir":"recv","t_ms":47,"message":{"jsonrpc":"2.0","method":"notifications/message"}}
+{"session":1,"dir":"send","t_ms":50,"message":{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"echo"}}}
+{"session":1,"dir":"recv","t_ms":53,"message":{"jsonrpc":"2.0","id":1,"result":{"isError":true}}}
+"#;
+        let t = parse(text).unwrap();
+        assert_eq!(t.sessions.get(&1).map(String::as_str), Some("python3 s.py"));
+        assert_eq!(t.exchanges.len(), 3);
+        assert_eq!(t.exchanges[0].latency_ms, Some(40));
+        assert!(t.exchanges[1].response.is_none());
+        assert_eq!(t.exchanges[2].tool(), Some("echo"));
+        assert!(t.exchanges[2].is_error());
+        assert!(parse("{\"tools\": []}").is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! Minimal JSON Schema checking (tool arguments vs `inputSchema`,
+//! `structuredContent` vs `outputSchema`).
+//!
+//! Covers the keywords tool schemas use in practice: type (string or list),
+//! enum, const, required, properties, additionalProperties (false or a
+//! schema), items, anyOf / oneOf, minimum / maximum (and the exclusive
+//! forms), minLength / maxLength, minItems / maxItems and format (date-time,
+//! date, time, email, uri, uuid, ipv4, ipv6, hostname). Unknown keywords,
+//! unknown formats and `$ref` are ignored, so a result is only reported for
+//! a definite mismatch.
+//!
+//! [`example`] goes the other way: a plausible value that satisfies the same
+//! keywords (`get tool --example`, `exec --generate`).
+
+use serde_json::Value;
+
+/// The output schema a tool declares (`outputSchema` / `output_schema`).
+pub fn output_schema(tool: &Value) -> Option<&Value> {
+    tool.get("outputSchema")
+        .or_else(|| tool.get("output_schema"))
+        .filter(|s| s.is_object())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    let actual = type_name(value);
+    actual == ty || (ty == "number" && actual == "integer")
+}
+
+fn at(path: &str) -> &str {
+    if path.is_empty() { "$" } else { path }
+}
+
+/// Mismatches of `value` against `schema`, one line each (`$.a.b: ...`).
+pub fn check(schema: &Value, value: &Value) -> Vec<String> {
+    let mut out = Vec::new();
+    walk(schema, value, "", &mut out);
+    out
+}
+
+fn walk(schema: &Value, value: &Value, path: &str, out: &mut Vec<String>) {
+    let Some(s) = schema.as_object() else {
+        if schema == &Value::Bool(false) {
+            out.push(format!("{}: not allowed by the schema", at(path)));
+        }
+        return;
+    };
+
+    let types: Vec<&str> = match s.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        out.push(format!(
+            "{}: expected {}, got {}",
+            at(path),
+            types.join("|"),
+            type_name(value)
+        ));
+        return;
+    }
+    if let Some(allowed) = s.get("enum").and_then(|v| v.as_array())
+        && !allowed.contains(value)
+    {
+        out.push(format!("{}: {value} is not an allowed value", at(path)));
+    }
+    if let Some(c) = s.get("const")
+        && c != value
+    {
+        out.push(format!("{}: expected {c}, got {value}", at(path)));
+    }
+    bounds(s, value, path, out);
+    for key in ["anyOf", "oneOf"] {
+        if let Some(branches) = s.get(key).and_then(|v| v.as_array())
+            && !branches.iter().any(|b| check(b, value).is_empty())
+        {
+            out.push(format!("{}: matches none of {key}", at(path)));
+        }
+    }
+
+    if let Value::Object(map) = value {
+        for name in s
+            .get("required")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str())
+        {
+            if !map.contains_key(name) {
+                out.push(format!("{}.{name}: required property missing", at(path)));
+            }
+        }
+        let props = s.get("properties").and_then(|v| v.as_object());
+        for (name, v) in map {
+            let child = format!("{}.{name}", at(path));
+            match props.and_then(|p| p.get(name)) {
+                Some(sub) => walk(sub, v, &child, out),
+                None => {
+                    if let Some(extra) = s.get("additionalProperties") {
+                        if extra == &Value::Bool(false) {
+                            out.push(format!("{child}: property not declared by the schema"));
+                        } else {
+                            walk(extra, v, &child, out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, s.get("items")) {
+        for (i, v) in items.iter().enumerate() {
+            walk(item_schema, v, &format!("{}[{i}]", at(path)), out);
+        }
+    }
+}
+
+/// Range, length, size and format keywords.
+fn bounds(s: &serde_json::Map<String, Value>, value: &Value, path: &str, out: &mut Vec<String>) {
+    let limit = |key: &str| s.get(key).and_then(|v| v.as_f64());
+    if let Some(n) = value.as_f64() {
+        let checks = [
+            ("minimum", limit("minimum").filter(|m| n < *m)),
+            ("maximum", limit("maximum").filter(|m| n > *m)),
+            (
+                "exclusiveMinimum",
+                limit("exclusiveMinimum").filter(|m| n <= *m),
+            ),
+            (
+                "exclusiveMaximum",
+                limit("exclusiveMaximum").filter(|m| n >= *m),
+            ),
+        ];
+        for (key, m) in checks {
+            if let Some(m) = m {
+                out.push(format!("{}: {value} violates {key} {m}", at(path)));
+            }
+        }
+    }
+    let size = match value {
+        Value::String(text) => Some((text.chars().count(), "minLength", "maxLength")),
+        Value::Array(items) => Some((items.len(), "minItems", "maxItems")),
+        _ => None,
+    };
+    if let Some((len, min_key, max_key)) = size {
+        let len_f = len as f64;
+        if let Some(m) = limit(min_key).filter(|m| len_f < *m) {
+            out.push(format!("{}: length {len} is below {min_key} {m}", at(path)));
+        }
+        if let Some(m) = limit(max_key).filter(|m| len_f > *m) {
+            out.push(format!("{}: length {len} is above {max_key} {m}", at(path)));
+        }
+    }
+    if let (Some(text), Some(format)) = (value.as_str(), s.get("format").and_then(|v| v.as_str()))
+        && !matches_format(format, text)
+    {
+        out.push(format!("{}: {value} is not a valid {format}", at(path)));
+    }
+}
+
+fn all_digits(s: &str, len: usize) -> bool {
+    s.len() == len && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_date(s: &str) -> bool {
+    let parts: Vec<&str> = s.split('-').collect();
+    matches!(parts.as_slice(), [y, m, d] if all_digits(y, 4) && all_digits(m, 2) && all_digits(d, 2))
+        && (1..=12).contains(&s[5..7].parse::<u8>().unwrap_or(0))
+        && (1..=31).contains(&s[8..10].parse::<u8>().unwrap_or(0))
+}
+
+fn is_time(s: &str) -> bool {
+    // hh:mm:ss[.frac](Z|+hh:mm|-hh:mm)
+    let (clock, zone) = match s.find(['Z', 'z', '+', '-']) {
+        Some(i) => s.split_at(i),
+        None => return false,
+    };
+    let clock = clock.split('.').next().unwrap_or("");
+    let hms: Vec<&str> = clock.split(':').collect();
+    let clock_ok = matches!(hms.as_slice(), [h, m, sec] if all_digits(h, 2) && all_digits(m, 2) && all_digits(sec, 2));
+    let zone_ok = zone.eq_ignore_ascii_case("z")
+        || (zone.len() == 6
+            && zone.is_ascii()
+            && all_digits(&zone[1..3], 2)
+            && &zone[3..4] == ":"
+            && all_digits(&zone[4..], 2));
+    clock_ok && zone_ok
+}
+
+fn is_hostname(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= 253
+        && s.trim_end_matches('.').split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Whether `s` is valid for a string `format` (unknown formats pass).
+pub fn matches_format(format: &str, s: &str) -> bool {
+    match format {
+        "date-time" => s
+            .split_once(['T', 't', ' '])
+            .is_some_and(|(d, t)| is_date(d) && is_time(t)),
+        "date" => is_date(s),
+        "time" => is_time(s),
+        "email" => s.rsplit_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && domain.contains('.') && is_hostname(domain)
+        }),
+        "uri" => url::Url::parse(s).is_ok(),
+        "uuid" => {
+            let groups: Vec<&str> = s.split('-').collect();
+            groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+                && groups
+                    .iter()
+                    .all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+        }
+        "ipv4" => s.parse::<std::net::Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<std::net::Ipv6Addr>().is_ok(),
+        "hostname" => is_hostname(s),
+        _ => true,
+    }
+}
+
+/* ---- Examples ---- */
+
+/// A plausible value for `schema`: its default, const, first enum value or
+/// first example, else a placeholder of its type that respects the range,
+/// length and format keywords. Objects get every declared property.
+pub fn example(schema: &Value) -> Value {
+    let Some(s) = schema.as_object() else {
+        return Value::Null;
+    };
+    for key in ["default", "const"] {
+        if let Some(v) = s.get(key) {
+            return v.clone();
+        }
+    }
+    for key in ["enum", "examples"] {
+        if let Some(v) = s
+            .get(key)
+            .and_then(|v| v.as_array())
+            .and_then(|a| a.first())
+        {
+            return v.clone();
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(branch) = s.get(key).and_then(|v| v.as_array()).and_then(|a| {
+            a.iter()
+                .find(|b| b.get("type") != Some(&Value::from("null")))
+        }) {
+            return example(branch);
+        }
+    }
+    let ty = match s.get("type") {
+        Some(Value::String(t)) => t.as_str(),
+        Some(Value::Array(ts)) => ts
+            .iter()
+            .filter_map(|t| t.as_str())
+            .find(|t| *t != "null")
+            .unwrap_or("null"),
+        _ if s.contains_key("properties") => "object",
+        _ if s.contains_key("items") => "array",
+        _ => "string",
+    };
+    let limit = |key: &str| s.get(key).and_then(|v| v.as_f64());
+    match ty {
+        "object" => Value::Object(
+            s.get("properties")
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flatten()
+                .map(|(name, sub)| (name.clone(), example(sub)))
+                .collect(),
+        ),
+        "array" => {
+            let min = limit("minItems").unwrap_or(0.0) as usize;
+            let max = limit("maxItems").map_or(usize::MAX, |m| m as usize);
+            let item = s.get("items").map_or_else(|| Value::from("test"), example);
+            Value::Array(vec![item; min.max(1).min(max)])
+        }
+        "integer" | "number" => {
+            let mut n: f64 = 1.0;
+            if let Some(m) = limit("minimum").filter(|m| n < *m) {
+                n = m;
+            }
+            if let Some(m) = limit("exclusiveMinimum").filter(|m| n <= *m) {
+                n = m + 1.0;
+            }
+            if let Some(m) = limit("maximum").filter(|m| n > *m) {
+                n = m;
+            }
+            if let Some(m) = limit("exclusiveMaximum").filter(|m| n >= *m) {
+                n = m - 1.0;
+            }
+            if ty == "integer" || n.fract() == 0.0 {
+                Value::from(n.ceil() as i64)
+            } else {
+                Value::from(n)
+            }
+        }
+        "boolean" => Value::Bool(true),
+        "null" => Value::Null,
+        _ => Value::from(example_string(s)),
+    }
+}
+
+fn example_string(s: &serde_json::Map<String, Value>) -> String {
+    let by_format = match s.get("format").and_then(|v| v.as_str()) {
+        Some("date-time") => Some("2025-01-01T00:00:00Z"),
+        Some("date") => Some("2025-01-01"),
+        Some("time") => Some("00:00:00Z"),
+        Some("email") => Some("user@example.com"),
+        Some("uri") => Some("https://example.com/"),
+        Some("uuid") => Some("00000000-0000-0000-0000-000000000000"),
+        Some("ipv4") => Some("127.0.0.1"),
+        Some("ipv6") => Some("::1"),
+        Some("hostname") => Some("example.com"),
+        _ => None,
+    };
+    if let Some(text) = by_format {
+        return text.to_string();
+    }
+    let min = s.get("minLength").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    let max = s
+        .get("maxLength")
+        .and_then(|v| v.as_u64())
+        .map_or(usize::MAX, |m| m as usize);
+    "test".chars().cycle().take(min.max(4).min(max)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_structured_content_mismatches() {
+        let schema = json!({
+            "type": "object",
+            "required": ["temperature", "unit"],
+            "additionalProperties": false,
+            "properties": {
+                "temperature": {"type": "number"},
+                "unit": {"enum": ["C", "F"]},
+                "readings": {"type": "array", "items": {"type": "integer"}}
+            }
+        });
+        assert!(check(&schema, &json!({"temperature": 21, "unit": "C"})).is_empty());
+        assert_eq!(
+            check(
+                &schema,
+                &json!({"temperature": "hot", "readings": [1, 2.5], "debug": true})
+            ),
+            vec![
+                "$.unit: required property missing",
+                "$.debug: property not declared by the schema",
+                "$.readings[1]: expected integer, got number",
+                "$.temperature: expected number, got string",
+            ]
+        );
+        assert_eq!(
+            check(&schema, &json!([1])),
+            vec!["$: expected object, got array"]
+        );
+        let any = json!({"anyOf": [{"type": "string"}, {"type": "null"}]});
+        assert!(check(&any, &Value::Null).is_empty());
+        assert_eq!(check(&any, &json!(1)), vec!["$: matches none of anyOf"]);
+    }
+
+    #[test]
+    fn checks_ranges_lengths_and_formats() {
+        let schema = json!({"type": "object", "properties": {
+            "port": {"type": "integer", "minimum": 1, "maximum": 65535},
+            "ratio": {"type": "number", "exclusiveMaximum": 1},
+            "name": {"type": "string", "minLength": 2, "maxLength": 4},
+            "ids": {"type": "array", "maxItems": 1},
+            "when": {"type": "string", "format": "date-time"},
+            "mail": {"type": "string", "format": "email"},
+            "id": {"type": "string", "format": "uuid"},
+            "other": {"type": "string", "format": "x-custom"}
+        }});
+        let ok = json!({
+            "port": 443, "ratio": 0.5, "name": "abc", "ids": [1],
+            "when": "2025-01-31T12:00:00.5+09:00", "mail": "a@b.io",
+            "id": "123e4567-e89b-12d3-a456-426614174000", "other": "anything"
+        });
+        assert!(check(&schema, &ok).is_empty());
+        let bad = json!({
+            "port": 0, "ratio": 1, "name": "abcdef", "ids": [1, 2],
+            "when": "2025-13-01T00:00:00Z", "mail": "nobody", "id": "123"
+        });
+        assert_eq!(
+            check(&schema, &bad),
+            vec![
+                "$.id: \"123\" is not a valid uuid",
+                "$.ids: length 2 is above maxItems 1",
+                "$.mail: \"nobody\" is not a valid email",
+                "$.name: length 6 is above maxLength 4",
+                "$.port: 0 violates minimum 1",
+                "$.ratio: 1 violates exclusiveMaximum 1",
+                "$.when: \"2025-13-01T00:00:00Z\" is not a valid date-time",
+            ]
+        );
+        assert!(matches_format("ipv6", "::1") && !matches_format("ipv4", "1.2.3"));
+        assert!(matches_format("hostname", "api.example.com") && !matches_format("hostname", "-x"));
+    }
+
+    #[test]
+    fn examples_satisfy_their_schema() {
+        let schema = json!({
+            "type": "object",
+            "required": ["url"],
+            "properties": {
+                "url": {"type": "string", "format": "uri"},
+                "mode": {"enum": ["fast", "slow"]},
+                "limit": {"type": "integer", "minimum": 10, "default": 20},
+                "port": {"type": "integer", "exclusiveMinimum": 1024, "maximum": 65535},
+                "ratio": {"type": "number", "maximum": 0.5},
+                "code": {"type": "string", "minLength": 6},
+                "tags": {"type": "array", "items": {"type": "string", "maxLength": 2}},
+                "filter": {"properties": {"open": {"type": ["boolean", "null"]}}},
+                "owner": {"anyOf": [{"type": "null"}, {"type": "string", "format": "email"}]}
+            }
+        });
+        let value = example(&schema);
+        assert_eq!(
+            value,
+            json!({
+                "url": "https://example.com/", "mode": "fast", "limit": 20, "port": 1025,
+                "ratio": 0.5, "code": "testte", "tags": ["te"], "filter": {"open": true},
+                "owner": "user@example.com"
+            })
+        );
+        assert!(check(&schema, &value).is_empty());
+    }
+}
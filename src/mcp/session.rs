@@ -0,0 +1,158 @@
+//! An initialized MCP session with a local server.
+//!
+//! [`McpSession::connect`] spawns the target or connects to its socket (see
+//! spawn: --env, --cwd, --stderr, initialize within --connect-timeout) and
+//! owns the running rmcp
+//! service, so commands enumerate and call through one handle instead of
+//! wiring up the client themselves. Requests are bounded by
+//! --request-timeout (crate::mcp::limits); `tools/call` can be cancelled
+//! with Ctrl-C through an interrupt [`Watch`].
+//!
+//! [`McpSession::shutdown`] closes the session gracefully (the shutdown
+//! phase of crate::mcp::timing); either way the server is killed when the
+//! session ends, unless [`McpSession::keep_alive`] was called.
+
+use anyhow::{Context, Result};
+use rmcp::model::{CallToolRequestParam, CallToolResult};
+use rmcp::service::{Peer, RoleClient, RunningService, Service};
+use serde_json::{Map, Value};
+
+use super::interrupt::Watch;
+use super::spawn::{self, ServerProcess};
+use super::timing::{self, Phase};
+use super::{TargetSpec, limits};
+
+/// A running session; `S` is the client handler (`()` when the server's
+/// notifications are of no interest).
+pub struct McpSession<S: Service<RoleClient> = ()> {
+    target: String,
+    service: RunningService<RoleClient, S>,
+    /// None for a socket target.
+    process: Option<ServerProcess>,
+}
+
+impl<S: Service<RoleClient>> std::fmt::Debug for McpSession<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpSession")
+            .field("target", &self.target)
+            .field("pid", &self.pid())
+            .field(
+                "server",
+                &self.service.peer_info().map(|i| &i.server_info.name),
+            )
+            .finish()
+    }
+}
+
+impl<S: Service<RoleClient>> McpSession<S> {
+    /// Spawn (or connect to) a local target and initialize a session with `client`.
+    pub async fn connect(spec: &TargetSpec, client: S) -> Result<Self> {
+        crate::utils::progress_events::phase("connect", Some(spec.original()));
+        let (service, process) = spawn::connect_process(spec, client).await?;
+        Ok(Self {
+            target: spec.original().to_string(),
+            service,
+            process,
+        })
+    }
+
+    /// The target as given on the command line.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn peer(&self) -> &Peer<RoleClient> {
+        self.service.peer()
+    }
+
+    /// OS process id of the server (None for a socket target).
+    pub fn pid(&self) -> Option<u32> {
+        self.process.and_then(|p| p.pid())
+    }
+
+    /// Leave the server running when the session ends (it is killed by
+    /// default, see spawn::ServerProcess).
+    pub fn keep_alive(&self) {
+        if let Some(process) = &self.process {
+            process.keep_alive();
+        }
+    }
+
+    /// The client handler the session was started with.
+    pub fn client(&self) -> &S {
+        self.service.service()
+    }
+
+    /// What the server answered to `initialize`.
+    pub fn server_info(&self) -> Option<&rmcp::model::ServerInfo> {
+        self.service.peer_info()
+    }
+
+    /// `tools/list` entries, as JSON.
+    pub async fn list_tools(&self) -> Result<Vec<Value>> {
+        crate::utils::progress_events::phase("enumerate", Some("tools"));
+        let listed = limits::request("tools/list", self.service.list_tools(Default::default()))
+            .await?
+            .context("Failed to list tools")?;
+        Ok(items(serde_json::to_value(listed)?, "tools"))
+    }
+
+    /// `resources/list` entries, as JSON.
+    pub async fn list_resources(&self) -> Result<Vec<Value>> {
+        crate::utils::progress_events::phase("enumerate", Some("resources"));
+        let listed = limits::request(
+            "resources/list",
+            self.service.list_resources(Default::default()),
+        )
+        .await?
+        .context("Failed to list resources")?;
+        Ok(items(serde_json::to_value(listed)?, "resources"))
+    }
+
+    /// Call tool `name`; Ctrl-C cancels it while `interrupt` is live (pass
+    /// `&Watch::default()` to leave Ctrl-C alone). The outer Result is the
+    /// timeout or the interrupt ([`super::interrupt::Interrupted`]), the
+    /// inner one the call itself.
+    pub async fn call_tool(
+        &self,
+        name: &str,
+        arguments: Map<String, Value>,
+        interrupt: &Watch,
+    ) -> Result<Result<CallToolResult, rmcp::ServiceError>> {
+        let params = CallToolRequestParam {
+            name: name.to_string().into(),
+            arguments: (!arguments.is_empty()).then_some(arguments),
+        };
+        limits::request("tools/call", interrupt.call_tool(self.peer(), params)).await?
+    }
+
+    /// Close the session and wait for the server to exit.
+    pub async fn shutdown(self) {
+        let _ = timing::timed(Phase::Shutdown, self.service.cancel()).await;
+    }
+}
+
+/// The `key` array of a list result.
+fn items(mut listed: Value, key: &str) -> Vec<Value> {
+    match listed.get_mut(key).map(Value::take) {
+        Some(Value::Array(items)) => items,
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn takes_list_items() {
+        let listed = json!({"tools": [{"name": "a"}, {"name": "b"}], "nextCursor": null});
+        assert_eq!(
+            items(listed, "tools"),
+            vec![json!({"name": "a"}), json!({"name": "b"})]
+        );
+        assert!(items(json!({}), "resources").is_empty());
+        assert!(items(json!({"tools": "x"}), "tools").is_empty());
+    }
+}
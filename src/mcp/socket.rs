@@ -0,0 +1,272 @@
+//! Socket targets: servers (local MCP gateways, embedded devices, lab
+//! setups) listening on a Unix domain socket, a Windows named pipe or a TCP
+//! port instead of being spawned.
+//!
+//!   -t unix:///run/mcp.sock              (unix://rel/path.sock is relative)
+//!   -t 'npipe:////./pipe/mcp'            (or -t '\\.\pipe\mcp')
+//!   -t tcp://192.168.1.20:7000           (newline-delimited JSON-RPC, no HTTP)
+//!
+//! Sessions ([`crate::mcp::spawn`] for rmcp, [`crate::mcp::raw`] for raw
+//! JSON-RPC) speak newline-delimited JSON-RPC over the stream [`connect`]
+//! opens, the way they use a spawned server's stdio; there is no process
+//! to track, so --env / --cwd / --stderr do not apply.
+
+use anyhow::{Context, Result, bail};
+use std::path::PathBuf;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub const UNIX_SCHEME: &str = "unix://";
+pub const PIPE_SCHEME: &str = "npipe://";
+pub const TCP_SCHEME: &str = "tcp://";
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+/// Where a socket target listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Socket {
+    Unix(PathBuf),
+    /// Full pipe name, e.g. `\\.\pipe\mcp`.
+    Pipe(String),
+    /// `host:port` (IPv6 hosts in brackets).
+    Tcp(String),
+}
+
+impl Socket {
+    /// The socket of a `unix://` / `npipe://` / `\\.\pipe\` / `tcp://`
+    /// target, if it is one.
+    pub fn parse(target: &str) -> Option<Result<Socket>> {
+        let target = target.trim();
+        if let Some(addr) = strip_scheme(target, TCP_SCHEME) {
+            return Some(tcp_address(addr).map(Socket::Tcp));
+        }
+        if let Some(path) = strip_scheme(target, UNIX_SCHEME) {
+            if path.is_empty() {
+                return Some(Err(anyhow::anyhow!(
+                    "unix socket target has no path: unix:///path/to/socket"
+                )));
+            }
+            return Some(Ok(Socket::Unix(PathBuf::from(path))));
+        }
+        let name = match strip_scheme(target, PIPE_SCHEME) {
+            Some(rest) => rest.replace('/', r"\"),
+            None if target.starts_with(PIPE_PREFIX) => target.to_string(),
+            None => return None,
+        };
+        if !name.starts_with(PIPE_PREFIX) || name.len() == PIPE_PREFIX.len() {
+            return Some(Err(anyhow::anyhow!(
+                "named pipe target must look like npipe:////./pipe/NAME or \\\\.\\pipe\\NAME"
+            )));
+        }
+        Some(Ok(Socket::Pipe(name)))
+    }
+
+    /// The address in target form.
+    pub fn address(&self) -> String {
+        match self {
+            Socket::Unix(path) => format!("{UNIX_SCHEME}{}", path.display()),
+            Socket::Pipe(name) => name.clone(),
+            Socket::Tcp(addr) => format!("{TCP_SCHEME}{addr}"),
+        }
+    }
+}
+
+/// `host:port` of a `tcp://` target (a trailing `/` is tolerated).
+fn tcp_address(addr: &str) -> Result<String> {
+    let addr = addr.trim_end_matches('/');
+    let url = url::Url::parse(&format!("{TCP_SCHEME}{addr}"))
+        .with_context(|| format!("Invalid TCP target '{TCP_SCHEME}{addr}'"))?;
+    let host = url.host_str().filter(|h| !h.is_empty());
+    match (host, url.port()) {
+        (Some(host), Some(port)) if url.path().is_empty() && url.username().is_empty() => {
+            Ok(format!("{host}:{port}"))
+        }
+        _ => bail!("TCP target must be tcp://HOST:PORT, got '{TCP_SCHEME}{addr}'"),
+    }
+}
+
+fn strip_scheme<'a>(target: &'a str, scheme: &str) -> Option<&'a str> {
+    target
+        .get(..scheme.len())
+        .filter(|p| p.eq_ignore_ascii_case(scheme))
+        .map(|_| &target[scheme.len()..])
+}
+
+/// Read half of a connected socket.
+pub type Reader = Box<dyn AsyncRead + Send + Unpin>;
+/// Write half of a connected socket.
+pub type Writer = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Connect to `socket` (TCP within --connect-timeout). Must be called
+/// within the Tokio runtime.
+pub fn connect(socket: &Socket) -> Result<(Reader, Writer)> {
+    match socket {
+        #[cfg(unix)]
+        Socket::Unix(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)
+                .with_context(|| format!("cannot connect to {}", path.display()))?;
+            stream.set_nonblocking(true)?;
+            let (read, write) = tokio::net::UnixStream::from_std(stream)?.into_split();
+            Ok((Box::new(read), Box::new(write)))
+        }
+        #[cfg(windows)]
+        Socket::Pipe(name) => {
+            let client = tokio::net::windows::named_pipe::ClientOptions::new()
+                .open(name)
+                .with_context(|| format!("cannot connect to {name}"))?;
+            let (read, write) = tokio::io::split(client);
+            Ok((Box::new(read), Box::new(write)))
+        }
+        Socket::Tcp(addr) => {
+            let stream = tcp_connect(addr).with_context(|| format!("cannot connect to {addr}"))?;
+            stream.set_nodelay(true).ok();
+            stream.set_nonblocking(true)?;
+            let (read, write) = tokio::net::TcpStream::from_std(stream)?.into_split();
+            Ok((Box::new(read), Box::new(write)))
+        }
+        #[allow(unreachable_patterns)]
+        other => bail!("{} is not supported on this platform", other.address()),
+    }
+}
+
+/// [`connect`] with blocking halves, for the threads of `proxy`. Dropping
+/// the write half shuts down writing, the EOF a spawned server sees when
+/// its stdin closes.
+pub fn connect_blocking(
+    socket: &Socket,
+) -> Result<(
+    Box<dyn std::io::Read + Send>,
+    Box<dyn std::io::Write + Send>,
+)> {
+    match socket {
+        #[cfg(unix)]
+        Socket::Unix(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)
+                .with_context(|| format!("cannot connect to {}", path.display()))?;
+            let close = |s: &std::os::unix::net::UnixStream| {
+                let _ = s.shutdown(std::net::Shutdown::Write);
+            };
+            Ok((
+                Box::new(stream.try_clone()?),
+                Box::new(Closing(stream, close)),
+            ))
+        }
+        #[cfg(windows)]
+        Socket::Pipe(name) => {
+            let pipe = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(name)
+                .with_context(|| format!("cannot connect to {name}"))?;
+            Ok((Box::new(pipe.try_clone()?), Box::new(pipe)))
+        }
+        Socket::Tcp(addr) => {
+            let stream = tcp_connect(addr).with_context(|| format!("cannot connect to {addr}"))?;
+            stream.set_nodelay(true).ok();
+            let close = |s: &std::net::TcpStream| {
+                let _ = s.shutdown(std::net::Shutdown::Write);
+            };
+            Ok((
+                Box::new(stream.try_clone()?),
+                Box::new(Closing(stream, close)),
+            ))
+        }
+        #[allow(unreachable_patterns)]
+        other => bail!("{} is not supported on this platform", other.address()),
+    }
+}
+
+/// A write half that runs its close function when dropped.
+struct Closing<W: std::io::Write>(W, fn(&W));
+
+impl<W: std::io::Write> std::io::Write for Closing<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<W: std::io::Write> Drop for Closing<W> {
+    fn drop(&mut self) {
+        (self.1)(&self.0);
+    }
+}
+
+/// The first address of `addr` that accepts a connection.
+fn tcp_connect(addr: &str) -> std::io::Result<std::net::TcpStream> {
+    use std::net::ToSocketAddrs;
+    let timeout = super::limits::connect_timeout().unwrap_or(super::limits::DEFAULT_CONNECT);
+    let mut last = None;
+    for resolved in addr.to_socket_addrs()? {
+        match std::net::TcpStream::connect_timeout(&resolved, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last = Some(e),
+        }
+    }
+    Err(last.unwrap_or_else(|| std::io::Error::other("no address resolved")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_socket_targets() {
+        assert_eq!(
+            Socket::parse("unix:///run/mcp.sock").unwrap().unwrap(),
+            Socket::Unix("/run/mcp.sock".into())
+        );
+        assert_eq!(
+            Socket::parse("UNIX://gw.sock").unwrap().unwrap(),
+            Socket::Unix("gw.sock".into())
+        );
+        assert_eq!(
+            Socket::parse("npipe:////./pipe/mcp").unwrap().unwrap(),
+            Socket::Pipe(r"\\.\pipe\mcp".into())
+        );
+        assert_eq!(
+            Socket::parse(r"\\.\pipe\mcp").unwrap().unwrap().address(),
+            r"\\.\pipe\mcp"
+        );
+        assert!(Socket::parse("unix://").unwrap().is_err());
+        assert!(Socket::parse("npipe://host/x").unwrap().is_err());
+        assert_eq!(
+            Socket::parse("tcp://10.0.0.5:7000/").unwrap().unwrap(),
+            Socket::Tcp("10.0.0.5:7000".into())
+        );
+        assert_eq!(
+            Socket::parse("tcp://[::1]:7000")
+                .unwrap()
+                .unwrap()
+                .address(),
+            "tcp://[::1]:7000"
+        );
+        assert!(Socket::parse("tcp://device.local").unwrap().is_err());
+        assert!(Socket::parse("tcp://h:1/mcp").unwrap().is_err());
+        assert!(Socket::parse("python3 server.py").is_none());
+    }
+
+    #[tokio::test]
+    async fn connects_over_tcp() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let line = lines.next_line().await.unwrap().unwrap();
+            write
+                .write_all(format!("{line}\n").as_bytes())
+                .await
+                .unwrap();
+        });
+        let (read, mut write) = connect(&Socket::Tcp(addr)).unwrap();
+        write.write_all(b"{\"ping\":1}\n").await.unwrap();
+        let echoed = BufReader::new(read).lines().next_line().await.unwrap();
+        assert_eq!(echoed.as_deref(), Some("{\"ping\":1}"));
+        server.await.unwrap();
+        assert!(connect(&Socket::Tcp("127.0.0.1:1".into())).is_err());
+    }
+}
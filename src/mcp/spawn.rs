@@ -0,0 +1,675 @@
+//! Starting local MCP servers for the rmcp client.
+//!
+//! [`connect`] spawns the target (stdout kept for the protocol), or connects
+//! to a socket target (crate::mcp::socket), and runs the initialize handshake within the connect timeout (crate::mcp::limits), so
+//! a command that never answers (wrong program, HTTP server, prompt waiting
+//! on stdin) fails instead of hanging.
+//!
+//! Spawned servers do not outlive their session: each is tracked
+//! ([`ServerProcess`], with its PID; raw JSON-RPC sessions in
+//! crate::mcp::raw and `proxy` register theirs too) and killed when the session is
+//! dropped, and [`kill_children`] kills whatever is left when mcp-hack exits
+//! early.
+//! [`ServerProcess::keep_alive`] opts a server out.
+//!
+//! What happens to the child's stderr is set by `--stderr` ([`StderrMode`]):
+//!   capture  (default) drained into a [`StderrTail`] (last [`TAIL_LINES`]
+//!            lines) and, with `--stderr-file PATH`, copied to that file
+//!   inherit  passed through to mcp-hack's own stderr
+//!   null     discarded
+//! The tail of the last server spawned ([`stderr_tail`]) is shown under
+//! error messages by crate::cmd::errors; a failed start-up waits briefly for
+//! the server's last words first.
+//! Raw sessions (crate::mcp::raw) follow the same setting.
+//!
+//! Servers are started with mcp-hack's environment plus the `env` of a
+//! config.toml target profile ([`add_profile_env`]) and the variables from
+//! `--env-file .env` and `--env KEY=VALUE` (later ones win), see [`set_env`],
+//! in `--cwd PATH` when given ([`set_cwd`]; relative paths in the target
+//! command then resolve against it). Raw sessions and `proxy` do the same.
+
+use anyhow::{Context, Result, bail};
+use rmcp::service::{
+    RoleClient, RunningService, RxJsonRpcMessage, Service, ServiceExt, TxJsonRpcMessage,
+};
+use rmcp::transport::Transport;
+use rmcp::transport::async_rw::AsyncRwTransport;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::watch;
+use tracing::Instrument;
+
+use super::TargetSpec;
+use super::limits;
+use super::record;
+use super::socket;
+use super::timing::{self, Phase};
+
+/// Lines of server stderr kept.
+pub const TAIL_LINES: usize = 20;
+
+/// How long a failed start-up waits for the server's last stderr output.
+const SETTLE: Duration = Duration::from_millis(250);
+
+/// Where a spawned server's stderr goes (`--stderr`).
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StderrMode {
+    Inherit,
+    #[default]
+    Capture,
+    Null,
+}
+
+static MODE: OnceLock<StderrMode> = OnceLock::new();
+static FILE: OnceLock<Mutex<File>> = OnceLock::new();
+static LAST: Mutex<Option<StderrTail>> = Mutex::new(None);
+static ENV: OnceLock<Vec<(String, String)>> = OnceLock::new();
+static PROFILE_ENV: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+static CWD: OnceLock<PathBuf> = OnceLock::new();
+
+/// Install the stderr handling for this process (first call wins).
+/// `file` (created / truncated) needs capture mode.
+pub fn set_stderr(mode: StderrMode, file: Option<&str>) -> Result<()> {
+    if let Some(path) = file {
+        if mode != StderrMode::Capture {
+            bail!("--stderr-file needs --stderr capture");
+        }
+        let f = File::create(path).with_context(|| format!("failed to create {path}"))?;
+        let _ = FILE.set(Mutex::new(f));
+    }
+    let _ = MODE.set(mode);
+    Ok(())
+}
+
+pub fn mode() -> StderrMode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+/// Stdio for a child's stderr under the current mode.
+pub fn stdio() -> Stdio {
+    match mode() {
+        StderrMode::Inherit => Stdio::inherit(),
+        StderrMode::Capture => Stdio::piped(),
+        StderrMode::Null => Stdio::null(),
+    }
+}
+
+/// `KEY=VALUE`; the value may be empty.
+fn env_pair(text: &str) -> Result<(String, String)> {
+    match text.split_once('=') {
+        Some((key, value))
+            if !key.trim().is_empty() && !key.trim().contains(char::is_whitespace) =>
+        {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => bail!("expected KEY=VALUE, got '{text}'"),
+    }
+}
+
+/// Variables of a `.env` file: `KEY=VALUE` lines, `#` comments and blank
+/// lines skipped, an `export ` prefix and quotes around the value dropped.
+pub fn parse_env_file(text: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let (key, value) = env_pair(line).with_context(|| format!("line {}", n + 1))?;
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+            .unwrap_or(value);
+        vars.push((key, value.to_string()));
+    }
+    Ok(vars)
+}
+
+/// Install the variables added to spawned servers (first call wins):
+/// those of `file`, then `pairs` (`KEY=VALUE`).
+pub fn set_env(pairs: &[String], file: Option<&str>) -> Result<()> {
+    let mut vars = match file {
+        Some(path) => {
+            let text =
+                std::fs::read_to_string(path).with_context(|| format!("failed to read {path}"))?;
+            parse_env_file(&text).with_context(|| format!("invalid {path}"))?
+        }
+        None => Vec::new(),
+    };
+    for pair in pairs {
+        vars.push(env_pair(pair)?);
+    }
+    let _ = ENV.set(vars);
+    Ok(())
+}
+
+/// Add the `env` of a target profile (`-t @name`); --env / --env-file win
+/// over it. mcp-hack's own environment is left alone.
+pub fn add_profile_env<'a>(vars: impl IntoIterator<Item = (&'a String, &'a String)>) {
+    let mut profile = PROFILE_ENV.lock().unwrap_or_else(|e| e.into_inner());
+    profile.extend(vars.into_iter().map(|(k, v)| (k.clone(), v.clone())));
+}
+
+/// Variables added to spawned servers, in order (a later one wins).
+pub fn env() -> Vec<(String, String)> {
+    let mut vars = PROFILE_ENV
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    vars.extend(ENV.get().into_iter().flatten().cloned());
+    vars
+}
+
+/// Install the working directory of spawned servers (first call wins).
+pub fn set_cwd(dir: &str) -> Result<()> {
+    let path = PathBuf::from(dir);
+    if !path.is_dir() {
+        bail!("'{dir}' is not a directory");
+    }
+    let _ = CWD.set(path);
+    Ok(())
+}
+
+/// Working directory of spawned servers (None: mcp-hack's own).
+pub fn cwd() -> Option<&'static Path> {
+    CWD.get().map(PathBuf::as_path)
+}
+
+/// Apply --env / --env-file and --cwd to a server command.
+pub fn prepare(cmd: &mut std::process::Command) {
+    cmd.envs(env());
+    if let Some(dir) = cwd() {
+        cmd.current_dir(dir);
+    }
+}
+
+/// Last lines of stderr of the most recently spawned server (empty when it
+/// wrote none or stderr is not captured).
+pub fn stderr_tail() -> Vec<String> {
+    LAST.lock()
+        .ok()
+        .and_then(|l| l.as_ref().map(StderrTail::lines))
+        .unwrap_or_default()
+}
+
+/// The last lines a child wrote to stderr, collected in the background.
+#[derive(Debug, Clone)]
+pub struct StderrTail {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    /// true once the stream hit EOF.
+    done: watch::Receiver<bool>,
+}
+
+impl Default for StderrTail {
+    fn default() -> Self {
+        let (_, done) = watch::channel(true);
+        Self {
+            lines: Arc::default(),
+            done,
+        }
+    }
+}
+
+impl StderrTail {
+    /// Drain `reader` on a background task (needs a Tokio runtime), copying
+    /// it to --stderr-file; the tail becomes [`stderr_tail`].
+    pub fn capture<R: AsyncRead + Unpin + Send + 'static>(reader: R) -> Self {
+        let tail = Self::drain(reader);
+        if let Ok(mut last) = LAST.lock() {
+            *last = Some(tail.clone());
+        }
+        tail
+    }
+
+    fn drain<R: AsyncRead + Unpin + Send + 'static>(reader: R) -> Self {
+        let (tx, done) = watch::channel(false);
+        let tail = Self {
+            lines: Arc::default(),
+            done,
+        };
+        let lines = tail.lines.clone();
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(reader);
+            let mut buf = Vec::new();
+            loop {
+                buf.clear();
+                match reader.read_until(b'\n', &mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        if let Some(Ok(mut f)) = FILE.get().map(|f| f.lock()) {
+                            let _ = f.write_all(&buf);
+                        }
+                        push(&lines, String::from_utf8_lossy(&buf).trim_end());
+                    }
+                }
+            }
+            let _ = tx.send(true);
+        });
+        tail
+    }
+
+    /// Lines kept so far, oldest first.
+    pub fn lines(&self) -> Vec<String> {
+        self.lines
+            .lock()
+            .map(|l| l.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Wait briefly for the rest of the output of a server that is exiting.
+    pub async fn settle(&self) {
+        let mut done = self.done.clone();
+        let _ = tokio::time::timeout(SETTLE, done.wait_for(|d| *d)).await;
+    }
+}
+
+fn push(lines: &Mutex<VecDeque<String>>, line: &str) {
+    if line.trim().is_empty() {
+        return;
+    }
+    if let Ok(mut lines) = lines.lock() {
+        if lines.len() == TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+}
+
+/// A tracked server: spawned for an rmcp / raw session, or by `proxy`,
+/// which pumps its stdio from blocking threads.
+#[derive(Debug)]
+enum Tracked {
+    Session(Child),
+    Blocking(std::process::Child),
+}
+
+impl Tracked {
+    fn start_kill(&mut self) {
+        let _ = match self {
+            Tracked::Session(child) => child.start_kill(),
+            Tracked::Blocking(child) => child.kill(),
+        };
+    }
+
+    fn try_wait(&mut self) -> std::io::Result<Option<std::process::ExitStatus>> {
+        match self {
+            Tracked::Session(child) => child.try_wait(),
+            Tracked::Blocking(child) => child.try_wait(),
+        }
+    }
+}
+
+/// Servers spawned for sessions and still owned by mcp-hack, by handle id.
+static CHILDREN: Mutex<BTreeMap<u64, Tracked>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn children() -> std::sync::MutexGuard<'static, BTreeMap<u64, Tracked>> {
+    CHILDREN.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Handle of a server process started by [`connect_process`].
+///
+/// The server is killed when its session ends (the rmcp service and with it
+/// the transport is dropped), or by [`kill_children`] when mcp-hack exits
+/// without unwinding, unless [`keep_alive`](Self::keep_alive) was called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerProcess {
+    id: u64,
+    pid: Option<u32>,
+}
+
+impl ServerProcess {
+    /// Own `child`: it is killed by [`kill_children`] from now on.
+    pub(super) fn track(child: Child) -> Self {
+        let pid = child.id();
+        Self::insert(pid, Tracked::Session(child))
+    }
+
+    /// [`track`](Self::track) for a server started with std::process (the
+    /// `proxy` command); [`wait_blocking`](Self::wait_blocking) reaps it.
+    pub fn track_blocking(child: std::process::Child) -> Self {
+        let pid = Some(child.id());
+        Self::insert(pid, Tracked::Blocking(child))
+    }
+
+    fn insert(pid: Option<u32>, child: Tracked) -> Self {
+        let process = Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            pid,
+        };
+        children().insert(process.id, child);
+        process
+    }
+
+    /// OS process id of the server.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// Whether the server will be killed with its session (not kept alive,
+    /// not killed yet).
+    pub fn is_tracked(&self) -> bool {
+        children().contains_key(&self.id)
+    }
+
+    /// Leave the server running when its session ends and after mcp-hack
+    /// exits. It still sees its stdin close; a server that exits on EOF
+    /// goes away regardless.
+    pub fn keep_alive(&self) {
+        let child = children().remove(&self.id);
+        drop(child);
+    }
+
+    /// Kill the server now (a no-op once it has exited or been released).
+    pub(super) fn kill(&self) {
+        let child = children().remove(&self.id);
+        if let Some(mut child) = child {
+            child.start_kill();
+        }
+    }
+
+    /// Block until the server exits and release it; None when it was
+    /// released or killed meanwhile.
+    pub fn wait_blocking(&self) -> Option<std::process::ExitStatus> {
+        loop {
+            {
+                let mut children = children();
+                let child = children.get_mut(&self.id)?;
+                match child.try_wait() {
+                    Ok(None) => {}
+                    Ok(Some(status)) => {
+                        children.remove(&self.id);
+                        return Some(status);
+                    }
+                    Err(_) => {
+                        children.remove(&self.id);
+                        return None;
+                    }
+                }
+            }
+            std::thread::sleep(EXIT_POLL);
+        }
+    }
+
+    /// Whether the server has exited (or is no longer tracked). An exited
+    /// server is reaped and released.
+    pub fn exited(&self) -> bool {
+        let mut children = children();
+        let Some(child) = children.get_mut(&self.id) else {
+            return true;
+        };
+        if matches!(child.try_wait(), Ok(None)) {
+            return false;
+        }
+        children.remove(&self.id);
+        true
+    }
+
+    /// Whether the server exits within `wait` (at once when it already has).
+    pub async fn exited_within(&self, wait: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            if self.exited() {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(EXIT_POLL).await;
+        }
+    }
+}
+
+/// How often [`ServerProcess::exited_within`] checks the server.
+const EXIT_POLL: Duration = Duration::from_millis(10);
+
+/// Kill every server still tracked, for exits that skip destructors
+/// (`std::process::exit`, e.g. on Ctrl-C).
+pub fn kill_children() {
+    let all = std::mem::take(&mut *children());
+    for (_, mut child) in all {
+        child.start_kill();
+    }
+}
+
+/// The server's stdio (or socket) as an rmcp transport; dropping it kills
+/// a spawned server.
+struct ChildTransport {
+    io: AsyncRwTransport<RoleClient, socket::Reader, socket::Writer>,
+    process: Option<ServerProcess>,
+}
+
+impl Drop for ChildTransport {
+    fn drop(&mut self) {
+        if let Some(process) = &self.process {
+            process.kill();
+        }
+    }
+}
+
+impl Transport<RoleClient> for ChildTransport {
+    type Error = std::io::Error;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        self.io.send(item)
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleClient>> {
+        self.io.receive().await
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), Self::Error>> + Send {
+        self.io.close()
+    }
+}
+
+/// Spawn a local target (or connect to a socket target) and initialize an
+/// MCP session with `client` (timed as the connect / initialize phases,
+/// recorded with --record). A spawned server is killed when the session
+/// ends. A server that fails to start is a connection error
+/// (crate::cmd::exit::Connection).
+pub async fn connect<S: Service<RoleClient>>(
+    spec: &TargetSpec,
+    client: S,
+) -> Result<RunningService<RoleClient, S>> {
+    Ok(connect_process(spec, client).await?.0)
+}
+
+/// [`connect`], also returning the handle of the server process (none for
+/// a socket target).
+pub async fn connect_process<S: Service<RoleClient>>(
+    spec: &TargetSpec,
+    client: S,
+) -> Result<(RunningService<RoleClient, S>, Option<ServerProcess>)> {
+    if !spec.is_local() {
+        return Err(crate::cmd::exit::usage(
+            "only local process and socket targets are supported",
+        ));
+    }
+    let span = tracing::debug_span!("server", target = %spec.original());
+    start(spec, client)
+        .instrument(span)
+        .await
+        .map_err(crate::cmd::exit::connection)
+}
+
+async fn start<S: Service<RoleClient>>(
+    spec: &TargetSpec,
+    client: S,
+) -> Result<(RunningService<RoleClient, S>, Option<ServerProcess>)> {
+    let (transport, tail, what) = match spec {
+        TargetSpec::LocalCommand { program, args, .. } => {
+            let (transport, tail) = spawn(program, args)?;
+            (
+                transport,
+                tail,
+                format!("Failed to spawn MCP process: {program}"),
+            )
+        }
+        TargetSpec::Socket { socket, .. } => {
+            let (read, write) = timing::measure(Phase::Connect, || socket::connect(socket))?;
+            tracing::debug!("connected to {}", socket.address());
+            let transport = ChildTransport {
+                io: AsyncRwTransport::new_client(read, write),
+                process: None,
+            };
+            let what = format!("Failed to initialize {}", socket.address());
+            (transport, StderrTail::default(), what)
+        }
+        TargetSpec::RemoteUrl { .. } => bail!("only local targets can be connected"),
+    };
+    let process = transport.process;
+
+    let started = limits::connect(timing::timed(
+        Phase::Initialize,
+        client.serve(record::wrap(transport, spec.original())),
+    ))
+    .await;
+    match started {
+        Ok(Ok(service)) => Ok((service, process)),
+        Ok(Err(e)) => {
+            tail.settle().await;
+            Err(e).context(what)
+        }
+        Err(e) => {
+            tail.settle().await;
+            Err(e)
+        }
+    }
+}
+
+/// Spawn `program` with piped stdio (stderr per --stderr).
+fn spawn(program: &str, args: &[String]) -> Result<(ChildTransport, StderrTail)> {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(stdio());
+    prepare(command.as_std_mut());
+    let mut child = timing::measure(Phase::Connect, || command.spawn())
+        .with_context(|| format!("Failed to spawn MCP process: {program}"))?;
+    let (Some(stdout), Some(stdin)) = (child.stdout.take(), child.stdin.take()) else {
+        bail!("Failed to spawn MCP process: {program}: no stdio pipes");
+    };
+    let tail = child
+        .stderr
+        .take()
+        .map(StderrTail::capture)
+        .unwrap_or_default();
+    let process = ServerProcess::track(child);
+    tracing::debug!("spawned {program} (pid {:?})", process.pid());
+    let transport = ChildTransport {
+        io: AsyncRwTransport::new_client(Box::new(stdout), Box::new(stdin)),
+        process: Some(process),
+    };
+    Ok((transport, tail))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keeps_the_stderr_tail() {
+        let mut output = String::from("\n");
+        for i in 0..25 {
+            output.push_str(&format!("line {i}\r\n"));
+        }
+        output.push_str("Traceback: \u{fffd}no newline");
+        let tail = StderrTail::drain(std::io::Cursor::new(output.into_bytes()));
+        tail.settle().await;
+        let lines = tail.lines();
+        assert_eq!(lines.len(), TAIL_LINES);
+        assert_eq!(lines[0], "line 6");
+        assert_eq!(lines.last().unwrap(), "Traceback: \u{fffd}no newline");
+        assert!(StderrTail::default().lines().is_empty());
+    }
+
+    #[test]
+    fn parses_env_files() {
+        let vars = parse_env_file(
+            "# api access\nexport API_KEY=\"sk-1 2\"\n\nEMPTY=\nURL='http://x/?a=b'\nRAW = v \n",
+        )
+        .unwrap();
+        let vars: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            vars,
+            vec![
+                ("API_KEY", "sk-1 2"),
+                ("EMPTY", ""),
+                ("URL", "http://x/?a=b"),
+                ("RAW", "v")
+            ]
+        );
+        let err = parse_env_file("A=1\nnot a pair\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+        assert!(env_pair("=v").is_err());
+        assert_eq!(env_pair("K=a=b").unwrap(), ("K".into(), "a=b".into()));
+    }
+
+    #[test]
+    fn rejects_missing_cwd() {
+        let err = set_cwd("/nonexistent/mcp-hack").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'/nonexistent/mcp-hack' is not a directory"
+        );
+        assert!(cwd().is_none());
+    }
+
+    #[test]
+    fn stderr_file_needs_capture() {
+        let err = set_stderr(StderrMode::Inherit, Some("unused.log")).unwrap_err();
+        assert_eq!(err.to_string(), "--stderr-file needs --stderr capture");
+        assert!(!std::path::Path::new("unused.log").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kills_tracked_servers() {
+        let sleep = || Command::new("sleep").arg("30").spawn().unwrap();
+        let killed = ServerProcess::track(sleep());
+        let kept = ServerProcess::track(sleep());
+        assert!(killed.pid().is_some());
+        assert!(killed.is_tracked() && kept.is_tracked());
+        kept.keep_alive();
+        assert!(!kept.is_tracked());
+        killed.kill();
+        assert!(!killed.is_tracked());
+        let _ = std::process::Command::new("kill")
+            .arg(kept.pid().unwrap().to_string())
+            .status();
+    }
+    #[cfg(unix)]
+    #[test]
+    fn waits_for_blocking_servers() {
+        let exits = std::process::Command::new("true").spawn().unwrap();
+        let exits = ServerProcess::track_blocking(exits);
+        assert!(exits.is_tracked());
+        assert!(exits.wait_blocking().unwrap().success());
+        assert!(!exits.is_tracked());
+
+        let sleeps = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let sleeps = ServerProcess::track_blocking(sleeps);
+        sleeps.kill();
+        assert_eq!(sleeps.wait_blocking(), None);
+    }
+}
@@ -0,0 +1,116 @@
+//! `ssh://` targets: an MCP server on another host, spoken to over SSH.
+//!
+//!   -t "ssh://[USER@]HOST[:PORT] -- REMOTE COMMAND"
+//!
+//! runs `ssh -T [-p PORT] [-l USER] HOST 'REMOTE COMMAND'`, so the server's
+//! stdio is forwarded over the SSH channel (no pseudo-terminal, which would
+//! mangle the protocol) and nothing needs installing on the host. The
+//! remote command is passed verbatim to the remote shell (`cd /srv && ./server`
+//! works). `--env` / `--env-file` variables are forwarded by name
+//! (`-o SendEnv=KEY`; ssh sends the value from its own environment, so
+//! secrets stay off both command lines). The server's sshd must accept them
+//! (`AcceptEnv KEY` in sshd_config), otherwise they are silently dropped.
+//! Authentication, jump hosts and host keys come from the local ssh config.
+
+use anyhow::{Context, Result, bail};
+use url::Url;
+
+use super::spawn;
+
+/// Target prefix.
+pub const SCHEME: &str = "ssh://";
+
+/// `ssh` arguments for a target starting with `ssh://`.
+pub fn ssh_args(target: &str) -> Result<Vec<String>> {
+    let env = spawn::env();
+    let keys: Vec<&str> = env.iter().map(|(k, _)| k.as_str()).collect();
+    build_args(target, &keys)
+}
+
+/// `ssh` arguments forwarding the variables named in `env_keys`.
+fn build_args(target: &str, env_keys: &[&str]) -> Result<Vec<String>> {
+    let Some((destination, command)) = target.split_once(" -- ") else {
+        bail!("ssh target needs a remote command: ssh://[USER@]HOST[:PORT] -- COMMAND");
+    };
+    let command = command.trim();
+    if command.is_empty() {
+        bail!("ssh target needs a remote command: ssh://[USER@]HOST[:PORT] -- COMMAND");
+    }
+    let url = Url::parse(destination.trim())
+        .with_context(|| format!("Invalid ssh target '{}'", destination.trim()))?;
+    if url.password().is_some() {
+        bail!("ssh targets do not take a password; use keys or an ssh agent");
+    }
+    let host = url
+        .host_str()
+        .filter(|h| !h.is_empty())
+        .context("ssh target has no host")?;
+    if host.starts_with('-') {
+        bail!("invalid ssh host '{host}'");
+    }
+    if !matches!(url.path(), "" | "/") {
+        bail!("put the remote command after ' -- ', not in the ssh URL path");
+    }
+
+    let mut args = vec!["-T".to_string()];
+    if let Some(port) = url.port() {
+        args.extend(["-p".to_string(), port.to_string()]);
+    }
+    if !url.username().is_empty() {
+        args.extend(["-l".to_string(), url.username().to_string()]);
+    }
+    let mut sent: Vec<&str> = Vec::new();
+    for key in env_keys {
+        if !sent.contains(key) {
+            sent.push(key);
+            args.extend(["-o".to_string(), format!("SendEnv={key}")]);
+        }
+    }
+    args.push(
+        host.trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+    );
+    args.push(command.to_string());
+    Ok(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_ssh_arguments() {
+        assert_eq!(
+            ssh_args("ssh://ops@jump.example:2222 -- cd /srv && ./server --stdio").unwrap(),
+            vec![
+                "-T",
+                "-p",
+                "2222",
+                "-l",
+                "ops",
+                "jump.example",
+                "cd /srv && ./server --stdio"
+            ]
+        );
+        assert_eq!(
+            ssh_args("ssh://[::1] -- srv").unwrap(),
+            vec!["-T", "::1", "srv"]
+        );
+        assert_eq!(
+            build_args("ssh://h -- srv", &["TOKEN", "DEBUG", "TOKEN"]).unwrap(),
+            vec![
+                "-T",
+                "-o",
+                "SendEnv=TOKEN",
+                "-o",
+                "SendEnv=DEBUG",
+                "h",
+                "srv"
+            ]
+        );
+        assert!(ssh_args("ssh://host").is_err());
+        assert!(ssh_args("ssh://u:pw@host -- srv").is_err());
+        assert!(ssh_args("ssh://host/opt/srv -- x").is_err());
+    }
+}
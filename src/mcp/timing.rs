@@ -0,0 +1,136 @@
+//! Per-command timing breakdown.
+//!
+//! Session helpers (raw::RawSession, the rmcp spawn paths) add the time they
+//! spend spawning the server, in the initialize handshake and shutting down
+//! to a process-wide accumulator; everything else since `begin` counts as
+//! the command's own operation. Commands put `snapshot().to_json()` in their
+//! JSON output as `timings`, so slow `npx` startup is not mistaken for tool
+//! latency.
+//!
+//! Each measured phase is also a `tracing` span (`connect`, `initialize`,
+//! `shutdown`, shown with -v, see crate::utils::diagnostics).
+
+use serde_json::{Value, json};
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Session phases tracked separately from the operation itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Spawning the server process
+    Connect,
+    /// initialize request / response and notifications/initialized
+    Initialize,
+    /// Closing stdin / cancelling the service and waiting for exit
+    Shutdown,
+}
+
+#[derive(Debug, Default)]
+struct Acc {
+    connect: Duration,
+    initialize: Duration,
+    shutdown: Duration,
+}
+
+static START: OnceLock<Instant> = OnceLock::new();
+static ACC: Mutex<Acc> = Mutex::new(Acc {
+    connect: Duration::ZERO,
+    initialize: Duration::ZERO,
+    shutdown: Duration::ZERO,
+});
+
+/// Mark the start of the command (first call wins).
+pub fn begin() {
+    START.get_or_init(Instant::now);
+}
+
+/// Add time spent in a session phase.
+pub fn add(phase: Phase, d: Duration) {
+    begin();
+    if let Ok(mut acc) = ACC.lock() {
+        match phase {
+            Phase::Connect => acc.connect += d,
+            Phase::Initialize => acc.initialize += d,
+            Phase::Shutdown => acc.shutdown += d,
+        }
+    }
+}
+
+fn span(phase: Phase) -> tracing::Span {
+    match phase {
+        Phase::Connect => tracing::debug_span!("connect"),
+        Phase::Initialize => tracing::debug_span!("initialize"),
+        Phase::Shutdown => tracing::debug_span!("shutdown"),
+    }
+}
+
+/// Run a synchronous step and account its duration to `phase`.
+pub fn measure<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    let _span = span(phase).entered();
+    let started = Instant::now();
+    let out = f();
+    add(phase, started.elapsed());
+    out
+}
+
+/// Await a future and account its duration to `phase`.
+pub async fn timed<F: Future>(phase: Phase, fut: F) -> F::Output {
+    let started = Instant::now();
+    let out = fut.instrument(span(phase)).await;
+    add(phase, started.elapsed());
+    out
+}
+
+/// Breakdown at one point in time (milliseconds).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timings {
+    pub connect_ms: u128,
+    pub initialize_ms: u128,
+    pub operation_ms: u128,
+    pub shutdown_ms: u128,
+}
+
+impl Timings {
+    pub fn to_json(self) -> Value {
+        json!({
+            "connect_ms": self.connect_ms,
+            "initialize_ms": self.initialize_ms,
+            "operation_ms": self.operation_ms,
+            "shutdown_ms": self.shutdown_ms,
+        })
+    }
+}
+
+/// Current breakdown; operation is the wall time since `begin` not spent in
+/// a session phase.
+pub fn snapshot() -> Timings {
+    let total = START.get_or_init(Instant::now).elapsed();
+    let (connect, initialize, shutdown) = ACC
+        .lock()
+        .map(|a| (a.connect, a.initialize, a.shutdown))
+        .unwrap_or_default();
+    Timings {
+        connect_ms: connect.as_millis(),
+        initialize_ms: initialize.as_millis(),
+        operation_ms: total
+            .saturating_sub(connect + initialize + shutdown)
+            .as_millis(),
+        shutdown_ms: shutdown.as_millis(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_excluded_from_operation() {
+        begin();
+        add(Phase::Initialize, Duration::from_millis(5));
+        let t = snapshot();
+        assert!(t.initialize_ms >= 5);
+        assert_eq!(t.to_json()["initialize_ms"], json!(t.initialize_ms));
+    }
+}
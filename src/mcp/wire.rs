@@ -0,0 +1,106 @@
+//! Wire log (`--wire-log` / `--wire-log=FILE`): every JSON-RPC frame
+//! exchanged with a server, printed as it passes.
+//!
+//! ```text
+//! 2025-01-01T00:00:00.000Z #1 === python3 server.py
+//! 2025-01-01T00:00:00.004Z #1 --> {"jsonrpc":"2.0","id":0,"method":"initialize",...}
+//! 2025-01-01T00:00:00.090Z #1 <-- {"jsonrpc":"2.0","id":0,"result":{...}}
+//! ```
+//!
+//! `-->` goes to the server, `<--` comes from it; `#N` numbers the servers
+//! started by the command (the same numbers as a --record transcript). Frames
+//! go to stderr, or to FILE (truncated); `--wire-format pretty` indents them.
+//! Everything crate::mcp::record sees is logged: raw sessions, the rmcp
+//! client transport and `proxy`.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+use super::record::Dir;
+use crate::utils::utc_timestamp;
+
+/// How frames are laid out (`--wire-format`).
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    /// One line per frame
+    #[default]
+    Compact,
+    /// Indented JSON below the frame header
+    Pretty,
+}
+
+struct WireLog {
+    sink: Mutex<Box<dyn Write + Send>>,
+    format: WireFormat,
+}
+
+static LOG: OnceLock<WireLog> = OnceLock::new();
+
+/// Start logging frames to `path` (`-` or None: stderr).
+pub fn start(path: Option<&str>, format: WireFormat) -> Result<()> {
+    let sink: Box<dyn Write + Send> = match path.filter(|p| *p != "-") {
+        Some(p) => Box::new(File::create(p).with_context(|| format!("failed to create {p}"))?),
+        None => Box::new(std::io::stderr()),
+    };
+    let _ = LOG.set(WireLog {
+        sink: Mutex::new(sink),
+        format,
+    });
+    Ok(())
+}
+
+pub fn enabled() -> bool {
+    LOG.get().is_some()
+}
+
+fn write(text: &str) {
+    if let Some(Ok(mut sink)) = LOG.get().map(|l| l.sink.lock()) {
+        let _ = writeln!(sink, "{text}").and_then(|_| sink.flush());
+    }
+}
+
+/// Log the start of server session `id`.
+pub fn session(id: u64, target: &str) {
+    write(&format!("{} #{id} === {target}", utc_timestamp()));
+}
+
+/// Log one frame of session `id`.
+pub fn frame(id: u64, dir: Dir, msg: &Value) {
+    if let Some(log) = LOG.get() {
+        write(&format_frame(&utc_timestamp(), id, dir, msg, log.format));
+    }
+}
+
+fn format_frame(time: &str, id: u64, dir: Dir, msg: &Value, format: WireFormat) -> String {
+    let arrow = match dir {
+        Dir::Send => "-->",
+        Dir::Recv => "<--",
+    };
+    match format {
+        WireFormat::Compact => format!("{time} #{id} {arrow} {msg}"),
+        WireFormat::Pretty => format!(
+            "{time} #{id} {arrow}\n{}",
+            serde_json::to_string_pretty(msg).unwrap_or_else(|_| msg.to_string())
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn formats_frames() {
+        let msg = json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        assert_eq!(
+            format_frame("T", 2, Dir::Send, &msg, WireFormat::Compact),
+            r#"T #2 --> {"id":1,"jsonrpc":"2.0","method":"tools/list"}"#
+        );
+        let pretty = format_frame("T", 2, Dir::Recv, &json!({"id": 1}), WireFormat::Pretty);
+        assert_eq!(pretty, "T #2 <--\n{\n  \"id\": 1\n}");
+    }
+}
@@ -0,0 +1,169 @@
+//! Accepted-findings baselines (`scan --baseline FILE [--update-baseline]`).
+//!
+//! A baseline is a JSON file listing the findings already known for one or
+//! more targets, keyed by the export dedup key (target + check + tool +
+//! title, see scan::export), so a severity change does not make a finding
+//! new. Scans compared against it report, export and gate on new findings
+//! only; baseline entries no longer produced are listed as resolved.
+//! `--update-baseline` replaces the entries of the scanned target with its
+//! current findings and keeps the other targets' entries.
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+
+use super::Finding;
+use super::export::dedup_key;
+use crate::utils::utc_timestamp;
+
+pub const VERSION: u32 = 1;
+
+/// One accepted finding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub key: String,
+    pub target: String,
+    pub check: String,
+    #[serde(default)]
+    pub tool: Option<String>,
+    pub severity: String,
+    pub title: String,
+}
+
+impl Entry {
+    fn from_finding(target: &str, f: &Finding) -> Self {
+        Entry {
+            key: dedup_key(target, f),
+            target: target.to_string(),
+            check: f.check.to_string(),
+            tool: f.tool.clone(),
+            severity: f.severity.as_str().to_string(),
+            title: f.title.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "check": self.check,
+            "tool": self.tool,
+            "severity": self.severity,
+            "title": self.title,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub version: u32,
+    pub updated_at: String,
+    pub findings: Vec<Entry>,
+}
+
+/// Current findings split against a baseline.
+#[derive(Debug, Default)]
+pub struct Compared {
+    pub new: Vec<Finding>,
+    pub known: Vec<Finding>,
+    /// Baseline entries of the target that were not produced again
+    pub resolved: Vec<Entry>,
+}
+
+/// Load a baseline file (None when it does not exist yet).
+pub fn load(path: &str) -> Result<Option<Baseline>> {
+    if !std::path::Path::new(path).exists() {
+        return Ok(None);
+    }
+    let raw =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read baseline {path}"))?;
+    let baseline: Baseline =
+        serde_json::from_str(&raw).with_context(|| format!("corrupt baseline {path}"))?;
+    if baseline.version != VERSION {
+        bail!(
+            "baseline {path} has version {} (expected {VERSION})",
+            baseline.version
+        );
+    }
+    Ok(Some(baseline))
+}
+
+pub fn save(path: &str, baseline: &Baseline) -> Result<()> {
+    let body = serde_json::to_string_pretty(baseline).context("failed to serialize baseline")?;
+    std::fs::write(path, body).with_context(|| format!("failed to write baseline {path}"))
+}
+
+pub fn compare(baseline: Option<&Baseline>, target: &str, findings: Vec<Finding>) -> Compared {
+    let entries: Vec<&Entry> = baseline
+        .map(|b| b.findings.iter().filter(|e| e.target == target).collect())
+        .unwrap_or_default();
+    let keys: HashSet<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+    let mut out = Compared::default();
+    let mut seen = HashSet::new();
+    for f in findings {
+        let key = dedup_key(target, &f);
+        if keys.contains(key.as_str()) {
+            out.known.push(f);
+            seen.insert(key);
+        } else {
+            out.new.push(f);
+        }
+    }
+    out.resolved = entries
+        .into_iter()
+        .filter(|e| !seen.contains(&e.key))
+        .cloned()
+        .collect();
+    out
+}
+
+/// `baseline` with the target's entries replaced by `findings`.
+pub fn update(baseline: Option<Baseline>, target: &str, findings: &[Finding]) -> Baseline {
+    let mut entries: Vec<Entry> = baseline
+        .map(|b| b.findings)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e.target != target)
+        .collect();
+    entries.extend(findings.iter().map(|f| Entry::from_finding(target, f)));
+    Baseline {
+        version: VERSION,
+        updated_at: utc_timestamp(),
+        findings: entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::Severity;
+
+    #[test]
+    fn compares_and_updates_per_target() {
+        let a = Finding::new("prompt-injection", Severity::High, "a").tool("read_file");
+        let b = Finding::new("dangerous-tool", Severity::Medium, "b").tool("exec");
+        let gone = Finding::new("tool-naming", Severity::Low, "gone");
+        let base = update(None, "srv", &[a.clone(), gone]);
+        let base = update(Some(base), "other", std::slice::from_ref(&b));
+
+        // Severity changes keep a finding known; other targets' entries do not match.
+        let mut a_raised = a.clone();
+        a_raised.severity = Severity::Critical;
+        let c = compare(Some(&base), "srv", vec![a_raised, b.clone()]);
+        assert_eq!(c.known.len(), 1);
+        assert_eq!(
+            c.new.iter().map(|f| f.title.as_str()).collect::<Vec<_>>(),
+            vec!["b"]
+        );
+        assert_eq!(c.resolved.len(), 1);
+        assert_eq!(c.resolved[0].title, "gone");
+
+        let updated = update(Some(base), "srv", &[a, b]);
+        let targets: Vec<(&str, &str)> = updated
+            .findings
+            .iter()
+            .map(|e| (e.target.as_str(), e.title.as_str()))
+            .collect();
+        assert_eq!(targets, vec![("other", "b"), ("srv", "a"), ("srv", "b")]);
+        assert!(compare(None, "srv", vec![]).new.is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Secret canarying of sensitive parameters.
+//!
+//! Parameters that look like secrets (password, token, api key, ...) are
+//! called with unique canary values. Everything the server sends afterwards
+//! in the same session is searched for those values:
+//!   - results of the call itself (echo) and of later calls
+//!   - log / other notifications, non-JSON stdout lines
+//!   - resource contents (resources/read) and prompts (prompts/list / get)
+//!
+//! A canary that resurfaces means the server stores or echoes credentials.
+//! Destructive tools (annotations.destructiveHint) are skipped unless asked.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::time::Duration;
+
+use super::risk::name_tokens;
+use super::{Finding, Severity, list_all, read_resources};
+use crate::cmd::shared::{coerce_value, placeholder_for};
+use crate::mcp::TargetSpec;
+use crate::mcp::raw::RawSession;
+use crate::utils::random_token;
+
+/// Parameter name tokens treated as secrets.
+const SECRET_TOKENS: &[&str] = &[
+    "password",
+    "passwd",
+    "passphrase",
+    "pwd",
+    "token",
+    "secret",
+    "apikey",
+    "credential",
+    "credentials",
+    "otp",
+];
+
+/// Upper bound on prompts fetched while sweeping.
+const MAX_PROMPTS: usize = 50;
+
+/// Names of secret-looking parameters in a tool's input schema.
+pub fn secret_params(tool: &Value) -> Vec<String> {
+    let Some(props) = tool
+        .get("inputSchema")
+        .or_else(|| tool.get("input_schema"))
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object())
+    else {
+        return Vec::new();
+    };
+    props
+        .iter()
+        .filter(|(name, p)| {
+            let ty = p.get("type").and_then(|v| v.as_str()).unwrap_or("string");
+            if ty != "string" {
+                return false;
+            }
+            let toks = name_tokens(name);
+            toks.iter().any(|t| SECRET_TOKENS.contains(&t.as_str()))
+                || toks.windows(2).any(|w| w[0] == "api" && w[1] == "key")
+                || p.get("format").and_then(|v| v.as_str()) == Some("password")
+                || p.get("writeOnly").and_then(|v| v.as_bool()) == Some(true)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// One canary value sent to a tool parameter.
+#[derive(Debug, Clone)]
+pub struct Plant {
+    pub tool: String,
+    pub param: String,
+    pub canary: String,
+}
+
+/// Where a canary resurfaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Channel {
+    /// Result of the call that planted it
+    Echo,
+    /// Result of a later call to the named tool
+    ToolResult(String),
+    /// Notification (method)
+    Notification(String),
+    /// Non-JSON stdout line
+    Stdout,
+    Resource(String),
+    Prompt(String),
+}
+
+impl Channel {
+    pub fn describe(&self) -> String {
+        match self {
+            Channel::Echo => "echoed in the call's own result".into(),
+            Channel::ToolResult(t) => format!("result of later call to {t}"),
+            Channel::Notification(m) => format!("notification {m}"),
+            Channel::Stdout => "server stdout".into(),
+            Channel::Resource(uri) => format!("resource {uri}"),
+            Channel::Prompt(name) => format!("prompt {name}"),
+        }
+    }
+
+    fn severity(&self) -> Severity {
+        match self {
+            Channel::Echo => Severity::Low,
+            Channel::Notification(_) | Channel::Stdout => Severity::Medium,
+            Channel::ToolResult(_) | Channel::Resource(_) | Channel::Prompt(_) => Severity::High,
+        }
+    }
+}
+
+/// Outcome of a canary run.
+#[derive(Debug, Default)]
+pub struct CanaryRun {
+    pub plants: Vec<Plant>,
+    /// (index into plants, channel)
+    pub sightings: Vec<(usize, Channel)>,
+    /// (tool, reason) for tools with secret params that were not called
+    pub skipped: Vec<(String, String)>,
+    pub resources_checked: usize,
+    pub prompts_checked: usize,
+}
+
+impl CanaryRun {
+    fn sweep(&mut self, text: &str, channel: Channel) {
+        for (i, plant) in self.plants.iter().enumerate() {
+            let seen = self.sightings.iter().any(|(j, c)| *j == i && *c == channel);
+            if !seen && text.contains(&plant.canary) {
+                self.sightings.push((i, channel.clone()));
+            }
+        }
+    }
+
+    fn sweep_frames(&mut self, frames: &[Value]) {
+        for f in frames {
+            let channel = match (
+                f.get("method").and_then(|v| v.as_str()),
+                f.get("non_json_line"),
+            ) {
+                (_, Some(_)) => Channel::Stdout,
+                (Some(m), _) => Channel::Notification(m.to_string()),
+                _ => continue,
+            };
+            self.sweep(&f.to_string(), channel);
+        }
+    }
+
+    /// One finding per (plant, channel).
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut out: Vec<Finding> = self
+            .sightings
+            .iter()
+            .map(|(i, channel)| {
+                let p = &self.plants[*i];
+                Finding::new(
+                    "secret-canary",
+                    channel.severity(),
+                    format!(
+                        "Secret parameter '{}' resurfaced: {}",
+                        p.param,
+                        channel.describe()
+                    ),
+                )
+                .tool(p.tool.clone())
+                .detail(
+                    "A unique canary sent as a credential came back from the server. \
+                     Credentials should be used, not stored, logged or echoed.",
+                )
+                .evidence(p.canary.clone())
+            })
+            .collect();
+        out.sort_by(Finding::report_order);
+        out
+    }
+}
+
+/// Arguments for one plant: canaries for secret params, placeholders for
+/// other required params.
+pub(super) fn plant_arguments(tool: &Value, secrets: &[(String, String)]) -> Map<String, Value> {
+    let schema = tool.get("inputSchema").or_else(|| tool.get("input_schema"));
+    let props = schema
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object());
+    let mut args = Map::new();
+    for name in schema
+        .and_then(|s| s.get("required"))
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+    {
+        let prop = props
+            .and_then(|p| p.get(name))
+            .cloned()
+            .unwrap_or(Value::Null);
+        let ty = prop
+            .get("type")
+            .and_then(|v| v.as_str())
+            .unwrap_or("string");
+        args.insert(name.to_string(), coerce_value(&placeholder_for(&prop), ty));
+    }
+    for (param, canary) in secrets {
+        args.insert(param.clone(), json!(canary));
+    }
+    args
+}
+
+/// Plant canaries in every secret-looking parameter, then sweep results,
+/// notifications, resources and prompts for them.
+pub async fn run(
+    spec: &TargetSpec,
+    timeout: Duration,
+    include_destructive: bool,
+) -> Result<CanaryRun> {
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    // Raw frames: canaries are searched for in everything the server sends.
+    let mut session = RawSession::spawn(spec, timeout)?;
+    let init = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    let tools = list_all(&mut session, "tools/list", "tools", usize::MAX).await?;
+
+    let mut run = CanaryRun::default();
+    crate::utils::progress_events::phase("plant", None);
+    for tool in &tools {
+        let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let params = secret_params(tool);
+        if name.is_empty() || params.is_empty() {
+            continue;
+        }
+        let destructive = tool
+            .pointer("/annotations/destructiveHint")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if destructive && !include_destructive {
+            run.skipped.push((
+                name.to_string(),
+                "destructive (use --include-destructive)".into(),
+            ));
+            continue;
+        }
+        let secrets: Vec<(String, String)> = params
+            .into_iter()
+            .map(|p| (p, format!("mcphackcanary{}", random_token(12))))
+            .collect();
+        let first_new = run.plants.len();
+        run.plants
+            .extend(secrets.iter().map(|(param, canary)| Plant {
+                tool: name.to_string(),
+                param: param.clone(),
+                canary: canary.clone(),
+            }));
+
+        let before = session.interleaved.len();
+        let resp = session
+            .request(
+                "tools/call",
+                Some(json!({ "name": name, "arguments": plant_arguments(tool, &secrets) })),
+            )
+            .await;
+        let frames = session.interleaved[before..].to_vec();
+        run.sweep_frames(&frames);
+        match resp {
+            Ok(resp) => {
+                let text = resp.to_string();
+                // Earlier plants showing up here leaked across calls; the
+                // current plant only echoes.
+                for (i, plant) in run.plants.clone().iter().enumerate() {
+                    if text.contains(&plant.canary) {
+                        let channel = if i >= first_new {
+                            Channel::Echo
+                        } else {
+                            Channel::ToolResult(name.to_string())
+                        };
+                        if !run.sightings.contains(&(i, channel.clone())) {
+                            run.sightings.push((i, channel));
+                        }
+                    }
+                }
+            }
+            Err(e) => run.skipped.push((name.to_string(), format!("{e:#}"))),
+        }
+    }
+    // Late log lines for the last calls.
+    let late = session.drain(Duration::from_millis(300)).await;
+    run.sweep_frames(&late);
+
+    if !run.plants.is_empty() {
+        if init.pointer("/result/capabilities/resources").is_some() {
+            crate::utils::progress_events::phase("sweep", Some("resources"));
+            let contents = read_resources(&mut session).await.unwrap_or_default();
+            run.resources_checked = contents.len();
+            for c in &contents {
+                let uri = c.get("uri").and_then(|v| v.as_str()).unwrap_or("?");
+                run.sweep(&c.to_string(), Channel::Resource(uri.to_string()));
+            }
+        }
+        if init.pointer("/result/capabilities/prompts").is_some() {
+            crate::utils::progress_events::phase("sweep", Some("prompts"));
+            let prompts = list_all(&mut session, "prompts/list", "prompts", MAX_PROMPTS)
+                .await
+                .unwrap_or_default();
+            for p in &prompts {
+                let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                run.sweep(&p.to_string(), Channel::Prompt(name.to_string()));
+                let needs_args = p
+                    .get("arguments")
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|a| {
+                        a.iter()
+                            .any(|arg| arg.get("required").and_then(|v| v.as_bool()) == Some(true))
+                    });
+                if !needs_args
+                    && let Ok(resp) = session
+                        .request("prompts/get", Some(json!({ "name": name })))
+                        .await
+                {
+                    run.sweep(&resp.to_string(), Channel::Prompt(name.to_string()));
+                }
+                run.prompts_checked += 1;
+            }
+        }
+        let frames = std::mem::take(&mut session.interleaved);
+        run.sweep_frames(&frames);
+    }
+    session.shutdown().await;
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_secret_params() {
+        let t = json!({"name": "login", "inputSchema": {"properties": {
+            "username": {"type": "string"},
+            "password": {"type": "string"},
+            "apiKey": {"type": "string"},
+            "x": {"type": "string", "format": "password"},
+            "token_count": {"type": "integer"},
+        }}});
+        let mut p = secret_params(&t);
+        p.sort();
+        assert_eq!(p, vec!["apiKey", "password", "x"]);
+    }
+
+    #[test]
+    fn sweep_records_each_channel_once() {
+        let mut run = CanaryRun {
+            plants: vec![Plant {
+                tool: "login".into(),
+                param: "password".into(),
+                canary: "mcphackcanaryabc".into(),
+            }],
+            ..Default::default()
+        };
+        run.sweep(
+            "stored: mcphackcanaryabc",
+            Channel::Resource("mem://db".into()),
+        );
+        run.sweep(
+            "again mcphackcanaryabc",
+            Channel::Resource("mem://db".into()),
+        );
+        run.sweep_frames(&[
+            json!({"method": "notifications/message", "params": {"data": "pw=mcphackcanaryabc"}}),
+        ]);
+        assert_eq!(run.sightings.len(), 2);
+        let f = run.findings();
+        assert_eq!(f[0].severity, Severity::High);
+        assert_eq!(f[1].severity, Severity::Medium);
+    }
+}
@@ -0,0 +1,231 @@
+//! Entropy and anomaly scoring of returned content.
+//!
+//! `ContentAnalyzer` is fed every response text of a run (fuzz) and flags
+//! responses that look unlike the rest or carry opaque data:
+//!   - encoded blobs: long tokens with high Shannon entropy (base64, hex, ciphertext)
+//!   - binary data: control characters / U+FFFD replacement characters
+//!   - length outliers: far above the run's median (robust, MAD based)
+//!   - charset anomalies: a character class never seen in earlier responses
+//!
+//! Regex-style rules miss these because the payload is unknown in advance.
+
+use serde_json::{Value, json};
+
+/// Tokens shorter than this are never considered blobs.
+const BLOB_MIN_LEN: usize = 32;
+/// Bits per character above which a long token counts as encoded / random.
+const BLOB_MIN_ENTROPY: f64 = 4.2;
+/// Share of control characters that marks a response as binary.
+const BINARY_RATIO: f64 = 0.05;
+/// Responses observed before length / charset baselines are trusted.
+const MIN_SAMPLES: usize = 5;
+/// Robust z-score (median / MAD) above which a length is an outlier.
+const LENGTH_Z: f64 = 6.0;
+
+/// Shannon entropy in bits per character.
+pub fn shannon_entropy(text: &str) -> f64 {
+    let mut counts = std::collections::HashMap::new();
+    let mut total = 0usize;
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Character classes used for charset anomalies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+    NonAscii,
+    Control,
+}
+
+impl Charset {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Charset::NonAscii => "non-ASCII",
+            Charset::Control => "control",
+        }
+    }
+}
+
+fn charsets(text: &str) -> Vec<Charset> {
+    let mut out = Vec::new();
+    if !text.is_ascii() {
+        out.push(Charset::NonAscii);
+    }
+    if text.chars().any(is_control) {
+        out.push(Charset::Control);
+    }
+    out
+}
+
+fn is_control(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\n' | '\r' | '\t')) || c == '\u{FFFD}'
+}
+
+/// One anomaly in a response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Anomaly {
+    /// (token prefix, token length, entropy)
+    EncodedBlob(String, usize, f64),
+    /// Share of control / replacement characters
+    Binary(f64),
+    /// (length, run median)
+    LengthOutlier(usize, usize),
+    /// Character class first seen in this response
+    Charset(&'static str),
+}
+
+impl Anomaly {
+    pub fn describe(&self) -> String {
+        match self {
+            Anomaly::EncodedBlob(prefix, len, h) => {
+                format!("encoded blob ({len} chars, entropy {h:.2}): {prefix}…")
+            }
+            Anomaly::Binary(ratio) => format!("binary data ({:.0}% control chars)", ratio * 100.0),
+            Anomaly::LengthOutlier(len, median) => {
+                format!("length outlier ({len} bytes vs median {median})")
+            }
+            Anomaly::Charset(class) => format!("new charset: {class} characters"),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Anomaly::EncodedBlob(..) => "encoded-blob",
+            Anomaly::Binary(_) => "binary",
+            Anomaly::LengthOutlier(..) => "length-outlier",
+            Anomaly::Charset(_) => "charset",
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "kind": self.kind(), "detail": self.describe() })
+    }
+}
+
+/// Encoded blobs (long high-entropy tokens) in a text.
+fn blobs(text: &str) -> Vec<Anomaly> {
+    text.split(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ',' | '<' | '>'))
+        .filter(|tok| tok.chars().count() >= BLOB_MIN_LEN)
+        // URLs and paths are long but structured; skip the obvious ones.
+        .filter(|tok| !tok.contains("://") && tok.matches('/').count() < 3)
+        .filter_map(|tok| {
+            let h = shannon_entropy(tok);
+            (h >= BLOB_MIN_ENTROPY).then(|| {
+                Anomaly::EncodedBlob(tok.chars().take(16).collect(), tok.chars().count(), h)
+            })
+        })
+        .take(3)
+        .collect()
+}
+
+fn median(sorted: &[usize]) -> f64 {
+    let n = sorted.len();
+    if n % 2 == 1 {
+        sorted[n / 2] as f64
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) as f64 / 2.0
+    }
+}
+
+/// Stateful analyzer for one run.
+#[derive(Debug, Default)]
+pub struct ContentAnalyzer {
+    lengths: Vec<usize>,
+    seen_charsets: Vec<Charset>,
+}
+
+impl ContentAnalyzer {
+    /// Score a response against the run so far, then record it.
+    pub fn observe(&mut self, text: &str) -> Vec<Anomaly> {
+        let mut out = blobs(text);
+
+        let chars = text.chars().count();
+        let control = text.chars().filter(|&c| is_control(c)).count();
+        if chars > 0 && control as f64 / chars as f64 >= BINARY_RATIO {
+            out.push(Anomaly::Binary(control as f64 / chars as f64));
+        }
+
+        let len = text.len();
+        if self.lengths.len() >= MIN_SAMPLES {
+            let mut sorted = self.lengths.clone();
+            sorted.sort_unstable();
+            let med = median(&sorted);
+            let mut dev: Vec<usize> = sorted
+                .iter()
+                .map(|&l| (l as f64 - med).abs() as usize)
+                .collect();
+            dev.sort_unstable();
+            // A constant-length run gets a small floor so +1 byte is not an outlier.
+            let mad = median(&dev).max(med * 0.05).max(8.0);
+            if (len as f64 - med) / (1.4826 * mad) > LENGTH_Z {
+                out.push(Anomaly::LengthOutlier(len, med as usize));
+            }
+        }
+
+        let classes = charsets(text);
+        for class in &classes {
+            if self.lengths.len() >= MIN_SAMPLES
+                && !self.seen_charsets.contains(class)
+                && !out.iter().any(|a| matches!(a, Anomaly::Binary(_)))
+            {
+                out.push(Anomaly::Charset(class.as_str()));
+            }
+        }
+        for class in classes {
+            if !self.seen_charsets.contains(&class) {
+                self.seen_charsets.push(class);
+            }
+        }
+        self.lengths.push(len);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_bounds() {
+        assert_eq!(shannon_entropy(""), 0.0);
+        assert_eq!(shannon_entropy("aaaa"), 0.0);
+        assert!((shannon_entropy("abcd") - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn flags_blob_and_binary() {
+        let mut a = ContentAnalyzer::default();
+        let blob = "token=QWxhZGRpbjpvcGVuIHNlc2FtZSBhbmQgc29tZSBtb3JlIHNlY3JldHM9";
+        assert!(a.observe(blob).iter().any(|x| x.kind() == "encoded-blob"));
+        assert!(a.observe("plain words only").is_empty());
+        assert!(
+            a.observe("\u{0}\u{1}\u{2}abc")
+                .iter()
+                .any(|x| x.kind() == "binary")
+        );
+    }
+
+    #[test]
+    fn flags_length_outlier_and_new_charset_after_baseline() {
+        let mut a = ContentAnalyzer::default();
+        for i in 0..6 {
+            assert!(a.observe(&format!("result number {i}")).is_empty());
+        }
+        let big = "word ".repeat(200);
+        assert!(a.observe(&big).iter().any(|x| x.kind() == "length-outlier"));
+        assert!(a.observe("résumé").iter().any(|x| x.kind() == "charset"));
+        assert!(a.observe("naïve").is_empty(), "class already seen");
+    }
+}
@@ -0,0 +1,340 @@
+//! Findings exporters for external security platforms.
+//!
+//! Formats:
+//!   defectdojo  DefectDojo "Generic Findings Import" JSON (upload via UI, or
+//!               sent to the import-scan API by `import_scan`)
+//!   generic     mcp-hack's own flat JSON interchange format
+//!   sarif       SARIF 2.1.0 log (GitHub code scanning, see scan::sarif)
+//!   stix        STIX 2.1 bundle of indicators from poisoned tools (scan::ioc)
+//!   ioc-csv     the same indicators as CSV (type,value,context)
+//!
+//! Every finding carries a dedup key derived from target + check + tool +
+//! title, stable across runs, so re-imports update rather than duplicate.
+//! DefectDojo receives it as `unique_id_from_tool`.
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use super::ioc::{self, Indicator};
+use super::{Finding, ScanInput, ScanReport, Severity};
+use crate::utils::{sha256_hex, stable_hash, utc_timestamp};
+
+/// Supported export formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    DefectDojo,
+    Generic,
+    Sarif,
+    Stix,
+    IocCsv,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "defectdojo" | "dojo" => Ok(ExportFormat::DefectDojo),
+            "generic" | "json" => Ok(ExportFormat::Generic),
+            "sarif" => Ok(ExportFormat::Sarif),
+            "stix" => Ok(ExportFormat::Stix),
+            "ioc-csv" | "csv" => Ok(ExportFormat::IocCsv),
+            other => {
+                bail!(
+                    "unknown export format '{other}' (expected defectdojo|generic|sarif|stix|ioc-csv)"
+                )
+            }
+        }
+    }
+}
+
+/// Parse a `FORMAT=PATH` export spec.
+pub fn parse_export_spec(spec: &str) -> Result<(ExportFormat, String)> {
+    let Some((fmt, path)) = spec.split_once('=') else {
+        bail!("invalid --export '{spec}' (expected FORMAT=PATH)");
+    };
+    if path.trim().is_empty() {
+        bail!("invalid --export '{spec}': empty path");
+    }
+    Ok((ExportFormat::parse(fmt)?, path.trim().to_string()))
+}
+
+/// Stable deduplication key for a finding on a given target.
+pub fn dedup_key(target: &str, f: &Finding) -> String {
+    stable_hash(&format!(
+        "{}\u{1f}{}\u{1f}{}\u{1f}{}",
+        target,
+        f.check,
+        f.tool.as_deref().unwrap_or(""),
+        f.title
+    ))
+}
+
+/// Render the report in the requested format (file contents).
+pub fn render(format: ExportFormat, input: &ScanInput, report: &ScanReport) -> String {
+    let doc = match format {
+        ExportFormat::DefectDojo => defectdojo(input, report),
+        ExportFormat::Generic => generic(input, report),
+        ExportFormat::Sarif => super::sarif::render(&input.target, &report.findings),
+        ExportFormat::Stix => stix(input, &ioc::extract(input, report)),
+        ExportFormat::IocCsv => return ioc_csv(&ioc::extract(input, report)),
+    };
+    serde_json::to_string_pretty(&doc).unwrap_or_else(|_| doc.to_string())
+}
+
+fn dojo_severity(s: Severity) -> &'static str {
+    match s {
+        Severity::Info => "Info",
+        Severity::Low => "Low",
+        Severity::Medium => "Medium",
+        Severity::High => "High",
+        Severity::Critical => "Critical",
+    }
+}
+
+fn defectdojo(input: &ScanInput, report: &ScanReport) -> Value {
+    let findings: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|f| {
+            let mut description = f.detail.clone();
+            if let Some(ev) = &f.evidence {
+                description.push_str(&format!("\n\nEvidence:\n```\n{ev}\n```"));
+            }
+            description.push_str(&format!("\n\nTarget: `{}`", input.target));
+            json!({
+                "title": match &f.tool {
+                    Some(t) => format!("{} ({t})", f.title),
+                    None => f.title.clone(),
+                },
+                "severity": dojo_severity(f.severity),
+                "description": description,
+                "component_name": input.server_name(),
+                "component_version": input.server_version(),
+                "vuln_id_from_tool": f.check,
+                "unique_id_from_tool": dedup_key(&input.target, f),
+                "static_finding": false,
+                "dynamic_finding": true,
+            })
+        })
+        .collect();
+    json!({ "findings": findings })
+}
+
+/* ---- DefectDojo Upload ---- */
+
+/// A DefectDojo instance and the engagement findings are imported into.
+#[derive(Debug, Clone)]
+pub struct Dojo {
+    /// Base URL, e.g. https://dojo.example.org
+    pub url: String,
+    /// API v2 key
+    pub token: String,
+    pub engagement: u64,
+}
+
+/// multipart/form-data body of an import-scan request: (content type, body).
+fn import_form(engagement: u64, test_title: &str, report: &str) -> (String, Vec<u8>) {
+    let boundary = format!("mcp-hack-{}", &sha256_hex(report.as_bytes())[..24]);
+    let mut body = String::new();
+    let engagement = engagement.to_string();
+    for (name, value) in [
+        ("scan_type", "Generic Findings Import"),
+        ("engagement", engagement.as_str()),
+        ("test_title", test_title),
+        ("minimum_severity", "Info"),
+        ("active", "true"),
+        ("verified", "false"),
+    ] {
+        body.push_str(&format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+        ));
+    }
+    body.push_str(&format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"mcp-hack.json\"\r\n\
+         Content-Type: application/json\r\n\r\n{report}\r\n--{boundary}--\r\n"
+    ));
+    (
+        format!("multipart/form-data; boundary={boundary}"),
+        body.into_bytes(),
+    )
+}
+
+/// Send the `defectdojo` export to DefectDojo's import-scan API (v2) as a
+/// new test of `dojo.engagement`; returns DefectDojo's answer. Blocking.
+pub fn import_scan(dojo: &Dojo, input: &ScanInput, report: &ScanReport) -> Result<Value> {
+    let url = format!("{}/api/v2/import-scan/", dojo.url.trim_end_matches('/'));
+    let doc = render(ExportFormat::DefectDojo, input, report);
+    let title = format!("mcp-hack {}", crate::mcp::redact_target(&input.target));
+    let (content_type, body) = import_form(dojo.engagement, &title, &doc);
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(120))
+        .build();
+    let response = agent
+        .post(&url)
+        .set("Authorization", &format!("Token {}", dojo.token))
+        .set("Content-Type", &content_type)
+        .send_bytes(&body);
+    match response {
+        Ok(r) => {
+            let text = r
+                .into_string()
+                .with_context(|| format!("failed to read the answer of {url}"))?;
+            serde_json::from_str(&text).with_context(|| format!("unexpected answer from {url}"))
+        }
+        Err(ureq::Error::Status(code, r)) => {
+            let text = r.into_string().unwrap_or_default();
+            bail!("{url} answered {code}: {}", text.trim())
+        }
+        Err(e) => Err(anyhow::Error::new(e).context(format!("failed to reach {url}"))),
+    }
+}
+
+fn generic(input: &ScanInput, report: &ScanReport) -> Value {
+    let findings: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|f| {
+            let mut v = f.to_json();
+            v["dedup_key"] = json!(dedup_key(&input.target, f));
+            v["target"] = json!(input.target);
+            v
+        })
+        .collect();
+    json!({
+        "format": "mcp-hack-findings",
+        "format_version": 1,
+        "scanner": {"name": "mcp-hack", "version": env!("CARGO_PKG_VERSION")},
+        "target": input.target,
+        "server": {"name": input.server_name(), "version": input.server_version()},
+        "summary": report.summary_json(),
+        "findings": findings,
+    })
+}
+
+/// Deterministic UUID (version 5 layout) derived from SHA-256 of `seed`.
+fn stix_uuid(seed: &str) -> String {
+    let h = sha256_hex(seed.as_bytes());
+    format!(
+        "{}-{}-5{}-{:x}{}-{}",
+        &h[0..8],
+        &h[8..12],
+        &h[13..16],
+        (u8::from_str_radix(&h[16..17], 16).unwrap_or(0) & 0x3) | 0x8,
+        &h[17..20],
+        &h[20..32]
+    )
+}
+
+fn stix(input: &ScanInput, indicators: &[Indicator]) -> Value {
+    let now = utc_timestamp();
+    let identity_id = format!("identity--{}", stix_uuid("mcp-hack"));
+    let mut objects = vec![json!({
+        "type": "identity",
+        "spec_version": "2.1",
+        "id": identity_id,
+        "created": "2025-01-01T00:00:00.000Z",
+        "modified": "2025-01-01T00:00:00.000Z",
+        "name": "mcp-hack",
+        "identity_class": "system",
+    })];
+    for i in indicators {
+        objects.push(json!({
+            "type": "indicator",
+            "spec_version": "2.1",
+            "id": format!("indicator--{}", stix_uuid(&format!("{}|{}|{}", i.kind.as_str(), i.value, i.context))),
+            "created_by_ref": identity_id,
+            "created": now,
+            "modified": now,
+            "valid_from": now,
+            "name": format!("MCP server {} indicator: {}", i.kind.as_str(), i.value),
+            "description": format!("Observed in {} of MCP server '{}' ({})", i.context, input.server_name(), input.target),
+            "indicator_types": ["malicious-activity"],
+            "pattern": i.kind.stix_pattern(&i.value),
+            "pattern_type": "stix",
+            "labels": ["mcp", "tool-poisoning"],
+        }));
+    }
+    json!({
+        "type": "bundle",
+        "id": format!("bundle--{}", stix_uuid(&format!("{}|{}", input.target, now))),
+        "objects": objects,
+    })
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn ioc_csv(indicators: &[Indicator]) -> String {
+    let mut out = String::from("type,value,context\n");
+    for i in indicators {
+        out.push_str(&format!(
+            "{},{},{}\n",
+            i.kind.as_str(),
+            csv_field(&i.value),
+            csv_field(&i.context)
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_spec_parsing() {
+        let (f, p) = parse_export_spec("dojo=out.json").unwrap();
+        assert_eq!(f, ExportFormat::DefectDojo);
+        assert_eq!(p, "out.json");
+        assert!(parse_export_spec("defectdojo").is_err());
+        assert!(parse_export_spec("xml=a").is_err());
+    }
+
+    #[test]
+    fn dedup_key_is_stable_and_distinct() {
+        let a = Finding::new("protocol", Severity::Low, "x").tool("t");
+        let b = Finding::new("protocol", Severity::High, "x").tool("t");
+        let c = Finding::new("protocol", Severity::Low, "y").tool("t");
+        // Severity changes keep the identity; title changes do not.
+        assert_eq!(dedup_key("srv", &a), dedup_key("srv", &b));
+        assert_ne!(dedup_key("srv", &a), dedup_key("srv", &c));
+        assert_eq!(stable_hash(""), "cbf29ce484222325");
+    }
+
+    #[test]
+    fn builds_import_scan_form() {
+        let report = r#"{"findings":[{"unique_id_from_tool":"k1"}]}"#;
+        let (content_type, body) = import_form(7, "mcp-hack srv", report);
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let body = String::from_utf8(body).unwrap();
+        assert!(!report.contains(boundary));
+        assert!(body.contains("name=\"scan_type\"\r\n\r\nGeneric Findings Import\r\n"));
+        assert!(body.contains("name=\"engagement\"\r\n\r\n7\r\n"));
+        assert!(body.contains(&format!(
+            "filename=\"mcp-hack.json\"\r\nContent-Type: application/json\r\n\r\n{report}\r\n"
+        )));
+        assert!(body.ends_with(&format!("--{boundary}--\r\n")));
+    }
+
+    #[test]
+    fn stix_uuid_shape() {
+        let u = stix_uuid("x");
+        assert_eq!(u.len(), 36);
+        assert_eq!(&u[14..15], "5");
+        assert!(matches!(&u[19..20], "8" | "9" | "a" | "b"));
+        assert_eq!(u, stix_uuid("x"));
+    }
+
+    #[test]
+    fn csv_quoting() {
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("plain"), "plain");
+    }
+}
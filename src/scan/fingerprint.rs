@@ -0,0 +1,558 @@
+//! Server implementation fingerprinting (`fingerprint`).
+//!
+//! A short session gathers observations: serverInfo, how capabilities are
+//! serialized, the exact error objects returned for an unknown method, an
+//! unknown tool and malformed tools/call params, handshake latency, and (for
+//! local targets on Linux) the executables of the server process tree. Each
+//! signature adds weighted evidence to a candidate stack; candidates are
+//! ranked by score. Signatures are heuristics: servers can override every
+//! one of these traits, so results are leads, not proof.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::time::{Duration, Instant};
+
+use super::growth::process_tree;
+use crate::mcp::TargetSpec;
+use crate::mcp::raw::RawSession;
+
+/// Minimum score for a candidate to be reported.
+const MIN_SCORE: u32 = 20;
+/// Bytes of a native executable searched for toolchain markers.
+const MAX_EXE_SCAN: u64 = 64 * 1024 * 1024;
+
+/// What a probe session observed.
+#[derive(Debug, Clone, Default)]
+pub struct Observations {
+    /// initialize result
+    pub initialize: Value,
+    pub initialize_ms: u128,
+    /// Response to an unknown method
+    pub unknown_method: Value,
+    /// Response to tools/call with a tool name that does not exist
+    pub unknown_tool: Value,
+    /// Response to tools/call without a tool name
+    pub bad_params: Value,
+    /// Basename of the launched program ("npx", "python3", ...)
+    pub launcher: String,
+    /// Executable basenames of the server process tree
+    pub executables: Vec<String>,
+    /// Toolchain detected in a native executable ("go" / "rust")
+    pub native: Option<&'static str>,
+}
+
+impl Observations {
+    fn server_name(&self) -> &str {
+        self.initialize
+            .pointer("/serverInfo/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    }
+
+    fn has_runtime(&self, names: &[&str]) -> bool {
+        self.executables
+            .iter()
+            .chain(std::iter::once(&self.launcher))
+            .any(|e| names.iter().any(|n| is_runtime(e, n)))
+    }
+
+    /// Error or result text of a probe response, lowercased.
+    fn text(resp: &Value) -> String {
+        let msg = resp
+            .pointer("/error/message")
+            .and_then(|v| v.as_str())
+            .or_else(|| {
+                resp.pointer("/result/content/0/text")
+                    .and_then(|v| v.as_str())
+            })
+            .unwrap_or("");
+        let data = resp.pointer("/error/data").map(|d| d.to_string());
+        format!("{msg} {}", data.unwrap_or_default()).to_lowercase()
+    }
+
+    fn code(resp: &Value) -> Option<i64> {
+        resp.pointer("/error/code").and_then(|v| v.as_i64())
+    }
+
+    /// Capability flags serialized as explicit `false` (pydantic dumps defaults).
+    fn explicit_false_flags(&self) -> bool {
+        self.initialize
+            .get("capabilities")
+            .and_then(|c| c.as_object())
+            .is_some_and(|caps| {
+                caps.values().any(|v| {
+                    v.as_object()
+                        .is_some_and(|o| o.values().any(|f| f == &Value::Bool(false)))
+                })
+            })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "initialize_ms": self.initialize_ms,
+            "capabilities": self.initialize.get("capabilities"),
+            "unknown_method": self.unknown_method.get("error").or(self.unknown_method.get("result")),
+            "unknown_tool": self.unknown_tool.get("error").or(self.unknown_tool.get("result")),
+            "bad_params": self.bad_params.get("error").or(self.bad_params.get("result")),
+            "launcher": self.launcher,
+            "executables": self.executables,
+            "native": self.native,
+        })
+    }
+}
+
+/// `name` is runtime `n`, optionally versioned ("python3.12", "node22").
+fn is_runtime(name: &str, n: &str) -> bool {
+    name.strip_prefix(n)
+        .is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit() || c == '.'))
+}
+
+/// A ranked guess at the server's stack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub stack: &'static str,
+    /// 0-100
+    pub score: u32,
+    pub evidence: Vec<String>,
+}
+
+/// Human-readable name of a stack id.
+pub fn stack_label(stack: &str) -> &'static str {
+    match stack {
+        "ts-sdk" => "official TypeScript SDK (@modelcontextprotocol/sdk)",
+        "python-sdk" => "official Python SDK (mcp)",
+        "fastmcp" => "FastMCP (Python)",
+        "rmcp" => "rmcp (Rust SDK)",
+        "go" => "Go (mcp-go / go-sdk)",
+        "node" => "Node.js (unidentified library)",
+        "python" => "Python (unidentified library)",
+        "jvm" => "JVM (Java / Kotlin SDK)",
+        "dotnet" => ".NET (C# SDK)",
+        _ => "unknown",
+    }
+}
+
+/// Products recognized by serverInfo.name: (name, product, stack).
+const PRODUCTS: &[(&str, &str, &str)] = &[
+    (
+        "secure-filesystem-server",
+        "MCP reference filesystem server",
+        "ts-sdk",
+    ),
+    (
+        "example-servers/everything",
+        "MCP reference everything server",
+        "ts-sdk",
+    ),
+    ("mcp-fetch", "MCP reference fetch server", "python-sdk"),
+    ("mcp-git", "MCP reference git server", "python-sdk"),
+    ("mcp-time", "MCP reference time server", "python-sdk"),
+    ("github-mcp-server", "GitHub MCP server", "go"),
+    ("Playwright", "Microsoft Playwright MCP", "ts-sdk"),
+];
+
+/// Known issues for a stack or product (fix versions to check against).
+pub struct KnownIssue {
+    /// Stack id or product name the issue applies to
+    pub applies_to: &'static str,
+    pub id: &'static str,
+    pub title: &'static str,
+    pub fixed_in: &'static str,
+}
+
+impl KnownIssue {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "applies_to": self.applies_to,
+            "id": self.id,
+            "title": self.title,
+            "fixed_in": self.fixed_in,
+        })
+    }
+}
+
+pub const KNOWN_ISSUES: &[KnownIssue] = &[
+    KnownIssue {
+        applies_to: "python-sdk",
+        id: "CVE-2025-53366",
+        title: "Unhandled validation errors in request handling can crash the server",
+        fixed_in: "mcp 1.9.4",
+    },
+    KnownIssue {
+        applies_to: "python-sdk",
+        id: "CVE-2025-53365",
+        title: "Streamable HTTP: exception on a closed connection crashes the server",
+        fixed_in: "mcp 1.10.0",
+    },
+    KnownIssue {
+        applies_to: "ts-sdk",
+        id: "CVE-2025-66414",
+        title: "DNS rebinding protection not enabled by default for localhost HTTP servers",
+        fixed_in: "@modelcontextprotocol/sdk 1.24.0",
+    },
+    KnownIssue {
+        applies_to: "MCP reference filesystem server",
+        id: "CVE-2025-53110",
+        title: "Directory prefix check allows access outside the allowed directories",
+        fixed_in: "@modelcontextprotocol/server-filesystem 2025.7.1",
+    },
+    KnownIssue {
+        applies_to: "MCP reference filesystem server",
+        id: "CVE-2025-53109",
+        title: "Symlink handling allows escaping the allowed directories",
+        fixed_in: "@modelcontextprotocol/server-filesystem 2025.7.1",
+    },
+];
+
+/// Fingerprint result.
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    pub candidates: Vec<Candidate>,
+    pub product: Option<&'static str>,
+}
+
+impl Fingerprint {
+    /// Known issues for the top candidate and the recognized product.
+    /// FastMCP servers run on the Python SDK and inherit its issues.
+    pub fn known_issues(&self) -> Vec<&'static KnownIssue> {
+        let top = match self.candidates.first().map(|c| c.stack) {
+            Some("fastmcp") => Some("python-sdk"),
+            other => other,
+        };
+        KNOWN_ISSUES
+            .iter()
+            .filter(|i| Some(i.applies_to) == top || Some(i.applies_to) == self.product)
+            .collect()
+    }
+}
+
+/// Score every stack against the observations.
+pub fn analyze(obs: &Observations) -> Fingerprint {
+    let mut scores: Vec<Candidate> = Vec::new();
+    let mut add = |stack: &'static str, weight: u32, why: String| match scores
+        .iter_mut()
+        .find(|c| c.stack == stack)
+    {
+        Some(c) => {
+            c.score += weight;
+            c.evidence.push(why);
+        }
+        None => scores.push(Candidate {
+            stack,
+            score: weight,
+            evidence: vec![why],
+        }),
+    };
+
+    // Runtime of the process tree (strongest local signal).
+    if obs.has_runtime(&["node", "npx", "bun", "bunx", "deno"]) {
+        for s in ["ts-sdk", "node"] {
+            add(s, 30, "server runs on a JavaScript runtime".into());
+        }
+    }
+    if obs.has_runtime(&["python", "uv", "uvx", "pipx"]) {
+        for s in ["python-sdk", "fastmcp", "python"] {
+            add(s, 30, "server runs on a Python interpreter".into());
+        }
+    }
+    if obs.has_runtime(&["java"]) {
+        add("jvm", 50, "server runs on a JVM".into());
+    }
+    if obs.has_runtime(&["dotnet"]) {
+        add("dotnet", 50, "server runs on .NET".into());
+    }
+    match obs.native {
+        Some("go") => add("go", 50, "native executable carries Go build info".into()),
+        Some("rust") => add("rmcp", 40, "native executable built with rustc".into()),
+        _ => {}
+    }
+
+    // serverInfo defaults.
+    let name = obs.server_name();
+    if name == "rmcp" {
+        add("rmcp", 50, "serverInfo.name is the rmcp default".into());
+    }
+    if name.starts_with("FastMCP") {
+        add(
+            "fastmcp",
+            45,
+            format!("serverInfo.name '{name}' is the FastMCP default"),
+        );
+    }
+    let product = PRODUCTS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map(|(_, product, stack)| {
+            add(
+                stack,
+                40,
+                format!("serverInfo.name '{name}' identifies {product}"),
+            );
+            *product
+        });
+
+    // Capability serialization quirks.
+    if obs.explicit_false_flags() {
+        for s in ["python-sdk", "fastmcp"] {
+            add(
+                s,
+                20,
+                "capabilities serialize unset flags as explicit false".into(),
+            );
+        }
+    }
+    if obs
+        .initialize
+        .pointer("/capabilities/experimental")
+        .is_some_and(|v| v.as_object().is_some_and(|o| o.is_empty()))
+    {
+        add(
+            "python-sdk",
+            10,
+            "empty experimental capability object".into(),
+        );
+    }
+
+    // Error formats.
+    let um = Observations::text(&obs.unknown_method);
+    match Observations::code(&obs.unknown_method) {
+        Some(-32602) if um.contains("invalid request parameters") => {
+            for s in ["python-sdk", "fastmcp"] {
+                add(
+                    s,
+                    35,
+                    "unknown method answered with -32602 'Invalid request parameters'".into(),
+                );
+            }
+        }
+        Some(-32601) if um.trim() == "method not found" => {
+            for s in ["ts-sdk", "go"] {
+                add(
+                    s,
+                    10,
+                    "unknown method answered with -32601 'Method not found'".into(),
+                );
+            }
+        }
+        _ => {}
+    }
+    let ut = Observations::text(&obs.unknown_tool);
+    if ut.contains("unknown tool:") {
+        for s in ["python-sdk", "fastmcp"] {
+            add(
+                s,
+                15,
+                "unknown tool reported as 'Unknown tool: <name>'".into(),
+            );
+        }
+    } else if ut.contains("mcp error -32602") {
+        add(
+            "ts-sdk",
+            25,
+            "errors carry the 'MCP error <code>:' prefix of McpError".into(),
+        );
+    } else if ut.contains("tool '") && ut.contains("not found") {
+        add(
+            "go",
+            15,
+            "unknown tool reported as tool '<name>' not found".into(),
+        );
+    }
+    let bp = Observations::text(&obs.bad_params);
+    if bp.contains("invalid_type") || bp.contains("\"code\":\"invalid") {
+        add(
+            "ts-sdk",
+            25,
+            "validation errors use zod issue format".into(),
+        );
+    }
+    if (bp.contains("validation error") && bp.contains("pydantic")) || bp.contains("field required")
+    {
+        for s in ["python-sdk", "fastmcp"] {
+            add(s, 25, "validation errors use pydantic format".into());
+        }
+    }
+    if bp.contains("missing field") {
+        add(
+            "rmcp",
+            25,
+            "validation errors use serde format ('missing field')".into(),
+        );
+    }
+
+    // A bare runtime match alone is not enough for a specific library, and
+    // generic runtime buckets only count when nothing specific matched.
+    let specific = |s: &str| !matches!(s, "node" | "python");
+    for c in &mut scores {
+        if specific(c.stack) && c.evidence.len() == 1 && c.evidence[0].starts_with("server runs") {
+            c.score = 0;
+        }
+    }
+    let best_specific = scores
+        .iter()
+        .filter(|c| specific(c.stack))
+        .map(|c| c.score)
+        .max()
+        .unwrap_or(0);
+    scores.retain(|c| c.score >= MIN_SCORE && (specific(c.stack) || best_specific < MIN_SCORE));
+    // On a tie, FastMCP traits without its serverInfo default are the SDK's.
+    scores.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| (a.stack == "fastmcp").cmp(&(b.stack == "fastmcp")))
+            .then_with(|| a.stack.cmp(b.stack))
+    });
+    for c in &mut scores {
+        c.score = c.score.min(100);
+    }
+    Fingerprint {
+        candidates: scores,
+        product,
+    }
+}
+
+fn basename(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+/// Toolchain markers in a native executable.
+fn native_toolchain(exe: &std::path::Path) -> Option<&'static str> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    std::fs::File::open(exe)
+        .ok()?
+        .take(MAX_EXE_SCAN)
+        .read_to_end(&mut buf)
+        .ok()?;
+    let has = |needle: &[u8]| buf.windows(needle.len()).any(|w| w == needle);
+    // Rust first: a Rust binary that mentions the Go marker (like this one)
+    // still embeds rustc paths, a Go binary never does.
+    if has(b"/rustc/") {
+        Some("rust")
+    } else if has(b"\xff Go buildinf:") {
+        Some("go")
+    } else {
+        None
+    }
+}
+
+/// Executable basenames of a process tree and the native toolchain of its
+/// first non-interpreter executable.
+fn inspect_processes(pid: u32) -> (Vec<String>, Option<&'static str>) {
+    const INTERPRETERS: &[&str] = &[
+        "node", "bun", "deno", "python", "uv", "uvx", "java", "dotnet", "sh", "bash", "npm", "npx",
+    ];
+    let mut names = Vec::new();
+    let mut native = None;
+    for p in process_tree(pid) {
+        let Ok(exe) = std::fs::read_link(format!("/proc/{p}/exe")) else {
+            continue;
+        };
+        let name = basename(&exe.to_string_lossy());
+        let interpreted = INTERPRETERS.iter().any(|i| is_runtime(&name, i));
+        if !interpreted && native.is_none() {
+            native = native_toolchain(&exe);
+        }
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    (names, native)
+}
+
+/// Run the probe session.
+pub async fn observe(spec: &TargetSpec, timeout: Duration) -> Result<Observations> {
+    let mut obs = Observations::default();
+    if let TargetSpec::LocalCommand { program, .. } = spec {
+        obs.launcher = basename(program);
+    }
+    // Raw frames: the stack shows in replies to malformed requests.
+    let mut session = RawSession::spawn(spec, timeout)?;
+    let started = Instant::now();
+    let init = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    obs.initialize_ms = started.elapsed().as_millis();
+    obs.initialize = init.get("result").cloned().unwrap_or(init);
+
+    // Launchers (npx, uvx) may still be resolving the real server; by now
+    // the handshake has completed, so the tree is complete.
+    if let Some(pid) = session.pid() {
+        (obs.executables, obs.native) = inspect_processes(pid);
+    }
+
+    // Probe failures (timeouts) leave the observation empty.
+    obs.unknown_method = session
+        .request("mcphack/fingerprint", None)
+        .await
+        .unwrap_or(Value::Null);
+    obs.unknown_tool = session
+        .request(
+            "tools/call",
+            Some(json!({"name": "mcphack_no_such_tool", "arguments": {}})),
+        )
+        .await
+        .unwrap_or(Value::Null);
+    obs.bad_params = session
+        .request("tools/call", Some(json!({"arguments": 1})))
+        .await
+        .unwrap_or(Value::Null);
+    session.shutdown().await;
+    Ok(obs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn python_sdk_traits() {
+        let obs = Observations {
+            initialize: json!({
+                "serverInfo": {"name": "FastMCP", "version": "1.9.0"},
+                "capabilities": {"experimental": {}, "tools": {"listChanged": false}},
+            }),
+            unknown_method: json!({"error": {"code": -32602, "message": "Invalid request parameters", "data": ""}}),
+            unknown_tool: json!({"result": {"content": [{"type": "text", "text": "Unknown tool: x"}], "isError": true}}),
+            launcher: "uvx".into(),
+            executables: vec!["python3.12".into()],
+            ..Default::default()
+        };
+        let fp = analyze(&obs);
+        assert_eq!(fp.candidates[0].stack, "fastmcp");
+        assert!(fp.candidates.iter().any(|c| c.stack == "python-sdk"));
+        assert!(!fp.candidates.iter().any(|c| c.stack == "python"));
+        assert!(fp.known_issues().iter().any(|i| i.id == "CVE-2025-53366"));
+    }
+
+    #[test]
+    fn product_and_runtime_only() {
+        let obs = Observations {
+            initialize: json!({"serverInfo": {"name": "secure-filesystem-server"}}),
+            launcher: "npx".into(),
+            executables: vec!["node".into()],
+            ..Default::default()
+        };
+        let fp = analyze(&obs);
+        assert_eq!(fp.candidates[0].stack, "ts-sdk");
+        assert_eq!(fp.product, Some("MCP reference filesystem server"));
+        assert!(fp.known_issues().iter().any(|i| i.id == "CVE-2025-53110"));
+
+        // A runtime alone only supports the generic bucket.
+        let obs = Observations {
+            executables: vec!["python3".into()],
+            ..Default::default()
+        };
+        let fp = analyze(&obs);
+        assert_eq!(fp.candidates.len(), 1);
+        assert_eq!(fp.candidates[0].stack, "python");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn detects_rust_executable() {
+        let exe = std::fs::read_link("/proc/self/exe").unwrap();
+        assert_eq!(native_toolchain(&exe), Some("rust"));
+    }
+}
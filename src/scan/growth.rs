@@ -0,0 +1,308 @@
+//! Resource growth tracking for long sessions.
+//!
+//! The server process tree (the spawned child plus its descendants, so
+//! `npx` / `uvx` launchers count the real server) is sampled periodically
+//! from /proc: resident memory and open file descriptors. At the end of a
+//! run both series are fitted with a least-squares line; a steady climb
+//! (good fit, growth above a floor) is reported as a leak finding with the
+//! curve as evidence.
+//!
+//! Sampling is Linux-only; elsewhere `sample` returns None and no findings
+//! are produced.
+
+use serde_json::{Value, json};
+use std::time::Instant;
+
+use super::{Finding, Severity};
+
+/// Samples required before a trend is trusted.
+const MIN_SAMPLES: usize = 6;
+/// Minimum coefficient of determination for a "steady" climb.
+const MIN_R2: f64 = 0.6;
+/// RSS growth floor (KiB) and relative floor over the first sample.
+const RSS_MIN_GROWTH_KB: f64 = 4096.0;
+const RSS_MIN_GROWTH_RATIO: f64 = 0.10;
+/// Descriptor growth floor.
+const FD_MIN_GROWTH: f64 = 8.0;
+
+/// One sample of the server process tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub elapsed_ms: u64,
+    pub rss_kb: u64,
+    pub fds: u64,
+    pub procs: usize,
+}
+
+impl Sample {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "elapsed_ms": self.elapsed_ms,
+            "rss_kb": self.rss_kb,
+            "fds": self.fds,
+            "procs": self.procs,
+        })
+    }
+}
+
+/// Parent pid from /proc/<pid>/stat (the field after the parenthesized comm).
+fn parent_of(stat: &str) -> Option<u32> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// `pid` and all of its descendants.
+pub(super) fn process_tree(pid: u32) -> Vec<u32> {
+    let mut parents: Vec<(u32, u32)> = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/proc") {
+        for e in entries.flatten() {
+            let Some(child) = e.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+            if let Some(ppid) = std::fs::read_to_string(e.path().join("stat"))
+                .ok()
+                .as_deref()
+                .and_then(parent_of)
+            {
+                parents.push((child, ppid));
+            }
+        }
+    }
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while i < tree.len() {
+        let p = tree[i];
+        tree.extend(parents.iter().filter(|(_, pp)| *pp == p).map(|(c, _)| *c));
+        i += 1;
+    }
+    tree
+}
+
+fn rss_kb(pid: u32) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))
+        .and_then(|v| v.split_whitespace().next())
+        .and_then(|v| v.parse().ok())
+}
+
+fn fd_count(pid: u32) -> u64 {
+    std::fs::read_dir(format!("/proc/{pid}/fd"))
+        .map(|d| d.count() as u64)
+        .unwrap_or(0)
+}
+
+/// Sample RSS / descriptors of a process tree; None when /proc is unavailable
+/// or the process is gone.
+pub fn sample(pid: u32, elapsed_ms: u64) -> Option<Sample> {
+    let root = rss_kb(pid)?;
+    let tree = process_tree(pid);
+    let rss = root + tree[1..].iter().filter_map(|p| rss_kb(*p)).sum::<u64>();
+    Some(Sample {
+        elapsed_ms,
+        rss_kb: rss,
+        fds: tree.iter().map(|p| fd_count(*p)).sum(),
+        procs: tree.len(),
+    })
+}
+
+/// Least-squares (slope per sample, r²).
+fn fit(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (i, y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        let dy = y - mean_y;
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 || syy == 0.0 {
+        return (0.0, 0.0);
+    }
+    (sxy / sxx, (sxy * sxy) / (sxx * syy))
+}
+
+/// Unicode block sparkline scaled to the series' own range.
+pub fn sparkline(values: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    values
+        .iter()
+        .map(|&v| {
+            if max == min {
+                BARS[0]
+            } else {
+                BARS[((v - min) * 7 / (max - min)) as usize]
+            }
+        })
+        .collect()
+}
+
+/// Samples of one session plus trend analysis.
+#[derive(Debug)]
+pub struct GrowthTracker {
+    started: Instant,
+    pub samples: Vec<Sample>,
+}
+
+impl Default for GrowthTracker {
+    fn default() -> Self {
+        GrowthTracker {
+            started: Instant::now(),
+            samples: Vec::new(),
+        }
+    }
+}
+
+impl GrowthTracker {
+    /// Take a sample of `pid`'s tree; returns false when sampling failed.
+    pub fn record(&mut self, pid: u32) -> bool {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        match sample(pid, elapsed) {
+            Some(s) => {
+                self.samples.push(s);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leak findings for RSS and descriptor series with a steady climb.
+    pub fn findings(&self) -> Vec<Finding> {
+        if self.samples.len() < MIN_SAMPLES {
+            return Vec::new();
+        }
+        let mut out = Vec::new();
+        let span_s = self.samples.last().map(|s| s.elapsed_ms).unwrap_or(0) as f64 / 1000.0;
+
+        let rss: Vec<u64> = self.samples.iter().map(|s| s.rss_kb).collect();
+        let (slope, r2) = fit(&rss.iter().map(|v| *v as f64).collect::<Vec<_>>());
+        let growth = slope * (rss.len() - 1) as f64;
+        if r2 >= MIN_R2
+            && growth >= RSS_MIN_GROWTH_KB
+            && growth >= rss[0] as f64 * RSS_MIN_GROWTH_RATIO
+        {
+            out.push(
+                Finding::new(
+                    "resource-growth",
+                    Severity::Medium,
+                    format!(
+                        "Server memory grows steadily ({} KiB -> {} KiB over {span_s:.0}s)",
+                        rss[0],
+                        rss[rss.len() - 1]
+                    ),
+                )
+                .detail(format!(
+                    "Resident memory of the server process tree climbed by ~{:.0} KiB \
+                     (linear fit r²={r2:.2}) under repeated requests. Unbounded caches or \
+                     per-request state make long sessions exhaust memory.",
+                    growth
+                ))
+                .evidence(format!("rss {}", sparkline(&rss))),
+            );
+        }
+
+        let fds: Vec<u64> = self.samples.iter().map(|s| s.fds).collect();
+        let (slope, r2) = fit(&fds.iter().map(|v| *v as f64).collect::<Vec<_>>());
+        let growth = slope * (fds.len() - 1) as f64;
+        if r2 >= MIN_R2 && growth >= FD_MIN_GROWTH {
+            out.push(
+                Finding::new(
+                    "resource-growth",
+                    Severity::Medium,
+                    format!(
+                        "Server leaks file descriptors ({} -> {} over {span_s:.0}s)",
+                        fds[0],
+                        fds[fds.len() - 1]
+                    ),
+                )
+                .detail(format!(
+                    "Open descriptors of the server process tree climbed by ~{growth:.0} \
+                     (linear fit r²={r2:.2}). Files, sockets or pipes are opened per request \
+                     and never closed; the server will hit its descriptor limit."
+                ))
+                .evidence(format!("fds {}", sparkline(&fds))),
+            );
+        }
+        out
+    }
+
+    /// `{samples, rss_curve, fd_curve}` for JSON output and reports.
+    pub fn to_json(&self) -> Value {
+        let rss: Vec<u64> = self.samples.iter().map(|s| s.rss_kb).collect();
+        let fds: Vec<u64> = self.samples.iter().map(|s| s.fds).collect();
+        json!({
+            "samples": self.samples.iter().map(Sample::to_json).collect::<Vec<_>>(),
+            "rss_curve": sparkline(&rss),
+            "fd_curve": sparkline(&fds),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(rss: &[u64], fds: &[u64]) -> GrowthTracker {
+        GrowthTracker {
+            started: Instant::now(),
+            samples: rss
+                .iter()
+                .zip(fds)
+                .enumerate()
+                .map(|(i, (r, f))| Sample {
+                    elapsed_ms: i as u64 * 1000,
+                    rss_kb: *r,
+                    fds: *f,
+                    procs: 1,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_parent_with_spaces_in_comm() {
+        assert_eq!(parent_of("42 (my (odd) srv) S 7 42 42 0"), Some(7));
+    }
+
+    #[test]
+    fn steady_climb_is_a_leak_noise_is_not() {
+        let t = tracker(
+            &[20_000, 26_000, 31_000, 37_000, 42_000, 48_000, 53_000],
+            &[10, 11, 10, 11, 10, 11, 10],
+        );
+        let f = t.findings();
+        assert_eq!(f.len(), 1);
+        assert!(f[0].title.contains("memory"));
+
+        let t = tracker(&[20_000; 8], &[10, 14, 18, 22, 26, 30, 34, 38]);
+        let f = t.findings();
+        assert_eq!(f.len(), 1);
+        assert!(f[0].title.contains("descriptors"));
+
+        let t = tracker(
+            &[20_000, 30_000, 21_000, 29_000, 20_500, 30_500, 21_000],
+            &[10; 7],
+        );
+        assert!(t.findings().is_empty());
+    }
+
+    #[test]
+    fn sparkline_scales() {
+        assert_eq!(sparkline(&[1, 1]), "▁▁");
+        assert_eq!(sparkline(&[0, 7, 14]), "▁▄█");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn samples_own_process() {
+        let s = sample(std::process::id(), 0).expect("proc sample");
+        assert!(s.rss_kb > 0 && s.fds > 0);
+    }
+}
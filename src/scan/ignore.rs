@@ -0,0 +1,194 @@
+//! Finding suppressions (`.mcp-hack-ignore`, or `scan --ignore-file PATH`).
+//!
+//! Accepted risks are listed once instead of reappearing in every run:
+//!
+//! ```yaml
+//! - check: prompt-injection        # check id (glob: `*`, `?`)
+//!   tool: read_*                   # optional tool glob
+//!   target: "python3 server.py*"   # optional target glob
+//!   expires: 2026-12-31            # optional; suppresses through that day
+//!   reason: description reviewed, wording is benign   # required
+//! ```
+//!
+//! A finding is suppressed by the first entry whose patterns all match
+//! (case-insensitive). Entries past their expiry date stop suppressing and
+//! are reported so they get reviewed. Unknown keys and check ids are
+//! rejected at load time, like policy files.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::{Finding, checks};
+use crate::utils::pattern::glob_match;
+
+/// File read when present and no --ignore-file is given.
+pub const DEFAULT_FILE: &str = ".mcp-hack-ignore";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Suppression {
+    pub check: String,
+    pub tool: Option<String>,
+    pub target: Option<String>,
+    /// Last day the entry applies (YYYY-MM-DD, UTC)
+    pub expires: Option<String>,
+    pub reason: String,
+}
+
+impl Suppression {
+    fn is_expired(&self, today: &str) -> bool {
+        self.expires.as_deref().is_some_and(|d| d < today)
+    }
+
+    fn matches(&self, target: &str, finding: &Finding) -> bool {
+        let glob = |p: &str, text: &str| glob_match(&p.to_lowercase(), &text.to_lowercase());
+        glob(&self.check, finding.check)
+            && self
+                .tool
+                .as_deref()
+                .is_none_or(|p| finding.tool.as_deref().is_some_and(|t| glob(p, t)))
+            && self.target.as_deref().is_none_or(|p| glob(p, target))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "check": self.check,
+            "tool": self.tool,
+            "target": self.target,
+            "expires": self.expires,
+            "reason": self.reason,
+        })
+    }
+}
+
+/// Parse suppression entries (a YAML list).
+pub fn parse(text: &str) -> Result<Vec<Suppression>> {
+    let entries: Vec<Suppression> = if text.trim().is_empty() {
+        Vec::new()
+    } else {
+        serde_yaml::from_str(text).context("invalid suppression file")?
+    };
+    for (i, e) in entries.iter().enumerate() {
+        let n = i + 1;
+        if e.reason.trim().is_empty() {
+            bail!("entry {n}: reason must not be empty");
+        }
+        let known = checks::REGISTRY
+            .iter()
+            .any(|c| glob_match(&e.check.to_lowercase(), c.id));
+        if !known {
+            bail!("entry {n}: '{}' matches no check id", e.check);
+        }
+        if let Some(d) = &e.expires
+            && !is_date(d)
+        {
+            bail!("entry {n}: expires '{d}' is not a YYYY-MM-DD date");
+        }
+    }
+    Ok(entries)
+}
+
+/// Load `path`, or [`DEFAULT_FILE`] when it exists (no entries otherwise).
+pub fn load(path: Option<&str>) -> Result<Vec<Suppression>> {
+    let path = match path {
+        Some(p) => p,
+        None if std::path::Path::new(DEFAULT_FILE).is_file() => DEFAULT_FILE,
+        None => return Ok(Vec::new()),
+    };
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read suppressions {path}"))?;
+    parse(&text).with_context(|| format!("suppressions {path}"))
+}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+    b.len() == 10
+        && b[4] == b'-'
+        && b[7] == b'-'
+        && b.iter()
+            .enumerate()
+            .all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+}
+
+/// Today's date (UTC) as YYYY-MM-DD.
+pub fn today() -> String {
+    crate::utils::utc_timestamp()[..10].to_string()
+}
+
+/// Findings split by the suppressions in effect on `today`.
+#[derive(Debug, Default)]
+pub struct Applied {
+    pub kept: Vec<Finding>,
+    /// Suppressed findings with the entry that matched
+    pub suppressed: Vec<(Finding, Suppression)>,
+    /// Entries past their expiry date (not applied)
+    pub expired: Vec<Suppression>,
+}
+
+pub fn apply(
+    entries: &[Suppression],
+    target: &str,
+    findings: Vec<Finding>,
+    today: &str,
+) -> Applied {
+    let (active, expired): (Vec<&Suppression>, Vec<&Suppression>) =
+        entries.iter().partition(|e| !e.is_expired(today));
+    let mut out = Applied {
+        expired: expired.into_iter().cloned().collect(),
+        ..Default::default()
+    };
+    for f in findings {
+        match active.iter().find(|e| e.matches(target, &f)) {
+            Some(e) => out.suppressed.push((f, (*e).clone())),
+            None => out.kept.push(f),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scan::Severity;
+
+    #[test]
+    fn suppresses_matching_findings_until_expiry() {
+        let entries = parse(
+            "- check: prompt-injection\n  tool: READ_*\n  reason: reviewed\n\
+             - check: dangerous-tool\n  target: 'python3 *'\n  expires: 2025-01-31\n  reason: lab only\n\
+             - check: tool-*\n  expires: 2025-03-01\n  reason: legacy names\n",
+        )
+        .unwrap();
+        let findings = vec![
+            Finding::new("prompt-injection", Severity::High, "a").tool("read_file"),
+            Finding::new("prompt-injection", Severity::High, "b").tool("write_file"),
+            Finding::new("dangerous-tool", Severity::High, "c").tool("exec"),
+            Finding::new("tool-naming", Severity::Low, "d"),
+        ];
+        let applied = apply(&entries, "python3 server.py", findings, "2025-02-01");
+        let titles = |v: &[Finding]| v.iter().map(|f| f.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&applied.kept), vec!["b", "c"]);
+        let suppressed: Vec<(&str, &str)> = applied
+            .suppressed
+            .iter()
+            .map(|(f, e)| (f.title.as_str(), e.reason.as_str()))
+            .collect();
+        assert_eq!(suppressed, vec![("a", "reviewed"), ("d", "legacy names")]);
+        assert_eq!(applied.expired.len(), 1);
+        assert_eq!(applied.expired[0].reason, "lab only");
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let err = |text: &str| format!("{:#}", parse(text).unwrap_err());
+        assert!(err("- check: dos\n  reason: x\n").contains("matches no check id"));
+        assert!(err("- check: protocol\n  reason: ' '\n").contains("reason must not be empty"));
+        assert!(
+            err("- check: protocol\n  reason: x\n  expires: 31/12/2025\n").contains("YYYY-MM-DD")
+        );
+        assert!(err("- check: protocol\n  reason: x\n  tools: a\n").contains("unknown field"));
+        assert!(err("- check: protocol\n").contains("missing field `reason`"));
+        assert!(parse("").unwrap().is_empty());
+    }
+}
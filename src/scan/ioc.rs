@@ -0,0 +1,316 @@
+//! Indicator (IOC) extraction for suspicious servers.
+//!
+//! When scan flags tool poisoning (prompt-injection / hidden-unicode findings),
+//! the affected tools are mined for shareable indicators:
+//!   - URLs and their domains, e-mail addresses (exfil endpoints)
+//!   - SHA-256 of the poisoned description text
+//!   - the package / image the server was launched from (npx, uvx, docker ...)
+//!
+//! Rendered by scan::export as a STIX 2.1 bundle or CSV.
+
+use std::collections::BTreeSet;
+
+use super::{ScanInput, ScanReport, Severity};
+use crate::mcp::TargetSpec;
+use crate::utils::sha256_hex;
+
+/// Indicator category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IocKind {
+    Url,
+    Domain,
+    Email,
+    Package,
+    DescriptionHash,
+}
+
+impl IocKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IocKind::Url => "url",
+            IocKind::Domain => "domain",
+            IocKind::Email => "email",
+            IocKind::Package => "package",
+            IocKind::DescriptionHash => "sha256",
+        }
+    }
+
+    /// STIX 2.1 pattern for a value of this kind.
+    pub fn stix_pattern(&self, value: &str) -> String {
+        let v = value.replace('\\', "\\\\").replace('\'', "\\'");
+        match self {
+            IocKind::Url => format!("[url:value = '{v}']"),
+            IocKind::Domain => format!("[domain-name:value = '{v}']"),
+            IocKind::Email => format!("[email-addr:value = '{v}']"),
+            IocKind::Package => format!("[software:name = '{v}']"),
+            IocKind::DescriptionHash => format!("[artifact:hashes.'SHA-256' = '{v}']"),
+        }
+    }
+}
+
+/// One extracted indicator.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Indicator {
+    pub kind: IocKind,
+    pub value: String,
+    /// Where it came from, e.g. "tool read_file description"
+    pub context: String,
+}
+
+/// Checks whose findings mark a tool as poisoned.
+const POISON_CHECKS: &[&str] = &["prompt-injection", "hidden-unicode"];
+
+/// Extract indicators from tools flagged at Medium or above by poisoning checks.
+/// Returns an empty list when the server does not look malicious.
+pub fn extract(input: &ScanInput, report: &ScanReport) -> Vec<Indicator> {
+    let flagged: BTreeSet<&str> = report
+        .findings
+        .iter()
+        .filter(|f| POISON_CHECKS.contains(&f.check) && f.severity >= Severity::Medium)
+        .filter_map(|f| f.tool.as_deref())
+        .collect();
+    if flagged.is_empty() {
+        return Vec::new();
+    }
+
+    let mut out = BTreeSet::new();
+    for t in &input.tools {
+        let name = t.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if !flagged.contains(name) {
+            continue;
+        }
+        let desc = t.get("description").and_then(|v| v.as_str()).unwrap_or("");
+        let mut texts = vec![desc.to_string()];
+        if let Some(props) = t
+            .get("inputSchema")
+            .and_then(|s| s.get("properties"))
+            .and_then(|v| v.as_object())
+        {
+            texts.extend(
+                props
+                    .values()
+                    .filter_map(|p| p.get("description").and_then(|v| v.as_str()))
+                    .map(str::to_string),
+            );
+        }
+        // Tag-character payloads are invisible; mine the decoded text too.
+        let hidden: Vec<String> = texts
+            .iter()
+            .flat_map(|t| super::unicode::analyze(t, false))
+            .filter_map(|i| i.hidden)
+            .collect();
+        texts.extend(hidden);
+
+        let context = format!("tool {name}");
+        for text in &texts {
+            for url in extract_urls(text) {
+                if let Some(host) = url_host(&url) {
+                    out.insert(Indicator {
+                        kind: IocKind::Domain,
+                        value: host,
+                        context: context.clone(),
+                    });
+                }
+                out.insert(Indicator {
+                    kind: IocKind::Url,
+                    value: url,
+                    context: context.clone(),
+                });
+            }
+            for email in extract_emails(text) {
+                out.insert(Indicator {
+                    kind: IocKind::Email,
+                    value: email,
+                    context: context.clone(),
+                });
+            }
+        }
+        if !desc.is_empty() {
+            out.insert(Indicator {
+                kind: IocKind::DescriptionHash,
+                value: sha256_hex(desc.as_bytes()),
+                context: format!("tool {name} description"),
+            });
+        }
+    }
+    if let Ok(spec) = crate::mcp::parse_target(&input.target)
+        && let Some(pkg) = launch_package(&spec)
+    {
+        out.insert(Indicator {
+            kind: IocKind::Package,
+            value: pkg,
+            context: "server launch command".into(),
+        });
+    }
+    out.into_iter().collect()
+}
+
+/// http(s) URLs embedded in free text.
+fn extract_urls(text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let lower = text.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = ["http://", "https://"]
+        .iter()
+        .filter_map(|p| lower[from..].find(p))
+        .min()
+    {
+        let start = from + pos;
+        let rest = &text[start..];
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '`'))
+            .unwrap_or(rest.len());
+        let url = rest[..end].trim_end_matches(['.', ',', ';', ':', ')', ']', '}', '!', '?']);
+        if url_host(url).is_some() {
+            out.push(url.to_string());
+        }
+        from = start + end.max(1);
+    }
+    out
+}
+
+/// Host part of an http(s) URL (lowercased, userinfo and port removed).
+fn url_host(url: &str) -> Option<String> {
+    let after = url.split_once("://")?.1;
+    let authority = after.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = if host.starts_with('[') {
+        host.split(']').next()?.trim_start_matches('[')
+    } else {
+        host.split(':').next()?
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_ascii_lowercase())
+    }
+}
+
+/// E-mail addresses (local@domain.tld) in free text.
+fn extract_emails(text: &str) -> Vec<String> {
+    text.split(|c: char| {
+        c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | ',' | ';' | '(' | ')')
+    })
+    .filter_map(|tok| {
+        let tok = tok.trim_matches(|c: char| !c.is_ascii_alphanumeric());
+        let (local, domain) = tok.split_once('@')?;
+        let valid_local = !local.is_empty()
+            && local
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "._%+-".contains(c));
+        let valid_domain = domain.contains('.')
+            && !domain.starts_with('.')
+            && domain
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+        (valid_local && valid_domain).then(|| tok.to_ascii_lowercase())
+    })
+    .collect()
+}
+
+/// Package or image a local target is launched from, e.g. "npm:@scope/server@1.0".
+pub fn launch_package(spec: &TargetSpec) -> Option<String> {
+    let TargetSpec::LocalCommand { program, args, .. } = spec else {
+        return None;
+    };
+    let base = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let first_positional = |skip: usize, flags_with_value: &[&str]| -> Option<String> {
+        let mut it = args.iter().skip(skip);
+        while let Some(a) = it.next() {
+            if a.starts_with('-') {
+                if flags_with_value.contains(&a.as_str()) {
+                    it.next();
+                }
+                continue;
+            }
+            return Some(a.clone());
+        }
+        None
+    };
+    match base {
+        "npx" | "bunx" => first_positional(0, &["-p", "--package"]).map(|p| format!("npm:{p}")),
+        "pnpm" | "yarn" if args.first().map(String::as_str) == Some("dlx") => {
+            first_positional(1, &[]).map(|p| format!("npm:{p}"))
+        }
+        "uvx" => first_positional(0, &["--from", "--with", "--python", "-p"])
+            .map(|p| format!("pypi:{p}")),
+        "pipx" if args.first().map(String::as_str) == Some("run") => {
+            first_positional(1, &["--spec"]).map(|p| format!("pypi:{p}"))
+        }
+        "docker" | "podman" if args.first().map(String::as_str) == Some("run") => first_positional(
+            1,
+            &[
+                "-e",
+                "--env",
+                "-v",
+                "--volume",
+                "--name",
+                "--network",
+                "-p",
+                "--publish",
+                "-w",
+                "--workdir",
+                "--env-file",
+                "--user",
+                "-u",
+                "--entrypoint",
+                "--mount",
+            ],
+        )
+        .map(|p| format!("oci:{p}")),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_and_hosts() {
+        let urls = extract_urls("send to https://evil.example.com/c?x=1. and http://[::1]:8/a");
+        assert_eq!(
+            urls,
+            vec!["https://evil.example.com/c?x=1", "http://[::1]:8/a"]
+        );
+        assert_eq!(
+            url_host("https://u:p@Host.Example:443/x").as_deref(),
+            Some("host.example")
+        );
+        assert_eq!(url_host("http://[::1]:8/a").as_deref(), Some("::1"));
+    }
+
+    #[test]
+    fn emails() {
+        assert_eq!(
+            extract_emails("bcc (attacker@evil.io) always; not a@b"),
+            vec!["attacker@evil.io"]
+        );
+    }
+
+    #[test]
+    fn launch_packages() {
+        let pkg = |t: &str| launch_package(&crate::mcp::parse_target(t).unwrap());
+        assert_eq!(
+            pkg("npx -y @scope/srv@1.2 --flag").as_deref(),
+            Some("npm:@scope/srv@1.2")
+        );
+        assert_eq!(
+            pkg("uvx mcp-server-fetch").as_deref(),
+            Some("pypi:mcp-server-fetch")
+        );
+        assert_eq!(
+            pkg("docker run -i --rm -e TOKEN ghcr.io/x/y:1").as_deref(),
+            Some("oci:ghcr.io/x/y:1")
+        );
+        assert_eq!(pkg("python3 server.py"), None);
+    }
+
+    #[test]
+    fn stix_pattern_escaping() {
+        assert_eq!(
+            IocKind::Url.stix_pattern("http://x/'a"),
+            "[url:value = 'http://x/\\'a']"
+        );
+    }
+}
@@ -0,0 +1,662 @@
+//! Security scan engine.
+//!
+//! collect -> ScanInput (initialize result, tool inventory, protocol probes)
+//! run_checks(ScanInput) -> ScanReport { checks (ran / skipped), findings }
+//!
+//! Checks are plain functions registered in `checks::REGISTRY`; each one
+//! inspects the collected input and returns findings, or a skip reason when
+//! it does not apply to the target (e.g. auth checks on a stdio process).
+//! risk classifies tools by dangerous capability (exec, fs-write, egress ...).
+//! unicode detects invisible characters / homoglyphs (also used by list / get).
+//! canary plants values in secret parameters and watches where they resurface.
+//! pollution plants markers through each tool and maps shared-state channels.
+//! shadow compares several servers' inventories for cross-server tool shadowing.
+//! diff compares two tool inventories (targets or snapshots) with unified diffs.
+//! pins stores tool definition hashes between runs (rug-pull detection).
+//! ignore suppresses accepted findings listed in `.mcp-hack-ignore`.
+//! baseline splits findings into known / new against an accepted baseline file.
+//! watch snapshots tool / resource / prompt definitions and diffs them (watch).
+//! policy loads allow / deny expectations (policy.yaml) checked by `policy`.
+//! entropy scores response text for blobs / binary / outliers (fuzz).
+//! growth samples server RSS / descriptors over long sessions (soak).
+//! fingerprint guesses the server's SDK / product from probe responses.
+//! templates are YAML detection rules over tools / resources / server info.
+//! rules is a YARA-style matcher for resource contents and tool results.
+//! export renders findings for external platforms (DefectDojo, generic JSON,
+//! SARIF via sarif, STIX / CSV indicators mined by ioc).
+//! report renders the scan JSON document as a Markdown / HTML report.
+//! replay compares replayed transcript responses with the recording (replay).
+//! playbook parses `run` chains: templating, saved values, conditions, assertions.
+//! stats aggregates transcripts and saved outputs of an engagement (stats).
+//! Collection talks raw JSON-RPC (mcp::raw) so tool objects are kept verbatim
+//! and deliberately malformed probes can be sent.
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+use crate::mcp::TargetSpec;
+use crate::mcp::raw::RawSession;
+
+pub mod baseline;
+pub mod canary;
+pub mod checks;
+pub mod diff;
+pub mod entropy;
+pub mod export;
+pub mod fingerprint;
+pub mod growth;
+pub mod ignore;
+pub mod ioc;
+pub mod pins;
+pub mod playbook;
+pub mod policy;
+pub mod pollution;
+pub mod replay;
+pub mod report;
+pub mod risk;
+pub mod rules;
+pub mod sarif;
+pub mod shadow;
+pub mod stats;
+pub mod templates;
+pub mod unicode;
+pub mod watch;
+
+/* ---- Findings ---- */
+
+/// Finding severity, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 5] = [
+        Severity::Critical,
+        Severity::High,
+        Severity::Medium,
+        Severity::Low,
+        Severity::Info,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Severity::ALL
+            .into_iter()
+            .find(|sev| sev.as_str().eq_ignore_ascii_case(s.trim()))
+    }
+}
+
+/// A single issue reported by a check.
+#[derive(Debug, Clone)]
+pub struct Finding {
+    /// Id of the check that produced it (e.g. "prompt-injection")
+    pub check: &'static str,
+    pub severity: Severity,
+    /// One-line summary
+    pub title: String,
+    /// Longer explanation / remediation hint
+    pub detail: String,
+    /// Affected tool, when the finding is tool-specific
+    pub tool: Option<String>,
+    /// Snippet that triggered the finding
+    pub evidence: Option<String>,
+}
+
+impl Finding {
+    pub fn new(check: &'static str, severity: Severity, title: impl Into<String>) -> Self {
+        Finding {
+            check,
+            severity,
+            title: title.into(),
+            detail: String::new(),
+            tool: None,
+            evidence: None,
+        }
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+
+    pub fn tool(mut self, tool: impl Into<String>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    pub fn evidence(mut self, evidence: impl Into<String>) -> Self {
+        self.evidence = Some(evidence.into());
+        self
+    }
+
+    /// Report order: severity (highest first), then check id, tool, title and
+    /// evidence. Total, so output does not depend on the order checks ran in.
+    pub fn report_order(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .severity
+            .cmp(&self.severity)
+            .then_with(|| self.check.cmp(other.check))
+            .then_with(|| self.tool.cmp(&other.tool))
+            .then_with(|| self.title.cmp(&other.title))
+            .then_with(|| self.evidence.cmp(&other.evidence))
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "check": self.check,
+            "severity": self.severity.as_str(),
+            "title": self.title,
+            "detail": self.detail,
+            "tool": self.tool,
+            "evidence": self.evidence,
+        })
+    }
+}
+
+/* ---- Collected Input ---- */
+
+/// Outcome of a single active protocol probe.
+#[derive(Debug, Clone)]
+pub enum ProbeResult {
+    /// A JSON-RPC response frame arrived
+    Response(Value),
+    /// Nothing arrived before the probe timeout
+    Timeout,
+    /// The server closed stdout / exited
+    Closed,
+    /// The probe could not be performed
+    Failed(String),
+}
+
+impl ProbeResult {
+    fn from_result(r: Result<Value>) -> Self {
+        match r {
+            Ok(v) => ProbeResult::Response(v),
+            Err(e) => {
+                let msg = format!("{e:#}");
+                if msg.contains("timed out") {
+                    ProbeResult::Timeout
+                } else if msg.contains("closed stdout") || msg.contains("failed to write") {
+                    ProbeResult::Closed
+                } else {
+                    ProbeResult::Failed(msg)
+                }
+            }
+        }
+    }
+
+    /// JSON-RPC error code, if this is an error response.
+    pub fn error_code(&self) -> Option<i64> {
+        match self {
+            ProbeResult::Response(v) => v.get("error")?.get("code")?.as_i64(),
+            _ => None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, ProbeResult::Response(v) if v.get("result").is_some())
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ProbeResult::Response(v) => v.to_string(),
+            ProbeResult::Timeout => "no response (timeout)".into(),
+            ProbeResult::Closed => "connection closed".into(),
+            ProbeResult::Failed(e) => format!("probe failed: {e}"),
+        }
+    }
+}
+
+/// Observations from active protocol probing (absent in passive scans).
+#[derive(Debug, Clone)]
+pub struct ProtocolProbes {
+    /// tools/list sent before the initialize handshake
+    pub pre_init_tools_list: ProbeResult,
+    /// A method no server should implement
+    pub unknown_method: ProbeResult,
+    /// A line that is not valid JSON
+    pub malformed_frame: ProbeResult,
+    /// initialize with a protocol version that does not exist
+    pub bogus_version: ProbeResult,
+}
+
+/// Everything the checks get to look at.
+#[derive(Debug, Clone)]
+pub struct ScanInput {
+    /// Original target string
+    pub target: String,
+    /// True for http(s)/ws(s) targets
+    pub remote: bool,
+    /// Whether credentials (Authorization header etc.) were supplied
+    pub auth_supplied: bool,
+    /// Raw `initialize` result (serverInfo, capabilities, protocolVersion)
+    pub initialize: Value,
+    /// Raw tool objects from tools/list (all pages)
+    pub tools: Vec<Value>,
+    /// Non-JSON lines the server wrote to stdout
+    pub stdout_noise: Vec<String>,
+    /// Active probe results; None for passive scans
+    pub probes: Option<ProtocolProbes>,
+    /// Previously pinned tool definitions (filled by the caller; None = first contact / disabled)
+    pub pinned: Option<pins::PinSet>,
+    /// resources/read content entries (only collected when content rules are loaded)
+    pub resources: Vec<Value>,
+    /// Content rules supplied by the caller (`scan --rules`)
+    pub rules: Vec<rules::Rule>,
+    /// Expected-state policy supplied by the caller (`scan --policy`)
+    pub policy: Option<policy::Policy>,
+    /// Detection templates supplied by the caller (`scan --templates`)
+    pub templates: Vec<templates::Template>,
+    /// Checks to run (`scan --checks` / `--skip-checks`)
+    pub selection: checks::Selection,
+}
+
+/// Server capabilities reported as declared / absent in scan output.
+pub const CAPABILITIES: &[&str] = &["tools", "resources", "prompts", "logging", "completions"];
+
+impl ScanInput {
+    /// Whether the initialize result declares a server capability.
+    pub fn has_capability(&self, name: &str) -> bool {
+        self.initialize
+            .get("capabilities")
+            .and_then(|c| c.get(name))
+            .is_some_and(|v| !v.is_null())
+    }
+
+    /// (declared, absent) among `CAPABILITIES`.
+    pub fn capability_coverage(&self) -> (Vec<&'static str>, Vec<&'static str>) {
+        CAPABILITIES.iter().partition(|c| self.has_capability(c))
+    }
+
+    pub fn server_name(&self) -> &str {
+        self.initialize
+            .pointer("/serverInfo/name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    }
+
+    pub fn server_version(&self) -> &str {
+        self.initialize
+            .pointer("/serverInfo/version")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    }
+
+    pub fn protocol_version(&self) -> &str {
+        self.initialize
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+    }
+}
+
+/// Upper bound on tools/list pages followed (guards against cursor loops).
+const MAX_PAGES: usize = 50;
+
+/// Connect to a local target and gather the scan input.
+///
+/// With `active`, a second short-lived session is used for protocol probes
+/// (pre-initialize request, malformed frame, ...) so a misbehaving server
+/// cannot spoil the inventory session.
+pub async fn collect(spec: &TargetSpec, timeout: Duration, active: bool) -> Result<ScanInput> {
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    // Raw frames: the initialize reply and tools as sent, stdout noise.
+    let mut session = RawSession::spawn(spec, timeout)?;
+    let initialize = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    let initialize = initialize.get("result").cloned().unwrap_or(initialize);
+
+    // Servers without the tools capability are scanned for what they do
+    // declare; tool checks then report not applicable.
+    let tools = if initialize.pointer("/capabilities/tools").is_some() {
+        crate::utils::progress_events::phase("enumerate", Some("tools"));
+        list_all(&mut session, "tools/list", "tools", usize::MAX).await?
+    } else {
+        Vec::new()
+    };
+    let stdout_noise = noise_lines(&session.interleaved);
+    session.shutdown().await;
+
+    let probes = if active {
+        crate::utils::progress_events::phase("probe", Some("protocol"));
+        Some(probe_protocol(spec, timeout.min(PROBE_TIMEOUT)).await?)
+    } else {
+        None
+    };
+
+    Ok(ScanInput {
+        target: spec.original().to_string(),
+        remote: spec.is_remote(),
+        auth_supplied: false,
+        initialize,
+        tools,
+        stdout_noise,
+        probes,
+        pinned: None,
+        resources: Vec::new(),
+        rules: Vec::new(),
+        policy: None,
+        templates: Vec::new(),
+        selection: checks::Selection::default(),
+    })
+}
+
+/// Follow `nextCursor` pagination of a list method (at most MAX_PAGES pages,
+/// stopping once `limit` items were collected) and return the `key` items.
+pub async fn list_all(
+    session: &mut RawSession,
+    method: &str,
+    key: &str,
+    limit: usize,
+) -> Result<Vec<Value>> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_PAGES {
+        let params = cursor.as_ref().map(|c| json!({ "cursor": c }));
+        let resp = session
+            .request(method, params)
+            .await
+            .with_context(|| format!("{method} failed"))?;
+        if let Some(err) = resp.get("error") {
+            anyhow::bail!("{method} returned error: {err}");
+        }
+        if let Some(arr) = resp
+            .get("result")
+            .and_then(|r| r.get(key))
+            .and_then(|v| v.as_array())
+        {
+            items.extend(arr.iter().cloned());
+        }
+        cursor = resp
+            .pointer("/result/nextCursor")
+            .and_then(|v| v.as_str())
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        if cursor.is_none() || items.len() >= limit {
+            break;
+        }
+    }
+    items.truncate(limit);
+    Ok(items)
+}
+
+/// Upper bound on resources read for content rules.
+const MAX_RESOURCES: usize = 100;
+
+/// List and read the target's resources (content entries of every
+/// resources/read result, capped at MAX_RESOURCES). Servers without the
+/// resources capability yield an empty list.
+pub async fn collect_resources(spec: &TargetSpec, timeout: Duration) -> Result<Vec<Value>> {
+    crate::utils::progress_events::phase("enumerate", Some("resources"));
+    let mut session = RawSession::spawn(spec, timeout)?;
+    let init = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    if init.pointer("/result/capabilities/resources").is_none() {
+        session.shutdown().await;
+        return Ok(Vec::new());
+    }
+    let contents = read_resources(&mut session).await;
+    session.shutdown().await;
+    contents
+}
+
+/// List and read resources on an initialized session (capped at MAX_RESOURCES).
+pub async fn read_resources(session: &mut RawSession) -> Result<Vec<Value>> {
+    let uris: Vec<String> = list_all(session, "resources/list", "resources", MAX_RESOURCES)
+        .await?
+        .iter()
+        .filter_map(|r| r.get("uri").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect();
+    let mut contents = Vec::new();
+    for uri in uris.iter().take(MAX_RESOURCES) {
+        // Unreadable resources are skipped; rules only need what can be read.
+        let Ok(resp) = session
+            .request("resources/read", Some(json!({ "uri": uri })))
+            .await
+        else {
+            continue;
+        };
+        if let Some(arr) = resp.pointer("/result/contents").and_then(|v| v.as_array()) {
+            contents.extend(arr.iter().map(|c| {
+                let mut c = c.clone();
+                if c.get("uri").is_none() {
+                    c["uri"] = json!(uri);
+                }
+                c
+            }));
+        }
+    }
+    Ok(contents)
+}
+
+/// Per-probe wait; probes are expected to be answered immediately.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn probe_protocol(spec: &TargetSpec, timeout: Duration) -> Result<ProtocolProbes> {
+    // Raw frames: the probes break the protocol on purpose.
+    let mut session = RawSession::spawn(spec, timeout)?;
+
+    let pre_init_tools_list = ProbeResult::from_result(session.request("tools/list", None).await);
+
+    // Handshake with a version from the future; a compliant server answers with
+    // one it supports instead of echoing ours.
+    let bogus_version = ProbeResult::from_result(
+        session
+            .request(
+                "initialize",
+                Some(json!({
+                    "protocolVersion": BOGUS_PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "mcp-hack", "version": env!("CARGO_PKG_VERSION")}
+                })),
+            )
+            .await,
+    );
+    let _ = session.notify("notifications/initialized", None).await;
+
+    let unknown_method = ProbeResult::from_result(
+        session
+            .request("mcp-hack/definitely-not-a-method", None)
+            .await,
+    );
+    let malformed_frame = ProbeResult::from_result(
+        session
+            .send_line_expect_any("{\"jsonrpc\": \"2.0\", ")
+            .await,
+    );
+    session.shutdown().await;
+
+    Ok(ProtocolProbes {
+        pre_init_tools_list,
+        unknown_method,
+        malformed_frame,
+        bogus_version,
+    })
+}
+
+pub const BOGUS_PROTOCOL_VERSION: &str = "2999-01-01";
+
+fn noise_lines(frames: &[Value]) -> Vec<String> {
+    frames
+        .iter()
+        .filter_map(|f| f.get("non_json_line").and_then(|v| v.as_str()))
+        .map(str::to_string)
+        .collect()
+}
+
+/* ---- Running Checks ---- */
+
+/// Per-check execution status.
+#[derive(Debug, Clone)]
+pub struct CheckRun {
+    pub id: &'static str,
+    pub title: &'static str,
+    /// None = ran; Some(reason) = skipped or not applicable
+    pub skipped: Option<String>,
+    /// False when the server lacks the capability the check inspects
+    pub applicable: bool,
+    pub findings: usize,
+}
+
+impl CheckRun {
+    /// "ran" | "skipped" | "not_applicable"
+    pub fn status(&self) -> &'static str {
+        match (&self.skipped, self.applicable) {
+            (_, false) => "not_applicable",
+            (Some(_), true) => "skipped",
+            (None, true) => "ran",
+        }
+    }
+}
+
+/// Aggregated scan result.
+#[derive(Debug, Clone)]
+pub struct ScanReport {
+    pub checks: Vec<CheckRun>,
+    /// Sorted by severity (desc), then check id, then tool
+    pub findings: Vec<Finding>,
+}
+
+impl ScanReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == severity)
+            .count()
+    }
+
+    pub fn summary_json(&self) -> Value {
+        let mut m = serde_json::Map::new();
+        for s in Severity::ALL {
+            m.insert(s.as_str().into(), json!(self.count(s)));
+        }
+        Value::Object(m)
+    }
+}
+
+/// Run every registered check against the input.
+pub fn run_checks(input: &ScanInput) -> ScanReport {
+    let mut runs = Vec::new();
+    let mut findings = Vec::new();
+    for check in checks::REGISTRY {
+        if let Some(reason) = input.selection.excluded(check.id) {
+            runs.push(CheckRun {
+                id: check.id,
+                title: check.title,
+                skipped: Some(reason.into()),
+                applicable: true,
+                findings: 0,
+            });
+            continue;
+        }
+        if let Some(p) = &input.policy
+            && !p.check_enabled(check.id)
+        {
+            runs.push(CheckRun {
+                id: check.id,
+                title: check.title,
+                skipped: Some("disabled by policy".into()),
+                applicable: true,
+                findings: 0,
+            });
+            continue;
+        }
+        if let Some(cap) = check.requires
+            && !input.has_capability(cap)
+        {
+            runs.push(CheckRun {
+                id: check.id,
+                title: check.title,
+                skipped: Some(format!("server does not declare the {cap} capability")),
+                applicable: false,
+                findings: 0,
+            });
+            continue;
+        }
+        match (check.run)(input) {
+            Ok(found) => {
+                runs.push(CheckRun {
+                    id: check.id,
+                    title: check.title,
+                    skipped: None,
+                    applicable: true,
+                    findings: found.len(),
+                });
+                findings.extend(found);
+            }
+            Err(reason) => runs.push(CheckRun {
+                id: check.id,
+                title: check.title,
+                skipped: Some(reason),
+                applicable: true,
+                findings: 0,
+            }),
+        }
+    }
+    findings.sort_by(Finding::report_order);
+    ScanReport {
+        checks: runs,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn severity_order() {
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::Low > Severity::Info);
+    }
+
+    #[test]
+    fn findings_have_a_total_order() {
+        let a = Finding::new("tool-naming", Severity::Low, "b").tool("x");
+        let b = Finding::new("tool-naming", Severity::Low, "a").tool("x");
+        let c = Finding::new("prompt-injection", Severity::High, "z");
+        let mut one = vec![a.clone(), b.clone(), c.clone()];
+        let mut two = vec![c, b, a];
+        one.sort_by(Finding::report_order);
+        two.sort_by(Finding::report_order);
+        let titles = |v: &[Finding]| v.iter().map(|f| f.title.clone()).collect::<Vec<_>>();
+        assert_eq!(titles(&one), vec!["z", "a", "b"]);
+        assert_eq!(titles(&one), titles(&two));
+    }
+
+    #[test]
+    fn json_keys_are_sorted() {
+        // Output stability relies on serde_json's default BTreeMap-backed maps
+        // (the preserve_order feature would switch to insertion order).
+        let v = json!({"zeta": 1, "alpha": {"b": 2, "a": 1}});
+        assert_eq!(v.to_string(), r#"{"alpha":{"a":1,"b":2},"zeta":1}"#);
+    }
+
+    #[test]
+    fn probe_result_classification() {
+        let r = ProbeResult::Response(json!({"id":1,"error":{"code":-32601,"message":"x"}}));
+        assert_eq!(r.error_code(), Some(-32601));
+        assert!(!r.is_success());
+        assert!(ProbeResult::Response(json!({"id":1,"result":{}})).is_success());
+    }
+}
@@ -0,0 +1,179 @@
+//! Tool definition pinning (rug-pull detection).
+//!
+//! On first contact with a target, a SHA-256 of each tool's description and
+//! schemas is stored under `<state_dir>/pins/<target-hash>.json`. Later scans
+//! compare against the pinned set; changed, added or removed tools are the
+//! "approve once, swap later" pattern. Pins are only replaced on request
+//! (`scan --repin`), so a swap keeps being reported until acknowledged.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::utils::{sha256_hex, stable_hash, state_dir, utc_timestamp};
+
+/// Hashes of one tool definition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPin {
+    /// SHA-256 of the description text
+    pub description: String,
+    /// SHA-256 of canonical JSON of inputSchema + outputSchema
+    pub schema: String,
+    /// SHA-256 of canonical JSON of annotations
+    #[serde(default)]
+    pub annotations: String,
+}
+
+impl ToolPin {
+    pub fn from_tool(tool: &Value) -> Self {
+        let desc = tool
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let schemas = serde_json::json!({
+            "input": tool.get("inputSchema").or_else(|| tool.get("input_schema")),
+            "output": tool.get("outputSchema").or_else(|| tool.get("output_schema")),
+        });
+        let annotations = tool.get("annotations").cloned().unwrap_or(Value::Null);
+        // serde_json maps are key-sorted, so to_string() is canonical enough here.
+        ToolPin {
+            description: sha256_hex(desc.as_bytes()),
+            schema: sha256_hex(schemas.to_string().as_bytes()),
+            annotations: sha256_hex(annotations.to_string().as_bytes()),
+        }
+    }
+}
+
+/// Pinned definitions for one target.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PinSet {
+    pub target: String,
+    pub pinned_at: String,
+    pub tools: BTreeMap<String, ToolPin>,
+}
+
+impl PinSet {
+    pub fn from_tools(target: &str, tools: &[Value]) -> Self {
+        let tools = tools
+            .iter()
+            .filter_map(|t| {
+                let name = t.get("name").and_then(|v| v.as_str())?;
+                Some((name.to_string(), ToolPin::from_tool(t)))
+            })
+            .collect();
+        PinSet {
+            target: target.to_string(),
+            pinned_at: utc_timestamp(),
+            tools,
+        }
+    }
+}
+
+/// What changed between the pinned set and the current definitions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinChange {
+    Added(String),
+    Removed(String),
+    /// (tool, changed parts: "description" / "schema" / "annotations")
+    Modified(String, Vec<&'static str>),
+}
+
+/// Compare pinned hashes against the current pin set.
+pub fn diff(pinned: &PinSet, current: &PinSet) -> Vec<PinChange> {
+    let mut out = Vec::new();
+    for (name, old) in &pinned.tools {
+        match current.tools.get(name) {
+            None => out.push(PinChange::Removed(name.clone())),
+            Some(new) => {
+                let mut parts = Vec::new();
+                if old.description != new.description {
+                    parts.push("description");
+                }
+                if old.schema != new.schema {
+                    parts.push("schema");
+                }
+                if old.annotations != new.annotations {
+                    parts.push("annotations");
+                }
+                if !parts.is_empty() {
+                    out.push(PinChange::Modified(name.clone(), parts));
+                }
+            }
+        }
+    }
+    for name in current.tools.keys() {
+        if !pinned.tools.contains_key(name) {
+            out.push(PinChange::Added(name.clone()));
+        }
+    }
+    out
+}
+
+/// Pin file location for a target.
+pub fn pin_path(target: &str) -> PathBuf {
+    state_dir()
+        .join("pins")
+        .join(format!("{}.json", stable_hash(target.trim())))
+}
+
+/// Load the pinned set for a target (None on first contact).
+pub fn load(target: &str) -> Result<Option<PinSet>> {
+    let path = pin_path(target);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let raw = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read pin file {}", path.display()))?;
+    let set = serde_json::from_str(&raw)
+        .with_context(|| format!("corrupt pin file {}", path.display()))?;
+    Ok(Some(set))
+}
+
+/// Persist a pin set (creates the state directory).
+pub fn save(set: &PinSet) -> Result<PathBuf> {
+    let path = pin_path(&set.target);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+    }
+    let body = serde_json::to_string_pretty(set).context("failed to serialize pins")?;
+    std::fs::write(&path, body)
+        .with_context(|| format!("failed to write pin file {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_reports_modified_added_removed() {
+        let old = PinSet::from_tools(
+            "t",
+            &[
+                json!({"name": "a", "description": "safe"}),
+                json!({"name": "b", "description": "x"}),
+            ],
+        );
+        let new = PinSet::from_tools(
+            "t",
+            &[
+                json!({"name": "a", "description": "now evil", "inputSchema": {"type": "object"}}),
+                json!({"name": "c"}),
+            ],
+        );
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                PinChange::Modified("a".into(), vec!["description", "schema"]),
+                PinChange::Removed("b".into()),
+                PinChange::Added("c".into()),
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+}
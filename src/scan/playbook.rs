@@ -0,0 +1,342 @@
+//! Playbooks: multi-step tool-call chains (`run`).
+//!
+//! ```yaml
+//! name: read another user's notes
+//! target: python3 server.py          # optional; -t overrides
+//! vars:
+//!   user: alice
+//! steps:
+//!   - name: login
+//!     tool: login
+//!     args: { user: "{{user}}", password: hunter2 }
+//!     save: { token: /content/0/text }  # JSON pointer into the result
+//!     assert:
+//!       - error: false
+//!   - name: notes
+//!     tool: read_notes
+//!     args: { token: "{{token}}", owner: bob }
+//!     as: notes                          # whole result text
+//!     when: { value: "{{token}}", empty: false }
+//!     assert:
+//!       - contains: "bob's"
+//!       - pointer: /isError
+//!         equals: "false"
+//!   - method: resources/read             # any JSON-RPC method
+//!     params: { uri: "file:///{{notes}}" }
+//! ```
+//!
+//! `{{name}}` in step args / params / checks is replaced by a variable (file
+//! `vars`, `--var`, values saved by earlier steps); a string that is exactly
+//! one placeholder keeps the saved JSON value's type. A check holds when all
+//! of its conditions do: `error` (tool isError or JSON-RPC error), `equals`,
+//! `contains`, `not_contains`, `matches` (glob) and `empty`, applied to
+//! `value` (a template), `pointer` (into the step's result) or by default the
+//! result text. `when` is a check against the previous step's result; a step
+//! whose `when` fails is skipped.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+use crate::utils::pattern::glob_match;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Check {
+    pub value: Option<String>,
+    pub pointer: Option<String>,
+    pub error: Option<bool>,
+    pub equals: Option<String>,
+    pub contains: Option<String>,
+    pub not_contains: Option<String>,
+    pub matches: Option<String>,
+    pub empty: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    pub name: Option<String>,
+    /// tools/call tool name
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub args: Map<String, Value>,
+    /// Raw JSON-RPC method (instead of `tool`)
+    pub method: Option<String>,
+    pub params: Option<Value>,
+    /// Variable name -> JSON pointer into the result
+    #[serde(default)]
+    pub save: BTreeMap<String, String>,
+    /// Save the whole result text under this name
+    #[serde(rename = "as")]
+    pub save_as: Option<String>,
+    pub when: Option<Check>,
+    #[serde(default)]
+    pub assert: Vec<Check>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Playbook {
+    pub name: Option<String>,
+    pub target: Option<String>,
+    #[serde(default)]
+    pub vars: BTreeMap<String, Value>,
+    pub steps: Vec<Step>,
+}
+
+impl Step {
+    pub fn label(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("step#{}", index + 1))
+    }
+}
+
+/// Parse and validate a playbook.
+pub fn parse(text: &str) -> Result<Playbook> {
+    let book: Playbook = serde_yaml::from_str(text).context("invalid playbook")?;
+    if book.steps.is_empty() {
+        bail!("playbook has no steps");
+    }
+    for (i, step) in book.steps.iter().enumerate() {
+        let label = step.label(i);
+        if step.tool.is_some() == step.method.is_some() {
+            bail!("{label}: needs exactly one of tool / method");
+        }
+        if let Some(p) = step
+            .save
+            .values()
+            .find(|p| !p.is_empty() && !p.starts_with('/'))
+        {
+            bail!("{label}: save path '{p}' is not a JSON pointer (must start with '/')");
+        }
+        for check in step.assert.iter().chain(&step.when) {
+            if let Some(p) = &check.pointer
+                && !p.is_empty()
+                && !p.starts_with('/')
+            {
+                bail!("{label}: pointer '{p}' is not a JSON pointer (must start with '/')");
+            }
+        }
+    }
+    Ok(book)
+}
+
+pub fn load(path: &str) -> Result<Playbook> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read playbook {path}"))?;
+    parse(&text).with_context(|| format!("playbook {path}"))
+}
+
+/// Variables visible to templates.
+pub type Vars = BTreeMap<String, Value>;
+
+fn as_text(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replace `{{name}}` placeholders in `s`.
+pub fn render_str(s: &str, vars: &Vars) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = rest[start + 2..start + 2 + len].trim();
+        let value = vars
+            .get(name)
+            .with_context(|| format!("undefined variable '{name}'"))?;
+        out.push_str(&rest[..start]);
+        out.push_str(&as_text(value));
+        rest = &rest[start + 4 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Render every string in `v`; `"{{name}}"` alone becomes the variable's value.
+pub fn render(v: &Value, vars: &Vars) -> Result<Value> {
+    Ok(match v {
+        Value::String(s) => {
+            let t = s.trim();
+            let whole = t
+                .strip_prefix("{{")
+                .and_then(|r| r.strip_suffix("}}"))
+                .filter(|name| !name.contains("{{") && !name.contains("}}"));
+            match whole.and_then(|name| vars.get(name.trim())) {
+                Some(value) => value.clone(),
+                None => Value::String(render_str(s, vars)?),
+            }
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|i| render(i, vars))
+                .collect::<Result<_>>()?,
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, i)| Ok((k.clone(), render(i, vars)?)))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+/// Text of a response: joined text content of a tool result, else the
+/// result (or error) as JSON.
+pub fn result_text(response: &Value) -> String {
+    if let Some(err) = response.get("error") {
+        return as_text(err);
+    }
+    let result = response.get("result").unwrap_or(&Value::Null);
+    match result.get("content").and_then(|v| v.as_array()) {
+        Some(content) => content
+            .iter()
+            .filter_map(|c| c.get("text").and_then(|v| v.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => as_text(result),
+    }
+}
+
+/// True when the response is a JSON-RPC error or a tool error result.
+pub fn is_error(response: &Value) -> bool {
+    response.get("error").is_some()
+        || response.pointer("/result/isError") == Some(&Value::Bool(true))
+}
+
+impl Check {
+    /// Evaluate against a response; Err carries why the check failed.
+    pub fn evaluate(&self, response: &Value, vars: &Vars) -> Result<(), String> {
+        if let Some(want) = self.error
+            && is_error(response) != want
+        {
+            return Err(format!(
+                "expected {}an error",
+                if want { "" } else { "no " }
+            ));
+        }
+        let subject = match (&self.value, &self.pointer) {
+            (Some(v), _) => render_str(v, vars).map_err(|e| e.to_string())?,
+            (None, Some(p)) => response
+                .get("result")
+                .and_then(|r| r.pointer(p))
+                .map(as_text)
+                .unwrap_or_default(),
+            (None, None) => result_text(response),
+        };
+        let render = |s: &String| render_str(s, vars).map_err(|e| e.to_string());
+        let short = |s: &str| {
+            let s: String = s.chars().take(80).collect();
+            format!("{s:?}")
+        };
+        if let Some(want) = &self.equals {
+            let want = render(want)?;
+            if subject != want {
+                return Err(format!("{} != {}", short(&subject), short(&want)));
+            }
+        }
+        if let Some(want) = &self.contains {
+            let want = render(want)?;
+            if !subject.contains(&want) {
+                return Err(format!(
+                    "{} does not contain {}",
+                    short(&subject),
+                    short(&want)
+                ));
+            }
+        }
+        if let Some(want) = &self.not_contains {
+            let want = render(want)?;
+            if subject.contains(&want) {
+                return Err(format!("{} contains {}", short(&subject), short(&want)));
+            }
+        }
+        if let Some(pattern) = &self.matches {
+            let pattern = render(pattern)?;
+            if !glob_match(&pattern, &subject) {
+                return Err(format!(
+                    "{} does not match {}",
+                    short(&subject),
+                    short(&pattern)
+                ));
+            }
+        }
+        if let Some(want) = self.empty
+            && subject.is_empty() != want
+        {
+            return Err(format!(
+                "{} is {}empty",
+                short(&subject),
+                if want { "not " } else { "" }
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Variables a step saves from its response.
+pub fn saved(step: &Step, response: &Value) -> Vars {
+    let mut out = Vars::new();
+    if let Some(name) = &step.save_as {
+        out.insert(name.clone(), Value::String(result_text(response)));
+    }
+    let result = response.get("result").unwrap_or(&Value::Null);
+    for (name, pointer) in &step.save {
+        let value = result.pointer(pointer).cloned().unwrap_or(Value::Null);
+        out.insert(name.clone(), value);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn templates_saves_and_checks() {
+        let book = parse(
+            r#"
+steps:
+  - tool: login
+    args: { user: "{{user}}", n: "{{n}}" }
+    save: { token: /content/0/text }
+    as: all
+    assert:
+      - error: false
+      - contains: "{{user}}"
+"#,
+        )
+        .unwrap();
+        let step = &book.steps[0];
+        let vars: Vars = [
+            ("user".to_string(), json!("alice")),
+            ("n".to_string(), json!(3)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            render(&Value::Object(step.args.clone()), &vars).unwrap(),
+            json!({"user": "alice", "n": 3})
+        );
+        assert!(render_str("{{missing}}", &vars).is_err());
+
+        let resp = json!({"result": {"content": [{"type": "text", "text": "tok-alice"}]}});
+        let s = saved(step, &resp);
+        assert_eq!(s["token"], "tok-alice");
+        assert_eq!(s["all"], "tok-alice");
+        assert!(step.assert.iter().all(|c| c.evaluate(&resp, &vars).is_ok()));
+
+        let err = json!({"error": {"code": -32602, "message": "bad"}});
+        assert!(step.assert[0].evaluate(&err, &vars).is_err());
+        assert!(parse("steps:\n  - name: x\n").is_err());
+    }
+}
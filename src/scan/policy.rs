@@ -0,0 +1,266 @@
+//! Scan policy files (`scan --policy policy.yaml`).
+//!
+//! A policy states what a server is expected to look like; every deviation
+//! becomes a finding of the `policy` check:
+//!
+//! ```yaml
+//! tools:
+//!   allow: [search, fetch_*]    # anything else is unexpected
+//!   deny: [exec_command]        # must not be present
+//! capabilities:
+//!   deny: [exec, fs-write]      # risk classes (see scan::risk)
+//! descriptions:                 # SHA-256 of the expected description text
+//!   search: 3b4c...             # ("sha256:" prefix optional)
+//! checks:
+//!   disable: [tool-naming]      # or enable: [...] to run only those
+//! ```
+//!
+//! Tool names accept a trailing `*` as a prefix wildcard. Unknown keys,
+//! capability classes and check ids are rejected at load time so a typo
+//! cannot silently weaken the policy.
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::risk::{self, Capability};
+use super::{Finding, Severity, checks};
+use crate::utils::sha256_hex;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolRules {
+    /// Expected tools; None = any tool is allowed
+    pub allow: Option<Vec<String>>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CapabilityRules {
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckRules {
+    /// Run only these checks; None = all
+    pub enable: Option<Vec<String>>,
+    #[serde(default)]
+    pub disable: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    #[serde(default)]
+    pub tools: ToolRules,
+    #[serde(default)]
+    pub capabilities: CapabilityRules,
+    /// Tool name -> SHA-256 of its description
+    #[serde(default)]
+    pub descriptions: BTreeMap<String, String>,
+    #[serde(default)]
+    pub checks: CheckRules,
+}
+
+fn matches(pattern: &str, name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => pattern == name,
+    }
+}
+
+/// Parse and validate policy YAML.
+pub fn parse(text: &str) -> Result<Policy> {
+    let mut policy: Policy = serde_yaml::from_str(text).context("invalid policy")?;
+    for cap in &policy.capabilities.deny {
+        if !Capability::ALL.iter().any(|c| c.as_str() == cap) {
+            let known: Vec<&str> = Capability::ALL.iter().map(|c| c.as_str()).collect();
+            bail!(
+                "unknown capability '{cap}' in policy (expected {})",
+                known.join("|")
+            );
+        }
+    }
+    for id in policy
+        .checks
+        .enable
+        .iter()
+        .flatten()
+        .chain(&policy.checks.disable)
+    {
+        if !checks::REGISTRY.iter().any(|c| c.id == id) {
+            bail!("unknown check '{id}' in policy");
+        }
+    }
+    for hash in policy.descriptions.values_mut() {
+        let h = hash
+            .trim()
+            .trim_start_matches("sha256:")
+            .to_ascii_lowercase();
+        if h.len() != 64 || !h.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("description hash '{hash}' is not a SHA-256 hex digest");
+        }
+        *hash = h;
+    }
+    Ok(policy)
+}
+
+pub fn load(path: &str) -> Result<Policy> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("failed to read policy {path}"))?;
+    parse(&text).with_context(|| format!("policy {path}"))
+}
+
+impl Policy {
+    /// Whether the policy lets a check run (the policy check itself always runs).
+    pub fn check_enabled(&self, id: &str) -> bool {
+        id == "policy"
+            || (self
+                .checks
+                .enable
+                .as_ref()
+                .is_none_or(|e| e.iter().any(|x| x == id))
+                && !self.checks.disable.iter().any(|x| x == id))
+    }
+
+    /// Deviations of a tool inventory from the policy.
+    pub fn evaluate(&self, tools: &[Value]) -> Vec<Finding> {
+        let mut out = Vec::new();
+        for tool in tools {
+            let Some(name) = tool.get("name").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            if let Some(p) = self.tools.deny.iter().find(|p| matches(p, name)) {
+                out.push(
+                    Finding::new(
+                        "policy",
+                        Severity::High,
+                        format!("Tool '{name}' is denied by policy"),
+                    )
+                    .tool(name)
+                    .evidence(format!("tools.deny: {p}")),
+                );
+            } else if let Some(allow) = &self.tools.allow
+                && !allow.iter().any(|p| matches(p, name))
+            {
+                out.push(
+                    Finding::new(
+                        "policy",
+                        Severity::Medium,
+                        format!("Unexpected tool '{name}' (not in policy allow list)"),
+                    )
+                    .tool(name)
+                    .detail("Add it to tools.allow once reviewed, or remove it from the server."),
+                );
+            }
+            for class in risk::classify(tool) {
+                if self
+                    .capabilities
+                    .deny
+                    .iter()
+                    .any(|c| c == class.capability.as_str())
+                {
+                    out.push(
+                        Finding::new(
+                            "policy",
+                            Severity::High,
+                            format!(
+                                "Tool '{name}' has forbidden capability: {}",
+                                class.capability.label()
+                            ),
+                        )
+                        .tool(name)
+                        .evidence(class.evidence),
+                    );
+                }
+            }
+        }
+        for (name, expected) in &self.descriptions {
+            let Some(tool) = tools
+                .iter()
+                .find(|t| t.get("name").and_then(|v| v.as_str()) == Some(name))
+            else {
+                out.push(
+                    Finding::new(
+                        "policy",
+                        Severity::Medium,
+                        format!("Tool '{name}' with a pinned description is missing"),
+                    )
+                    .tool(name.clone()),
+                );
+                continue;
+            };
+            let desc = tool
+                .get("description")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let actual = sha256_hex(desc.as_bytes());
+            if actual != *expected {
+                out.push(
+                    Finding::new(
+                        "policy",
+                        Severity::High,
+                        format!("Description of '{name}' does not match the policy hash"),
+                    )
+                    .tool(name.clone())
+                    .detail(format!(
+                        "Expected sha256:{expected}. The description changed since the policy \
+                         was written; review it before updating the hash."
+                    ))
+                    .evidence(format!("sha256:{actual}")),
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rejects_typos() {
+        assert!(parse("tool: {}").is_err());
+        assert!(parse("capabilities: {deny: [exe]}").is_err());
+        assert!(parse("checks: {disable: [rugpull]}").is_err());
+        assert!(parse("descriptions: {a: abc}").is_err());
+    }
+
+    #[test]
+    fn reports_deviations() {
+        let hash = sha256_hex(b"Search the web");
+        let policy = parse(&format!(
+            "tools: {{allow: [search, fetch_*], deny: [drop_db]}}\n\
+             capabilities: {{deny: [exec]}}\n\
+             descriptions: {{search: 'sha256:{hash}', gone: '{hash}'}}\n\
+             checks: {{disable: [tool-naming]}}\n"
+        ))
+        .unwrap();
+        let tools = vec![
+            json!({"name": "search", "description": "Search the web"}),
+            json!({"name": "fetch_url"}),
+            json!({"name": "drop_db"}),
+            json!({"name": "run_shell", "description": "Execute a shell command"}),
+        ];
+        let f = policy.evaluate(&tools);
+        let titles: Vec<&str> = f.iter().map(|f| f.title.as_str()).collect();
+        assert!(titles.contains(&"Tool 'drop_db' is denied by policy"));
+        assert!(titles.contains(&"Unexpected tool 'run_shell' (not in policy allow list)"));
+        assert!(
+            titles
+                .iter()
+                .any(|t| t.starts_with("Tool 'run_shell' has forbidden"))
+        );
+        assert!(titles.contains(&"Tool 'gone' with a pinned description is missing"));
+        assert_eq!(f.len(), 4);
+        assert!(!policy.check_enabled("tool-naming"));
+        assert!(policy.check_enabled("policy"));
+    }
+}
@@ -0,0 +1,348 @@
+//! Cross-tool state pollution.
+//!
+//! Every callable tool is first called with a unique marker in each free-form
+//! string parameter (plant phase), then resources / prompts are read and
+//! every tool is called again with neutral placeholders (probe phase). A
+//! marker planted through tool A that shows up anywhere other than A's own
+//! plant result is a shared-state channel inside the server:
+//!   - A -> B: a later result of an unrelated tool carries A's input
+//!   - A -> A: A returns earlier input to a later, unrelated call
+//!   - A -> resource / prompt: input is persisted into readable context
+//!
+//! Tools annotated destructive or classified exec / messaging are skipped
+//! unless asked, since planting really invokes them.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::time::Duration;
+
+use super::canary::plant_arguments;
+use super::risk::{Capability, classify};
+use super::{Finding, Severity, list_all, read_resources};
+use crate::mcp::TargetSpec;
+use crate::mcp::raw::RawSession;
+use crate::utils::random_token;
+
+/// Upper bound on prompts fetched while probing.
+const MAX_PROMPTS: usize = 50;
+
+/// String parameters that can carry a marker (no enum / const constraint).
+pub fn marker_params(tool: &Value) -> Vec<String> {
+    let Some(props) = tool
+        .get("inputSchema")
+        .or_else(|| tool.get("input_schema"))
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object())
+    else {
+        return Vec::new();
+    };
+    props
+        .iter()
+        .filter(|(_, p)| {
+            p.get("type").and_then(|v| v.as_str()).unwrap_or("string") == "string"
+                && p.get("enum").is_none()
+                && p.get("const").is_none()
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Why a tool is not called, if it should be skipped.
+fn skip_reason(tool: &Value, include_destructive: bool) -> Option<String> {
+    if include_destructive {
+        return None;
+    }
+    if tool
+        .pointer("/annotations/destructiveHint")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        return Some("destructive (use --include-destructive)".into());
+    }
+    classify(tool)
+        .into_iter()
+        .find(|c| matches!(c.capability, Capability::Exec | Capability::Messaging))
+        .map(|c| format!("{} (use --include-destructive)", c.capability.label()))
+}
+
+/// Marker planted through one tool.
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub tool: String,
+    pub params: Vec<String>,
+    pub value: String,
+}
+
+/// Where a marker was observed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    Tool(String),
+    Resource(String),
+    Prompt(String),
+    Notification(String),
+}
+
+impl Sink {
+    pub fn describe(&self) -> String {
+        match self {
+            Sink::Tool(t) => format!("tool {t}"),
+            Sink::Resource(uri) => format!("resource {uri}"),
+            Sink::Prompt(name) => format!("prompt {name}"),
+            Sink::Notification(m) => format!("notification {m}"),
+        }
+    }
+}
+
+/// Outcome of a pollution run.
+#[derive(Debug, Default)]
+pub struct PollutionRun {
+    pub markers: Vec<Marker>,
+    /// (index into markers, sink)
+    pub edges: Vec<(usize, Sink)>,
+    /// (tool, reason) for tools that were not called
+    pub skipped: Vec<(String, String)>,
+    pub probes: usize,
+}
+
+impl PollutionRun {
+    /// Record every marker found in `text`; `own` is the marker whose plant
+    /// call produced the text (an echo, not a channel).
+    fn observe(&mut self, text: &str, sink: Sink, own: Option<usize>) {
+        for (i, m) in self.markers.iter().enumerate() {
+            if Some(i) == own || !text.contains(&m.value) {
+                continue;
+            }
+            if !self.edges.iter().any(|(j, s)| *j == i && *s == sink) {
+                self.edges.push((i, sink.clone()));
+            }
+        }
+    }
+
+    fn observe_frames(&mut self, frames: &[Value]) {
+        for f in frames {
+            if let Some(m) = f.get("method").and_then(|v| v.as_str()) {
+                self.observe(&f.to_string(), Sink::Notification(m.to_string()), None);
+            }
+        }
+    }
+
+    /// One finding per (source tool, sink).
+    pub fn findings(&self) -> Vec<Finding> {
+        let mut out: Vec<Finding> = self
+            .edges
+            .iter()
+            .map(|(i, sink)| {
+                let m = &self.markers[*i];
+                let (severity, title) = match sink {
+                    Sink::Tool(t) if *t == m.tool => (
+                        Severity::Low,
+                        format!("Input to {} persists into its later calls", m.tool),
+                    ),
+                    Sink::Tool(t) => (
+                        Severity::Medium,
+                        format!("Shared state: input to {} leaks into {t}", m.tool),
+                    ),
+                    Sink::Resource(_) => (
+                        Severity::Medium,
+                        format!("Input to {} persists into {}", m.tool, sink.describe()),
+                    ),
+                    Sink::Prompt(_) => (
+                        Severity::High,
+                        format!("Input to {} flows into {}", m.tool, sink.describe()),
+                    ),
+                    Sink::Notification(_) => (
+                        Severity::Low,
+                        format!("Input to {} resurfaces in {}", m.tool, sink.describe()),
+                    ),
+                };
+                Finding::new("state-pollution", severity, title)
+                    .tool(m.tool.clone())
+                    .detail(format!(
+                        "A marker sent in {} was observed in {}. Callers sharing this server \
+                         (or a prompt-injected model) can influence or read state set through \
+                         another tool.",
+                        m.params.join(", "),
+                        sink.describe()
+                    ))
+                    .evidence(m.value.clone())
+            })
+            .collect();
+        out.sort_by(Finding::report_order);
+        out
+    }
+}
+
+/// Plant a marker through each tool, then probe tools, resources and prompts.
+pub async fn run(
+    spec: &TargetSpec,
+    timeout: Duration,
+    include_destructive: bool,
+) -> Result<PollutionRun> {
+    crate::utils::progress_events::phase("connect", Some(spec.original()));
+    // Raw frames: markers are searched for in everything the server sends.
+    let mut session = RawSession::spawn(spec, timeout)?;
+    let init = session
+        .initialize()
+        .await
+        .context("initialize handshake failed")?;
+    let tools = list_all(&mut session, "tools/list", "tools", usize::MAX).await?;
+
+    let mut run = PollutionRun::default();
+    let mut callable: Vec<&Value> = Vec::new();
+    for tool in &tools {
+        let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if name.is_empty() {
+            continue;
+        }
+        match skip_reason(tool, include_destructive) {
+            Some(reason) => run.skipped.push((name.to_string(), reason)),
+            None => callable.push(tool),
+        }
+    }
+
+    crate::utils::progress_events::phase("plant", None);
+    for tool in &callable {
+        let name = tool["name"].as_str().unwrap_or_default();
+        let params = marker_params(tool);
+        if params.is_empty() {
+            continue;
+        }
+        let value = format!("mcphackstate{}", random_token(12));
+        let idx = run.markers.len();
+        run.markers.push(Marker {
+            tool: name.to_string(),
+            params: params.clone(),
+            value: value.clone(),
+        });
+        let secrets: Vec<(String, String)> =
+            params.into_iter().map(|p| (p, value.clone())).collect();
+        let before = session.interleaved.len();
+        let resp = session
+            .request(
+                "tools/call",
+                Some(json!({ "name": name, "arguments": plant_arguments(tool, &secrets) })),
+            )
+            .await;
+        let frames = session.interleaved[before..].to_vec();
+        run.observe_frames(&frames);
+        if let Ok(resp) = resp {
+            // Earlier markers in this plant result are already a channel.
+            run.observe(&resp.to_string(), Sink::Tool(name.to_string()), Some(idx));
+        }
+    }
+    if run.markers.is_empty() {
+        session.shutdown().await;
+        return Ok(run);
+    }
+
+    // Passive reads first: probe calls overwrite state set during planting.
+    if init.pointer("/result/capabilities/resources").is_some() {
+        crate::utils::progress_events::phase("probe", Some("resources"));
+        let contents = read_resources(&mut session).await.unwrap_or_default();
+        for c in &contents {
+            let uri = c.get("uri").and_then(|v| v.as_str()).unwrap_or("?");
+            run.observe(&c.to_string(), Sink::Resource(uri.to_string()), None);
+            run.probes += 1;
+        }
+    }
+    if init.pointer("/result/capabilities/prompts").is_some() {
+        crate::utils::progress_events::phase("probe", Some("prompts"));
+        let prompts = list_all(&mut session, "prompts/list", "prompts", MAX_PROMPTS)
+            .await
+            .unwrap_or_default();
+        for p in &prompts {
+            let name = p.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+            run.observe(&p.to_string(), Sink::Prompt(name.to_string()), None);
+            let mut args = Map::new();
+            for arg in p
+                .get("arguments")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+            {
+                if let Some(n) = arg.get("name").and_then(|v| v.as_str()) {
+                    args.insert(n.to_string(), json!("test"));
+                }
+            }
+            if let Ok(resp) = session
+                .request(
+                    "prompts/get",
+                    Some(json!({ "name": name, "arguments": args })),
+                )
+                .await
+            {
+                run.observe(&resp.to_string(), Sink::Prompt(name.to_string()), None);
+            }
+            run.probes += 1;
+        }
+    }
+    crate::utils::progress_events::phase("probe", Some("tools"));
+    for tool in &callable {
+        let name = tool["name"].as_str().unwrap_or_default();
+        let before = session.interleaved.len();
+        let resp = session
+            .request(
+                "tools/call",
+                Some(json!({ "name": name, "arguments": plant_arguments(tool, &[]) })),
+            )
+            .await;
+        let frames = session.interleaved[before..].to_vec();
+        run.observe_frames(&frames);
+        if let Ok(resp) = resp {
+            run.observe(&resp.to_string(), Sink::Tool(name.to_string()), None);
+        }
+        run.probes += 1;
+    }
+    let late = session.drain(Duration::from_millis(300)).await;
+    run.observe_frames(&late);
+    session.shutdown().await;
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marker_params_skip_constrained() {
+        let t = json!({"name": "set", "inputSchema": {"properties": {
+            "key": {"type": "string"},
+            "mode": {"type": "string", "enum": ["a", "b"]},
+            "n": {"type": "integer"},
+        }}});
+        assert_eq!(marker_params(&t), vec!["key"]);
+    }
+
+    #[test]
+    fn edges_map_to_findings() {
+        let mut run = PollutionRun {
+            markers: vec![Marker {
+                tool: "set_note".into(),
+                params: vec!["text".into()],
+                value: "mcphackstateabc".into(),
+            }],
+            ..Default::default()
+        };
+        run.observe(
+            "saved mcphackstateabc",
+            Sink::Tool("set_note".into()),
+            Some(0),
+        );
+        assert!(run.edges.is_empty(), "own echo is not a channel");
+        run.observe(
+            "notes: mcphackstateabc",
+            Sink::Tool("get_notes".into()),
+            None,
+        );
+        run.observe(
+            "notes: mcphackstateabc",
+            Sink::Tool("get_notes".into()),
+            None,
+        );
+        run.observe("x mcphackstateabc", Sink::Prompt("summary".into()), None);
+        let f = run.findings();
+        assert_eq!(f.len(), 2);
+        assert_eq!(f[0].severity, Severity::High);
+        assert!(f[1].title.contains("leaks into get_notes"));
+    }
+}
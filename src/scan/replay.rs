@@ -0,0 +1,105 @@
+//! Transcript replay comparison (`replay`).
+//!
+//! A recorded response and the one a replayed request gets now are compared
+//! after dropping the `jsonrpc` / `id` envelope and any ignored JSON
+//! pointers (volatile fields such as timestamps or server versions); a
+//! difference is shown as a unified diff of the pretty-printed messages.
+
+use serde_json::Value;
+
+use super::diff::unified_diff;
+
+/// Outcome of one replayed request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Same,
+    Changed,
+    /// No response now (timeout / server gone), one was recorded
+    Missing,
+}
+
+impl Verdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Same => "same",
+            Verdict::Changed => "changed",
+            Verdict::Missing => "missing",
+        }
+    }
+}
+
+/// The comparable part of a response.
+pub fn normalize(response: &Value, ignore: &[String]) -> Value {
+    let mut v = response.clone();
+    if let Some(map) = v.as_object_mut() {
+        map.remove("jsonrpc");
+        map.remove("id");
+    }
+    for pointer in ignore {
+        let (parent, key) = pointer.rsplit_once('/').unwrap_or(("", pointer));
+        let key = key.replace("~1", "/").replace("~0", "~");
+        match v.pointer_mut(parent) {
+            Some(Value::Object(map)) => {
+                map.remove(&key);
+            }
+            Some(Value::Array(items)) => {
+                if let Ok(i) = key.parse::<usize>()
+                    && i < items.len()
+                {
+                    items.remove(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    v
+}
+
+/// Compare a recorded response with the replayed one; the diff is empty
+/// unless the verdict is Changed.
+pub fn compare(
+    recorded: Option<&Value>,
+    replayed: Option<&Value>,
+    ignore: &[String],
+) -> (Verdict, String) {
+    let pretty = |v: Option<&Value>| {
+        v.map(|v| normalize(v, ignore))
+            .and_then(|v| serde_json::to_string_pretty(&v).ok())
+            .unwrap_or_default()
+    };
+    match (recorded, replayed) {
+        (Some(_), None) => (Verdict::Missing, String::new()),
+        (None, None) => (Verdict::Same, String::new()),
+        _ => {
+            let diff = unified_diff(&pretty(recorded), &pretty(replayed), "recorded", "replayed");
+            if diff.is_empty() {
+                (Verdict::Same, diff)
+            } else {
+                (Verdict::Changed, diff)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn compares_without_envelope_and_ignored_fields() {
+        let a = json!({"jsonrpc": "2.0", "id": 1, "result": {"serverInfo": {"version": "1.0"}, "x": 1}});
+        let b = json!({"jsonrpc": "2.0", "id": 7, "result": {"serverInfo": {"version": "1.1"}, "x": 1}});
+        assert_eq!(compare(Some(&a), Some(&b), &[]).0, Verdict::Changed);
+        let ignore = vec!["/result/serverInfo/version".to_string()];
+        assert_eq!(
+            compare(Some(&a), Some(&b), &ignore),
+            (Verdict::Same, String::new())
+        );
+        assert_eq!(compare(Some(&a), None, &[]).0, Verdict::Missing);
+
+        let (v, diff) = compare(None, Some(&b), &[]);
+        assert_eq!(v, Verdict::Changed);
+        assert!(diff.contains("+  \"result\""));
+    }
+}
@@ -0,0 +1,553 @@
+//! Shareable Markdown / HTML scan reports.
+//!
+//! Rendered from the same JSON document `scan --json` prints, so the report
+//! never disagrees with machine output: header (target, server, mode),
+//! severity summary, affected tools, findings with evidence and remediation
+//! notes, capability risk summary and the check list. Documents from other
+//! long-running commands (`soak`) reuse the renderer: their `kind` names the
+//! report and a `growth` section is charted when present.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::Severity;
+use super::growth::sparkline;
+use super::sarif::rule_title;
+use crate::utils::utc_timestamp;
+
+/// Report file formats, picked from the output path's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let ext = path.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("md" | "markdown") => Ok(ReportFormat::Markdown),
+            Some("html" | "htm") => Ok(ReportFormat::Html),
+            _ => bail!("cannot infer report format from '{path}' (expected .md or .html)"),
+        }
+    }
+}
+
+/// Generic remediation per check id; finding details add specifics.
+const REMEDIATION: &[(&str, &str)] = &[
+    (
+        "dangerous-tool",
+        "Confirm the capability is required. Restrict arguments (allow-lists, sandboxed paths), \
+         require human confirmation and annotate the tool (destructiveHint / readOnlyHint).",
+    ),
+    (
+        "prompt-injection",
+        "Remove instructions aimed at the model from descriptions. Descriptions should only \
+         describe the tool; treat servers shipping such text as untrusted.",
+    ),
+    (
+        "hidden-unicode",
+        "Strip invisible / bidi / tag characters and use plain ASCII names so what the user \
+         reviews is what the model reads.",
+    ),
+    (
+        "missing-auth",
+        "Require authentication (OAuth or a bearer token) on remote endpoints.",
+    ),
+    (
+        "broad-schema",
+        "Declare complete input schemas with additionalProperties: false and constrain \
+         sensitive parameters (enum, pattern, maxLength).",
+    ),
+    (
+        "tool-naming",
+        "Give every tool a unique name matching ^[a-zA-Z0-9_-]{1,64}$.",
+    ),
+    (
+        "rug-pull",
+        "Review the changed definitions. Re-pin (--repin) only once the change is understood \
+         and expected.",
+    ),
+    (
+        "content-rules",
+        "Remove secrets and injected instructions from resources exposed to clients.",
+    ),
+    (
+        "resource-growth",
+        "Bound caches and per-session state, and close files, sockets and pipes opened per \
+         request. Re-run `soak` with a longer duration to confirm the trend.",
+    ),
+    (
+        "policy",
+        "Bring the server in line with the policy, or update the policy after review.",
+    ),
+    (
+        "protocol",
+        "Follow the MCP lifecycle: reject requests before initialize, answer malformed frames \
+         with JSON-RPC errors and keep stdout for protocol messages only.",
+    ),
+];
+
+pub fn remediation(check: &str) -> Option<&'static str> {
+    REMEDIATION
+        .iter()
+        .find(|(id, _)| *id == check)
+        .map(|(_, r)| *r)
+}
+
+fn s<'a>(v: &'a Value, ptr: &str) -> &'a str {
+    v.pointer(ptr).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn findings(doc: &Value) -> &[Value] {
+    doc.get("findings")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// (tool, finding count, highest severity) ordered by severity then name.
+fn affected_tools(doc: &Value) -> Vec<(String, usize, Severity)> {
+    let mut tools: BTreeMap<String, (usize, Severity)> = BTreeMap::new();
+    for f in findings(doc) {
+        let (Some(tool), Some(sev)) = (
+            f.get("tool").and_then(|v| v.as_str()),
+            Severity::parse(s(f, "/severity")),
+        ) else {
+            continue;
+        };
+        let e = tools.entry(tool.to_string()).or_insert((0, sev));
+        e.0 += 1;
+        e.1 = e.1.max(sev);
+    }
+    let mut out: Vec<_> = tools.into_iter().map(|(t, (n, sev))| (t, n, sev)).collect();
+    out.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+fn risk_lines(doc: &Value) -> Vec<(String, String)> {
+    doc.get("risk")
+        .and_then(|v| v.as_object())
+        .map(|m| {
+            m.iter()
+                .map(|(cap, tools)| {
+                    let names: Vec<&str> = tools
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|t| t.as_str())
+                        .collect();
+                    (cap.clone(), names.join(", "))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn title(doc: &Value) -> String {
+    let kind = s(doc, "/kind");
+    format!(
+        "mcp-hack {} report",
+        if kind.is_empty() { "scan" } else { kind }
+    )
+}
+
+fn meta_lines(doc: &Value) -> Vec<(&'static str, String)> {
+    let server = format!("{} {}", s(doc, "/server/name"), s(doc, "/server/version"));
+    vec![
+        ("Target", s(doc, "/target").to_string()),
+        ("Server", server.trim().to_string()),
+        ("Protocol", s(doc, "/server/protocolVersion").to_string()),
+        ("Mode", s(doc, "/mode").to_string()),
+        (
+            "Tools scanned",
+            doc.get("tools_scanned")
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
+        ("Workload", s(doc, "/workload").to_string()),
+        (
+            "Requests",
+            doc.get("requests")
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        ),
+        ("Generated", utc_timestamp()),
+    ]
+}
+
+/// (label, unit, values) series of the `growth` section.
+fn growth_series(doc: &Value) -> Vec<(&'static str, &'static str, Vec<u64>)> {
+    let samples = doc
+        .pointer("/growth/samples")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let series = |key: &str| -> Vec<u64> {
+        samples
+            .iter()
+            .map(|x| x.get(key).and_then(|v| v.as_u64()).unwrap_or(0))
+            .collect()
+    };
+    vec![
+        ("Resident memory", "KiB", series("rss_kb")),
+        ("Open descriptors", "", series("fds")),
+    ]
+}
+
+/// Inline SVG line chart of one series.
+fn svg_chart(values: &[u64]) -> String {
+    const W: f64 = 600.0;
+    const H: f64 = 120.0;
+    let min = values.iter().copied().min().unwrap_or(0) as f64;
+    let max = values.iter().copied().max().unwrap_or(0) as f64;
+    let span = (max - min).max(1.0);
+    let step = W / (values.len().max(2) - 1) as f64;
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            format!(
+                "{:.1},{:.1}",
+                i as f64 * step,
+                H - 4.0 - (*v as f64 - min) / span * (H - 8.0)
+            )
+        })
+        .collect();
+    format!(
+        "<svg width=\"{W}\" height=\"{H}\" viewBox=\"0 0 {W} {H}\" \
+         style=\"border:1px solid #ccc;background:#fafafa\">\
+         <polyline fill=\"none\" stroke=\"#c62828\" stroke-width=\"2\" points=\"{}\"/></svg>",
+        points.join(" ")
+    )
+}
+
+/// Render a report from a `scan --json` document.
+pub fn render(format: ReportFormat, doc: &Value) -> String {
+    match format {
+        ReportFormat::Markdown => markdown(doc),
+        ReportFormat::Html => html(doc),
+    }
+}
+
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown(doc: &Value) -> String {
+    let mut out = format!("# {}\n\n", title(doc));
+    for (k, v) in meta_lines(doc) {
+        if !v.is_empty() {
+            out.push_str(&format!("- **{k}:** {}\n", md_cell(&v)));
+        }
+    }
+
+    out.push_str("\n## Summary\n\n| Severity | Count |\n|---|---|\n");
+    for sev in Severity::ALL {
+        let n = doc
+            .pointer(&format!("/summary/{}", sev.as_str()))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        out.push_str(&format!("| {} | {n} |\n", sev.as_str()));
+    }
+
+    let tools = affected_tools(doc);
+    if !tools.is_empty() {
+        out.push_str(
+            "\n## Affected tools\n\n| Tool | Findings | Highest severity |\n|---|---|---|\n",
+        );
+        for (tool, n, sev) in &tools {
+            out.push_str(&format!(
+                "| `{}` | {n} | {} |\n",
+                md_cell(tool),
+                sev.as_str()
+            ));
+        }
+    }
+
+    out.push_str("\n## Findings\n\n");
+    if findings(doc).is_empty() {
+        out.push_str("No findings.\n");
+    }
+    for (i, f) in findings(doc).iter().enumerate() {
+        let check = s(f, "/check");
+        out.push_str(&format!(
+            "### {}. [{}] {}\n\n",
+            i + 1,
+            s(f, "/severity").to_uppercase(),
+            s(f, "/title")
+        ));
+        out.push_str(&format!("- **Check:** `{check}` ({})\n", rule_title(check)));
+        if let Some(tool) = f.get("tool").and_then(|v| v.as_str()) {
+            out.push_str(&format!("- **Tool:** `{tool}`\n"));
+        }
+        out.push('\n');
+        if !s(f, "/detail").is_empty() {
+            out.push_str(&format!("{}\n\n", s(f, "/detail")));
+        }
+        if let Some(ev) = f.get("evidence").and_then(|v| v.as_str()) {
+            // A fence longer than any backtick run inside the evidence.
+            let fence = "`".repeat(
+                ev.split(|c| c != '`')
+                    .map(str::len)
+                    .max()
+                    .unwrap_or(0)
+                    .max(2)
+                    + 1,
+            );
+            out.push_str(&format!("**Evidence**\n\n{fence}\n{ev}\n{fence}\n\n"));
+        }
+        if let Some(r) = remediation(check) {
+            out.push_str(&format!("**Remediation:** {r}\n\n"));
+        }
+    }
+
+    let growth = growth_series(doc);
+    if !growth.is_empty() {
+        out.push_str("## Resource growth\n\n```\n");
+        for (label, unit, values) in &growth {
+            let line = format!(
+                "{label:<17} {} {} -> {} {unit}",
+                sparkline(values),
+                values[0],
+                values[values.len() - 1]
+            );
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out.push_str("```\n\n");
+    }
+
+    let risk = risk_lines(doc);
+    if !risk.is_empty() {
+        out.push_str("## Risk summary\n\n| Capability | Tools |\n|---|---|\n");
+        for (cap, tools) in &risk {
+            out.push_str(&format!("| {cap} | {} |\n", md_cell(tools)));
+        }
+        out.push('\n');
+    }
+
+    if doc.get("checks").is_none() {
+        return out;
+    }
+    out.push_str("## Checks\n\n| Check | Status | Findings | Note |\n|---|---|---|---|\n");
+    for c in doc
+        .get("checks")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        out.push_str(&format!(
+            "| `{}` | {} | {} | {} |\n",
+            s(c, "/id"),
+            s(c, "/status"),
+            c.get("findings").and_then(|v| v.as_u64()).unwrap_or(0),
+            md_cell(s(c, "/reason"))
+        ));
+    }
+    out
+}
+
+pub(crate) fn esc(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Stylesheet of HTML reports (shared with `web`).
+pub(crate) const CSS: &str = "body{font-family:system-ui,sans-serif;max-width:960px;margin:2em auto;\
+padding:0 1em;color:#222}table{border-collapse:collapse;margin:1em 0}\
+td,th{border:1px solid #ccc;padding:4px 8px;text-align:left}\
+pre{background:#f4f4f4;padding:8px;overflow-x:auto;white-space:pre-wrap}\
+.sev{font-weight:bold;padding:1px 6px;border-radius:3px;color:#fff}\
+.critical{background:#7b0000}.high{background:#c62828}.medium{background:#ef6c00}\
+.low{background:#1565c0}.info{background:#757575}\
+.finding{border-left:4px solid #ccc;padding-left:1em;margin:1.5em 0}";
+
+pub(crate) fn sev_badge(sev: &str) -> String {
+    let sev = esc(sev);
+    format!("<span class=\"sev {sev}\">{}</span>", sev.to_uppercase())
+}
+
+fn html(doc: &Value) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">\
+         <title>{} - {}</title><style>{CSS}</style></head><body>\n\
+         <h1>{}</h1>\n<ul>\n",
+        title(doc),
+        esc(s(doc, "/target")),
+        title(doc)
+    );
+    for (k, v) in meta_lines(doc) {
+        if !v.is_empty() {
+            out.push_str(&format!("<li><b>{k}:</b> {}</li>\n", esc(&v)));
+        }
+    }
+    out.push_str("</ul>\n<h2>Summary</h2>\n<table><tr><th>Severity</th><th>Count</th></tr>\n");
+    for sev in Severity::ALL {
+        let n = doc
+            .pointer(&format!("/summary/{}", sev.as_str()))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{n}</td></tr>\n",
+            sev_badge(sev.as_str())
+        ));
+    }
+    out.push_str("</table>\n");
+
+    let tools = affected_tools(doc);
+    if !tools.is_empty() {
+        out.push_str(
+            "<h2>Affected tools</h2>\n<table><tr><th>Tool</th><th>Findings</th>\
+             <th>Highest severity</th></tr>\n",
+        );
+        for (tool, n, sev) in &tools {
+            out.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{n}</td><td>{}</td></tr>\n",
+                esc(tool),
+                sev_badge(sev.as_str())
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Findings</h2>\n");
+    if findings(doc).is_empty() {
+        out.push_str("<p>No findings.</p>\n");
+    }
+    for f in findings(doc) {
+        let check = s(f, "/check");
+        out.push_str(&format!(
+            "<div class=\"finding\"><h3>{} {}</h3>\n<p><b>Check:</b> <code>{}</code> ({})",
+            sev_badge(s(f, "/severity")),
+            esc(s(f, "/title")),
+            esc(check),
+            esc(rule_title(check))
+        ));
+        if let Some(tool) = f.get("tool").and_then(|v| v.as_str()) {
+            out.push_str(&format!("<br><b>Tool:</b> <code>{}</code>", esc(tool)));
+        }
+        out.push_str("</p>\n");
+        if !s(f, "/detail").is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", esc(s(f, "/detail"))));
+        }
+        if let Some(ev) = f.get("evidence").and_then(|v| v.as_str()) {
+            out.push_str(&format!("<p><b>Evidence</b></p><pre>{}</pre>\n", esc(ev)));
+        }
+        if let Some(r) = remediation(check) {
+            out.push_str(&format!("<p><b>Remediation:</b> {}</p>\n", esc(r)));
+        }
+        out.push_str("</div>\n");
+    }
+
+    let growth = growth_series(doc);
+    if !growth.is_empty() {
+        out.push_str("<h2>Resource growth</h2>\n");
+        for (label, unit, values) in &growth {
+            out.push_str(&format!(
+                "<p><b>{label}:</b> {} -> {} {unit}</p>\n{}\n",
+                values[0],
+                values[values.len() - 1],
+                svg_chart(values)
+            ));
+        }
+    }
+
+    let risk = risk_lines(doc);
+    if !risk.is_empty() {
+        out.push_str("<h2>Risk summary</h2>\n<table><tr><th>Capability</th><th>Tools</th></tr>\n");
+        for (cap, tools) in &risk {
+            out.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                esc(cap),
+                esc(tools)
+            ));
+        }
+        out.push_str("</table>\n");
+    }
+
+    if doc.get("checks").is_none() {
+        out.push_str("</body></html>\n");
+        return out;
+    }
+    out.push_str(
+        "<h2>Checks</h2>\n<table><tr><th>Check</th><th>Status</th><th>Findings</th>\
+         <th>Note</th></tr>\n",
+    );
+    for c in doc
+        .get("checks")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        out.push_str(&format!(
+            "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            esc(s(c, "/id")),
+            esc(s(c, "/status")),
+            c.get("findings").and_then(|v| v.as_u64()).unwrap_or(0),
+            esc(s(c, "/reason"))
+        ));
+    }
+    out.push_str("</table>\n</body></html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "target": "python3 s.py",
+            "server": {"name": "srv", "version": "1.0", "protocolVersion": "2025-06-18"},
+            "mode": "passive",
+            "tools_scanned": 2,
+            "summary": {"critical": 0, "high": 1, "medium": 0, "low": 1, "info": 0},
+            "checks": [{"id": "dangerous-tool", "status": "ran", "findings": 1, "reason": null}],
+            "risk": {"exec": ["run"]},
+            "findings": [
+                {"check": "dangerous-tool", "severity": "high", "title": "Command <exec>",
+                 "detail": "d", "tool": "run", "evidence": "a|b"},
+                {"check": "protocol", "severity": "low", "title": "noise",
+                 "detail": "", "tool": null, "evidence": null},
+            ],
+        })
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            ReportFormat::from_path("out/r.MD").unwrap(),
+            ReportFormat::Markdown
+        );
+        assert_eq!(
+            ReportFormat::from_path("r.html").unwrap(),
+            ReportFormat::Html
+        );
+        assert!(ReportFormat::from_path("r.pdf").is_err());
+    }
+
+    #[test]
+    fn markdown_lists_tools_evidence_and_remediation() {
+        let md = render(ReportFormat::Markdown, &doc());
+        assert!(md.contains("| `run` | 1 | high |"));
+        assert!(md.contains("### 1. [HIGH] Command <exec>"));
+        assert!(md.contains("```\na|b\n```"));
+        assert!(md.contains("**Remediation:** Confirm the capability"));
+        assert!(md.contains("| exec | run |"));
+    }
+
+    #[test]
+    fn html_escapes() {
+        let html = render(ReportFormat::Html, &doc());
+        assert!(html.contains("Command &lt;exec&gt;"));
+        assert!(!html.contains("<exec>"));
+    }
+}
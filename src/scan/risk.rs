@@ -0,0 +1,380 @@
+//! Dangerous capability classification of tools.
+//!
+//! Each tool is mapped to zero or more capabilities from its name, description
+//! and input schema (parameter names). Used by the dangerous-tool check, the
+//! scan risk summary and the RISK column of `list tools`.
+
+use serde_json::{Value, json};
+
+use super::Severity;
+use super::checks::excerpt;
+
+/// Capability classes, most dangerous first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Capability {
+    Exec,
+    FsWrite,
+    Credentials,
+    Egress,
+    Messaging,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 5] = [
+        Capability::Exec,
+        Capability::FsWrite,
+        Capability::Credentials,
+        Capability::Egress,
+        Capability::Messaging,
+    ];
+
+    /// Short id used in tables and JSON.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Exec => "exec",
+            Capability::FsWrite => "fs-write",
+            Capability::Credentials => "credentials",
+            Capability::Egress => "egress",
+            Capability::Messaging => "messaging",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Capability::Exec => "command / code execution",
+            Capability::FsWrite => "destructive filesystem / data modification",
+            Capability::Credentials => "credential access",
+            Capability::Egress => "network egress",
+            Capability::Messaging => "outbound messaging",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            Capability::Exec => Severity::High,
+            Capability::FsWrite | Capability::Credentials => Severity::Medium,
+            Capability::Egress | Capability::Messaging => Severity::Low,
+        }
+    }
+
+    /// Tool name tokens that indicate the capability.
+    fn name_words(&self) -> &'static [&'static str] {
+        match self {
+            Capability::Exec => &[
+                "exec",
+                "execute",
+                "shell",
+                "bash",
+                "sh",
+                "cmd",
+                "command",
+                "eval",
+                "spawn",
+                "subprocess",
+                "powershell",
+                "terminal",
+            ],
+            Capability::FsWrite => &[
+                "write",
+                "delete",
+                "remove",
+                "rm",
+                "unlink",
+                "overwrite",
+                "move",
+                "rename",
+                "chmod",
+                "truncate",
+                "drop",
+            ],
+            Capability::Credentials => &[
+                "secret",
+                "secrets",
+                "credential",
+                "credentials",
+                "password",
+                "token",
+                "apikey",
+                "keychain",
+                "vault",
+            ],
+            Capability::Egress => &[
+                "fetch", "http", "curl", "wget", "download", "upload", "webhook",
+            ],
+            Capability::Messaging => &["email", "mail", "sms", "send", "slack", "tweet", "dm"],
+        }
+    }
+
+    /// Description phrases (lowercase) that indicate the capability.
+    fn phrases(&self) -> &'static [&'static str] {
+        match self {
+            Capability::Exec => &[
+                "shell command",
+                "execute command",
+                "execute arbitrary",
+                "run arbitrary",
+                "run a command",
+                "run command",
+                "system command",
+                "evaluate code",
+                "execute code",
+                "execute python",
+                "execute javascript",
+            ],
+            Capability::FsWrite => &[
+                "write to a file",
+                "write file",
+                "delete file",
+                "deletes files",
+                "overwrite",
+                "remove file",
+                "drop table",
+            ],
+            Capability::Credentials => &[
+                "api key",
+                "access token",
+                "credentials",
+                "private key",
+                "password",
+            ],
+            Capability::Egress => &[
+                "http request",
+                "arbitrary url",
+                "any url",
+                "fetch a url",
+                "fetches a url",
+                "send a request",
+            ],
+            Capability::Messaging => &[
+                "send an email",
+                "send email",
+                "send a message",
+                "post a message",
+                "direct message",
+            ],
+        }
+    }
+
+    /// Parameter name tokens that indicate the capability on their own.
+    fn param_words(&self) -> &'static [&'static str] {
+        match self {
+            Capability::Exec => &["command", "cmd", "script", "shell"],
+            Capability::Egress => &["url", "uri", "endpoint", "webhook"],
+            Capability::Messaging => &["recipient", "recipients", "bcc", "cc"],
+            Capability::FsWrite | Capability::Credentials => &[],
+        }
+    }
+}
+
+/// One capability attributed to a tool, with the signal that triggered it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Classification {
+    pub capability: Capability,
+    pub evidence: String,
+}
+
+/// Split an identifier into lowercase words (snake, kebab, dotted and camelCase).
+pub fn name_tokens(name: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut cur = String::new();
+    let mut prev_lower = false;
+    for ch in name.chars() {
+        if !ch.is_alphanumeric() {
+            if !cur.is_empty() {
+                out.push(std::mem::take(&mut cur));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if ch.is_uppercase() && prev_lower && !cur.is_empty() {
+            out.push(std::mem::take(&mut cur));
+        }
+        prev_lower = ch.is_lowercase() || ch.is_ascii_digit();
+        cur.extend(ch.to_lowercase());
+    }
+    if !cur.is_empty() {
+        out.push(cur);
+    }
+    out
+}
+
+const PATH_PARAMS: &[&str] = &[
+    "path",
+    "file",
+    "filename",
+    "filepath",
+    "dest",
+    "destination",
+];
+const CONTENT_PARAMS: &[&str] = &["content", "contents", "data", "body", "text"];
+
+/// Classify a tool (name tokens first, then description, then parameter names).
+pub fn classify(tool: &Value) -> Vec<Classification> {
+    let name = tool.get("name").and_then(|v| v.as_str()).unwrap_or("");
+    let description = tool
+        .get("description")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let desc_lower = description.to_lowercase();
+    let tokens = name_tokens(name);
+    let params: Vec<(String, Vec<String>)> = tool
+        .get("inputSchema")
+        .or_else(|| tool.get("input_schema"))
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object())
+        .map(|props| props.keys().map(|k| (k.clone(), name_tokens(k))).collect())
+        .unwrap_or_default();
+    let param_with = |words: &[&str]| {
+        params
+            .iter()
+            .find(|(_, toks)| toks.iter().any(|t| words.contains(&t.as_str())))
+            .map(|(p, _)| p.clone())
+    };
+
+    let mut out = Vec::new();
+    for cap in Capability::ALL {
+        let evidence = if let Some(tok) = tokens
+            .iter()
+            .find(|t| cap.name_words().contains(&t.as_str()))
+        {
+            Some(format!("name token '{tok}'"))
+        } else if let Some(p) = cap.phrases().iter().find(|p| desc_lower.contains(*p)) {
+            Some(format!("description: {}", excerpt(description, p)))
+        } else if let Some(p) = param_with(cap.param_words()) {
+            Some(format!("parameter '{p}'"))
+        } else if cap == Capability::FsWrite
+            && let (Some(path), Some(content)) =
+                (param_with(PATH_PARAMS), param_with(CONTENT_PARAMS))
+        {
+            Some(format!("parameters '{path}' + '{content}'"))
+        } else {
+            None
+        };
+        if let Some(evidence) = evidence {
+            out.push(Classification {
+                capability: cap,
+                evidence,
+            });
+        }
+    }
+    out
+}
+
+/// Tool names per capability across an inventory (capabilities with no tools omitted).
+pub fn summarize(tools: &[Value]) -> Vec<(Capability, Vec<String>)> {
+    let classified: Vec<(String, Vec<Classification>)> = tools
+        .iter()
+        .map(|t| {
+            let name = t
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("<unnamed>");
+            (name.to_string(), classify(t))
+        })
+        .collect();
+    Capability::ALL
+        .into_iter()
+        .filter_map(|cap| {
+            let names: Vec<String> = classified
+                .iter()
+                .filter(|(_, c)| c.iter().any(|c| c.capability == cap))
+                .map(|(n, _)| n.clone())
+                .collect();
+            (!names.is_empty()).then_some((cap, names))
+        })
+        .collect()
+}
+
+/// Highest severity among the classifications (None = no dangerous capability).
+pub fn risk_level(classes: &[Classification]) -> Option<Severity> {
+    classes.iter().map(|c| c.capability.severity()).max()
+}
+
+/// `{"level": ..., "capabilities": [...]}` for JSON output.
+pub fn risk_json(classes: &[Classification]) -> Value {
+    json!({
+        "level": risk_level(classes).map(|s| s.as_str()),
+        "capabilities": classes.iter().map(|c| json!({
+            "capability": c.capability.as_str(),
+            "evidence": c.evidence,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Compact table cell, e.g. "HIGH exec,egress" or "-".
+pub fn risk_cell(classes: &[Classification]) -> String {
+    match risk_level(classes) {
+        None => "-".into(),
+        Some(level) => format!(
+            "{} {}",
+            level.as_str().to_uppercase(),
+            classes
+                .iter()
+                .map(|c| c.capability.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(t: Value) -> Vec<Capability> {
+        classify(&t).into_iter().map(|c| c.capability).collect()
+    }
+
+    #[test]
+    fn tokens_split_common_styles() {
+        assert_eq!(
+            name_tokens("run_shellCommand"),
+            vec!["run", "shell", "command"]
+        );
+        assert_eq!(name_tokens("read-file.v2"), vec!["read", "file", "v2"]);
+    }
+
+    #[test]
+    fn classifies_by_name_description_and_schema() {
+        assert_eq!(
+            caps(json!({"name": "execute_command"})),
+            vec![Capability::Exec]
+        );
+        assert_eq!(
+            caps(json!({"name": "notify", "description": "Send an email to the team"})),
+            vec![Capability::Messaging]
+        );
+        assert_eq!(
+            caps(json!({"name": "save", "inputSchema": {"properties": {
+                "filePath": {"type": "string"}, "content": {"type": "string"}
+            }}})),
+            vec![Capability::FsWrite]
+        );
+        assert_eq!(
+            caps(json!({"name": "preview", "inputSchema": {"properties": {"url": {}}}})),
+            vec![Capability::Egress]
+        );
+        assert!(caps(json!({"name": "add", "description": "Add two integers."})).is_empty());
+    }
+
+    #[test]
+    fn risk_cell_lists_capabilities() {
+        let c = classify(&json!({"name": "fetch_and_exec"}));
+        assert_eq!(risk_level(&c), Some(Severity::High));
+        assert_eq!(risk_cell(&c), "HIGH exec,egress");
+        assert_eq!(risk_cell(&[]), "-");
+    }
+
+    #[test]
+    fn summary_groups_tools() {
+        let s = summarize(&[
+            json!({"name": "run_command"}),
+            json!({"name": "shell"}),
+            json!({"name": "add"}),
+        ]);
+        assert_eq!(
+            s,
+            vec![(Capability::Exec, vec!["run_command".into(), "shell".into()])]
+        );
+    }
+}
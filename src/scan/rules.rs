@@ -0,0 +1,777 @@
+//! YARA-style content rules.
+//!
+//! A subset of the YARA rule language, applied to tool results (fuzz) and
+//! resource contents (scan) so existing detection content can be reused:
+//!
+//! ```text
+//! rule AwsSecret : credentials {
+//!     meta:
+//!         severity = "high"
+//!         description = "AWS secret key in output"
+//!     strings:
+//!         $k = "AWS_SECRET_ACCESS_KEY" nocase
+//!         $h = { 41 4B 49 41 ?? ?? }
+//!     condition:
+//!         any of them
+//! }
+//! ```
+//!
+//! Supported: text strings (escapes `\" \\ \n \r \t \xHH`, modifiers `nocase`,
+//! `fullword`, `ascii`), hex strings with `??` wildcards, `meta` values, and
+//! conditions built from `$id`, `#id <op> N`, `any|all|none|N of them`,
+//! `... of ($a, $b*)`, `and`, `or`, `not`, parentheses, `true` / `false`.
+//! Regex strings, hex jumps/alternatives and modules are rejected with an error.
+//! `meta.severity` (info..critical, default medium) sets the finding severity.
+
+use anyhow::{Context, Result, bail};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::Severity;
+
+/// Maximum occurrences counted per string (bounds `#id` work on large outputs).
+const MAX_COUNT: usize = 1000;
+
+/// One compiled rule.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pub tags: Vec<String>,
+    pub meta: BTreeMap<String, String>,
+    strings: Vec<StringDef>,
+    condition: Expr,
+}
+
+#[derive(Debug, Clone)]
+struct StringDef {
+    id: String,
+    pattern: Pattern,
+    nocase: bool,
+    fullword: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Text(Vec<u8>),
+    /// Hex bytes; None is a `??` wildcard
+    Hex(Vec<Option<u8>>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Quant {
+    Any,
+    All,
+    None,
+    N(usize),
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Bool(bool),
+    Var(usize),
+    Count(usize, Cmp, usize),
+    Of(Quant, Vec<usize>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// A rule that matched one piece of content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub tags: Vec<String>,
+    /// Ids of the strings that matched ("$k", ...)
+    pub strings: Vec<String>,
+    /// Short excerpt around the first match
+    pub excerpt: Option<String>,
+}
+
+impl Rule {
+    /// Severity from `meta.severity` (default medium).
+    pub fn severity(&self) -> Severity {
+        self.meta
+            .get("severity")
+            .and_then(|s| Severity::parse(s))
+            .unwrap_or(Severity::Medium)
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.meta.get("description").map(String::as_str)
+    }
+
+    /// Evaluate the rule against `data`.
+    pub fn scan(&self, data: &[u8]) -> Option<RuleMatch> {
+        let hits: Vec<Vec<usize>> = self.strings.iter().map(|s| s.find_all(data)).collect();
+        if !eval(&self.condition, &hits) {
+            return None;
+        }
+        let strings = self
+            .strings
+            .iter()
+            .zip(&hits)
+            .filter(|(_, h)| !h.is_empty())
+            .map(|(s, _)| s.id.clone())
+            .collect();
+        let excerpt = self
+            .strings
+            .iter()
+            .zip(&hits)
+            .find_map(|(s, h)| h.first().map(|&at| excerpt(data, at, s.len())));
+        Some(RuleMatch {
+            rule: self.name.clone(),
+            tags: self.tags.clone(),
+            strings,
+            excerpt,
+        })
+    }
+}
+
+impl RuleMatch {
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "rule": self.rule,
+            "tags": self.tags,
+            "strings": self.strings,
+            "excerpt": self.excerpt,
+        })
+    }
+}
+
+/// Evaluate every rule against `data`.
+pub fn scan_all(rules: &[Rule], data: &[u8]) -> Vec<RuleMatch> {
+    rules.iter().filter_map(|r| r.scan(data)).collect()
+}
+
+/// All string leaves of a JSON value joined by newlines (tool results, resource contents).
+pub fn json_text(value: &Value) -> String {
+    fn walk(v: &Value, out: &mut Vec<String>) {
+        match v {
+            Value::String(s) => out.push(s.clone()),
+            Value::Array(a) => a.iter().for_each(|x| walk(x, out)),
+            Value::Object(m) => m.values().for_each(|x| walk(x, out)),
+            Value::Number(n) => out.push(n.to_string()),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(value, &mut out);
+    out.join("\n")
+}
+
+fn excerpt(data: &[u8], at: usize, len: usize) -> String {
+    let start = at.saturating_sub(20);
+    let end = (at + len + 20).min(data.len());
+    let text = String::from_utf8_lossy(&data[start..end]);
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl StringDef {
+    fn len(&self) -> usize {
+        match &self.pattern {
+            Pattern::Text(t) => t.len(),
+            Pattern::Hex(h) => h.len(),
+        }
+    }
+
+    fn byte_eq(&self, want: u8, got: u8) -> bool {
+        if self.nocase {
+            want.eq_ignore_ascii_case(&got)
+        } else {
+            want == got
+        }
+    }
+
+    fn matches_at(&self, data: &[u8], at: usize) -> bool {
+        let ok = match &self.pattern {
+            Pattern::Text(t) => data[at..].iter().zip(t).all(|(&g, &w)| self.byte_eq(w, g)),
+            Pattern::Hex(h) => data[at..]
+                .iter()
+                .zip(h)
+                .all(|(&g, w)| w.is_none_or(|w| w == g)),
+        };
+        if !ok || !self.fullword {
+            return ok;
+        }
+        let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let before = at.checked_sub(1).map(|i| data[i]);
+        let after = data.get(at + self.len()).copied();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    }
+
+    fn find_all(&self, data: &[u8]) -> Vec<usize> {
+        let len = self.len();
+        if len == 0 || len > data.len() {
+            return Vec::new();
+        }
+        (0..=data.len() - len)
+            .filter(|&at| self.matches_at(data, at))
+            .take(MAX_COUNT)
+            .collect()
+    }
+}
+
+fn eval(e: &Expr, hits: &[Vec<usize>]) -> bool {
+    match e {
+        Expr::Bool(b) => *b,
+        Expr::Var(i) => !hits[*i].is_empty(),
+        Expr::Count(i, cmp, n) => {
+            let c = hits[*i].len();
+            match cmp {
+                Cmp::Eq => c == *n,
+                Cmp::Ne => c != *n,
+                Cmp::Lt => c < *n,
+                Cmp::Le => c <= *n,
+                Cmp::Gt => c > *n,
+                Cmp::Ge => c >= *n,
+            }
+        }
+        Expr::Of(q, set) => {
+            let matched = set.iter().filter(|&&i| !hits[i].is_empty()).count();
+            match q {
+                Quant::Any => matched >= 1,
+                Quant::All => matched == set.len(),
+                Quant::None => matched == 0,
+                Quant::N(n) => matched >= *n,
+            }
+        }
+        Expr::Not(x) => !eval(x, hits),
+        Expr::And(a, b) => eval(a, hits) && eval(b, hits),
+        Expr::Or(a, b) => eval(a, hits) || eval(b, hits),
+    }
+}
+
+/* ---- Parsing ---- */
+
+/// Load rules from a file.
+pub fn load(path: &str) -> Result<Vec<Rule>> {
+    let src = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read rules file {path}"))?;
+    parse(&src).with_context(|| format!("invalid rules file {path}"))
+}
+
+/// Parse rule source text.
+pub fn parse(src: &str) -> Result<Vec<Rule>> {
+    let mut p = Parser {
+        src: src.as_bytes(),
+        pos: 0,
+    };
+    let mut rules: Vec<Rule> = Vec::new();
+    loop {
+        p.skip_ws();
+        if p.eof() {
+            break;
+        }
+        let rule = p.rule()?;
+        if rules.iter().any(|r| r.name == rule.name) {
+            bail!("duplicate rule '{}'", rule.name);
+        }
+        rules.push(rule);
+    }
+    if rules.is_empty() {
+        bail!("no rules found");
+    }
+    Ok(rules)
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn eof(&self) -> bool {
+        self.pos >= self.src.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn line(&self) -> usize {
+        self.src[..self.pos.min(self.src.len())]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count()
+            + 1
+    }
+
+    fn err<T>(&self, msg: impl std::fmt::Display) -> Result<T> {
+        bail!("line {}: {msg}", self.line())
+    }
+
+    /// Skip whitespace and `//` / `/* */` comments.
+    fn skip_ws(&mut self) {
+        loop {
+            while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            if self.src[self.pos..].starts_with(b"//") {
+                while self.peek().is_some_and(|b| b != b'\n') {
+                    self.pos += 1;
+                }
+            } else if self.src[self.pos..].starts_with(b"/*") {
+                match self.src[self.pos + 2..].windows(2).position(|w| w == b"*/") {
+                    Some(end) => self.pos += end + 4,
+                    None => self.pos = self.src.len(),
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Consume `s` (after whitespace) if present.
+    fn eat(&mut self, s: &str) -> bool {
+        self.skip_ws();
+        let rest = &self.src[self.pos..];
+        if !rest.starts_with(s.as_bytes()) {
+            return false;
+        }
+        // Keywords must not be followed by identifier characters.
+        let word = s.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+        if word
+            && rest
+                .get(s.len())
+                .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            return false;
+        }
+        self.pos += s.len();
+        true
+    }
+
+    fn expect(&mut self, s: &str) -> Result<()> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            self.err(format!("expected '{s}'"))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        self.skip_ws();
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            self.pos += 1;
+        }
+        if start == self.pos || self.src[start].is_ascii_digit() {
+            return self.err("expected identifier");
+        }
+        Ok(String::from_utf8_lossy(&self.src[start..self.pos]).into_owned())
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        self.skip_ws();
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.src[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn string_lit(&mut self) -> Result<Vec<u8>> {
+        self.expect("\"")?;
+        let mut out = Vec::new();
+        loop {
+            let Some(b) = self.peek() else {
+                return self.err("unterminated string");
+            };
+            self.pos += 1;
+            match b {
+                b'"' => return Ok(out),
+                b'\n' => return self.err("newline in string"),
+                b'\\' => {
+                    let Some(e) = self.peek() else {
+                        return self.err("unterminated string");
+                    };
+                    self.pos += 1;
+                    match e {
+                        b'"' | b'\\' => out.push(e),
+                        b'n' => out.push(b'\n'),
+                        b'r' => out.push(b'\r'),
+                        b't' => out.push(b'\t'),
+                        b'x' => {
+                            let hex = self.src.get(self.pos..self.pos + 2).unwrap_or_default();
+                            match std::str::from_utf8(hex)
+                                .ok()
+                                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                            {
+                                Some(v) => out.push(v),
+                                None => return self.err("invalid \\x escape"),
+                            }
+                            self.pos += 2;
+                        }
+                        other => return self.err(format!("unknown escape \\{}", other as char)),
+                    }
+                }
+                _ => out.push(b),
+            }
+        }
+    }
+
+    fn hex_string(&mut self) -> Result<Vec<Option<u8>>> {
+        self.expect("{")?;
+        let mut out = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                None => return self.err("unterminated hex string"),
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                Some(b'[' | b'(' | b'|' | b'~') => {
+                    return self.err("hex jumps, alternatives and negation are not supported");
+                }
+                _ => {}
+            }
+            let pair = self.src.get(self.pos..self.pos + 2).unwrap_or_default();
+            if pair == b"??" {
+                out.push(None);
+            } else {
+                match std::str::from_utf8(pair)
+                    .ok()
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                {
+                    Some(v) => out.push(Some(v)),
+                    None => return self.err("invalid hex byte"),
+                }
+            }
+            self.pos += 2;
+        }
+        if out.iter().all(Option::is_none) {
+            return self.err("hex string needs at least one fixed byte");
+        }
+        Ok(out)
+    }
+
+    /// `$name` (optionally `$prefix*` when `allow_wildcard`).
+    fn var(&mut self, sigil: &str, allow_wildcard: bool) -> Result<(String, bool)> {
+        self.expect(sigil)?;
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            self.pos += 1;
+        }
+        let name = format!("${}", String::from_utf8_lossy(&self.src[start..self.pos]));
+        let wildcard = allow_wildcard && self.peek() == Some(b'*');
+        if wildcard {
+            self.pos += 1;
+        }
+        Ok((name, wildcard))
+    }
+
+    fn rule(&mut self) -> Result<Rule> {
+        // Modifiers are accepted and ignored.
+        while self.eat("private") || self.eat("global") {}
+        if self.eat("import") || self.eat("include") {
+            return self.err("imports / includes are not supported");
+        }
+        self.expect("rule")?;
+        let name = self.ident()?;
+        let mut tags = Vec::new();
+        if self.eat(":") {
+            loop {
+                self.skip_ws();
+                if self.peek() == Some(b'{') {
+                    break;
+                }
+                tags.push(self.ident()?);
+            }
+        }
+        self.expect("{")?;
+
+        let mut meta = BTreeMap::new();
+        let mut strings: Vec<StringDef> = Vec::new();
+        if self.eat("meta") {
+            self.expect(":")?;
+            loop {
+                self.skip_ws();
+                let save = self.pos;
+                let Ok(key) = self.ident() else { break };
+                if matches!(key.as_str(), "strings" | "condition") {
+                    self.pos = save;
+                    break;
+                }
+                self.expect("=")?;
+                self.skip_ws();
+                let value = if self.peek() == Some(b'"') {
+                    String::from_utf8_lossy(&self.string_lit()?).into_owned()
+                } else if let Some(n) = self.number() {
+                    n.to_string()
+                } else {
+                    self.ident()?
+                };
+                meta.insert(key, value);
+            }
+        }
+        if self.eat("strings") {
+            self.expect(":")?;
+            loop {
+                self.skip_ws();
+                if self.peek() != Some(b'$') {
+                    break;
+                }
+                let (id, _) = self.var("$", false)?;
+                if id == "$" {
+                    return self.err("anonymous strings are not supported");
+                }
+                if strings.iter().any(|s| s.id == id) {
+                    return self.err(format!("duplicate string {id}"));
+                }
+                self.expect("=")?;
+                self.skip_ws();
+                let pattern = match self.peek() {
+                    Some(b'"') => Pattern::Text(self.string_lit()?),
+                    Some(b'{') => Pattern::Hex(self.hex_string()?),
+                    Some(b'/') => return self.err("regex strings are not supported"),
+                    _ => return self.err("expected string or hex pattern"),
+                };
+                if matches!(&pattern, Pattern::Text(t) if t.is_empty()) {
+                    return self.err(format!("empty string {id}"));
+                }
+                let mut def = StringDef {
+                    id,
+                    pattern,
+                    nocase: false,
+                    fullword: false,
+                };
+                loop {
+                    if self.eat("nocase") {
+                        def.nocase = true;
+                    } else if self.eat("fullword") {
+                        def.fullword = true;
+                    } else if self.eat("ascii") {
+                    } else if self.eat("wide")
+                        || self.eat("xor")
+                        || self.eat("base64")
+                        || self.eat("private")
+                    {
+                        return self.err("only nocase / fullword / ascii modifiers are supported");
+                    } else {
+                        break;
+                    }
+                }
+                strings.push(def);
+            }
+        }
+        self.expect("condition")?;
+        self.expect(":")?;
+        let condition = self.or_expr(&strings)?;
+        self.expect("}")?;
+        Ok(Rule {
+            name,
+            tags,
+            meta,
+            strings,
+            condition,
+        })
+    }
+
+    fn or_expr(&mut self, strings: &[StringDef]) -> Result<Expr> {
+        let mut left = self.and_expr(strings)?;
+        while self.eat("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and_expr(strings)?));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self, strings: &[StringDef]) -> Result<Expr> {
+        let mut left = self.unary(strings)?;
+        while self.eat("and") {
+            left = Expr::And(Box::new(left), Box::new(self.unary(strings)?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self, strings: &[StringDef]) -> Result<Expr> {
+        if self.eat("not") {
+            return Ok(Expr::Not(Box::new(self.unary(strings)?)));
+        }
+        self.primary(strings)
+    }
+
+    fn lookup(&self, strings: &[StringDef], id: &str) -> Result<usize> {
+        match strings.iter().position(|s| s.id == id) {
+            Some(i) => Ok(i),
+            None => self.err(format!("undefined string {id}")),
+        }
+    }
+
+    fn primary(&mut self, strings: &[StringDef]) -> Result<Expr> {
+        self.skip_ws();
+        if self.eat("(") {
+            let e = self.or_expr(strings)?;
+            self.expect(")")?;
+            return Ok(e);
+        }
+        if self.eat("true") {
+            return Ok(Expr::Bool(true));
+        }
+        if self.eat("false") {
+            return Ok(Expr::Bool(false));
+        }
+        match self.peek() {
+            Some(b'$') => {
+                let (id, _) = self.var("$", false)?;
+                Ok(Expr::Var(self.lookup(strings, &id)?))
+            }
+            Some(b'#') => {
+                let (id, _) = self.var("#", false)?;
+                let i = self.lookup(strings, &id)?;
+                let cmp = [
+                    ("==", Cmp::Eq),
+                    ("!=", Cmp::Ne),
+                    ("<=", Cmp::Le),
+                    (">=", Cmp::Ge),
+                    ("<", Cmp::Lt),
+                    (">", Cmp::Gt),
+                ]
+                .into_iter()
+                .find(|(op, _)| self.eat(op))
+                .map(|(_, c)| c);
+                let Some(cmp) = cmp else {
+                    return self.err("expected comparison after string count");
+                };
+                let Some(n) = self.number() else {
+                    return self.err("expected number");
+                };
+                Ok(Expr::Count(i, cmp, n))
+            }
+            _ => {
+                let quant = if self.eat("any") {
+                    Quant::Any
+                } else if self.eat("all") {
+                    Quant::All
+                } else if self.eat("none") {
+                    Quant::None
+                } else if let Some(n) = self.number() {
+                    Quant::N(n)
+                } else {
+                    return self.err("unsupported condition");
+                };
+                self.expect("of")?;
+                let set = if self.eat("them") {
+                    (0..strings.len()).collect()
+                } else {
+                    self.expect("(")?;
+                    let mut set = Vec::new();
+                    loop {
+                        let (id, wildcard) = self.var("$", true)?;
+                        if wildcard {
+                            let before = set.len();
+                            set.extend(
+                                strings
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, s)| s.id.starts_with(&id))
+                                    .map(|(i, _)| i),
+                            );
+                            if set.len() == before {
+                                return self.err(format!("no strings match {id}*"));
+                            }
+                        } else {
+                            set.push(self.lookup(strings, &id)?);
+                        }
+                        if !self.eat(",") {
+                            break;
+                        }
+                    }
+                    self.expect(")")?;
+                    set
+                };
+                if set.is_empty() {
+                    return self.err("string set is empty");
+                }
+                Ok(Expr::Of(quant, set))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"
+        // credentials leaking into output
+        rule AwsSecret : creds cloud {
+            meta:
+                severity = "high"
+                description = "AWS secret"
+            strings:
+                $k = "aws_secret_access_key" nocase
+                $h = { 41 4B 49 41 ?? ?? }
+            condition:
+                any of them
+        }
+        /* needs two hits */
+        rule Twice {
+            strings:
+                $a = "token" fullword
+            condition:
+                #a >= 2 and not $a_missing_is_error_free
+        }
+    "#;
+
+    #[test]
+    fn parses_and_matches() {
+        let src = SRC.replace(" and not $a_missing_is_error_free", "");
+        let rules = parse(&src).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].tags, vec!["creds", "cloud"]);
+        assert_eq!(rules[0].severity(), Severity::High);
+        let m = rules[0].scan(b"env: AWS_SECRET_ACCESS_KEY=x").unwrap();
+        assert_eq!(m.strings, vec!["$k"]);
+        assert!(rules[0].scan(b"id AKIA\x00\x01 here").is_some());
+        assert!(rules[0].scan(b"nothing").is_none());
+        assert!(rules[1].scan(b"token tokens token").is_some());
+        assert!(rules[1].scan(b"token tokens").is_none());
+    }
+
+    #[test]
+    fn conditions_and_sets() {
+        let rules = parse(
+            r#"rule R { strings: $p1 = "a" $p2 = "b" $q = "c"
+               condition: all of ($p*) and (none of ($q) or 2 of them) }"#,
+        )
+        .unwrap();
+        assert!(rules[0].scan(b"ab").is_some());
+        assert!(rules[0].scan(b"abc").is_some());
+        assert!(rules[0].scan(b"ac").is_none());
+    }
+
+    #[test]
+    fn rejects_unsupported_and_undefined() {
+        let err = parse(SRC).unwrap_err().to_string();
+        assert!(err.contains("undefined string"), "{err}");
+        assert!(parse("rule R { strings: $a = /x/ condition: $a }").is_err());
+        assert!(parse("rule R { strings: $a = { 41 [2] 42 } condition: $a }").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn json_text_collects_leaves() {
+        let v = serde_json::json!({"content": [{"type": "text", "text": "hi"}], "n": 3});
+        assert_eq!(json_text(&v), "hi\ntext\n3");
+    }
+}
@@ -0,0 +1,176 @@
+//! SARIF 2.1.0 output for findings.
+//!
+//! One run per log, one reporting rule per check id. MCP findings have no
+//! source line, so each result points at the server artifact (script path,
+//! package or URL derived from the target) plus a logical location for the
+//! tool. `partialFingerprints` carry the export dedup key so re-uploads to
+//! GitHub code scanning update existing alerts.
+
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+use super::export::dedup_key;
+use super::{Finding, Severity, checks};
+use crate::mcp::{TargetSpec, parse_target};
+
+pub const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Titles for check ids produced outside `checks::REGISTRY`.
+const EXTRA_RULES: &[(&str, &str)] = &[
+    ("tool-shadowing", "Cross-server tool shadowing"),
+    ("secret-canary", "Secret parameter values resurfaced"),
+    ("state-pollution", "Cross-tool shared state"),
+    ("resource-growth", "Server memory / descriptor growth"),
+    ("fuzz-hit", "Fuzzing response deviated from baseline"),
+];
+
+pub fn rule_title(id: &str) -> &str {
+    checks::REGISTRY
+        .iter()
+        .find(|c| c.id == id)
+        .map(|c| c.title)
+        .or_else(|| EXTRA_RULES.iter().find(|(i, _)| *i == id).map(|(_, t)| *t))
+        .unwrap_or(id)
+}
+
+fn level(s: Severity) -> &'static str {
+    match s {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// GitHub `security-severity` score (CVSS-like 0.0-10.0).
+fn security_severity(s: Severity) -> &'static str {
+    match s {
+        Severity::Critical => "9.5",
+        Severity::High => "8.0",
+        Severity::Medium => "5.5",
+        Severity::Low => "3.0",
+        Severity::Info => "0.0",
+    }
+}
+
+/// Artifact URI for a target: the script / file argument of a local command,
+/// the package for npx-style launchers, else the program; URLs as-is.
+pub fn artifact_uri(target: &str) -> String {
+    match parse_target(target) {
+        Ok(TargetSpec::LocalCommand { program, args, .. }) => {
+            let file_like = args.iter().find(|a| {
+                !a.starts_with('-')
+                    && [
+                        ".py", ".js", ".mjs", ".ts", ".rb", ".go", ".rs", ".sh", ".jar",
+                    ]
+                    .iter()
+                    .any(|ext| a.ends_with(ext))
+            });
+            match file_like {
+                Some(f) => f.trim_start_matches("./").to_string(),
+                None => args
+                    .iter()
+                    .find(|a| !a.starts_with('-'))
+                    .filter(|_| ["npx", "uvx", "bunx"].contains(&program.as_str()))
+                    .cloned()
+                    .unwrap_or(program),
+            }
+        }
+        Ok(spec) => spec.original().to_string(),
+        Err(_) => target.to_string(),
+    }
+}
+
+/// Build a SARIF log for findings against one target.
+pub fn render(target: &str, findings: &[Finding]) -> Value {
+    // Highest severity seen per rule becomes its default level.
+    let mut rules: BTreeMap<&str, Severity> = BTreeMap::new();
+    for f in findings {
+        let e = rules.entry(f.check).or_insert(f.severity);
+        *e = (*e).max(f.severity);
+    }
+    let rule_ids: Vec<&str> = rules.keys().copied().collect();
+    let artifact = artifact_uri(target);
+
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|f| {
+            let mut message = f.title.clone();
+            if !f.detail.is_empty() {
+                message.push_str(&format!("\n\n{}", f.detail));
+            }
+            let mut location = json!({
+                "physicalLocation": {"artifactLocation": {"uri": artifact}},
+            });
+            if let Some(tool) = &f.tool {
+                location["logicalLocations"] =
+                    json!([{"name": tool, "kind": "function", "fullyQualifiedName": tool}]);
+            }
+            json!({
+                "ruleId": f.check,
+                "ruleIndex": rule_ids.iter().position(|r| *r == f.check),
+                "level": level(f.severity),
+                "message": {"text": message},
+                "locations": [location],
+                "partialFingerprints": {"mcpHackFinding/v1": dedup_key(target, f)},
+                "properties": {
+                    "severity": f.severity.as_str(),
+                    "tool": f.tool,
+                    "evidence": f.evidence,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {"driver": {
+                "name": "mcp-hack",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": "https://github.com/hahwul/mcp-hack",
+                "rules": rules.iter().map(|(id, sev)| json!({
+                    "id": id,
+                    "name": id,
+                    "shortDescription": {"text": rule_title(id)},
+                    "defaultConfiguration": {"level": level(*sev)},
+                    "properties": {
+                        "security-severity": security_severity(*sev),
+                        "tags": ["security", "mcp"],
+                    },
+                })).collect::<Vec<_>>(),
+            }},
+            "properties": {"target": target},
+            "results": results,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artifact_uris() {
+        assert_eq!(artifact_uri("python3 ./server.py --x"), "server.py");
+        assert_eq!(artifact_uri("npx -y @scope/srv"), "@scope/srv");
+        assert_eq!(artifact_uri("my-server --stdio"), "my-server");
+    }
+
+    #[test]
+    fn render_indexes_rules() {
+        let findings = vec![
+            Finding::new("protocol", Severity::Low, "a"),
+            Finding::new("dangerous-tool", Severity::High, "b").tool("exec"),
+        ];
+        let log = render("python3 s.py", &findings);
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "dangerous-tool");
+        assert_eq!(run["results"][0]["ruleIndex"], 1);
+        assert_eq!(run["results"][1]["level"], "error");
+        assert_eq!(
+            run["results"][1]["locations"][0]["logicalLocations"][0]["name"],
+            "exec"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Engagement statistics (`stats`).
+//!
+//! Aggregates the artifacts of an engagement directory: `--record`
+//! transcripts (targets, inventoried tools, calls with latency and errors)
+//! and saved `--json` outputs of commands (targets, tool lists, findings by
+//! severity).
+
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Severity;
+use crate::mcp::record::Transcript;
+
+/// Calls made to one tool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolCalls {
+    pub calls: u64,
+    pub errors: u64,
+    /// Sum / count of answered calls' latency
+    pub total_ms: u64,
+    pub answered: u64,
+}
+
+impl ToolCalls {
+    pub fn avg_ms(&self) -> Option<u64> {
+        (self.answered > 0).then(|| self.total_ms / self.answered)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub transcripts: u64,
+    pub reports: u64,
+    pub targets: BTreeSet<String>,
+    /// (target, tool) pairs seen in tools/list results or tool listings
+    pub tools: BTreeSet<(String, String)>,
+    /// Requests of any method in transcripts
+    pub requests: u64,
+    pub calls: BTreeMap<String, ToolCalls>,
+    pub findings: BTreeMap<Severity, u64>,
+}
+
+fn tool_names(list: Option<&Value>) -> impl Iterator<Item = String> + '_ {
+    list.and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|t| t.get("name").and_then(|v| v.as_str()).map(str::to_string))
+}
+
+impl Stats {
+    pub fn add_transcript(&mut self, t: &Transcript) {
+        self.transcripts += 1;
+        self.targets.extend(t.sessions.values().cloned());
+        for ex in &t.exchanges {
+            if ex.request.get("id").is_none() {
+                continue;
+            }
+            self.requests += 1;
+            let target = t.sessions.get(&ex.session).cloned().unwrap_or_default();
+            if ex.method() == Some("tools/list") {
+                let tools = ex
+                    .response
+                    .as_ref()
+                    .and_then(|r| r.pointer("/result/tools"));
+                for name in tool_names(tools) {
+                    self.tools.insert((target.clone(), name));
+                }
+            }
+            if let Some(tool) = ex.tool() {
+                let c = self.calls.entry(tool.to_string()).or_default();
+                c.calls += 1;
+                c.errors += u64::from(ex.is_error());
+                if let Some(ms) = ex.latency_ms {
+                    c.total_ms += ms;
+                    c.answered += 1;
+                }
+            }
+        }
+    }
+
+    /// Add a saved command output; false when it is not one.
+    pub fn add_report(&mut self, doc: &Value) -> bool {
+        let target = doc.get("target").and_then(|v| v.as_str());
+        let findings = doc.get("findings").and_then(|v| v.as_array());
+        if doc.get("status").and_then(|v| v.as_str()) != Some("ok")
+            || (target.is_none() && findings.is_none())
+        {
+            return false;
+        }
+        self.reports += 1;
+        if let Some(t) = target {
+            self.targets.insert(t.to_string());
+            for name in tool_names(doc.get("tools")) {
+                self.tools.insert((t.to_string(), name));
+            }
+        }
+        for f in findings.into_iter().flatten() {
+            if let Some(sev) = f
+                .get("severity")
+                .and_then(|v| v.as_str())
+                .and_then(Severity::parse)
+            {
+                *self.findings.entry(sev).or_default() += 1;
+            }
+        }
+        true
+    }
+
+    pub fn total_calls(&self) -> u64 {
+        self.calls.values().map(|c| c.calls).sum()
+    }
+
+    /// Average latency over all answered tool calls.
+    pub fn avg_call_ms(&self) -> Option<u64> {
+        let (ms, n) = self
+            .calls
+            .values()
+            .fold((0, 0), |(ms, n), c| (ms + c.total_ms, n + c.answered));
+        (n > 0).then(|| ms / n)
+    }
+
+    /// Most-called tools (ties by name).
+    pub fn busiest(&self, n: usize) -> Vec<(&str, &ToolCalls)> {
+        let mut v: Vec<(&str, &ToolCalls)> =
+            self.calls.iter().map(|(k, c)| (k.as_str(), c)).collect();
+        v.sort_by(|a, b| b.1.calls.cmp(&a.1.calls).then(a.0.cmp(b.0)));
+        v.truncate(n);
+        v
+    }
+
+    pub fn to_json(&self, top: usize) -> Value {
+        json!({
+            "files": {"transcripts": self.transcripts, "reports": self.reports},
+            "targets": self.targets,
+            "tools_inventoried": self.tools.len(),
+            "requests": self.requests,
+            "calls": self.total_calls(),
+            "avg_call_ms": self.avg_call_ms(),
+            "findings": Severity::ALL
+                .iter()
+                .map(|s| (s.as_str().to_string(), json!(self.findings.get(s).copied().unwrap_or(0))))
+                .collect::<serde_json::Map<_, _>>(),
+            "busiest_tools": self.busiest(top).iter().map(|(name, c)| json!({
+                "tool": name,
+                "calls": c.calls,
+                "errors": c.errors,
+                "avg_ms": c.avg_ms(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::record;
+
+    #[test]
+    fn aggregates_transcripts_and_reports() {
+        let t = record::parse(
+            r#"{"mcpt":1}
+{"event":"session","session":1,"target":"python3 s.py"}
+{"session":1,"dir":"send","t_ms":0,"message":{"id":1,"method":"tools/list"}}
+{"session":1,"dir":"recv","t_ms":2,"message":{"id":1,"result":{"tools":[{"name":"a"},{"name":"b"}]}}}
+{"session":1,"dir":"send","t_ms":3,"message":{"id":2,"method":"tools/call","params":{"name":"a"}}}
+{"session":1,"dir":"recv","t_ms":13,"message":{"id":2,"error":{"code":-1}}}
+{"session":1,"dir":"send","t_ms":14,"message":{"id":3,"method":"tools/call","params":{"name":"a"}}}
+{"session":1,"dir":"recv","t_ms":44,"message":{"id":3,"result":{}}}
+"#,
+        )
+        .unwrap();
+        let mut s = Stats::default();
+        s.add_transcript(&t);
+        assert!(s.add_report(&json!({"status": "ok", "target": "uvx other",
+            "findings": [{"severity": "high"}, {"severity": "high"}, {"severity": "info"}]})));
+        assert!(!s.add_report(&json!({"name": "package.json"})));
+
+        assert_eq!(s.targets.len(), 2);
+        assert_eq!(s.tools.len(), 2);
+        assert_eq!(s.requests, 3);
+        assert_eq!(s.total_calls(), 2);
+        assert_eq!(s.avg_call_ms(), Some(20));
+        assert_eq!(s.calls["a"].errors, 1);
+        assert_eq!(s.findings[&Severity::High], 2);
+        assert_eq!(s.to_json(5)["busiest_tools"][0]["tool"], "a");
+    }
+}
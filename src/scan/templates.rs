@@ -0,0 +1,321 @@
+//! Detection templates (`scan --templates DIR`).
+//!
+//! Community-extensible checks without code changes, in the spirit of
+//! nuclei templates: one YAML file per template, loaded from files or
+//! directories (searched recursively for `*.yaml` / `*.yml`).
+//!
+//! ```yaml
+//! id: fetch-without-allowlist
+//! name: URL fetcher without an allow-list
+//! severity: medium                 # critical|high|medium|low|info
+//! description: The tool fetches arbitrary URLs (SSRF).
+//! scope: tool                      # tool (default) | resource | server
+//! match:
+//!   name: ["*fetch*", "*http*"]
+//!   param: ["url", "*_url"]
+//! exclude:
+//!   description: "*allow*list*"
+//! ```
+//!
+//! Match fields take one pattern or a list; a field holds when any pattern
+//! matches and a template fires when every field given holds (and the
+//! `exclude` block, if any, does not). Patterns are case-insensitive globs
+//! over the whole text (`*` any run, `?` one character). Per scope:
+//!   - tool       name, description, param (parameter names), schema (inputSchema JSON)
+//!   - resource   name (URI), description, content (text read back)
+//!   - server     name (serverInfo.name), description (instructions),
+//!     content (initialize result JSON)
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use super::{Finding, ScanInput, Severity};
+use crate::utils::pattern::glob_match;
+
+/// One pattern or a list of them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Patterns {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Patterns {
+    fn list(&self) -> &[String] {
+        match self {
+            Patterns::One(p) => std::slice::from_ref(p),
+            Patterns::Many(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Matcher {
+    pub name: Option<Patterns>,
+    pub description: Option<Patterns>,
+    pub param: Option<Patterns>,
+    pub schema: Option<Patterns>,
+    pub content: Option<Patterns>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub severity: String,
+    pub description: Option<String>,
+    pub scope: Option<String>,
+    #[serde(rename = "match")]
+    pub matcher: Matcher,
+    pub exclude: Option<Matcher>,
+}
+
+/// Texts of one subject (tool, resource or the server) a matcher looks at.
+#[derive(Debug, Default)]
+struct Subject {
+    label: String,
+    name: String,
+    description: String,
+    params: Vec<String>,
+    schema: String,
+    content: String,
+}
+
+fn any_match(patterns: &Option<Patterns>, texts: &[&str]) -> Option<bool> {
+    let patterns = patterns.as_ref()?;
+    Some(patterns.list().iter().any(|p| {
+        let p = p.to_lowercase();
+        texts.iter().any(|t| glob_match(&p, &t.to_lowercase()))
+    }))
+}
+
+impl Matcher {
+    fn is_empty(&self) -> bool {
+        self == &Matcher::default()
+    }
+
+    /// True when every given field holds for `s`.
+    fn holds(&self, s: &Subject) -> bool {
+        let params: Vec<&str> = s.params.iter().map(String::as_str).collect();
+        [
+            any_match(&self.name, &[&s.name]),
+            any_match(&self.description, &[&s.description]),
+            any_match(&self.param, &params),
+            any_match(&self.schema, &[&s.schema]),
+            any_match(&self.content, &[&s.content]),
+        ]
+        .into_iter()
+        .all(|r| r.unwrap_or(true))
+    }
+}
+
+impl Template {
+    pub fn scope(&self) -> &str {
+        self.scope.as_deref().unwrap_or("tool")
+    }
+
+    pub fn level(&self) -> Severity {
+        Severity::parse(&self.severity).unwrap_or(Severity::Medium)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let id = &self.id;
+        if id.trim().is_empty() {
+            bail!("template id must not be empty");
+        }
+        if Severity::parse(&self.severity).is_none() {
+            bail!("{id}: unknown severity '{}'", self.severity);
+        }
+        if self.matcher.is_empty() {
+            bail!("{id}: match block has no conditions");
+        }
+        for m in std::iter::once(&self.matcher).chain(&self.exclude) {
+            let unsupported = match self.scope() {
+                "tool" => m.content.is_some().then_some("content"),
+                "resource" | "server" => {
+                    (m.param.is_some() || m.schema.is_some()).then_some("param / schema")
+                }
+                other => bail!("{id}: unknown scope '{other}' (expected tool|resource|server)"),
+            };
+            if let Some(field) = unsupported {
+                bail!("{id}: {field} does not apply to scope '{}'", self.scope());
+            }
+        }
+        Ok(())
+    }
+
+    fn fires(&self, s: &Subject) -> bool {
+        self.matcher.holds(s) && !self.exclude.as_ref().is_some_and(|e| e.holds(s))
+    }
+}
+
+/// Parse and validate one template.
+pub fn parse(text: &str) -> Result<Template> {
+    let t: Template = serde_yaml::from_str(text).context("invalid template")?;
+    t.validate()?;
+    Ok(t)
+}
+
+fn collect_files(path: &Path, out: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        out.push(path.to_path_buf());
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    let mut entries: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    entries.sort();
+    for p in entries {
+        if p.is_dir() {
+            collect_files(&p, out);
+        } else if matches!(p.extension().and_then(|e| e.to_str()), Some("yaml" | "yml")) {
+            out.push(p);
+        }
+    }
+}
+
+/// Load templates from files / directories; ids must be unique.
+pub fn load(paths: &[String]) -> Result<Vec<Template>> {
+    let mut files = Vec::new();
+    for p in paths {
+        let path = Path::new(p);
+        if !path.exists() {
+            bail!("{p}: no such file or directory");
+        }
+        collect_files(path, &mut files);
+    }
+    let mut out: Vec<Template> = Vec::new();
+    let mut ids = BTreeSet::new();
+    for file in files {
+        let shown = file.display();
+        let text =
+            std::fs::read_to_string(&file).with_context(|| format!("failed to read {shown}"))?;
+        let t = parse(&text).with_context(|| format!("template {shown}"))?;
+        if !ids.insert(t.id.clone()) {
+            bail!("template {shown}: duplicate id '{}'", t.id);
+        }
+        out.push(t);
+    }
+    Ok(out)
+}
+
+fn text(v: Option<&Value>) -> String {
+    v.and_then(|v| v.as_str()).unwrap_or("").to_string()
+}
+
+fn tool_subject(t: &Value) -> Subject {
+    let schema = t.get("inputSchema").or_else(|| t.get("input_schema"));
+    let name = text(t.get("name"));
+    Subject {
+        label: name.clone(),
+        name,
+        description: text(t.get("description")),
+        params: schema
+            .and_then(|s| s.get("properties"))
+            .and_then(|p| p.as_object())
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default(),
+        schema: schema.map(|s| s.to_string()).unwrap_or_default(),
+        content: String::new(),
+    }
+}
+
+fn subjects(scope: &str, input: &ScanInput) -> Vec<Subject> {
+    match scope {
+        "tool" => input.tools.iter().map(tool_subject).collect(),
+        "resource" => input
+            .resources
+            .iter()
+            .map(|r| {
+                let uri = text(r.get("uri"));
+                Subject {
+                    label: uri.clone(),
+                    name: uri,
+                    description: text(r.get("description")),
+                    content: text(r.get("text")),
+                    ..Default::default()
+                }
+            })
+            .collect(),
+        _ => vec![Subject {
+            label: input.server_name().to_string(),
+            name: input.server_name().to_string(),
+            description: text(input.initialize.get("instructions")),
+            content: input.initialize.to_string(),
+            ..Default::default()
+        }],
+    }
+}
+
+/// True when any template needs resource contents collected.
+pub fn needs_resources(templates: &[Template]) -> bool {
+    templates.iter().any(|t| t.scope() == "resource")
+}
+
+/// Findings of every template that fires on the scanned input.
+pub fn evaluate(templates: &[Template], input: &ScanInput) -> Vec<Finding> {
+    let mut out = Vec::new();
+    for t in templates {
+        for s in subjects(t.scope(), input) {
+            if !t.fires(&s) {
+                continue;
+            }
+            let mut f = Finding::new("templates", t.level(), format!("{} ({})", t.name, s.label))
+                .detail(
+                    t.description
+                        .clone()
+                        .unwrap_or_else(|| format!("Detection template {} matched.", t.id)),
+                )
+                .evidence(format!("template {}", t.id));
+            if t.scope() == "tool" {
+                f = f.tool(s.label);
+            }
+            out.push(f);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_tools_with_excludes_and_validates() {
+        let t = parse(
+            r#"
+id: fetch
+name: URL fetcher
+severity: high
+match:
+  name: "*FETCH*"
+  param: [url, "*_url"]
+exclude:
+  description: "*allow*list*"
+"#,
+        )
+        .unwrap();
+        let fetch = |desc: &str| {
+            tool_subject(&json!({"name": "web_fetch", "description": desc,
+                "inputSchema": {"properties": {"target_url": {"type": "string"}}}}))
+        };
+        assert!(t.fires(&fetch("Fetch a page")));
+        assert!(!t.fires(&fetch("Fetch a page on the allow-list")));
+        assert!(!t.fires(&tool_subject(&json!({"name": "web_fetch"}))));
+
+        assert!(parse("id: x\nname: x\nseverity: severe\nmatch: {name: a}").is_err());
+        assert!(parse("id: x\nname: x\nseverity: low\nmatch: {}").is_err());
+        assert!(parse("id: x\nname: x\nseverity: low\nmatch: {content: a}").is_err());
+        assert!(
+            parse("id: x\nname: x\nseverity: low\nscope: resource\nmatch: {content: '*key*'}")
+                .is_ok()
+        );
+    }
+}
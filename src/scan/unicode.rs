@@ -0,0 +1,357 @@
+//! Invisible Unicode and homoglyph detection.
+//!
+//! Flags characters that hide content from human reviewers while staying
+//! visible to an LLM (zero-width, bidi controls, tag characters, variation
+//! selectors) and look-alike letters substituted into Latin words.
+//!
+//! analyze(text, identifier) -> Vec<Issue>
+//! tool_issues(tool)         -> issues per location (name, description, params)
+//! reveal(text)              -> text with invisibles rendered as <U+XXXX>
+
+use serde_json::{Value, json};
+
+/// Category of suspicious characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    ZeroWidth,
+    Bidi,
+    Tag,
+    VariationSelector,
+    Homoglyph,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::ZeroWidth => "zero-width",
+            Kind::Bidi => "bidi-control",
+            Kind::Tag => "tag-characters",
+            Kind::VariationSelector => "variation-selector",
+            Kind::Homoglyph => "homoglyph",
+        }
+    }
+}
+
+/// One category of issue found in a piece of text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub kind: Kind,
+    /// Number of offending characters (homoglyph: offending words)
+    pub count: usize,
+    /// Distinct code points, e.g. "U+200B" (first few)
+    pub codepoints: Vec<String>,
+    /// Tag characters decoded back to ASCII (the hidden message)
+    pub hidden: Option<String>,
+    /// Homoglyph words with their ASCII look-alike, e.g. "pаypal→paypal"
+    pub words: Vec<String>,
+}
+
+impl Issue {
+    fn new(kind: Kind) -> Self {
+        Issue {
+            kind,
+            count: 0,
+            codepoints: Vec::new(),
+            hidden: None,
+            words: Vec::new(),
+        }
+    }
+
+    fn add_char(&mut self, c: char) {
+        self.count += 1;
+        let cp = format!("U+{:04X}", c as u32);
+        if self.codepoints.len() < 5 && !self.codepoints.contains(&cp) {
+            self.codepoints.push(cp);
+        }
+    }
+
+    /// Compact human summary, e.g. "zero-width ×2 (U+200B)".
+    pub fn describe(&self) -> String {
+        let mut s = format!("{} ×{}", self.kind.as_str(), self.count);
+        if let Some(h) = &self.hidden {
+            s.push_str(&format!(" hidden={h:?}"));
+        } else if !self.words.is_empty() {
+            s.push_str(&format!(" ({})", self.words.join(", ")));
+        } else if !self.codepoints.is_empty() {
+            s.push_str(&format!(" ({})", self.codepoints.join(" ")));
+        }
+        s
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind.as_str(),
+            "count": self.count,
+            "codepoints": self.codepoints,
+            "hidden": self.hidden,
+            "words": self.words,
+        })
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}'
+            ..='\u{2064}' | '\u{FEFF}' | '\u{180E}' | '\u{00AD}'
+    )
+}
+
+fn is_bidi(c: char) -> bool {
+    matches!(
+        c,
+        '\u{200E}' | '\u{200F}' | '\u{061C}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+fn is_tag(c: char) -> bool {
+    ('\u{E0000}'..='\u{E007F}').contains(&c)
+}
+
+fn is_variation_selector(c: char) -> bool {
+    matches!(c, '\u{FE00}'..='\u{FE0F}' | '\u{E0100}'..='\u{E01EF}')
+}
+
+/// Map a confusable letter (Cyrillic, Greek, fullwidth) to its ASCII look-alike.
+fn confusable(c: char) -> Option<char> {
+    let ascii = match c {
+        'а' => 'a',
+        'е' => 'e',
+        'о' => 'o',
+        'р' => 'p',
+        'с' => 'c',
+        'у' => 'y',
+        'х' => 'x',
+        'і' => 'i',
+        'ј' => 'j',
+        'ѕ' => 's',
+        'ԁ' => 'd',
+        'һ' => 'h',
+        'ӏ' => 'l',
+        'ԛ' => 'q',
+        'ԝ' => 'w',
+        'А' => 'A',
+        'В' => 'B',
+        'Е' => 'E',
+        'К' => 'K',
+        'М' => 'M',
+        'Н' => 'H',
+        'О' => 'O',
+        'Р' => 'P',
+        'С' => 'C',
+        'Т' => 'T',
+        'Х' => 'X',
+        'І' => 'I',
+        'Ј' => 'J',
+        'Ѕ' => 'S',
+        'У' => 'Y',
+        'α' => 'a',
+        'ο' => 'o',
+        'ν' => 'v',
+        'ρ' => 'p',
+        'ι' => 'i',
+        'κ' => 'k',
+        'υ' => 'u',
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Ζ' => 'Z',
+        'Η' => 'H',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Υ' => 'Y',
+        'Χ' => 'X',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0)?,
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// Emoji and pictographs legitimately use ZWJ / variation selectors.
+fn is_pictographic(c: char) -> bool {
+    matches!(c as u32, 0x2190..=0x2BFF | 0x1F000..=0x1FAFF)
+}
+
+/// Analyze `text`. `identifier` = tool / parameter name, where any non-ASCII
+/// look-alike is suspicious (in prose only mixed-script words are flagged).
+pub fn analyze(text: &str, identifier: bool) -> Vec<Issue> {
+    let mut zw = Issue::new(Kind::ZeroWidth);
+    let mut bidi = Issue::new(Kind::Bidi);
+    let mut tag = Issue::new(Kind::Tag);
+    let mut vs = Issue::new(Kind::VariationSelector);
+    let mut hidden = String::new();
+
+    let mut prev: Option<char> = None;
+    for c in text.chars() {
+        let after_pictograph = prev.is_some_and(|p| is_pictographic(p) || is_variation_selector(p));
+        if is_tag(c) {
+            tag.add_char(c);
+            let ascii = c as u32 - 0xE0000;
+            if (0x20..0x7F).contains(&ascii) {
+                hidden.push(ascii as u8 as char);
+            }
+        } else if is_bidi(c) {
+            bidi.add_char(c);
+        } else if is_zero_width(c) {
+            if !(c == '\u{200D}' && after_pictograph) {
+                zw.add_char(c);
+            }
+        } else if is_variation_selector(c) && !after_pictograph {
+            vs.add_char(c);
+        }
+        prev = Some(c);
+    }
+    if !hidden.is_empty() {
+        tag.hidden = Some(hidden);
+    }
+
+    let mut homo = Issue::new(Kind::Homoglyph);
+    for word in text.split(|c: char| !c.is_alphanumeric()) {
+        if word.is_empty() {
+            continue;
+        }
+        let has_ascii = word.chars().any(|c| c.is_ascii_alphabetic());
+        let confusables: Vec<char> = word.chars().filter(|c| confusable(*c).is_some()).collect();
+        if confusables.is_empty() {
+            continue;
+        }
+        let all_lookalike = word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || confusable(c).is_some());
+        if (has_ascii || identifier) && all_lookalike {
+            homo.count += 1;
+            for c in &confusables {
+                let cp = format!("U+{:04X}", *c as u32);
+                if homo.codepoints.len() < 5 && !homo.codepoints.contains(&cp) {
+                    homo.codepoints.push(cp);
+                }
+            }
+            if homo.words.len() < 5 {
+                homo.words.push(format!("{word}→{}", skeleton(word)));
+            }
+        }
+    }
+
+    [zw, bidi, tag, vs, homo]
+        .into_iter()
+        .filter(|i| i.count > 0)
+        .collect()
+}
+
+/// ASCII skeleton: confusables mapped to ASCII, invisible characters removed.
+pub fn skeleton(text: &str) -> String {
+    text.chars()
+        .filter(|c| !(is_zero_width(*c) || is_bidi(*c) || is_tag(*c) || is_variation_selector(*c)))
+        .map(|c| confusable(c).unwrap_or(c))
+        .collect()
+}
+
+/// Render invisible / control characters as `<U+XXXX>` so they show up in terminals.
+pub fn reveal(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if is_zero_width(c) || is_bidi(c) || is_tag(c) || is_variation_selector(c) {
+            out.push_str(&format!("<U+{:04X}>", c as u32));
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Analyze every human-reviewed string of a tool object.
+/// Returns (location, issue) pairs; location is "name", "description",
+/// "param:<name>" or "param:<name>.description".
+pub fn tool_issues(tool: &Value) -> Vec<(String, Issue)> {
+    let mut out = Vec::new();
+    let mut push = |loc: String, text: &str, identifier: bool| {
+        for i in analyze(text, identifier) {
+            out.push((loc.clone(), i));
+        }
+    };
+    if let Some(n) = tool.get("name").and_then(|v| v.as_str()) {
+        push("name".into(), n, true);
+    }
+    if let Some(d) = tool.get("description").and_then(|v| v.as_str()) {
+        push("description".into(), d, false);
+    }
+    if let Some(props) = tool
+        .get("inputSchema")
+        .or_else(|| tool.get("input_schema"))
+        .and_then(|s| s.get("properties"))
+        .and_then(|v| v.as_object())
+    {
+        for (pname, p) in props {
+            push(format!("param:{pname}"), pname, true);
+            if let Some(d) = p.get("description").and_then(|v| v.as_str()) {
+                push(format!("param:{pname}.description"), d, false);
+            }
+        }
+    }
+    out
+}
+
+/// JSON array for embedding in list / get output.
+pub fn issues_json(issues: &[(String, Issue)]) -> Value {
+    Value::Array(
+        issues
+            .iter()
+            .map(|(loc, i)| {
+                let mut v = i.to_json();
+                v["location"] = json!(loc);
+                v
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_zero_width_and_bidi() {
+        let issues = analyze("read\u{200B}file \u{202E}txt.exe", false);
+        let kinds: Vec<Kind> = issues.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![Kind::ZeroWidth, Kind::Bidi]);
+    }
+
+    #[test]
+    fn decodes_tag_characters() {
+        let smuggled: String = "hi"
+            .chars()
+            .map(|c| char::from_u32(0xE0000 + c as u32).unwrap())
+            .collect();
+        let issues = analyze(&format!("Adds numbers{smuggled}"), false);
+        assert_eq!(issues[0].kind, Kind::Tag);
+        assert_eq!(issues[0].hidden.as_deref(), Some("hi"));
+    }
+
+    #[test]
+    fn homoglyph_mixed_script_only_in_prose() {
+        // Cyrillic 'а' inside a Latin word
+        let issues = analyze("send to pаypal", false);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, Kind::Homoglyph);
+        assert_eq!(issues[0].words, vec!["pаypal→paypal".to_string()]);
+        // Plain Russian prose is fine; a pure-Cyrillic identifier is not.
+        assert!(analyze("сор", false).is_empty());
+        assert_eq!(analyze("ехес", true).len(), 1);
+    }
+
+    #[test]
+    fn emoji_sequences_are_not_flagged() {
+        assert!(analyze("done ❤️ 👩\u{200D}💻", false).is_empty());
+    }
+
+    #[test]
+    fn reveal_and_skeleton() {
+        assert_eq!(reveal("a\u{200B}b"), "a<U+200B>b");
+        assert_eq!(skeleton("ехес\u{200B}"), "exec");
+    }
+}
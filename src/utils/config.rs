@@ -0,0 +1,442 @@
+//! User configuration file (`config.toml`).
+//!
+//! Location: `MCP_HACK_CONFIG`, else `<config_dir>/config.toml`
+//! (`$XDG_CONFIG_HOME/mcp-hack` or `~/.config/mcp-hack`). A missing file is
+//! an empty configuration.
+//!
+//! ```toml
+//! target = "npx -y @modelcontextprotocol/server-everything"
+//! timeout = 60                 # seconds, every command's --timeout
+//! format = "json"              # any --format value, where supported
+//! wordlist = "/usr/share/wordlists/mcp.txt"
+//! danger_patterns = ["delete*", "*drop*"]   # exec asks before calling these
+//!
+//! [headers]                    # -H defaults for remote transports
+//! Authorization = "Bearer ..."
+//!
+//! [targets.everything]         # named target, used as `-t @everything`
+//! command = "npx -y @modelcontextprotocol/server-everything"
+//! env = { DEBUG = "1" }        # set for the spawned server
+//!
+//! [targets.prod]
+//! url = "https://mcp.example.com/mcp"
+//! bearer_env = "PROD_TOKEN"    # Authorization: Bearer $PROD_TOKEN
+//! headers = { X-Tenant = "acme" }
+//! ```
+//!
+//! Precedence: CLI flag > environment (MCP_TARGET) > config. Values become
+//! defaults of the parsed command line, so an explicit flag always wins.
+//!
+//! Only the TOML subset needed here is read: tables, dotted keys, basic /
+//! literal strings, integers, floats, booleans, arrays and inline tables.
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::cmd::output::OutputFormat;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub target: Option<String>,
+    pub timeout: Option<u64>,
+    pub format: Option<String>,
+    pub wordlist: Option<String>,
+    /// Tool name globs `exec` treats like destructiveHint tools
+    #[serde(default)]
+    pub danger_patterns: Vec<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub targets: BTreeMap<String, TargetProfile>,
+}
+
+/// A named target (`[targets.NAME]`).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TargetProfile {
+    /// Local server command line
+    pub command: Option<String>,
+    /// Remote server URL
+    pub url: Option<String>,
+    /// Environment variable holding a bearer token
+    pub bearer_env: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Environment variables for the spawned server
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl TargetProfile {
+    /// Target string (command line or URL).
+    pub fn target(&self) -> &str {
+        self.command
+            .as_deref()
+            .or(self.url.as_deref())
+            .unwrap_or("")
+    }
+
+    /// Headers as KEY=VALUE, with the bearer token (when set) first.
+    pub fn header_args(&self) -> Result<Vec<String>> {
+        let mut out = Vec::new();
+        if let Some(var) = &self.bearer_env {
+            let token = std::env::var(var)
+                .ok()
+                .filter(|t| !t.trim().is_empty())
+                .with_context(|| format!("bearer token variable {var} is not set"))?;
+            out.push(format!("Authorization=Bearer {}", token.trim()));
+        }
+        out.extend(self.headers.iter().map(|(k, v)| format!("{k}={v}")));
+        Ok(out)
+    }
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Location of the configuration file.
+pub fn path() -> PathBuf {
+    match std::env::var("MCP_HACK_CONFIG") {
+        Ok(p) if !p.trim().is_empty() => PathBuf::from(p),
+        _ => super::config_dir().join("config.toml"),
+    }
+}
+
+/// Parse configuration TOML.
+pub fn parse(text: &str) -> Result<Config> {
+    let doc = parse_toml(text)?;
+    let config: Config = serde_json::from_value(doc).context("invalid configuration")?;
+    if let Some(f) = &config.format
+        && OutputFormat::from_str(f, true).is_err()
+    {
+        let names: Vec<&str> = OutputFormat::value_variants()
+            .iter()
+            .map(|v| v.as_str())
+            .collect();
+        bail!("unknown format '{f}' (expected {})", names.join("|"));
+    }
+    for (name, t) in &config.targets {
+        if t.command.is_some() == t.url.is_some() {
+            bail!("targets.{name}: needs exactly one of command / url");
+        }
+    }
+    Ok(config)
+}
+
+/// Load the configuration file once; later calls return the same value.
+pub fn load() -> Result<&'static Config> {
+    if let Some(c) = CONFIG.get() {
+        return Ok(c);
+    }
+    let path = path();
+    let config = match std::fs::read_to_string(&path) {
+        Ok(text) => parse(&text).with_context(|| format!("{}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+        Err(e) => return Err(e).with_context(|| format!("failed to read {}", path.display())),
+    };
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded configuration (empty before / without `load`).
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/* ---- TOML subset ---- */
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    /// Skip spaces / tabs (and newlines and comments when `lines`).
+    fn skip(&mut self, lines: bool) {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {}
+                '\n' if lines => {}
+                '#' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                    continue;
+                }
+                _ => break,
+            }
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, want: char) -> Result<()> {
+        match self.bump() {
+            Some(c) if c == want => Ok(()),
+            Some(c) => bail!("line {}: expected '{want}', found '{c}'", self.line),
+            None => bail!("line {}: expected '{want}', found end of file", self.line),
+        }
+    }
+
+    /// Key segments of `a.b."c d"`.
+    fn key(&mut self) -> Result<Vec<String>> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip(false);
+            let part = match self.peek() {
+                Some('"') | Some('\'') => self.string()?,
+                _ => {
+                    let start = self.pos;
+                    while self
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        bail!("line {}: expected a key", self.line);
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip(false);
+            if self.peek() == Some('.') {
+                self.bump();
+            } else {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let quote = self.bump().unwrap_or('"');
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => bail!("line {}: unterminated string", self.line),
+                Some(c) if c == quote => return Ok(out),
+                Some('\\') if quote == '"' => {
+                    let c = self.bump().unwrap_or(' ');
+                    match c {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        '"' | '\\' => out.push(c),
+                        'u' => {
+                            let hex: String = (0..4).filter_map(|_| self.bump()).collect();
+                            let ch = u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .with_context(|| format!("line {}: bad \\u escape", self.line))?;
+                            out.push(ch);
+                        }
+                        other => bail!("line {}: unknown escape '\\{other}'", self.line),
+                    }
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        self.skip(false);
+        match self.peek() {
+            Some('"') | Some('\'') => Ok(Value::String(self.string()?)),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip(true);
+                    if self.peek() == Some(']') {
+                        self.bump();
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip(true);
+                    match self.peek() {
+                        Some(',') => {
+                            self.bump();
+                        }
+                        Some(']') => {}
+                        _ => bail!("line {}: expected ',' or ']' in array", self.line),
+                    }
+                }
+            }
+            Some('{') => {
+                self.bump();
+                let mut table = Map::new();
+                self.skip(false);
+                if self.peek() == Some('}') {
+                    self.bump();
+                    return Ok(Value::Object(table));
+                }
+                loop {
+                    let key = self.key()?;
+                    self.expect('=')?;
+                    let v = self.value()?;
+                    insert(&mut table, &key, v, self.line)?;
+                    self.skip(false);
+                    match self.bump() {
+                        Some(',') => {}
+                        Some('}') => return Ok(Value::Object(table)),
+                        _ => bail!("line {}: expected ',' or '}}' in inline table", self.line),
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| !matches!(c, ',' | ']' | '}' | '#' | '\n' | ' ' | '\t' | '\r'))
+                {
+                    self.pos += 1;
+                }
+                let raw: String = self.chars[start..self.pos].iter().collect();
+                let clean = raw.replace('_', "");
+                if let Ok(i) = clean.parse::<i64>() {
+                    return Ok(Value::from(i));
+                }
+                if let Ok(f) = clean.parse::<f64>() {
+                    return Ok(Value::from(f));
+                }
+                match raw.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "" => bail!("line {}: expected a value", self.line),
+                    _ => bail!("line {}: unsupported value '{raw}'", self.line),
+                }
+            }
+        }
+    }
+}
+
+/// Insert `value` at the dotted `key` below `table`.
+fn insert(table: &mut Map<String, Value>, key: &[String], value: Value, line: usize) -> Result<()> {
+    let (last, parents) = key.split_last().context("empty key")?;
+    let mut cur = table;
+    for part in parents {
+        let entry = cur
+            .entry(part.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        cur = entry
+            .as_object_mut()
+            .with_context(|| format!("line {line}: '{part}' is not a table"))?;
+    }
+    if cur.contains_key(last) {
+        bail!("line {line}: duplicate key '{}'", key.join("."));
+    }
+    cur.insert(last.clone(), value);
+    Ok(())
+}
+
+/// Parse TOML (the subset described above) into JSON.
+pub fn parse_toml(text: &str) -> Result<Value> {
+    let mut p = Parser {
+        chars: text.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut root = Map::new();
+    let mut current: Vec<String> = Vec::new();
+    loop {
+        p.skip(true);
+        let Some(c) = p.peek() else { break };
+        if c == '[' {
+            p.bump();
+            if p.peek() == Some('[') {
+                bail!("line {}: arrays of tables are not supported", p.line);
+            }
+            current = p.key()?;
+            p.expect(']')?;
+            let mut cur = &mut root;
+            for part in &current {
+                cur = cur
+                    .entry(part.clone())
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .with_context(|| format!("line {}: '{part}' is not a table", p.line))?;
+            }
+        } else {
+            let key = p.key()?;
+            p.expect('=')?;
+            let value = p.value()?;
+            let full: Vec<String> = current.iter().chain(&key).cloned().collect();
+            insert(&mut root, &full, value, p.line)?;
+        }
+        p.skip(false);
+        match p.bump() {
+            None | Some('\n') => {}
+            Some(c) => bail!("line {}: unexpected '{c}' after value", p.line),
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_config_subset() {
+        let doc = parse_toml(
+            r#"
+# defaults
+target = "npx -y server"   # trailing comment
+timeout = 1_000
+ratio = 0.5
+tags = ["a", 'b\n',
+  "c",]
+inline = { x = 1, y.z = true }
+
+[headers]
+"X-Key" = "v\"1"
+
+[targets.fs]
+command = 'python3 s.py'
+"#,
+        )
+        .unwrap();
+        assert_eq!(doc["timeout"], 1000);
+        assert_eq!(doc["tags"], json!(["a", "b\\n", "c"]));
+        assert_eq!(doc["inline"]["y"]["z"], true);
+        assert_eq!(doc["headers"]["X-Key"], "v\"1");
+        assert_eq!(doc["targets"]["fs"]["command"], "python3 s.py");
+        assert!(parse_toml("a = 1\na = 2").is_err());
+        assert!(parse_toml("a = nope").is_err());
+
+        let c = parse("target = \"x\"\nformat = \"json\"\n[headers]\nA = \"b\"").unwrap();
+        assert_eq!(c.target.as_deref(), Some("x"));
+        assert_eq!(c.headers["A"], "b");
+        assert!(parse("format = \"xml\"").is_err());
+        for f in ["yaml", "markdown", "csv", "table"] {
+            assert!(parse(&format!("format = \"{f}\"")).is_ok(), "{f}");
+        }
+        assert!(parse("colour = true").is_err());
+
+        let c =
+            parse("[targets.prod]\nurl = \"https://h/mcp\"\nheaders = { X-T = \"a\" }").unwrap();
+        let prod = &c.targets["prod"];
+        assert_eq!(prod.target(), "https://h/mcp");
+        assert_eq!(prod.header_args().unwrap(), vec!["X-T=a"]);
+        assert!(parse("[targets.x]\nenv = { A = \"1\" }").is_err());
+    }
+}
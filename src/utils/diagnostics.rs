@@ -0,0 +1,380 @@
+//! Console diagnostics built on `tracing`.
+//!
+//! Log lines (crate::utils::logging, rmcp's own events) and spans go to
+//! stderr, filtered like `RUST_LOG`:
+//!
+//! ```text
+//! RUST_LOG=debug                        everything at debug and above
+//! RUST_LOG=mcp_hack=trace,rmcp=debug    per target (longest prefix wins)
+//! RUST_LOG=off
+//! ```
+//!
+//! Without RUST_LOG the filter follows -q / -v / -vv: errors only, info,
+//! debug (adds the connect / initialize / shutdown spans) and trace (adds a
+//! span per request, e.g. `request{method=tools/call}`, and rmcp's debug
+//! events). A span prints one line when it ends, with its duration, so a
+//! slow or failing phase shows up with its context:
+//!
+//! ```text
+//! [DEBUG][1735689600090] server{target=python3 server.py}:initialize: done in 86.2ms
+//! [TRACE][1735689600131] request{method=tools/call}: tools/call (--request-timeout) timed out after 5s
+//! ```
+//!
+//! Events are also written to --log-file (crate::utils::logging::event),
+//! independently of this filter.
+
+use anyhow::{Result, bail};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+use super::logging::{self, LogLevel};
+
+/// RUST_LOG-style filter: `[target=]level` directives, comma separated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    /// (target prefix, most verbose level shown; None = off)
+    directives: Vec<(String, Option<Level>)>,
+    default: Option<Level>,
+}
+
+impl Filter {
+    pub fn parse(spec: &str) -> Result<Filter> {
+        let mut filter = Filter {
+            directives: Vec::new(),
+            default: None,
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            if directive.contains('[') {
+                bail!("span field filters are not supported: '{directive}'");
+            }
+            match directive.split_once('=') {
+                Some((target, level)) => filter
+                    .directives
+                    .push((target.trim().to_string(), parse_level(level)?)),
+                None => match parse_level(directive) {
+                    Ok(level) => filter.default = level,
+                    // A bare target enables everything under it.
+                    Err(_) => filter
+                        .directives
+                        .push((directive.to_string(), Some(Level::TRACE))),
+                },
+            }
+        }
+        // Longest prefix first.
+        filter
+            .directives
+            .sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Ok(filter)
+    }
+
+    /// The filter used without RUST_LOG.
+    pub fn for_level(level: LogLevel) -> Filter {
+        let spec = match level {
+            LogLevel::Error => "mcp_hack=error",
+            LogLevel::Info => "mcp_hack=info",
+            LogLevel::Debug => "mcp_hack=debug",
+            LogLevel::Trace => "mcp_hack=trace,rmcp=debug",
+        };
+        Filter::parse(spec).expect("built-in filter")
+    }
+
+    pub fn enabled(&self, target: &str, level: &Level) -> bool {
+        let max = self
+            .directives
+            .iter()
+            .find(|(prefix, _)| {
+                target
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map(|(_, max)| *max)
+            .unwrap_or(self.default);
+        // Level ordering: TRACE > DEBUG > ... > ERROR (more verbose is greater).
+        max.is_some_and(|max| *level <= max)
+    }
+}
+
+fn parse_level(s: &str) -> Result<Option<Level>> {
+    Ok(Some(match s.trim().to_ascii_lowercase().as_str() {
+        "off" => return Ok(None),
+        "error" => Level::ERROR,
+        "warn" => Level::WARN,
+        "info" => Level::INFO,
+        "debug" => Level::DEBUG,
+        "trace" => Level::TRACE,
+        other => bail!("unknown level '{other}'"),
+    }))
+}
+
+/// Install the stderr subscriber: RUST_LOG when set, else `level` (-q / -v).
+pub fn init(level: LogLevel) -> Result<()> {
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(spec) if !spec.trim().is_empty() => Filter::parse(&spec)?,
+        _ => Filter::for_level(level),
+    };
+    let subscriber = Diagnostics {
+        filter,
+        spans: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+    };
+    // A second init (tests) keeps the first subscriber.
+    let _ = tracing::subscriber::set_global_default(subscriber);
+    Ok(())
+}
+
+struct SpanData {
+    meta: &'static Metadata<'static>,
+    fields: String,
+    parent: Option<Id>,
+    opened: Instant,
+    refs: usize,
+}
+
+struct Diagnostics {
+    filter: Filter,
+    spans: Mutex<HashMap<u64, SpanData>>,
+    next_id: AtomicU64,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last.
+    static STACK: RefCell<Vec<Id>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Diagnostics {
+    fn shown(&self, meta: &Metadata<'_>) -> bool {
+        self.filter.enabled(meta.target(), meta.level())
+    }
+
+    /// `outer{fields}:inner{fields}` for the span `id` and its parents.
+    fn scope(&self, id: Option<Id>) -> String {
+        let Ok(spans) = self.spans.lock() else {
+            return String::new();
+        };
+        let mut names = Vec::new();
+        let mut next = id;
+        while let Some(span) = next.and_then(|id| spans.get(&id.into_u64())) {
+            if span.fields.is_empty() {
+                names.push(span.meta.name().to_string());
+            } else {
+                names.push(format!("{}{{{}}}", span.meta.name(), span.fields));
+            }
+            next = span.parent.clone();
+        }
+        names.reverse();
+        names.join(":")
+    }
+
+    fn print(&self, level: &Level, target: &str, scope: &str, text: &str) {
+        let mut line = format!("[{}][{}] ", level.as_str(), now_ms());
+        if !target.starts_with("mcp_hack") {
+            let _ = write!(line, "{target}: ");
+        }
+        if !scope.is_empty() {
+            let _ = write!(line, "{scope}: ");
+        }
+        line.push_str(text);
+        eprintln!("{line}");
+    }
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
+
+fn current() -> Option<Id> {
+    STACK.with(|s| s.borrow().last().cloned())
+}
+
+/// Collects `message` and the other fields as `key=value ...`.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            if !self.rest.is_empty() {
+                self.rest.push(' ');
+            }
+            let _ = write!(self.rest, "{}={value:?}", field.name());
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_debug(field, &format_args!("{value}"));
+    }
+}
+
+impl Subscriber for Diagnostics {
+    fn enabled(&self, meta: &Metadata<'_>) -> bool {
+        self.shown(meta)
+            || (meta.is_event()
+                && logging::file_enabled()
+                && meta.target().starts_with("mcp_hack")
+                && *meta.level() <= Level::INFO)
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        let parent = if attrs.is_contextual() {
+            current()
+        } else {
+            attrs.parent().cloned()
+        };
+        // A span keeps its parent open, so its scope stays printable.
+        let parent = parent.map(|p| self.clone_span(&p));
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut spans) = self.spans.lock() {
+            spans.insert(
+                id,
+                SpanData {
+                    meta: attrs.metadata(),
+                    fields: fields.rest,
+                    parent,
+                    opened: Instant::now(),
+                    refs: 1,
+                },
+            );
+        }
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if let Ok(mut spans) = self.spans.lock()
+            && let Some(data) = spans.get_mut(&span.into_u64())
+            && !fields.rest.is_empty()
+        {
+            if !data.fields.is_empty() {
+                data.fields.push(' ');
+            }
+            data.fields.push_str(&fields.rest);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let meta = event.metadata();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let mut text = fields.message;
+        if !fields.rest.is_empty() {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&fields.rest);
+        }
+        let parent = if event.is_contextual() {
+            current()
+        } else {
+            event.parent().cloned()
+        };
+        let scope = self.scope(parent);
+        if self.shown(meta) {
+            self.print(meta.level(), meta.target(), &scope, &text);
+        }
+        if meta.target().starts_with("mcp_hack") {
+            let level = match *meta.level() {
+                Level::ERROR | Level::WARN => LogLevel::Error,
+                Level::INFO => LogLevel::Info,
+                Level::DEBUG => LogLevel::Debug,
+                _ => LogLevel::Trace,
+            };
+            logging::event(
+                level,
+                "message",
+                serde_json::json!({ "message": text, "span": (!scope.is_empty()).then_some(scope) }),
+            );
+        }
+    }
+
+    fn enter(&self, span: &Id) {
+        STACK.with(|s| s.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &Id) {
+        STACK.with(|s| {
+            let mut stack = s.borrow_mut();
+            if let Some(i) = stack.iter().rposition(|id| id == span) {
+                stack.remove(i);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Ok(mut spans) = self.spans.lock()
+            && let Some(data) = spans.get_mut(&span.into_u64())
+        {
+            data.refs += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let closed = {
+            let Ok(mut spans) = self.spans.lock() else {
+                return false;
+            };
+            match spans.get_mut(&span.into_u64()) {
+                Some(data) if data.refs > 1 => {
+                    data.refs -= 1;
+                    return false;
+                }
+                Some(data) => (data.meta, data.opened.elapsed(), data.parent.clone()),
+                None => return false,
+            }
+        };
+        let (meta, elapsed, parent) = closed;
+        if self.shown(meta) {
+            let scope = self.scope(Some(span.clone()));
+            let text = format!("done in {:.1}ms", elapsed.as_secs_f64() * 1000.0);
+            self.print(meta.level(), meta.target(), &scope, &text);
+        }
+        if let Ok(mut spans) = self.spans.lock() {
+            spans.remove(&span.into_u64());
+        }
+        if let Some(parent) = parent {
+            self.try_close(parent);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rust_log_filters() {
+        let f = Filter::parse("warn, mcp_hack=trace, mcp_hack::mcp::raw=off,rmcp").unwrap();
+        assert!(f.enabled("mcp_hack::cmd::exec", &Level::TRACE));
+        assert!(!f.enabled("mcp_hack::mcp::raw", &Level::ERROR));
+        assert!(f.enabled("rmcp::service", &Level::DEBUG));
+        assert!(f.enabled("tokio", &Level::WARN));
+        assert!(!f.enabled("tokio", &Level::INFO));
+        assert!(!f.enabled("mcp_hackish", &Level::TRACE));
+        let quiet = Filter::for_level(LogLevel::Error);
+        assert!(!quiet.enabled("mcp_hack", &Level::INFO));
+        assert!(!quiet.enabled("rmcp", &Level::ERROR));
+        assert!(Filter::parse("mcp_hack=loud").is_err());
+        assert!(Filter::parse("mcp_hack[request]=debug").is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! A JSONPath subset with an optional comparison, for `exec --expect-jsonpath`
+//! (and bare paths for `exec --extract`).
+//!
+//! ```text
+//! $.structuredContent.count >= 1     member access, then compare
+//! $.content[0].text == "ok"          array index (negative counts from the end)
+//! $['odd key'][*].id != null         quoted member, wildcard
+//! $.structuredContent.items          no operator: exists and is not null / false
+//! ```
+//!
+//! Operators: `== != < <= > >=`. The right-hand side is a JSON literal
+//! (`1`, `"x"`, `true`, `null`, `[1,2]`); anything that does not parse as JSON
+//! is taken as a string, so `$.status == ok` works too. Numbers compare by
+//! value (1 == 1.0), `< <= > >=` apply to two numbers or two strings. With a
+//! wildcard the expression holds when any selected value satisfies it.
+//! Recursive descent (`..`) and filters (`[?()]`) are not supported.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    fn as_str(self) -> &'static str {
+        match self {
+            Op::Eq => "==",
+            Op::Ne => "!=",
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+        }
+    }
+
+    fn holds(self, left: &Value, right: &Value) -> bool {
+        match self {
+            Op::Eq => same(left, right),
+            Op::Ne => !same(left, right),
+            _ => {
+                let ord = match (left, right) {
+                    (Value::Number(a), Value::Number(b)) => a
+                        .as_f64()
+                        .zip(b.as_f64())
+                        .and_then(|(a, b)| a.partial_cmp(&b)),
+                    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                    _ => None,
+                };
+                match (self, ord) {
+                    (_, None) => false,
+                    (Op::Lt, Some(o)) => o == Ordering::Less,
+                    (Op::Le, Some(o)) => o != Ordering::Greater,
+                    (Op::Gt, Some(o)) => o == Ordering::Greater,
+                    (_, Some(o)) => o != Ordering::Less,
+                }
+            }
+        }
+    }
+}
+
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// A parsed path (`$.a[0]['b']`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path(Vec<Segment>);
+
+/// A parsed `PATH [OP VALUE]` expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    path: Path,
+    compare: Option<(Op, Value)>,
+}
+
+fn parse_path(chars: &[char], pos: &mut usize) -> Result<Path> {
+    if chars.first() != Some(&'$') {
+        bail!("a JSONPath starts with $");
+    }
+    *pos = 1;
+    let mut path = Vec::new();
+    while let Some(&c) = chars.get(*pos) {
+        match c {
+            '.' => {
+                *pos += 1;
+                if chars.get(*pos) == Some(&'.') {
+                    bail!("recursive descent (..) is not supported");
+                }
+                let start = *pos;
+                while chars
+                    .get(*pos)
+                    .is_some_and(|&c| !matches!(c, '.' | '[') && !ends_path(c))
+                {
+                    *pos += 1;
+                }
+                let name: String = chars[start..*pos].iter().collect();
+                match name.as_str() {
+                    "" => bail!("empty member name at offset {start}"),
+                    "*" => path.push(Segment::Wildcard),
+                    _ => path.push(Segment::Key(name)),
+                }
+            }
+            '[' => {
+                *pos += 1;
+                let close = chars[*pos..]
+                    .iter()
+                    .position(|&c| c == ']')
+                    .map(|i| *pos + i);
+                let Some(close) = close else {
+                    bail!("unclosed [ at offset {}", *pos - 1);
+                };
+                let inner: String = chars[*pos..close].iter().collect();
+                let inner = inner.trim();
+                let quoted = inner.len() >= 2
+                    && ((inner.starts_with('\'') && inner.ends_with('\''))
+                        || (inner.starts_with('"') && inner.ends_with('"')));
+                if inner == "*" {
+                    path.push(Segment::Wildcard);
+                } else if quoted {
+                    path.push(Segment::Key(inner[1..inner.len() - 1].to_string()));
+                } else if let Ok(i) = inner.parse::<i64>() {
+                    path.push(Segment::Index(i));
+                } else {
+                    bail!("unsupported selector [{inner}] (use [N], [*] or ['name'])");
+                }
+                *pos = close + 1;
+            }
+            c if ends_path(c) => break,
+            c => bail!("unexpected '{c}' at offset {}", *pos),
+        }
+    }
+    Ok(Path(path))
+}
+
+/// Whitespace or the start of an operator.
+fn ends_path(c: char) -> bool {
+    matches!(c, '=' | '!' | '<' | '>') || c.is_whitespace()
+}
+
+impl Path {
+    pub fn parse(text: &str) -> Result<Path> {
+        let chars: Vec<char> = text.trim().chars().collect();
+        let mut pos = 0;
+        let path = parse_path(&chars, &mut pos)?;
+        if pos < chars.len() {
+            bail!(
+                "unexpected '{}' after the path",
+                chars[pos..].iter().collect::<String>()
+            );
+        }
+        Ok(path)
+    }
+
+    /// Values the path selects in `root`.
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for segment in &self.0 {
+            current = current
+                .into_iter()
+                .flat_map(|v| -> Vec<&'a Value> {
+                    match (segment, v) {
+                        (Segment::Key(k), Value::Object(map)) => map.get(k).into_iter().collect(),
+                        (Segment::Index(i), Value::Array(items)) => {
+                            let i = if *i < 0 { items.len() as i64 + i } else { *i };
+                            usize::try_from(i)
+                                .ok()
+                                .and_then(|i| items.get(i))
+                                .into_iter()
+                                .collect()
+                        }
+                        (Segment::Wildcard, Value::Array(items)) => items.iter().collect(),
+                        (Segment::Wildcard, Value::Object(map)) => map.values().collect(),
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+impl Expr {
+    pub fn parse(text: &str) -> Result<Expr> {
+        let chars: Vec<char> = text.trim().chars().collect();
+        let mut pos = 0;
+        let path = parse_path(&chars, &mut pos)?;
+        let rest: String = chars[pos..].iter().collect();
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Ok(Expr {
+                path,
+                compare: None,
+            });
+        }
+        let op = [Op::Eq, Op::Ne, Op::Le, Op::Ge, Op::Lt, Op::Gt]
+            .into_iter()
+            .find(|op| rest.starts_with(op.as_str()));
+        let Some(op) = op else {
+            bail!("expected one of == != < <= > >= after the path, got '{rest}'");
+        };
+        let literal = rest[op.as_str().len()..].trim();
+        if literal.is_empty() {
+            bail!("missing value after {}", op.as_str());
+        }
+        let value = serde_json::from_str(literal).unwrap_or_else(|_| {
+            let unquoted = literal
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .unwrap_or(literal);
+            Value::String(unquoted.to_string())
+        });
+        Ok(Expr {
+            path,
+            compare: Some((op, value)),
+        })
+    }
+
+    /// Evaluate against `root`; Err carries what was found instead.
+    pub fn evaluate(&self, root: &Value) -> Result<(), String> {
+        let selected = self.path.select(root);
+        if selected.is_empty() {
+            return Err("path matched nothing".to_string());
+        }
+        let holds = |v: &&Value| match &self.compare {
+            Some((op, want)) => op.holds(v, want),
+            None => !matches!(v, Value::Null | Value::Bool(false)),
+        };
+        if selected.iter().any(holds) {
+            return Ok(());
+        }
+        let found: Vec<String> = selected
+            .iter()
+            .take(3)
+            .map(|v| {
+                let s = v.to_string();
+                match s.char_indices().nth(60) {
+                    Some((i, _)) => format!("{}…", &s[..i]),
+                    None => s,
+                }
+            })
+            .collect();
+        Err(format!("got {}", found.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn selects_and_compares() {
+        let doc = json!({
+            "content": [{"type": "text", "text": "ok"}, {"type": "text", "text": "two"}],
+            "structuredContent": {"count": 3, "items": [{"id": 1}, {"id": 2}], "odd key": "x"},
+            "isError": false,
+        });
+        let holds = |e: &str| Expr::parse(e).unwrap().evaluate(&doc).is_ok();
+        assert!(holds("$.content[0].text == \"ok\""));
+        assert!(holds("$.content[-1].text == two"));
+        assert!(holds("$.structuredContent.count >= 3"));
+        assert!(holds("$.structuredContent.count == 3.0"));
+        assert!(!holds("$.structuredContent.count < 3"));
+        assert!(holds("$.structuredContent.items[*].id == 2"));
+        assert!(holds("$['structuredContent']['odd key'] == 'x'"));
+        assert!(holds("$.isError == false"));
+        assert!(holds("$.structuredContent.items"));
+        assert!(!holds("$.isError"));
+        assert_eq!(
+            Expr::parse("$.missing").unwrap().evaluate(&doc),
+            Err("path matched nothing".to_string())
+        );
+        assert_eq!(
+            Expr::parse("$.structuredContent.count==1")
+                .unwrap()
+                .evaluate(&doc),
+            Err("got 3".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_bare_paths() {
+        let doc = json!({"content": [{"text": "a"}, {"text": "b"}]});
+        let path = Path::parse("$.content[*].text").unwrap();
+        assert_eq!(path.select(&doc), [&json!("a"), &json!("b")]);
+        assert!(Path::parse("$.content == 1").is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_syntax() {
+        for bad in [
+            "count",
+            "$..id",
+            "$.a[?(@.x)]",
+            "$.a ~ 1",
+            "$.a ==",
+            "$.a[0",
+        ] {
+            assert!(Expr::parse(bad).is_err(), "{bad}");
+        }
+    }
+}
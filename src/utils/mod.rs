@@ -1,19 +1,40 @@
-//! Utilities: logging (dynamic level), minimal JSON string helpers, ANSI color (respects NO_COLOR),
+//! Utilities: logging (dynamic level, `tracing` diagnostics), minimal JSON string helpers, ANSI color (respects NO_COLOR),
 //! progress tracking, monotonic timing, simple error context trait.
 //!
 //! Key items:
-//!   init_logging / derive_level
+//!   init_logging / derive_level, logging::init_file (--log-file)
+//!   diagnostics (tracing subscriber: spans on stderr, RUST_LOG filters)
 //!   output::* (json_escape etc.)
 //!   monotonic_ms
 //!   Progress / ProgressSnapshot
+//!   progress_events::* (NDJSON progress for embedding UIs)
+//!   stable_hash / sha256_hex / utc_timestamp
+//!   pattern (--filter globs and /regex/)
+//!   jsonpath (JSONPath subset for exec --expect-jsonpath)
+//!   query (jq-like filters for --query)
+//!   parse_duration / expand_env (`${VAR}` in user-supplied values)
+//!   base64_encode / base64_decode (binary tool result content)
+//!   state_dir (persistent per-user state: pins, last target, ...)
+//!   config_dir / config (user configuration file, see config.rs)
+//!   cache_dir (disposable per-user data: listing cache)
+//!
+//! This is a small helper toolkit; not every helper has a command wired to it yet.
+#![allow(dead_code)]
 
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicU8, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod config;
+pub mod diagnostics;
+pub mod jsonpath;
+pub mod pattern;
+pub mod query;
+
 /// Logging helpers.
 pub mod logging {
     use super::*;
+    use std::io::Write;
 
     #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
     pub enum LogLevel {
@@ -68,20 +89,128 @@ pub mod logging {
         }
     }
 
-    fn timestamp() -> u128 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis())
-            .unwrap_or(0)
+    /// Log a message through `tracing` (stderr, see crate::utils::diagnostics;
+    /// also written to --log-file).
+    pub fn log(level: LogLevel, msg: impl AsRef<str>) {
+        let msg = msg.as_ref();
+        match level {
+            LogLevel::Error => tracing::error!("{msg}"),
+            LogLevel::Info => tracing::info!("{msg}"),
+            LogLevel::Debug => tracing::debug!("{msg}"),
+            LogLevel::Trace => tracing::trace!("{msg}"),
+        }
     }
 
-    fn should_emit(level: LogLevel) -> bool {
-        level <= current_log_level()
+    /// Layout of `--log-file` lines (`--log-format`).
+    #[derive(clap::ValueEnum, Copy, Clone, Debug, Default, Eq, PartialEq)]
+    pub enum LogFormat {
+        /// `TS LEVEL EVENT key=value...`
+        #[default]
+        Text,
+        /// One JSON object per line (level, ts, event, target, elapsed_ms, ...)
+        Json,
     }
 
-    pub fn log(level: LogLevel, msg: impl AsRef<str>) {
-        if should_emit(level) {
-            println!("[{}][{}] {}", level.as_str(), timestamp(), msg.as_ref());
+    struct LogFile {
+        sink: std::sync::Mutex<Box<dyn std::io::Write + Send>>,
+        format: LogFormat,
+        started: std::time::Instant,
+    }
+
+    static FILE: OnceLock<LogFile> = OnceLock::new();
+    static TARGET: OnceLock<String> = OnceLock::new();
+
+    /// Also write log lines and structured events to `path` (appended, so
+    /// several runs, or the children of a fleet, can share one file).
+    /// -q only quiets the console: the file keeps at least info lines.
+    pub fn init_file(path: &str, format: LogFormat) -> anyhow::Result<()> {
+        use anyhow::Context;
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {path}"))?;
+        let _ = FILE.set(LogFile {
+            sink: std::sync::Mutex::new(Box::new(file)),
+            format,
+            started: std::time::Instant::now(),
+        });
+        Ok(())
+    }
+
+    pub fn file_enabled() -> bool {
+        FILE.get().is_some()
+    }
+
+    /// Target the command works on, added to every log file line.
+    pub fn set_target(target: &str) {
+        let _ = TARGET.set(target.to_string());
+    }
+
+    /// Write a structured event (`fields` is a JSON object) to the log file.
+    /// Events never reach the console.
+    pub fn event(level: LogLevel, event: &str, fields: serde_json::Value) {
+        let Some(file) = FILE.get() else {
+            return;
+        };
+        if level > current_log_level().max(LogLevel::Info) {
+            return;
+        }
+        let line = format_line(
+            file.format,
+            &super::utc_timestamp(),
+            level,
+            event,
+            TARGET.get().map(String::as_str),
+            file.started.elapsed().as_millis(),
+            &fields,
+        );
+        if let Ok(mut sink) = file.sink.lock() {
+            // One write per line keeps lines whole in a shared (appended) file.
+            let _ = sink
+                .write_all(format!("{line}\n").as_bytes())
+                .and_then(|_| sink.flush());
+        }
+    }
+
+    fn format_line(
+        format: LogFormat,
+        ts: &str,
+        level: LogLevel,
+        event: &str,
+        target: Option<&str>,
+        elapsed_ms: u128,
+        fields: &serde_json::Value,
+    ) -> String {
+        let extra = fields.as_object().into_iter().flatten();
+        match format {
+            LogFormat::Json => {
+                let mut line = serde_json::Map::new();
+                line.insert("ts".into(), ts.into());
+                line.insert("level".into(), level.as_str().to_lowercase().into());
+                line.insert("event".into(), event.into());
+                line.insert("target".into(), target.into());
+                line.insert("elapsed_ms".into(), (elapsed_ms as u64).into());
+                for (k, v) in extra {
+                    line.entry(k.clone()).or_insert_with(|| v.clone());
+                }
+                serde_json::Value::Object(line).to_string()
+            }
+            LogFormat::Text => {
+                let mut line =
+                    format!("{ts} {:<5} {event} elapsed_ms={elapsed_ms}", level.as_str());
+                if let Some(t) = target {
+                    line.push_str(&format!(" target={t:?}"));
+                }
+                for (k, v) in extra {
+                    match v {
+                        serde_json::Value::Null => continue,
+                        serde_json::Value::String(s) => line.push_str(&format!(" {k}={s:?}")),
+                        v => line.push_str(&format!(" {k}={v}")),
+                    }
+                }
+                line
+            }
         }
     }
 
@@ -114,6 +243,41 @@ pub mod logging {
     macro_rules! log_trace {
         ($($t:tt)*) => { $crate::utils::logging::trace(format!($($t)*)) };
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        #[test]
+        fn formats_log_file_lines() {
+            let fields = json!({"phase": "connect", "item": "srv x", "n": 2, "none": null});
+            assert_eq!(
+                format_line(
+                    LogFormat::Json,
+                    "T",
+                    LogLevel::Info,
+                    "phase",
+                    Some("srv"),
+                    12,
+                    &fields
+                ),
+                r#"{"elapsed_ms":12,"event":"phase","item":"srv x","level":"info","n":2,"none":null,"phase":"connect","target":"srv","ts":"T"}"#
+            );
+            assert_eq!(
+                format_line(
+                    LogFormat::Text,
+                    "T",
+                    LogLevel::Error,
+                    "finish",
+                    None,
+                    5,
+                    &fields
+                ),
+                r#"T ERROR finish elapsed_ms=5 item="srv x" n=2 phase="connect""#
+            );
+        }
+    }
 }
 
 pub use logging::{derive_level, init_logging};
@@ -212,6 +376,226 @@ pub fn monotonic_ms() -> u128 {
     base.elapsed().as_millis()
 }
 
+/// Pseudo-random alphanumeric token (NOT cryptographically secure).
+///
+/// Used for canary / probe values; seeded from wall clock, PID and a process-wide counter.
+pub fn random_token(len: usize) -> String {
+    use std::sync::atomic::AtomicU64;
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let mut state = nanos
+        ^ ((std::process::id() as u64) << 32)
+        ^ COUNTER
+            .fetch_add(1, Ordering::Relaxed)
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    if state == 0 {
+        state = 0x2545_F491_4F6C_DD1D;
+    }
+    let mut out = String::with_capacity(len);
+    for _ in 0..len {
+        // xorshift64*
+        state ^= state >> 12;
+        state ^= state << 25;
+        state ^= state >> 27;
+        let n = state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        out.push(ALPHABET[(n >> 58) as usize % ALPHABET.len()] as char);
+    }
+    out
+}
+
+/// Stable 64-bit FNV-1a digest rendered as 16 hex chars.
+///
+/// Deterministic across runs and platforms (unlike `DefaultHasher`), so it is
+/// safe for persisted fingerprints / dedup keys. NOT collision resistant
+/// against an adversary.
+pub fn stable_hash(data: &str) -> String {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in data.as_bytes() {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{h:016x}")
+}
+
+/// SHA-256 digest as lowercase hex (FIPS 180-4; small self-contained implementation).
+pub fn sha256_hex(data: &[u8]) -> String {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    let mut msg = data.to_vec();
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&bit_len.to_be_bytes());
+
+    for chunk in msg.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in chunk.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (slot, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *slot = slot.wrapping_add(v);
+        }
+    }
+    h.iter().map(|v| format!("{v:08x}")).collect()
+}
+
+/// Current UTC time as RFC 3339 with millisecond precision ("2025-01-31T12:00:00.000Z").
+pub fn utc_timestamp() -> String {
+    let d = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format_utc(d.as_secs() as i64, d.subsec_millis())
+}
+
+/// Format Unix seconds as RFC 3339 UTC (civil-from-days, proleptic Gregorian).
+pub fn format_utc(secs: i64, millis: u32) -> String {
+    let days = secs.div_euclid(86_400);
+    let tod = secs.rem_euclid(86_400);
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        tod / 3600,
+        (tod % 3600) / 60,
+        tod % 60
+    )
+}
+
+/// Parse a human duration: "500ms", "30s", "5m", "2h" (bare numbers are seconds).
+pub fn parse_duration(s: &str) -> anyhow::Result<std::time::Duration> {
+    use std::time::Duration;
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid duration '{s}' (expected e.g. 30s, 5m, 1h)"))?;
+    match unit.trim() {
+        "ms" => Ok(Duration::from_millis(n)),
+        "" | "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n * 60)),
+        "h" => Ok(Duration::from_secs(n * 3600)),
+        other => anyhow::bail!("invalid duration unit '{other}' in '{s}' (use ms, s, m or h)"),
+    }
+}
+
+/// Directory for persistent per-user state.
+///
+/// `MCP_HACK_STATE_DIR` > `$XDG_STATE_HOME/mcp-hack` > `~/.local/state/mcp-hack`.
+/// Not created here; writers create it on demand.
+pub fn state_dir() -> std::path::PathBuf {
+    use std::path::PathBuf;
+    if let Ok(dir) = std::env::var("MCP_HACK_STATE_DIR")
+        && !dir.trim().is_empty()
+    {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_STATE_HOME")
+        && !dir.trim().is_empty()
+    {
+        return PathBuf::from(dir).join("mcp-hack");
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".local/state/mcp-hack")
+}
+
+/// Directory for per-user configuration (config.toml, profiles.json).
+///
+/// `$XDG_CONFIG_HOME/mcp-hack` > `~/.config/mcp-hack`.
+pub fn config_dir() -> std::path::PathBuf {
+    use std::path::PathBuf;
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME")
+        && !dir.trim().is_empty()
+    {
+        return PathBuf::from(dir).join("mcp-hack");
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".config/mcp-hack")
+}
+
+/// Directory for disposable per-user data (the listing cache).
+///
+/// `MCP_HACK_CACHE_DIR` > `$XDG_CACHE_HOME/mcp-hack` > `~/.cache/mcp-hack`.
+pub fn cache_dir() -> std::path::PathBuf {
+    use std::path::PathBuf;
+    if let Ok(dir) = std::env::var("MCP_HACK_CACHE_DIR")
+        && !dir.trim().is_empty()
+    {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_CACHE_HOME")
+        && !dir.trim().is_empty()
+    {
+        return PathBuf::from(dir).join("mcp-hack");
+    }
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".cache/mcp-hack")
+}
+
 /// Lightweight progress indicator state.
 pub struct Progress {
     total: Option<u64>,
@@ -252,6 +636,307 @@ impl ProgressSnapshot {
         }
         (self.current as f64) / (self.elapsed_ms as f64 / 1000.0)
     }
+
+    /// Completion percentage (None when the total is unknown or zero).
+    pub fn percent(&self) -> Option<f64> {
+        match self.total {
+            Some(t) if t > 0 => Some((self.current as f64 / t as f64) * 100.0),
+            _ => None,
+        }
+    }
+}
+
+/// Machine-readable progress events (NDJSON) for GUIs / CI wrappers.
+///
+/// Disabled unless a sink is installed with `init_fd` (`--progress-fd N`).
+/// Events never go to stdout unless the caller explicitly passes fd 1.
+///
+/// Event shape:
+///   {"event":"progress","phase":"fuzz","current":3,"total":10,"percent":30.0,
+///    "item":"admin","elapsed_ms":120,"rate_per_sec":25.0,"ts":1700000000000}
+pub mod progress_events {
+    use super::*;
+    use std::io::Write;
+    use std::sync::Mutex;
+
+    static SINK: OnceLock<Mutex<Box<dyn Write + Send>>> = OnceLock::new();
+
+    /// Install the event sink on an already-open file descriptor (2 = stderr).
+    pub fn init_fd(fd: i32) -> anyhow::Result<()> {
+        let sink: Box<dyn Write + Send> = match fd {
+            2 => Box::new(std::io::stderr()),
+            1 => Box::new(std::io::stdout()),
+            _ => open_fd(fd)?,
+        };
+        SINK.set(Mutex::new(sink))
+            .map_err(|_| anyhow::anyhow!("progress sink already initialized"))
+    }
+
+    /// A duplicate of `fd`, checked to be open for writing. fd 0 (stdin) is
+    /// rejected; the original fd is left to its owner.
+    #[cfg(unix)]
+    pub(super) fn open_fd(fd: i32) -> anyhow::Result<Box<dyn Write + Send>> {
+        use std::os::fd::FromRawFd;
+        if fd < 3 {
+            anyhow::bail!("fd {fd} is not a progress fd (use 1 for stdout, 2 for stderr, or 3+)");
+        }
+        // SAFETY: fcntl only inspects the descriptor table; an unknown fd fails with EBADF.
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+        if flags == -1 {
+            anyhow::bail!("fd {fd} is not open");
+        }
+        if flags & libc::O_ACCMODE == libc::O_RDONLY {
+            anyhow::bail!("fd {fd} is not open for writing");
+        }
+        // SAFETY: as above; the duplicate (close-on-exec, numbered 3+) is a new
+        // descriptor that nothing else refers to.
+        let dup = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 3) };
+        if dup == -1 {
+            let err = std::io::Error::last_os_error();
+            anyhow::bail!("failed to duplicate fd {fd}: {err}");
+        }
+        // SAFETY: `dup` is open and owned by nobody else.
+        Ok(Box::new(unsafe { std::fs::File::from_raw_fd(dup) }))
+    }
+
+    #[cfg(not(unix))]
+    fn open_fd(fd: i32) -> anyhow::Result<Box<dyn Write + Send>> {
+        anyhow::bail!("--progress-fd {fd} is only supported on unix (use 2 for stderr)")
+    }
+
+    pub fn enabled() -> bool {
+        SINK.get().is_some()
+    }
+
+    fn write_event(event: serde_json::Value) {
+        let Some(sink) = SINK.get() else {
+            return;
+        };
+        if let Ok(mut w) = sink.lock() {
+            let _ = writeln!(w, "{event}");
+            let _ = w.flush();
+        }
+    }
+
+    fn now_ms() -> u128 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0)
+    }
+
+    /// Emit a counted progress event for the given phase.
+    pub fn emit(phase: &str, snapshot: &ProgressSnapshot, item: Option<&str>) {
+        if !enabled() {
+            return;
+        }
+        write_event(serde_json::json!({
+            "event": "progress",
+            "phase": phase,
+            "current": snapshot.current,
+            "total": snapshot.total,
+            "percent": snapshot.percent(),
+            "item": item,
+            "elapsed_ms": snapshot.elapsed_ms,
+            "rate_per_sec": snapshot.rate_per_sec(),
+            "ts": now_ms(),
+        }));
+    }
+
+    /// Emit a phase transition without counts (e.g. "connect", "done").
+    pub fn phase(phase: &str, item: Option<&str>) {
+        logging::event(
+            logging::LogLevel::Info,
+            "phase",
+            serde_json::json!({ "phase": phase, "item": item }),
+        );
+        if !enabled() {
+            return;
+        }
+        write_event(serde_json::json!({
+            "event": "phase",
+            "phase": phase,
+            "item": item,
+            "ts": now_ms(),
+        }));
+    }
 }
 
 // End of utils module.
+
+/// Replace `${NAME}` with the environment variable NAME (`${NAME:-default}`
+/// when it may be unset or empty); `$$` is a literal `$`. A `$` not
+/// followed by `{` or `$` is kept. Unset variables without a default fail.
+pub fn expand_env(s: &str) -> anyhow::Result<String> {
+    expand_vars(s, |name| std::env::var(name).ok())
+}
+
+fn expand_vars(s: &str, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| anyhow::anyhow!("unterminated ${{...}} in '{s}'"))?;
+            let (name, default) = match body[..end].split_once(":-") {
+                Some((n, d)) => (n, Some(d)),
+                None => (&body[..end], None),
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                anyhow::bail!("invalid variable name '${{{}}}'", &body[..end]);
+            }
+            match (
+                lookup(name).filter(|v| !v.is_empty() || default.is_none()),
+                default,
+            ) {
+                (Some(v), _) => out.push_str(&v),
+                (None, Some(d)) => out.push_str(d),
+                (None, None) => anyhow::bail!("environment variable {name} is not set"),
+            }
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Encode bytes as standard, padded base64.
+pub fn base64_encode(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+/// Decode base64 (standard or URL-safe alphabet, padding optional,
+/// whitespace ignored).
+pub fn base64_decode(s: &str) -> anyhow::Result<Vec<u8>> {
+    use base64::Engine;
+    use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+    const LENIENT: GeneralPurpose = GeneralPurpose::new(
+        &base64::alphabet::STANDARD,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+    );
+    let standard: String = s
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            '-' => '+',
+            '_' => '/',
+            c => c,
+        })
+        .collect();
+    Ok(LENIENT.decode(standard)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_base64() {
+        assert_eq!(base64_decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVsbG8").unwrap(), b"hello");
+        assert_eq!(base64_decode("aGVs\nbG8h").unwrap(), b"hello!");
+        assert_eq!(base64_decode("-_8=").unwrap(), vec![0xfb, 0xff]);
+        assert!(base64_decode("aGV*").is_err());
+        assert!(base64_decode("aGVsb").is_err());
+    }
+
+    #[test]
+    fn base64_round_trips() {
+        for len in 0..=7 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 97 + 250) as u8).collect();
+            let encoded = base64_encode(&bytes);
+            assert_eq!(encoded.len() % 4, 0);
+            assert_eq!(base64_decode(&encoded).unwrap(), bytes);
+            let url_safe = encoded
+                .trim_end_matches('=')
+                .replace('+', "-")
+                .replace('/', "_");
+            assert_eq!(base64_decode(&url_safe).unwrap(), bytes);
+        }
+        assert_eq!(base64_encode(b"hello"), "aGVsbG8=");
+    }
+
+    #[test]
+    fn expands_variables() {
+        let lookup = |n: &str| match n {
+            "TOKEN" => Some("s3cr3t".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        assert_eq!(
+            expand_vars("Bearer ${TOKEN}", lookup).unwrap(),
+            "Bearer s3cr3t"
+        );
+        assert_eq!(
+            expand_vars("${MISSING:-x}/${EMPTY:-y}/${EMPTY}", lookup).unwrap(),
+            "x/y/"
+        );
+        assert_eq!(expand_vars("$$5 $HOME", lookup).unwrap(), "$5 $HOME");
+        assert!(expand_vars("${MISSING}", lookup).is_err());
+        assert!(expand_vars("${TOKEN", lookup).is_err());
+        assert!(expand_vars("${A B}", lookup).is_err());
+    }
+
+    #[test]
+    fn sha256_known_vectors() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        // Two-block message
+        assert_eq!(
+            sha256_hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn utc_formatting() {
+        assert_eq!(format_utc(0, 0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(format_utc(951_782_400, 5), "2000-02-29T00:00:00.005Z");
+        assert_eq!(format_utc(1_700_000_000, 0), "2023-11-14T22:13:20.000Z");
+    }
+
+    #[test]
+    fn duration_units() {
+        use std::time::Duration;
+        assert_eq!(parse_duration("60s").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert!(parse_duration("5d").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn progress_fd_must_be_open_for_writing() {
+        use std::os::fd::AsRawFd;
+        assert!(progress_events::open_fd(0).is_err());
+        assert!(progress_events::open_fd(1_000_000).is_err());
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let err = progress_events::open_fd(file.as_raw_fd()).err().unwrap();
+        assert!(err.to_string().contains("not open for writing"), "{err}");
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open("/dev/null")
+            .unwrap();
+        let dup = progress_events::open_fd(file.as_raw_fd());
+        drop(file);
+        assert!(dup.unwrap().write_all(b"x\n").is_ok());
+    }
+}
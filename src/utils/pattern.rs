@@ -0,0 +1,164 @@
+//! Name patterns for `--filter` and proxy tamper rules: a glob, or a
+//! regular expression between slashes.
+//!
+//! ```text
+//! get_*              glob: `*` any run, `?` one character; whole name,
+//!                    case-insensitive
+//! /^(read|list)_/    regex: found anywhere in the name (anchor with ^ $)
+//! /file/i            regex, case-insensitive
+//! ```
+//!
+//! Regexes use the regex crate's syntax and run in time linear in the text
+//! (no backreferences or lookaround), so the same `regex` helper serves
+//! `exec --expect-regex` and `test()` in `--query` on whole tool results.
+//! `glob_match` is the plain, case-sensitive glob matcher shared by proxy
+//! tamper rules, scan templates, playbooks and suppressions.
+
+use anyhow::{Result, bail};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer};
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// Lowercased glob.
+    Glob(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn parse(spec: &str) -> Result<Pattern> {
+        if let Some(body) = spec.strip_prefix('/')
+            && let Some(end) = body.rfind('/')
+        {
+            let flags = &body[end + 1..];
+            let ignore_case = match flags {
+                "" => false,
+                "i" => true,
+                _ => bail!("unknown regex flags '{flags}' (only i is supported)"),
+            };
+            return Ok(Pattern::Regex(regex(&body[..end], ignore_case)?));
+        }
+        if spec.is_empty() {
+            bail!("empty pattern");
+        }
+        Ok(Pattern::Glob(spec.to_lowercase()))
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob_match(glob, &text.to_lowercase()),
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+
+    /// Like `matches`, but a glob may match any part of `text`.
+    pub fn find(&self, text: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob_match(&format!("*{glob}*"), &text.to_lowercase()),
+            Pattern::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+/// Rule files (proxy tamper rules) write patterns as strings.
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        let spec = String::deserialize(d)?;
+        Pattern::parse(&spec).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Glob match with `*` (any run) and `?` (one character).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Position after the last `*` and the text index it resumed from.
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi + 1, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Compile a regular expression (syntax of the regex crate).
+pub fn regex(pattern: &str, ignore_case: bool) -> Result<Regex> {
+    Ok(RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()?)
+}
+
+/// Compiled program size cap, so `a{1000}{1000}` fails instead of eating memory.
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn re(p: &str) -> Regex {
+        regex(p, false).unwrap()
+    }
+
+    #[test]
+    fn matches_regex_subset() {
+        assert!(re("^(read|list)_").is_match("list_files"));
+        assert!(!re("^(read|list)_").is_match("unlist_files"));
+        assert!(re("file$").is_match("read_file"));
+        assert!(re(r"^\w+_v\d{1,2}$").is_match("fetch_v12"));
+        assert!(!re(r"^\w+_v\d{1,2}$").is_match("fetch_v123"));
+        assert!(re("^[a-c-]+x?$").is_match("ab-c"));
+        assert!(re("^[^0-9]*$").is_match("abc"));
+        assert!(!re("^[^0-9]*$").is_match("a1"));
+        assert!(re("^(ab)*c").is_match("ababc"));
+        assert!(re("^(a*)*b").is_match("aab"));
+        assert!(re(r"a\.b").is_match("a.b") && !re(r"a\.b").is_match("axb"));
+        assert!(regex("FILE", true).unwrap().is_match("read_file"));
+        for bad in ["(a", "a)", "*a", "[a", "a{2,1}", "[z-a]", "a{1000}{1000}"] {
+            assert!(regex(bad, false).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn large_texts_run_in_linear_time() {
+        let text = format!("{}c", "a".repeat(200_000));
+        assert!(!re("^(a*)*b").is_match(&text));
+        assert!(!re("a.*b").is_match(&text));
+        assert!(re(".*c$").is_match(&text));
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("delete_*", "delete_file"));
+        assert!(glob_match("*../*", "a/../etc/passwd"));
+        assert!(glob_match("tools/?ist", "tools/list"));
+        assert!(!glob_match("tools/*", "resources/list"));
+        assert!(glob_match("*", ""));
+    }
+
+    #[test]
+    fn parses_globs_and_slashed_regexes() {
+        assert!(Pattern::parse("Get_*").unwrap().matches("get_user"));
+        assert!(!Pattern::parse("get_*").unwrap().matches("forget_user"));
+        assert!(Pattern::parse("/user/").unwrap().matches("get_user"));
+        assert!(Pattern::parse("/USER/i").unwrap().matches("get_user"));
+        assert!(Pattern::parse("/x/g").is_err());
+        assert!(Pattern::parse("").is_err());
+        // A lone slash is a glob.
+        assert!(Pattern::parse("/").unwrap().matches("/"));
+        assert!(Pattern::parse("../").unwrap().find("a/../b"));
+        assert!(!Pattern::parse("../").unwrap().matches("a/../b"));
+    }
+}
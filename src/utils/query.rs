@@ -0,0 +1,1396 @@
+//! A jq-like filter language for `--query`, so JSON output can be sliced
+//! without jq installed.
+//!
+//! ```text
+//! .tools[].name                       every tool name
+//! .tools | length                     how many
+//! .tools[] | select(.name | test("^read_")) | {name, description}
+//! .findings | map(select(.severity == "high")) | length
+//! [.tools[].name] | sort | join(",")
+//! .result_summary // .result          first non-null / non-false
+//! ```
+//!
+//! Paths: `.`, `.name`, `."odd key"`, `.[N]` (negative from the end),
+//! `.[N:M]`, `.[]`, `.["key"]`, a trailing `?` ignores errors.
+//! Operators: `|`, `,`, `//`, `and`, `or`, `== != < <= > >=` (jq ordering:
+//! null < false < true < numbers < strings < arrays < objects),
+//! `+ - * /`. Literals: numbers, "strings", true, false, null, `[...]`
+//! array construction, `{a, b: .x, "c": 1}` objects.
+//! Functions: length keys values first last sort unique reverse add min max
+//! flatten not type tostring tonumber tojson empty to_entries from_entries
+//! ascii_downcase ascii_upcase any all, and map(f) select(f) sort_by(f)
+//! unique_by(f) has(k) contains(x) test(re) startswith(s) endswith(s)
+//! join(s) split(s). `test` uses the regex syntax of utils::pattern.
+//! No variables, reduce / foreach, paths or string interpolation.
+//! Filters are capped at 4096 tokens and 64 levels of nesting.
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+
+use crate::utils::pattern::regex;
+
+/* ---- Tokens ---- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    Field(String),
+    Ident(String),
+    Str(String),
+    Num(f64),
+    /// Punctuation and operators.
+    Sym(&'static str),
+}
+
+const SYMBOLS: &[&str] = &[
+    "//", "==", "!=", "<=", ">=", "|", ",", ";", "(", ")", "[", "]", "{", "}", ":", "?", "<", ">",
+    "+", "-", "*", "/",
+];
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        if c == '.' {
+            if chars.get(i + 1) == Some(&'.') {
+                bail!("recursive descent (..) is not supported");
+            }
+            if chars.get(i + 1).is_some_and(|&c| is_ident_start(c)) {
+                let start = i + 1;
+                i = start;
+                while chars.get(i).is_some_and(|&c| is_ident(c)) {
+                    i += 1;
+                }
+                out.push(Token::Field(chars[start..i].iter().collect()));
+            } else {
+                out.push(Token::Dot);
+                i += 1;
+            }
+            continue;
+        }
+        if c == '"' {
+            let start = i;
+            i += 1;
+            let mut escaped = false;
+            while let Some(&c) = chars.get(i) {
+                i += 1;
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => break,
+                    _ => {}
+                }
+            }
+            let literal: String = chars[start..i].iter().collect();
+            let s: String = serde_json::from_str(&literal)
+                .map_err(|_| anyhow!("unterminated or invalid string {literal}"))?;
+            out.push(Token::Str(s));
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let start = i;
+            while chars
+                .get(i)
+                .is_some_and(|&c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E'))
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| anyhow!("invalid number '{text}'"))?;
+            out.push(Token::Num(n));
+            continue;
+        }
+        if is_ident_start(c) {
+            let start = i;
+            while chars.get(i).is_some_and(|&c| is_ident(c)) {
+                i += 1;
+            }
+            out.push(Token::Ident(chars[start..i].iter().collect()));
+            continue;
+        }
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+            Some(s) => {
+                out.push(Token::Sym(s));
+                i += s.len();
+            }
+            None => bail!("unexpected '{c}' at offset {i}"),
+        }
+    }
+    Ok(out)
+}
+
+/* ---- Syntax Tree ---- */
+
+#[derive(Debug, Clone, PartialEq)]
+enum Ast {
+    Identity,
+    Literal(Value),
+    Index(Box<Ast>, Box<Ast>),
+    Slice(Box<Ast>, Option<Box<Ast>>, Option<Box<Ast>>),
+    Iterate(Box<Ast>),
+    Try(Box<Ast>),
+    Pipe(Box<Ast>, Box<Ast>),
+    Comma(Box<Ast>, Box<Ast>),
+    Alt(Box<Ast>, Box<Ast>),
+    And(Box<Ast>, Box<Ast>),
+    Or(Box<Ast>, Box<Ast>),
+    Binary(&'static str, Box<Ast>, Box<Ast>),
+    Array(Option<Box<Ast>>),
+    Object(Vec<(Ast, Ast)>),
+    Call(String, Vec<Ast>),
+}
+
+/// Limits that keep parsing and evaluation recursion shallow.
+const MAX_TOKENS: usize = 4096;
+const MAX_NESTING: usize = 64;
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Open ( [ { and function arguments around the current position.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn at(&self, sym: &str) -> bool {
+        matches!(self.peek(), Some(Token::Sym(s)) if *s == sym)
+    }
+
+    fn at_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(w)) if w == word)
+    }
+
+    fn eat(&mut self, sym: &str) -> bool {
+        let found = self.at(sym);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, sym: &str) -> Result<()> {
+        if self.eat(sym) {
+            return Ok(());
+        }
+        match self.peek() {
+            Some(t) => bail!("expected '{sym}', found {}", describe(t)),
+            None => bail!("expected '{sym}' at the end"),
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Ast> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            bail!("query nested more than {MAX_NESTING} levels deep");
+        }
+        let mut left = self.comma()?;
+        while self.eat("|") {
+            let right = self.comma()?;
+            left = Ast::Pipe(Box::new(left), Box::new(right));
+        }
+        self.depth -= 1;
+        Ok(left)
+    }
+
+    fn comma(&mut self) -> Result<Ast> {
+        let mut left = self.alternative()?;
+        while self.eat(",") {
+            let right = self.alternative()?;
+            left = Ast::Comma(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn alternative(&mut self) -> Result<Ast> {
+        let left = self.or()?;
+        if self.eat("//") {
+            // Right-associative, like jq.
+            let right = self.alternative()?;
+            return Ok(Ast::Alt(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Ast> {
+        let mut left = self.and()?;
+        while self.at_word("or") {
+            self.pos += 1;
+            let right = self.and()?;
+            left = Ast::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Ast> {
+        let mut left = self.comparison()?;
+        while self.at_word("and") {
+            self.pos += 1;
+            let right = self.comparison()?;
+            left = Ast::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Ast> {
+        let left = self.additive()?;
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                let right = self.additive()?;
+                return Ok(Ast::Binary(op, Box::new(left), Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    fn additive(&mut self) -> Result<Ast> {
+        let mut left = self.multiplicative()?;
+        loop {
+            let op = if self.eat("+") {
+                "+"
+            } else if self.eat("-") {
+                "-"
+            } else {
+                return Ok(left);
+            };
+            let right = self.multiplicative()?;
+            left = Ast::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Ast> {
+        let mut left = self.postfix()?;
+        loop {
+            let op = if self.eat("*") {
+                "*"
+            } else if self.eat("/") {
+                "/"
+            } else {
+                return Ok(left);
+            };
+            let right = self.postfix()?;
+            left = Ast::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Ast> {
+        let mut term = self.primary()?;
+        loop {
+            if let Some(Token::Field(name)) = self.peek() {
+                let key = Ast::Literal(Value::String(name.clone()));
+                self.pos += 1;
+                term = Ast::Index(Box::new(term), Box::new(key));
+            } else if self.at("[") {
+                self.pos += 1;
+                term = self.bracket(term)?;
+            } else if self.at("?") {
+                self.pos += 1;
+                term = Ast::Try(Box::new(term));
+            } else if self.peek() == Some(&Token::Dot)
+                && matches!(
+                    self.tokens.get(self.pos + 1),
+                    Some(Token::Sym("[")) | Some(Token::Str(_))
+                )
+            {
+                // `.a.["b"]` / `.a."b"`
+                self.pos += 1;
+                if let Some(Token::Str(s)) = self.peek() {
+                    let key = Ast::Literal(Value::String(s.clone()));
+                    self.pos += 1;
+                    term = Ast::Index(Box::new(term), Box::new(key));
+                }
+            } else {
+                return Ok(term);
+            }
+        }
+    }
+
+    /// After `[` following a term: `[]`, `[i]`, `[a:b]`.
+    fn bracket(&mut self, term: Ast) -> Result<Ast> {
+        if self.eat("]") {
+            return Ok(Ast::Iterate(Box::new(term)));
+        }
+        let start = if self.at(":") {
+            None
+        } else {
+            Some(Box::new(self.pipe()?))
+        };
+        if self.eat(":") {
+            let end = if self.at("]") {
+                None
+            } else {
+                Some(Box::new(self.pipe()?))
+            };
+            self.expect("]")?;
+            return Ok(Ast::Slice(Box::new(term), start, end));
+        }
+        self.expect("]")?;
+        let index = start.ok_or_else(|| anyhow!("empty index"))?;
+        Ok(Ast::Index(Box::new(term), index))
+    }
+
+    fn primary(&mut self) -> Result<Ast> {
+        let Some(token) = self.peek().cloned() else {
+            bail!("unexpected end of query");
+        };
+        self.pos += 1;
+        match token {
+            Token::Dot => {
+                if let Some(Token::Str(s)) = self.peek() {
+                    let key = Ast::Literal(Value::String(s.clone()));
+                    self.pos += 1;
+                    return Ok(Ast::Index(Box::new(Ast::Identity), Box::new(key)));
+                }
+                Ok(Ast::Identity)
+            }
+            Token::Field(name) => Ok(Ast::Index(
+                Box::new(Ast::Identity),
+                Box::new(Ast::Literal(Value::String(name))),
+            )),
+            Token::Str(s) => Ok(Ast::Literal(Value::String(s))),
+            Token::Num(n) => Ok(Ast::Literal(number(n))),
+            Token::Sym("-") => {
+                let operand = self.postfix()?;
+                Ok(Ast::Binary(
+                    "-",
+                    Box::new(Ast::Literal(number(0.0))),
+                    Box::new(operand),
+                ))
+            }
+            Token::Sym("(") => {
+                let inner = self.pipe()?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Token::Sym("[") => {
+                if self.eat("]") {
+                    return Ok(Ast::Array(None));
+                }
+                let inner = self.pipe()?;
+                self.expect("]")?;
+                Ok(Ast::Array(Some(Box::new(inner))))
+            }
+            Token::Sym("{") => self.object(),
+            Token::Ident(word) => match word.as_str() {
+                "true" => Ok(Ast::Literal(Value::Bool(true))),
+                "false" => Ok(Ast::Literal(Value::Bool(false))),
+                "null" => Ok(Ast::Literal(Value::Null)),
+                _ => {
+                    let mut args = Vec::new();
+                    if self.eat("(") {
+                        args.push(self.pipe()?);
+                        while self.eat(";") {
+                            args.push(self.pipe()?);
+                        }
+                        self.expect(")")?;
+                    }
+                    check_arity(&word, args.len())?;
+                    Ok(Ast::Call(word, args))
+                }
+            },
+            other => bail!("unexpected {}", describe(&other)),
+        }
+    }
+
+    /// After `{`: `{a, "b": f, (k): v}`.
+    fn object(&mut self) -> Result<Ast> {
+        let mut entries = Vec::new();
+        if self.eat("}") {
+            return Ok(Ast::Object(entries));
+        }
+        loop {
+            let key_token = self.peek().cloned();
+            self.pos += 1;
+            let (key, shorthand) = match key_token {
+                Some(Token::Ident(name)) | Some(Token::Field(name)) => {
+                    let field = Ast::Index(
+                        Box::new(Ast::Identity),
+                        Box::new(Ast::Literal(Value::String(name.clone()))),
+                    );
+                    (Ast::Literal(Value::String(name)), Some(field))
+                }
+                Some(Token::Str(s)) => {
+                    let field = Ast::Index(
+                        Box::new(Ast::Identity),
+                        Box::new(Ast::Literal(Value::String(s.clone()))),
+                    );
+                    (Ast::Literal(Value::String(s)), Some(field))
+                }
+                Some(Token::Sym("(")) => {
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                Some(t) => bail!("unexpected {} in object", describe(&t)),
+                None => bail!("unclosed {{"),
+            };
+            let value = if self.eat(":") {
+                self.alternative()?
+            } else {
+                shorthand.ok_or_else(|| anyhow!("expected ':' after a computed key"))?
+            };
+            entries.push((key, value));
+            if self.eat("}") {
+                return Ok(Ast::Object(entries));
+            }
+            self.expect(",")?;
+        }
+    }
+}
+
+fn describe(t: &Token) -> String {
+    match t {
+        Token::Dot => "'.'".into(),
+        Token::Field(f) => format!("'.{f}'"),
+        Token::Ident(w) => format!("'{w}'"),
+        Token::Str(s) => format!("{s:?}"),
+        Token::Num(n) => n.to_string(),
+        Token::Sym(s) => format!("'{s}'"),
+    }
+}
+
+const FUNCTIONS_0: &[&str] = &[
+    "length",
+    "keys",
+    "values",
+    "first",
+    "last",
+    "sort",
+    "unique",
+    "reverse",
+    "add",
+    "min",
+    "max",
+    "flatten",
+    "not",
+    "type",
+    "tostring",
+    "tonumber",
+    "tojson",
+    "empty",
+    "to_entries",
+    "from_entries",
+    "ascii_downcase",
+    "ascii_upcase",
+    "any",
+    "all",
+];
+const FUNCTIONS_1: &[&str] = &[
+    "map",
+    "select",
+    "sort_by",
+    "unique_by",
+    "has",
+    "contains",
+    "test",
+    "startswith",
+    "endswith",
+    "join",
+    "split",
+];
+
+fn check_arity(name: &str, arity: usize) -> Result<()> {
+    let known = match arity {
+        0 => FUNCTIONS_0.contains(&name),
+        1 => FUNCTIONS_1.contains(&name),
+        _ => false,
+    };
+    if known {
+        return Ok(());
+    }
+    if FUNCTIONS_0.contains(&name) || FUNCTIONS_1.contains(&name) {
+        bail!(
+            "{name} takes {} argument(s)",
+            usize::from(FUNCTIONS_1.contains(&name))
+        );
+    }
+    bail!("unknown function {name}/{arity}")
+}
+
+/* ---- Evaluation ---- */
+
+fn number(n: f64) -> Value {
+    if n.fract() == 0.0 && n.abs() < 9e15 {
+        Value::from(n as i64)
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn type_name(v: &Value) -> &'static str {
+    match v {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn truthy(v: &Value) -> bool {
+    !matches!(v, Value::Null | Value::Bool(false))
+}
+
+fn rank(v: &Value) -> u8 {
+    match v {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+    }
+}
+
+/// jq's total order of JSON values.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => {
+            let (x, y) = (x.as_f64().unwrap_or(0.0), y.as_f64().unwrap_or(0.0));
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(a, b)| compare(a, b))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => {
+            let keys = |m: &Map<String, Value>| {
+                let mut k: Vec<String> = m.keys().cloned().collect();
+                k.sort();
+                k
+            };
+            let (kx, ky) = (keys(x), keys(y));
+            kx.cmp(&ky).then_with(|| {
+                kx.iter()
+                    .map(|k| compare(&x[k], &y[k]))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn arithmetic(op: &str, a: &Value, b: &Value) -> Result<Value> {
+    Ok(match (op, a, b) {
+        ("+", Value::Null, x) | ("+", x, Value::Null) => x.clone(),
+        ("+", Value::String(x), Value::String(y)) => Value::String(format!("{x}{y}")),
+        ("+", Value::Array(x), Value::Array(y)) => {
+            Value::Array(x.iter().chain(y).cloned().collect())
+        }
+        ("+", Value::Object(x), Value::Object(y)) => {
+            let mut m = x.clone();
+            m.extend(y.clone());
+            Value::Object(m)
+        }
+        ("-", Value::Array(x), Value::Array(y)) => {
+            Value::Array(x.iter().filter(|v| !y.contains(v)).cloned().collect())
+        }
+        (_, Value::Number(_), Value::Number(_)) => {
+            let (x, y) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            match op {
+                "+" => number(x + y),
+                "-" => number(x - y),
+                "*" => number(x * y),
+                _ if y == 0.0 => bail!("division by zero"),
+                _ => number(x / y),
+            }
+        }
+        _ => bail!("cannot apply {op} to {} and {}", type_name(a), type_name(b)),
+    })
+}
+
+/// jq's `contains`: substrings, and recursively for arrays / objects.
+fn contains(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(x), Value::String(y)) => x.contains(y.as_str()),
+        (Value::Array(x), Value::Array(y)) => y.iter().all(|b| x.iter().any(|a| contains(a, b))),
+        (Value::Object(x), Value::Object(y)) => y
+            .iter()
+            .all(|(k, b)| x.get(k).is_some_and(|a| contains(a, b))),
+        _ => a == b,
+    }
+}
+
+fn flatten_into(v: &Value, out: &mut Vec<Value>) {
+    match v {
+        Value::Array(items) => items.iter().for_each(|i| flatten_into(i, out)),
+        other => out.push(other.clone()),
+    }
+}
+
+fn items<'a>(v: &'a Value, what: &str) -> Result<&'a Vec<Value>> {
+    match v {
+        Value::Array(items) => Ok(items),
+        other => bail!("{what} needs an array, got {}", type_name(other)),
+    }
+}
+
+fn text<'a>(v: &'a Value, what: &str) -> Result<&'a str> {
+    match v {
+        Value::String(s) => Ok(s),
+        other => bail!("{what} needs a string, got {}", type_name(other)),
+    }
+}
+
+fn index(target: &Value, key: &Value) -> Result<Value> {
+    Ok(match (target, key) {
+        (Value::Null, Value::String(_) | Value::Number(_)) => Value::Null,
+        (Value::Object(m), Value::String(k)) => m.get(k).cloned().unwrap_or(Value::Null),
+        (Value::Array(items), Value::Number(n)) => {
+            let n = n.as_f64().unwrap_or(0.0).floor() as i64;
+            let i = if n < 0 { items.len() as i64 + n } else { n };
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| items.get(i))
+                .cloned()
+                .unwrap_or(Value::Null)
+        }
+        _ => bail!(
+            "cannot index {} with {}",
+            type_name(target),
+            match key {
+                Value::String(s) => format!("{s:?}"),
+                other => type_name(other).to_string(),
+            }
+        ),
+    })
+}
+
+fn slice(target: &Value, start: Option<&Value>, end: Option<&Value>) -> Result<Value> {
+    let bound = |v: Option<&Value>, len: usize, default: usize| -> Result<usize> {
+        let Some(v) = v.filter(|v| !v.is_null()) else {
+            return Ok(default);
+        };
+        let n = v
+            .as_f64()
+            .ok_or_else(|| anyhow!("slice bounds must be numbers"))?
+            .floor() as i64;
+        let n = if n < 0 { len as i64 + n } else { n };
+        Ok(n.clamp(0, len as i64) as usize)
+    };
+    Ok(match target {
+        Value::Null => Value::Null,
+        Value::Array(items) => {
+            let (s, e) = (
+                bound(start, items.len(), 0)?,
+                bound(end, items.len(), items.len())?,
+            );
+            Value::Array(items[s..e.max(s)].to_vec())
+        }
+        Value::String(text) => {
+            let chars: Vec<char> = text.chars().collect();
+            let (s, e) = (
+                bound(start, chars.len(), 0)?,
+                bound(end, chars.len(), chars.len())?,
+            );
+            Value::String(chars[s..e.max(s)].iter().collect())
+        }
+        other => bail!("cannot slice {}", type_name(other)),
+    })
+}
+
+/// Cartesian product of the outputs of `entries` (key, value) on `input`.
+fn build_object(entries: &[(Ast, Ast)], input: &Value) -> Result<Vec<Value>> {
+    let mut partial = vec![Map::new()];
+    for (key, value) in entries {
+        let keys = eval(key, input)?;
+        let values = eval(value, input)?;
+        let mut next = Vec::new();
+        for m in &partial {
+            for k in &keys {
+                let k = text(k, "object key")?;
+                for v in &values {
+                    let mut m = m.clone();
+                    m.insert(k.to_string(), v.clone());
+                    next.push(m);
+                }
+            }
+        }
+        partial = next;
+    }
+    Ok(partial.into_iter().map(Value::Object).collect())
+}
+
+fn eval(ast: &Ast, input: &Value) -> Result<Vec<Value>> {
+    Ok(match ast {
+        Ast::Identity => vec![input.clone()],
+        Ast::Literal(v) => vec![v.clone()],
+        Ast::Index(target, key) => {
+            let mut out = Vec::new();
+            for t in eval(target, input)? {
+                for k in eval(key, input)? {
+                    out.push(index(&t, &k)?);
+                }
+            }
+            out
+        }
+        Ast::Slice(target, start, end) => {
+            let bounds = |b: &Option<Box<Ast>>| -> Result<Option<Value>> {
+                match b {
+                    Some(b) => Ok(eval(b, input)?.into_iter().next()),
+                    None => Ok(None),
+                }
+            };
+            let (s, e) = (bounds(start)?, bounds(end)?);
+            eval(target, input)?
+                .iter()
+                .map(|t| slice(t, s.as_ref(), e.as_ref()))
+                .collect::<Result<_>>()?
+        }
+        Ast::Iterate(target) => {
+            let mut out = Vec::new();
+            for t in eval(target, input)? {
+                match t {
+                    Value::Array(items) => out.extend(items),
+                    Value::Object(m) => out.extend(m.into_iter().map(|(_, v)| v)),
+                    other => bail!("cannot iterate over {}", type_name(&other)),
+                }
+            }
+            out
+        }
+        Ast::Try(inner) => eval(inner, input).unwrap_or_default(),
+        Ast::Pipe(left, right) => {
+            let mut out = Vec::new();
+            for v in eval(left, input)? {
+                out.extend(eval(right, &v)?);
+            }
+            out
+        }
+        Ast::Comma(left, right) => {
+            let mut out = eval(left, input)?;
+            out.extend(eval(right, input)?);
+            out
+        }
+        Ast::Alt(left, right) => {
+            let found: Vec<Value> = eval(left, input)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(truthy)
+                .collect();
+            if found.is_empty() {
+                eval(right, input)?
+            } else {
+                found
+            }
+        }
+        Ast::And(left, right) | Ast::Or(left, right) => {
+            let is_and = matches!(ast, Ast::And(..));
+            let mut out = Vec::new();
+            for l in eval(left, input)? {
+                if truthy(&l) != is_and {
+                    out.push(Value::Bool(!is_and));
+                    continue;
+                }
+                for r in eval(right, input)? {
+                    out.push(Value::Bool(truthy(&r)));
+                }
+            }
+            out
+        }
+        Ast::Binary(op, left, right) => {
+            let mut out = Vec::new();
+            for r in eval(right, input)? {
+                for l in eval(left, input)? {
+                    let ord = compare(&l, &r);
+                    out.push(match *op {
+                        "==" => Value::Bool(ord.is_eq()),
+                        "!=" => Value::Bool(ord.is_ne()),
+                        "<" => Value::Bool(ord.is_lt()),
+                        "<=" => Value::Bool(ord.is_le()),
+                        ">" => Value::Bool(ord.is_gt()),
+                        ">=" => Value::Bool(ord.is_ge()),
+                        _ => arithmetic(op, &l, &r)?,
+                    });
+                }
+            }
+            out
+        }
+        Ast::Array(inner) => {
+            let items = match inner {
+                Some(inner) => eval(inner, input)?,
+                None => Vec::new(),
+            };
+            vec![Value::Array(items)]
+        }
+        Ast::Object(entries) => build_object(entries, input)?,
+        Ast::Call(name, args) => call(name, args, input)?,
+    })
+}
+
+fn call(name: &str, args: &[Ast], input: &Value) -> Result<Vec<Value>> {
+    if let [arg] = args {
+        return call1(name, arg, input);
+    }
+    let one = |v: Value| Ok(vec![v]);
+    match name {
+        "empty" => Ok(Vec::new()),
+        "not" => one(Value::Bool(!truthy(input))),
+        "type" => one(Value::String(type_name(input).into())),
+        "length" => one(match input {
+            Value::Null => Value::from(0),
+            Value::Bool(_) => bail!("boolean has no length"),
+            Value::Number(n) => number(n.as_f64().unwrap_or(0.0).abs()),
+            Value::String(s) => Value::from(s.chars().count()),
+            Value::Array(a) => Value::from(a.len()),
+            Value::Object(m) => Value::from(m.len()),
+        }),
+        "keys" => one(match input {
+            Value::Object(m) => {
+                let mut k: Vec<String> = m.keys().cloned().collect();
+                k.sort();
+                Value::from(k)
+            }
+            Value::Array(a) => Value::from((0..a.len()).collect::<Vec<_>>()),
+            other => bail!("{} has no keys", type_name(other)),
+        }),
+        "values" => one(match input {
+            Value::Object(m) => Value::Array(m.values().cloned().collect()),
+            Value::Array(_) => input.clone(),
+            other => bail!("{} has no values", type_name(other)),
+        }),
+        "first" => one(items(input, name)?.first().cloned().unwrap_or(Value::Null)),
+        "last" => one(items(input, name)?.last().cloned().unwrap_or(Value::Null)),
+        "sort" | "unique" => {
+            let mut sorted = items(input, name)?.clone();
+            sorted.sort_by(compare);
+            if name == "unique" {
+                sorted.dedup_by(|a, b| compare(a, b).is_eq());
+            }
+            one(Value::Array(sorted))
+        }
+        "reverse" => one(match input {
+            Value::String(s) => Value::String(s.chars().rev().collect()),
+            Value::Null => Value::Array(Vec::new()),
+            other => Value::Array(items(other, name)?.iter().rev().cloned().collect()),
+        }),
+        "add" => {
+            let mut total = Value::Null;
+            for v in items(input, name)? {
+                total = arithmetic("+", &total, v)?;
+            }
+            one(total)
+        }
+        "min" | "max" => {
+            let list = items(input, name)?;
+            let pick = if name == "min" {
+                list.iter().min_by(|a, b| compare(a, b))
+            } else {
+                list.iter().max_by(|a, b| compare(a, b))
+            };
+            one(pick.cloned().unwrap_or(Value::Null))
+        }
+        "flatten" => {
+            let mut out = Vec::new();
+            items(input, name)?
+                .iter()
+                .for_each(|v| flatten_into(v, &mut out));
+            one(Value::Array(out))
+        }
+        "tostring" => one(match input {
+            Value::String(_) => input.clone(),
+            other => Value::String(other.to_string()),
+        }),
+        "tonumber" => one(match input {
+            Value::Number(_) => input.clone(),
+            Value::String(s) => number(
+                s.trim()
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("cannot parse {s:?} as a number"))?,
+            ),
+            other => bail!("cannot convert {} to a number", type_name(other)),
+        }),
+        "tojson" => one(Value::String(input.to_string())),
+        "to_entries" => match input {
+            Value::Object(m) => one(Value::Array(
+                m.iter()
+                    .map(|(k, v)| serde_json::json!({"key": k, "value": v}))
+                    .collect(),
+            )),
+            other => bail!("to_entries needs an object, got {}", type_name(other)),
+        },
+        "from_entries" => {
+            let mut m = Map::new();
+            for e in items(input, name)? {
+                let key = e
+                    .get("key")
+                    .or_else(|| e.get("name"))
+                    .or_else(|| e.get("k"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let key = match key {
+                    Value::String(s) => s,
+                    Value::Null => bail!("from_entries: entry without a key"),
+                    other => other.to_string(),
+                };
+                let value = e
+                    .get("value")
+                    .or_else(|| e.get("v"))
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                m.insert(key, value);
+            }
+            one(Value::Object(m))
+        }
+        "ascii_downcase" => one(Value::String(text(input, name)?.to_ascii_lowercase())),
+        "ascii_upcase" => one(Value::String(text(input, name)?.to_ascii_uppercase())),
+        "any" => one(Value::Bool(items(input, name)?.iter().any(truthy))),
+        "all" => one(Value::Bool(items(input, name)?.iter().all(truthy))),
+        _ => bail!("unknown function {name}/0"),
+    }
+}
+
+fn call1(name: &str, arg: &Ast, input: &Value) -> Result<Vec<Value>> {
+    match name {
+        "map" => {
+            let mut out = Vec::new();
+            for v in match input {
+                Value::Object(m) => m.values().cloned().collect(),
+                other => items(other, name)?.clone(),
+            } {
+                out.extend(eval(arg, &v)?);
+            }
+            Ok(vec![Value::Array(out)])
+        }
+        "select" => {
+            let keep = eval(arg, input)?.iter().any(truthy);
+            Ok(if keep {
+                vec![input.clone()]
+            } else {
+                Vec::new()
+            })
+        }
+        "sort_by" | "unique_by" => {
+            let mut keyed = Vec::new();
+            for v in items(input, name)? {
+                keyed.push((Value::Array(eval(arg, v)?), v.clone()));
+            }
+            keyed.sort_by(|a, b| compare(&a.0, &b.0));
+            if name == "unique_by" {
+                keyed.dedup_by(|a, b| compare(&a.0, &b.0).is_eq());
+            }
+            Ok(vec![Value::Array(
+                keyed.into_iter().map(|(_, v)| v).collect(),
+            )])
+        }
+        _ => {
+            let mut out = Vec::new();
+            for a in eval(arg, input)? {
+                out.push(call_with(name, &a, input)?);
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Functions whose argument is a value computed from the input.
+fn call_with(name: &str, arg: &Value, input: &Value) -> Result<Value> {
+    Ok(match name {
+        "has" => Value::Bool(match (input, arg) {
+            (Value::Object(m), Value::String(k)) => m.contains_key(k),
+            (Value::Array(a), Value::Number(n)) => n
+                .as_f64()
+                .is_some_and(|n| n >= 0.0 && (n as usize) < a.len()),
+            _ => bail!(
+                "cannot check whether {} has a {}",
+                type_name(input),
+                type_name(arg)
+            ),
+        }),
+        "contains" => {
+            if type_name(input) != type_name(arg) {
+                bail!("{} cannot contain {}", type_name(input), type_name(arg));
+            }
+            Value::Bool(contains(input, arg))
+        }
+        "test" => {
+            let re = regex(text(arg, "test")?, false)?;
+            Value::Bool(re.is_match(text(input, "test")?))
+        }
+        "startswith" => Value::Bool(text(input, name)?.starts_with(text(arg, name)?)),
+        "endswith" => Value::Bool(text(input, name)?.ends_with(text(arg, name)?)),
+        "join" => {
+            let sep = text(arg, name)?;
+            let parts: Vec<String> = items(input, name)?
+                .iter()
+                .map(|v| match v {
+                    Value::Null => Ok(String::new()),
+                    Value::String(s) => Ok(s.clone()),
+                    Value::Number(_) | Value::Bool(_) => Ok(v.to_string()),
+                    other => Err(anyhow!("cannot join {}", type_name(other))),
+                })
+                .collect::<Result<_>>()?;
+            Value::String(parts.join(sep))
+        }
+        "split" => {
+            let sep = text(arg, name)?;
+            let s = text(input, name)?;
+            if sep.is_empty() {
+                Value::from(s.chars().map(String::from).collect::<Vec<_>>())
+            } else {
+                Value::from(s.split(sep).collect::<Vec<_>>())
+            }
+        }
+        _ => bail!("unknown function {name}/1"),
+    })
+}
+
+/* ---- Public API ---- */
+
+/// A parsed filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    source: String,
+    ast: Ast,
+}
+
+impl Query {
+    pub fn parse(source: &str) -> Result<Query> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            depth: 0,
+        };
+        if parser.tokens.is_empty() {
+            bail!("empty query");
+        }
+        if parser.tokens.len() > MAX_TOKENS {
+            bail!("query longer than {MAX_TOKENS} tokens");
+        }
+        let ast = parser.pipe()?;
+        if let Some(t) = parser.peek() {
+            bail!("unexpected {} after the query", describe(t));
+        }
+        Ok(Query {
+            source: source.trim().to_string(),
+            ast,
+        })
+    }
+
+    /// The filter as written.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Every output of the filter applied to `input`.
+    pub fn apply(&self, input: &Value) -> Result<Vec<Value>> {
+        eval(&self.ast, input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn run(q: &str, input: &Value) -> Vec<Value> {
+        Query::parse(q)
+            .unwrap_or_else(|e| panic!("{q}: {e}"))
+            .apply(input)
+            .unwrap_or_else(|e| panic!("{q}: {e}"))
+    }
+
+    fn fails(q: &str, input: &Value) -> String {
+        let query = Query::parse(q).unwrap_or_else(|e| panic!("{q}: {e}"));
+        match query.apply(input) {
+            Ok(out) => panic!("{q}: expected an error, got {out:?}"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn tools() -> Value {
+        json!({
+            "tools": [
+                {"name": "read_file", "risk": 3, "tags": ["fs"]},
+                {"name": "echo", "risk": 1, "tags": []},
+                {"name": "read_env", "risk": 5, "tags": ["env", "secret"]},
+            ],
+            "odd key": {"x": null},
+        })
+    }
+
+    #[test]
+    fn paths_and_slices() {
+        let doc = tools();
+        assert_eq!(
+            run(".tools[].name", &doc),
+            [json!("read_file"), json!("echo"), json!("read_env")]
+        );
+        assert_eq!(run(".tools[-1].name", &doc), [json!("read_env")]);
+        assert_eq!(run(".tools[1:].[0].name", &doc), [json!("echo")]);
+        assert_eq!(run(".tools[:1] | length", &doc), [json!(1)]);
+        assert_eq!(run(".tools[0].name[0:4]", &doc), [json!("read")]);
+        assert_eq!(run(".[\"odd key\"].x", &doc), [json!(null)]);
+        assert_eq!(run(".missing.deeper", &doc), [json!(null)]);
+        assert_eq!(run(".tools[9]", &doc), [json!(null)]);
+        assert_eq!(run(".tools.name?", &doc), Vec::<Value>::new());
+        assert_eq!(run("{a: 1, b: 2}[]", &json!(null)), [json!(1), json!(2)]);
+    }
+
+    #[test]
+    fn operators() {
+        let doc = tools();
+        assert_eq!(run(".\"odd key\".x // \"none\"", &doc), [json!("none")]);
+        assert_eq!(run("false // null // 3", &doc), [json!(3)]);
+        assert_eq!(run("1, 2 | . * 10", &doc), [json!(10), json!(20)]);
+        assert_eq!(run("[.tools[].risk] | add / 3", &doc), [json!(3)]);
+        assert_eq!(
+            run("\"a\" + \"b\", [1, 2] - [2]", &doc),
+            [json!("ab"), json!([1])]
+        );
+        assert_eq!(run("{a: 1} + {b: 2}", &doc), [json!({"a": 1, "b": 2})]);
+        assert_eq!(run("null + 1", &doc), [json!(1)]);
+        assert_eq!(run("1 == 1.0, 2 > 10", &doc), [json!(true), json!(false)]);
+        // jq ordering: null < false < true < numbers < strings < arrays < objects
+        assert_eq!(
+            run("[{}, [], \"a\", 1, true, false, null] | sort", &doc),
+            [json!([null, false, true, 1, "a", [], {}])]
+        );
+        assert_eq!(
+            run("true and (1, null), false or false", &doc),
+            [json!(true), json!(false), json!(false)]
+        );
+    }
+
+    #[test]
+    fn construction() {
+        let doc = tools();
+        assert_eq!(
+            run(
+                "[.tools[] | select(.name | test(\"^read_\")) | {name, r: .risk}]",
+                &doc
+            ),
+            [json!([{"name": "read_file", "r": 3}, {"name": "read_env", "r": 5}])]
+        );
+        assert_eq!(
+            run("{(.tools[0].name): 1, \"k\": (.tools | length)}", &doc),
+            [json!({"read_file": 1, "k": 3})]
+        );
+        assert_eq!(run("{a: (1, 2)}", &doc), [json!({"a": 1}), json!({"a": 2})]);
+        assert_eq!(run("[]", &doc), [json!([])]);
+        assert_eq!(run("[empty]", &doc), [json!([])]);
+    }
+
+    #[test]
+    fn array_functions() {
+        let doc = tools();
+        assert_eq!(
+            run(
+                ".tools | map(select(.risk >= 3 and (.tags | length) > 0)) | length",
+                &doc
+            ),
+            [json!(2)]
+        );
+        assert_eq!(
+            run(".tools | sort_by(.risk) | map(.name) | first, last", &doc),
+            [json!("echo"), json!("read_env")]
+        );
+        assert_eq!(
+            run(".tools | unique_by(.tags | length) | map(.name)", &doc),
+            [json!(["echo", "read_file", "read_env"])]
+        );
+        assert_eq!(
+            run("[.tools[].tags[]] | unique", &doc),
+            [json!(["env", "fs", "secret"])]
+        );
+        assert_eq!(
+            run("[.tools[].risk] | min, max, reverse", &doc),
+            [json!(1), json!(5), json!([5, 1, 3])]
+        );
+        assert_eq!(run("[[1, [2]], 3] | flatten", &doc), [json!([1, 2, 3])]);
+        assert_eq!(
+            run("[true, null] | any, all", &doc),
+            [json!(true), json!(false)]
+        );
+        assert_eq!(run("[] | first, add", &doc), [json!(null), json!(null)]);
+        assert_eq!(
+            run(".tools[] | .tags | contains([\"env\"])", &doc)[2],
+            json!(true)
+        );
+        assert_eq!(
+            run(".tools | has(2), has(3)", &doc),
+            [json!(true), json!(false)]
+        );
+    }
+
+    #[test]
+    fn object_functions() {
+        let doc = tools();
+        assert_eq!(
+            run(".tools[0] | keys", &doc),
+            [json!(["name", "risk", "tags"])]
+        );
+        assert_eq!(
+            run(".tools[0] | has(\"risk\"), has(\"nope\")", &doc),
+            [json!(true), json!(false)]
+        );
+        assert_eq!(
+            run("{b: 2, a: 1} | to_entries | from_entries", &doc),
+            [json!({"a": 1, "b": 2})]
+        );
+        assert_eq!(
+            run("[{name: \"k\", v: 1}] | from_entries", &doc),
+            [json!({"k": 1})]
+        );
+        assert_eq!(run("{a: 1, b: 2} | map(. + 1)", &doc), [json!([2, 3])]);
+        assert_eq!(run("{a: 1} | values, length", &doc), [json!([1]), json!(1)]);
+        assert_eq!(
+            run("{a: {b: 1, c: 2}} | contains({a: {b: 1}})", &doc),
+            [json!(true)]
+        );
+    }
+
+    #[test]
+    fn string_functions() {
+        let doc = tools();
+        assert_eq!(
+            run("[.tools[].name] | sort | join(\",\")", &doc),
+            [json!("echo,read_env,read_file")]
+        );
+        assert_eq!(
+            run("\"a,b\" | split(\",\"), split(\"\")", &doc),
+            [json!(["a", "b"]), json!(["a", ",", "b"])]
+        );
+        assert_eq!(
+            run("\"Read_File\" | ascii_downcase, ascii_upcase, length", &doc),
+            [json!("read_file"), json!("READ_FILE"), json!(9)]
+        );
+        assert_eq!(
+            run(
+                "\"read_file\" | startswith(\"read\"), endswith(\"x\"), test(\"_f\\\\w+$\")",
+                &doc
+            ),
+            [json!(true), json!(false), json!(true)]
+        );
+        assert_eq!(
+            run(
+                "\"12\" | tonumber, (1 | tostring), ([1] | tojson), type",
+                &doc
+            ),
+            [json!(12), json!("1"), json!("[1]"), json!("string")]
+        );
+        assert_eq!(run("[1, null, \"x\"] | join(\"-\")", &doc), [json!("1--x")]);
+    }
+
+    #[test]
+    fn rejects_bad_syntax() {
+        for bad in [
+            "",
+            ".tools[",
+            ".a | foo",
+            "map(.a; .b)",
+            "{(.a)}",
+            "..",
+            ".a ==",
+            "\"x",
+            "1e",
+            ".a)",
+            ".tools | nope(1)",
+        ] {
+            assert!(Query::parse(bad).is_err(), "{bad}");
+        }
+        let err = Query::parse(&format!("{}1{}", "(".repeat(100), ")".repeat(100)))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("nested"), "{err}");
+        assert!(Query::parse(&vec!["."; 5000].join("|")).is_err());
+        assert!(Query::parse(&vec!["."; 1000].join("|")).is_ok());
+    }
+
+    #[test]
+    fn reports_filter_errors() {
+        let doc = tools();
+        assert_eq!(
+            fails(".tools.name", &doc),
+            "cannot index array with \"name\""
+        );
+        assert_eq!(
+            fails(".tools[0].risk[]", &doc),
+            "cannot iterate over number"
+        );
+        assert_eq!(fails("1 / 0", &doc), "division by zero");
+        assert_eq!(fails("{} - 1", &doc), "cannot apply - to object and number");
+        assert_eq!(fails("true | length", &doc), "boolean has no length");
+        assert_eq!(fails("1 | keys", &doc), "number has no keys");
+        assert_eq!(
+            fails(".tools | sort_by(.x) | join(1)", &doc),
+            "join needs a string, got number"
+        );
+        assert_eq!(fails("[{}] | join(\",\")", &doc), "cannot join object");
+        assert_eq!(
+            fails("\"x\" | tonumber", &doc),
+            "cannot parse \"x\" as a number"
+        );
+        assert_eq!(
+            fails("[{}] | from_entries", &doc),
+            "from_entries: entry without a key"
+        );
+        assert_eq!(
+            fails(".tools | contains(\"x\")", &doc),
+            "array cannot contain string"
+        );
+        assert_eq!(
+            fails("1 | test(\"a\")", &doc),
+            "test needs a string, got number"
+        );
+        assert!(fails("\"a\" | test(\"(\")", &doc).contains("regex"));
+        // `?` and `//` swallow errors on the left.
+        assert_eq!(run("(1 / 0)?", &doc), Vec::<Value>::new());
+        assert_eq!(
+            run(".tools.name // \"fallback\"", &doc),
+            [json!("fallback")]
+        );
+    }
+
+    #[test]
+    fn handles_large_inputs() {
+        let big = "a".repeat(500_000) + "c";
+        let doc = json!({
+            "text": big,
+            "items": (0..20_000).rev().map(|i| json!({"id": i, "even": i % 2 == 0})).collect::<Vec<_>>(),
+        });
+        assert_eq!(
+            run(".text | test(\"a.*b\"), test(\"^(a*)*c$\"), length", &doc),
+            [json!(false), json!(true), json!(500_001)]
+        );
+        assert_eq!(
+            run(
+                ".items | map(select(.even)) | sort_by(.id) | length, .[0].id",
+                &doc
+            ),
+            [json!(10_000), json!(0)]
+        );
+        assert_eq!(run("[.items[].id] | unique | last", &doc), [json!(19_999)]);
+        let mut nested = json!(1);
+        for _ in 0..100 {
+            nested = json!([nested]);
+        }
+        assert_eq!(run("flatten", &nested), [json!([1])]);
+    }
+}